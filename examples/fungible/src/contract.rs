@@ -90,6 +90,35 @@ impl Contract for FungibleTokenContract {
                 self.claim(source_account, amount, target_account).await;
                 FungibleResponse::Ok
             }
+
+            Operation::Approve {
+                owner,
+                spender,
+                allowance,
+            } => {
+                self.check_account_authentication(owner);
+                self.state.approve(owner, spender, allowance).await;
+                FungibleResponse::Ok
+            }
+
+            Operation::TransferFrom {
+                owner,
+                spender,
+                amount,
+                target_account,
+            } => {
+                self.check_account_authentication(spender);
+                self.state.spend_allowance(owner, spender, amount).await;
+                self.state.debit(owner, amount).await;
+                self.finish_transfer_to_account(amount, target_account, owner)
+                    .await;
+                FungibleResponse::Ok
+            }
+
+            Operation::Allowance { owner, spender } => {
+                let allowance = self.state.allowance(owner, spender).await;
+                FungibleResponse::Allowance(allowance)
+            }
         }
     }
 
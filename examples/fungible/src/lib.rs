@@ -11,7 +11,7 @@ use {
     async_graphql::InputType,
     futures::{stream, StreamExt},
     linera_sdk::{
-        base::{ApplicationId, BytecodeId},
+        base::{ApplicationId, BytecodeId, KeyPair, Owner, TimeoutConfig},
         test::{ActiveChain, TestValidator},
     },
 };
@@ -136,3 +136,94 @@ pub async fn query_account(
             .expect("Account balance cannot be parsed as a number"),
     )
 }
+
+/// Queries the amount that `spender` may still transfer from the account owned by `owner` on a
+/// specific `chain`.
+#[cfg(all(any(test, feature = "test"), not(target_arch = "wasm32")))]
+pub async fn query_allowance(
+    application_id: ApplicationId<FungibleTokenAbi>,
+    chain: &ActiveChain,
+    owner: AccountOwner,
+    spender: AccountOwner,
+) -> Amount {
+    let query = format!(
+        "query {{ allowance(owner: {}, spender: {}) }}",
+        owner.to_value(),
+        spender.to_value()
+    );
+    let response = chain.graphql_query(application_id, query).await;
+    response["allowance"]
+        .as_str()
+        .expect("Allowance is missing from the response")
+        .parse()
+        .expect("Allowance cannot be parsed as a number")
+}
+
+/// Creates a fungible token application on `owner_chain` with an account holding
+/// `initial_amount` tokens for the chain's owner, and adds a new spender key as a co-owner of
+/// that chain. Returns the application and the spender's key pair.
+#[cfg(all(any(test, feature = "test"), not(target_arch = "wasm32")))]
+pub async fn create_with_spender(
+    owner_chain: &ActiveChain,
+    bytecode_id: BytecodeId<FungibleTokenAbi, Parameters, InitialState>,
+    ticker_symbol: &str,
+    initial_amount: Amount,
+) -> (ApplicationId<FungibleTokenAbi>, KeyPair) {
+    let owner = AccountOwner::from(owner_chain.public_key());
+    let initial_state = InitialStateBuilder::default().with_account(owner, initial_amount);
+    let params = Parameters::new(ticker_symbol);
+    let application_id = owner_chain
+        .create_application(bytecode_id, params, initial_state.build(), vec![])
+        .await;
+
+    let spender_key_pair = KeyPair::generate();
+    owner_chain
+        .add_block(|block| {
+            block.with_owner_change(
+                vec![
+                    Owner::from(owner_chain.public_key()),
+                    Owner::from(spender_key_pair.public()),
+                ],
+                vec![],
+                0,
+                false,
+                TimeoutConfig::default(),
+            );
+        })
+        .await;
+
+    (application_id, spender_key_pair)
+}
+
+/// Approves an `allowance` for the `spender` on the account of the `chain`'s current owner.
+#[cfg(all(any(test, feature = "test"), not(target_arch = "wasm32")))]
+pub async fn approve(
+    application_id: ApplicationId<FungibleTokenAbi>,
+    chain: &ActiveChain,
+    spender: AccountOwner,
+    allowance: Amount,
+) {
+    let owner = AccountOwner::from(chain.public_key());
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Approve {
+                    owner,
+                    spender,
+                    allowance,
+                },
+            );
+        })
+        .await;
+}
+
+/// Queries the balance of the application's own account, which holds the escrowed tokens.
+#[cfg(all(any(test, feature = "test"), not(target_arch = "wasm32")))]
+pub async fn query_escrow(
+    application_id: ApplicationId<FungibleTokenAbi>,
+    chain: &ActiveChain,
+) -> Option<Amount> {
+    let escrow = AccountOwner::Application(application_id.forget_abi());
+    query_account(application_id, chain, escrow).await
+}
@@ -56,6 +56,15 @@ impl FungibleTokenService {
         &self.state.accounts
     }
 
+    async fn allowance(
+        &self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+    ) -> Result<Amount, async_graphql::Error> {
+        let allowance = self.state.allowances.get(&(owner, spender)).await?;
+        Ok(allowance.unwrap_or_default())
+    }
+
     async fn ticker_symbol(&self) -> Result<String, async_graphql::Error> {
         let runtime = self
             .runtime
@@ -12,6 +12,8 @@ use linera_sdk::{
 #[view(context = "ViewStorageContext")]
 pub struct FungibleTokenState {
     pub accounts: MapView<AccountOwner, Amount>,
+    /// The amounts that spenders may still transfer, indexed by owner and spender.
+    pub allowances: MapView<(AccountOwner, AccountOwner), Amount>,
 }
 
 #[allow(dead_code)]
@@ -71,4 +73,46 @@ impl FungibleTokenState {
                 .expect("Failed insertion operation");
         }
     }
+
+    /// Obtains the amount that `spender` may still transfer from the `owner`'s account.
+    pub(crate) async fn allowance(&self, owner: AccountOwner, spender: AccountOwner) -> Amount {
+        self.allowances
+            .get(&(owner, spender))
+            .await
+            .expect("Failure in the retrieval")
+            .unwrap_or_default()
+    }
+
+    /// Sets the amount that `spender` may transfer from the `owner`'s account.
+    pub(crate) async fn approve(
+        &mut self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        allowance: Amount,
+    ) {
+        if allowance == Amount::ZERO {
+            self.allowances
+                .remove(&(owner, spender))
+                .expect("Failed to remove an empty allowance");
+        } else {
+            self.allowances
+                .insert(&(owner, spender), allowance)
+                .expect("Failed insert statement");
+        }
+    }
+
+    /// Tries to deduct the requested `amount` from the allowance of `spender` on the `owner`'s
+    /// account.
+    pub(crate) async fn spend_allowance(
+        &mut self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        amount: Amount,
+    ) {
+        let mut allowance = self.allowance(owner, spender).await;
+        allowance
+            .try_sub_assign(amount)
+            .expect("Spender does not have a sufficient allowance for transfer");
+        self.approve(owner, spender, allowance).await;
+    }
 }
@@ -0,0 +1,136 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests for the allowances of the Fungible Token application.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use fungible::{Account, FungibleTokenAbi, InitialState, Operation, Parameters};
+use linera_sdk::{
+    base::{AccountOwner, Amount, ApplicationId, KeyPair},
+    test::{ActiveChain, TestValidator},
+};
+
+/// Test spending an allowance.
+///
+/// Creates the application on an `owner_chain`, initializing it with a single account with some
+/// tokens for that chain's owner, who then approves an allowance for a `spender`. The spender
+/// transfers part of the allowance to a new `receiver_chain`, and the balances and the remaining
+/// allowance are checked.
+#[tokio::test]
+async fn test_transfer_from() {
+    let initial_amount = Amount::from_tokens(20);
+    let allowance = Amount::from_tokens(10);
+    let transfer_amount = Amount::from_tokens(6);
+
+    let (validator, mut owner_chain, application_id, spender_key_pair) =
+        setup(initial_amount).await;
+    let owner = AccountOwner::from(owner_chain.public_key());
+    let spender = AccountOwner::from(spender_key_pair.public());
+    let receiver_chain = validator.new_chain().await;
+    let receiver = AccountOwner::from(receiver_chain.public_key());
+
+    fungible::approve(application_id, &owner_chain, spender, allowance).await;
+    assert_eq!(
+        fungible::query_allowance(application_id, &owner_chain, owner, spender).await,
+        allowance,
+    );
+
+    owner_chain.set_key_pair(spender_key_pair);
+    owner_chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::TransferFrom {
+                    owner,
+                    spender,
+                    amount: transfer_amount,
+                    target_account: Account {
+                        chain_id: receiver_chain.id(),
+                        owner: receiver,
+                    },
+                },
+            );
+        })
+        .await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &owner_chain, owner).await,
+        Some(initial_amount.saturating_sub(transfer_amount)),
+    );
+    assert_eq!(
+        fungible::query_allowance(application_id, &owner_chain, owner, spender).await,
+        allowance.saturating_sub(transfer_amount),
+    );
+
+    receiver_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &receiver_chain, receiver).await,
+        Some(transfer_amount),
+    );
+}
+
+/// Test that a spender can't transfer more than its allowance, and that approving a zero
+/// allowance revokes it.
+#[tokio::test]
+async fn test_transfer_from_exceeding_allowance() {
+    let initial_amount = Amount::from_tokens(20);
+    let allowance = Amount::from_tokens(5);
+
+    let (_validator, mut owner_chain, application_id, spender_key_pair) =
+        setup(initial_amount).await;
+    let owner_key_pair = owner_chain.key_pair().copy();
+    let owner = AccountOwner::from(owner_key_pair.public());
+    let spender = AccountOwner::from(spender_key_pair.public());
+
+    fungible::approve(application_id, &owner_chain, spender, allowance).await;
+
+    owner_chain.set_key_pair(spender_key_pair);
+    let result = owner_chain
+        .try_add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::TransferFrom {
+                    owner,
+                    spender,
+                    amount: Amount::from_tokens(6),
+                    target_account: Account {
+                        chain_id: owner_chain.id(),
+                        owner: spender,
+                    },
+                },
+            );
+        })
+        .await;
+    assert!(result.is_err());
+
+    owner_chain.set_key_pair(owner_key_pair);
+    fungible::approve(application_id, &owner_chain, spender, Amount::ZERO).await;
+    assert_eq!(
+        fungible::query_allowance(application_id, &owner_chain, owner, spender).await,
+        Amount::ZERO,
+    );
+    assert_eq!(
+        fungible::query_account(application_id, &owner_chain, owner).await,
+        Some(initial_amount),
+    );
+}
+
+/// Creates the application with an account holding `initial_amount` tokens for the owner of
+/// the returned chain, and adds a new spender key as a co-owner of that chain.
+async fn setup(
+    initial_amount: Amount,
+) -> (
+    TestValidator,
+    ActiveChain,
+    ApplicationId<FungibleTokenAbi>,
+    KeyPair,
+) {
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<FungibleTokenAbi, Parameters, InitialState>().await;
+    let owner_chain = validator.new_chain().await;
+    let (application_id, spender_key_pair) =
+        fungible::create_with_spender(&owner_chain, bytecode_id, "ALW", initial_amount).await;
+    (validator, owner_chain, application_id, spender_key_pair)
+}
@@ -3,14 +3,20 @@
 
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+mod state;
+
 use fungible::{FungibleResponse, FungibleTokenAbi, InitialState, Operation, Parameters};
 use linera_sdk::{
     base::{Account, AccountOwner, ChainId, WithContractAbi},
+    views::{RootView, View},
     Contract, ContractRuntime,
 };
 use native_fungible::{Message, TICKER_SYMBOL};
 
+use self::state::NativeFungibleTokenState;
+
 pub struct NativeFungibleTokenContract {
+    state: NativeFungibleTokenState,
     runtime: ContractRuntime<Self>,
 }
 
@@ -26,7 +32,10 @@ impl Contract for NativeFungibleTokenContract {
     type InstantiationArgument = InitialState;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
-        NativeFungibleTokenContract { runtime }
+        let state = NativeFungibleTokenState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        NativeFungibleTokenContract { state, runtime }
     }
 
     async fn instantiate(&mut self, state: Self::InstantiationArgument) {
@@ -89,6 +98,68 @@ impl Contract for NativeFungibleTokenContract {
                 );
                 FungibleResponse::Ok
             }
+
+            Operation::Approve {
+                owner,
+                spender,
+                allowance,
+            } => {
+                self.check_account_authentication(owner);
+
+                // Native tokens can only be moved by their owner, so the approved tokens are
+                // escrowed in the application's account until they are spent or released.
+                let previous_allowance = self.state.allowance(owner, spender).await;
+                let escrow_account = self.escrow_account();
+                if allowance > previous_allowance {
+                    self.runtime.transfer(
+                        Some(owner),
+                        escrow_account,
+                        allowance.saturating_sub(previous_allowance),
+                    );
+                } else if allowance < previous_allowance {
+                    let owner_account = Account {
+                        chain_id: escrow_account.chain_id,
+                        owner: Some(owner),
+                    };
+                    self.runtime.transfer(
+                        escrow_account.owner,
+                        owner_account,
+                        previous_allowance.saturating_sub(allowance),
+                    );
+                }
+                self.state.set_allowance(owner, spender, allowance);
+                FungibleResponse::Ok
+            }
+
+            Operation::TransferFrom {
+                owner,
+                spender,
+                amount,
+                target_account,
+            } => {
+                self.check_account_authentication(spender);
+
+                let mut allowance = self.state.allowance(owner, spender).await;
+                allowance
+                    .try_sub_assign(amount)
+                    .expect("Spender does not have a sufficient allowance for transfer");
+                self.state.set_allowance(owner, spender, allowance);
+
+                let fungible_target_account = target_account;
+                let target_account = self.normalize_account(target_account);
+
+                let escrow_account = self.escrow_account();
+                self.runtime
+                    .transfer(escrow_account.owner, target_account, amount);
+
+                self.transfer(fungible_target_account.chain_id);
+                FungibleResponse::Ok
+            }
+
+            Operation::Allowance { owner, spender } => {
+                let allowance = self.state.allowance(owner, spender).await;
+                FungibleResponse::Allowance(allowance)
+            }
         }
     }
 
@@ -99,7 +170,9 @@ impl Contract for NativeFungibleTokenContract {
         }
     }
 
-    async fn store(self) {}
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
 }
 
 impl NativeFungibleTokenContract {
@@ -126,6 +199,15 @@ impl NativeFungibleTokenContract {
         }
     }
 
+    /// The account of this application on the current chain, holding the approved tokens.
+    fn escrow_account(&mut self) -> Account {
+        let application_id = self.runtime.application_id().forget_abi();
+        Account {
+            chain_id: self.runtime.chain_id(),
+            owner: Some(AccountOwner::Application(application_id)),
+        }
+    }
+
     fn normalize_account(&self, account: fungible::Account) -> Account {
         Account {
             chain_id: account.chain_id,
@@ -3,19 +3,25 @@
 
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
+mod state;
+
 use std::sync::{Arc, Mutex};
 
 use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use fungible::{Operation, Parameters};
 use linera_sdk::{
-    base::{AccountOwner, WithServiceAbi},
+    base::{AccountOwner, Amount, WithServiceAbi},
     graphql::GraphQLMutationRoot,
+    views::View,
     Service, ServiceRuntime,
 };
 use native_fungible::{AccountEntry, TICKER_SYMBOL};
 
+use self::state::NativeFungibleTokenState;
+
 #[derive(Clone)]
 pub struct NativeFungibleTokenService {
+    state: Arc<NativeFungibleTokenState>,
     runtime: Arc<Mutex<ServiceRuntime<Self>>>,
 }
 
@@ -29,7 +35,11 @@ impl Service for NativeFungibleTokenService {
     type Parameters = Parameters;
 
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        let state = NativeFungibleTokenState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
         NativeFungibleTokenService {
+            state: Arc::new(state),
             runtime: Arc::new(Mutex::new(runtime)),
         }
     }
@@ -95,4 +105,13 @@ impl NativeFungibleTokenService {
             runtime: self.runtime.clone(),
         })
     }
+
+    async fn allowance(
+        &self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+    ) -> Result<Amount, async_graphql::Error> {
+        let allowance = self.state.allowances.get(&(owner, spender)).await?;
+        Ok(allowance.unwrap_or_default())
+    }
 }
@@ -0,0 +1,46 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_sdk::{
+    base::{AccountOwner, Amount},
+    views::{linera_views, MapView, RootView, ViewStorageContext},
+};
+
+/// The application state. Balances are kept by the system, so only allowances are stored here.
+#[derive(RootView)]
+#[view(context = "ViewStorageContext")]
+pub struct NativeFungibleTokenState {
+    /// The amounts that spenders may still transfer, indexed by owner and spender. The approved
+    /// tokens are held in the application's own account until they are spent or released.
+    pub allowances: MapView<(AccountOwner, AccountOwner), Amount>,
+}
+
+#[allow(dead_code)]
+impl NativeFungibleTokenState {
+    /// Obtains the amount that `spender` may still transfer from the `owner`'s account.
+    pub(crate) async fn allowance(&self, owner: AccountOwner, spender: AccountOwner) -> Amount {
+        self.allowances
+            .get(&(owner, spender))
+            .await
+            .expect("Failure in the retrieval")
+            .unwrap_or_default()
+    }
+
+    /// Sets the amount that `spender` may transfer from the `owner`'s account.
+    pub(crate) fn set_allowance(
+        &mut self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        allowance: Amount,
+    ) {
+        if allowance == Amount::ZERO {
+            self.allowances
+                .remove(&(owner, spender))
+                .expect("Failed to remove an empty allowance");
+        } else {
+            self.allowances
+                .insert(&(owner, spender), allowance)
+                .expect("Failed insert statement");
+        }
+    }
+}
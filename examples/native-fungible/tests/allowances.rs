@@ -0,0 +1,246 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests for the allowances of the Native Fungible Token application.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use fungible::{Account, FungibleTokenAbi, InitialState, Operation, Parameters};
+use linera_sdk::{
+    base::{AccountOwner, Amount, ApplicationId, ChainId, KeyPair},
+    test::{ActiveChain, TestValidator},
+};
+
+/// Test that approving an allowance moves the approved tokens from the owner's account into
+/// the application's escrow account.
+#[tokio::test]
+async fn test_approve_escrows_tokens() {
+    let initial_amount = Amount::from_tokens(20);
+    let allowance = Amount::from_tokens(10);
+
+    let (_validator, owner_chain, application_id, spender_key_pair) = setup(initial_amount).await;
+    let owner = AccountOwner::from(owner_chain.public_key());
+    let spender = AccountOwner::from(spender_key_pair.public());
+
+    fungible::approve(application_id, &owner_chain, spender, allowance).await;
+
+    assert_eq!(
+        fungible::query_allowance(application_id, &owner_chain, owner, spender).await,
+        allowance,
+    );
+    assert_eq!(
+        fungible::query_account(application_id, &owner_chain, owner).await,
+        Some(initial_amount.saturating_sub(allowance)),
+    );
+    assert_eq!(
+        fungible::query_escrow(application_id, &owner_chain).await,
+        Some(allowance)
+    );
+
+    // Raising the allowance escrows the difference.
+    fungible::approve(
+        application_id,
+        &owner_chain,
+        spender,
+        Amount::from_tokens(15),
+    )
+    .await;
+    assert_eq!(
+        fungible::query_account(application_id, &owner_chain, owner).await,
+        Some(Amount::from_tokens(5)),
+    );
+    assert_eq!(
+        fungible::query_escrow(application_id, &owner_chain).await,
+        Some(Amount::from_tokens(15))
+    );
+
+    // Approving more than the owner has fails.
+    let result = owner_chain
+        .try_add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Approve {
+                    owner,
+                    spender,
+                    allowance: Amount::from_tokens(21),
+                },
+            );
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+/// Test spending an allowance.
+///
+/// The owner approves an allowance for a `spender`, who transfers part of it to a new
+/// `receiver_chain`. The tokens are paid out of the escrow, and the owner's balance is
+/// unaffected by the transfer itself.
+#[tokio::test]
+async fn test_transfer_from() {
+    let initial_amount = Amount::from_tokens(20);
+    let allowance = Amount::from_tokens(10);
+    let transfer_amount = Amount::from_tokens(6);
+
+    let (validator, mut owner_chain, application_id, spender_key_pair) =
+        setup(initial_amount).await;
+    let owner = AccountOwner::from(owner_chain.public_key());
+    let spender = AccountOwner::from(spender_key_pair.public());
+    let receiver_chain = validator.new_chain().await;
+    let receiver = AccountOwner::from(receiver_chain.public_key());
+
+    fungible::approve(application_id, &owner_chain, spender, allowance).await;
+
+    owner_chain.set_key_pair(spender_key_pair);
+    owner_chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::TransferFrom {
+                    owner,
+                    spender,
+                    amount: transfer_amount,
+                    target_account: Account {
+                        chain_id: receiver_chain.id(),
+                        owner: receiver,
+                    },
+                },
+            );
+        })
+        .await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &owner_chain, owner).await,
+        Some(initial_amount.saturating_sub(allowance)),
+    );
+    assert_eq!(
+        fungible::query_allowance(application_id, &owner_chain, owner, spender).await,
+        allowance.saturating_sub(transfer_amount),
+    );
+    assert_eq!(
+        fungible::query_escrow(application_id, &owner_chain).await,
+        Some(allowance.saturating_sub(transfer_amount)),
+    );
+
+    receiver_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &receiver_chain, receiver).await,
+        Some(transfer_amount),
+    );
+}
+
+/// Test that a spender can't transfer more than its allowance.
+#[tokio::test]
+async fn test_transfer_from_exceeding_allowance() {
+    let initial_amount = Amount::from_tokens(20);
+    let allowance = Amount::from_tokens(5);
+
+    let (_validator, mut owner_chain, application_id, spender_key_pair) =
+        setup(initial_amount).await;
+    let owner = AccountOwner::from(owner_chain.public_key());
+    let spender = AccountOwner::from(spender_key_pair.public());
+
+    fungible::approve(application_id, &owner_chain, spender, allowance).await;
+
+    owner_chain.set_key_pair(spender_key_pair);
+    let result = owner_chain
+        .try_add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::TransferFrom {
+                    owner,
+                    spender,
+                    amount: Amount::from_tokens(6),
+                    target_account: Account {
+                        chain_id: owner_chain.id(),
+                        owner: spender,
+                    },
+                },
+            );
+        })
+        .await;
+    assert!(result.is_err());
+
+    assert_eq!(
+        fungible::query_allowance(application_id, &owner_chain, owner, spender).await,
+        allowance,
+    );
+    assert_eq!(
+        fungible::query_escrow(application_id, &owner_chain).await,
+        Some(allowance)
+    );
+}
+
+/// Test that approving a zero allowance revokes it and returns the escrowed tokens to the
+/// owner.
+#[tokio::test]
+async fn test_revoke_allowance() {
+    let initial_amount = Amount::from_tokens(20);
+    let allowance = Amount::from_tokens(5);
+
+    let (_validator, mut owner_chain, application_id, spender_key_pair) =
+        setup(initial_amount).await;
+    let owner_key_pair = owner_chain.key_pair().copy();
+    let owner = AccountOwner::from(owner_key_pair.public());
+    let spender = AccountOwner::from(spender_key_pair.public());
+
+    fungible::approve(application_id, &owner_chain, spender, allowance).await;
+    fungible::approve(application_id, &owner_chain, spender, Amount::ZERO).await;
+
+    assert_eq!(
+        fungible::query_allowance(application_id, &owner_chain, owner, spender).await,
+        Amount::ZERO,
+    );
+    assert_eq!(
+        fungible::query_account(application_id, &owner_chain, owner).await,
+        Some(initial_amount),
+    );
+    assert_eq!(
+        fungible::query_escrow(application_id, &owner_chain).await,
+        Some(Amount::ZERO)
+    );
+
+    // The spender can't use the revoked allowance anymore.
+    owner_chain.set_key_pair(spender_key_pair);
+    let result = owner_chain
+        .try_add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::TransferFrom {
+                    owner,
+                    spender,
+                    amount: Amount::ONE,
+                    target_account: Account {
+                        chain_id: owner_chain.id(),
+                        owner: spender,
+                    },
+                },
+            );
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+/// Creates the application on the admin chain, which has native tokens to hand out, with an
+/// account holding `initial_amount` tokens for the chain's owner. A new spender key is added as
+/// a co-owner of that chain.
+async fn setup(
+    initial_amount: Amount,
+) -> (
+    TestValidator,
+    ActiveChain,
+    ApplicationId<FungibleTokenAbi>,
+    KeyPair,
+) {
+    let (validator, bytecode_id) =
+        TestValidator::with_current_bytecode::<FungibleTokenAbi, Parameters, InitialState>().await;
+    let owner_chain = validator.get_chain(&ChainId::root(0));
+    let (application_id, spender_key_pair) = fungible::create_with_spender(
+        &owner_chain,
+        bytecode_id,
+        native_fungible::TICKER_SYMBOL,
+        initial_amount,
+    )
+    .await;
+    (validator, owner_chain, application_id, spender_key_pair)
+}
@@ -8,6 +8,7 @@ use std::{borrow::Cow, fmt, io, num::ParseIntError, str::FromStr};
 
 use ed25519_dalek::{self as dalek, Signer, Verifier};
 use generic_array::typenum::Unsigned;
+use k256::ecdsa as secp256k1;
 use linera_witty::{
     GuestPointer, HList, InstanceWithMemory, Layout, Memory, Runtime, RuntimeError, RuntimeMemory,
     WitLoad, WitStore, WitType,
@@ -26,12 +27,36 @@ use {
 
 use crate::doc_scalar;
 
+/// The length of a compressed SEC1-encoded secp256k1 public key.
+const SECP256K1_PUBLIC_KEY_LENGTH: usize = 33;
+
+/// The length of a recoverable secp256k1 signature: `r`, `s` and the recovery ID `v`.
+const SECP256K1_SIGNATURE_LENGTH: usize = 65;
+
 /// A signature key-pair.
-pub struct KeyPair(dalek::SigningKey);
+pub struct KeyPair(SigningKey);
+
+/// The secret key of a [`KeyPair`], for one of the supported signature schemes.
+enum SigningKey {
+    Ed25519(dalek::SigningKey),
+    Secp256k1(secp256k1::SigningKey),
+}
 
-/// A signature public key.
+/// A signature public key, tagged with its signature scheme.
 #[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
-pub struct PublicKey(pub [u8; dalek::PUBLIC_KEY_LENGTH]);
+pub enum PublicKey {
+    /// An Ed25519 public key.
+    Ed25519([u8; dalek::PUBLIC_KEY_LENGTH]),
+    /// A compressed SEC1-encoded secp256k1 public key, as used by Ethereum.
+    Secp256k1([u8; SECP256K1_PUBLIC_KEY_LENGTH]),
+}
+
+/// An Ethereum address, i.e. the last 20 bytes of the Keccak-256 hash of an uncompressed
+/// secp256k1 public key.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Serialize, Deserialize)]
+pub struct EthereumAddress(pub [u8; 20]);
+
+impl<'de> BcsHashable<'de> for EthereumAddress {}
 
 type HasherOutputSize = <sha3::Sha3_256 as sha3::digest::OutputSizeUser>::OutputSize;
 type HasherOutput = generic_array::GenericArray<u8, HasherOutputSize>;
@@ -49,9 +74,15 @@ pub struct CryptoHashVec(pub Vec<CryptoHash>);
 
 impl<'de> BcsHashable<'de> for CryptoHashVec {}
 
-/// A signature value.
+/// A signature value, tagged with its signature scheme.
 #[derive(Eq, PartialEq, Copy, Clone)]
-pub struct Signature(pub dalek::Signature);
+pub enum Signature {
+    /// An Ed25519 signature.
+    Ed25519(dalek::Signature),
+    /// A recoverable secp256k1 signature `r || s || v` over the Keccak-256 hash of the
+    /// message, using Ethereum's `personal_sign` prefix (EIP-191).
+    Secp256k1([u8; SECP256K1_SIGNATURE_LENGTH]),
+}
 
 /// Error type for cryptographic errors.
 #[derive(Error, Debug)]
@@ -69,10 +100,23 @@ pub enum CryptoError {
     )]
     IncorrectHashSize(usize),
     #[error(
-        "Byte slice has length {0} but a `PublicKey` requires exactly {expected} bytes",
-        expected = dalek::PUBLIC_KEY_LENGTH,
+        "Byte slice has length {0} but a `PublicKey` requires exactly {ed25519} (Ed25519) or \
+         {secp256k1} (secp256k1) bytes",
+        ed25519 = dalek::PUBLIC_KEY_LENGTH,
+        secp256k1 = SECP256K1_PUBLIC_KEY_LENGTH,
     )]
     IncorrectPublicKeySize(usize),
+    #[error(
+        "Byte slice has length {0} but a `Signature` requires exactly {ed25519} (Ed25519) or \
+         {secp256k1} (secp256k1) bytes",
+        ed25519 = dalek::SIGNATURE_LENGTH,
+        secp256k1 = SECP256K1_SIGNATURE_LENGTH,
+    )]
+    IncorrectSignatureSize(usize),
+    #[error("Byte slice has length {0} but an `EthereumAddress` requires exactly 20 bytes")]
+    IncorrectAddressSize(usize),
+    #[error("Invalid secret key: {0}")]
+    InvalidSecretKey(String),
     #[error("Could not parse integer: {0}")]
     ParseIntError(#[from] ParseIntError),
 }
@@ -82,7 +126,38 @@ impl PublicKey {
     #[cfg(with_testing)]
     pub fn test_key(name: u8) -> PublicKey {
         let addr = [name; dalek::PUBLIC_KEY_LENGTH];
-        PublicKey(addr)
+        PublicKey::Ed25519(addr)
+    }
+
+    /// Returns the encoded bytes of the key, without the scheme tag.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            PublicKey::Ed25519(bytes) => bytes,
+            PublicKey::Secp256k1(bytes) => bytes,
+        }
+    }
+
+    /// Returns the Ethereum address of a secp256k1 key, or `None` if this is not a valid
+    /// secp256k1 key.
+    pub fn ethereum_address(&self) -> Option<EthereumAddress> {
+        let PublicKey::Secp256k1(bytes) = self else {
+            return None;
+        };
+        let key = secp256k1::VerifyingKey::from_sec1_bytes(bytes).ok()?;
+        Some(EthereumAddress::from_verifying_key(&key))
+    }
+}
+
+impl EthereumAddress {
+    fn from_verifying_key(key: &secp256k1::VerifyingKey) -> Self {
+        use sha3::{Digest as _, Keccak256};
+
+        let point = key.to_encoded_point(false);
+        // Skip the SEC1 tag byte; the address is the end of the hash of the coordinates.
+        let hash = Keccak256::digest(&point.as_bytes()[1..]);
+        let mut address = [0; 20];
+        address.copy_from_slice(&hash[12..]);
+        EthereumAddress(address)
     }
 }
 
@@ -113,16 +188,46 @@ impl KeyPair {
         Self::generate_from(&mut rng)
     }
 
+    #[cfg(all(with_getrandom, with_testing))]
+    /// Generates a new secp256k1 key-pair.
+    pub fn generate_secp256k1() -> Self {
+        let mut rng = rand::rngs::OsRng;
+        Self::generate_secp256k1_from(&mut rng)
+    }
+
     #[cfg(with_getrandom)]
     /// Generates a new key-pair from the given RNG. Use with care.
     pub fn generate_from<R: CryptoRng>(rng: &mut R) -> Self {
         let keypair = dalek::SigningKey::generate(rng);
-        KeyPair(keypair)
+        KeyPair(SigningKey::Ed25519(keypair))
+    }
+
+    #[cfg(with_getrandom)]
+    /// Generates a new secp256k1 key-pair from the given RNG. Use with care.
+    pub fn generate_secp256k1_from<R: CryptoRng>(rng: &mut R) -> Self {
+        let keypair = secp256k1::SigningKey::random(rng);
+        KeyPair(SigningKey::Secp256k1(keypair))
+    }
+
+    /// Creates a secp256k1 key-pair from a 32-byte secret key, e.g. one exported from an
+    /// Ethereum wallet.
+    pub fn from_secp256k1_secret_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let keypair = secp256k1::SigningKey::from_slice(bytes)
+            .map_err(|error| CryptoError::InvalidSecretKey(error.to_string()))?;
+        Ok(KeyPair(SigningKey::Secp256k1(keypair)))
     }
 
     /// Obtains the public key of a key-pair.
     pub fn public(&self) -> PublicKey {
-        PublicKey(self.0.verifying_key().to_bytes())
+        match &self.0 {
+            SigningKey::Ed25519(keypair) => PublicKey::Ed25519(keypair.verifying_key().to_bytes()),
+            SigningKey::Secp256k1(keypair) => {
+                let point = keypair.verifying_key().to_encoded_point(true);
+                let mut bytes = [0; SECP256K1_PUBLIC_KEY_LENGTH];
+                bytes.copy_from_slice(point.as_bytes());
+                PublicKey::Secp256k1(bytes)
+            }
+        }
     }
 
     /// Copies the key-pair, **including the secret key**.
@@ -130,7 +235,94 @@ impl KeyPair {
     /// The `Clone` and `Copy` traits are deliberately not implemented for `KeyPair` to prevent
     /// accidental copies of secret keys.
     pub fn copy(&self) -> KeyPair {
-        KeyPair(self.0.clone())
+        KeyPair(match &self.0 {
+            SigningKey::Ed25519(keypair) => SigningKey::Ed25519(keypair.clone()),
+            SigningKey::Secp256k1(keypair) => SigningKey::Secp256k1(keypair.clone()),
+        })
+    }
+}
+
+/// The binary representation of a [`PublicKey`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "PublicKey")]
+enum SerializedPublicKey {
+    Ed25519([u8; dalek::PUBLIC_KEY_LENGTH]),
+    /// The SEC1 tag byte and the x coordinate.
+    Secp256k1(u8, [u8; 32]),
+}
+
+impl From<PublicKey> for SerializedPublicKey {
+    fn from(public_key: PublicKey) -> Self {
+        match public_key {
+            PublicKey::Ed25519(bytes) => SerializedPublicKey::Ed25519(bytes),
+            PublicKey::Secp256k1(bytes) => {
+                let mut x = [0; 32];
+                x.copy_from_slice(&bytes[1..]);
+                SerializedPublicKey::Secp256k1(bytes[0], x)
+            }
+        }
+    }
+}
+
+impl From<SerializedPublicKey> for PublicKey {
+    fn from(public_key: SerializedPublicKey) -> Self {
+        match public_key {
+            SerializedPublicKey::Ed25519(bytes) => PublicKey::Ed25519(bytes),
+            SerializedPublicKey::Secp256k1(tag, x) => {
+                let mut bytes = [0; SECP256K1_PUBLIC_KEY_LENGTH];
+                bytes[0] = tag;
+                bytes[1..].copy_from_slice(&x);
+                PublicKey::Secp256k1(bytes)
+            }
+        }
+    }
+}
+
+/// The binary representation of Ed25519 public keys before other signature schemes were
+/// supported. The [`Owner`](crate::identifiers::Owner) of an Ed25519 key is still the hash of
+/// this representation, so that existing owners don't change.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "PublicKey")]
+pub(crate) struct LegacyEd25519PublicKey(pub [u8; dalek::PUBLIC_KEY_LENGTH]);
+
+impl<'de> BcsHashable<'de> for LegacyEd25519PublicKey {}
+
+/// The binary representation of a [`Signature`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Signature")]
+enum SerializedSignature {
+    Ed25519(dalek::Signature),
+    /// The `r` and `s` values and the recovery ID `v`.
+    Secp256k1([u8; 32], [u8; 32], u8),
+}
+
+impl From<Signature> for SerializedSignature {
+    fn from(signature: Signature) -> Self {
+        match signature {
+            Signature::Ed25519(signature) => SerializedSignature::Ed25519(signature),
+            Signature::Secp256k1(bytes) => {
+                let mut r = [0; 32];
+                let mut s = [0; 32];
+                r.copy_from_slice(&bytes[..32]);
+                s.copy_from_slice(&bytes[32..64]);
+                SerializedSignature::Secp256k1(r, s, bytes[64])
+            }
+        }
+    }
+}
+
+impl From<SerializedSignature> for Signature {
+    fn from(signature: SerializedSignature) -> Self {
+        match signature {
+            SerializedSignature::Ed25519(signature) => Signature::Ed25519(signature),
+            SerializedSignature::Secp256k1(r, s, v) => {
+                let mut bytes = [0; SECP256K1_SIGNATURE_LENGTH];
+                bytes[..32].copy_from_slice(&r);
+                bytes[32..64].copy_from_slice(&s);
+                bytes[64] = v;
+                Signature::Secp256k1(bytes)
+            }
+        }
     }
 }
 
@@ -142,7 +334,7 @@ impl Serialize for PublicKey {
         if serializer.is_human_readable() {
             serializer.serialize_str(&self.to_string())
         } else {
-            serializer.serialize_newtype_struct("PublicKey", &self.0)
+            SerializedPublicKey::from(*self).serialize(serializer)
         }
     }
 }
@@ -157,12 +349,7 @@ impl<'de> Deserialize<'de> for PublicKey {
             let value = Self::from_str(&s).map_err(serde::de::Error::custom)?;
             Ok(value)
         } else {
-            #[derive(Deserialize)]
-            #[serde(rename = "PublicKey")]
-            struct Foo([u8; dalek::PUBLIC_KEY_LENGTH]);
-
-            let value = Foo::deserialize(deserializer)?;
-            Ok(Self(value.0))
+            Ok(SerializedPublicKey::deserialize(deserializer)?.into())
         }
     }
 }
@@ -207,7 +394,15 @@ impl Serialize for KeyPair {
     {
         // This is only used for JSON configuration.
         assert!(serializer.is_human_readable());
-        serializer.serialize_str(&hex::encode(self.0.to_bytes()))
+        match &self.0 {
+            SigningKey::Ed25519(keypair) => {
+                serializer.serialize_str(&hex::encode(keypair.to_bytes()))
+            }
+            SigningKey::Secp256k1(keypair) => serializer.serialize_str(&format!(
+                "{SECP256K1_SECRET_KEY_PREFIX}{}",
+                hex::encode(keypair.to_bytes())
+            )),
+        }
     }
 }
 
@@ -219,22 +414,29 @@ impl<'de> Deserialize<'de> for KeyPair {
         // This is only used for JSON configuration.
         assert!(deserializer.is_human_readable());
         let s = String::deserialize(deserializer)?;
+        if let Some(s) = s.strip_prefix(SECP256K1_SECRET_KEY_PREFIX) {
+            let value = hex::decode(s).map_err(serde::de::Error::custom)?;
+            return KeyPair::from_secp256k1_secret_bytes(&value).map_err(serde::de::Error::custom);
+        }
         let value = hex::decode(s).map_err(serde::de::Error::custom)?;
         let key =
             dalek::SigningKey::from_bytes(value[..].try_into().map_err(serde::de::Error::custom)?);
-        Ok(KeyPair(key))
+        Ok(KeyPair(SigningKey::Ed25519(key)))
     }
 }
 
+/// The prefix distinguishing serialized secp256k1 secret keys from Ed25519 ones.
+const SECP256K1_SECRET_KEY_PREFIX: &str = "secp256k1:";
+
 impl Serialize for Signature {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::ser::Serializer,
     {
         if serializer.is_human_readable() {
-            serializer.serialize_str(&hex::encode(self.0.to_bytes()))
+            serializer.serialize_str(&self.to_string())
         } else {
-            serializer.serialize_newtype_struct("Signature", &self.0)
+            SerializedSignature::from(*self).serialize(serializer)
         }
     }
 }
@@ -247,16 +449,9 @@ impl<'de> Deserialize<'de> for Signature {
         if deserializer.is_human_readable() {
             let s = String::deserialize(deserializer)?;
             let value = hex::decode(s).map_err(serde::de::Error::custom)?;
-            let sig =
-                dalek::Signature::try_from(value.as_slice()).map_err(serde::de::Error::custom)?;
-            Ok(Signature(sig))
+            Signature::try_from(value.as_slice()).map_err(serde::de::Error::custom)
         } else {
-            #[derive(Deserialize)]
-            #[serde(rename = "Signature")]
-            struct Foo(dalek::Signature);
-
-            let value = Foo::deserialize(deserializer)?;
-            Ok(Self(value.0))
+            Ok(SerializedSignature::deserialize(deserializer)?.into())
         }
     }
 }
@@ -274,24 +469,78 @@ impl TryFrom<&[u8]> for PublicKey {
     type Error = CryptoError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        if value.len() != dalek::PUBLIC_KEY_LENGTH {
-            return Err(CryptoError::IncorrectPublicKeySize(value.len()));
+        match value.len() {
+            dalek::PUBLIC_KEY_LENGTH => {
+                let mut pubkey = [0u8; dalek::PUBLIC_KEY_LENGTH];
+                pubkey.copy_from_slice(value);
+                Ok(PublicKey::Ed25519(pubkey))
+            }
+            SECP256K1_PUBLIC_KEY_LENGTH => {
+                let mut pubkey = [0u8; SECP256K1_PUBLIC_KEY_LENGTH];
+                pubkey.copy_from_slice(value);
+                Ok(PublicKey::Secp256k1(pubkey))
+            }
+            len => Err(CryptoError::IncorrectPublicKeySize(len)),
         }
-        let mut pubkey = [0u8; dalek::PUBLIC_KEY_LENGTH];
-        pubkey.copy_from_slice(value);
-        Ok(PublicKey(pubkey))
     }
 }
 
-impl From<[u64; 4]> for PublicKey {
-    fn from(integers: [u64; 4]) -> Self {
-        PublicKey(u64_array_to_le_bytes(integers))
+impl Signature {
+    /// Returns the encoded bytes of the signature, without the scheme tag.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Signature::Ed25519(signature) => signature.to_bytes().to_vec(),
+            Signature::Secp256k1(bytes) => bytes.to_vec(),
+        }
     }
 }
 
-impl From<PublicKey> for [u64; 4] {
-    fn from(pub_key: PublicKey) -> Self {
-        le_bytes_to_u64_array(&pub_key.0)
+impl TryFrom<&[u8]> for Signature {
+    type Error = CryptoError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value.len() {
+            dalek::SIGNATURE_LENGTH => {
+                let signature = dalek::Signature::from_slice(value).map_err(|error| {
+                    CryptoError::InvalidSignature {
+                        error: error.to_string(),
+                        type_name: "Signature".to_string(),
+                    }
+                })?;
+                Ok(Signature::Ed25519(signature))
+            }
+            SECP256K1_SIGNATURE_LENGTH => {
+                let mut bytes = [0u8; SECP256K1_SIGNATURE_LENGTH];
+                bytes.copy_from_slice(value);
+                Ok(Signature::Secp256k1(bytes))
+            }
+            len => Err(CryptoError::IncorrectSignatureSize(len)),
+        }
+    }
+}
+
+impl FromStr for EthereumAddress {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = hex::decode(s.strip_prefix("0x").unwrap_or(s))?;
+        let address = value
+            .as_slice()
+            .try_into()
+            .map_err(|_| CryptoError::IncorrectAddressSize(value.len()))?;
+        Ok(EthereumAddress(address))
+    }
+}
+
+impl fmt::Display for EthereumAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for EthereumAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
     }
 }
 
@@ -317,6 +566,13 @@ impl TryFrom<&[u8]> for CryptoHash {
     }
 }
 
+/// Wraps a raw Sha3-256 output, such as the hash of a view.
+impl From<HasherOutput> for CryptoHash {
+    fn from(output: HasherOutput) -> Self {
+        CryptoHash(output)
+    }
+}
+
 impl From<[u64; 4]> for CryptoHash {
     fn from(integers: [u64; 4]) -> Self {
         CryptoHash(u64_array_to_le_bytes(integers).into())
@@ -331,14 +587,14 @@ impl From<CryptoHash> for [u64; 4] {
 
 impl fmt::Display for Signature {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = hex::encode(self.0.to_bytes());
+        let s = hex::encode(self.to_bytes());
         write!(f, "{}", s)
     }
 }
 
 impl fmt::Display for PublicKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(&self.0[..]))
+        write!(f, "{}", hex::encode(self.as_bytes()))
     }
 }
 
@@ -351,13 +607,13 @@ impl fmt::Display for CryptoHash {
 
 impl fmt::Debug for Signature {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(&self.0.to_bytes()[0..8]))
+        write!(f, "{}", hex::encode(&self.to_bytes()[0..8]))
     }
 }
 
 impl fmt::Debug for PublicKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(&self.0[..8]))
+        write!(f, "{}", hex::encode(&self.as_bytes()[..8]))
     }
 }
 
@@ -452,22 +708,64 @@ impl Signature {
     {
         let mut message = Vec::new();
         value.write(&mut message);
-        let signature = secret.0.sign(&message);
-        Signature(signature)
+        match &secret.0 {
+            SigningKey::Ed25519(keypair) => Signature::Ed25519(keypair.sign(&message)),
+            SigningKey::Secp256k1(keypair) => {
+                let (signature, recovery_id) = keypair
+                    .sign_digest_recoverable(ethereum_message_digest(&message))
+                    .expect("signing a digest with a valid secp256k1 key should not fail");
+                let mut bytes = [0; SECP256K1_SIGNATURE_LENGTH];
+                bytes[..64].copy_from_slice(&signature.to_bytes());
+                // Ethereum encodes the recovery ID as `v = 27 + recovery_id`.
+                bytes[64] = 27 + recovery_id.to_byte();
+                Signature::Secp256k1(bytes)
+            }
+        }
     }
 
-    fn check_internal<'de, T>(
-        &self,
-        value: &T,
-        author: PublicKey,
-    ) -> Result<(), dalek::SignatureError>
+    fn check_internal<'de, T>(&self, value: &T, author: PublicKey) -> Result<(), String>
     where
         T: BcsSignable<'de>,
     {
         let mut message = Vec::new();
         value.write(&mut message);
-        let public_key = dalek::VerifyingKey::from_bytes(&author.0)?;
-        public_key.verify(&message, &self.0)
+        match (self, author) {
+            (Signature::Ed25519(signature), PublicKey::Ed25519(bytes)) => {
+                let public_key =
+                    dalek::VerifyingKey::from_bytes(&bytes).map_err(|error| error.to_string())?;
+                public_key
+                    .verify(&message, signature)
+                    .map_err(|error| error.to_string())
+            }
+            (Signature::Secp256k1(bytes), PublicKey::Secp256k1(key_bytes)) => {
+                let public_key = secp256k1::VerifyingKey::from_sec1_bytes(&key_bytes)
+                    .map_err(|error| error.to_string())?;
+                let signature = secp256k1::Signature::from_slice(&bytes[..64])
+                    .map_err(|error| error.to_string())?;
+                // Only accept the canonical form of each signature: otherwise the same
+                // signature could be submitted with different bytes.
+                if signature.normalize_s().is_some() {
+                    return Err("secp256k1 signature has a high `s` value".to_string());
+                }
+                if !matches!(bytes[64], 27 | 28) {
+                    return Err(format!("invalid secp256k1 recovery ID {}", bytes[64]));
+                }
+                let recovery_id = secp256k1::RecoveryId::from_byte(bytes[64] - 27)
+                    .expect("0 and 1 are valid recovery IDs");
+                // The signature is valid if and only if it recovers the author's key.
+                let recovered_key = secp256k1::VerifyingKey::recover_from_digest(
+                    ethereum_message_digest(&message),
+                    &signature,
+                    recovery_id,
+                )
+                .map_err(|error| error.to_string())?;
+                if recovered_key != public_key {
+                    return Err("secp256k1 signature does not match the public key".to_string());
+                }
+                Ok(())
+            }
+            _ => Err("signature scheme does not match the public key".to_string()),
+        }
     }
 
     /// Checks a signature.
@@ -477,7 +775,7 @@ impl Signature {
     {
         self.check_internal(value, author)
             .map_err(|error| CryptoError::InvalidSignature {
-                error: error.to_string(),
+                error,
                 type_name: T::type_name().to_string(),
             })
     }
@@ -499,10 +797,7 @@ impl Signature {
         }
     }
 
-    fn verify_batch_internal<'a, 'de, T, I>(
-        value: &'a T,
-        votes: I,
-    ) -> Result<(), dalek::SignatureError>
+    fn verify_batch_internal<'a, 'de, T, I>(value: &'a T, votes: I) -> Result<(), String>
     where
         T: BcsSignable<'de>,
         I: IntoIterator<Item = (&'a PublicKey, &'a Signature)>,
@@ -513,11 +808,24 @@ impl Signature {
         let mut signatures = Vec::new();
         let mut public_keys = Vec::new();
         for (addr, sig) in votes.into_iter() {
-            messages.push(msg.as_slice());
-            signatures.push(sig.0);
-            public_keys.push(dalek::VerifyingKey::from_bytes(&addr.0)?);
+            // Only Ed25519 signatures can be verified in a batch.
+            match (sig, addr) {
+                (Signature::Ed25519(signature), PublicKey::Ed25519(bytes)) => {
+                    messages.push(msg.as_slice());
+                    signatures.push(*signature);
+                    public_keys.push(
+                        dalek::VerifyingKey::from_bytes(bytes)
+                            .map_err(|error| error.to_string())?,
+                    );
+                }
+                _ => sig.check_internal(value, *addr)?,
+            }
+        }
+        if messages.is_empty() {
+            return Ok(());
         }
         dalek::verify_batch(&messages[..], &signatures[..], &public_keys[..])
+            .map_err(|error| error.to_string())
     }
 
     /// Verifies a batch of signatures.
@@ -610,81 +918,6 @@ impl WitStore for CryptoHash {
     }
 }
 
-impl WitType for PublicKey {
-    const SIZE: u32 = <(u64, u64, u64, u64) as WitType>::SIZE;
-    type Layout = <(u64, u64, u64, u64) as WitType>::Layout;
-    type Dependencies = HList![];
-
-    fn wit_type_name() -> Cow<'static, str> {
-        "public-key".into()
-    }
-
-    fn wit_type_declaration() -> Cow<'static, str> {
-        concat!(
-            "    record public-key {\n",
-            "        part1: u64,\n",
-            "        part2: u64,\n",
-            "        part3: u64,\n",
-            "        part4: u64,\n",
-            "    }\n",
-        )
-        .into()
-    }
-}
-
-impl WitLoad for PublicKey {
-    fn load<Instance>(
-        memory: &Memory<'_, Instance>,
-        location: GuestPointer,
-    ) -> Result<Self, RuntimeError>
-    where
-        Instance: InstanceWithMemory,
-        <Instance::Runtime as Runtime>::Memory: RuntimeMemory<Instance>,
-    {
-        let (part1, part2, part3, part4) = WitLoad::load(memory, location)?;
-        Ok(PublicKey::from([part1, part2, part3, part4]))
-    }
-
-    fn lift_from<Instance>(
-        flat_layout: <Self::Layout as Layout>::Flat,
-        memory: &Memory<'_, Instance>,
-    ) -> Result<Self, RuntimeError>
-    where
-        Instance: InstanceWithMemory,
-        <Instance::Runtime as Runtime>::Memory: RuntimeMemory<Instance>,
-    {
-        let (part1, part2, part3, part4) = WitLoad::lift_from(flat_layout, memory)?;
-        Ok(PublicKey::from([part1, part2, part3, part4]))
-    }
-}
-
-impl WitStore for PublicKey {
-    fn store<Instance>(
-        &self,
-        memory: &mut Memory<'_, Instance>,
-        location: GuestPointer,
-    ) -> Result<(), RuntimeError>
-    where
-        Instance: InstanceWithMemory,
-        <Instance::Runtime as Runtime>::Memory: RuntimeMemory<Instance>,
-    {
-        let [part1, part2, part3, part4] = (*self).into();
-        (part1, part2, part3, part4).store(memory, location)
-    }
-
-    fn lower<Instance>(
-        &self,
-        memory: &mut Memory<'_, Instance>,
-    ) -> Result<<Self::Layout as Layout>::Flat, RuntimeError>
-    where
-        Instance: InstanceWithMemory,
-        <Instance::Runtime as Runtime>::Memory: RuntimeMemory<Instance>,
-    {
-        let [part1, part2, part3, part4] = (*self).into();
-        (part1, part2, part3, part4).lower(memory)
-    }
-}
-
 #[cfg(with_testing)]
 impl Arbitrary for CryptoHash {
     type Parameters = ();
@@ -743,6 +976,102 @@ fn test_signatures() {
     assert!(s.check(&foo, addr1).is_err());
 }
 
+#[cfg(all(with_getrandom, with_testing))]
+#[test]
+fn test_secp256k1_signatures() {
+    let key1 = KeyPair::generate_secp256k1();
+    let addr1 = key1.public();
+    let key2 = KeyPair::generate();
+    let addr2 = key2.public();
+
+    let ts = TestString("hello".into());
+    let tsx = TestString("hellox".into());
+
+    let s = Signature::new(&ts, &key1);
+    assert!(s.check(&ts, addr1).is_ok());
+    assert!(s.check(&ts, addr2).is_err());
+    assert!(s.check(&tsx, addr1).is_err());
+    assert!(Signature::new(&ts, &key2).check(&ts, addr1).is_err());
+
+    // Both schemes can be mixed in a batch.
+    let s2 = Signature::new(&ts, &key2);
+    assert!(Signature::verify_batch(&ts, [(&addr1, &s), (&addr2, &s2)]).is_ok());
+    assert!(Signature::verify_batch(&ts, [(&addr2, &s), (&addr1, &s2)]).is_err());
+
+    for (public_key, signature) in [(addr1, s), (addr2, s2)] {
+        let bytes = bcs::to_bytes(&public_key).unwrap();
+        assert_eq!(bcs::from_bytes::<PublicKey>(&bytes).unwrap(), public_key);
+        let json = serde_json::to_string(&public_key).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PublicKey>(&json).unwrap(),
+            public_key
+        );
+        let bytes = bcs::to_bytes(&signature).unwrap();
+        assert_eq!(bcs::from_bytes::<Signature>(&bytes).unwrap(), signature);
+        let json = serde_json::to_string(&signature).unwrap();
+        assert_eq!(serde_json::from_str::<Signature>(&json).unwrap(), signature);
+    }
+}
+
+#[cfg(all(with_getrandom, with_testing))]
+#[test]
+fn test_secp256k1_non_canonical_signatures() {
+    let key_pair = KeyPair::generate_secp256k1();
+    let public_key = key_pair.public();
+    let ts = TestString("hello".into());
+    let Signature::Secp256k1(bytes) = Signature::new(&ts, &key_pair) else {
+        panic!("expected a secp256k1 signature");
+    };
+    assert!(Signature::Secp256k1(bytes).check(&ts, public_key).is_ok());
+
+    // The recovery ID must be `27` or `28`, and match the public key.
+    for v in [0, 1, 29, 31, 55 - bytes[64]] {
+        let mut bytes = bytes;
+        bytes[64] = v;
+        assert!(Signature::Secp256k1(bytes).check(&ts, public_key).is_err());
+    }
+
+    // The same signature with `s` replaced by `n - s` is rejected, even with the matching
+    // recovery ID.
+    let signature = secp256k1::Signature::from_slice(&bytes[..64]).unwrap();
+    let (r, s) = signature.split_scalars();
+    let high_s = secp256k1::Signature::from_scalars(r.to_bytes(), (-*s).to_bytes()).unwrap();
+    let mut high_s_bytes = bytes;
+    high_s_bytes[..64].copy_from_slice(&high_s.to_bytes());
+    high_s_bytes[64] = 55 - bytes[64];
+    assert!(Signature::Secp256k1(high_s_bytes)
+        .check(&ts, public_key)
+        .is_err());
+}
+
+#[test]
+fn test_ethereum_address() {
+    let secret =
+        hex::decode("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318").unwrap();
+    let key_pair = KeyPair::from_secp256k1_secret_bytes(&secret).unwrap();
+    let address = key_pair.public().ethereum_address().unwrap();
+    assert_eq!(
+        address,
+        "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23"
+            .parse()
+            .unwrap()
+    );
+    let json = serde_json::to_string(&key_pair).unwrap();
+    let key_pair2 = serde_json::from_str::<KeyPair>(&json).unwrap();
+    assert_eq!(key_pair.public(), key_pair2.public());
+}
+
+/// Returns the Keccak-256 digest of the message with Ethereum's `personal_sign` prefix
+/// (EIP-191), so that signatures can be produced by existing Ethereum wallets.
+fn ethereum_message_digest(message: &[u8]) -> sha3::Keccak256 {
+    use sha3::Digest as _;
+
+    let mut digest = sha3::Keccak256::new();
+    digest.update(format!("\x19Ethereum Signed Message:\n{}", message.len()));
+    digest.update(message);
+    digest
+}
+
 /// Reads the `bytes` as four little-endian unsigned 64-bit integers and returns them.
 fn le_bytes_to_u64_array(bytes: &[u8]) -> [u64; 4] {
     let mut integers = [0u64; 4];
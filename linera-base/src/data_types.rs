@@ -34,8 +34,8 @@ use crate::{
     crypto::{BcsHashable, CryptoHash},
     doc_scalar, hex_debug,
     identifiers::{
-        ApplicationId, BlobId, BlobType, BytecodeId, Destination, GenericApplicationId, MessageId,
-        UserApplicationId,
+        ApplicationId, BlobId, BlobType, BytecodeId, Destination, EventId, GenericApplicationId,
+        MessageId, Owner, UserApplicationId,
     },
     limited_writer::{LimitedWriter, LimitedWriterError},
     time::{Duration, SystemTime},
@@ -763,6 +763,13 @@ pub enum OracleResponse {
     Blob(BlobId),
     /// An assertion oracle that passed.
     Assert,
+    /// The value of an event read from another chain's stream.
+    Event(
+        EventId,
+        #[debug(with = "hex_debug")]
+        #[serde(with = "serde_bytes")]
+        Vec<u8>,
+    ),
 }
 
 impl Display for OracleResponse {
@@ -774,6 +781,10 @@ impl Display for OracleResponse {
             OracleResponse::Post(bytes) => write!(f, "Post:{}", STANDARD_NO_PAD.encode(bytes))?,
             OracleResponse::Blob(blob_id) => write!(f, "Blob:{}", blob_id)?,
             OracleResponse::Assert => write!(f, "Assert")?,
+            OracleResponse::Event(event_id, value) => {
+                let bytes = bcs::to_bytes(&(event_id, value)).map_err(|_| fmt::Error)?;
+                write!(f, "Event:{}", STANDARD_NO_PAD.encode(bytes))?
+            }
         };
 
         Ok(())
@@ -799,6 +810,11 @@ impl FromStr for OracleResponse {
                 BlobId::from_str(string).context("Invalid BlobId")?,
             ));
         }
+        if let Some(string) = s.strip_prefix("Event:") {
+            let bytes = STANDARD_NO_PAD.decode(string).context("Invalid base64")?;
+            let (event_id, value) = bcs::from_bytes(&bytes).context("Invalid event")?;
+            return Ok(OracleResponse::Event(event_id, value));
+        }
         Err(anyhow::anyhow!("Invalid enum! Enum: {}", s))
     }
 }
@@ -818,6 +834,9 @@ pub struct UserApplicationDescription {
     pub parameters: Vec<u8>,
     /// Required dependencies.
     pub required_application_ids: Vec<UserApplicationId>,
+    /// The owner allowed to upgrade the application to a new bytecode, if any.
+    #[debug(skip_if = Option::is_none)]
+    pub admin: Option<Owner>,
 }
 
 impl From<&UserApplicationDescription> for UserApplicationId {
@@ -18,7 +18,9 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
     bcs_scalar,
-    crypto::{BcsHashable, CryptoError, CryptoHash, PublicKey},
+    crypto::{
+        BcsHashable, CryptoError, CryptoHash, EthereumAddress, LegacyEd25519PublicKey, PublicKey,
+    },
     data_types::BlockHeight,
     doc_scalar, hex_debug,
 };
@@ -420,6 +422,33 @@ pub struct StreamId {
     pub stream_name: StreamName,
 }
 
+/// The identifier of an event: the chain and stream that contain it, and its key.
+#[derive(
+    Clone,
+    Debug,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    WitLoad,
+    WitStore,
+    WitType,
+    SimpleObject,
+)]
+pub struct EventId {
+    /// The chain on which the event was emitted.
+    pub chain_id: ChainId,
+    /// The stream the event belongs to.
+    pub stream_id: StreamId,
+    /// The key of the event within the stream.
+    #[serde(with = "serde_bytes")]
+    #[debug(with = "hex_debug")]
+    pub key: Vec<u8>,
+}
+
 /// The destination of a message, relative to a particular application.
 #[derive(
     Clone,
@@ -795,20 +824,38 @@ impl Display for Owner {
 
 impl From<PublicKey> for Owner {
     fn from(value: PublicKey) -> Self {
-        Self(CryptoHash::new(&value))
+        Self::from(&value)
     }
 }
 
 impl From<&PublicKey> for Owner {
+    /// Returns the owner of a public key. For secp256k1 keys, this is derived from the
+    /// Ethereum address, so that it can also be computed from the address alone.
     fn from(value: &PublicKey) -> Self {
-        Self(CryptoHash::new(value))
+        if let PublicKey::Ed25519(bytes) = value {
+            return Self(CryptoHash::new(&LegacyEd25519PublicKey(*bytes)));
+        }
+        match value.ethereum_address() {
+            Some(address) => Self::from(address),
+            None => Self(CryptoHash::new(value)),
+        }
+    }
+}
+
+impl From<EthereumAddress> for Owner {
+    fn from(value: EthereumAddress) -> Self {
+        Self(CryptoHash::new(&value))
     }
 }
 
 impl std::str::FromStr for Owner {
     type Err = CryptoError;
 
+    /// Parses an owner from its hash or, if prefixed with `0x`, from an Ethereum address.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") {
+            return Ok(Owner::from(EthereumAddress::from_str(s)?));
+        }
         Ok(Owner(CryptoHash::from_str(s)?))
     }
 }
@@ -1018,7 +1065,8 @@ doc_scalar!(
 
 #[cfg(test)]
 mod tests {
-    use super::ChainId;
+    use super::{ChainId, Owner};
+    use crate::crypto::PublicKey;
 
     /// Verifies that chain IDs that are explicitly used in some example and test scripts don't
     /// change.
@@ -1037,4 +1085,18 @@ mod tests {
             "9c8a838e8f7b63194f6c7585455667a8379d2b5db19a3300e9961f0b1e9091ea"
         );
     }
+
+    /// Verifies that the owners of Ed25519 keys are still the hash of the untagged key, as
+    /// before other signature schemes were supported.
+    #[test]
+    fn ed25519_owners() {
+        use sha3::{Digest as _, Sha3_256};
+
+        let bytes = [7; 32];
+        let mut hasher = Sha3_256::default();
+        hasher.update(b"PublicKey::");
+        hasher.update(bytes);
+        let owner = Owner::from(PublicKey::Ed25519(bytes));
+        assert_eq!(owner.0.as_bytes().as_slice(), hasher.finalize().as_slice());
+    }
 }
@@ -28,6 +28,7 @@ pub mod ownership;
 pub mod port;
 #[cfg(with_metrics)]
 pub mod prometheus_util;
+pub mod state_proof;
 #[cfg(not(chain))]
 pub mod task;
 #[cfg(not(chain))]
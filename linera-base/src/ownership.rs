@@ -15,6 +15,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
+    crypto::{BcsHashable, CryptoHash, PublicKey},
     data_types::{Round, TimeDelta},
     doc_scalar,
     identifiers::Owner,
@@ -179,6 +180,69 @@ impl ChainOwnership {
     }
 }
 
+/// A set of public keys that acts as a single [`Owner`]: Any `threshold` of them have to sign
+/// a block proposal to authenticate as that owner.
+#[derive(PartialEq, Eq, Clone, Hash, Debug, Serialize, Deserialize)]
+pub struct MultiSigOwner {
+    /// The public keys of the members.
+    pub public_keys: BTreeSet<PublicKey>,
+    /// The number of distinct member signatures required.
+    pub threshold: u32,
+}
+
+impl<'de> BcsHashable<'de> for MultiSigOwner {}
+
+impl MultiSigOwner {
+    /// Creates a new multi-signature owner, or returns an error if the threshold is zero or
+    /// exceeds the number of distinct public keys.
+    pub fn new(
+        public_keys: impl IntoIterator<Item = PublicKey>,
+        threshold: u32,
+    ) -> Result<Self, MultiSigOwnerError> {
+        let public_keys = public_keys.into_iter().collect::<BTreeSet<_>>();
+        let num_keys = public_keys.len();
+        if threshold == 0 || usize::try_from(threshold).map_or(true, |t| t > num_keys) {
+            return Err(MultiSigOwnerError::InvalidThreshold {
+                threshold,
+                num_keys,
+            });
+        }
+        Ok(MultiSigOwner {
+            public_keys,
+            threshold,
+        })
+    }
+
+    /// Returns the [`Owner`] that this set of keys acts as.
+    pub fn owner(&self) -> Owner {
+        Owner::from(self)
+    }
+
+    /// Returns whether the given public key is one of the members.
+    pub fn contains(&self, public_key: &PublicKey) -> bool {
+        self.public_keys.contains(public_key)
+    }
+}
+
+impl From<&MultiSigOwner> for Owner {
+    fn from(value: &MultiSigOwner) -> Self {
+        Owner(CryptoHash::new(value))
+    }
+}
+
+/// Errors that can happen when creating a [`MultiSigOwner`].
+#[derive(Clone, Debug, Error)]
+pub enum MultiSigOwnerError {
+    /// The threshold must be positive and at most the number of keys.
+    #[error("Invalid threshold {threshold} for {num_keys} distinct public keys")]
+    InvalidThreshold {
+        /// The requested threshold.
+        threshold: u32,
+        /// The number of distinct public keys.
+        num_keys: usize,
+    },
+}
+
 /// Errors that can happen when attempting to close a chain.
 #[derive(Clone, Copy, Debug, Error, WitStore, WitType)]
 pub enum CloseChainError {
@@ -191,6 +255,28 @@ pub enum CloseChainError {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_multi_sig_owner() {
+        use crate::crypto::KeyPair;
+
+        let keys = (0..3)
+            .map(|_| KeyPair::generate().public())
+            .collect::<Vec<_>>();
+        assert!(MultiSigOwner::new(keys.clone(), 0).is_err());
+        assert!(MultiSigOwner::new(keys.clone(), 4).is_err());
+        // Duplicate keys only count once.
+        assert!(MultiSigOwner::new([keys[0], keys[0]], 2).is_err());
+
+        let two_of_three = MultiSigOwner::new(keys.clone(), 2).unwrap();
+        let three_of_three = MultiSigOwner::new(keys.clone(), 3).unwrap();
+        assert!(two_of_three.contains(&keys[1]));
+        assert_ne!(two_of_three.owner(), three_of_three.owner());
+        assert_ne!(
+            MultiSigOwner::new([keys[0]], 1).unwrap().owner(),
+            Owner::from(keys[0])
+        );
+    }
+
     #[test]
     fn test_ownership_round_timeouts() {
         use crate::crypto::KeyPair;
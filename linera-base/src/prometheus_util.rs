@@ -5,7 +5,7 @@
 
 use prometheus::{
     exponential_buckets, histogram_opts, register_histogram_vec, register_int_counter_vec,
-    HistogramVec, IntCounterVec, Opts,
+    register_int_gauge_vec, HistogramVec, IntCounterVec, IntGaugeVec, Opts,
 };
 
 use crate::time::Instant;
@@ -22,6 +22,12 @@ pub fn register_int_counter_vec(
     register_int_counter_vec!(counter_opts, label_names).expect("IntCounter can be created")
 }
 
+/// Wrapper arount prometheus register_int_gauge_vec! macro which also sets the linera namespace
+pub fn register_int_gauge_vec(name: &str, description: &str, label_names: &[&str]) -> IntGaugeVec {
+    let gauge_opts = Opts::new(name, description).namespace(LINERA_NAMESPACE);
+    register_int_gauge_vec!(gauge_opts, label_names).expect("IntGauge can be created")
+}
+
 /// Wrapper arount prometheus register_histogram_vec! macro which also sets the linera namespace
 pub fn register_histogram_vec(
     name: &str,
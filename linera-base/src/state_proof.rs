@@ -0,0 +1,224 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Proofs of the value of a key of a user application against the `state_hash` of a block.
+//!
+//! The hash of a chain's execution state combines the hash of the system state with the
+//! hash of the collection of application states. The state of each application is hashed
+//! as a binary Merkle tree indexed by the hashes of its keys, so that the value of one key
+//! is proven by the path from its leaf to the root of the tree, together with the hashes of
+//! the other parts of the execution state.
+//!
+//! The verifier recomputes these hashes as the views of `linera-views` do. The tests of
+//! `linera-views` and `linera-execution` check [`MerkleProof::root_hash`],
+//! [`collection_hash`] and [`execution_state_hash`] against the views.
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest as _, Sha3_256};
+use thiserror::Error;
+
+use crate::{
+    crypto::{BcsHashable, CryptoHash},
+    doc_scalar,
+    identifiers::UserApplicationId,
+};
+
+/// The number of bits of the key hashes, which bounds the depth of a Merkle tree.
+const MAX_DEPTH: usize = 256;
+
+/// A proof of the value of a key in a Merkle tree, as maintained by the Merkle views of
+/// `linera-views`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// For each node on the path from the root to the key, the hash of the child that is
+    /// not on the path, if any.
+    pub siblings: Vec<Option<CryptoHash>>,
+    /// Where the path of the key ends.
+    pub terminal: MerkleTerminal,
+}
+
+/// The end of the path of a key in a Merkle tree.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MerkleTerminal {
+    /// The path ends in an empty subtree, so the key is absent.
+    Empty,
+    /// The path ends in a leaf. If the leaf belongs to another key, the key is absent.
+    Leaf {
+        /// The hash of the key of the leaf.
+        key_hash: CryptoHash,
+        /// The hash of the value of the leaf.
+        value_hash: CryptoHash,
+    },
+}
+
+/// A proof of the value of a key of a user application, which can be checked against the
+/// `state_hash` of a block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StateProof {
+    /// The hash of the state of the system application.
+    pub system_hash: CryptoHash,
+    /// The hashes of the states of the other user applications.
+    pub other_applications: Vec<(UserApplicationId, CryptoHash)>,
+    /// The path of the key in the state of the application, or `None` if the application
+    /// has no state on the chain.
+    pub key_proof: Option<MerkleProof>,
+    /// The value of the key, if any.
+    pub value: Option<Vec<u8>>,
+}
+
+/// An error when verifying a [`StateProof`] or a [`MerkleProof`].
+#[derive(Debug, Error)]
+pub enum StateProofError {
+    /// The proof lists the proven application among the other applications.
+    #[error("The proof lists application {0:?} among the other applications")]
+    DuplicateApplication(UserApplicationId),
+    /// The path in the Merkle tree is longer than the key hashes.
+    #[error("The Merkle path has {0} nodes but key hashes only have {MAX_DEPTH} bits")]
+    PathTooLong(usize),
+    /// The terminal leaf is not on the path of the key.
+    #[error("The leaf at the end of the Merkle path is not on the path of the key")]
+    MisplacedLeaf,
+    /// The proof claims a value that doesn't match the end of the path.
+    #[error("The value of the key does not match the end of the Merkle path")]
+    ValueMismatch,
+    /// The proof is not consistent with the state hash.
+    #[error("The proof does not match the state hash {0}")]
+    StateHashMismatch(CryptoHash),
+}
+
+/// The type name under which the execution state hash is turned into a [`CryptoHash`].
+#[derive(Serialize, Deserialize)]
+struct ExecutionStateViewHash([u8; 32]);
+
+impl<'de> BcsHashable<'de> for ExecutionStateViewHash {}
+
+impl MerkleProof {
+    /// Computes the root hash of the Merkle tree in which `key` has the given value, or is
+    /// absent if `value` is `None`.
+    pub fn root_hash(&self, key: &[u8], value: Option<&[u8]>) -> Result<[u8; 32], StateProofError> {
+        let depth = self.siblings.len();
+        if depth > MAX_DEPTH {
+            return Err(StateProofError::PathTooLong(depth));
+        }
+        let key_hash: [u8; 32] = Sha3_256::digest(key).into();
+        let mut node_hash = match (&self.terminal, value) {
+            (MerkleTerminal::Empty, None) => None,
+            (MerkleTerminal::Empty, Some(_)) => return Err(StateProofError::ValueMismatch),
+            (
+                MerkleTerminal::Leaf {
+                    key_hash: leaf_key_hash,
+                    value_hash,
+                },
+                value,
+            ) => {
+                let leaf_key_hash = leaf_key_hash.as_bytes();
+                if (0..depth).any(|level| bit(leaf_key_hash, level) != bit(&key_hash, level)) {
+                    return Err(StateProofError::MisplacedLeaf);
+                }
+                let is_key = leaf_key_hash[..] == key_hash[..];
+                match value {
+                    Some(value) if !is_key || Sha3_256::digest(value) != *value_hash.as_bytes() => {
+                        return Err(StateProofError::ValueMismatch)
+                    }
+                    None if is_key => return Err(StateProofError::ValueMismatch),
+                    _ => {}
+                }
+                let mut hasher = Sha3_256::default();
+                hasher.update([0]);
+                hasher.update(leaf_key_hash);
+                hasher.update(value_hash.as_bytes());
+                Some(hasher.finalize().into())
+            }
+        };
+        for (level, sibling) in self.siblings.iter().enumerate().rev() {
+            let mut children = [sibling.map(|hash| <[u8; 32]>::from(*hash.as_bytes())); 2];
+            children[bit(&key_hash, level)] = node_hash;
+            let mut hasher = Sha3_256::default();
+            hasher.update([1]);
+            for child in children {
+                match child {
+                    None => hasher.update([0]),
+                    Some(hash) => {
+                        hasher.update([1]);
+                        hasher.update(hash);
+                    }
+                }
+            }
+            node_hash = Some(hasher.finalize().into());
+        }
+        // An empty tree is hashed as an empty sequence of bytes.
+        Ok(node_hash.unwrap_or_else(|| Sha3_256::digest(b"").into()))
+    }
+}
+
+impl StateProof {
+    /// Verifies the proof against the `state_hash` of a block, and returns the value of
+    /// `key` in the state of the given application, if any.
+    pub fn verify(
+        &self,
+        state_hash: CryptoHash,
+        application_id: UserApplicationId,
+        key: &[u8],
+    ) -> Result<Option<&[u8]>, StateProofError> {
+        let mut applications = Vec::with_capacity(self.other_applications.len() + 1);
+        for (other_id, hash) in &self.other_applications {
+            if *other_id == application_id {
+                return Err(StateProofError::DuplicateApplication(application_id));
+            }
+            applications.push((
+                bcs::to_bytes(other_id).expect("Serialization should not fail"),
+                <[u8; 32]>::from(*hash.as_bytes()),
+            ));
+        }
+        let value = self.value.as_deref();
+        match &self.key_proof {
+            Some(key_proof) => {
+                let application_hash = key_proof.root_hash(key, value)?;
+                applications.push((
+                    bcs::to_bytes(&application_id).expect("Serialization should not fail"),
+                    application_hash,
+                ));
+            }
+            None if value.is_some() => return Err(StateProofError::ValueMismatch),
+            None => {}
+        }
+        let hash = execution_state_hash(self.system_hash, collection_hash(applications));
+        if hash != state_hash {
+            return Err(StateProofError::StateHashMismatch(state_hash));
+        }
+        Ok(value)
+    }
+}
+
+/// Computes the hash of a hashed collection view, as in `linera-views`, from the
+/// serialized indices of its entries and their hashes.
+pub fn collection_hash(mut entries: Vec<(Vec<u8>, [u8; 32])>) -> [u8; 32] {
+    // The entries are hashed in the order of their serialized indices.
+    entries.sort();
+    let mut hasher = Sha3_256::default();
+    hasher.update(bcs::to_bytes(&(entries.len() as u32)).expect("Serialization should not fail"));
+    for (index, hash) in entries {
+        hasher.update(index);
+        hasher.update(hash);
+    }
+    hasher.finalize().into()
+}
+
+/// Computes the hash of an `ExecutionStateView` from the hashes of its system state and
+/// of the collection of its application states.
+pub fn execution_state_hash(system_hash: CryptoHash, users_hash: [u8; 32]) -> CryptoHash {
+    let mut hasher = Sha3_256::default();
+    hasher.update(system_hash.as_bytes());
+    hasher.update(users_hash);
+    CryptoHash::new(&ExecutionStateViewHash(hasher.finalize().into()))
+}
+
+/// Returns the bit of `key_hash` at the given depth of a Merkle tree.
+fn bit(key_hash: &[u8], depth: usize) -> usize {
+    ((key_hash[depth / 8] >> (7 - depth % 8)) & 1) as usize
+}
+
+doc_scalar!(
+    StateProof,
+    "A proof of the value of a key of a user application against the state hash of a block"
+);
@@ -13,7 +13,7 @@ use futures::stream::{self, StreamExt, TryStreamExt};
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        Amount, ArithmeticError, Blob, BlockHeight, OracleResponse, Timestamp,
+        Amount, ArithmeticError, Blob, BlockHeight, OracleResponse, Round, TimeDelta, Timestamp,
         UserApplicationDescription,
     },
     ensure,
@@ -23,20 +23,22 @@ use linera_base::{
     },
 };
 use linera_execution::{
-    committee::ValidatorName, system::OpenChainConfig, ExecutionOutcome, ExecutionRuntimeContext,
-    ExecutionStateView, Message, MessageContext, Operation, OperationContext, Query, QueryContext,
-    RawExecutionOutcome, RawOutgoingMessage, ResourceController, ResourceTracker, Response,
-    ServiceRuntimeEndpoint, TransactionTracker,
+    committee::ValidatorName,
+    system::{request_deadline, OpenChainConfig},
+    ExecutionOutcome, ExecutionRuntimeContext, ExecutionStateView, Message, MessageContext,
+    Operation, OperationContext, Query, QueryContext, RawExecutionOutcome, RawOutgoingMessage,
+    ResourceController, ResourceTracker, Response, ServiceRuntimeEndpoint, TransactionTracker,
 };
 use linera_views::{
+    common::CustomSerialize,
     context::Context,
     log_view::LogView,
     map_view::MapView,
     queue_view::QueueView,
     reentrant_collection_view::ReentrantCollectionView,
     register_view::RegisterView,
-    set_view::SetView,
-    views::{ClonableView, CryptoHashView, RootView, View},
+    set_view::{CustomSetView, SetView},
+    views::{ClonableView, CryptoHashView, RootView, View, ViewError},
 };
 use serde::{Deserialize, Serialize};
 
@@ -154,6 +156,9 @@ static STATE_HASH_COMPUTATION_LATENCY: LazyLock<HistogramVec> = LazyLock::new(||
 /// The BCS-serialized size of an empty [`Block`].
 const EMPTY_BLOCK_SIZE: usize = 91;
 
+/// How long after the timestamp of its block a message can be delayed, in seconds.
+pub const MAX_MESSAGE_DELAY_SECS: u64 = 30 * 24 * 60 * 60;
+
 /// An origin, cursor and timestamp of a unskippable bundle in our inbox.
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct TimestampedBundleInInbox {
@@ -163,9 +168,57 @@ pub struct TimestampedBundleInInbox {
     pub seen: Timestamp,
 }
 
+/// An origin, cursor and due time of a delayed bundle in our inbox.
+#[derive(
+    Debug,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    async_graphql::SimpleObject,
+)]
+pub struct DelayedBundleInInbox {
+    /// The time from which the bundle can be received: the later of its `not_before`
+    /// timestamp and the time when it was added to the inbox.
+    pub due: Timestamp,
+    /// The origin and cursor of the bundle.
+    pub entry: BundleInInbox,
+}
+
+/// Delayed bundles are serialized with their due time in big-endian order first, so that
+/// they are ordered by due time as set indices.
+impl CustomSerialize for DelayedBundleInInbox {
+    fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
+        Ok(bcs::to_bytes(&(
+            self.due.micros().to_be_bytes(),
+            &self.entry,
+        ))?)
+    }
+
+    fn from_custom_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+        let (due, entry) = bcs::from_bytes::<([u8; 8], BundleInInbox)>(bytes)?;
+        Ok(Self {
+            due: Timestamp::from(u64::from_be_bytes(due)),
+            entry,
+        })
+    }
+}
+
 /// An origin and cursor of a unskippable bundle that is no longer in our inbox.
 #[derive(
-    Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, async_graphql::SimpleObject,
+    Debug,
+    Clone,
+    Hash,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    async_graphql::SimpleObject,
 )]
 pub struct BundleInInbox {
     /// The origin from which we received the bundle.
@@ -227,6 +280,12 @@ where
     pub outbox_counters: RegisterView<C, BTreeMap<BlockHeight, u32>>,
     /// Channels able to multicast messages to subscribers.
     pub channels: ReentrantCollectionView<C, ChannelFullName, ChannelStateView<C>>,
+    /// Delayed bundles in our inboxes, ordered by the time when they become due.
+    #[graphql(skip)]
+    pub delayed_bundles: CustomSetView<C, DelayedBundleInInbox>,
+    /// The time when each of the `delayed_bundles` becomes due.
+    #[graphql(skip)]
+    pub delayed_bundle_due_times: MapView<C, BundleInInbox, Timestamp>,
 }
 
 /// Block-chaining state.
@@ -307,6 +366,16 @@ impl ChainTipState {
     }
 }
 
+/// How far a chain got in certifying its next block. As long as it is unchanged, the
+/// blocks that are not confirmed yet make no progress.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ChainProgress {
+    /// The height of the next block.
+    pub next_block_height: BlockHeight,
+    /// The current round of the chain manager.
+    pub round: Round,
+}
+
 /// The state of a channel followed by subscribers.
 #[derive(Debug, ClonableView, View, SimpleObject)]
 pub struct ChannelStateView<C>
@@ -422,6 +491,37 @@ where
         }
     }
 
+    /// Returns how far the chain got in certifying its next block.
+    pub fn progress(&self) -> ChainProgress {
+        ChainProgress {
+            next_block_height: self.tip_state.get().next_block_height,
+            round: self.manager.current_round(),
+        }
+    }
+
+    /// Returns whether blobs are kept for blocks that are not confirmed yet: the blobs of
+    /// the proposal and of the locked block, and those of a validated block that is still
+    /// missing some.
+    pub async fn has_pending_blobs(&self) -> Result<bool, ViewError> {
+        Ok(self.pending_validated_blobs.count().await? > 0
+            || self.manager.has_pending_blobs().await?)
+    }
+
+    /// Removes the blobs kept for blocks that are not confirmed yet, and returns them. The
+    /// validated block that is still missing blobs, if any, is dropped too. The proposal and
+    /// the locked block are kept, and clients provide their blobs again when they retry.
+    pub async fn remove_pending_blobs(&mut self) -> Result<Vec<Blob>, ViewError> {
+        let mut blobs = self.manager.remove_pending_blobs().await?;
+        for (blob_id, maybe_blob) in self.pending_validated_blobs.index_values().await? {
+            if let Some(blob) = maybe_blob {
+                blobs.insert(blob_id, blob);
+            }
+        }
+        self.pending_validated_blobs.clear();
+        self.pending_validated_block.set(None);
+        Ok(blobs.into_values().collect())
+    }
+
     /// Verifies that this chain is up-to-date and all the messages executed ahead of time
     /// have been properly received by now.
     pub async fn validate_incoming_bundles(&self) -> Result<(), ChainError> {
@@ -430,7 +530,16 @@ where
         let max_stream_queries = self.context().max_stream_queries();
         let stream = stream::iter(pairs)
             .map(|(origin, inbox)| async move {
-                if let Some(bundle) = inbox.removed_bundles.front().await? {
+                let missing_bundle = match inbox.removed_bundles.front().await? {
+                    Some(bundle) => Some(bundle),
+                    None => inbox
+                        .removed_delayed_bundles
+                        .index_values_in_range(.., false, Some(1))
+                        .await?
+                        .pop()
+                        .map(|(_, bundle)| bundle),
+                };
+                if let Some(bundle) = missing_bundle {
                     return Err(ChainError::MissingCrossChainUpdate {
                         chain_id,
                         origin: origin.into(),
@@ -461,10 +570,19 @@ where
     ) -> Result<Option<BlockHeight>, ChainError> {
         let inbox = self.inboxes.try_load_entry(origin).await?;
         match inbox {
-            Some(inbox) => match inbox.removed_bundles.back().await? {
-                Some(bundle) => Ok(Some(bundle.height)),
-                None => Ok(None),
-            },
+            Some(inbox) => {
+                let delayed_bundle = inbox
+                    .removed_delayed_bundles
+                    .index_values_in_range(.., true, Some(1))
+                    .await?
+                    .pop();
+                let height = inbox
+                    .removed_bundles
+                    .back()
+                    .await?
+                    .map(|bundle| bundle.height);
+                Ok(height.max(delayed_bundle.map(|(_, bundle)| bundle.height)))
+            }
             None => Ok(None),
         }
     }
@@ -521,6 +639,7 @@ where
             let mut inbox = self.inboxes.try_load_entry_mut(origin).await?;
             let entry = BundleInInbox::new(origin.clone(), &bundle);
             let skippable = bundle.is_skippable();
+            let not_before = bundle.not_before();
             let newly_added = inbox
                 .add_bundle(bundle)
                 .await
@@ -531,9 +650,20 @@ where
                     )),
                 })?;
             if newly_added && !skippable {
-                let seen = local_time;
-                self.unskippable_bundles
-                    .push_back(TimestampedBundleInInbox { entry, seen });
+                match not_before {
+                    // Delayed bundles only become due at their scheduled time.
+                    Some(not_before) => {
+                        let due = not_before.max(local_time);
+                        self.delayed_bundle_due_times.insert(&entry, due)?;
+                        self.delayed_bundles
+                            .insert(&DelayedBundleInInbox { due, entry })?;
+                    }
+                    None => {
+                        let seen = local_time;
+                        self.unskippable_bundles
+                            .push_back(TimestampedBundleInInbox { entry, seen });
+                    }
+                }
             }
         }
 
@@ -606,6 +736,16 @@ where
                     block_timestamp: timestamp,
                 }
             );
+            if let Some(not_before) = bundle.not_before() {
+                ensure!(
+                    not_before <= timestamp,
+                    ChainError::PrematureBundle {
+                        chain_id,
+                        not_before,
+                        block_timestamp: timestamp,
+                    }
+                );
+            }
             let bundles = bundles_by_origin.entry(origin).or_default();
             bundles.push(bundle);
         }
@@ -626,7 +766,14 @@ where
                     .remove_bundle(bundle)
                     .await
                     .map_err(|error| ChainError::from((chain_id, origin.clone(), error)))?;
-                if was_present && !bundle.is_skippable() {
+                if was_present && bundle.not_before().is_some() {
+                    let entry = BundleInInbox::new(origin.clone(), bundle);
+                    if let Some(due) = self.delayed_bundle_due_times.get(&entry).await? {
+                        self.delayed_bundle_due_times.remove(&entry)?;
+                        self.delayed_bundles
+                            .remove(&DelayedBundleInInbox { due, entry })?;
+                    }
+                } else if was_present && !bundle.is_skippable() {
                     removed_unskippable.insert(BundleInInbox::new(origin.clone(), bundle));
                 }
             }
@@ -657,6 +804,27 @@ where
         Ok(())
     }
 
+    /// Returns the time since which the oldest unskippable bundle that is already due at
+    /// `local_time` has been waiting in our inboxes, if any.
+    pub async fn oldest_due_unskippable_bundle(
+        &self,
+        local_time: Timestamp,
+    ) -> Result<Option<Timestamp>, ChainError> {
+        let undelayed = self
+            .unskippable_bundles
+            .front()
+            .await?
+            .map(|ts_entry| ts_entry.seen);
+        let delayed = self
+            .delayed_bundles
+            .indices_in_range(.., false, Some(1))
+            .await?
+            .pop()
+            .map(|delayed| delayed.due)
+            .filter(|due| *due <= local_time);
+        Ok(undelayed.into_iter().chain(delayed).min())
+    }
+
     /// Executes a block: first the incoming messages, then the main operation.
     /// * Modifies the state of outboxes and channels, if needed.
     /// * As usual, in case of errors, `self` may not be consistent any more and should be thrown
@@ -668,6 +836,20 @@ where
         local_time: Timestamp,
         replaying_oracle_responses: Option<Vec<Vec<OracleResponse>>>,
     ) -> Result<BlockExecutionOutcome, ChainError> {
+        let (outcome, _) = self
+            .execute_block_and_track_resources(block, local_time, replaying_oracle_responses)
+            .await?;
+        Ok(outcome)
+    }
+
+    /// Same as [`ChainStateView::execute_block`], but also returns the resources used by the
+    /// block, as measured by the committee's resource control policy.
+    pub async fn execute_block_and_track_resources(
+        &mut self,
+        block: &ProposedBlock,
+        local_time: Timestamp,
+        replaying_oracle_responses: Option<Vec<Vec<OracleResponse>>>,
+    ) -> Result<(BlockExecutionOutcome, ResourceTracker), ChainError> {
         #[cfg(with_metrics)]
         let _execution_latency = BLOCK_EXECUTION_LATENCY.measure_latency();
 
@@ -705,8 +887,38 @@ where
         let Some((_, committee)) = self.execution_state.system.current_committee() else {
             return Err(ChainError::InactiveChain(chain_id));
         };
+        let policy = committee.policy();
+        ensure!(
+            block.operations.len() as u64 <= policy.maximum_operations_per_block,
+            ChainError::TooManyOperations {
+                count: block.operations.len() as u64,
+                maximum: policy.maximum_operations_per_block,
+            }
+        );
+        ensure!(
+            block.incoming_bundles.len() as u64 <= policy.maximum_incoming_bundles_per_block,
+            ChainError::TooManyIncomingBundles {
+                count: block.incoming_bundles.len() as u64,
+                maximum: policy.maximum_incoming_bundles_per_block,
+            }
+        );
+        let operation_argument_bytes = block
+            .operations
+            .iter()
+            .map(|operation| match operation {
+                Operation::System(_) => 0,
+                Operation::User { bytes, .. } => bytes.len() as u64,
+            })
+            .sum::<u64>();
+        ensure!(
+            operation_argument_bytes <= policy.maximum_operation_argument_bytes,
+            ChainError::OperationArgumentsTooLarge {
+                size: operation_argument_bytes,
+                maximum: policy.maximum_operation_argument_bytes,
+            }
+        );
         let mut resource_controller = ResourceController {
-            policy: Arc::new(committee.policy().clone()),
+            policy: Arc::new(policy.clone()),
             tracker: ResourceTracker::default(),
             account: block.authenticated_signer,
         };
@@ -763,6 +975,7 @@ where
         let mut oracle_responses = Vec::new();
         let mut events = Vec::new();
         let mut messages = Vec::new();
+        let mut stored_bytes = BTreeMap::<UserApplicationId, i32>::new();
         for (txn_index, transaction) in block.transactions() {
             let chain_execution_context = match transaction {
                 Transaction::ReceiveMessages(_) => ChainExecutionContext::IncomingBundle(txn_index),
@@ -827,6 +1040,14 @@ where
                 .update_execution_outcomes_with_app_registrations(&mut txn_tracker)
                 .await
                 .with_execution_context(chain_execution_context)?;
+            self.execution_state
+                .record_outstanding_requests(block.height, block.timestamp, &mut txn_tracker)
+                .await
+                .with_execution_context(chain_execution_context)?;
+            for (application_id, delta) in txn_tracker.stored_bytes() {
+                let total = stored_bytes.entry(*application_id).or_default();
+                *total = total.checked_add(*delta).ok_or(ArithmeticError::Overflow)?;
+            }
             let (txn_outcomes, txn_oracle_responses, new_next_message_index) = txn_tracker
                 .destructure()
                 .with_execution_context(chain_execution_context)?;
@@ -834,6 +1055,7 @@ where
             let (txn_messages, txn_events) = self
                 .process_execution_outcomes(block.height, txn_outcomes)
                 .await?;
+            check_message_delays(block.timestamp, &txn_messages)?;
             if matches!(
                 transaction,
                 Transaction::ExecuteOperation(_)
@@ -867,9 +1089,13 @@ where
             events.push(txn_events);
         }
 
-        // Finally, charge for the block fee, except if the chain is closed. Closed chains should
-        // always be able to reject incoming messages.
+        // Finally, charge for the storage growth of each application and the block fee, except
+        // if the chain is closed. Closed chains should always be able to reject incoming messages.
         if !self.is_closed() {
+            resource_controller
+                .track_storage_rent(&mut self.execution_state, &stored_bytes)
+                .await
+                .with_execution_context(ChainExecutionContext::Block)?;
             resource_controller
                 .with_state(&mut self.execution_state)
                 .await?
@@ -924,7 +1150,7 @@ where
             oracle_responses,
             events,
         };
-        Ok(outcome)
+        Ok((outcome, resource_controller.tracker))
     }
 
     /// Executes a message as part of an incoming bundle in a block.
@@ -945,6 +1171,7 @@ where
         let context = MessageContext {
             chain_id: block.chain_id,
             is_bouncing: posted_message.is_bouncing(),
+            is_request: posted_message.is_request(),
             height: block.height,
             certificate_hash: incoming_bundle.bundle.certificate_hash,
             message_id,
@@ -952,7 +1179,17 @@ where
             refund_grant_to: posted_message.refund_grant_to,
         };
         let mut grant = posted_message.grant;
+        // Requests that already timed out at their sender are not executed anymore.
+        let is_expired_request = posted_message.is_request()
+            && block.timestamp >= request_deadline(incoming_bundle.bundle.timestamp);
         match incoming_bundle.action {
+            MessageAction::Accept if is_expired_request => {
+                // Like a rejected request, this tells the sender that the request failed.
+                self.execution_state
+                    .bounce_message(context, grant, posted_message.message.clone(), txn_tracker)
+                    .await
+                    .with_execution_context(ChainExecutionContext::IncomingBundle(txn_index))?;
+            }
             MessageAction::Accept => {
                 // Once a chain is closed, accepting incoming messages is not allowed.
                 ensure!(!self.is_closed(), ChainError::ClosedChain);
@@ -990,7 +1227,7 @@ where
                     }
                 );
                 if posted_message.is_tracked() {
-                    // Bounce the message.
+                    // Bounce the message, or the failed response to a request.
                     self.execution_state
                         .bounce_message(context, grant, posted_message.message.clone(), txn_tracker)
                         .await
@@ -1087,6 +1324,7 @@ where
             destination,
             authenticated,
             grant,
+            not_before,
             kind,
             message,
         } in raw_outcome.messages
@@ -1107,6 +1345,7 @@ where
                 authenticated_signer,
                 grant,
                 refund_grant_to,
+                not_before,
                 kind,
                 message: lift(message),
             });
@@ -1244,6 +1483,43 @@ where
     }
 }
 
+/// Checks that the delayed messages of a transaction are not delayed too long, and that the
+/// messages to each destination are delayed until the same time, so that each of the
+/// bundles sent by the transaction is either delayed as a whole or not at all.
+fn check_message_delays(
+    block_timestamp: Timestamp,
+    messages: &[OutgoingMessage],
+) -> Result<(), ChainError> {
+    let max_delay = TimeDelta::from_secs(MAX_MESSAGE_DELAY_SECS);
+    let mut delays = HashMap::new();
+    for message in messages {
+        if let Some(not_before) = message.not_before {
+            ensure!(
+                not_before.delta_since(block_timestamp) <= max_delay,
+                ChainError::MessageDelayTooLong {
+                    not_before,
+                    block_timestamp,
+                }
+            );
+        }
+        // The messages of a transaction are bundled by recipient and by channel.
+        let channel_application_id = match &message.destination {
+            Destination::Recipient(_) => None,
+            Destination::Subscribers(_) => Some(message.message.application_id()),
+        };
+        let delay = delays
+            .entry((&message.destination, channel_application_id))
+            .or_insert(message.not_before);
+        ensure!(
+            *delay == message.not_before,
+            ChainError::MixedMessageDelays {
+                destination: message.destination.clone(),
+            }
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn empty_block_size() {
     let executed_block = crate::data_types::ExecutedBlock {
@@ -20,6 +20,7 @@ use linera_base::{
         Account, BlobId, BlobType, ChainId, ChannelName, Destination, GenericApplicationId,
         MessageId, Owner, StreamId,
     },
+    ownership::MultiSigOwner,
 };
 use linera_execution::{
     committee::{Committee, Epoch, ValidatorName},
@@ -316,6 +317,19 @@ pub struct BlockProposal {
     pub blobs: Vec<Blob>,
     #[debug(skip_if = Option::is_none)]
     pub validated_block_certificate: Option<LiteCertificate<'static>>,
+    /// If the owner is a multi-signature owner: its members and their signatures.
+    #[debug(skip_if = Option::is_none)]
+    pub multi_sig: Option<MultiSigAuthentication>,
+}
+
+/// The signatures authenticating a block proposal on behalf of a [`MultiSigOwner`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(with_testing, derive(Eq, PartialEq))]
+pub struct MultiSigAuthentication {
+    /// The public keys and threshold the proposal's owner was derived from.
+    pub owner: MultiSigOwner,
+    /// Signatures on the proposal content by members other than the proposer.
+    pub signatures: Vec<(PublicKey, Signature)>,
 }
 
 /// A posted message together with routing information.
@@ -332,6 +346,9 @@ pub struct OutgoingMessage {
     /// Where to send a refund for the unused part of the grant after execution, if any.
     #[debug(skip_if = Option::is_none)]
     pub refund_grant_to: Option<Account>,
+    /// The earliest time at which the message can be received, if any.
+    #[debug(skip_if = Option::is_none)]
+    pub not_before: Option<Timestamp>,
     /// The kind of message being sent.
     pub kind: MessageKind,
     /// The message itself.
@@ -352,6 +369,9 @@ pub struct PostedMessage {
     /// Where to send a refund for the unused part of the grant after execution, if any.
     #[debug(skip_if = Option::is_none)]
     pub refund_grant_to: Option<Account>,
+    /// The earliest time at which the message can be received, if any.
+    #[debug(skip_if = Option::is_none)]
+    pub not_before: Option<Timestamp>,
     /// The kind of message being sent.
     pub kind: MessageKind,
     /// The index of the message in the sending block.
@@ -386,6 +406,7 @@ impl OutgoingMessage {
             authenticated_signer,
             grant,
             refund_grant_to,
+            not_before,
             kind,
             message,
         } = self;
@@ -393,6 +414,7 @@ impl OutgoingMessage {
             authenticated_signer,
             grant,
             refund_grant_to,
+            not_before,
             kind,
             index,
             message,
@@ -576,6 +598,15 @@ impl Target {
 }
 
 impl MessageBundle {
+    /// Returns the earliest time at which this bundle can be received, if it is delayed.
+    ///
+    /// The messages of a bundle are all delayed until the same time, or not at all.
+    pub fn not_before(&self) -> Option<Timestamp> {
+        self.messages
+            .first()
+            .and_then(|posted_message| posted_message.not_before)
+    }
+
     pub fn is_skippable(&self) -> bool {
         self.messages.iter().all(PostedMessage::is_skippable)
     }
@@ -586,7 +617,7 @@ impl MessageBundle {
             match posted_message.kind {
                 MessageKind::Simple | MessageKind::Bouncing => {}
                 MessageKind::Protected => return false,
-                MessageKind::Tracked => tracked = true,
+                MessageKind::Tracked | MessageKind::Request => tracked = true,
             }
         }
         tracked
@@ -608,8 +639,12 @@ impl MessageBundle {
 
 impl PostedMessage {
     pub fn is_skippable(&self) -> bool {
+        if self.not_before.is_some() {
+            // Delayed messages are held in the inbox until they can be received.
+            return false;
+        }
         match self.kind {
-            MessageKind::Protected | MessageKind::Tracked => false,
+            MessageKind::Protected | MessageKind::Tracked | MessageKind::Request => false,
             MessageKind::Simple | MessageKind::Bouncing => self.grant == Amount::ZERO,
         }
     }
@@ -619,7 +654,11 @@ impl PostedMessage {
     }
 
     pub fn is_tracked(&self) -> bool {
-        matches!(self.kind, MessageKind::Tracked)
+        matches!(self.kind, MessageKind::Tracked | MessageKind::Request)
+    }
+
+    pub fn is_request(&self) -> bool {
+        matches!(self.kind, MessageKind::Request)
     }
 
     pub fn is_bouncing(&self) -> bool {
@@ -796,6 +835,7 @@ impl BlockProposal {
             signature,
             blobs,
             validated_block_certificate: None,
+            multi_sig: None,
         }
     }
 
@@ -821,12 +861,61 @@ impl BlockProposal {
             signature,
             blobs,
             validated_block_certificate: Some(lite_cert),
+            multi_sig: None,
         }
     }
 
+    /// Turns this into a proposal on behalf of the given multi-signature owner, adding the
+    /// co-signers' signatures. The proposer must be one of the members.
+    pub fn with_multi_sig<'a>(
+        mut self,
+        multi_sig_owner: MultiSigOwner,
+        co_signers: impl IntoIterator<Item = &'a KeyPair>,
+    ) -> Self {
+        let signatures = co_signers
+            .into_iter()
+            .filter(|key_pair| key_pair.public() != self.public_key)
+            .map(|key_pair| {
+                let signature = Signature::new(&self.content, key_pair);
+                (key_pair.public(), signature)
+            })
+            .collect();
+        self.owner = Owner::from(&multi_sig_owner);
+        self.multi_sig = Some(MultiSigAuthentication {
+            owner: multi_sig_owner,
+            signatures,
+        });
+        self
+    }
+
     pub fn check_signature(&self, public_key: PublicKey) -> Result<(), CryptoError> {
         self.signature.check(&self.content, public_key)
     }
+
+    /// Returns whether the proposal's owner is authenticated by its public key or, for a
+    /// multi-signature owner, by enough distinct valid member signatures.
+    ///
+    /// This does not check the proposer's own signature; see [`Self::check_signature`].
+    pub fn check_owner_authentication(&self) -> bool {
+        let Some(multi_sig) = &self.multi_sig else {
+            return self.owner == Owner::from(self.public_key);
+        };
+        if self.owner != Owner::from(&multi_sig.owner)
+            || !multi_sig.owner.contains(&self.public_key)
+        {
+            return false;
+        }
+        let mut signers = BTreeSet::from([self.public_key]);
+        for (public_key, signature) in &multi_sig.signatures {
+            if multi_sig.owner.contains(public_key)
+                && !signers.contains(public_key)
+                && signature.check(&self.content, *public_key).is_ok()
+            {
+                signers.insert(*public_key);
+            }
+        }
+        signers.len() >= usize::try_from(multi_sig.owner.threshold).unwrap_or(usize::MAX)
+    }
 }
 
 impl LiteVote {
@@ -10,7 +10,9 @@ use linera_base::{
 #[cfg(with_testing)]
 use linera_views::context::{create_test_memory_context, MemoryContext};
 use linera_views::{
+    common::CustomSerialize,
     context::Context,
+    map_view::CustomMapView,
     queue_view::QueueView,
     register_view::RegisterView,
     views::{ClonableView, View, ViewError},
@@ -36,6 +38,9 @@ mod inbox_tests;
 /// * The cursors of added bundles (resp. removed bundles) must be increasing over time.
 /// * Reconciliation of added and removed bundles is allowed to skip some added bundles.
 ///   However, the opposite is not true: every removed bundle must be eventually added.
+/// * Bundles with a `not_before` timestamp are kept aside in `delayed_bundles` (resp.
+///   `removed_delayed_bundles`), so that they do not hold back later bundles from the same
+///   origin while they are not due yet.
 #[derive(Debug, ClonableView, View, async_graphql::SimpleObject)]
 pub struct InboxStateView<C>
 where
@@ -50,6 +55,13 @@ where
     /// These bundles have been removed by anticipation and are waiting to be added.
     /// At least one of `added_bundles` and `removed_bundles` should be empty.
     pub removed_bundles: QueueView<C, MessageBundle>,
+    /// Delayed bundles that have been added and are waiting to be removed, by cursor.
+    #[graphql(skip)]
+    pub delayed_bundles: CustomMapView<C, Cursor, MessageBundle>,
+    /// Delayed bundles that have been removed by anticipation and are waiting to be added,
+    /// by cursor.
+    #[graphql(skip)]
+    pub removed_delayed_bundles: CustomMapView<C, Cursor, MessageBundle>,
 }
 
 #[derive(
@@ -104,6 +116,22 @@ impl From<&MessageBundle> for Cursor {
     }
 }
 
+/// Cursors are serialized in big-endian order, so that they are ordered as map indices.
+impl CustomSerialize for Cursor {
+    fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
+        let bytes = (self.height.0.to_be_bytes(), self.index.to_be_bytes());
+        Ok(bcs::to_bytes(&bytes)?)
+    }
+
+    fn from_custom_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+        let (height, index) = bcs::from_bytes::<([u8; 8], [u8; 4])>(bytes)?;
+        Ok(Self {
+            height: BlockHeight(u64::from_be_bytes(height)),
+            index: u32::from_be_bytes(index),
+        })
+    }
+}
+
 impl Cursor {
     fn try_add_one(self) -> Result<Self, ArithmeticError> {
         let value = Self {
@@ -165,11 +193,15 @@ where
 
     /// Consumes a bundle from the inbox.
     ///
-    /// Returns `true` if the bundle was already known, i.e. it was present in `added_bundles`.
+    /// Returns `true` if the bundle was already known, i.e. it was present in `added_bundles`
+    /// or `delayed_bundles`.
     pub(crate) async fn remove_bundle(
         &mut self,
         bundle: &MessageBundle,
     ) -> Result<bool, InboxError> {
+        if bundle.not_before().is_some() {
+            return self.remove_delayed_bundle(bundle).await;
+        }
         // Record the latest cursor.
         let cursor = Cursor::from(bundle);
         ensure!(
@@ -225,7 +257,8 @@ where
     /// Pushes a bundle to the inbox. The verifications should not fail in production unless
     /// many validators are faulty.
     ///
-    /// Returns `true` if the bundle was new, `false` if it was already in `removed_bundles`.
+    /// Returns `true` if the bundle was new, `false` if it was already in `removed_bundles`
+    /// or `removed_delayed_bundles`.
     pub(crate) async fn add_bundle(&mut self, bundle: MessageBundle) -> Result<bool, InboxError> {
         // Record the latest cursor.
         let cursor = Cursor::from(&bundle);
@@ -236,6 +269,26 @@ where
                 next_cursor: *self.next_cursor_to_add.get(),
             }
         );
+        // Delayed bundles that were removed by anticipation cannot be skipped.
+        if let Some((previous_cursor, previous_bundle)) = self
+            .removed_delayed_bundles
+            .index_values_in_range(.., false, Some(1))
+            .await?
+            .pop()
+        {
+            ensure!(
+                previous_cursor >= cursor,
+                InboxError::UnexpectedBundle {
+                    previous_bundle,
+                    bundle,
+                }
+            );
+        }
+        if bundle.not_before().is_some() {
+            let newly_added = self.add_delayed_bundle(cursor, bundle).await?;
+            self.next_cursor_to_add.set(cursor.try_add_one()?);
+            return Ok(newly_added);
+        }
         // Find if the bundle was removed ahead of time.
         let newly_added = match self.removed_bundles.front().await? {
             Some(previous_bundle) => {
@@ -272,6 +325,62 @@ where
         self.next_cursor_to_add.set(cursor.try_add_one()?);
         Ok(newly_added)
     }
+
+    /// Consumes a delayed bundle. Unlike other bundles, delayed bundles may be removed out of
+    /// order with respect to the rest of the inbox.
+    async fn remove_delayed_bundle(&mut self, bundle: &MessageBundle) -> Result<bool, InboxError> {
+        let cursor = Cursor::from(bundle);
+        if let Some(previous_bundle) = self.delayed_bundles.get(&cursor).await? {
+            ensure!(
+                bundle == &previous_bundle,
+                InboxError::UnexpectedBundle {
+                    previous_bundle,
+                    bundle: bundle.clone(),
+                }
+            );
+            self.delayed_bundles.remove(&cursor)?;
+            tracing::trace!("Consuming delayed bundle {:?}", bundle);
+            return Ok(true);
+        }
+        // The bundle was not added yet, so it must not have been passed already.
+        ensure!(
+            cursor >= *self.next_cursor_to_add.get()
+                && !self.removed_delayed_bundles.contains_key(&cursor).await?,
+            InboxError::IncorrectOrder {
+                bundle: bundle.clone(),
+                next_cursor: *self.next_cursor_to_add.get(),
+            }
+        );
+        tracing::trace!("Marking delayed bundle as expected: {:?}", bundle);
+        self.removed_delayed_bundles
+            .insert(&cursor, bundle.clone())?;
+        Ok(false)
+    }
+
+    /// Adds a delayed bundle, or reconciles it with a delayed bundle removed by anticipation.
+    async fn add_delayed_bundle(
+        &mut self,
+        cursor: Cursor,
+        bundle: MessageBundle,
+    ) -> Result<bool, InboxError> {
+        match self.removed_delayed_bundles.get(&cursor).await? {
+            Some(previous_bundle) => {
+                ensure!(
+                    bundle == previous_bundle,
+                    InboxError::UnexpectedBundle {
+                        previous_bundle,
+                        bundle,
+                    }
+                );
+                self.removed_delayed_bundles.remove(&cursor)?;
+                Ok(false)
+            }
+            None => {
+                self.delayed_bundles.insert(&cursor, bundle)?;
+                Ok(true)
+            }
+        }
+    }
 }
 
 #[cfg(with_testing)]
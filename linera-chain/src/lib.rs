@@ -20,13 +20,13 @@ mod outbox;
 #[cfg(with_testing)]
 pub mod test;
 
-pub use chain::ChainStateView;
+pub use chain::{ChainProgress, ChainStateView, MAX_MESSAGE_DELAY_SECS};
 use data_types::{MessageBundle, Origin, PostedMessage};
 use linera_base::{
     bcs,
     crypto::{CryptoError, CryptoHash},
     data_types::{ArithmeticError, BlockHeight, Round, Timestamp},
-    identifiers::{ApplicationId, BlobId, ChainId},
+    identifiers::{ApplicationId, BlobId, ChainId, Destination},
 };
 use linera_execution::ExecutionError;
 use linera_views::views::ViewError;
@@ -104,6 +104,15 @@ pub enum ChainError {
         bundle_timestamp: Timestamp,
         block_timestamp: Timestamp,
     },
+    #[error(
+        "Incoming message bundle in block proposed to {chain_id:?} cannot be received before \
+        {not_before:}, which is later than the block timestamp {block_timestamp:}."
+    )]
+    PrematureBundle {
+        chain_id: ChainId,
+        not_before: Timestamp,
+        block_timestamp: Timestamp,
+    },
     #[error("The signature was not created by a valid entity")]
     InvalidSigner,
     #[error(
@@ -141,6 +150,14 @@ pub enum ChainError {
     InternalError(String),
     #[error("Block proposal is too large")]
     BlockProposalTooLarge,
+    #[error("Block has {count} operations but at most {maximum} are allowed")]
+    TooManyOperations { count: u64, maximum: u64 },
+    #[error("Block has {count} incoming bundles but at most {maximum} are allowed")]
+    TooManyIncomingBundles { count: u64, maximum: u64 },
+    #[error(
+        "The operation arguments in the block have {size} bytes but at most {maximum} are allowed"
+    )]
+    OperationArgumentsTooLarge { size: u64, maximum: u64 },
     #[error(transparent)]
     BcsError(#[from] bcs::Error),
     #[error("Insufficient balance to pay the fees")]
@@ -155,6 +172,19 @@ pub enum ChainError {
     MissingMandatoryApplications(Vec<ApplicationId>),
     #[error("Can't use grant across different broadcast messages")]
     GrantUseOnBroadcast,
+    #[error(
+        "A message cannot be delayed until {not_before:}, more than \
+        {MAX_MESSAGE_DELAY_SECS} seconds after the block timestamp {block_timestamp:}"
+    )]
+    MessageDelayTooLong {
+        not_before: Timestamp,
+        block_timestamp: Timestamp,
+    },
+    #[error(
+        "The messages of a transaction to {destination:?} must all be delayed until the same \
+        time, or not at all"
+    )]
+    MixedMessageDelays { destination: Destination },
     #[error("ExecutedBlock contains fewer oracle responses than requests")]
     MissingOracleResponseList,
     #[error("Unexpected hash for CertificateValue! Expected: {expected:?}, Actual: {actual:?}")]
@@ -517,6 +517,34 @@ where
         self.locked_blobs.get(blob_id).await
     }
 
+    /// Returns whether blobs are kept for the proposal or the locked block.
+    pub async fn has_pending_blobs(&self) -> Result<bool, ViewError> {
+        let has_blobs = |proposal: &BlockProposal| !proposal.blobs.is_empty();
+        Ok(self.proposed.get().as_ref().is_some_and(has_blobs)
+            || matches!(self.locked.get(), Some(LockedBlock::Fast(proposal)) if has_blobs(proposal))
+            || self.locked_blobs.count().await? > 0)
+    }
+
+    /// Removes the blobs of the proposal and of the locked block, and returns them. The
+    /// proposal and the locked block themselves are kept, since they determine which
+    /// blocks we can still vote for.
+    pub async fn remove_pending_blobs(&mut self) -> Result<BTreeMap<BlobId, Blob>, ViewError> {
+        let mut blobs = self
+            .locked_blobs
+            .index_values()
+            .await?
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        self.locked_blobs.clear();
+        if let Some(proposal) = self.proposed.get_mut() {
+            blobs.extend(proposal.blobs.drain(..).map(|blob| (blob.id(), blob)));
+        }
+        if let Some(LockedBlock::Fast(proposal)) = self.locked.get_mut() {
+            blobs.extend(proposal.blobs.drain(..).map(|blob| (blob.id(), blob)));
+        }
+        Ok(blobs)
+    }
+
     /// Updates `current_round` and `round_timeout` if necessary.
     ///
     /// This must be after every change to `timeout`, `locked` or `proposed`.
@@ -569,7 +597,13 @@ where
 
     /// Returns whether the signer is a valid owner and allowed to propose a block in the
     /// proposal's round.
+    ///
+    /// If the owner is a multi-signature owner, this also checks that the proposal carries
+    /// enough valid signatures by its members.
     pub fn verify_owner(&self, proposal: &BlockProposal) -> bool {
+        if !proposal.check_owner_authentication() {
+            return false;
+        }
         let owner = &proposal.owner;
         if self.ownership.get().super_owners.contains(owner) {
             return true;
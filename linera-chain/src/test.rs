@@ -162,6 +162,7 @@ impl<T: Into<Message>> MessageTestExt for T {
             authenticated_signer: None,
             grant: Amount::ZERO,
             refund_grant_to: None,
+            not_before: None,
             kind,
             index,
             message: self.into(),
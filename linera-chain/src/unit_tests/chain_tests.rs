@@ -13,17 +13,21 @@ use linera_base::{
         UserApplicationDescription,
     },
     hashed::Hashed,
-    identifiers::{ApplicationId, BytecodeId, ChainId, MessageId},
+    identifiers::{
+        AccountOwner, ApplicationId, BytecodeId, ChainId, Destination, MessageId, Owner,
+    },
     ownership::ChainOwnership,
 };
 use linera_execution::{
     committee::{Committee, Epoch, ValidatorName, ValidatorState},
-    system::{OpenChainConfig, Recipient},
+    system::{request_deadline, OpenChainConfig, Recipient, StorageDeposit},
     test_utils::{ExpectedCall, MockApplication},
-    ExecutionError, ExecutionRuntimeConfig, ExecutionRuntimeContext, Message, MessageKind,
-    Operation, ResourceControlPolicy, SystemMessage, SystemOperation, TestExecutionRuntimeContext,
+    ApplicationStorageUsage, ContractRuntime as _, ExecutionError, ExecutionRuntimeConfig,
+    ExecutionRuntimeContext, Message, MessageKind, Operation, ResourceControlPolicy, SystemMessage,
+    SystemOperation, TestExecutionRuntimeContext,
 };
 use linera_views::{
+    batch::Batch,
     context::{Context as _, MemoryContext},
     memory::TEST_MEMORY_MAX_STREAM_QUERIES,
     random::generate_test_namespace,
@@ -32,9 +36,10 @@ use linera_views::{
 
 use crate::{
     block::{Block, ConfirmedBlock},
-    data_types::{IncomingBundle, MessageAction, MessageBundle, Origin},
+    chain::check_message_delays,
+    data_types::{IncomingBundle, MessageAction, MessageBundle, Origin, OutgoingMessage},
     test::{make_child_block, make_first_block, BlockTestExt, MessageTestExt},
-    ChainError, ChainExecutionContext, ChainStateView,
+    ChainError, ChainExecutionContext, ChainStateView, MAX_MESSAGE_DELAY_SECS,
 };
 
 impl ChainStateView<MemoryContext<TestExecutionRuntimeContext>>
@@ -74,6 +79,7 @@ fn make_app_description() -> (UserApplicationDescription, Blob, Blob) {
             creation: make_admin_message_id(BlockHeight(2)),
             required_application_ids: vec![],
             parameters: vec![],
+            admin: None,
         },
         contract_blob,
         service_blob,
@@ -112,7 +118,7 @@ async fn test_block_size_limit() {
     let mut chain = ChainStateView::new(chain_id).await;
 
     // The size of the executed valid block below.
-    let maximum_executed_block_size = 676;
+    let maximum_executed_block_size = 677;
 
     // Initialize the chain.
     let mut config = make_open_chain_config();
@@ -180,6 +186,92 @@ async fn test_block_size_limit() {
     );
 }
 
+#[tokio::test]
+async fn test_block_transaction_limits() -> anyhow::Result<()> {
+    let time = Timestamp::from(0);
+    let message_id = make_admin_message_id(BlockHeight(3));
+    let chain_id = ChainId::child(message_id);
+    let mut chain = ChainStateView::new(chain_id).await;
+
+    // Initialize the chain.
+    let mut config = make_open_chain_config();
+    config.committees.insert(
+        Epoch(0),
+        Committee::new(
+            BTreeMap::from([(
+                ValidatorName(PublicKey::test_key(1)),
+                ValidatorState {
+                    network_address: PublicKey::test_key(1).to_string(),
+                    votes: 1,
+                },
+            )]),
+            ResourceControlPolicy {
+                maximum_operations_per_block: 1,
+                maximum_incoming_bundles_per_block: 1,
+                maximum_operation_argument_bytes: 3,
+                ..ResourceControlPolicy::default()
+            },
+        ),
+    );
+    chain
+        .execute_init_message(message_id, &config, time, time)
+        .await?;
+    let open_chain_bundle = IncomingBundle {
+        origin: Origin::chain(admin_id()),
+        bundle: MessageBundle {
+            certificate_hash: CryptoHash::test_hash("certificate"),
+            height: BlockHeight(1),
+            transaction_index: 0,
+            timestamp: time,
+            messages: vec![Message::System(SystemMessage::OpenChain(config))
+                .to_posted(0, MessageKind::Protected)],
+        },
+        action: MessageAction::Accept,
+    };
+    let valid_block = make_first_block(chain_id)
+        .with_incoming_bundle(open_chain_bundle.clone())
+        .with_simple_transfer(chain_id, Amount::ONE);
+
+    let invalid_block = valid_block
+        .clone()
+        .with_simple_transfer(chain_id, Amount::ONE);
+    let result = chain.execute_block(&invalid_block, time, None).await;
+    assert_matches!(
+        result,
+        Err(ChainError::TooManyOperations {
+            count: 2,
+            maximum: 1
+        })
+    );
+
+    let invalid_block = valid_block.clone().with_incoming_bundle(open_chain_bundle);
+    let result = chain.execute_block(&invalid_block, time, None).await;
+    assert_matches!(
+        result,
+        Err(ChainError::TooManyIncomingBundles {
+            count: 2,
+            maximum: 1
+        })
+    );
+
+    let mut invalid_block = valid_block.clone();
+    invalid_block.operations = vec![Operation::User {
+        application_id: ApplicationId::from(&make_app_description().0),
+        bytes: b"four".to_vec(),
+    }];
+    let result = chain.execute_block(&invalid_block, time, None).await;
+    assert_matches!(
+        result,
+        Err(ChainError::OperationArgumentsTooLarge {
+            size: 4,
+            maximum: 3
+        })
+    );
+
+    chain.execute_block(&valid_block, time, None).await?;
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_application_permissions() -> anyhow::Result<()> {
     let time = Timestamp::from(0);
@@ -276,3 +368,367 @@ async fn test_application_permissions() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_delayed_message_bundle() -> anyhow::Result<()> {
+    let time = Timestamp::from(0);
+    let delivery_time = Timestamp::from(1_000);
+    let message_id = make_admin_message_id(BlockHeight(3));
+    let chain_id = ChainId::child(message_id);
+    let mut chain = ChainStateView::new(chain_id).await;
+    chain
+        .execute_init_message(message_id, &make_open_chain_config(), time, time)
+        .await?;
+
+    let credit = Message::System(SystemMessage::Credit {
+        target: None,
+        amount: Amount::ONE,
+        source: None,
+    });
+    let mut posted_message = credit.clone().to_posted(0, MessageKind::Simple);
+    posted_message.not_before = Some(delivery_time);
+    let bundle = MessageBundle {
+        certificate_hash: CryptoHash::test_hash("certificate"),
+        height: BlockHeight(4),
+        transaction_index: 0,
+        timestamp: time,
+        messages: vec![posted_message],
+    };
+    assert!(!bundle.is_skippable());
+    let later_bundle = MessageBundle {
+        certificate_hash: CryptoHash::test_hash("later certificate"),
+        height: BlockHeight(5),
+        transaction_index: 0,
+        timestamp: time,
+        messages: vec![credit.to_posted(0, MessageKind::Tracked)],
+    };
+    let origin = Origin::chain(admin_id());
+    chain
+        .receive_message_bundle(&origin, bundle.clone(), time, true)
+        .await?;
+    chain
+        .receive_message_bundle(&origin, later_bundle.clone(), time, true)
+        .await?;
+
+    // The delayed bundle is kept aside and only becomes due at its delivery time.
+    assert_eq!(chain.unskippable_bundles.count(), 1);
+    let delayed = chain.delayed_bundles.indices().await?;
+    assert_eq!(delayed.len(), 1);
+    assert_eq!(delayed[0].due, delivery_time);
+    assert_eq!(
+        chain.oldest_due_unskippable_bundle(delivery_time).await?,
+        Some(time)
+    );
+
+    // The delayed bundle does not block the later bundle from the same origin.
+    let later_incoming_bundle = IncomingBundle {
+        origin: origin.clone(),
+        bundle: later_bundle,
+        action: MessageAction::Accept,
+    };
+    chain
+        .remove_bundles_from_inboxes(time, &[later_incoming_bundle])
+        .await?;
+    assert_eq!(chain.unskippable_bundles.count(), 0);
+    assert_eq!(chain.oldest_due_unskippable_bundle(time).await?, None);
+    assert_eq!(
+        chain.oldest_due_unskippable_bundle(delivery_time).await?,
+        Some(delivery_time)
+    );
+
+    let incoming_bundle = IncomingBundle {
+        origin,
+        bundle,
+        action: MessageAction::Accept,
+    };
+    let result = chain
+        .remove_bundles_from_inboxes(time, &[incoming_bundle.clone()])
+        .await;
+    assert_matches!(result, Err(ChainError::PrematureBundle { not_before, .. })
+        if not_before == delivery_time
+    );
+
+    chain
+        .remove_bundles_from_inboxes(delivery_time, &[incoming_bundle])
+        .await?;
+    assert_eq!(chain.delayed_bundles.count().await?, 0);
+    assert_eq!(chain.delayed_bundle_due_times.count().await?, 0);
+    assert_eq!(
+        chain.oldest_due_unskippable_bundle(delivery_time).await?,
+        None
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_expired_request_is_not_executed() -> anyhow::Result<()> {
+    let time = Timestamp::from(0);
+    let message_id = make_admin_message_id(BlockHeight(3));
+    let chain_id = ChainId::child(message_id);
+    let mut chain = ChainStateView::new(chain_id).await;
+    chain
+        .execute_init_message(message_id, &make_open_chain_config(), time, time)
+        .await?;
+
+    // The application is not even registered, so executing the request would fail.
+    let application_id = ApplicationId::default();
+    let request = Message::User {
+        application_id,
+        bytes: b"ping".to_vec(),
+    };
+    let bundle = IncomingBundle {
+        origin: Origin::chain(admin_id()),
+        bundle: MessageBundle {
+            certificate_hash: CryptoHash::test_hash("certificate"),
+            height: BlockHeight(4),
+            transaction_index: 0,
+            timestamp: time,
+            messages: vec![request.to_posted(0, MessageKind::Request)],
+        },
+        action: MessageAction::Accept,
+    };
+    let block = make_first_block(chain_id)
+        .with_incoming_bundle(bundle)
+        .with_timestamp(request_deadline(time));
+    let (outcome, _) = chain.execute_block(&block, time, None).await?;
+
+    // Instead, the sender is told that the request failed.
+    let request_id = MessageId {
+        chain_id: admin_id(),
+        height: BlockHeight(4),
+        index: 0,
+    };
+    assert_eq!(outcome.messages[0].len(), 1);
+    let response = &outcome.messages[0][0];
+    assert_eq!(response.destination, Destination::Recipient(admin_id()));
+    assert_eq!(response.kind, MessageKind::Protected);
+    assert_eq!(
+        response.message,
+        Message::System(SystemMessage::Response {
+            application_id,
+            request_id,
+            request: b"ping".to_vec(),
+            response: None,
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_message_delays() {
+    let block_timestamp = Timestamp::from(1_000);
+    let max_not_before = Timestamp::from(1_000 + MAX_MESSAGE_DELAY_SECS * 1_000_000);
+    let credit = |recipient: u32, not_before: Option<Timestamp>| OutgoingMessage {
+        destination: Destination::Recipient(ChainId::root(recipient)),
+        authenticated_signer: None,
+        grant: Amount::ZERO,
+        refund_grant_to: None,
+        not_before,
+        kind: MessageKind::Tracked,
+        message: Message::System(SystemMessage::Credit {
+            target: None,
+            amount: Amount::ONE,
+            source: None,
+        }),
+    };
+
+    // Each bundle is either delayed as a whole or not at all.
+    let messages = [
+        credit(1, Some(max_not_before)),
+        credit(1, Some(max_not_before)),
+        credit(2, None),
+    ];
+    check_message_delays(block_timestamp, &messages).unwrap();
+    let messages = [credit(1, Some(max_not_before)), credit(1, None)];
+    assert_matches!(
+        check_message_delays(block_timestamp, &messages),
+        Err(ChainError::MixedMessageDelays { destination })
+            if destination == Destination::Recipient(ChainId::root(1))
+    );
+
+    // Messages can't be delayed for too long.
+    let too_late = Timestamp::from(max_not_before.micros() + 1);
+    assert_matches!(
+        check_message_delays(block_timestamp, &[credit(1, Some(too_late))]),
+        Err(ChainError::MessageDelayTooLong { not_before, .. }) if not_before == too_late
+    );
+}
+
+#[tokio::test]
+async fn test_storage_rent() -> anyhow::Result<()> {
+    let time = Timestamp::from(0);
+    let message_id = make_admin_message_id(BlockHeight(3));
+    let chain_id = ChainId::child(message_id);
+    let mut chain = ChainStateView::new(chain_id).await;
+
+    // Create a mock application.
+    let (app_description, contract_blob, service_blob) = make_app_description();
+    let application_id = ApplicationId::from(&app_description);
+    let application = MockApplication::default();
+    let extra = &chain.context().extra();
+    extra
+        .user_contracts()
+        .insert(application_id, application.clone().into());
+    extra.add_blobs([contract_blob, service_blob]).await?;
+
+    // Initialize the chain, with a committee that only charges for storage.
+    let committee = |byte_stored| {
+        Committee::new(
+            BTreeMap::from([(
+                ValidatorName(PublicKey::test_key(1)),
+                ValidatorState {
+                    network_address: PublicKey::test_key(1).to_string(),
+                    votes: 1,
+                },
+            )]),
+            ResourceControlPolicy {
+                byte_stored,
+                ..ResourceControlPolicy::default()
+            },
+        )
+    };
+    let mut config = make_open_chain_config();
+    config
+        .committees
+        .insert(Epoch(0), committee(Amount::from_micros(1)));
+    chain
+        .execute_init_message(message_id, &config, time, time)
+        .await?;
+    // The chain balance only covers part of the storage rent; the signer pays the rest.
+    let owner = Owner::from(PublicKey::test_key(2));
+    chain
+        .execution_state
+        .system
+        .balance
+        .set(Amount::from_micros(40));
+    chain
+        .execution_state
+        .system
+        .balances
+        .insert(&AccountOwner::User(owner), Amount::ONE)?;
+    let open_chain_message = Message::System(SystemMessage::OpenChain(config));
+    let register_app_message = SystemMessage::RegisterApplications {
+        applications: vec![app_description],
+    };
+    let bundle = IncomingBundle {
+        origin: Origin::chain(admin_id()),
+        bundle: MessageBundle {
+            certificate_hash: CryptoHash::test_hash("certificate"),
+            height: BlockHeight(1),
+            transaction_index: 0,
+            timestamp: Timestamp::from(0),
+            messages: vec![
+                open_chain_message.to_posted(0, MessageKind::Protected),
+                register_app_message.to_posted(1, MessageKind::Simple),
+            ],
+        },
+        action: MessageAction::Accept,
+    };
+    let app_operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    // Storing 100 bytes is charged at the end of the block.
+    application.expect_call(ExpectedCall::execute_operation(|runtime, _, _| {
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0], vec![0; 99]);
+        runtime.write_batch(batch)?;
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+    let block = make_first_block(chain_id)
+        .with_incoming_bundle(bundle)
+        .with_operation(app_operation.clone())
+        .with_authenticated_signer(Some(owner));
+    let executed_block = chain.execute_block(&block, time, None).await?.with(block);
+    let value = Hashed::new(ConfirmedBlock::new(executed_block));
+    let system = &chain.execution_state.system;
+    assert_eq!(*system.balance.get(), Amount::ZERO);
+    assert_eq!(
+        system.balances.get(&AccountOwner::User(owner)).await?,
+        Some(Amount::ONE.try_sub(Amount::from_micros(60))?)
+    );
+    assert_eq!(
+        system.storage_deposits.get(&application_id).await?,
+        Some(BTreeMap::from([
+            (
+                None,
+                StorageDeposit {
+                    bytes: 40,
+                    amount: Amount::from_micros(40),
+                }
+            ),
+            (
+                Some(owner),
+                StorageDeposit {
+                    bytes: 60,
+                    amount: Amount::from_micros(60),
+                }
+            ),
+        ]))
+    );
+    assert_eq!(
+        chain.execution_state.storage_usage().await?,
+        vec![ApplicationStorageUsage {
+            application_id,
+            bytes_stored: 100,
+        }]
+    );
+
+    // Deleting the entry again refunds the application's deposits to the accounts that paid
+    // them, even though the price changed and the block has no signer. The deposits of other
+    // applications are not refunded.
+    let other_deposits = BTreeMap::from([(
+        None,
+        StorageDeposit {
+            bytes: 10,
+            amount: Amount::from_micros(10),
+        },
+    )]);
+    chain
+        .execution_state
+        .system
+        .storage_deposits
+        .insert(&ApplicationId::default(), other_deposits.clone())?;
+    chain
+        .execution_state
+        .system
+        .committees
+        .get_mut()
+        .insert(Epoch(0), committee(Amount::from_micros(2)));
+    application.expect_call(ExpectedCall::execute_operation(|runtime, _, _| {
+        let mut batch = Batch::new();
+        batch.delete_key(vec![0]);
+        runtime.write_batch(batch)?;
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+    let block = make_child_block(&value).with_operation(app_operation);
+    chain.execute_block(&block, time, None).await?;
+    let system = &chain.execution_state.system;
+    assert_eq!(*system.balance.get(), Amount::from_micros(40));
+    assert_eq!(
+        system.balances.get(&AccountOwner::User(owner)).await?,
+        Some(Amount::ONE)
+    );
+    assert_eq!(system.storage_deposits.get(&application_id).await?, None);
+    assert_eq!(
+        system
+            .storage_deposits
+            .get(&ApplicationId::default())
+            .await?,
+        Some(other_deposits)
+    );
+    assert_eq!(
+        chain.execution_state.storage_usage().await?,
+        vec![ApplicationStorageUsage {
+            application_id,
+            bytes_stored: 0,
+        }]
+    );
+
+    Ok(())
+}
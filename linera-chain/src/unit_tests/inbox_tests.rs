@@ -39,6 +39,17 @@ fn make_unskippable_bundle(
     bundle
 }
 
+fn make_delayed_bundle(
+    certificate_hash: CryptoHash,
+    height: u64,
+    index: u32,
+    message: impl Into<Vec<u8>>,
+) -> MessageBundle {
+    let mut bundle = make_bundle(certificate_hash, height, index, message);
+    bundle.messages[0].not_before = Some(Timestamp::from(1_000));
+    bundle
+}
+
 #[tokio::test]
 async fn test_inbox_add_then_remove_skippable() {
     let hash = CryptoHash::test_hash("1");
@@ -338,3 +349,82 @@ async fn test_inbox_add_then_remove_mixed() {
     assert_eq!(view.added_bundles.count(), 0);
     assert_eq!(view.removed_bundles.count(), 0);
 }
+
+#[tokio::test]
+async fn test_inbox_delayed() {
+    let hash = CryptoHash::test_hash("1");
+    let mut view = InboxStateView::new().await;
+    // Add a delayed bundle, followed by an unskippable one.
+    assert!(view
+        .add_bundle(make_delayed_bundle(hash, 0, 0, [0]))
+        .await
+        .unwrap());
+    assert!(view
+        .add_bundle(make_unskippable_bundle(hash, 0, 1, [1]))
+        .await
+        .unwrap());
+    assert_eq!(view.added_bundles.count(), 1);
+    assert_eq!(view.delayed_bundles.count().await.unwrap(), 1);
+    // The delayed bundle does not hold back the later one.
+    assert!(view
+        .remove_bundle(&make_unskippable_bundle(hash, 0, 1, [1]))
+        .await
+        .unwrap());
+    // Fail to remove non-matching delayed bundle.
+    assert_matches!(
+        view.remove_bundle(&make_delayed_bundle(hash, 0, 0, [1]))
+            .await,
+        Err(InboxError::UnexpectedBundle { .. })
+    );
+    assert!(view
+        .remove_bundle(&make_delayed_bundle(hash, 0, 0, [0]))
+        .await
+        .unwrap());
+    // Fail to remove the delayed bundle twice.
+    assert_matches!(
+        view.remove_bundle(&make_delayed_bundle(hash, 0, 0, [0]))
+            .await,
+        Err(InboxError::IncorrectOrder { .. })
+    );
+    // Remove a delayed bundle by anticipation.
+    assert!(!view
+        .remove_bundle(&make_delayed_bundle(hash, 1, 0, [2]))
+        .await
+        .unwrap());
+    // Fail to skip the delayed bundle that was removed by anticipation.
+    assert_matches!(
+        view.add_bundle(make_bundle(hash, 1, 1, [3])).await,
+        Err(InboxError::UnexpectedBundle { .. })
+    );
+    assert!(!view
+        .add_bundle(make_delayed_bundle(hash, 1, 0, [2]))
+        .await
+        .unwrap());
+    // Inbox is empty again.
+    assert_eq!(view.added_bundles.count(), 0);
+    assert_eq!(view.removed_bundles.count(), 0);
+    assert_eq!(view.delayed_bundles.count().await.unwrap(), 0);
+    assert_eq!(view.removed_delayed_bundles.count().await.unwrap(), 0);
+}
+
+#[test]
+fn test_cursor_custom_serialization_is_ordered() {
+    let cursors = [
+        (0, 1),
+        (0, 256),
+        (1, 0),
+        (255, 3),
+        (256, 0),
+        (u64::MAX, u32::MAX),
+    ]
+    .map(|(height, index)| Cursor {
+        height: BlockHeight(height),
+        index,
+    });
+    for pair in cursors.windows(2) {
+        let bytes0 = pair[0].to_custom_bytes().unwrap();
+        let bytes1 = pair[1].to_custom_bytes().unwrap();
+        assert!(bytes0 < bytes1);
+        assert_eq!(Cursor::from_custom_bytes(&bytes0).unwrap(), pair[0]);
+    }
+}
@@ -7,6 +7,7 @@ fn main() {
         with_storage: { any(
             feature = "scylladb",
             feature = "rocksdb",
+            feature = "redb",
             feature = "dynamodb",
             feature = "storage-service"
         ) },
@@ -24,7 +24,10 @@ use linera_core::{
     join_set_ext::{JoinSet, JoinSetExt as _},
     node::CrossChainMessageDelivery,
 };
-use linera_rpc::node_provider::{NodeOptions, NodeProvider};
+use linera_rpc::{
+    config::QuicConfig,
+    node_provider::{NodeOptions, NodeProvider},
+};
 use linera_storage::Storage;
 use thiserror_context::Context;
 use tracing::{debug, info};
@@ -47,8 +50,11 @@ use {
         Operation,
     },
     linera_rpc::{
-        config::NetworkProtocol, grpc::GrpcClient, mass_client::MassClient,
-        simple::SimpleMassClient, RpcMessage,
+        config::NetworkProtocol,
+        grpc::GrpcClient,
+        mass_client::MassClient,
+        simple::{QuicConnector, SimpleMassClient},
+        RpcMessage,
     },
     linera_sdk::abis::fungible,
     std::{collections::HashMap, iter},
@@ -88,6 +94,8 @@ where
     pub recv_timeout: Duration,
     pub retry_delay: Duration,
     pub max_retries: u32,
+    /// How the validators that use QUIC are authenticated.
+    pub quic_config: QuicConfig,
     pub chain_listeners: JoinSet,
     pub blanket_message_policy: BlanketMessagePolicy,
     pub restrict_chain_ids_to: Option<HashSet<ChainId>>,
@@ -159,7 +167,11 @@ where
             retry_delay: options.retry_delay,
             max_retries: options.max_retries,
         };
-        let node_provider = NodeProvider::new(node_options);
+        let quic_config = QuicConfig {
+            trusted_roots_path: options.quic_trusted_roots.clone(),
+            ..QuicConfig::default()
+        };
+        let node_provider = NodeProvider::new(node_options).with_quic_config(quic_config.clone());
         let delivery = CrossChainMessageDelivery::new(options.wait_for_outgoing_messages);
         let chain_ids = wallet.chain_ids();
         let name = match chain_ids.len() {
@@ -186,6 +198,7 @@ where
             recv_timeout: options.recv_timeout,
             retry_delay: options.retry_delay,
             max_retries: options.max_retries,
+            quic_config,
             chain_listeners: JoinSet::default(),
             blanket_message_policy: options.blanket_message_policy,
             restrict_chain_ids_to: options.restrict_chain_ids_to,
@@ -233,6 +246,7 @@ where
             recv_timeout: send_recv_timeout,
             retry_delay,
             max_retries,
+            quic_config: QuicConfig::default(),
             chain_listeners: JoinSet::default(),
             blanket_message_policy: BlanketMessagePolicy::Accept,
             restrict_chain_ids_to: None,
@@ -281,7 +295,7 @@ where
     }
 
     pub fn make_node_provider(&self) -> NodeProvider {
-        NodeProvider::new(self.make_node_options())
+        NodeProvider::new(self.make_node_options()).with_quic_config(self.quic_config.clone())
     }
 
     fn make_node_options(&self) -> NodeOptions {
@@ -907,6 +921,7 @@ where
                     let network = config.network.clone_with_protocol(protocol);
                     Box::new(SimpleMassClient::new(
                         network,
+                        QuicConnector::new(self.quic_config.clone()),
                         self.send_timeout,
                         self.recv_timeout,
                     ))
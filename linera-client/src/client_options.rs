@@ -11,18 +11,18 @@ use std::{
 
 use chrono::{DateTime, Utc};
 use linera_base::{
-    crypto::CryptoHash,
+    crypto::{CryptoHash, PublicKey},
     data_types::{Amount, ApplicationPermissions, TimeDelta},
     identifiers::{
         Account, ApplicationId, BytecodeId, ChainId, MessageId, Owner, UserApplicationId,
     },
-    ownership::{ChainOwnership, TimeoutConfig},
+    ownership::{ChainOwnership, MultiSigOwner, MultiSigOwnerError, TimeoutConfig},
 };
 use linera_core::{client::BlanketMessagePolicy, DEFAULT_GRACE_PERIOD};
 use linera_execution::{
     committee::ValidatorName, ResourceControlPolicy, WasmRuntime, WithWasmDefault as _,
 };
-use linera_views::store::CommonStoreConfig;
+use linera_views::{encryption::EncryptionKey, store::CommonStoreConfig};
 
 #[cfg(feature = "fs")]
 use crate::config::GenesisConfig;
@@ -55,6 +55,8 @@ pub enum Error {
     Persistence(#[from] Box<dyn std::error::Error + Send + Sync>),
     #[error("config error: {0}")]
     Config(#[from] crate::config::Error),
+    #[error("invalid multi-signature owner: {0}")]
+    MultiSigOwner(#[from] MultiSigOwnerError),
 }
 
 #[cfg(feature = "fs")]
@@ -118,6 +120,10 @@ pub struct ClientOptions {
     #[arg(long, default_value = "1000")]
     pub cache_size: usize,
 
+    /// Encrypts the values of the storage with a key derived from this passphrase.
+    #[arg(long, env = "LINERA_STORAGE_PASSPHRASE", hide_env_values = true)]
+    pub storage_passphrase: Option<String>,
+
     /// Subcommand.
     #[command(subcommand)]
     pub command: ClientCommand,
@@ -134,6 +140,11 @@ pub struct ClientOptions {
     #[arg(long, default_value = "10")]
     pub max_retries: u32,
 
+    /// A PEM file with the root certificates trusted to authenticate the validators that
+    /// use QUIC, in addition to the usual web PKI roots.
+    #[arg(long)]
+    pub quic_trusted_roots: Option<PathBuf>,
+
     /// Whether to wait until a quorum of validators has confirmed that all sent cross-chain
     /// messages have been delivered.
     #[arg(long)]
@@ -186,6 +197,11 @@ impl ClientOptions {
             max_concurrent_queries: self.max_concurrent_queries,
             max_stream_queries: self.max_stream_queries,
             cache_size: self.cache_size,
+            compression_threshold: None,
+            encryption_key: self
+                .storage_passphrase
+                .as_deref()
+                .map(EncryptionKey::from_passphrase),
         }
     }
 
@@ -219,6 +235,7 @@ impl ClientOptions {
                     Ok(StorageConfigNamespace {
                         storage_config,
                         namespace,
+                        compression_threshold: None,
                     })
                 } else {
                     Err(Error::NoStorageOption)
@@ -315,6 +332,9 @@ pub enum ClientCommand {
 
         /// Amount to transfer
         amount: Amount,
+
+        #[clap(flatten)]
+        multi_sig_config: MultiSigConfig,
     },
 
     /// Open (i.e. activate) a new chain deriving the UID from an existing one.
@@ -409,6 +429,25 @@ pub enum ClientCommand {
         account: Option<Account>,
     },
 
+    /// Simulate the execution of a block made of the given operations, then print the resources
+    /// it would use and the fees it would be charged, without proposing it.
+    ///
+    /// NOTE: Incoming messages are not included, and the chain is not synchronized with
+    /// validators first. Call `linera sync` first to do so.
+    Estimate {
+        /// The chain to execute the operations on. If not specified, the wallet's default chain
+        /// is used.
+        chain_id: Option<ChainId>,
+
+        /// The operations to estimate, as a JSON array.
+        #[arg(long, required_unless_present = "json_operations_path")]
+        json_operations: Option<String>,
+
+        /// Path to a JSON file containing the operations to estimate, as a JSON array.
+        #[arg(long)]
+        json_operations_path: Option<PathBuf>,
+    },
+
     /// (DEPRECATED) Synchronize the local state of the chain with a quorum validators, then query the
     /// local balance.
     ///
@@ -558,6 +597,18 @@ pub enum ClientCommand {
         /// Set the maximum write data per block.
         #[arg(long)]
         maximum_bytes_written_per_block: Option<u64>,
+
+        /// Set the maximum number of operations per block.
+        #[arg(long)]
+        maximum_operations_per_block: Option<u64>,
+
+        /// Set the maximum number of incoming message bundles per block.
+        #[arg(long)]
+        maximum_incoming_bundles_per_block: Option<u64>,
+
+        /// Set the maximum total size of the user operation arguments per block, in bytes.
+        #[arg(long)]
+        maximum_operation_argument_bytes: Option<u64>,
     },
 
     /// Send one transfer per chain in bulk mode
@@ -686,6 +737,18 @@ pub enum ClientCommand {
         #[arg(long)]
         maximum_bytes_written_per_block: Option<u64>,
 
+        /// Set the maximum number of operations per block.
+        #[arg(long)]
+        maximum_operations_per_block: Option<u64>,
+
+        /// Set the maximum number of incoming message bundles per block.
+        #[arg(long)]
+        maximum_incoming_bundles_per_block: Option<u64>,
+
+        /// Set the maximum total size of the user operation arguments per block, in bytes.
+        #[arg(long)]
+        maximum_operation_argument_bytes: Option<u64>,
+
         /// Force this wallet to generate keys using a PRNG and a given seed. USE FOR
         /// TESTING ONLY.
         #[arg(long)]
@@ -800,6 +863,10 @@ pub enum ClientCommand {
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
         required_application_ids: Option<Vec<UserApplicationId>>,
+
+        /// The owner allowed to upgrade the application to a new bytecode later, if any.
+        #[arg(long)]
+        admin: Option<Owner>,
     },
 
     /// Create an application, and publish the required bytecode.
@@ -833,6 +900,23 @@ pub enum ClientCommand {
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
         required_application_ids: Option<Vec<UserApplicationId>>,
+
+        /// The owner allowed to upgrade the application to a new bytecode later, if any.
+        #[arg(long)]
+        admin: Option<Owner>,
+    },
+
+    /// Upgrade an application to a new bytecode.
+    ///
+    /// The block is created on the chain that created the application, and is signed by the
+    /// chain's owner, who must be the application's admin. Chains using the application
+    /// receive the upgrade from the creator chain.
+    UpgradeApplication {
+        /// The ID of the application to upgrade.
+        application_id: UserApplicationId,
+
+        /// The bytecode ID of the new version of the application.
+        bytecode_id: BytecodeId,
     },
 
     /// Request an application from another chain, so it can be used on this one.
@@ -851,7 +935,17 @@ pub enum ClientCommand {
     },
 
     /// Create an unassigned key-pair.
-    Keygen,
+    Keygen {
+        /// Create a secp256k1 key pair, whose owner is derived from its Ethereum address,
+        /// instead of an Ed25519 one.
+        #[arg(long)]
+        secp256k1: bool,
+
+        /// Import the hex-encoded secp256k1 secret key in this file, e.g. one exported from
+        /// an Ethereum wallet, instead of generating a new one.
+        #[arg(long, requires = "secp256k1")]
+        secret_key_file: Option<PathBuf>,
+    },
 
     /// Link an owner with a key pair in the wallet to a chain that was created for that owner.
     Assign {
@@ -964,6 +1058,80 @@ pub enum DatabaseToolCommand {
         #[arg(long = "storage")]
         storage_config: String,
     },
+
+    /// Write a consistent point-in-time snapshot of a namespace into a new directory
+    ///
+    /// The namespace must not be open in another process. The storage of a running
+    /// validator is snapshotted with `linera-server admin snapshot-storage` instead.
+    #[command(name = "snapshot")]
+    Snapshot {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+
+        /// The directory to create for the snapshot.
+        #[arg(long)]
+        path: PathBuf,
+    },
+
+    /// Restore a namespace from a snapshot. The namespace must not exist.
+    #[command(name = "restore")]
+    Restore {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+
+        /// The directory containing the snapshot.
+        #[arg(long)]
+        path: PathBuf,
+    },
+
+    /// Upgrade the data of a namespace to the schema version of this binary
+    #[command(name = "migrate")]
+    Migrate {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+    },
+
+    /// Write all the key-value pairs of a namespace into a new file, in a format that
+    /// can be imported into any backend
+    #[command(name = "export")]
+    Export {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+
+        /// The file to create for the export.
+        #[arg(long)]
+        path: PathBuf,
+    },
+
+    /// Create a namespace from an export. The namespace must not exist.
+    #[command(name = "import")]
+    Import {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+
+        /// The file containing the export.
+        #[arg(long)]
+        path: PathBuf,
+    },
+
+    /// Remove the blobs of a namespace that are not used by any confirmed block, and the
+    /// blobs kept for proposals that are not confirmed yet. The validator using the
+    /// namespace should be stopped.
+    #[command(name = "collect_garbage")]
+    CollectGarbage {
+        /// Storage configuration for the blockchain history.
+        #[arg(long = "storage")]
+        storage_config: String,
+
+        /// Only report the blobs that would be removed.
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 impl DatabaseToolCommand {
@@ -975,6 +1143,12 @@ impl DatabaseToolCommand {
             DatabaseToolCommand::CheckAbsence { storage_config } => storage_config,
             DatabaseToolCommand::Initialize { storage_config } => storage_config,
             DatabaseToolCommand::ListNamespaces { storage_config } => storage_config,
+            DatabaseToolCommand::Snapshot { storage_config, .. } => storage_config,
+            DatabaseToolCommand::Restore { storage_config, .. } => storage_config,
+            DatabaseToolCommand::Migrate { storage_config } => storage_config,
+            DatabaseToolCommand::Export { storage_config, .. } => storage_config,
+            DatabaseToolCommand::Import { storage_config, .. } => storage_config,
+            DatabaseToolCommand::CollectGarbage { storage_config, .. } => storage_config,
         };
         Ok(storage_config.parse::<StorageConfigNamespace>()?)
     }
@@ -1211,6 +1385,10 @@ pub enum ProjectCommand {
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
         required_application_ids: Option<Vec<UserApplicationId>>,
+
+        /// The owner allowed to upgrade the application to a new bytecode later, if any.
+        #[arg(long)]
+        admin: Option<Owner>,
     },
 }
 
@@ -1316,6 +1494,35 @@ impl TryFrom<ChainOwnershipConfig> for ChainOwnership {
     }
 }
 
+#[derive(Debug, Clone, clap::Args)]
+pub struct MultiSigConfig {
+    /// The public keys of the members, if the sender is a multi-signature owner. The wallet
+    /// must contain the key pairs of at least the threshold of them.
+    #[arg(long, num_args(0..))]
+    multi_sig_public_keys: Vec<PublicKey>,
+
+    /// The number of member signatures required, if the sender is a multi-signature owner.
+    #[arg(long, requires = "multi_sig_public_keys")]
+    multi_sig_threshold: Option<u32>,
+}
+
+impl MultiSigConfig {
+    /// Returns the configured multi-signature owner, if any. The threshold defaults to the
+    /// number of public keys.
+    pub fn into_multi_sig_owner(self) -> Result<Option<MultiSigOwner>, Error> {
+        let MultiSigConfig {
+            multi_sig_public_keys,
+            multi_sig_threshold,
+        } = self;
+        if multi_sig_public_keys.is_empty() {
+            return Ok(None);
+        }
+        let threshold = multi_sig_threshold
+            .unwrap_or_else(|| u32::try_from(multi_sig_public_keys.len()).unwrap_or(u32::MAX));
+        Ok(Some(MultiSigOwner::new(multi_sig_public_keys, threshold)?))
+    }
+}
+
 #[derive(Debug, Clone, clap::Args)]
 pub struct ApplicationPermissionsConfig {
     /// If present, only operations from the specified applications are allowed, and
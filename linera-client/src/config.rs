@@ -16,7 +16,9 @@ use linera_execution::{
     committee::{Committee, ValidatorName, ValidatorState},
     ResourceControlPolicy,
 };
-use linera_rpc::config::{ValidatorInternalNetworkConfig, ValidatorPublicNetworkConfig};
+use linera_rpc::config::{
+    QuicConfig, ValidatorInternalNetworkConfig, ValidatorPublicNetworkConfig,
+};
 use linera_storage::Storage;
 use serde::{Deserialize, Serialize};
 
@@ -56,6 +58,9 @@ pub struct ValidatorServerConfig {
     pub validator: ValidatorConfig,
     pub key: KeyPair,
     pub internal_network: ValidatorInternalNetworkConfig,
+    /// The certificate of the proxy and the shards, if they use QUIC.
+    #[serde(default)]
+    pub quic: QuicConfig,
 }
 
 #[cfg(web)]
@@ -177,6 +182,10 @@ impl<W: Deref<Target = Wallet>> WalletState<W> {
     pub fn generate_key_pair(&mut self) -> KeyPair {
         KeyPair::generate_from(&mut self.prng)
     }
+
+    pub fn generate_secp256k1_key_pair(&mut self) -> KeyPair {
+        KeyPair::generate_secp256k1_from(&mut self.prng)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -1,11 +1,20 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{fmt, str::FromStr};
+use std::{
+    fmt,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+    str::FromStr,
+};
 
 use async_trait::async_trait;
 use linera_execution::WasmRuntime;
-use linera_storage::{DbStorage, Storage};
+use linera_storage::{
+    collect_garbage_offline, initialize_schema_version, migrate, DbStorage,
+    GarbageCollectionReport, Storage,
+};
 #[cfg(feature = "storage-service")]
 use linera_storage_service::{
     client::ServiceStoreClient,
@@ -13,19 +22,21 @@ use linera_storage_service::{
 };
 #[cfg(feature = "dynamodb")]
 use linera_views::dynamo_db::{get_config, DynamoDbStore, DynamoDbStoreConfig};
+#[cfg(feature = "redb")]
+use linera_views::redb_db::{RedbStore, RedbStoreConfig};
+#[cfg(feature = "rocksdb")]
+use linera_views::rocks_db::{RocksDbSpawnMode, RocksDbStore, RocksDbStoreConfig};
 #[cfg(with_storage)]
 use linera_views::store::LocalAdminKeyValueStore as _;
 use linera_views::{
+    export::{export_key_values, import_key_values, ExportSummary},
     memory::{MemoryStore, MemoryStoreConfig},
-    store::CommonStoreConfig,
+    store::{CommonStoreConfig, KeyValueStore},
     views::ViewError,
 };
 use tracing::error;
-#[cfg(feature = "rocksdb")]
-use {
-    linera_views::rocks_db::{PathWithGuard, RocksDbSpawnMode, RocksDbStore, RocksDbStoreConfig},
-    std::path::PathBuf,
-};
+#[cfg(any(feature = "rocksdb", feature = "redb"))]
+use {linera_views::common::PathWithGuard, std::path::PathBuf};
 #[cfg(feature = "scylladb")]
 use {
     linera_views::scylla_db::{ScyllaDbStore, ScyllaDbStoreConfig},
@@ -49,11 +60,14 @@ pub enum Error {
     Config(#[from] crate::config::Error),
 }
 
+util::impl_from_dynamic!(Error:Backend, ViewError);
 util::impl_from_dynamic!(Error:Backend, linera_views::memory::MemoryStoreError);
 #[cfg(feature = "storage-service")]
 util::impl_from_dynamic!(Error:Backend, linera_storage_service::common::ServiceStoreError);
 #[cfg(feature = "rocksdb")]
 util::impl_from_dynamic!(Error:Backend, linera_views::rocks_db::RocksDbStoreError);
+#[cfg(feature = "redb")]
+util::impl_from_dynamic!(Error:Backend, linera_views::redb_db::RedbStoreError);
 #[cfg(feature = "dynamodb")]
 util::impl_from_dynamic!(Error:Backend, linera_views::dynamo_db::DynamoDbStoreError);
 #[cfg(feature = "scylladb")]
@@ -69,6 +83,9 @@ pub enum StoreConfig {
     /// The RocksDB key value store
     #[cfg(feature = "rocksdb")]
     RocksDb(RocksDbStoreConfig, String),
+    /// The redb key value store
+    #[cfg(feature = "redb")]
+    Redb(RedbStoreConfig, String),
     /// The DynamoDb key value store
     #[cfg(feature = "dynamodb")]
     DynamoDb(DynamoDbStoreConfig, String),
@@ -97,6 +114,12 @@ pub enum StorageConfig {
         /// Whether to use `block_in_place` or `spawn_blocking`.
         spawn_mode: RocksDbSpawnMode,
     },
+    /// The redb description
+    #[cfg(feature = "redb")]
+    Redb {
+        /// The path used
+        path: PathBuf,
+    },
     /// The DynamoDB description
     #[cfg(feature = "dynamodb")]
     DynamoDb {
@@ -119,6 +142,45 @@ impl StorageConfig {
     pub fn is_rocks_db(&self) -> bool {
         matches!(self, StorageConfig::RocksDb { .. })
     }
+
+    #[cfg(feature = "redb")]
+    pub fn is_redb(&self) -> bool {
+        matches!(self, StorageConfig::Redb { .. })
+    }
+
+    /// Whether the values of this storage can be compressed.
+    pub fn supports_compression(&self) -> bool {
+        match self {
+            #[cfg(feature = "storage-service")]
+            StorageConfig::Service { .. } => false,
+            StorageConfig::Memory => false,
+            #[cfg(feature = "rocksdb")]
+            StorageConfig::RocksDb { .. } => true,
+            #[cfg(feature = "redb")]
+            StorageConfig::Redb { .. } => true,
+            #[cfg(feature = "dynamodb")]
+            StorageConfig::DynamoDb { .. } => true,
+            #[cfg(feature = "scylladb")]
+            StorageConfig::ScyllaDb { .. } => true,
+        }
+    }
+
+    /// Whether the values of this storage can be encrypted.
+    pub fn supports_encryption(&self) -> bool {
+        match self {
+            #[cfg(feature = "storage-service")]
+            StorageConfig::Service { .. } => false,
+            StorageConfig::Memory => false,
+            #[cfg(feature = "rocksdb")]
+            StorageConfig::RocksDb { .. } => true,
+            #[cfg(feature = "redb")]
+            StorageConfig::Redb { .. } => true,
+            #[cfg(feature = "dynamodb")]
+            StorageConfig::DynamoDb { .. } => true,
+            #[cfg(feature = "scylladb")]
+            StorageConfig::ScyllaDb { .. } => true,
+        }
+    }
 }
 
 /// The description of a storage implementation.
@@ -129,14 +191,21 @@ pub struct StorageConfigNamespace {
     pub storage_config: StorageConfig,
     /// The namespace used
     pub namespace: String,
+    /// The size above which values are compressed, if any
+    pub compression_threshold: Option<usize>,
 }
 
+const ZSTD: &str = "zstd:";
+/// The size above which values are compressed if the storage specification does not say.
+const DEFAULT_COMPRESSION_THRESHOLD: usize = 1024;
 const MEMORY: &str = "memory";
 const MEMORY_EXT: &str = "memory:";
 #[cfg(feature = "storage-service")]
 const STORAGE_SERVICE: &str = "service:";
 #[cfg(feature = "rocksdb")]
 const ROCKS_DB: &str = "rocksdb:";
+#[cfg(feature = "redb")]
+const REDB: &str = "redb:";
 #[cfg(feature = "dynamodb")]
 const DYNAMO_DB: &str = "dynamodb:";
 #[cfg(feature = "scylladb")]
@@ -146,12 +215,35 @@ impl FromStr for StorageConfigNamespace {
     type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if let Some(s) = input.strip_prefix(ZSTD) {
+            let (compression_threshold, s) = match s.split_once(':') {
+                Some((threshold, rest)) if threshold.bytes().all(|b| b.is_ascii_digit()) => {
+                    let threshold = threshold.parse::<usize>().map_err(|_| {
+                        Error::Format(format!("Failed to parse {threshold} as a threshold"))
+                    })?;
+                    (threshold, rest)
+                }
+                _ => (DEFAULT_COMPRESSION_THRESHOLD, s),
+            };
+            let mut config = Self::from_str(s)?;
+            if config.compression_threshold.is_some() {
+                return Err(Error::Format("The compression is specified twice".into()));
+            }
+            if !config.storage_config.supports_compression() {
+                return Err(Error::Format(format!(
+                    "Compression is not supported by the storage {s}"
+                )));
+            }
+            config.compression_threshold = Some(compression_threshold);
+            return Ok(config);
+        }
         if input == MEMORY {
             let namespace = DEFAULT_NAMESPACE.to_string();
             let storage_config = StorageConfig::Memory;
             return Ok(StorageConfigNamespace {
                 storage_config,
                 namespace,
+                compression_threshold: None,
             });
         }
         if let Some(s) = input.strip_prefix(MEMORY_EXT) {
@@ -160,6 +252,7 @@ impl FromStr for StorageConfigNamespace {
             return Ok(StorageConfigNamespace {
                 storage_config,
                 namespace,
+                compression_threshold: None,
             });
         }
         #[cfg(feature = "storage-service")]
@@ -192,6 +285,7 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
             return Ok(StorageConfigNamespace {
                 storage_config,
                 namespace,
+                compression_threshold: None,
             });
         }
         #[cfg(feature = "rocksdb")]
@@ -210,6 +304,7 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
                 return Ok(StorageConfigNamespace {
                     storage_config,
                     namespace,
+                    compression_threshold: None,
                 });
             }
             if parts.len() == 3 {
@@ -228,10 +323,32 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
                 return Ok(StorageConfigNamespace {
                     storage_config,
                     namespace,
+                    compression_threshold: None,
                 });
             }
             return Err(Error::Format("We should have one or three parts".into()));
         }
+        #[cfg(feature = "redb")]
+        if let Some(s) = input.strip_prefix(REDB) {
+            let parts = s.split(':').collect::<Vec<_>>();
+            let (path, namespace) = match parts.as_slice() {
+                [path] if !path.is_empty() => (path, DEFAULT_NAMESPACE),
+                [path, namespace] if !path.is_empty() => (path, *namespace),
+                _ => {
+                    return Err(Error::Format(
+                        "For redb, the formatting has to be redb:directory or redb:directory:namespace".into(),
+                    ));
+                }
+            };
+            let path = path.to_string().into();
+            let namespace = namespace.to_string();
+            let storage_config = StorageConfig::Redb { path };
+            return Ok(StorageConfigNamespace {
+                storage_config,
+                namespace,
+                compression_threshold: None,
+            });
+        }
         #[cfg(feature = "dynamodb")]
         if let Some(s) = input.strip_prefix(DYNAMO_DB) {
             let mut parts = s.splitn(2, ':');
@@ -257,6 +374,7 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
             return Ok(StorageConfigNamespace {
                 storage_config,
                 namespace,
+                compression_threshold: None,
             });
         }
         #[cfg(feature = "scylladb")]
@@ -312,6 +430,7 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
             return Ok(StorageConfigNamespace {
                 storage_config,
                 namespace,
+                compression_threshold: None,
             });
         }
         error!("available storage: memory");
@@ -319,6 +438,8 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
         error!("Also available is linera-storage-service");
         #[cfg(feature = "rocksdb")]
         error!("Also available is RocksDB");
+        #[cfg(feature = "redb")]
+        error!("Also available is redb");
         #[cfg(feature = "dynamodb")]
         error!("Also available is DynamoDB");
         #[cfg(feature = "scylladb")]
@@ -334,6 +455,15 @@ impl StorageConfigNamespace {
         common_config: CommonStoreConfig,
     ) -> Result<StoreConfig, Error> {
         let namespace = self.namespace.clone();
+        if common_config.encryption_key.is_some() && !self.storage_config.supports_encryption() {
+            return Err(Error::InvalidOperation(format!(
+                "Encryption is not supported by the storage {self}"
+            )));
+        }
+        let common_config = CommonStoreConfig {
+            compression_threshold: self.compression_threshold,
+            ..common_config
+        };
         match &self.storage_config {
             #[cfg(feature = "storage-service")]
             StorageConfig::Service { endpoint } => {
@@ -361,6 +491,13 @@ impl StorageConfigNamespace {
                 let config = RocksDbStoreConfig::new(*spawn_mode, path_with_guard, common_config);
                 Ok(StoreConfig::RocksDb(config, namespace))
             }
+            #[cfg(feature = "redb")]
+            StorageConfig::Redb { path } => {
+                let path_buf = path.to_path_buf();
+                let path_with_guard = PathWithGuard::new(path_buf);
+                let config = RedbStoreConfig::new(path_with_guard, common_config);
+                Ok(StoreConfig::Redb(config, namespace))
+            }
             #[cfg(feature = "dynamodb")]
             StorageConfig::DynamoDb { use_localstack } => {
                 let aws_config = get_config(*use_localstack).await?;
@@ -379,6 +516,9 @@ impl StorageConfigNamespace {
 impl fmt::Display for StorageConfigNamespace {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let namespace = &self.namespace;
+        if let Some(compression_threshold) = self.compression_threshold {
+            write!(f, "{ZSTD}{compression_threshold}:")?;
+        }
         match &self.storage_config {
             #[cfg(feature = "storage-service")]
             StorageConfig::Service { endpoint } => {
@@ -395,6 +535,10 @@ impl fmt::Display for StorageConfigNamespace {
                 };
                 write!(f, "rocksdb:{}:{}:{}", path.display(), spawn_mode, namespace)
             }
+            #[cfg(feature = "redb")]
+            StorageConfig::Redb { path } => {
+                write!(f, "redb:{}:{}", path.display(), namespace)
+            }
             #[cfg(feature = "dynamodb")]
             StorageConfig::DynamoDb { use_localstack } => match use_localstack {
                 true => write!(f, "dynamodb:{}:localstack", namespace),
@@ -426,6 +570,11 @@ impl StoreConfig {
                 RocksDbStore::delete_all(&config).await?;
                 Ok(())
             }
+            #[cfg(feature = "redb")]
+            StoreConfig::Redb(config, _namespace) => {
+                RedbStore::delete_all(&config).await?;
+                Ok(())
+            }
             #[cfg(feature = "dynamodb")]
             StoreConfig::DynamoDb(config, _namespace) => {
                 DynamoDbStore::delete_all(&config).await?;
@@ -456,6 +605,11 @@ impl StoreConfig {
                 RocksDbStore::delete(&config, &namespace).await?;
                 Ok(())
             }
+            #[cfg(feature = "redb")]
+            StoreConfig::Redb(config, namespace) => {
+                RedbStore::delete(&config, &namespace).await?;
+                Ok(())
+            }
             #[cfg(feature = "dynamodb")]
             StoreConfig::DynamoDb(config, namespace) => {
                 DynamoDbStore::delete(&config, &namespace).await?;
@@ -484,6 +638,10 @@ impl StoreConfig {
             StoreConfig::RocksDb(config, namespace) => {
                 Ok(RocksDbStore::exists(&config, &namespace).await?)
             }
+            #[cfg(feature = "redb")]
+            StoreConfig::Redb(config, namespace) => {
+                Ok(RedbStore::exists(&config, &namespace).await?)
+            }
             #[cfg(feature = "dynamodb")]
             StoreConfig::DynamoDb(config, namespace) => {
                 Ok(DynamoDbStore::exists(&config, &namespace).await?)
@@ -495,34 +653,9 @@ impl StoreConfig {
         }
     }
 
-    /// Initializes the database
+    /// Initializes the database, recording the current schema version in a new table
     pub async fn initialize(self) -> Result<(), ViewError> {
-        match self {
-            StoreConfig::Memory(_, _) => Err(ViewError::StoreError {
-                backend: "memory".to_string(),
-                error: "initialize does not make sense for memory storage".to_string(),
-            }),
-            #[cfg(feature = "storage-service")]
-            StoreConfig::Service(config, namespace) => {
-                ServiceStoreClient::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
-                Ok(())
-            }
-            #[cfg(feature = "rocksdb")]
-            StoreConfig::RocksDb(config, namespace) => {
-                RocksDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
-                Ok(())
-            }
-            #[cfg(feature = "dynamodb")]
-            StoreConfig::DynamoDb(config, namespace) => {
-                DynamoDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
-                Ok(())
-            }
-            #[cfg(feature = "scylladb")]
-            StoreConfig::ScyllaDb(config, namespace) => {
-                ScyllaDbStore::maybe_create_and_connect(&config, &namespace, ROOT_KEY).await?;
-                Ok(())
-            }
-        }
+        self.run_with_store("initialize", InitializeNamespace).await
     }
 
     /// Lists all the namespaces of the storage
@@ -542,6 +675,11 @@ impl StoreConfig {
                 let tables = RocksDbStore::list_all(&config).await?;
                 Ok(tables)
             }
+            #[cfg(feature = "redb")]
+            StoreConfig::Redb(config, _namespace) => {
+                let tables = RedbStore::list_all(&config).await?;
+                Ok(tables)
+            }
             #[cfg(feature = "dynamodb")]
             StoreConfig::DynamoDb(config, _namespace) => {
                 let tables = DynamoDbStore::list_all(&config).await?;
@@ -554,6 +692,225 @@ impl StoreConfig {
             }
         }
     }
+
+    /// Writes a point-in-time snapshot of one table of the database into the directory `path`.
+    /// This connects to the table, so a running validator, which holds its database, must
+    /// be snapshotted through its admin service instead.
+    pub async fn snapshot(self, path: &Path) -> Result<(), ViewError> {
+        self.run_with_store("snapshot", SnapshotNamespace(path))
+            .await
+    }
+
+    /// Restores one table of the database from the snapshot in the directory `path`
+    pub async fn restore(self, path: &Path) -> Result<(), ViewError> {
+        self.run_with_store("restore", RestoreNamespace(path)).await
+    }
+
+    /// Upgrades the data of one table of the database to the current schema version,
+    /// returning the version it had before
+    pub async fn migrate(self) -> Result<u32, ViewError> {
+        self.run_with_store("migrate", MigrateNamespace).await
+    }
+
+    /// Writes the key-value pairs of one table of the database into the new file `path`,
+    /// in a format that can be imported into any backend
+    pub async fn export(self, path: &Path) -> Result<ExportSummary, ViewError> {
+        self.run_with_store("export", ExportNamespace(path)).await
+    }
+
+    /// Creates one table of the database with the key-value pairs of the export in the
+    /// file `path`
+    pub async fn import(self, path: &Path) -> Result<ExportSummary, ViewError> {
+        self.run_with_store("import", ImportNamespace(path)).await
+    }
+
+    /// Removes the blobs of one table of the database that are not used by any confirmed
+    /// block, or only reports them if `dry_run` is set. The validator should be stopped.
+    pub async fn collect_garbage(
+        self,
+        dry_run: bool,
+    ) -> Result<GarbageCollectionReport, ViewError> {
+        self.run_with_store("collect_garbage", CollectGarbage { dry_run })
+            .await
+    }
+
+    /// Runs `job` on one table of the database, with the key-value store of its backend.
+    /// `operation` names the job in the error returned for the memory storage.
+    #[allow(unused_variables)]
+    async fn run_with_store<Job>(self, operation: &str, job: Job) -> Result<Job::Output, ViewError>
+    where
+        Job: RunnableWithStore,
+    {
+        match self {
+            StoreConfig::Memory(_, _) => Err(ViewError::StoreError {
+                backend: "memory".to_string(),
+                error: format!("{operation} does not make sense for memory storage"),
+            }),
+            #[cfg(feature = "storage-service")]
+            StoreConfig::Service(config, namespace) => {
+                job.run::<ServiceStoreClient>(config, &namespace).await
+            }
+            #[cfg(feature = "rocksdb")]
+            StoreConfig::RocksDb(config, namespace) => {
+                job.run::<RocksDbStore>(config, &namespace).await
+            }
+            #[cfg(feature = "redb")]
+            StoreConfig::Redb(config, namespace) => job.run::<RedbStore>(config, &namespace).await,
+            #[cfg(feature = "dynamodb")]
+            StoreConfig::DynamoDb(config, namespace) => {
+                job.run::<DynamoDbStore>(config, &namespace).await
+            }
+            #[cfg(feature = "scylladb")]
+            StoreConfig::ScyllaDb(config, namespace) => {
+                job.run::<ScyllaDbStore>(config, &namespace).await
+            }
+        }
+    }
+}
+
+/// A job on one table of the database, run with the key-value store of any backend by
+/// [`StoreConfig::run_with_store`].
+#[async_trait]
+trait RunnableWithStore {
+    type Output;
+
+    async fn run<S>(self, config: S::Config, namespace: &str) -> Result<Self::Output, ViewError>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync;
+}
+
+struct InitializeNamespace;
+
+#[async_trait]
+impl RunnableWithStore for InitializeNamespace {
+    type Output = ();
+
+    async fn run<S>(self, config: S::Config, namespace: &str) -> Result<(), ViewError>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync,
+    {
+        let store = S::maybe_create_and_connect(&config, namespace, ROOT_KEY).await?;
+        initialize_schema_version(&store).await
+    }
+}
+
+struct SnapshotNamespace<'a>(&'a Path);
+
+#[async_trait]
+impl RunnableWithStore for SnapshotNamespace<'_> {
+    type Output = ();
+
+    async fn run<S>(self, config: S::Config, namespace: &str) -> Result<(), ViewError>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync,
+    {
+        if !S::exists(&config, namespace).await? {
+            return Err(ViewError::StoreError {
+                backend: S::get_name(),
+                error: format!("the namespace {namespace} does not exist"),
+            });
+        }
+        let store = S::connect(&config, namespace, ROOT_KEY).await?;
+        store.snapshot(self.0).await?;
+        Ok(())
+    }
+}
+
+struct RestoreNamespace<'a>(&'a Path);
+
+#[async_trait]
+impl RunnableWithStore for RestoreNamespace<'_> {
+    type Output = ();
+
+    async fn run<S>(self, config: S::Config, namespace: &str) -> Result<(), ViewError>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync,
+    {
+        S::restore(&config, namespace, self.0).await?;
+        Ok(())
+    }
+}
+
+struct MigrateNamespace;
+
+#[async_trait]
+impl RunnableWithStore for MigrateNamespace {
+    type Output = u32;
+
+    async fn run<S>(self, config: S::Config, namespace: &str) -> Result<u32, ViewError>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync,
+    {
+        let store = S::connect(&config, namespace, ROOT_KEY).await?;
+        migrate(&store).await
+    }
+}
+
+struct ExportNamespace<'a>(&'a Path);
+
+#[async_trait]
+impl RunnableWithStore for ExportNamespace<'_> {
+    type Output = ExportSummary;
+
+    async fn run<S>(self, config: S::Config, namespace: &str) -> Result<ExportSummary, ViewError>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync,
+    {
+        let store = S::connect(&config, namespace, ROOT_KEY).await?;
+        let writer = BufWriter::new(File::create_new(self.0)?);
+        export_key_values(&store, writer).await
+    }
+}
+
+struct ImportNamespace<'a>(&'a Path);
+
+#[async_trait]
+impl RunnableWithStore for ImportNamespace<'_> {
+    type Output = ExportSummary;
+
+    async fn run<S>(self, config: S::Config, namespace: &str) -> Result<ExportSummary, ViewError>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync,
+    {
+        let reader = BufReader::new(File::open(self.0)?);
+        if S::exists(&config, namespace).await? {
+            return Err(ViewError::StoreError {
+                backend: S::get_name(),
+                error: format!("the namespace {namespace} already exists"),
+            });
+        }
+        S::create(&config, namespace).await?;
+        let store = S::connect(&config, namespace, ROOT_KEY).await?;
+        import_key_values(&store, reader).await
+    }
+}
+
+struct CollectGarbage {
+    dry_run: bool,
+}
+
+#[async_trait]
+impl RunnableWithStore for CollectGarbage {
+    type Output = GarbageCollectionReport;
+
+    async fn run<S>(
+        self,
+        config: S::Config,
+        namespace: &str,
+    ) -> Result<GarbageCollectionReport, ViewError>
+    where
+        S: KeyValueStore + Clone + Send + Sync + 'static,
+        S::Error: Send + Sync,
+    {
+        collect_garbage_offline::<S>(config, namespace, ROOT_KEY, self.dry_run).await
+    }
 }
 
 #[async_trait]
@@ -582,9 +939,13 @@ where
     match config {
         StoreConfig::Memory(config, namespace) => {
             let store_config = MemoryStoreConfig::new(config.common_config.max_stream_queries);
-            let mut storage =
-                DbStorage::<MemoryStore, _>::new(store_config, &namespace, ROOT_KEY, wasm_runtime)
-                    .await?;
+            let mut storage = DbStorage::<MemoryStore, _>::initialize(
+                store_config,
+                &namespace,
+                ROOT_KEY,
+                wasm_runtime,
+            )
+            .await?;
             genesis_config.initialize_storage(&mut storage).await?;
             Ok(job.run(storage).await)
         }
@@ -602,6 +963,12 @@ where
                     .await?;
             Ok(job.run(storage).await)
         }
+        #[cfg(feature = "redb")]
+        StoreConfig::Redb(config, namespace) => {
+            let storage =
+                DbStorage::<RedbStore, _>::new(config, &namespace, ROOT_KEY, wasm_runtime).await?;
+            Ok(job.run(storage).await)
+        }
         #[cfg(feature = "dynamodb")]
         StoreConfig::DynamoDb(config, namespace) => {
             let storage =
@@ -652,6 +1019,14 @@ pub async fn full_initialize_storage(
             .await?;
             Ok(genesis_config.initialize_storage(&mut storage).await?)
         }
+        #[cfg(feature = "redb")]
+        StoreConfig::Redb(config, namespace) => {
+            let wasm_runtime = None;
+            let mut storage =
+                DbStorage::<RedbStore, _>::initialize(config, &namespace, ROOT_KEY, wasm_runtime)
+                    .await?;
+            Ok(genesis_config.initialize_storage(&mut storage).await?)
+        }
         #[cfg(feature = "dynamodb")]
         StoreConfig::DynamoDb(config, namespace) => {
             let wasm_runtime = None;
@@ -685,23 +1060,27 @@ fn test_memory_storage_config_from_str() {
         StorageConfigNamespace::from_str("memory:").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::Memory,
-            namespace: "".into()
+            namespace: "".into(),
+            compression_threshold: None,
         }
     );
     assert_eq!(
         StorageConfigNamespace::from_str("memory").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::Memory,
-            namespace: DEFAULT_NAMESPACE.into()
+            namespace: DEFAULT_NAMESPACE.into(),
+            compression_threshold: None,
         }
     );
     assert_eq!(
         StorageConfigNamespace::from_str("memory:table_linera").unwrap(),
         StorageConfigNamespace {
             storage_config: StorageConfig::Memory,
-            namespace: DEFAULT_NAMESPACE.into()
+            namespace: DEFAULT_NAMESPACE.into(),
+            compression_threshold: None,
         }
     );
+    assert!(StorageConfigNamespace::from_str("zstd:memory").is_err());
 }
 
 #[cfg(feature = "storage-service")]
@@ -713,7 +1092,8 @@ fn test_shared_store_config_from_str() {
             storage_config: StorageConfig::Service {
                 endpoint: "127.0.0.1:8942".to_string()
             },
-            namespace: "linera".into()
+            namespace: "linera".into(),
+            compression_threshold: None,
         }
     );
     assert!(StorageConfigNamespace::from_str("service:tcp:127.0.0.1:8942").is_err());
@@ -730,7 +1110,8 @@ fn test_rocks_db_storage_config_from_str() {
                 path: "foo.db".into(),
                 spawn_mode: RocksDbSpawnMode::BlockInPlace,
             },
-            namespace: "chosen_namespace".into()
+            namespace: "chosen_namespace".into(),
+            compression_threshold: None,
         }
     );
     assert!(StorageConfigNamespace::from_str("rocksdb_foo.db").is_err());
@@ -741,9 +1122,74 @@ fn test_rocks_db_storage_config_from_str() {
                 path: "foo.db".into(),
                 spawn_mode: RocksDbSpawnMode::SpawnBlocking,
             },
-            namespace: DEFAULT_NAMESPACE.to_string()
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            compression_threshold: None,
+        }
+    );
+}
+
+#[cfg(feature = "redb")]
+#[test]
+fn test_redb_storage_config_from_str() {
+    assert_eq!(
+        StorageConfigNamespace::from_str("redb:foo.db:chosen_namespace").unwrap(),
+        StorageConfigNamespace {
+            storage_config: StorageConfig::Redb {
+                path: "foo.db".into(),
+            },
+            namespace: "chosen_namespace".into(),
+            compression_threshold: None,
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str("redb:foo.db").unwrap(),
+        StorageConfigNamespace {
+            storage_config: StorageConfig::Redb {
+                path: "foo.db".into(),
+            },
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            compression_threshold: None,
+        }
+    );
+    assert!(StorageConfigNamespace::from_str("redb:").is_err());
+    assert!(StorageConfigNamespace::from_str("redb:foo.db:a:b").is_err());
+}
+
+#[cfg(feature = "rocksdb")]
+#[test]
+fn test_compressed_storage_config_from_str() {
+    let config = StorageConfigNamespace::from_str(
+        "zstd:4096:rocksdb:foo.db:block_in_place:chosen_namespace",
+    )
+    .unwrap();
+    assert_eq!(
+        config,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::RocksDb {
+                path: "foo.db".into(),
+                spawn_mode: RocksDbSpawnMode::BlockInPlace,
+            },
+            namespace: "chosen_namespace".into(),
+            compression_threshold: Some(4096),
+        }
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str(&config.to_string()).unwrap(),
+        config
+    );
+    assert_eq!(
+        StorageConfigNamespace::from_str("zstd:rocksdb:foo.db").unwrap(),
+        StorageConfigNamespace {
+            storage_config: StorageConfig::RocksDb {
+                path: "foo.db".into(),
+                spawn_mode: RocksDbSpawnMode::SpawnBlocking,
+            },
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            compression_threshold: Some(DEFAULT_COMPRESSION_THRESHOLD),
         }
     );
+    assert!(StorageConfigNamespace::from_str("zstd:1:zstd:2:rocksdb:foo.db").is_err());
+    assert!(StorageConfigNamespace::from_str("zstd:4096:memory").is_err());
 }
 
 #[cfg(feature = "dynamodb")]
@@ -755,7 +1201,8 @@ fn test_aws_storage_config_from_str() {
             storage_config: StorageConfig::DynamoDb {
                 use_localstack: false
             },
-            namespace: "table".to_string()
+            namespace: "table".to_string(),
+            compression_threshold: None,
         }
     );
     assert_eq!(
@@ -764,7 +1211,8 @@ fn test_aws_storage_config_from_str() {
             storage_config: StorageConfig::DynamoDb {
                 use_localstack: false
             },
-            namespace: "table".to_string()
+            namespace: "table".to_string(),
+            compression_threshold: None,
         }
     );
     assert_eq!(
@@ -773,7 +1221,8 @@ fn test_aws_storage_config_from_str() {
             storage_config: StorageConfig::DynamoDb {
                 use_localstack: true
             },
-            namespace: "table".to_string()
+            namespace: "table".to_string(),
+            compression_threshold: None,
         }
     );
     assert!(StorageConfigNamespace::from_str("dynamodb").is_err());
@@ -791,7 +1240,8 @@ fn test_scylla_db_storage_config_from_str() {
             storage_config: StorageConfig::ScyllaDb {
                 uri: "localhost:9042".to_string()
             },
-            namespace: DEFAULT_NAMESPACE.to_string()
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            compression_threshold: None,
         }
     );
     assert_eq!(
@@ -801,7 +1251,8 @@ fn test_scylla_db_storage_config_from_str() {
             storage_config: StorageConfig::ScyllaDb {
                 uri: "db_hostname:230".to_string()
             },
-            namespace: "table_other_storage".to_string()
+            namespace: "table_other_storage".to_string(),
+            compression_threshold: None,
         }
     );
     assert_eq!(
@@ -810,7 +1261,8 @@ fn test_scylla_db_storage_config_from_str() {
             storage_config: StorageConfig::ScyllaDb {
                 uri: "db_hostname:230".to_string()
             },
-            namespace: DEFAULT_NAMESPACE.to_string()
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            compression_threshold: None,
         }
     );
     assert!(StorageConfigNamespace::from_str("scylladb:-10").is_err());
@@ -19,7 +19,7 @@ use linera_base::{
 use linera_chain::{
     data_types::{BlockProposal, ExecutedBlock, MessageBundle, Origin, ProposedBlock, Target},
     types::{Block, ConfirmedBlockCertificate, TimeoutCertificate, ValidatedBlockCertificate},
-    ChainStateView,
+    ChainProgress, ChainStateView,
 };
 use linera_execution::{
     committee::{Epoch, ValidatorName},
@@ -31,7 +31,7 @@ use tracing::{instrument, trace, warn};
 
 use super::{config::ChainWorkerConfig, state::ChainWorkerState, DeliveryNotifier};
 use crate::{
-    data_types::{ChainInfoQuery, ChainInfoResponse},
+    data_types::{ChainInfoQuery, ChainInfoResponse, OperationsEstimate},
     value_cache::ValueCache,
     worker::{NetworkActions, WorkerError},
 };
@@ -89,6 +89,13 @@ where
         callback: oneshot::Sender<Result<(ExecutedBlock, ChainInfoResponse), WorkerError>>,
     },
 
+    /// Execute a block, discarding any changes to the chain state, and return its estimated cost.
+    EstimateBlockExecution {
+        block: ProposedBlock,
+        #[debug(skip)]
+        callback: oneshot::Sender<Result<OperationsEstimate, WorkerError>>,
+    },
+
     /// Process a leader timeout issued for this multi-owner chain.
     ProcessTimeout {
         certificate: TimeoutCertificate,
@@ -160,6 +167,14 @@ where
         new_trackers: BTreeMap<ValidatorName, u64>,
         callback: oneshot::Sender<Result<(), WorkerError>>,
     },
+
+    /// Remove the blobs kept for blocks that are not confirmed yet, if the chain made no
+    /// progress since `progress`.
+    RemovePendingBlobs {
+        progress: ChainProgress,
+        #[debug(skip)]
+        callback: oneshot::Sender<Result<Vec<Blob>, WorkerError>>,
+    },
 }
 
 /// The actor worker type.
@@ -289,6 +304,9 @@ where
                 ChainWorkerRequest::StageBlockExecution { block, callback } => callback
                     .send(self.worker.stage_block_execution(block).await)
                     .is_ok(),
+                ChainWorkerRequest::EstimateBlockExecution { block, callback } => callback
+                    .send(self.worker.estimate_block_execution(block).await)
+                    .is_ok(),
                 ChainWorkerRequest::ProcessTimeout {
                     certificate,
                     callback,
@@ -354,6 +372,9 @@ where
                             .await,
                     )
                     .is_ok(),
+                ChainWorkerRequest::RemovePendingBlobs { progress, callback } => callback
+                    .send(self.worker.remove_pending_blobs(progress).await)
+                    .is_ok(),
             };
 
             if !responded {
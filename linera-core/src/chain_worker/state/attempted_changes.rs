@@ -17,7 +17,7 @@ use linera_chain::{
     },
     manager,
     types::{ConfirmedBlockCertificate, TimeoutCertificate, ValidatedBlockCertificate},
-    ChainStateView,
+    ChainProgress, ChainStateView,
 };
 use linera_execution::{
     committee::{Committee, Epoch, ValidatorName},
@@ -140,6 +140,12 @@ where
         } else {
             BTreeMap::new()
         };
+        if !blobs.is_empty() || !proposal.blobs.is_empty() {
+            self.state
+                .storage
+                .write_pending_blobs_chain(self.state.chain_id())
+                .await?;
+        }
         let key_pair = self.state.config.key_pair();
         let manager = &mut self.state.chain.manager;
         match manager.create_vote(proposal, executed_block, key_pair, local_time, blobs)? {
@@ -216,6 +222,12 @@ where
             .maybe_get_required_blobs(required_blob_ids, &[])
             .await?;
         let missing_blob_ids = super::missing_blob_ids(&maybe_blobs);
+        if !maybe_blobs.is_empty() {
+            self.state
+                .storage
+                .write_pending_blobs_chain(self.state.chain_id())
+                .await?;
+        }
         if !missing_blob_ids.is_empty() {
             let chain = &mut self.state.chain;
             let pending_validated_block = chain.pending_validated_block.get_mut();
@@ -564,17 +576,13 @@ where
     /// Votes for falling back to a public chain.
     pub(super) async fn vote_for_fallback(&mut self) -> Result<(), WorkerError> {
         let chain = &mut self.state.chain;
-        if let (Some(epoch), Some(entry)) = (
+        let local_time = self.state.storage.clock().current_time();
+        if let (Some(epoch), Some(seen)) = (
             chain.execution_state.system.epoch.get(),
-            chain.unskippable_bundles.front().await?,
+            chain.oldest_due_unskippable_bundle(local_time).await?,
         ) {
             let ownership = chain.execution_state.system.ownership.get();
-            let elapsed = self
-                .state
-                .storage
-                .clock()
-                .current_time()
-                .delta_since(entry.seen);
+            let elapsed = local_time.delta_since(seen);
             if elapsed >= ownership.timeout_config.fallback_duration {
                 let chain_id = chain.chain_id();
                 let height = chain.tip_state.get().next_block_height;
@@ -608,6 +616,22 @@ where
         ))
     }
 
+    /// Removes the blobs kept for blocks that are not confirmed yet, if the chain made no
+    /// progress since `progress`.
+    pub(super) async fn remove_pending_blobs(
+        &mut self,
+        progress: ChainProgress,
+    ) -> Result<Vec<Blob>, WorkerError> {
+        if self.state.chain.progress() != progress {
+            return Ok(Vec::new());
+        }
+        let blobs = self.state.chain.remove_pending_blobs().await?;
+        if !blobs.is_empty() {
+            self.save().await?;
+        }
+        Ok(blobs)
+    }
+
     /// Stores the chain state in persistent storage.
     ///
     /// Waits until the [`ChainStateView`] is no longer shared before persisting the changes.
@@ -23,7 +23,7 @@ use linera_chain::{
         BlockProposal, ExecutedBlock, Medium, MessageBundle, Origin, ProposedBlock, Target,
     },
     types::{Block, ConfirmedBlockCertificate, TimeoutCertificate, ValidatedBlockCertificate},
-    ChainError, ChainStateView,
+    ChainError, ChainProgress, ChainStateView,
 };
 use linera_execution::{
     committee::{Epoch, ValidatorName},
@@ -41,7 +41,7 @@ use self::{
 };
 use super::{ChainWorkerConfig, DeliveryNotifier};
 use crate::{
-    data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
+    data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest, OperationsEstimate},
     value_cache::ValueCache,
     worker::{NetworkActions, WorkerError},
 };
@@ -186,6 +186,18 @@ where
             .await
     }
 
+    /// Executes a block without persisting any changes to the state, and returns the resources
+    /// it used, the fees it was charged and the resulting balances.
+    pub(super) async fn estimate_block_execution(
+        &mut self,
+        block: ProposedBlock,
+    ) -> Result<OperationsEstimate, WorkerError> {
+        ChainWorkerStateWithTemporaryChanges::new(self)
+            .await
+            .estimate_block_execution(block)
+            .await
+    }
+
     /// Processes a leader timeout issued for this multi-owner chain.
     pub(super) async fn process_timeout(
         &mut self,
@@ -550,6 +562,18 @@ where
             .update_received_certificate_trackers(new_trackers)
             .await
     }
+
+    /// Removes the blobs kept for blocks that are not confirmed yet, if the chain made no
+    /// progress since `progress`. Returns the removed blobs.
+    pub(super) async fn remove_pending_blobs(
+        &mut self,
+        progress: ChainProgress,
+    ) -> Result<Vec<Blob>, WorkerError> {
+        ChainWorkerStateWithAttemptedChanges::new(self)
+            .await
+            .remove_pending_blobs(progress)
+            .await
+    }
 }
 
 /// Returns the keys whose value is `None`.
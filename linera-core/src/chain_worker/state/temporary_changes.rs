@@ -34,7 +34,7 @@ use {
 
 use super::{check_block_epoch, ChainWorkerState};
 use crate::{
-    data_types::{ChainInfo, ChainInfoQuery, ChainInfoResponse},
+    data_types::{ChainInfo, ChainInfoQuery, ChainInfoResponse, OperationsEstimate},
     worker::WorkerError,
 };
 
@@ -150,6 +150,34 @@ where
         Ok((executed_block, response))
     }
 
+    /// Executes a block without persisting any changes to the state, and returns the resources
+    /// it used, the fees it was charged and the resulting balances.
+    pub(super) async fn estimate_block_execution(
+        &mut self,
+        block: ProposedBlock,
+    ) -> Result<OperationsEstimate, WorkerError> {
+        let local_time = self.0.storage.clock().current_time();
+        let (outcome, resources) = Box::pin(
+            self.0
+                .chain
+                .execute_block_and_track_resources(&block, local_time, None),
+        )
+        .await?;
+        let system = &self.0.chain.execution_state.system;
+        let owner_balance = match block.authenticated_signer {
+            Some(owner) => system.balances.get(&AccountOwner::User(owner)).await?,
+            None => None,
+        };
+        Ok(OperationsEstimate {
+            resources,
+            fees: resources.fees,
+            messages: outcome.messages.into_iter().flatten().collect(),
+            balance: *system.balance.get(),
+            owner_balance,
+            error: None,
+        })
+    }
+
     /// Validates a block proposed to extend this chain.
     pub(super) async fn validate_block(
         &mut self,
@@ -167,6 +195,7 @@ where
             blobs,
             validated_block_certificate,
             signature: _,
+            multi_sig,
         } = proposal;
         ensure!(
             validated_block_certificate.is_some() == outcome.is_some(),
@@ -176,8 +205,9 @@ where
                     .to_string()
             )
         );
+        // A multi-signature owner's members are checked in `verify_owner` below.
         ensure!(
-            *owner == Owner::from(public_key),
+            multi_sig.is_some() || *owner == Owner::from(public_key),
             WorkerError::InvalidBlockProposal("Public key does not match owner".into())
         );
         self.0.ensure_is_active()?;
@@ -317,7 +347,11 @@ where
                         continue; // We are not subscribed to this channel.
                     }
                 }
-                for bundle in inbox.added_bundles.elements().await? {
+                let mut bundles = inbox.added_bundles.elements().await?;
+                let delayed_bundles = inbox.delayed_bundles.index_values().await?;
+                bundles.extend(delayed_bundles.into_iter().map(|(_, bundle)| bundle));
+                bundles.sort_by_key(|bundle| (bundle.height, bundle.transaction_index));
+                for bundle in bundles {
                     messages.push(IncomingBundle {
                         origin: origin.clone(),
                         bundle,
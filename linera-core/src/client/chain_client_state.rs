@@ -9,7 +9,7 @@ use linera_base::{
     data_types::{Blob, BlockHeight, Timestamp},
     ensure,
     identifiers::{BlobId, Owner},
-    ownership::ChainOwnership,
+    ownership::{ChainOwnership, MultiSigOwner},
 };
 use linera_chain::data_types::ProposedBlock;
 use tokio::sync::Mutex;
@@ -33,6 +33,8 @@ pub struct ChainClientState {
     pending_proposal: Option<ProposedBlock>,
     /// Known key pairs from present and past identities.
     known_key_pairs: BTreeMap<Owner, KeyPair>,
+    /// Known multi-signature owners that we can sign for with our key pairs.
+    known_multi_sig_owners: BTreeMap<Owner, MultiSigOwner>,
 
     /// This contains blobs belonging to our `pending_block` that may not even have
     /// been processed by (i.e. been proposed to) our own local chain manager yet.
@@ -58,6 +60,7 @@ impl ChainClientState {
             .collect();
         let mut state = ChainClientState {
             known_key_pairs,
+            known_multi_sig_owners: BTreeMap::new(),
             block_hash,
             timestamp,
             next_block_height,
@@ -111,6 +114,32 @@ impl ChainClientState {
         &self.known_key_pairs
     }
 
+    pub fn known_multi_sig_owners(&self) -> &BTreeMap<Owner, MultiSigOwner> {
+        &self.known_multi_sig_owners
+    }
+
+    /// Returns the key pairs of the members of the given multi-signature owner that we know,
+    /// or `None` if these are fewer than its threshold.
+    pub fn multi_sig_key_pairs(&self, multi_sig_owner: &MultiSigOwner) -> Option<Vec<&KeyPair>> {
+        let key_pairs = multi_sig_owner
+            .public_keys
+            .iter()
+            .filter_map(|public_key| self.known_key_pairs.get(&Owner::from(public_key)))
+            .collect::<Vec<_>>();
+        let threshold = usize::try_from(multi_sig_owner.threshold).ok()?;
+        (key_pairs.len() >= threshold).then_some(key_pairs)
+    }
+
+    /// Returns whether we can sign for the given owner, i.e. we have its secret key or, if it
+    /// is a multi-signature owner, enough of its members' secret keys.
+    pub fn can_sign_for(&self, owner: &Owner) -> bool {
+        self.known_key_pairs.contains_key(owner)
+            || self
+                .known_multi_sig_owners
+                .get(owner)
+                .is_some_and(|multi_sig_owner| self.multi_sig_key_pairs(multi_sig_owner).is_some())
+    }
+
     /// Returns whether the given ownership includes anyone whose secret key we don't have.
     pub fn has_other_owners(&self, ownership: &ChainOwnership) -> bool {
         ownership
@@ -124,6 +153,12 @@ impl ChainClientState {
         new_public_key
     }
 
+    pub(super) fn insert_known_multi_sig_owner(&mut self, multi_sig_owner: MultiSigOwner) -> Owner {
+        let owner = Owner::from(&multi_sig_owner);
+        self.known_multi_sig_owners.insert(owner, multi_sig_owner);
+        owner
+    }
+
     pub(super) fn update_from_info(&mut self, info: &ChainInfo) {
         if info.next_block_height > self.next_block_height {
             self.next_block_height = info.next_block_height;
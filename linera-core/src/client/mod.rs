@@ -37,7 +37,7 @@ use linera_base::{
         Account, AccountOwner, ApplicationId, BlobId, BlobType, BytecodeId, ChainId, MessageId,
         Owner, UserApplicationId,
     },
-    ownership::{ChainOwnership, TimeoutConfig},
+    ownership::{ChainOwnership, MultiSigOwner, TimeoutConfig},
 };
 use linera_chain::{
     data_types::{
@@ -71,7 +71,8 @@ use tracing::{debug, error, info, instrument, warn, Instrument as _};
 
 use crate::{
     data_types::{
-        BlockHeightRange, ChainInfo, ChainInfoQuery, ChainInfoResponse, ClientOutcome, RoundTimeout,
+        BlockHeightRange, ChainInfo, ChainInfoQuery, ChainInfoResponse, ClientOutcome,
+        OperationsEstimate, RoundTimeout,
     },
     local_node::{LocalNodeClient, LocalNodeError},
     node::{
@@ -785,7 +786,7 @@ where
     }
 
     /// Obtains up to `self.options.max_pending_message_bundles` pending message bundles for the
-    /// local chain.
+    /// local chain, but no more than the committee's policy allows in a single block.
     #[instrument(level = "trace")]
     async fn pending_message_bundles(&self) -> Result<Vec<IncomingBundle>, ChainClientError> {
         let query = ChainInfoQuery::new(self.chain_id).with_pending_message_bundles();
@@ -833,15 +834,35 @@ where
             }
         }
 
+        // A block can't contain more incoming bundles than the policy allows; the rest are
+        // left for the next block.
+        let max_bundles = match self.local_committee().await {
+            Ok(committee) => self.options.max_pending_message_bundles.min(
+                usize::try_from(committee.policy().maximum_incoming_bundles_per_block)
+                    .unwrap_or(usize::MAX),
+            ),
+            Err(LocalNodeError::InactiveChain(_)) => self.options.max_pending_message_bundles,
+            Err(error) => return Err(error.into()),
+        };
+
+        // Delayed bundles are held back until they are due. They don't block later bundles
+        // from the same origin.
+        let local_time = self.storage_client().clock().current_time();
         Ok(pending_message_bundles
             .into_iter()
+            .filter(|bundle| {
+                !bundle
+                    .bundle
+                    .not_before()
+                    .is_some_and(|not_before| not_before > local_time)
+            })
             .filter_map(|mut bundle| {
                 self.options
                     .message_policy
                     .must_handle(&mut bundle)
                     .then_some(bundle)
             })
-            .take(self.options.max_pending_message_bundles)
+            .take(max_bundles)
             .collect())
     }
 
@@ -946,7 +967,7 @@ where
             .ownership
             .all_owners()
             .chain(&manager.leader)
-            .filter(|owner| state.known_key_pairs().contains_key(owner));
+            .filter(|owner| state.can_sign_for(owner));
         let Some(identity) = our_identities.next() else {
             return Err(ChainClientError::CannotFindKeyForChain(self.chain_id));
         };
@@ -957,16 +978,23 @@ where
         Ok(*identity)
     }
 
-    /// Obtains the key pair associated to the current identity.
+    /// Obtains the key pair associated to the current identity. If that is a multi-signature
+    /// owner, this is the key pair of one of its members.
     #[instrument(level = "trace")]
     pub async fn key_pair(&self) -> Result<KeyPair, ChainClientError> {
         let id = self.identity().await?;
-        Ok(self
-            .state()
-            .known_key_pairs()
+        let state = self.state();
+        if let Some(key_pair) = state.known_key_pairs().get(&id) {
+            return Ok(key_pair.copy());
+        }
+        let multi_sig_owner = state
+            .known_multi_sig_owners()
             .get(&id)
-            .expect("key should be known at this point")
-            .copy())
+            .expect("key or multi-signature owner should be known at this point");
+        let key_pairs = state
+            .multi_sig_key_pairs(multi_sig_owner)
+            .expect("enough member keys should be known at this point");
+        Ok(key_pairs[0].copy())
     }
 
     /// Obtains the public key associated to the current identity.
@@ -2044,6 +2072,58 @@ where
         self.execute_operations(vec![operation]).await
     }
 
+    /// Estimates the resources used and the fees charged for executing a list of operations in
+    /// a new block, without proposing it.
+    ///
+    /// Incoming messages are not included. Does not attempt to synchronize with validators.
+    /// If the block fails to execute, the error is returned as part of the estimate.
+    #[instrument(level = "trace", skip(operations))]
+    pub async fn estimate_operations(
+        &self,
+        operations: Vec<Operation>,
+    ) -> Result<OperationsEstimate, ChainClientError> {
+        let identity = self.identity().await?;
+        let (previous_block_hash, height, timestamp) = {
+            let state = self.state();
+            (
+                state.block_hash(),
+                state.next_block_height(),
+                self.next_timestamp(&[], state.timestamp()),
+            )
+        };
+        let block = ProposedBlock {
+            epoch: self.epoch().await?,
+            chain_id: self.chain_id,
+            incoming_bundles: Vec::new(),
+            operations,
+            previous_block_hash,
+            height,
+            authenticated_signer: Some(identity),
+            timestamp,
+        };
+        loop {
+            let result = self
+                .client
+                .local_node
+                .estimate_block_execution(block.clone())
+                .await;
+            return match result {
+                Ok(estimate) => Ok(estimate),
+                Err(LocalNodeError::BlobsNotFound(blob_ids)) => {
+                    self.receive_certificates_for_blobs(blob_ids).await?;
+                    continue; // We found the missing blobs: retry.
+                }
+                Err(LocalNodeError::WorkerError(WorkerError::ChainError(error))) => {
+                    Ok(OperationsEstimate {
+                        error: Some(error.to_string()),
+                        ..OperationsEstimate::default()
+                    })
+                }
+                Err(error) => Err(error.into()),
+            };
+        }
+    }
+
     /// Executes a new block.
     ///
     /// This must be preceded by a call to `prepare_chain()`.
@@ -2463,19 +2543,30 @@ where
             .already_handled_proposal(round, &executed_block.block);
         let key_pair = self.key_pair().await?;
         // Create the final block proposal.
-        let proposal = if let Some(locked) = info.manager.requested_locked {
-            Box::new(match *locked {
+        let mut proposal = if let Some(locked) = info.manager.requested_locked {
+            match *locked {
                 LockedBlock::Regular(cert) => {
                     BlockProposal::new_retry(round, cert, &key_pair, blobs)
                 }
                 LockedBlock::Fast(proposal) => {
                     BlockProposal::new_initial(round, proposal.content.block, &key_pair, blobs)
                 }
-            })
+            }
         } else {
             let block = executed_block.block.clone();
-            Box::new(BlockProposal::new_initial(round, block, &key_pair, blobs))
+            BlockProposal::new_initial(round, block, &key_pair, blobs)
         };
+        // If we are proposing on behalf of a multi-signature owner, add the co-signatures.
+        {
+            let state = self.state();
+            if let Some(multi_sig_owner) = state.known_multi_sig_owners().get(&identity) {
+                let co_signers = state
+                    .multi_sig_key_pairs(multi_sig_owner)
+                    .expect("enough member keys should be known at this point");
+                proposal = proposal.with_multi_sig(multi_sig_owner.clone(), co_signers);
+            }
+        }
+        let proposal = Box::new(proposal);
         if !already_handled_locally {
             // Check the final block proposal. This will be cheaper after #1401.
             self.client
@@ -2626,6 +2717,21 @@ where
             .await
     }
 
+    /// Adds the given key pairs and multi-signature owner, so that this client can propose
+    /// blocks on behalf of that owner if it knows at least the threshold of member keys.
+    #[instrument(level = "trace", skip(key_pairs))]
+    pub fn add_multi_sig_owner(
+        &self,
+        multi_sig_owner: MultiSigOwner,
+        key_pairs: Vec<KeyPair>,
+    ) -> Owner {
+        let mut state = self.state_mut();
+        for key_pair in key_pairs {
+            state.insert_known_key_pair(key_pair);
+        }
+        state.insert_known_multi_sig_owner(multi_sig_owner)
+    }
+
     /// Rotates the key of the chain.
     #[instrument(level = "trace", skip(key_pair))]
     pub async fn rotate_key_pair(
@@ -2870,6 +2976,7 @@ where
                 parameters,
                 instantiation_argument,
                 required_application_ids,
+                None,
             )
             .await?
             .map(|(app_id, cert)| (app_id.with_abi(), cert)))
@@ -2883,7 +2990,8 @@ where
             bytecode_id,
             parameters,
             instantiation_argument,
-            required_application_ids
+            required_application_ids,
+            admin
         )
     )]
     pub async fn create_application_untyped(
@@ -2892,6 +3000,7 @@ where
         parameters: Vec<u8>,
         instantiation_argument: Vec<u8>,
         required_application_ids: Vec<UserApplicationId>,
+        admin: Option<Owner>,
     ) -> Result<ClientOutcome<(UserApplicationId, ConfirmedBlockCertificate)>, ChainClientError>
     {
         self.execute_operation(Operation::System(SystemOperation::CreateApplication {
@@ -2899,6 +3008,7 @@ where
             parameters,
             instantiation_argument,
             required_application_ids,
+            admin,
         }))
         .await?
         .try_map(|certificate| {
@@ -2915,6 +3025,21 @@ where
         })
     }
 
+    /// Upgrades an application to a new bytecode. This must be the chain that created the
+    /// application, and the block must be signed by the application's admin.
+    #[instrument(level = "trace")]
+    pub async fn upgrade_application(
+        &self,
+        application_id: UserApplicationId,
+        bytecode_id: BytecodeId,
+    ) -> Result<ClientOutcome<ConfirmedBlockCertificate>, ChainClientError> {
+        self.execute_operation(Operation::System(SystemOperation::UpgradeApplication {
+            application_id,
+            bytecode_id,
+        }))
+        .await
+    }
+
     /// Creates a new committee and starts using it (admin chains only).
     #[instrument(level = "trace", skip(committee))]
     pub async fn stage_new_committee(
@@ -4,6 +4,7 @@
 
 use std::{collections::BTreeMap, ops::Not};
 
+use async_graphql::SimpleObject;
 use custom_debug_derive::Debug;
 use linera_base::{
     crypto::{BcsSignable, CryptoError, CryptoHash, KeyPair, Signature},
@@ -11,13 +12,13 @@ use linera_base::{
     identifiers::{AccountOwner, ChainDescription, ChainId},
 };
 use linera_chain::{
-    data_types::{ChainAndHeight, IncomingBundle, Medium, MessageBundle},
+    data_types::{ChainAndHeight, IncomingBundle, Medium, MessageBundle, OutgoingMessage},
     manager::ChainManagerInfo,
     ChainStateView,
 };
 use linera_execution::{
     committee::{Committee, Epoch, ValidatorName},
-    ExecutionRuntimeContext,
+    ExecutionRuntimeContext, ResourceTracker,
 };
 use linera_storage::ChainRuntimeContext;
 use linera_views::context::Context;
@@ -249,6 +250,15 @@ impl CrossChainRequest {
         }
     }
 
+    /// Which chain the cross-chain request is sent from.
+    pub fn origin_chain_id(&self) -> ChainId {
+        use CrossChainRequest::*;
+        match self {
+            UpdateRecipient { sender, .. } => *sender,
+            ConfirmUpdatedRecipient { recipient, .. } => *recipient,
+        }
+    }
+
     /// Returns true if the cross-chain request has messages lower or equal than `height`.
     pub fn has_messages_lower_or_equal_than(&self, height: BlockHeight) -> bool {
         match self {
@@ -317,6 +327,27 @@ impl ChainInfoResponse {
 
 impl<'de> BcsSignable<'de> for ChainInfo {}
 
+/// The estimated cost of executing a list of operations in a new block.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, SimpleObject)]
+pub struct OperationsEstimate {
+    /// The resources used by the block.
+    pub resources: ResourceTracker,
+    /// The fees charged for these resources, net of storage refunds. Grants allocated to
+    /// outgoing messages are not included: see `resources.grants`.
+    pub fees: Amount,
+    /// The messages that the block would send.
+    pub messages: Vec<OutgoingMessage>,
+    /// The chain's balance after executing the block. Fees are paid from this balance first.
+    pub balance: Amount,
+    /// The authenticated signer's balance on the chain after executing the block, if any.
+    /// This pays for the fees that the chain's balance doesn't cover.
+    #[debug(skip_if = Option::is_none)]
+    pub owner_balance: Option<Amount>,
+    /// The error, if the block failed to execute. In that case, the other fields are empty.
+    #[debug(skip_if = Option::is_none)]
+    pub error: Option<String>,
+}
+
 /// The outcome of trying to commit a list of operations to the chain.
 #[derive(Debug)]
 pub enum ClientOutcome<T> {
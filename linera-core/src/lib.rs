@@ -24,5 +24,6 @@ pub(crate) mod updater;
 pub(crate) mod value_cache;
 
 pub use updater::DEFAULT_GRACE_PERIOD;
+pub use value_cache::ValueCacheStats;
 
 pub use crate::join_set_ext::{JoinSetExt, TaskHandle};
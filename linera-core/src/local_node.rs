@@ -25,7 +25,9 @@ use tokio::sync::OwnedRwLockReadGuard;
 use tracing::{instrument, warn};
 
 use crate::{
-    data_types::{BlockHeightRange, ChainInfo, ChainInfoQuery, ChainInfoResponse},
+    data_types::{
+        BlockHeightRange, ChainInfo, ChainInfoQuery, ChainInfoResponse, OperationsEstimate,
+    },
     notifier::Notifier,
     worker::{ProcessableCertificate, WorkerError, WorkerState},
 };
@@ -178,6 +180,14 @@ where
         Ok(self.node.state.stage_block_execution(block).await?)
     }
 
+    #[instrument(level = "trace", skip_all)]
+    pub async fn estimate_block_execution(
+        &self,
+        block: ProposedBlock,
+    ) -> Result<OperationsEstimate, LocalNodeError> {
+        Ok(self.node.state.estimate_block_execution(block).await?)
+    }
+
     /// Reads blobs from storage.
     pub async fn read_blobs_from_storage(
         &self,
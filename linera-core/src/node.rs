@@ -262,6 +262,8 @@ pub enum NodeError {
     EmptyBlobsNotFound,
     #[error("Local error handling validator response")]
     ResponseHandlingError { error: String },
+    #[error("The validator rejected the request because of its rate limits; retry after {retry_after_ms} ms")]
+    RateLimited { retry_after_ms: u64 },
 }
 
 impl From<tonic::Status> for NodeError {
@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashSet;
+use std::{collections::HashSet, future::Future};
 
 use custom_debug_derive::Debug;
 use futures::{future::try_join_all, stream::FuturesUnordered, StreamExt};
@@ -10,6 +10,7 @@ use linera_base::{
     data_types::{Blob, BlockHeight},
     ensure,
     identifiers::{BlobId, ChainId},
+    time::{timer, Duration},
 };
 use linera_chain::{
     data_types::BlockProposal,
@@ -27,6 +28,12 @@ use crate::{
     node::{CrossChainMessageDelivery, NodeError, ValidatorNode},
 };
 
+/// The maximal number of times a request rejected by the rate limits of a validator is retried.
+const MAX_RATE_LIMITED_RETRIES: u32 = 3;
+
+/// The maximal delay before retrying a request rejected by the rate limits of a validator.
+const MAX_RATE_LIMITED_DELAY: Duration = Duration::from_secs(5);
+
 /// A validator node together with the validator's name.
 #[derive(Clone, Debug)]
 pub struct RemoteNode<N> {
@@ -42,7 +49,9 @@ impl<N: ValidatorNode> RemoteNode<N> {
         query: ChainInfoQuery,
     ) -> Result<Box<ChainInfo>, NodeError> {
         let chain_id = query.chain_id;
-        let response = self.node.handle_chain_info_query(query).await?;
+        let response = self
+            .with_backoff(|| self.node.handle_chain_info_query(query.clone()))
+            .await?;
         self.check_and_return_info(response, chain_id)
     }
 
@@ -52,7 +61,9 @@ impl<N: ValidatorNode> RemoteNode<N> {
         proposal: Box<BlockProposal>,
     ) -> Result<Box<ChainInfo>, NodeError> {
         let chain_id = proposal.content.block.chain_id;
-        let response = self.node.handle_block_proposal(*proposal).await?;
+        let response = self
+            .with_backoff(|| self.node.handle_block_proposal((*proposal).clone()))
+            .await?;
         self.check_and_return_info(response, chain_id)
     }
 
@@ -61,7 +72,9 @@ impl<N: ValidatorNode> RemoteNode<N> {
         certificate: TimeoutCertificate,
     ) -> Result<Box<ChainInfo>, NodeError> {
         let chain_id = certificate.inner().chain_id();
-        let response = self.node.handle_timeout_certificate(certificate).await?;
+        let response = self
+            .with_backoff(|| self.node.handle_timeout_certificate(certificate.clone()))
+            .await?;
         self.check_and_return_info(response, chain_id)
     }
 
@@ -72,8 +85,10 @@ impl<N: ValidatorNode> RemoteNode<N> {
     ) -> Result<Box<ChainInfo>, NodeError> {
         let chain_id = certificate.inner().chain_id();
         let response = self
-            .node
-            .handle_confirmed_certificate(certificate, delivery)
+            .with_backoff(|| {
+                self.node
+                    .handle_confirmed_certificate(certificate.clone(), delivery)
+            })
             .await?;
         self.check_and_return_info(response, chain_id)
     }
@@ -83,7 +98,9 @@ impl<N: ValidatorNode> RemoteNode<N> {
         certificate: ValidatedBlockCertificate,
     ) -> Result<Box<ChainInfo>, NodeError> {
         let chain_id = certificate.inner().chain_id();
-        let response = self.node.handle_validated_certificate(certificate).await?;
+        let response = self
+            .with_backoff(|| self.node.handle_validated_certificate(certificate.clone()))
+            .await?;
         self.check_and_return_info(response, chain_id)
     }
 
@@ -95,8 +112,10 @@ impl<N: ValidatorNode> RemoteNode<N> {
     ) -> Result<Box<ChainInfo>, NodeError> {
         let chain_id = certificate.value.chain_id;
         let response = self
-            .node
-            .handle_lite_certificate(certificate, delivery)
+            .with_backoff(|| {
+                self.node
+                    .handle_lite_certificate(certificate.clone(), delivery)
+            })
             .await?;
         self.check_and_return_info(response, chain_id)
     }
@@ -177,9 +196,9 @@ impl<N: ValidatorNode> RemoteNode<N> {
         };
         let query = ChainInfoQuery::new(chain_id).with_sent_certificate_hashes_in_range(range);
         if let Ok(info) = self.handle_chain_info_query(query).await {
+            let hashes = info.requested_sent_certificate_hashes;
             let certificates = self
-                .node
-                .download_certificates(info.requested_sent_certificate_hashes)
+                .with_backoff(|| self.node.download_certificates(hashes.clone()))
                 .await?
                 .into_iter()
                 .map(|c| {
@@ -198,8 +217,12 @@ impl<N: ValidatorNode> RemoteNode<N> {
         &self,
         blob_id: BlobId,
     ) -> Result<ConfirmedBlockCertificate, NodeError> {
-        let last_used_hash = self.node.blob_last_used_by(blob_id).await?;
-        let certificate = self.node.download_certificate(last_used_hash).await?;
+        let last_used_hash = self
+            .with_backoff(|| self.node.blob_last_used_by(blob_id))
+            .await?;
+        let certificate = self
+            .with_backoff(|| self.node.download_certificate(last_used_hash))
+            .await?;
         if !certificate.requires_blob(&blob_id) {
             warn!(
                 "Got invalid last used by certificate for blob {} from validator {}",
@@ -215,7 +238,7 @@ impl<N: ValidatorNode> RemoteNode<N> {
     pub(crate) async fn upload_blobs(&self, blobs: Vec<Blob>) -> Result<(), NodeError> {
         let tasks = blobs
             .into_iter()
-            .map(|blob| self.node.upload_blob(blob.into()));
+            .map(|blob| self.with_backoff(move || self.node.upload_blob(blob.content().clone())));
         try_join_all(tasks).await?;
         Ok(())
     }
@@ -227,9 +250,12 @@ impl<N: ValidatorNode> RemoteNode<N> {
         chain_id: ChainId,
         blobs: Vec<Blob>,
     ) -> Result<(), NodeError> {
-        let tasks = blobs
-            .into_iter()
-            .map(|blob| self.node.handle_pending_blob(chain_id, blob.into_content()));
+        let tasks = blobs.into_iter().map(|blob| {
+            self.with_backoff(move || {
+                self.node
+                    .handle_pending_blob(chain_id, blob.content().clone())
+            })
+        });
         try_join_all(tasks).await?;
         Ok(())
     }
@@ -250,7 +276,7 @@ impl<N: ValidatorNode> RemoteNode<N> {
 
     #[instrument(level = "trace")]
     async fn try_download_blob(&self, blob_id: BlobId) -> Option<Blob> {
-        match self.node.download_blob(blob_id).await {
+        match self.with_backoff(|| self.node.download_blob(blob_id)).await {
             Ok(blob) => {
                 let blob = Blob::new(blob);
                 if blob.id() != blob_id {
@@ -305,7 +331,34 @@ impl<N: ValidatorNode> RemoteNode<N> {
         if hashes.is_empty() {
             return Ok(Vec::new());
         }
-        self.node.download_certificates(hashes).await
+        self.with_backoff(|| self.node.download_certificates(hashes.clone()))
+            .await
+    }
+
+    /// Sends a request with `send`, and sends it again after the requested delay if the
+    /// validator rejected it because of its rate limits.
+    async fn with_backoff<T, F, Fut>(&self, send: F) -> Result<T, NodeError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, NodeError>>,
+    {
+        let mut retries = 0;
+        loop {
+            match send().await {
+                Err(NodeError::RateLimited { retry_after_ms })
+                    if retries < MAX_RATE_LIMITED_RETRIES =>
+                {
+                    retries += 1;
+                    let delay = Duration::from_millis(retry_after_ms).min(MAX_RATE_LIMITED_DELAY);
+                    warn!(
+                        "Validator {} is rate limiting our requests; retrying in {delay:?}",
+                        self.name
+                    );
+                    timer::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
     }
 
     #[instrument(level = "trace", skip(validators))]
@@ -259,6 +259,52 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_process_inbox_respects_incoming_bundle_limit<B>(
+    storage_builder: B,
+) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let policy = ResourceControlPolicy {
+        maximum_incoming_bundles_per_block: 2,
+        ..ResourceControlPolicy::default()
+    };
+    let mut builder = TestBuilder::new(storage_builder, 4, 1)
+        .await?
+        .with_policy(policy);
+    let sender = builder.add_root_chain(1, Amount::from_tokens(5)).await?;
+    let receiver = builder.add_root_chain(2, Amount::ZERO).await?;
+    let receiver_id = receiver.chain_id();
+    // Each transfer is in its own block, so the receiver gets five bundles.
+    let mut certificates = Vec::new();
+    for _ in 0..5 {
+        let certificate = sender
+            .transfer_to_account(None, Amount::ONE, Account::chain(receiver_id))
+            .await?
+            .unwrap();
+        certificates.push(certificate);
+    }
+    receiver
+        .receive_certificate_and_update_validators(certificates.pop().unwrap())
+        .await?;
+    // At most two bundles fit in a block, so three blocks are needed.
+    let (certificates, timeout) = receiver.process_inbox().await?;
+    assert!(timeout.is_none());
+    assert_eq!(certificates.len(), 3);
+    for certificate in &certificates {
+        assert!(certificate.block().body.incoming_bundles.len() <= 2);
+    }
+    assert_eq!(receiver.next_block_height(), BlockHeight::from(3));
+    assert_eq!(receiver.local_balance().await?, Amount::from_tokens(5));
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
@@ -1240,6 +1286,51 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
+#[test_log::test(tokio::test)]
+async fn test_estimate_operations<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut builder = TestBuilder::new(storage_builder, 4, 1)
+        .await?
+        .with_policy(ResourceControlPolicy::fuel_and_block());
+    let sender = builder.add_root_chain(1, Amount::from_tokens(3)).await?;
+    let receiver = builder.add_root_chain(2, Amount::ZERO).await?;
+    let transfer = |amount| {
+        Operation::System(SystemOperation::Transfer {
+            owner: None,
+            recipient: Recipient::chain(receiver.chain_id()),
+            amount,
+        })
+    };
+
+    let estimate = sender
+        .estimate_operations(vec![transfer(Amount::ONE)])
+        .await?;
+    assert_eq!(estimate.error, None);
+    assert_eq!(estimate.resources.blocks, 1);
+    assert_eq!(estimate.resources.operations, 1);
+    assert_eq!(estimate.resources.messages, 1);
+    assert_eq!(estimate.fees, Amount::from_millis(1));
+    assert_eq!(estimate.messages.len(), 1);
+    assert_eq!(estimate.balance, Amount::from_millis(1_999));
+    assert_eq!(estimate.owner_balance, None);
+
+    // Failing operations are reported in the estimate.
+    let estimate = sender
+        .estimate_operations(vec![transfer(Amount::from_tokens(4))])
+        .await?;
+    assert!(estimate.error.is_some());
+    assert!(estimate.messages.is_empty());
+
+    // Nothing was committed or changed locally.
+    assert_eq!(sender.next_block_height(), BlockHeight::ZERO);
+    assert_eq!(sender.local_balance().await?, Amount::from_tokens(3));
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new().await; "storage_service"))]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
@@ -12,7 +12,7 @@ use linera_base::{
 use linera_chain::types::Timeout;
 use linera_execution::committee::Epoch;
 
-use super::{ValueCache, DEFAULT_VALUE_CACHE_SIZE};
+use super::{ValueCache, ValueCacheStats, DEFAULT_VALUE_CACHE_SIZE};
 
 /// Tests attempt to retrieve non-existent value.
 #[tokio::test]
@@ -24,6 +24,28 @@ async fn test_retrieve_missing_value() {
     assert!(cache.keys::<Vec<_>>().await.is_empty());
 }
 
+/// Tests counting the values in the cache and the lookups.
+#[tokio::test]
+async fn test_stats() {
+    let cache = ValueCache::<CryptoHash, Hashed<Timeout>>::default();
+    let value = create_dummy_certificate_value(0);
+    let hash = value.hash();
+
+    assert!(cache.get(&hash).await.is_none());
+    assert!(cache.insert(Cow::Borrowed(&value)).await);
+    assert!(cache.get(&hash).await.is_some());
+    assert!(cache.get(&hash).await.is_some());
+    assert_eq!(
+        cache.stats().await,
+        ValueCacheStats {
+            size: 1,
+            capacity: DEFAULT_VALUE_CACHE_SIZE,
+            hits: 2,
+            misses: 1,
+        }
+    );
+}
+
 /// Tests inserting a certificate value in the cache.
 #[tokio::test]
 async fn test_insert_single_certificate_value() {
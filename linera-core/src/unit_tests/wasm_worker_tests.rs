@@ -192,6 +192,7 @@ where
         parameters: parameters_bytes.clone(),
         instantiation_argument: initial_value_bytes.clone(),
         required_application_ids: vec![],
+        admin: None,
     };
     let application_id = UserApplicationId {
         bytecode_id,
@@ -206,6 +207,7 @@ where
         creation: application_id.creation,
         required_application_ids: vec![],
         parameters: parameters_bytes,
+        admin: None,
     };
     let create_block = make_first_block(creator_chain.into())
         .with_timestamp(2)
@@ -233,6 +235,7 @@ where
                 authenticated_signer: None,
                 grant: Amount::ZERO,
                 refund_grant_to: None,
+                not_before: None,
                 kind: MessageKind::Protected,
                 message: Message::System(SystemMessage::ApplicationCreated),
             }]],
@@ -24,7 +24,7 @@ use linera_base::{
         Account, AccountOwner, ChainDescription, ChainId, ChannelName, Destination,
         GenericApplicationId, MessageId, Owner,
     },
-    ownership::{ChainOwnership, TimeoutConfig},
+    ownership::{ChainOwnership, MultiSigOwner, TimeoutConfig},
 };
 use linera_chain::{
     data_types::{
@@ -293,6 +293,7 @@ where
                             destination: Destination::Recipient(incoming_bundle.origin.sender),
                             grant: Amount::ZERO,
                             refund_grant_to: None,
+                            not_before: None,
                             kind: MessageKind::Bouncing,
                             message: posted_message.message.clone(),
                         }]
@@ -350,6 +351,7 @@ fn direct_outgoing_message(
         authenticated_signer: None,
         grant: Amount::ZERO,
         refund_grant_to: None,
+        not_before: None,
         kind,
         message: Message::System(message),
     }
@@ -365,6 +367,7 @@ fn channel_outgoing_message(
         authenticated_signer: None,
         grant: Amount::ZERO,
         refund_grant_to: None,
+        not_before: None,
         kind,
         message: Message::System(message),
     }
@@ -623,6 +626,124 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_handle_block_proposal_multi_sig_owner<B>(mut storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let key_pairs = generate_key_pairs(3);
+    let multi_sig_owner =
+        MultiSigOwner::new(key_pairs.iter().map(KeyPair::public), 2).expect("valid threshold");
+    let owner = multi_sig_owner.owner();
+    let (_, worker) = init_worker_with_chains(
+        storage_builder.build().await?,
+        vec![
+            (ChainDescription::Root(1), owner, Amount::from_tokens(5)),
+            (
+                ChainDescription::Root(2),
+                PublicKey::test_key(2).into(),
+                Amount::ZERO,
+            ),
+        ],
+    )
+    .await;
+    let block = make_first_block(ChainId::root(1))
+        .with_simple_transfer(ChainId::root(2), Amount::from_tokens(5))
+        .with_authenticated_signer(Some(owner));
+
+    // A single member's signature is below the threshold.
+    let proposal = block
+        .clone()
+        .into_first_proposal(&key_pairs[0])
+        .with_multi_sig(multi_sig_owner.clone(), []);
+    assert_matches!(
+        worker.handle_block_proposal(proposal).await,
+        Err(WorkerError::InvalidOwner)
+    );
+
+    // Signatures by non-members don't count.
+    let outsider = KeyPair::generate();
+    let proposal = block
+        .clone()
+        .into_first_proposal(&key_pairs[0])
+        .with_multi_sig(multi_sig_owner.clone(), [&outsider]);
+    assert_matches!(
+        worker.handle_block_proposal(proposal).await,
+        Err(WorkerError::InvalidOwner)
+    );
+
+    // The proposer must be a member, too.
+    let proposal = block
+        .clone()
+        .into_first_proposal(&outsider)
+        .with_multi_sig(multi_sig_owner.clone(), &key_pairs[..2]);
+    assert_matches!(
+        worker.handle_block_proposal(proposal).await,
+        Err(WorkerError::InvalidOwner)
+    );
+    let chain = worker.chain_state_view(ChainId::root(1)).await?;
+    assert!(chain.manager.validated_vote().is_none());
+    drop(chain);
+
+    // Two of the three members can authorize the transfer.
+    let proposal = block
+        .into_first_proposal(&key_pairs[2])
+        .with_multi_sig(multi_sig_owner, [&key_pairs[0]]);
+    worker.handle_block_proposal(proposal).await?;
+    let chain = worker.chain_state_view(ChainId::root(1)).await?;
+    assert!(chain.manager.validated_vote().is_some());
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_handle_block_proposal_secp256k1_owner<B>(mut storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let key_pair = KeyPair::generate_secp256k1();
+    let address = key_pair.public().ethereum_address().unwrap();
+    // The owner can be computed from the Ethereum address alone.
+    let owner = Owner::from(address);
+    assert_eq!(owner, Owner::from(key_pair.public()));
+    let (_, worker) = init_worker_with_chains(
+        storage_builder.build().await?,
+        vec![
+            (ChainDescription::Root(1), owner, Amount::from_tokens(5)),
+            (
+                ChainDescription::Root(2),
+                PublicKey::test_key(2).into(),
+                Amount::ZERO,
+            ),
+        ],
+    )
+    .await;
+    let block = make_first_block(ChainId::root(1))
+        .with_simple_transfer(ChainId::root(2), Amount::from_tokens(5))
+        .with_authenticated_signer(Some(owner));
+
+    // An Ed25519 signature with the wrong scheme's public key is rejected.
+    let mut proposal = block.clone().into_first_proposal(&key_pair);
+    proposal.signature = Signature::new(&proposal.content, &KeyPair::generate());
+    assert_matches!(
+        worker.handle_block_proposal(proposal).await,
+        Err(WorkerError::CryptoError(_))
+    );
+
+    let proposal = block.into_first_proposal(&key_pair);
+    worker.handle_block_proposal(proposal).await?;
+    let chain = worker.chain_state_view(ChainId::root(1)).await?;
+    assert!(chain.manager.validated_vote().is_some());
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
 #[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
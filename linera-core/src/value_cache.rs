@@ -9,7 +9,12 @@ mod unit_tests;
 
 #[cfg(with_metrics)]
 use std::{any::type_name, sync::LazyLock};
-use std::{borrow::Cow, hash::Hash, num::NonZeroUsize};
+use std::{
+    borrow::Cow,
+    hash::Hash,
+    num::NonZeroUsize,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use linera_base::{crypto::CryptoHash, data_types::Blob, hashed::Hashed, identifiers::BlobId};
 use lru::LruCache;
@@ -47,6 +52,23 @@ where
     V: Clone,
 {
     cache: Mutex<LruCache<K, V>>,
+    /// The number of values found by [`ValueCache::get`].
+    hits: AtomicU64,
+    /// The number of values not found by [`ValueCache::get`].
+    misses: AtomicU64,
+}
+
+/// The occupancy of a [`ValueCache`] and the number of lookups since it was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ValueCacheStats {
+    /// The number of values in the cache.
+    pub size: usize,
+    /// The maximal number of values in the cache.
+    pub capacity: usize,
+    /// The number of lookups that found their value.
+    pub hits: u64,
+    /// The number of lookups that did not find their value.
+    pub misses: u64,
 }
 
 impl<K, V> Default for ValueCache<K, V>
@@ -60,6 +82,8 @@ where
 
         ValueCache {
             cache: Mutex::new(LruCache::new(size)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }
     }
 }
@@ -111,10 +135,28 @@ where
             .collect()
     }
 
+    /// Returns the number of values in the cache and of lookups with [`ValueCache::get`].
+    pub async fn stats(&self) -> ValueCacheStats {
+        let cache = self.cache.lock().await;
+        ValueCacheStats {
+            size: cache.len(),
+            capacity: cache.cap().get(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
     /// Returns a `V` from the cache, if present.
     pub async fn get(&self, hash: &K) -> Option<V> {
         let maybe_value = self.cache.lock().await.get(hash).cloned();
 
+        let counter = if maybe_value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
         #[cfg(with_metrics)]
         {
             let metric = if maybe_value.is_some() {
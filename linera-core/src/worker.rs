@@ -31,7 +31,7 @@ use linera_chain::{
         Block, CertificateValue, ConfirmedBlock, ConfirmedBlockCertificate, GenericCertificate,
         LiteCertificate, Timeout, TimeoutCertificate, ValidatedBlock, ValidatedBlockCertificate,
     },
-    ChainError, ChainStateView,
+    ChainError, ChainProgress, ChainStateView,
 };
 use linera_execution::{
     committee::{Epoch, ValidatorName},
@@ -55,10 +55,10 @@ use {
 
 use crate::{
     chain_worker::{ChainWorkerActor, ChainWorkerConfig, ChainWorkerRequest, DeliveryNotifier},
-    data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
+    data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest, OperationsEstimate},
     join_set_ext::{JoinSet, JoinSetExt},
     notifier::Notifier,
-    value_cache::ValueCache,
+    value_cache::{ValueCache, ValueCacheStats},
 };
 
 #[cfg(test)]
@@ -221,6 +221,8 @@ pub enum WorkerError {
     InvalidBlockProposal(String),
     #[error("The worker is too busy to handle new chains")]
     FullChainWorkerCache,
+    #[error("Chain {0} is not handled by this worker, e.g. because it belongs to another shard")]
+    ChainNotHandled(ChainId),
     #[error("Failed to join spawned worker task")]
     JoinError,
     #[error("Blob exceeds size limit")]
@@ -275,6 +277,9 @@ where
     executed_block_cache: Arc<ValueCache<CryptoHash, Hashed<Block>>>,
     /// Chain IDs that should be tracked by a worker.
     tracked_chains: Option<Arc<RwLock<HashSet<ChainId>>>>,
+    /// Returns whether the worker may run a given chain. Requests for other chains are
+    /// rejected.
+    handled_chains: Option<HandledChains>,
     /// One-shot channels to notify callers when messages of a particular chain have been
     /// delivered.
     delivery_notifiers: Arc<Mutex<DeliveryNotifiers>>,
@@ -284,6 +289,9 @@ where
     chain_workers: Arc<Mutex<LruCache<ChainId, ChainActorEndpoint<StorageClient>>>>,
 }
 
+/// Decides which chains a worker may run.
+type HandledChains = Arc<dyn Fn(ChainId) -> bool + Send + Sync>;
+
 /// The sender endpoint for [`ChainWorkerRequest`]s.
 type ChainActorEndpoint<StorageClient> =
     mpsc::UnboundedSender<ChainWorkerRequest<<StorageClient as Storage>::Context>>;
@@ -307,6 +315,7 @@ where
             chain_worker_config: ChainWorkerConfig::default().with_key_pair(key_pair),
             executed_block_cache: Arc::new(ValueCache::default()),
             tracked_chains: None,
+            handled_chains: None,
             delivery_notifiers: Arc::default(),
             chain_worker_tasks: Arc::default(),
             chain_workers: Arc::new(Mutex::new(LruCache::new(chain_worker_limit))),
@@ -326,6 +335,7 @@ where
             chain_worker_config: ChainWorkerConfig::default(),
             executed_block_cache: Arc::new(ValueCache::default()),
             tracked_chains: Some(tracked_chains),
+            handled_chains: None,
             delivery_notifiers: Arc::default(),
             chain_worker_tasks: Arc::default(),
             chain_workers: Arc::new(Mutex::new(LruCache::new(chain_worker_limit))),
@@ -361,6 +371,18 @@ where
         self
     }
 
+    /// Configures the chains that this worker may run, e.g. those of its shard. It is
+    /// called for each request, so the set of chains can change over time: see
+    /// [`Self::drain_chain_workers`].
+    #[instrument(level = "trace", skip(self, handles_chain))]
+    pub fn with_handled_chains(
+        mut self,
+        handles_chain: impl Fn(ChainId) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.handled_chains = Some(Arc::new(handles_chain));
+        self
+    }
+
     /// Returns an instance with the specified grace period, in microseconds.
     ///
     /// Blocks with a timestamp this far in the future will still be accepted, but the validator
@@ -512,6 +534,18 @@ where
         .await
     }
 
+    /// Executes a block without persisting any changes, and returns its estimated cost.
+    #[instrument(level = "trace", skip(self, block))]
+    pub async fn estimate_block_execution(
+        &self,
+        block: ProposedBlock,
+    ) -> Result<OperationsEstimate, WorkerError> {
+        self.query_chain_worker(block.chain_id, move |callback| {
+            ChainWorkerRequest::EstimateBlockExecution { block, callback }
+        })
+        .await
+    }
+
     /// Executes a [`Query`] for an application's state on a specific chain.
     #[instrument(level = "trace", skip(self, chain_id, query))]
     pub async fn query_application(
@@ -678,15 +712,15 @@ where
     ) -> Result<ChainActorEndpoint<StorageClient>, WorkerError> {
         let (sender, new_receiver) = timeout(Duration::from_secs(3), async move {
             loop {
-                match self.try_get_chain_worker_endpoint(chain_id) {
-                    Some(endpoint) => break endpoint,
+                match self.try_get_chain_worker_endpoint(chain_id)? {
+                    Some(endpoint) => break Ok::<_, WorkerError>(endpoint),
                     None => sleep(Duration::from_millis(250)).await,
                 }
                 warn!("No chain worker candidates found for eviction, retrying...");
             }
         })
         .await
-        .map_err(|_| WorkerError::FullChainWorkerCache)?;
+        .map_err(|_| WorkerError::FullChainWorkerCache)??;
 
         if let Some(receiver) = new_receiver {
             let delivery_notifier = self
@@ -725,20 +759,34 @@ where
     fn try_get_chain_worker_endpoint(
         &self,
         chain_id: ChainId,
-    ) -> Option<(
-        ChainActorEndpoint<StorageClient>,
-        Option<mpsc::UnboundedReceiver<ChainWorkerRequest<StorageClient::Context>>>,
-    )> {
+    ) -> Result<
+        Option<(
+            ChainActorEndpoint<StorageClient>,
+            Option<mpsc::UnboundedReceiver<ChainWorkerRequest<StorageClient::Context>>>,
+        )>,
+        WorkerError,
+    > {
         let mut chain_workers = self.chain_workers.lock().unwrap();
 
+        // This is checked while holding the lock, so that once `drain_chain_workers`
+        // removed a chain, no new actor is created for it.
+        if let Some(handles_chain) = &self.handled_chains {
+            if !handles_chain(chain_id) {
+                return Err(WorkerError::ChainNotHandled(chain_id));
+            }
+        }
+
         if let Some(endpoint) = chain_workers.get(&chain_id) {
-            Some((endpoint.clone(), None))
+            Ok(Some((endpoint.clone(), None)))
         } else {
             if chain_workers.len() >= usize::from(chain_workers.cap()) {
-                let (chain_to_evict, _) = chain_workers
+                let Some((chain_to_evict, _)) = chain_workers
                     .iter()
                     .rev()
-                    .find(|(_, candidate_endpoint)| candidate_endpoint.strong_count() <= 1)?;
+                    .find(|(_, candidate_endpoint)| candidate_endpoint.strong_count() <= 1)
+                else {
+                    return Ok(None);
+                };
                 let chain_to_evict = *chain_to_evict;
 
                 chain_workers.pop(&chain_to_evict);
@@ -748,8 +796,96 @@ where
             let (sender, receiver) = mpsc::unbounded_channel();
             chain_workers.push(chain_id, sender.clone());
 
-            Some((sender, Some(receiver)))
+            Ok(Some((sender, Some(receiver))))
+        }
+    }
+
+    /// Removes the [`ChainWorkerActor`]s of the chains for which `should_evict` returns
+    /// `true` from the cache, e.g. because these chains were moved to another shard. Their
+    /// state is loaded again from storage if they are needed later.
+    #[instrument(level = "trace", skip_all)]
+    pub fn evict_chain_workers(&self, should_evict: impl FnMut(ChainId) -> bool) {
+        self.remove_chain_workers(should_evict);
+    }
+
+    /// Removes the [`ChainWorkerActor`]s of the chains for which `should_drain` returns
+    /// `true` from the cache, and waits up to `limit` until the requests they were
+    /// handling are done. Returns the chains that still had requests running then.
+    ///
+    /// Together with [`Self::with_handled_chains`], this hands chains over to another
+    /// worker: once `should_drain` matches the chains that the worker doesn't handle
+    /// anymore, no request is running or can start for these chains when this returns an
+    /// empty list.
+    #[instrument(level = "trace", skip_all)]
+    pub async fn drain_chain_workers(
+        &self,
+        should_drain: impl FnMut(ChainId) -> bool,
+        limit: Duration,
+    ) -> Vec<ChainId> {
+        let endpoints = self
+            .remove_chain_workers(should_drain)
+            .into_iter()
+            .map(|(chain_id, endpoint)| (chain_id, endpoint.downgrade()))
+            .collect::<Vec<_>>();
+        // Each request holds an endpoint until it has its response.
+        let drained = async {
+            while endpoints
+                .iter()
+                .any(|(_, endpoint)| endpoint.strong_count() > 0)
+            {
+                sleep(Duration::from_millis(10)).await;
+            }
+        };
+        if timeout(limit, drained).await.is_ok() {
+            return Vec::new();
         }
+        let undrained = endpoints
+            .into_iter()
+            .filter(|(_, endpoint)| endpoint.strong_count() > 0)
+            .map(|(chain_id, _)| chain_id)
+            .collect::<Vec<_>>();
+        warn!(
+            "Requests for chains {:?} were still running after {:?}",
+            undrained, limit
+        );
+        undrained
+    }
+
+    /// Removes the [`ChainWorkerActor`]s of the chains for which `should_remove` returns
+    /// `true` from the cache, and returns their endpoints.
+    fn remove_chain_workers(
+        &self,
+        mut should_remove: impl FnMut(ChainId) -> bool,
+    ) -> Vec<(ChainId, ChainActorEndpoint<StorageClient>)> {
+        let mut chain_workers = self.chain_workers.lock().unwrap();
+        let chains_to_remove = chain_workers
+            .iter()
+            .map(|(chain_id, _)| *chain_id)
+            .filter(|chain_id| should_remove(*chain_id))
+            .collect::<Vec<_>>();
+        let endpoints = chains_to_remove
+            .into_iter()
+            .filter_map(|chain_id| Some((chain_id, chain_workers.pop(&chain_id)?)))
+            .collect();
+        self.clean_up_finished_chain_workers(&chain_workers);
+        endpoints
+    }
+
+    /// Returns the chains whose [`ChainWorkerActor`]s are in the cache, from the most
+    /// recently used to the least recently used.
+    #[instrument(level = "trace", skip(self))]
+    pub fn loaded_chain_ids(&self) -> Vec<ChainId> {
+        let chain_workers = self.chain_workers.lock().unwrap();
+        chain_workers
+            .iter()
+            .map(|(chain_id, _)| *chain_id)
+            .collect()
+    }
+
+    /// Returns the statistics of the cache of executed blocks.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn executed_block_cache_stats(&self) -> ValueCacheStats {
+        self.executed_block_cache.stats().await
     }
 
     /// Cleans up any finished chain workers and their delivery notifiers.
@@ -1048,6 +1184,22 @@ where
         })
         .await
     }
+
+    /// Removes the blobs that a chain keeps for blocks that are not confirmed yet, if it made
+    /// no progress since `progress`, as found by a [`GarbageCollector`]. Returns the removed
+    /// blobs.
+    ///
+    /// [`GarbageCollector`]: linera_storage::GarbageCollector
+    pub async fn remove_pending_blobs(
+        &self,
+        chain_id: ChainId,
+        progress: ChainProgress,
+    ) -> Result<Vec<Blob>, WorkerError> {
+        self.query_chain_worker(chain_id, move |callback| {
+            ChainWorkerRequest::RemovePendingBlobs { progress, callback }
+        })
+        .await
+    }
 }
 
 #[cfg(with_testing)]
@@ -3,12 +3,17 @@
 
 use std::collections::{HashMap, HashSet};
 
-use linera_base::{data_types::UserApplicationDescription, identifiers::UserApplicationId};
+use linera_base::{
+    data_types::{ArithmeticError, UserApplicationDescription},
+    identifiers::{BytecodeId, Owner, UserApplicationId},
+};
 use linera_views::{
     context::Context,
     map_view::HashedMapView,
+    set_view::HashedSetView,
     views::{ClonableView, HashableView},
 };
+use serde::{Deserialize, Serialize};
 #[cfg(with_testing)]
 use {
     linera_views::context::{create_test_memory_context, MemoryContext},
@@ -26,6 +31,19 @@ mod applications_tests;
 pub struct ApplicationRegistryView<C> {
     /// The applications that are known by the chain.
     pub known_applications: HashedMapView<C, UserApplicationId, UserApplicationDescription>,
+    /// The latest known upgrade of each upgraded application.
+    pub upgrades: HashedMapView<C, UserApplicationId, ApplicationUpgrade>,
+    /// The upgraded applications whose new contract has not run on this chain yet.
+    pub pending_migrations: HashedSetView<C, UserApplicationId>,
+}
+
+/// A new bytecode for an existing application.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct ApplicationUpgrade {
+    /// The bytecode replacing the one the application was created with.
+    pub bytecode_id: BytecodeId,
+    /// The number of upgrades of the application so far. Outdated upgrades are ignored.
+    pub version: u32,
 }
 
 #[cfg(with_testing)]
@@ -68,6 +86,7 @@ where
         application_id: UserApplicationId,
         parameters: Vec<u8>,
         required_application_ids: Vec<UserApplicationId>,
+        admin: Option<Owner>,
     ) -> Result<(), SystemExecutionError> {
         // Make sure that referenced applications ids have been registered.
         for required_id in &required_application_ids {
@@ -83,6 +102,7 @@ where
             parameters,
             creation,
             required_application_ids,
+            admin,
         };
         self.known_applications
             .insert(&application_id, description)?;
@@ -100,6 +120,77 @@ where
             .ok_or_else(|| SystemExecutionError::UnknownApplicationId(Box::new(id)))
     }
 
+    /// Retrieves an application's description, with the bytecode of its latest upgrade if any.
+    pub async fn describe_current_code(
+        &self,
+        id: UserApplicationId,
+    ) -> Result<UserApplicationDescription, SystemExecutionError> {
+        let mut description = self.describe_application(id).await?;
+        if let Some(upgrade) = self.upgrades.get(&id).await? {
+            description.bytecode_id = upgrade.bytecode_id;
+        }
+        Ok(description)
+    }
+
+    /// Upgrades an application to a new bytecode and returns the resulting upgrade.
+    pub async fn upgrade_application(
+        &mut self,
+        id: UserApplicationId,
+        bytecode_id: BytecodeId,
+    ) -> Result<ApplicationUpgrade, SystemExecutionError> {
+        let version = match self.upgrades.get(&id).await? {
+            Some(upgrade) => upgrade
+                .version
+                .checked_add(1)
+                .ok_or(ArithmeticError::Overflow)?,
+            None => 1,
+        };
+        let upgrade = ApplicationUpgrade {
+            bytecode_id,
+            version,
+        };
+        self.apply_upgrade(id, upgrade)?;
+        Ok(upgrade)
+    }
+
+    /// Returns whether `upgrade` is newer than the latest upgrade known for the application.
+    pub async fn is_new_upgrade(
+        &self,
+        id: UserApplicationId,
+        upgrade: &ApplicationUpgrade,
+    ) -> Result<bool, SystemExecutionError> {
+        Ok(self
+            .upgrades
+            .get(&id)
+            .await?
+            .map_or(true, |current| current.version < upgrade.version))
+    }
+
+    /// Records an upgrade, so that the application is migrated the next time it runs.
+    pub fn apply_upgrade(
+        &mut self,
+        id: UserApplicationId,
+        upgrade: ApplicationUpgrade,
+    ) -> Result<(), SystemExecutionError> {
+        self.upgrades.insert(&id, upgrade)?;
+        self.pending_migrations.insert(&id)?;
+        Ok(())
+    }
+
+    /// Retrieves the known upgrades of the given applications.
+    pub async fn describe_upgrades(
+        &self,
+        ids: impl IntoIterator<Item = UserApplicationId>,
+    ) -> Result<Vec<(UserApplicationId, ApplicationUpgrade)>, SystemExecutionError> {
+        let mut result = Vec::new();
+        for id in ids {
+            if let Some(upgrade) = self.upgrades.get(&id).await? {
+                result.push((id, upgrade));
+            }
+        }
+        Ok(result)
+    }
+
     /// Retrieves the recursive dependencies of applications and apply a topological sort.
     pub async fn find_dependencies(
         &self,
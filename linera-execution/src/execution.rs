@@ -6,18 +6,22 @@ use std::{
     mem, vec,
 };
 
+use async_graphql::SimpleObject;
 use futures::{stream::FuturesOrdered, FutureExt, StreamExt, TryStreamExt};
 use linera_base::{
-    data_types::{Amount, BlockHeight, Timestamp},
-    identifiers::{Account, AccountOwner, ChainId, Destination, Owner},
+    crypto::CryptoHash,
+    data_types::{Amount, ArithmeticError, BlockHeight, Timestamp},
+    identifiers::{Account, AccountOwner, ChainId, Destination, EventId, MessageId, Owner},
+    state_proof::StateProof,
 };
 use linera_views::{
     context::Context,
-    key_value_store_view::KeyValueStoreView,
+    merkle_map_view::MerkleKeyValueStoreView,
     reentrant_collection_view::HashedReentrantCollectionView,
-    views::{ClonableView, View},
+    views::{ClonableView, HashableView, View, ViewError},
 };
 use linera_views_derive::CryptoHashView;
+use serde::{Deserialize, Serialize};
 #[cfg(with_testing)]
 use {
     crate::{
@@ -30,11 +34,12 @@ use {
 
 use super::{runtime::ServiceRuntimeRequest, ExecutionRequest};
 use crate::{
-    resources::ResourceController, system::SystemExecutionStateView, ContractSyncRuntime,
-    ExecutionError, ExecutionOutcome, ExecutionRuntimeConfig, ExecutionRuntimeContext, Message,
-    MessageContext, MessageKind, Operation, OperationContext, Query, QueryContext,
-    RawExecutionOutcome, RawOutgoingMessage, Response, ServiceSyncRuntime, SystemMessage,
-    TransactionTracker, UserApplicationDescription, UserApplicationId,
+    resources::ResourceController,
+    system::{request_deadline, OutstandingRequest, RequestDeadline, SystemExecutionStateView},
+    ContractSyncRuntime, ExecutionError, ExecutionOutcome, ExecutionRuntimeConfig,
+    ExecutionRuntimeContext, Message, MessageContext, MessageKind, Operation, OperationContext,
+    Query, QueryContext, RawExecutionOutcome, RawOutgoingMessage, Response, ServiceSyncRuntime,
+    SystemMessage, TransactionTracker, UserApplicationDescription, UserApplicationId,
 };
 
 /// A view accessing the execution state of a chain.
@@ -43,7 +48,72 @@ pub struct ExecutionStateView<C> {
     /// System application.
     pub system: SystemExecutionStateView<C>,
     /// User applications.
-    pub users: HashedReentrantCollectionView<C, UserApplicationId, KeyValueStoreView<C>>,
+    pub users: HashedReentrantCollectionView<C, UserApplicationId, MerkleKeyValueStoreView<C>>,
+}
+
+/// The number of bytes stored by a user application on a chain.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, SimpleObject)]
+pub struct ApplicationStorageUsage {
+    /// The application using the storage.
+    pub application_id: UserApplicationId,
+    /// The total size of the keys and values stored by the application.
+    pub bytes_stored: u64,
+}
+
+impl<C> ExecutionStateView<C>
+where
+    C: Context + Send + Sync,
+{
+    /// Returns the number of bytes currently stored by each user application.
+    pub async fn storage_usage(&self) -> Result<Vec<ApplicationStorageUsage>, ViewError> {
+        let mut usage = Vec::new();
+        for application_id in self.users.indices().await? {
+            let Some(view) = self.users.try_load_entry(&application_id).await? else {
+                continue;
+            };
+            let size = view.total_size();
+            usage.push(ApplicationStorageUsage {
+                application_id,
+                bytes_stored: u64::from(size.key) + u64::from(size.value),
+            });
+        }
+        Ok(usage)
+    }
+}
+
+impl<C> ExecutionStateView<C>
+where
+    C: Context + Clone + Send + Sync + 'static,
+{
+    /// Returns a proof of the value of `key` in the state of the given application, to be
+    /// checked against the hash of this execution state.
+    pub async fn prove_application_state(
+        &self,
+        application_id: UserApplicationId,
+        key: &[u8],
+    ) -> Result<StateProof, ViewError> {
+        let system_hash = CryptoHash::from(self.system.hash().await?);
+        let mut other_applications = Vec::new();
+        let mut key_proof = None;
+        let mut value = None;
+        for id in self.users.indices().await? {
+            let Some(view) = self.users.try_load_entry(&id).await? else {
+                continue;
+            };
+            if id == application_id {
+                key_proof = Some(view.prove(key).await?);
+                value = view.get(key).await?;
+            } else {
+                other_applications.push((id, CryptoHash::from(view.hash().await?)));
+            }
+        }
+        Ok(StateProof {
+            system_hash,
+            other_applications,
+            key_proof,
+            value,
+        })
+    }
 }
 
 /// How to interact with a long-lived service runtime.
@@ -129,6 +199,8 @@ pub enum UserAction {
     Instantiate(OperationContext, Vec<u8>),
     Operation(OperationContext, Vec<u8>),
     Message(MessageContext, Vec<u8>),
+    ProcessEvent(MessageContext, EventId, Vec<u8>),
+    HandleResponse(MessageContext, MessageId, Vec<u8>, Option<Vec<u8>>),
 }
 
 impl UserAction {
@@ -138,6 +210,8 @@ impl UserAction {
             Instantiate(context, _) => context.authenticated_signer,
             Operation(context, _) => context.authenticated_signer,
             Message(context, _) => context.authenticated_signer,
+            ProcessEvent(context, _, _) => context.authenticated_signer,
+            HandleResponse(context, _, _, _) => context.authenticated_signer,
         }
     }
 
@@ -146,6 +220,8 @@ impl UserAction {
             UserAction::Instantiate(context, _) => context.height,
             UserAction::Operation(context, _) => context.height,
             UserAction::Message(context, _) => context.height,
+            UserAction::ProcessEvent(context, _, _) => context.height,
+            UserAction::HandleResponse(context, _, _, _) => context.height,
         }
     }
 }
@@ -207,7 +283,7 @@ where
         let (execution_state_sender, mut execution_state_receiver) =
             futures::channel::mpsc::unbounded();
         let txn_tracker_moved = mem::take(txn_tracker);
-        let (code, description) = self.load_contract(application_id).await?;
+        let (code, description, needs_migration) = self.load_contract(application_id).await?;
         let contract_runtime_task = linera_base::task::Blocking::spawn(move |mut codes| {
             let runtime = ContractSyncRuntime::new(
                 execution_state_sender,
@@ -221,7 +297,7 @@ where
 
             async move {
                 let code = codes.next().await.expect("we send this immediately below");
-                runtime.preload_contract(application_id, code, description)?;
+                runtime.preload_contract(application_id, code, description, needs_migration)?;
                 runtime.run_action(application_id, chain_id, action)
             }
         })
@@ -246,7 +322,8 @@ where
     /// Schedules application registration messages when needed.
     ///
     /// Ensures that the outgoing messages in `results` are preceded by a system message that
-    /// registers the application that will handle the messages.
+    /// registers the application that will handle the messages, followed by its latest upgrades
+    /// if any.
     pub async fn update_execution_outcomes_with_app_registrations(
         &self,
         txn_tracker: &mut TransactionTracker,
@@ -283,18 +360,38 @@ where
                         &HashMap::new(),
                     )
                     .await?;
+                let upgrades = self
+                    .system
+                    .registry
+                    .describe_upgrades(applications.iter().map(UserApplicationId::from))
+                    .await?;
 
-                Ok::<_, ExecutionError>(RawOutgoingMessage {
+                let mut messages = vec![RawOutgoingMessage {
                     destination: destination.clone(),
                     authenticated: false,
                     grant: Amount::ZERO,
+                    not_before: None,
                     kind: MessageKind::Simple,
                     message: SystemMessage::RegisterApplications { applications },
-                })
+                }];
+                if !upgrades.is_empty() {
+                    messages.push(RawOutgoingMessage {
+                        destination: destination.clone(),
+                        authenticated: false,
+                        grant: Amount::ZERO,
+                        not_before: None,
+                        kind: MessageKind::Simple,
+                        message: SystemMessage::UpgradeApplications { upgrades },
+                    });
+                }
+                Ok::<_, ExecutionError>(messages)
             })
             .collect::<FuturesOrdered<_>>()
             .try_collect::<Vec<_>>()
-            .await?;
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
 
         let system_outcome = RawExecutionOutcome {
             messages,
@@ -312,6 +409,138 @@ where
         Ok(())
     }
 
+    /// Records the requests sent in the current transaction as outstanding, and schedules a
+    /// message to this chain to time them out if no response was received, unless one is
+    /// pending already.
+    ///
+    /// This must be called after all of the transaction's messages were added, so that their
+    /// IDs are final.
+    pub async fn record_outstanding_requests(
+        &mut self,
+        height: BlockHeight,
+        timestamp: Timestamp,
+        txn_tracker: &mut TransactionTracker,
+    ) -> Result<(), ExecutionError> {
+        let chain_id = self.context().extra().chain_id();
+        let message_count = txn_tracker
+            .outcomes_mut()
+            .iter()
+            .map(ExecutionOutcome::message_count)
+            .sum::<usize>();
+        let message_count = u32::try_from(message_count).map_err(|_| ArithmeticError::Overflow)?;
+        let mut index = txn_tracker
+            .next_message_index()
+            .checked_sub(message_count)
+            .ok_or(ArithmeticError::Underflow)?;
+        let deadline = request_deadline(timestamp);
+        let mut has_messages_to_self = false;
+        for outcome in txn_tracker.outcomes_mut().iter() {
+            has_messages_to_self |= outcome.has_message_to(chain_id);
+            let ExecutionOutcome::User(application_id, outcome) = outcome else {
+                index += u32::try_from(outcome.message_count()).expect("checked above");
+                continue;
+            };
+            for message in &outcome.messages {
+                if let (MessageKind::Request, Destination::Recipient(recipient)) =
+                    (message.kind, &message.destination)
+                {
+                    let request_id = MessageId {
+                        chain_id,
+                        height,
+                        index,
+                    };
+                    let request = OutstandingRequest {
+                        application_id: *application_id,
+                        recipient: *recipient,
+                        request: message.message.clone(),
+                        deadline,
+                    };
+                    self.system
+                        .outstanding_requests
+                        .insert(&request_id, request)?;
+                    self.system.request_deadlines.insert(&RequestDeadline {
+                        deadline,
+                        request_id,
+                    })?;
+                }
+                index += 1;
+            }
+        }
+        // The timeout is delayed, so it can't be bundled with other messages to this chain.
+        // It is scheduled by a later transaction instead: the requests can only time out in a
+        // later block anyway.
+        if self.system.request_timer.get().is_some() || has_messages_to_self {
+            return Ok(());
+        }
+        let Some(oldest) = self
+            .system
+            .request_deadlines
+            .indices_in_range(.., false, Some(1))
+            .await?
+            .pop()
+        else {
+            return Ok(());
+        };
+        self.system.request_timer.set(Some(oldest.deadline));
+        let outcome = RawExecutionOutcome::default().with_message(RawOutgoingMessage {
+            destination: Destination::Recipient(chain_id),
+            authenticated: false,
+            grant: Amount::ZERO,
+            not_before: Some(oldest.deadline),
+            kind: MessageKind::Protected,
+            message: SystemMessage::RequestTimeout,
+        });
+        txn_tracker.add_system_outcome(outcome)?;
+        Ok(())
+    }
+
+    /// Tells the applications that sent the requests that timed out by now that they failed.
+    async fn time_out_requests(
+        &mut self,
+        context: MessageContext,
+        local_time: Timestamp,
+        txn_tracker: &mut TransactionTracker,
+        resource_controller: &mut ResourceController<Option<Owner>>,
+    ) -> Result<(), ExecutionError> {
+        self.system.request_timer.set(None);
+        let now = *self.system.timestamp.get();
+        while let Some(oldest) = self
+            .system
+            .request_deadlines
+            .indices_in_range(.., false, Some(1))
+            .await?
+            .pop()
+        {
+            if oldest.deadline > now {
+                break;
+            }
+            self.system.request_deadlines.remove(&oldest)?;
+            let Some(outstanding) = self
+                .system
+                .outstanding_requests
+                .get(&oldest.request_id)
+                .await?
+            else {
+                continue;
+            };
+            self.system
+                .outstanding_requests
+                .remove(&oldest.request_id)?;
+            self.run_user_action(
+                outstanding.application_id,
+                context.chain_id,
+                local_time,
+                UserAction::HandleResponse(context, oldest.request_id, outstanding.request, None),
+                context.refund_grant_to,
+                None,
+                txn_tracker,
+                resource_controller,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
     pub async fn execute_operation(
         &mut self,
         context: OperationContext,
@@ -373,6 +602,58 @@ where
     ) -> Result<(), ExecutionError> {
         assert_eq!(context.chain_id, self.context().extra().chain_id());
         match message {
+            Message::System(SystemMessage::Event { event_id, value }) => {
+                self.process_event(
+                    context,
+                    local_time,
+                    event_id,
+                    value,
+                    txn_tracker,
+                    resource_controller,
+                )
+                .await?;
+            }
+            Message::System(SystemMessage::Response {
+                application_id,
+                request_id,
+                response,
+                ..
+            }) => {
+                // Responses to requests that were already answered or timed out are ignored.
+                let responder_id = context.message_id.chain_id;
+                let Some(outstanding) = self.system.outstanding_requests.get(&request_id).await?
+                else {
+                    return Ok(());
+                };
+                if outstanding.application_id != application_id
+                    || outstanding.recipient != responder_id
+                {
+                    return Ok(());
+                }
+                self.system.outstanding_requests.remove(&request_id)?;
+                self.system.request_deadlines.remove(&RequestDeadline {
+                    deadline: outstanding.deadline,
+                    request_id,
+                })?;
+                self.run_user_action(
+                    application_id,
+                    context.chain_id,
+                    local_time,
+                    UserAction::HandleResponse(context, request_id, outstanding.request, response),
+                    context.refund_grant_to,
+                    grant,
+                    txn_tracker,
+                    resource_controller,
+                )
+                .await?;
+            }
+            Message::System(SystemMessage::RequestTimeout) => {
+                // Only the chain that sent the requests can time them out.
+                if context.message_id.chain_id == context.chain_id {
+                    self.time_out_requests(context, local_time, txn_tracker, resource_controller)
+                        .await?;
+                }
+            }
             Message::System(message) => {
                 let outcome = self
                     .system
@@ -400,6 +681,38 @@ where
         Ok(())
     }
 
+    /// Hands over a new event to the applications subscribed to its stream.
+    async fn process_event(
+        &mut self,
+        context: MessageContext,
+        local_time: Timestamp,
+        event_id: EventId,
+        value: Vec<u8>,
+        txn_tracker: &mut TransactionTracker,
+        resource_controller: &mut ResourceController<Option<Owner>>,
+    ) -> Result<(), ExecutionError> {
+        let subscribers = self
+            .system
+            .event_subscriptions
+            .get(&(event_id.chain_id, event_id.stream_id.clone()))
+            .await?
+            .unwrap_or_default();
+        for application_id in subscribers {
+            self.run_user_action(
+                application_id,
+                context.chain_id,
+                local_time,
+                UserAction::ProcessEvent(context, event_id.clone(), value.clone()),
+                context.refund_grant_to,
+                None,
+                txn_tracker,
+                resource_controller,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
     pub async fn bounce_message(
         &self,
         context: MessageContext,
@@ -419,11 +732,38 @@ where
                     destination: Destination::Recipient(context.message_id.chain_id),
                     authenticated: true,
                     grant,
+                    not_before: None,
                     kind: MessageKind::Bouncing,
                     message,
                 });
                 txn_tracker.add_system_outcome(outcome)?;
             }
+            Message::User {
+                application_id,
+                bytes,
+            } if context.is_request => {
+                // Instead of bouncing the request, notify the sender that it failed. Like the
+                // response itself, this can't be rejected.
+                let mut outcome = RawExecutionOutcome {
+                    authenticated_signer: context.authenticated_signer,
+                    refund_grant_to: context.refund_grant_to,
+                    ..Default::default()
+                };
+                outcome.messages.push(RawOutgoingMessage {
+                    destination: Destination::Recipient(context.message_id.chain_id),
+                    authenticated: true,
+                    grant,
+                    not_before: None,
+                    kind: MessageKind::Protected,
+                    message: SystemMessage::Response {
+                        application_id,
+                        request_id: context.message_id,
+                        request: bytes,
+                        response: None,
+                    },
+                });
+                txn_tracker.add_system_outcome(outcome)?;
+            }
             Message::User {
                 application_id,
                 bytes,
@@ -437,6 +777,7 @@ where
                     destination: Destination::Recipient(context.message_id.chain_id),
                     authenticated: true,
                     grant,
+                    not_before: None,
                     kind: MessageKind::Bouncing,
                     message: bytes,
                 });
@@ -459,6 +800,7 @@ where
             destination: Destination::Recipient(account.chain_id),
             authenticated: false,
             grant: Amount::ZERO,
+            not_before: None,
             kind: MessageKind::Tracked,
             message: SystemMessage::Credit {
                 amount,
@@ -13,10 +13,10 @@ use linera_base::prometheus_util::{bucket_latencies, register_histogram_vec, Mea
 use linera_base::{
     data_types::{Amount, ApplicationPermissions, BlobContent, Timestamp},
     hex_debug, hex_vec_debug,
-    identifiers::{Account, AccountOwner, BlobId, MessageId, Owner},
+    identifiers::{Account, AccountOwner, BlobId, ChainId, EventId, MessageId, Owner, StreamId},
     ownership::ChainOwnership,
 };
-use linera_views::{batch::Batch, context::Context, views::View};
+use linera_views::{batch::Batch, context::Context, store::KeyRange, views::View};
 use oneshot::Sender;
 #[cfg(with_metrics)]
 use prometheus::HistogramVec;
@@ -59,19 +59,33 @@ where
     C: Context + Clone + Send + Sync + 'static,
     C::Extra: ExecutionRuntimeContext,
 {
+    /// Loads the current contract code of an application.
+    ///
+    /// Also returns whether the application was upgraded and still needs to migrate its state
+    /// on this chain. Migration is expected to happen right away, so it is no longer pending
+    /// afterwards.
     pub(crate) async fn load_contract(
         &mut self,
         id: UserApplicationId,
-    ) -> Result<(UserContractCode, UserApplicationDescription), ExecutionError> {
+    ) -> Result<(UserContractCode, UserApplicationDescription, bool), ExecutionError> {
         #[cfg(with_metrics)]
         let _latency = LOAD_CONTRACT_LATENCY.measure_latency();
-        let description = self.system.registry.describe_application(id).await?;
+        let description = self.system.registry.describe_current_code(id).await?;
         let code = self
             .context()
             .extra()
             .get_user_contract(&description)
             .await?;
-        Ok((code, description))
+        let needs_migration = self
+            .system
+            .registry
+            .pending_migrations
+            .contains(&id)
+            .await?;
+        if needs_migration {
+            self.system.registry.pending_migrations.remove(&id)?;
+        }
+        Ok((code, description, needs_migration))
     }
 
     pub(crate) async fn load_service(
@@ -80,7 +94,7 @@ where
     ) -> Result<(UserServiceCode, UserApplicationDescription), ExecutionError> {
         #[cfg(with_metrics)]
         let _latency = LOAD_SERVICE_LATENCY.measure_latency();
-        let description = self.system.registry.describe_application(id).await?;
+        let description = self.system.registry.describe_current_code(id).await?;
         let code = self
             .context()
             .extra()
@@ -245,14 +259,54 @@ where
                 callback.respond(result);
             }
 
+            FindKeysInRange {
+                id,
+                key_prefix,
+                range,
+                callback,
+            } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => view
+                        .find_keys_in_range(&KeyRange::prefixed(&key_prefix, &range))
+                        .await?
+                        .into_iter()
+                        .map(|key| key[key_prefix.len()..].to_vec())
+                        .collect(),
+                    None => Vec::new(),
+                };
+                callback.respond(result);
+            }
+
+            FindKeyValuesInRange {
+                id,
+                key_prefix,
+                range,
+                callback,
+            } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => view
+                        .find_key_values_in_range(&KeyRange::prefixed(&key_prefix, &range))
+                        .await?
+                        .into_iter()
+                        .map(|(key, value)| (key[key_prefix.len()..].to_vec(), value))
+                        .collect(),
+                    None => Vec::new(),
+                };
+                callback.respond(result);
+            }
+
             WriteBatch {
                 id,
                 batch,
                 callback,
             } => {
                 let mut view = self.users.try_load_entry_mut(&id).await?;
+                let stored_bytes_before = view.total_size().sum_i32()?;
                 view.write_batch(batch).await?;
-                callback.respond(());
+                let stored_bytes_after = view.total_size().sum_i32()?;
+                callback.respond(stored_bytes_after - stored_bytes_before);
             }
 
             OpenChain {
@@ -303,6 +357,7 @@ where
                         bytecode_id,
                         parameters,
                         required_application_ids,
+                        /* admin */ None,
                     )
                     .await?;
                 callback.respond(Ok(create_application_result));
@@ -340,6 +395,54 @@ where
                 self.system.assert_blob_exists(blob_id).await?;
                 callback.respond(self.system.blob_used(None, blob_id).await?)
             }
+
+            ReadEvent { event_id, callback } => {
+                let value = self.context().extra().get_event(event_id).await?;
+                callback.respond(value);
+            }
+
+            NotifyEventSubscribers {
+                event_id,
+                value,
+                callback,
+            } => {
+                let messages = self.system.event_messages(event_id, value).await?;
+                let outcome = RawExecutionOutcome {
+                    messages,
+                    ..RawExecutionOutcome::default()
+                };
+                callback.respond(outcome);
+            }
+
+            SubscribeToEvents {
+                chain_id,
+                stream_id,
+                subscriber_id,
+                callback,
+            } => {
+                let mut outcome = RawExecutionOutcome::default();
+                let message = self
+                    .system
+                    .subscribe_to_events(chain_id, stream_id, subscriber_id)
+                    .await?;
+                outcome.messages.extend(message);
+                callback.respond(outcome);
+            }
+
+            UnsubscribeFromEvents {
+                chain_id,
+                stream_id,
+                subscriber_id,
+                callback,
+            } => {
+                let mut outcome = RawExecutionOutcome::default();
+                let message = self
+                    .system
+                    .unsubscribe_from_events(chain_id, stream_id, subscriber_id)
+                    .await?;
+                outcome.messages.extend(message);
+                callback.respond(outcome);
+            }
         }
 
         Ok(())
@@ -353,7 +456,7 @@ pub enum ExecutionRequest {
     LoadContract {
         id: UserApplicationId,
         #[debug(skip)]
-        callback: Sender<(UserContractCode, UserApplicationDescription)>,
+        callback: Sender<(UserContractCode, UserApplicationDescription, bool)>,
     },
 
     #[cfg(not(web))]
@@ -463,11 +566,29 @@ pub enum ExecutionRequest {
         callback: Sender<Vec<(Vec<u8>, Vec<u8>)>>,
     },
 
+    FindKeysInRange {
+        id: UserApplicationId,
+        #[debug(with = hex_debug)]
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+        #[debug(skip)]
+        callback: Sender<Vec<Vec<u8>>>,
+    },
+
+    FindKeyValuesInRange {
+        id: UserApplicationId,
+        #[debug(with = hex_debug)]
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+        #[debug(skip)]
+        callback: Sender<Vec<(Vec<u8>, Vec<u8>)>>,
+    },
+
     WriteBatch {
         id: UserApplicationId,
         batch: Batch,
         #[debug(skip)]
-        callback: Sender<()>,
+        callback: Sender<i32>,
     },
 
     OpenChain {
@@ -521,4 +642,34 @@ pub enum ExecutionRequest {
         #[debug(skip)]
         callback: Sender<bool>,
     },
+
+    ReadEvent {
+        event_id: EventId,
+        #[debug(skip)]
+        callback: Sender<Vec<u8>>,
+    },
+
+    NotifyEventSubscribers {
+        event_id: EventId,
+        #[debug(with = hex_debug)]
+        value: Vec<u8>,
+        #[debug(skip)]
+        callback: Sender<RawExecutionOutcome<SystemMessage, Amount>>,
+    },
+
+    SubscribeToEvents {
+        chain_id: ChainId,
+        stream_id: StreamId,
+        subscriber_id: UserApplicationId,
+        #[debug(skip)]
+        callback: Sender<RawExecutionOutcome<SystemMessage, Amount>>,
+    },
+
+    UnsubscribeFromEvents {
+        chain_id: ChainId,
+        stream_id: StreamId,
+        subscriber_id: UserApplicationId,
+        #[debug(skip)]
+        callback: Sender<RawExecutionOutcome<SystemMessage, Amount>>,
+    },
 }
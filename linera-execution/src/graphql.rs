@@ -13,6 +13,7 @@ use linera_views::{context::Context, map_view::MapView};
 
 use crate::{
     committee::{Committee, Epoch, ValidatorName, ValidatorState},
+    execution::ApplicationStorageUsage,
     system::{Recipient, UserData},
     ChannelSubscription, ExecutionStateView, SystemExecutionStateView,
 };
@@ -54,6 +55,12 @@ impl<C: Send + Sync + Context> ExecutionStateView<C> {
     async fn _system(&self) -> &SystemExecutionStateView<C> {
         &self.system
     }
+
+    /// The number of bytes currently stored by each user application.
+    #[graphql(derived(name = "storage_usage"))]
+    async fn _storage_usage(&self) -> Result<Vec<ApplicationStorageUsage>, async_graphql::Error> {
+        Ok(self.storage_usage().await?)
+    }
 }
 
 #[async_graphql::Object(cache_control(no_cache))]
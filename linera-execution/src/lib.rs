@@ -42,12 +42,13 @@ use linera_base::{
     doc_scalar, hex_debug,
     identifiers::{
         Account, AccountOwner, ApplicationId, BlobId, BytecodeId, ChainId, ChannelName,
-        Destination, GenericApplicationId, MessageId, Owner, StreamName, UserApplicationId,
+        Destination, EventId, GenericApplicationId, MessageId, Owner, StreamName,
+        UserApplicationId,
     },
     ownership::ChainOwnership,
     task,
 };
-use linera_views::{batch::Batch, views::ViewError};
+use linera_views::{batch::Batch, store::KeyRange, views::ViewError};
 use serde::{Deserialize, Serialize};
 use system::OpenChainConfig;
 use thiserror::Error;
@@ -63,8 +64,8 @@ pub use crate::wasm::{
     ViewSystemApi, WasmContractModule, WasmExecutionError, WasmServiceModule,
 };
 pub use crate::{
-    applications::ApplicationRegistryView,
-    execution::{ExecutionStateView, ServiceRuntimeEndpoint},
+    applications::{ApplicationRegistryView, ApplicationUpgrade},
+    execution::{ApplicationStorageUsage, ExecutionStateView, ServiceRuntimeEndpoint},
     execution_state_actor::ExecutionRequest,
     policy::ResourceControlPolicy,
     resources::{ResourceController, ResourceTracker},
@@ -261,6 +262,10 @@ pub enum ExecutionError {
     EventKeyTooLong,
     #[error("Stream names can be at most {MAX_STREAM_NAME_LEN} bytes.")]
     StreamNameTooLong,
+    #[error("Requests can only be sent to a single recipient chain")]
+    RequestToSubscribers,
+    #[error("There is no pending request for the application to respond to")]
+    NoRequestToRespondTo,
     // TODO(#2127): Remove this error and the unstable-oracles feature once there are fees
     // and enforced limits for all oracles.
     #[error("Unstable oracles are disabled on this network.")]
@@ -271,12 +276,15 @@ pub enum ExecutionError {
     ServiceModuleSend(#[from] linera_base::task::SendError<UserServiceCode>),
     #[error("Blobs not found: {0:?}")]
     BlobsNotFound(Vec<BlobId>),
+    #[error("Events not found: {0:?}")]
+    EventsNotFound(Vec<EventId>),
 }
 
 impl From<ViewError> for ExecutionError {
     fn from(error: ViewError) -> Self {
         match error {
             ViewError::BlobsNotFound(blob_ids) => ExecutionError::BlobsNotFound(blob_ids),
+            ViewError::EventsNotFound(event_ids) => ExecutionError::EventsNotFound(event_ids),
             error => ExecutionError::ViewError(error),
         }
     }
@@ -316,6 +324,28 @@ pub trait UserContract {
         message: Vec<u8>,
     ) -> Result<(), ExecutionError>;
 
+    /// Handles a new event on a stream the application subscribed to.
+    fn process_event(
+        &mut self,
+        context: MessageContext,
+        event_id: EventId,
+        value: Vec<u8>,
+    ) -> Result<(), ExecutionError>;
+
+    /// Handles the response to a request that the application sent, or `None` if the request
+    /// was rejected by the receiving chain or timed out.
+    fn handle_response(
+        &mut self,
+        context: MessageContext,
+        request_id: MessageId,
+        request: Vec<u8>,
+        response: Option<Vec<u8>>,
+    ) -> Result<(), ExecutionError>;
+
+    /// Migrates the application state after an upgrade, before the new code first runs on
+    /// the current chain.
+    fn migrate(&mut self) -> Result<(), ExecutionError>;
+
     /// Finishes execution of the current transaction.
     fn finalize(&mut self, context: FinalizeContext) -> Result<(), ExecutionError>;
 }
@@ -378,11 +408,19 @@ pub trait ExecutionRuntimeContext {
 
     async fn contains_blob(&self, blob_id: BlobId) -> Result<bool, ViewError>;
 
+    async fn get_event(&self, event_id: EventId) -> Result<Vec<u8>, ViewError>;
+
     #[cfg(with_testing)]
     async fn add_blobs(
         &self,
         blobs: impl IntoIterator<Item = Blob> + Send,
     ) -> Result<(), ViewError>;
+
+    #[cfg(with_testing)]
+    async fn add_events(
+        &self,
+        events: impl IntoIterator<Item = (EventId, Vec<u8>)> + Send,
+    ) -> Result<(), ViewError>;
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -409,6 +447,8 @@ pub struct MessageContext {
     pub chain_id: ChainId,
     /// Whether the message was rejected by the original receiver and is now bouncing back.
     pub is_bouncing: bool,
+    /// Whether the message is a request that the receiving application may respond to.
+    pub is_request: bool,
     /// The authenticated signer of the operation that created the message, if any.
     #[debug(skip_if = Option::is_none)]
     pub authenticated_signer: Option<Owner>,
@@ -572,6 +612,14 @@ pub trait BaseRuntime {
         promise: &Self::FindKeysByPrefix,
     ) -> Result<Vec<Vec<u8>>, ExecutionError>;
 
+    /// Creates the promise to access the keys of a range, after a specific prefix. The
+    /// promise is resolved with [`BaseRuntime::find_keys_by_prefix_wait`].
+    fn find_keys_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+    ) -> Result<Self::FindKeysByPrefix, ExecutionError>;
+
     /// Reads the data from the key/values having a specific prefix.
     #[cfg(feature = "test")]
     #[expect(clippy::type_complexity)]
@@ -589,6 +637,14 @@ pub trait BaseRuntime {
         key_prefix: Vec<u8>,
     ) -> Result<Self::FindKeyValuesByPrefix, ExecutionError>;
 
+    /// Creates the promise to access the key/values of a range, after a specific prefix.
+    /// The promise is resolved with [`BaseRuntime::find_key_values_by_prefix_wait`].
+    fn find_key_values_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+    ) -> Result<Self::FindKeyValuesByPrefix, ExecutionError>;
+
     /// Resolves the promise to access key/values having a specific prefix
     #[expect(clippy::type_complexity)]
     fn find_key_values_by_prefix_wait(
@@ -623,6 +679,9 @@ pub trait BaseRuntime {
 
     /// Asserts the existence of a data blob with the given hash.
     fn assert_data_blob_exists(&mut self, hash: &CryptoHash) -> Result<(), ExecutionError>;
+
+    /// Reads the value of an event emitted on another chain.
+    fn read_event(&mut self, event_id: EventId) -> Result<Vec<u8>, ExecutionError>;
 }
 
 pub trait ServiceRuntime: BaseRuntime {
@@ -661,6 +720,22 @@ pub trait ContractRuntime: BaseRuntime {
     /// Schedules a message to be sent.
     fn send_message(&mut self, message: SendMessageRequest<Vec<u8>>) -> Result<(), ExecutionError>;
 
+    /// Schedules a message to be sent, that can only be received in a block whose timestamp
+    /// is at least `not_before`.
+    fn send_delayed_message(
+        &mut self,
+        message: SendMessageRequest<Vec<u8>>,
+        not_before: Timestamp,
+    ) -> Result<(), ExecutionError>;
+
+    /// Schedules a request to be sent to this application on another chain. The response, or
+    /// the failure if the request is rejected or times out, is handed over to the
+    /// `handle_response` entrypoint.
+    fn send_request(&mut self, request: SendMessageRequest<Vec<u8>>) -> Result<(), ExecutionError>;
+
+    /// Responds to the request currently being executed.
+    fn respond(&mut self, response: Vec<u8>) -> Result<(), ExecutionError>;
+
     /// Schedules to subscribe to some `channel` on a `chain`.
     fn subscribe(&mut self, chain: ChainId, channel: ChannelName) -> Result<(), ExecutionError>;
 
@@ -700,6 +775,23 @@ pub trait ContractRuntime: BaseRuntime {
         value: Vec<u8>,
     ) -> Result<(), ExecutionError>;
 
+    /// Subscribes the current application to an event stream of another chain. New events
+    /// will be delivered to the application's `process_event` entrypoint.
+    fn subscribe_to_events(
+        &mut self,
+        chain_id: ChainId,
+        application_id: UserApplicationId,
+        name: StreamName,
+    ) -> Result<(), ExecutionError>;
+
+    /// Unsubscribes the current application from an event stream of another chain.
+    fn unsubscribe_from_events(
+        &mut self,
+        chain_id: ChainId,
+        application_id: UserApplicationId,
+        name: StreamName,
+    ) -> Result<(), ExecutionError>;
+
     /// Opens a new chain.
     fn open_chain(
         &mut self,
@@ -791,6 +883,8 @@ pub struct RawOutgoingMessage<Message, Grant = Resources> {
     pub authenticated: bool,
     /// The grant needed for message execution, typically specified as an `Amount` or as `Resources`.
     pub grant: Grant,
+    /// The earliest time at which the message can be received, if any.
+    pub not_before: Option<Timestamp>,
     /// The kind of outgoing message being sent.
     pub kind: MessageKind,
     /// The message itself.
@@ -817,6 +911,7 @@ impl<Message> From<SendMessageRequest<Message>> for RawOutgoingMessage<Message,
             destination,
             authenticated,
             grant,
+            not_before: None,
             kind,
             message,
         }
@@ -836,6 +931,10 @@ pub enum MessageKind {
     Tracked,
     /// This message is a receipt automatically created when the original message was rejected.
     Bouncing,
+    /// The message is tracked like [`MessageKind::Tracked`], and the receiving application may
+    /// respond to it. If it is rejected, the sender gets a failed response instead of the
+    /// message itself.
+    Request,
 }
 
 /// Externally visible results of an execution. These results are meant in the context of
@@ -892,6 +991,21 @@ impl ExecutionOutcome {
             ExecutionOutcome::User(_, outcome) => outcome.messages.len(),
         }
     }
+
+    /// Returns whether any of the messages is sent directly to the given chain.
+    pub fn has_message_to(&self, chain_id: ChainId) -> bool {
+        let destination = Destination::Recipient(chain_id);
+        match self {
+            ExecutionOutcome::System(outcome) => outcome
+                .messages
+                .iter()
+                .any(|message| message.destination == destination),
+            ExecutionOutcome::User(_, outcome) => outcome
+                .messages
+                .iter()
+                .any(|message| message.destination == destination),
+        }
+    }
 }
 
 impl<Message, Grant> RawExecutionOutcome<Message, Grant> {
@@ -934,6 +1048,7 @@ impl<Message> RawOutgoingMessage<Message, Resources> {
             destination,
             authenticated,
             grant,
+            not_before,
             kind,
             message,
         } = self;
@@ -941,6 +1056,7 @@ impl<Message> RawOutgoingMessage<Message, Resources> {
             destination,
             authenticated,
             grant: policy.total_price(&grant)?,
+            not_before,
             kind,
             message,
         })
@@ -1000,6 +1116,7 @@ pub struct TestExecutionRuntimeContext {
     user_contracts: Arc<DashMap<UserApplicationId, UserContractCode>>,
     user_services: Arc<DashMap<UserApplicationId, UserServiceCode>>,
     blobs: Arc<DashMap<BlobId, Blob>>,
+    events: Arc<DashMap<EventId, Vec<u8>>>,
 }
 
 #[cfg(with_testing)]
@@ -1011,6 +1128,7 @@ impl TestExecutionRuntimeContext {
             user_contracts: Arc::default(),
             user_services: Arc::default(),
             blobs: Arc::default(),
+            events: Arc::default(),
         }
     }
 }
@@ -1075,6 +1193,14 @@ impl ExecutionRuntimeContext for TestExecutionRuntimeContext {
         Ok(self.blobs.contains_key(&blob_id))
     }
 
+    async fn get_event(&self, event_id: EventId) -> Result<Vec<u8>, ViewError> {
+        Ok(self
+            .events
+            .get(&event_id)
+            .ok_or_else(|| ViewError::EventsNotFound(vec![event_id]))?
+            .clone())
+    }
+
     #[cfg(with_testing)]
     async fn add_blobs(
         &self,
@@ -1086,6 +1212,18 @@ impl ExecutionRuntimeContext for TestExecutionRuntimeContext {
 
         Ok(())
     }
+
+    #[cfg(with_testing)]
+    async fn add_events(
+        &self,
+        events: impl IntoIterator<Item = (EventId, Vec<u8>)> + Send,
+    ) -> Result<(), ViewError> {
+        for (event_id, value) in events {
+            self.events.insert(event_id, value);
+        }
+
+        Ok(())
+    }
 }
 
 impl From<SystemOperation> for Operation {
@@ -24,8 +24,8 @@ pub struct ResourceControlPolicy {
     pub byte_read: Amount,
     /// The price of writing a byte
     pub byte_written: Amount,
-    /// The price of increasing storage by a byte.
-    // TODO(#1536): This is not fully supported.
+    /// The price of increasing storage by a byte. The amount paid is refunded to the same
+    /// accounts when the byte is freed.
     pub byte_stored: Amount,
     /// The base price of adding an operation to a block.
     pub operation: Amount,
@@ -36,8 +36,6 @@ pub struct ResourceControlPolicy {
     /// The additional price for each byte in the argument of a user message.
     pub message_byte: Amount,
 
-    // TODO(#1538): Cap the number of transactions per block and the total size of their
-    // arguments.
     /// The maximum amount of fuel a block can consume.
     pub maximum_fuel_per_block: u64,
     /// The maximum size of an executed block. This includes the block proposal itself as well as
@@ -53,6 +51,12 @@ pub struct ResourceControlPolicy {
     pub maximum_bytes_read_per_block: u64,
     /// The maximum data to write per block
     pub maximum_bytes_written_per_block: u64,
+    /// The maximum number of operations in a block.
+    pub maximum_operations_per_block: u64,
+    /// The maximum number of incoming message bundles in a block.
+    pub maximum_incoming_bundles_per_block: u64,
+    /// The maximum total size of the arguments of the user operations in a block.
+    pub maximum_operation_argument_bytes: u64,
 }
 
 impl fmt::Display for ResourceControlPolicy {
@@ -76,6 +80,9 @@ impl fmt::Display for ResourceControlPolicy {
             maximum_block_proposal_size,
             maximum_bytes_read_per_block,
             maximum_bytes_written_per_block,
+            maximum_operations_per_block,
+            maximum_incoming_bundles_per_block,
+            maximum_operation_argument_bytes,
         } = self;
         write!(
             f,
@@ -97,7 +104,10 @@ impl fmt::Display for ResourceControlPolicy {
             {maximum_bytecode_size} maximum size of service and contract bytecode\n\
             {maximum_block_proposal_size} maximum size of a block proposal\n\
             {maximum_bytes_read_per_block} maximum number bytes read per block\n\
-            {maximum_bytes_written_per_block} maximum number bytes written per block",
+            {maximum_bytes_written_per_block} maximum number bytes written per block\n\
+            {maximum_operations_per_block} maximum number of operations per block\n\
+            {maximum_incoming_bundles_per_block} maximum number of incoming bundles per block\n\
+            {maximum_operation_argument_bytes} maximum size of the operation arguments per block",
         )
     }
 }
@@ -123,6 +133,9 @@ impl Default for ResourceControlPolicy {
             maximum_block_proposal_size: u64::MAX,
             maximum_bytes_read_per_block: u64::MAX,
             maximum_bytes_written_per_block: u64::MAX,
+            maximum_operations_per_block: u64::MAX,
+            maximum_incoming_bundles_per_block: u64::MAX,
+            maximum_operation_argument_bytes: u64::MAX,
         }
     }
 }
@@ -169,8 +182,6 @@ impl ResourceControlPolicy {
         self.byte_written.try_mul(count as u128)
     }
 
-    // TODO(#1536): This is not fully implemented.
-    #[allow(dead_code)]
     pub(crate) fn bytes_stored_price(&self, count: u64) -> Result<Amount, ArithmeticError> {
         self.byte_stored.try_mul(count as u128)
     }
@@ -179,6 +190,11 @@ impl ResourceControlPolicy {
         self.fuel_unit.try_mul(u128::from(fuel))
     }
 
+    /// Returns how many bytes can be stored with the given balance.
+    pub(crate) fn affordable_bytes_stored(&self, balance: Amount) -> u64 {
+        u64::try_from(balance.saturating_div(self.byte_stored)).unwrap_or(u64::MAX)
+    }
+
     /// Returns how much fuel can be paid with the given balance.
     pub(crate) fn remaining_fuel(&self, balance: Amount) -> u64 {
         u64::try_from(balance.saturating_div(self.fuel_unit)).unwrap_or(u64::MAX)
@@ -245,6 +261,9 @@ impl ResourceControlPolicy {
             maximum_block_proposal_size: 13_000_000,
             maximum_bytes_read_per_block: 100_000_000,
             maximum_bytes_written_per_block: 10_000_000,
+            maximum_operations_per_block: u64::MAX,
+            maximum_incoming_bundles_per_block: u64::MAX,
+            maximum_operation_argument_bytes: u64::MAX,
         }
     }
 }
@@ -3,20 +3,21 @@
 
 //! This module tracks the resources used during the execution of a transaction.
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
+use async_graphql::SimpleObject;
 use custom_debug_derive::Debug;
 use linera_base::{
     data_types::{Amount, ArithmeticError},
     ensure,
-    identifiers::{AccountOwner, Owner},
+    identifiers::{AccountOwner, Owner, UserApplicationId},
 };
 use linera_views::{context::Context, views::ViewError};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    system::SystemExecutionError, ExecutionError, ExecutionStateView, Message, Operation,
-    ResourceControlPolicy,
+    system::{StorageDeposit, SystemExecutionError},
+    ExecutionError, ExecutionStateView, Message, Operation, ResourceControlPolicy,
 };
 
 #[derive(Clone, Debug, Default)]
@@ -30,7 +31,7 @@ pub struct ResourceController<Account = Amount, Tracker = ResourceTracker> {
 }
 
 /// The resources used so far by an execution process.
-#[derive(Copy, Debug, Clone, Default)]
+#[derive(Copy, Debug, Clone, Default, Serialize, Deserialize, SimpleObject)]
 pub struct ResourceTracker {
     /// The number of blocks created.
     pub blocks: u32,
@@ -58,6 +59,8 @@ pub struct ResourceTracker {
     pub message_bytes: u64,
     /// The amount allocated to message grants.
     pub grants: Amount,
+    /// The fees charged so far, net of storage refunds. This does not include `grants`.
+    pub fees: Amount,
 }
 
 /// How to access the balance of an account.
@@ -97,9 +100,16 @@ where
         Ok(())
     }
 
-    /// Subtracts an amount from a balance and reports an error if that is impossible.
+    /// Charges fees to the account and reports an error if that is impossible.
     fn update_balance(&mut self, fees: Amount) -> Result<(), ExecutionError> {
-        self.account.try_sub_assign(fees).map_err(|_| {
+        self.debit(fees)?;
+        self.tracker.as_mut().fees.try_add_assign(fees)?;
+        Ok(())
+    }
+
+    /// Subtracts an amount from a balance and reports an error if that is impossible.
+    fn debit(&mut self, amount: Amount) -> Result<(), ExecutionError> {
+        self.account.try_sub_assign(amount).map_err(|_| {
             SystemExecutionError::InsufficientFundingForFees {
                 balance: self.balance().unwrap_or(Amount::MAX),
             }
@@ -121,7 +131,7 @@ where
     /// Tracks the allocation of a grant.
     pub fn track_grant(&mut self, grant: Amount) -> Result<(), ExecutionError> {
         self.tracker.as_mut().grants.try_add_assign(grant)?;
-        self.update_balance(grant)
+        self.debit(grant)
     }
 
     /// Tracks the creation of a block.
@@ -252,9 +262,8 @@ where
         Ok(())
     }
 
-    /// Tracks a change in the number of bytes stored.
-    // TODO(#1536): This is not fully implemented.
-    #[allow(dead_code)]
+    /// Tracks a change in the number of bytes stored. The corresponding fee (or refund) is
+    /// only settled at the end of the block, by `track_storage_rent`.
     pub(crate) fn track_stored_bytes(&mut self, delta: i32) -> Result<(), ExecutionError> {
         self.tracker.as_mut().bytes_stored = self
             .tracker
@@ -428,4 +437,137 @@ impl ResourceController<Option<Owner>, ResourceTracker> {
             account: Sources { sources },
         })
     }
+
+    /// Charges for the growth of the storage used by each application in the block, and
+    /// records the deposits by application and payer. The chain's balance is charged first,
+    /// then the authenticated signer's.
+    ///
+    /// If an application freed storage instead, its own deposits are refunded to the
+    /// accounts that paid them, regardless of the current price: the chain's deposit first,
+    /// then the owners'.
+    pub async fn track_storage_rent<C>(
+        &mut self,
+        view: &mut ExecutionStateView<C>,
+        stored_bytes: &BTreeMap<UserApplicationId, i32>,
+    ) -> Result<(), ExecutionError>
+    where
+        C: Context + Clone + Send + Sync + 'static,
+    {
+        for (application_id, delta) in stored_bytes {
+            let bytes = u64::from(delta.unsigned_abs());
+            if *delta < 0 {
+                self.refund_storage_deposits(view, *application_id, bytes)
+                    .await?;
+            } else if *delta > 0 {
+                self.charge_storage_deposit(view, *application_id, bytes)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Charges for `bytes` new bytes stored by the application.
+    async fn charge_storage_deposit<C>(
+        &mut self,
+        view: &mut ExecutionStateView<C>,
+        application_id: UserApplicationId,
+        bytes: u64,
+    ) -> Result<(), ExecutionError>
+    where
+        C: Context + Clone + Send + Sync + 'static,
+    {
+        let price = self.policy.bytes_stored_price(bytes)?;
+        if price == Amount::ZERO {
+            return Ok(());
+        }
+        let system = &mut view.system;
+        let mut deposits = system
+            .storage_deposits
+            .get(&application_id)
+            .await?
+            .unwrap_or_default();
+        // The chain pays for as many bytes as it can afford, and the signer for the rest.
+        let chain_balance = *system.balance.get();
+        let chain_bytes = bytes.min(self.policy.affordable_bytes_stored(chain_balance));
+        let owner_bytes = bytes - chain_bytes;
+        if owner_bytes > 0 {
+            let owner_amount = self.policy.bytes_stored_price(owner_bytes)?;
+            let owner_balance = match self.account {
+                Some(owner) => system.balances.get_mut(&AccountOwner::User(owner)).await?,
+                None => None,
+            };
+            let (Some(owner), Some(owner_balance)) = (
+                self.account,
+                owner_balance.filter(|balance| **balance >= owner_amount),
+            ) else {
+                return Err(SystemExecutionError::InsufficientFundingForFees {
+                    balance: chain_balance,
+                }
+                .into());
+            };
+            owner_balance.try_sub_assign(owner_amount)?;
+            deposits
+                .entry(Some(owner))
+                .or_default()
+                .try_add_assign(StorageDeposit {
+                    bytes: owner_bytes,
+                    amount: owner_amount,
+                })?;
+        }
+        if chain_bytes > 0 {
+            let chain_amount = self.policy.bytes_stored_price(chain_bytes)?;
+            system.balance.get_mut().try_sub_assign(chain_amount)?;
+            deposits
+                .entry(None)
+                .or_default()
+                .try_add_assign(StorageDeposit {
+                    bytes: chain_bytes,
+                    amount: chain_amount,
+                })?;
+        }
+        system.storage_deposits.insert(&application_id, deposits)?;
+        self.tracker.fees.try_add_assign(price)?;
+        Ok(())
+    }
+
+    /// Refunds the deposits for `bytes` bytes freed by the application.
+    async fn refund_storage_deposits<C>(
+        &mut self,
+        view: &mut ExecutionStateView<C>,
+        application_id: UserApplicationId,
+        mut bytes: u64,
+    ) -> Result<(), ExecutionError>
+    where
+        C: Context + Clone + Send + Sync + 'static,
+    {
+        let system = &mut view.system;
+        let Some(mut deposits) = system.storage_deposits.get(&application_id).await? else {
+            return Ok(()); // These bytes were stored before storage rent was charged.
+        };
+        let mut refunded = Amount::ZERO;
+        for (payer, deposit) in &mut deposits {
+            if bytes == 0 {
+                break;
+            }
+            let refund = deposit.split_off(bytes);
+            bytes -= refund.bytes;
+            match payer {
+                None => system.balance.get_mut().try_add_assign(refund.amount)?,
+                Some(owner) => system
+                    .balances
+                    .get_mut_or_default(&AccountOwner::User(*owner))
+                    .await?
+                    .try_add_assign(refund.amount)?,
+            }
+            refunded.try_add_assign(refund.amount)?;
+        }
+        deposits.retain(|_, deposit| deposit.bytes > 0);
+        if deposits.is_empty() {
+            system.storage_deposits.remove(&application_id)?;
+        } else {
+            system.storage_deposits.insert(&application_id, deposits)?;
+        }
+        self.tracker.fees = self.tracker.fees.saturating_sub(refunded);
+        Ok(())
+    }
 }
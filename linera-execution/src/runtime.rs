@@ -17,12 +17,12 @@ use linera_base::{
     },
     ensure,
     identifiers::{
-        Account, AccountOwner, ApplicationId, BlobId, BlobType, ChainId, ChannelName, MessageId,
-        Owner, StreamName,
+        Account, AccountOwner, ApplicationId, BlobId, BlobType, ChainId, ChannelName, Destination,
+        EventId, MessageId, Owner, StreamId, StreamName,
     },
     ownership::ChainOwnership,
 };
-use linera_views::batch::Batch;
+use linera_views::{batch::Batch, store::KeyRange};
 use oneshot::Receiver;
 
 use crate::{
@@ -32,9 +32,10 @@ use crate::{
     system::CreateApplicationResult,
     util::{ReceiverExt, UnboundedSenderExt},
     BaseRuntime, BytecodeId, ContractRuntime, ExecutionError, FinalizeContext, MessageContext,
-    OperationContext, QueryContext, RawExecutionOutcome, ServiceRuntime, TransactionTracker,
-    UserApplicationDescription, UserApplicationId, UserContractCode, UserContractInstance,
-    UserServiceCode, UserServiceInstance, MAX_EVENT_KEY_LEN, MAX_STREAM_NAME_LEN,
+    MessageKind, OperationContext, QueryContext, RawExecutionOutcome, RawOutgoingMessage,
+    ServiceRuntime, SystemMessage, TransactionTracker, UserApplicationDescription,
+    UserApplicationId, UserContractCode, UserContractInstance, UserServiceCode,
+    UserServiceInstance, MAX_EVENT_KEY_LEN, MAX_STREAM_NAME_LEN,
 };
 
 #[cfg(test)]
@@ -73,6 +74,9 @@ pub struct SyncRuntimeInternal<UserInstance> {
     /// The current message being executed, if there is one.
     #[debug(skip_if = Option::is_none)]
     executing_message: Option<ExecutingMessage>,
+    /// The request being executed, until the application responds to it.
+    #[debug(skip_if = Option::is_none)]
+    unanswered_request: Option<Vec<u8>>,
 
     /// How to interact with the storage view of the execution state.
     execution_state_sender: ExecutionStateSender,
@@ -83,6 +87,8 @@ pub struct SyncRuntimeInternal<UserInstance> {
     is_finalizing: bool,
     /// Applications that need to be finalized.
     applications_to_finalize: Vec<UserApplicationId>,
+    /// Upgraded applications that need to migrate their state before they run.
+    applications_to_migrate: HashSet<UserApplicationId>,
 
     /// Application instances loaded in this transaction.
     loaded_applications: HashMap<UserApplicationId, LoadedApplication<UserInstance>>,
@@ -297,9 +303,11 @@ impl<UserInstance> SyncRuntimeInternal<UserInstance> {
             local_time,
             authenticated_signer,
             executing_message,
+            unanswered_request: None,
             execution_state_sender,
             is_finalizing: false,
             applications_to_finalize: Vec::new(),
+            applications_to_migrate: HashSet::new(),
             loaded_applications: HashMap::new(),
             call_stack: Vec::new(),
             active_applications: HashSet::new(),
@@ -393,7 +401,7 @@ impl SyncRuntimeInternal<UserContractInstance> {
             }
             #[cfg(not(web))]
             hash_map::Entry::Vacant(entry) => {
-                let (code, description) = self
+                let (code, description, needs_migration) = self
                     .execution_state_sender
                     .send_request(|callback| ExecutionRequest::LoadContract { id, callback })?
                     .recv_response()?;
@@ -401,6 +409,9 @@ impl SyncRuntimeInternal<UserContractInstance> {
                 let instance = code.instantiate(this)?;
 
                 self.applications_to_finalize.push(id);
+                if needs_migration {
+                    self.applications_to_migrate.insert(id);
+                }
                 Ok(entry
                     .insert(LoadedApplication::new(instance, description))
                     .clone())
@@ -674,6 +685,14 @@ impl<UserInstance> BaseRuntime for SyncRuntimeHandle<UserInstance> {
         self.inner().find_keys_by_prefix_wait(promise)
     }
 
+    fn find_keys_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+    ) -> Result<Self::FindKeysByPrefix, ExecutionError> {
+        self.inner().find_keys_in_range_new(key_prefix, range)
+    }
+
     fn find_key_values_by_prefix_new(
         &mut self,
         key_prefix: Vec<u8>,
@@ -681,6 +700,14 @@ impl<UserInstance> BaseRuntime for SyncRuntimeHandle<UserInstance> {
         self.inner().find_key_values_by_prefix_new(key_prefix)
     }
 
+    fn find_key_values_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+    ) -> Result<Self::FindKeyValuesByPrefix, ExecutionError> {
+        self.inner().find_key_values_in_range_new(key_prefix, range)
+    }
+
     fn find_key_values_by_prefix_wait(
         &mut self,
         promise: &Self::FindKeyValuesByPrefix,
@@ -716,6 +743,10 @@ impl<UserInstance> BaseRuntime for SyncRuntimeHandle<UserInstance> {
     fn assert_data_blob_exists(&mut self, hash: &CryptoHash) -> Result<(), ExecutionError> {
         self.inner().assert_data_blob_exists(hash)
     }
+
+    fn read_event(&mut self, event_id: EventId) -> Result<Vec<u8>, ExecutionError> {
+        self.inner().read_event(event_id)
+    }
 }
 
 impl<UserInstance> BaseRuntime for SyncRuntimeInternal<UserInstance> {
@@ -896,6 +927,25 @@ impl<UserInstance> BaseRuntime for SyncRuntimeInternal<UserInstance> {
         state.find_keys_queries.register(receiver)
     }
 
+    fn find_keys_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+    ) -> Result<Self::FindKeysByPrefix, ExecutionError> {
+        let id = self.application_id()?;
+        let state = self.view_user_states.entry(id).or_default();
+        self.resource_controller.track_read_operations(1)?;
+        let receiver = self.execution_state_sender.send_request(move |callback| {
+            ExecutionRequest::FindKeysInRange {
+                id,
+                key_prefix,
+                range,
+                callback,
+            }
+        })?;
+        state.find_keys_queries.register(receiver)
+    }
+
     fn find_keys_by_prefix_wait(
         &mut self,
         promise: &Self::FindKeysByPrefix,
@@ -929,6 +979,25 @@ impl<UserInstance> BaseRuntime for SyncRuntimeInternal<UserInstance> {
         state.find_key_values_queries.register(receiver)
     }
 
+    fn find_key_values_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+    ) -> Result<Self::FindKeyValuesByPrefix, ExecutionError> {
+        let id = self.application_id()?;
+        let state = self.view_user_states.entry(id).or_default();
+        self.resource_controller.track_read_operations(1)?;
+        let receiver = self.execution_state_sender.send_request(move |callback| {
+            ExecutionRequest::FindKeyValuesInRange {
+                id,
+                key_prefix,
+                range,
+                callback,
+            }
+        })?;
+        state.find_key_values_queries.register(receiver)
+    }
+
     fn find_key_values_by_prefix_wait(
         &mut self,
         promise: &Self::FindKeyValuesByPrefix,
@@ -1048,6 +1117,26 @@ impl<UserInstance> BaseRuntime for SyncRuntimeInternal<UserInstance> {
         }
         Ok(())
     }
+
+    fn read_event(&mut self, event_id: EventId) -> Result<Vec<u8>, ExecutionError> {
+        let value =
+            if let Some(response) = self.transaction_tracker.next_replayed_oracle_response()? {
+                match response {
+                    OracleResponse::Event(recorded_id, value) if recorded_id == event_id => value,
+                    _ => return Err(ExecutionError::OracleResponseMismatch),
+                }
+            } else {
+                let event_id = event_id.clone();
+                self.execution_state_sender
+                    .send_request(|callback| ExecutionRequest::ReadEvent { event_id, callback })?
+                    .recv_response()?
+            };
+        self.resource_controller
+            .track_bytes_read(value.len() as u64)?;
+        self.transaction_tracker
+            .add_oracle_response(OracleResponse::Event(event_id, value.clone()));
+        Ok(value)
+    }
 }
 
 impl<UserInstance> Clone for SyncRuntimeHandle<UserInstance> {
@@ -1066,23 +1155,27 @@ impl ContractSyncRuntime {
         action: &UserAction,
         txn_tracker: TransactionTracker,
     ) -> Self {
-        SyncRuntime(Some(ContractSyncRuntimeHandle::from(
-            SyncRuntimeInternal::new(
-                chain_id,
-                action.height(),
-                local_time,
-                action.signer(),
-                if let UserAction::Message(context, _) = action {
-                    Some(context.into())
-                } else {
-                    None
-                },
-                execution_state_sender,
-                refund_grant_to,
-                resource_controller,
-                txn_tracker,
-            ),
-        )))
+        let mut runtime = SyncRuntimeInternal::new(
+            chain_id,
+            action.height(),
+            local_time,
+            action.signer(),
+            if let UserAction::Message(context, _) = action {
+                Some(context.into())
+            } else {
+                None
+            },
+            execution_state_sender,
+            refund_grant_to,
+            resource_controller,
+            txn_tracker,
+        );
+        if let UserAction::Message(context, request) = action {
+            if context.is_request {
+                runtime.unanswered_request = Some(request.clone());
+            }
+        }
+        SyncRuntime(Some(ContractSyncRuntimeHandle::from(runtime)))
     }
 
     pub(crate) fn preload_contract(
@@ -1090,6 +1183,7 @@ impl ContractSyncRuntime {
         id: UserApplicationId,
         code: UserContractCode,
         description: UserApplicationDescription,
+        needs_migration: bool,
     ) -> Result<(), ExecutionError> {
         let this = self
             .0
@@ -1104,6 +1198,9 @@ impl ContractSyncRuntime {
                 description,
             ));
             this_guard.applications_to_finalize.push(id);
+            if needs_migration {
+                this_guard.applications_to_migrate.insert(id);
+            }
         }
 
         Ok(())
@@ -1150,6 +1247,12 @@ impl ContractSyncRuntimeHandle {
                 code.execute_operation(context, operation).map(|_| ())
             }
             UserAction::Message(context, message) => code.execute_message(context, message),
+            UserAction::ProcessEvent(context, event_id, value) => {
+                code.process_event(context, event_id, value)
+            }
+            UserAction::HandleResponse(context, request_id, request, response) => {
+                code.handle_response(context, request_id, request, response)
+            }
         })?;
         self.finalize(finalize_context)?;
         Ok(())
@@ -1196,13 +1299,17 @@ impl ContractSyncRuntimeHandle {
 
             application
         };
+        let needs_migration = self.inner().applications_to_migrate.remove(&application_id);
 
-        closure(
-            &mut contract
-                .instance
-                .try_lock()
-                .expect("Application should not be already executing"),
-        )?;
+        let mut instance = contract
+            .instance
+            .try_lock()
+            .expect("Application should not be already executing");
+        if needs_migration {
+            instance.migrate()?;
+        }
+        closure(&mut instance)?;
+        drop(instance);
 
         let mut runtime = self.inner();
         let application_status = runtime.pop_application();
@@ -1260,6 +1367,70 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         Ok(())
     }
 
+    fn send_delayed_message(
+        &mut self,
+        message: SendMessageRequest<Vec<u8>>,
+        not_before: Timestamp,
+    ) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        let application = this.current_application_mut();
+
+        let mut message = RawOutgoingMessage::from(message);
+        message.not_before = Some(not_before);
+        application.outcome.messages.push(message);
+
+        Ok(())
+    }
+
+    fn send_request(&mut self, request: SendMessageRequest<Vec<u8>>) -> Result<(), ExecutionError> {
+        ensure!(
+            matches!(request.destination, Destination::Recipient(_)),
+            ExecutionError::RequestToSubscribers
+        );
+        let mut this = self.inner();
+        let application = this.current_application_mut();
+
+        let mut message = RawOutgoingMessage::from(request);
+        message.kind = MessageKind::Request;
+        application.outcome.messages.push(message);
+
+        Ok(())
+    }
+
+    fn respond(&mut self, response: Vec<u8>) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        // Only the application that received the request can respond to it.
+        ensure!(
+            !this.is_finalizing && this.call_stack.len() == 1,
+            ExecutionError::NoRequestToRespondTo
+        );
+        let request = this
+            .unanswered_request
+            .take()
+            .ok_or(ExecutionError::NoRequestToRespondTo)?;
+        let request_id = this
+            .executing_message
+            .expect("requests are only executed as messages")
+            .id;
+        let application_id = this.current_application().id;
+        // The sender is waiting for the response, so it can't be skipped or rejected.
+        let outcome = RawExecutionOutcome::default().with_message(RawOutgoingMessage {
+            destination: Destination::Recipient(request_id.chain_id),
+            authenticated: false,
+            grant: Amount::ZERO,
+            not_before: None,
+            kind: MessageKind::Protected,
+            message: SystemMessage::Response {
+                application_id,
+                request_id,
+                request,
+                response: Some(response),
+            },
+        });
+        this.transaction_tracker.add_system_outcome(outcome)?;
+        Ok(())
+    }
+
     fn subscribe(&mut self, chain: ChainId, channel: ChannelName) -> Result<(), ExecutionError> {
         let mut this = self.inner();
         let application = this.current_application_mut();
@@ -1343,11 +1514,16 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         let (contract, context) =
             self.inner()
                 .prepare_for_call(self.clone(), authenticated, callee_id)?;
+        let needs_migration = self.inner().applications_to_migrate.remove(&callee_id);
 
-        let value = contract
+        let mut contract = contract
             .try_lock()
-            .expect("Applications should not have reentrant calls")
-            .execute_operation(context, argument)?;
+            .expect("Applications should not have reentrant calls");
+        if needs_migration {
+            contract.migrate()?;
+        }
+        let value = contract.execute_operation(context, argument)?;
+        drop(contract);
 
         self.inner().finish_call()?;
 
@@ -1369,8 +1545,87 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
             name.0.len() <= MAX_STREAM_NAME_LEN,
             ExecutionError::StreamNameTooLong
         );
+        let chain_id = this.chain_id;
         let application = this.current_application_mut();
-        application.outcome.events.push((name, key, value));
+        application
+            .outcome
+            .events
+            .push((name.clone(), key.clone(), value.clone()));
+        let event_id = EventId {
+            chain_id,
+            stream_id: StreamId {
+                application_id: application.id.into(),
+                stream_name: name,
+            },
+            key,
+        };
+        let outcome = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::NotifyEventSubscribers {
+                event_id,
+                value,
+                callback,
+            })?
+            .recv_response()?;
+        this.transaction_tracker.add_system_outcome(outcome)?;
+        Ok(())
+    }
+
+    fn subscribe_to_events(
+        &mut self,
+        chain_id: ChainId,
+        application_id: UserApplicationId,
+        name: StreamName,
+    ) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        ensure!(
+            name.0.len() <= MAX_STREAM_NAME_LEN,
+            ExecutionError::StreamNameTooLong
+        );
+        let subscriber_id = this.current_application().id;
+        let stream_id = StreamId {
+            application_id: application_id.into(),
+            stream_name: name,
+        };
+        let outcome = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::SubscribeToEvents {
+                chain_id,
+                stream_id,
+                subscriber_id,
+                callback,
+            })?
+            .recv_response()?;
+        this.transaction_tracker.add_system_outcome(outcome)?;
+        Ok(())
+    }
+
+    fn unsubscribe_from_events(
+        &mut self,
+        chain_id: ChainId,
+        application_id: UserApplicationId,
+        name: StreamName,
+    ) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        ensure!(
+            name.0.len() <= MAX_STREAM_NAME_LEN,
+            ExecutionError::StreamNameTooLong
+        );
+        let subscriber_id = this.current_application().id;
+        let stream_id = StreamId {
+            application_id: application_id.into(),
+            stream_name: name,
+        };
+        let outcome = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::UnsubscribeFromEvents {
+                chain_id,
+                stream_id,
+                subscriber_id,
+                callback,
+            })?
+            .recv_response()?;
+        this.transaction_tracker.add_system_outcome(outcome)?;
         Ok(())
     }
 
@@ -1479,13 +1734,18 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         )?;
         this.resource_controller
             .track_bytes_written(batch.size() as u64)?;
-        this.execution_state_sender
+        let stored_bytes_delta = this
+            .execution_state_sender
             .send_request(|callback| ExecutionRequest::WriteBatch {
                 id,
                 batch,
                 callback,
             })?
             .recv_response()?;
+        this.resource_controller
+            .track_stored_bytes(stored_bytes_delta)?;
+        this.transaction_tracker
+            .track_stored_bytes(id, stored_bytes_delta)?;
         Ok(())
     }
 }
@@ -9,9 +9,9 @@ mod tests;
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Display, Formatter},
-    iter,
+    iter, mem,
 };
 
 use async_graphql::Enum;
@@ -19,20 +19,22 @@ use custom_debug_derive::Debug;
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        Amount, ApplicationPermissions, ArithmeticError, BlobContent, OracleResponse, Timestamp,
+        Amount, ApplicationPermissions, ArithmeticError, BlobContent, OracleResponse, TimeDelta,
+        Timestamp,
     },
     ensure, hex_debug,
     identifiers::{
-        Account, AccountOwner, BlobId, BlobType, BytecodeId, ChainDescription, ChainId, MessageId,
-        Owner,
+        Account, AccountOwner, BlobId, BlobType, BytecodeId, ChainDescription, ChainId, EventId,
+        MessageId, Owner, StreamId,
     },
     ownership::{ChainOwnership, TimeoutConfig},
 };
 use linera_views::{
+    common::CustomSerialize,
     context::Context,
     map_view::HashedMapView,
     register_view::HashedRegisterView,
-    set_view::HashedSetView,
+    set_view::{HashedCustomSetView, HashedSetView},
     views::{ClonableView, HashableView, View, ViewError},
 };
 use serde::{Deserialize, Serialize};
@@ -44,7 +46,7 @@ use {linera_base::prometheus_util::register_int_counter_vec, prometheus::IntCoun
 use crate::test_utils::SystemExecutionState;
 use crate::{
     committee::{Committee, Epoch},
-    ApplicationRegistryView, ChannelName, ChannelSubscription, Destination,
+    ApplicationRegistryView, ApplicationUpgrade, ChannelName, ChannelSubscription, Destination,
     ExecutionRuntimeContext, MessageContext, MessageKind, OperationContext, QueryContext,
     RawExecutionOutcome, RawOutgoingMessage, TransactionTracker, UserApplicationDescription,
     UserApplicationId,
@@ -55,6 +57,12 @@ pub static OPEN_CHAIN_MESSAGE_INDEX: u32 = 0;
 /// The relative index of the `ApplicationCreated` message created by the `CreateApplication`
 /// operation.
 pub static CREATE_APPLICATION_MESSAGE_INDEX: u32 = 0;
+/// How long a request waits for its response, in seconds, before the sending application is
+/// told that it failed.
+pub const REQUEST_TIMEOUT_SECS: u64 = 24 * 60 * 60;
+/// The maximum number of chains that can subscribe to an event stream. Each event is sent to
+/// every subscriber, at the expense of the publishing chain.
+pub const MAX_EVENT_STREAM_SUBSCRIBERS: usize = 1_000;
 
 /// The number of times the [`SystemOperation::OpenChain`] was executed.
 #[cfg(with_metrics)]
@@ -98,6 +106,103 @@ pub struct SystemExecutionStateView<C> {
     pub application_permissions: HashedRegisterView<C, ApplicationPermissions>,
     /// Blobs that have been used or published on this chain.
     pub used_blobs: HashedSetView<C, BlobId>,
+    /// The applications of this chain subscribed to the event streams of other chains.
+    pub event_subscriptions: HashedMapView<C, (ChainId, StreamId), BTreeSet<UserApplicationId>>,
+    /// The chains subscribed to the event streams of this chain.
+    pub event_subscribers: HashedMapView<C, StreamId, BTreeSet<ChainId>>,
+    /// The storage rent paid for the bytes currently stored by each application, by payer:
+    /// `None` for the chain's balance, or the owner who paid.
+    pub storage_deposits:
+        HashedMapView<C, UserApplicationId, BTreeMap<Option<Owner>, StorageDeposit>>,
+    /// The requests sent by applications on this chain that are waiting for a response.
+    pub outstanding_requests: HashedMapView<C, MessageId, OutstandingRequest>,
+    /// The outstanding requests, in the order in which they time out.
+    pub request_deadlines: HashedCustomSetView<C, RequestDeadline>,
+    /// When the pending [`SystemMessage::RequestTimeout`] to this chain is due, if any.
+    pub request_timer: HashedRegisterView<C, Option<Timestamp>>,
+}
+
+/// Returns when a request sent in a block with the given timestamp times out. From then on,
+/// the sender doesn't wait for the response anymore, and the recipient doesn't execute the
+/// request anymore.
+pub fn request_deadline(timestamp: Timestamp) -> Timestamp {
+    timestamp.saturating_add(TimeDelta::from_secs(REQUEST_TIMEOUT_SECS))
+}
+
+/// A request sent by an application, waiting for a response.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct OutstandingRequest {
+    /// The application that sent the request.
+    pub application_id: UserApplicationId,
+    /// The chain that the request was sent to, and that must respond.
+    pub recipient: ChainId,
+    /// The request itself.
+    #[serde(with = "serde_bytes")]
+    #[debug(with = "hex_debug")]
+    pub request: Vec<u8>,
+    /// When the request times out.
+    pub deadline: Timestamp,
+}
+
+/// The time when an outstanding request times out.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct RequestDeadline {
+    /// When the request times out.
+    pub deadline: Timestamp,
+    /// The ID of the request.
+    pub request_id: MessageId,
+}
+
+/// Deadlines are serialized in big-endian order, so that they are ordered as set indices.
+impl CustomSerialize for RequestDeadline {
+    fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
+        let bytes = (self.deadline.micros().to_be_bytes(), &self.request_id);
+        Ok(bcs::to_bytes(&bytes)?)
+    }
+
+    fn from_custom_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+        let (deadline, request_id) = bcs::from_bytes::<([u8; 8], MessageId)>(bytes)?;
+        Ok(Self {
+            deadline: Timestamp::from(u64::from_be_bytes(deadline)),
+            request_id,
+        })
+    }
+}
+
+/// The storage rent paid by one account for a number of bytes, so that it can be refunded
+/// when the bytes are freed.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StorageDeposit {
+    /// The number of bytes paid for.
+    pub bytes: u64,
+    /// The amount paid.
+    pub amount: Amount,
+}
+
+impl StorageDeposit {
+    /// Adds another deposit by the same account to this one.
+    pub fn try_add_assign(&mut self, other: StorageDeposit) -> Result<(), ArithmeticError> {
+        self.bytes = self
+            .bytes
+            .checked_add(other.bytes)
+            .ok_or(ArithmeticError::Overflow)?;
+        self.amount.try_add_assign(other.amount)
+    }
+
+    /// Removes up to `bytes` bytes from this deposit, and returns the part of the deposit
+    /// that paid for them.
+    pub fn split_off(&mut self, bytes: u64) -> StorageDeposit {
+        if bytes >= self.bytes {
+            return mem::take(self);
+        }
+        // Round down, so that the remainder is refunded together with the last byte.
+        let amount = Amount::from_attos(
+            u128::from(self.amount) / u128::from(self.bytes) * u128::from(bytes),
+        );
+        self.bytes -= bytes;
+        self.amount = self.amount.saturating_sub(amount);
+        StorageDeposit { bytes, amount }
+    }
 }
 
 /// The configuration for a new chain.
@@ -183,6 +288,16 @@ pub enum SystemOperation {
         instantiation_argument: Vec<u8>,
         #[debug(skip_if = Vec::is_empty)]
         required_application_ids: Vec<UserApplicationId>,
+        /// The owner allowed to upgrade the application later, if any.
+        #[debug(skip_if = Option::is_none)]
+        admin: Option<Owner>,
+    },
+    /// Upgrades an application to a new bytecode. This must be signed by the application's
+    /// admin, on the chain that created the application. The upgrade is broadcast to the
+    /// chains using the application on the `Upgrades` channel.
+    UpgradeApplication {
+        application_id: UserApplicationId,
+        bytecode_id: BytecodeId,
     },
     /// Requests a message from another chain to register a user application on this chain.
     RequestApplication {
@@ -249,9 +364,40 @@ pub enum SystemMessage {
     RegisterApplications {
         applications: Vec<UserApplicationDescription>,
     },
+    /// Shares the latest upgrades of some applications. Upgrades of applications that the
+    /// recipient doesn't know are ignored.
+    UpgradeApplications {
+        upgrades: Vec<(UserApplicationId, ApplicationUpgrade)>,
+    },
     /// Requests a `RegisterApplication` message from the target chain to register the specified
     /// application on the sender chain.
     RequestApplication(UserApplicationId),
+    /// Subscribes the sender chain to an event stream of the receiver chain.
+    SubscribeToEvents { stream_id: StreamId },
+    /// Unsubscribes the sender chain from an event stream of the receiver chain.
+    UnsubscribeFromEvents { stream_id: StreamId },
+    /// Notifies a subscriber chain of a new event on one of the streams it subscribed to.
+    Event {
+        event_id: EventId,
+        #[serde(with = "serde_bytes")]
+        #[debug(with = "hex_debug")]
+        value: Vec<u8>,
+    },
+    /// Returns the response to a request back to the application that sent it. The response
+    /// is `None` if the request was rejected.
+    Response {
+        application_id: UserApplicationId,
+        request_id: MessageId,
+        #[serde(with = "serde_bytes")]
+        #[debug(with = "hex_debug")]
+        request: Vec<u8>,
+        #[debug(skip_if = Option::is_none)]
+        response: Option<Vec<u8>>,
+    },
+    /// Sent by a chain to itself, to be received when its oldest outstanding request times
+    /// out. The sending applications of all the requests that timed out by then are told that
+    /// they failed.
+    RequestTimeout,
 }
 
 /// A query to the system state.
@@ -272,6 +418,8 @@ pub struct SystemResponse {
 pub enum SystemChannel {
     /// Channel used to broadcast reconfigurations.
     Admin,
+    /// Channel used to broadcast upgrades of the applications created on a chain.
+    Upgrades,
 }
 
 impl SystemChannel {
@@ -287,6 +435,7 @@ impl Display for SystemChannel {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         let display_name = match self {
             SystemChannel::Admin => "Admin",
+            SystemChannel::Upgrades => "Upgrades",
         };
 
         write!(formatter, "{display_name}")
@@ -409,8 +558,16 @@ pub enum SystemExecutionError {
     TicksOutOfOrder,
     #[error("Application {0:?} is not registered by the chain")]
     UnknownApplicationId(Box<UserApplicationId>),
+    #[error("Application {0:?} can only be upgraded by its admin")]
+    UnauthorizedApplicationUpgrade(Box<UserApplicationId>),
+    #[error("Application {0:?} can only be upgraded on the chain that created it")]
+    UpgradeOutsideCreatorChain(Box<UserApplicationId>),
     #[error("Chain is not active yet.")]
     InactiveChain,
+    #[error(
+        "Event stream {0:?} already has the maximum of {MAX_EVENT_STREAM_SUBSCRIBERS} subscribers"
+    )]
+    TooManyEventSubscribers(Box<StreamId>),
 
     #[error("Blobs not found: {0:?}")]
     BlobsNotFound(Vec<BlobId>),
@@ -550,6 +707,7 @@ where
                             destination: Destination::Subscribers(SystemChannel::Admin.name()),
                             authenticated: false,
                             grant: Amount::ZERO,
+                            not_before: None,
                             kind: MessageKind::Protected,
                             message: SystemMessage::CreateCommittee { epoch, committee },
                         };
@@ -564,6 +722,7 @@ where
                             destination: Destination::Subscribers(SystemChannel::Admin.name()),
                             authenticated: false,
                             grant: Amount::ZERO,
+                            not_before: None,
                             kind: MessageKind::Protected,
                             message: SystemMessage::RemoveCommittee { epoch },
                         };
@@ -595,6 +754,7 @@ where
                     destination: Destination::Recipient(chain_id),
                     authenticated: false,
                     grant: Amount::ZERO,
+                    not_before: None,
                     kind: MessageKind::Protected,
                     message: SystemMessage::Subscribe {
                         id: context.chain_id,
@@ -617,6 +777,7 @@ where
                     destination: Destination::Recipient(chain_id),
                     authenticated: false,
                     grant: Amount::ZERO,
+                    not_before: None,
                     kind: MessageKind::Protected,
                     message: SystemMessage::Unsubscribe {
                         id: context.chain_id,
@@ -640,6 +801,7 @@ where
                 parameters,
                 instantiation_argument,
                 required_application_ids,
+                admin,
             } => {
                 let next_message_id = context.next_message_id(txn_tracker.next_message_index());
                 let CreateApplicationResult {
@@ -652,6 +814,7 @@ where
                         bytecode_id,
                         parameters,
                         required_application_ids,
+                        admin,
                     )
                     .await?;
                 self.record_bytecode_blobs(blobs_to_register, txn_tracker)
@@ -659,6 +822,38 @@ where
                 outcome.messages.push(message);
                 new_application = Some((app_id, instantiation_argument.clone()));
             }
+            UpgradeApplication {
+                application_id,
+                bytecode_id,
+            } => {
+                ensure!(
+                    application_id.creation.chain_id == context.chain_id,
+                    SystemExecutionError::UpgradeOutsideCreatorChain(Box::new(application_id))
+                );
+                let description = self.registry.describe_application(application_id).await?;
+                ensure!(
+                    description.admin.is_some()
+                        && description.admin == context.authenticated_signer,
+                    SystemExecutionError::UnauthorizedApplicationUpgrade(Box::new(application_id))
+                );
+                self.check_and_record_bytecode_blobs(&bytecode_id, txn_tracker)
+                    .await?;
+                let upgrade = self
+                    .registry
+                    .upgrade_application(application_id, bytecode_id)
+                    .await?;
+                let message = RawOutgoingMessage {
+                    destination: Destination::Subscribers(SystemChannel::Upgrades.name()),
+                    authenticated: false,
+                    grant: Amount::ZERO,
+                    not_before: None,
+                    kind: MessageKind::Protected,
+                    message: SystemMessage::UpgradeApplications {
+                        upgrades: vec![(application_id, upgrade)],
+                    },
+                };
+                outcome.messages.push(message);
+            }
             RequestApplication {
                 chain_id,
                 application_id,
@@ -667,6 +862,7 @@ where
                     destination: Destination::Recipient(chain_id),
                     authenticated: false,
                     grant: Amount::ZERO,
+                    not_before: None,
                     kind: MessageKind::Simple,
                     message: SystemMessage::RequestApplication(application_id),
                 };
@@ -723,6 +919,7 @@ where
                     destination: Destination::Recipient(account.chain_id),
                     authenticated: false,
                     grant: Amount::ZERO,
+                    not_before: None,
                     kind: MessageKind::Tracked,
                     message: SystemMessage::Credit {
                         amount,
@@ -765,6 +962,7 @@ where
             destination: Destination::Recipient(target_id),
             authenticated: true,
             grant: Amount::ZERO,
+            not_before: None,
             kind: MessageKind::Simple,
             message: SystemMessage::Withdraw {
                 amount,
@@ -842,6 +1040,7 @@ where
                             destination: Destination::Recipient(account.chain_id),
                             authenticated: false,
                             grant: Amount::ZERO,
+                            not_before: None,
                             kind: MessageKind::Tracked,
                             message: SystemMessage::Credit {
                                 amount,
@@ -872,9 +1071,55 @@ where
                 for application in applications {
                     self.check_and_record_bytecode_blobs(&application.bytecode_id, txn_tracker)
                         .await?;
+                    let creator_id = application.creation.chain_id;
+                    let is_upgradable = application.admin.is_some();
                     self.registry
                         .register_application(application.clone())
                         .await?;
+                    // Follow the creator chain's upgrades of the applications we may use.
+                    let subscription = ChannelSubscription {
+                        chain_id: creator_id,
+                        name: SystemChannel::Upgrades.name(),
+                    };
+                    if is_upgradable
+                        && creator_id != context.chain_id
+                        && !self.subscriptions.contains(&subscription).await?
+                    {
+                        self.subscriptions.insert(&subscription)?;
+                        outcome.messages.push(RawOutgoingMessage {
+                            destination: Destination::Recipient(creator_id),
+                            authenticated: false,
+                            grant: Amount::ZERO,
+                            not_before: None,
+                            kind: MessageKind::Protected,
+                            message: SystemMessage::Subscribe {
+                                id: context.chain_id,
+                                subscription,
+                            },
+                        });
+                    }
+                }
+            }
+            UpgradeApplications { upgrades } => {
+                for (application_id, upgrade) in upgrades {
+                    if !self
+                        .registry
+                        .known_applications
+                        .contains_key(&application_id)
+                        .await?
+                    {
+                        continue;
+                    }
+                    if !self
+                        .registry
+                        .is_new_upgrade(application_id, &upgrade)
+                        .await?
+                    {
+                        continue;
+                    }
+                    self.check_and_record_bytecode_blobs(&upgrade.bytecode_id, txn_tracker)
+                        .await?;
+                    self.registry.apply_upgrade(application_id, upgrade)?;
                 }
             }
             RequestApplication(application_id) => {
@@ -885,15 +1130,55 @@ where
                         &Default::default(),
                     )
                     .await?;
+                let upgrades = self
+                    .registry
+                    .describe_upgrades(applications.iter().map(UserApplicationId::from))
+                    .await?;
+                let destination = Destination::Recipient(context.message_id.chain_id);
                 let message = RawOutgoingMessage {
-                    destination: Destination::Recipient(context.message_id.chain_id),
+                    destination: destination.clone(),
                     authenticated: false,
                     grant: Amount::ZERO,
+                    not_before: None,
                     kind: MessageKind::Simple,
                     message: SystemMessage::RegisterApplications { applications },
                 };
                 outcome.messages.push(message);
+                if !upgrades.is_empty() {
+                    outcome.messages.push(RawOutgoingMessage {
+                        destination,
+                        authenticated: false,
+                        grant: Amount::ZERO,
+                        not_before: None,
+                        kind: MessageKind::Simple,
+                        message: SystemMessage::UpgradeApplications { upgrades },
+                    });
+                }
+            }
+            SubscribeToEvents { stream_id } => {
+                let subscriber_id = context.message_id.chain_id;
+                let subscribers = self
+                    .event_subscribers
+                    .get_mut_or_default(&stream_id)
+                    .await?;
+                ensure!(
+                    subscribers.contains(&subscriber_id)
+                        || subscribers.len() < MAX_EVENT_STREAM_SUBSCRIBERS,
+                    SystemExecutionError::TooManyEventSubscribers(Box::new(stream_id))
+                );
+                subscribers.insert(subscriber_id);
+            }
+            UnsubscribeFromEvents { stream_id } => {
+                if let Some(subscribers) = self.event_subscribers.get_mut(&stream_id).await? {
+                    subscribers.remove(&context.message_id.chain_id);
+                    if subscribers.is_empty() {
+                        self.event_subscribers.remove(&stream_id)?;
+                    }
+                }
             }
+            // Events, responses and timeouts are handed over to the applications by the execution
+            // state.
+            Event { .. } | Response { .. } | RequestTimeout => {}
             // These messages are executed immediately when cross-chain requests are received.
             Subscribe { .. } | Unsubscribe { .. } | OpenChain(_) => {}
             // This message is only a placeholder: Its ID is part of the application ID.
@@ -979,6 +1264,7 @@ where
             destination: Destination::Recipient(child_id),
             authenticated: false,
             grant: Amount::ZERO,
+            not_before: None,
             kind: MessageKind::Protected,
             message: SystemMessage::OpenChain(config),
         };
@@ -990,6 +1276,7 @@ where
             destination: Destination::Recipient(admin_id),
             authenticated: false,
             grant: Amount::ZERO,
+            not_before: None,
             kind: MessageKind::Protected,
             message: SystemMessage::Subscribe {
                 id: child_id,
@@ -1011,6 +1298,7 @@ where
                     destination: Destination::Recipient(subscription.chain_id),
                     authenticated: false,
                     grant: Amount::ZERO,
+                    not_before: None,
                     kind: MessageKind::Protected,
                     message: SystemMessage::Unsubscribe { id, subscription },
                 };
@@ -1029,6 +1317,7 @@ where
         bytecode_id: BytecodeId,
         parameters: Vec<u8>,
         required_application_ids: Vec<UserApplicationId>,
+        admin: Option<Owner>,
     ) -> Result<CreateApplicationResult, SystemExecutionError> {
         let id = UserApplicationId {
             bytecode_id,
@@ -1048,13 +1337,19 @@ where
             }
         }
         self.registry
-            .register_new_application(id, parameters.clone(), required_application_ids.clone())
+            .register_new_application(
+                id,
+                parameters.clone(),
+                required_application_ids.clone(),
+                admin,
+            )
             .await?;
         // Send a message to ourself to increment the message ID.
         let message = RawOutgoingMessage {
             destination: Destination::Recipient(next_message_id.chain_id),
             authenticated: false,
             grant: Amount::ZERO,
+            not_before: None,
             kind: MessageKind::Protected,
             message: SystemMessage::ApplicationCreated,
         };
@@ -1090,6 +1385,86 @@ where
         Ok(())
     }
 
+    /// Records the subscription of `subscriber_id` to the event stream `stream_id` of
+    /// `chain_id`. Returns the message to subscribe this chain to the stream, if it wasn't
+    /// subscribed already.
+    pub async fn subscribe_to_events(
+        &mut self,
+        chain_id: ChainId,
+        stream_id: StreamId,
+        subscriber_id: UserApplicationId,
+    ) -> Result<Option<RawOutgoingMessage<SystemMessage, Amount>>, SystemExecutionError> {
+        let subscribers = self
+            .event_subscriptions
+            .get_mut_or_default(&(chain_id, stream_id.clone()))
+            .await?;
+        let is_new_stream = subscribers.is_empty();
+        subscribers.insert(subscriber_id);
+        Ok(is_new_stream.then(|| RawOutgoingMessage {
+            destination: Destination::Recipient(chain_id),
+            authenticated: false,
+            grant: Amount::ZERO,
+            not_before: None,
+            kind: MessageKind::Simple,
+            message: SystemMessage::SubscribeToEvents { stream_id },
+        }))
+    }
+
+    /// Removes the subscription of `subscriber_id` to the event stream `stream_id` of
+    /// `chain_id`. Returns the message to unsubscribe this chain from the stream, if no other
+    /// application is subscribed to it.
+    pub async fn unsubscribe_from_events(
+        &mut self,
+        chain_id: ChainId,
+        stream_id: StreamId,
+        subscriber_id: UserApplicationId,
+    ) -> Result<Option<RawOutgoingMessage<SystemMessage, Amount>>, SystemExecutionError> {
+        let key = (chain_id, stream_id);
+        let Some(subscribers) = self.event_subscriptions.get_mut(&key).await? else {
+            return Ok(None);
+        };
+        if !subscribers.remove(&subscriber_id) || !subscribers.is_empty() {
+            return Ok(None);
+        }
+        self.event_subscriptions.remove(&key)?;
+        let (chain_id, stream_id) = key;
+        Ok(Some(RawOutgoingMessage {
+            destination: Destination::Recipient(chain_id),
+            authenticated: false,
+            grant: Amount::ZERO,
+            not_before: None,
+            kind: MessageKind::Simple,
+            message: SystemMessage::UnsubscribeFromEvents { stream_id },
+        }))
+    }
+
+    /// Returns the messages notifying the subscribers of a stream of a new event.
+    pub async fn event_messages(
+        &self,
+        event_id: EventId,
+        value: Vec<u8>,
+    ) -> Result<Vec<RawOutgoingMessage<SystemMessage, Amount>>, SystemExecutionError> {
+        let subscribers = self
+            .event_subscribers
+            .get(&event_id.stream_id)
+            .await?
+            .unwrap_or_default();
+        Ok(subscribers
+            .into_iter()
+            .map(|chain_id| RawOutgoingMessage {
+                destination: Destination::Recipient(chain_id),
+                authenticated: false,
+                grant: Amount::ZERO,
+                not_before: None,
+                kind: MessageKind::Simple,
+                message: SystemMessage::Event {
+                    event_id: event_id.clone(),
+                    value: value.clone(),
+                },
+            })
+            .collect())
+    }
+
     pub async fn read_blob_content(
         &mut self,
         blob_id: BlobId,
@@ -17,6 +17,7 @@ use std::{
 
 #[cfg(web)]
 use js_sys::wasm_bindgen;
+use linera_base::identifiers::{EventId, MessageId};
 
 use crate::{
     ContractSyncRuntimeHandle, ExecutionError, FinalizeContext, MessageContext, OperationContext,
@@ -148,6 +149,29 @@ type ExecuteMessageHandler = Box<
         + Send
         + Sync,
 >;
+type ProcessEventHandler = Box<
+    dyn FnOnce(
+            &mut ContractSyncRuntimeHandle,
+            MessageContext,
+            EventId,
+            Vec<u8>,
+        ) -> Result<(), ExecutionError>
+        + Send
+        + Sync,
+>;
+type HandleResponseHandler = Box<
+    dyn FnOnce(
+            &mut ContractSyncRuntimeHandle,
+            MessageContext,
+            MessageId,
+            Vec<u8>,
+            Option<Vec<u8>>,
+        ) -> Result<(), ExecutionError>
+        + Send
+        + Sync,
+>;
+type MigrateHandler =
+    Box<dyn FnOnce(&mut ContractSyncRuntimeHandle) -> Result<(), ExecutionError> + Send + Sync>;
 type FinalizeHandler = Box<
     dyn FnOnce(&mut ContractSyncRuntimeHandle, FinalizeContext) -> Result<(), ExecutionError>
         + Send
@@ -172,6 +196,12 @@ pub enum ExpectedCall {
     ExecuteOperation(#[debug(skip)] ExecuteOperationHandler),
     /// An expected call to [`UserContract::execute_message`].
     ExecuteMessage(#[debug(skip)] ExecuteMessageHandler),
+    /// An expected call to [`UserContract::process_event`].
+    ProcessEvent(#[debug(skip)] ProcessEventHandler),
+    /// An expected call to [`UserContract::handle_response`].
+    HandleResponse(#[debug(skip)] HandleResponseHandler),
+    /// An expected call to [`UserContract::migrate`].
+    Migrate(#[debug(skip)] MigrateHandler),
     /// An expected call to [`UserContract::finalize`].
     Finalize(#[debug(skip)] FinalizeHandler),
     /// An expected call to [`UserService::handle_query`].
@@ -184,6 +214,9 @@ impl Display for ExpectedCall {
             ExpectedCall::Instantiate(_) => "instantiate",
             ExpectedCall::ExecuteOperation(_) => "execute_operation",
             ExpectedCall::ExecuteMessage(_) => "execute_message",
+            ExpectedCall::ProcessEvent(_) => "process_event",
+            ExpectedCall::HandleResponse(_) => "handle_response",
+            ExpectedCall::Migrate(_) => "migrate",
             ExpectedCall::Finalize(_) => "finalize",
             ExpectedCall::HandleQuery(_) => "handle_query",
         };
@@ -240,6 +273,52 @@ impl ExpectedCall {
         ExpectedCall::ExecuteMessage(Box::new(handler))
     }
 
+    /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s
+    /// [`UserContract::process_event`] implementation, which is handled by the provided
+    /// `handler`.
+    pub fn process_event(
+        handler: impl FnOnce(
+                &mut ContractSyncRuntimeHandle,
+                MessageContext,
+                EventId,
+                Vec<u8>,
+            ) -> Result<(), ExecutionError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        ExpectedCall::ProcessEvent(Box::new(handler))
+    }
+
+    /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s
+    /// [`UserContract::handle_response`] implementation, which is handled by the provided
+    /// `handler`.
+    pub fn handle_response(
+        handler: impl FnOnce(
+                &mut ContractSyncRuntimeHandle,
+                MessageContext,
+                MessageId,
+                Vec<u8>,
+                Option<Vec<u8>>,
+            ) -> Result<(), ExecutionError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        ExpectedCall::HandleResponse(Box::new(handler))
+    }
+
+    /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s [`UserContract::migrate`]
+    /// implementation, which is handled by the provided `handler`.
+    pub fn migrate(
+        handler: impl FnOnce(&mut ContractSyncRuntimeHandle) -> Result<(), ExecutionError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        ExpectedCall::Migrate(Box::new(handler))
+    }
+
     /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s [`UserContract::finalize`]
     /// implementation, which is handled by the provided `handler`.
     pub fn finalize(
@@ -347,6 +426,51 @@ impl UserContract for MockApplicationInstance<ContractSyncRuntimeHandle> {
         }
     }
 
+    fn process_event(
+        &mut self,
+        context: MessageContext,
+        event_id: EventId,
+        value: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        match self.next_expected_call() {
+            Some(ExpectedCall::ProcessEvent(handler)) => {
+                handler(&mut self.runtime, context, event_id, value)
+            }
+            Some(unexpected_call) => panic!(
+                "Expected a call to `process_event`, got a call to `{unexpected_call}` instead."
+            ),
+            None => panic!("Unexpected call to `process_event`"),
+        }
+    }
+
+    fn handle_response(
+        &mut self,
+        context: MessageContext,
+        request_id: MessageId,
+        request: Vec<u8>,
+        response: Option<Vec<u8>>,
+    ) -> Result<(), ExecutionError> {
+        match self.next_expected_call() {
+            Some(ExpectedCall::HandleResponse(handler)) => {
+                handler(&mut self.runtime, context, request_id, request, response)
+            }
+            Some(unexpected_call) => panic!(
+                "Expected a call to `handle_response`, got a call to `{unexpected_call}` instead."
+            ),
+            None => panic!("Unexpected call to `handle_response`"),
+        }
+    }
+
+    fn migrate(&mut self) -> Result<(), ExecutionError> {
+        match self.next_expected_call() {
+            Some(ExpectedCall::Migrate(handler)) => handler(&mut self.runtime),
+            Some(unexpected_call) => {
+                panic!("Expected a call to `migrate`, got a call to `{unexpected_call}` instead.")
+            }
+            None => panic!("Unexpected call to `migrate`"),
+        }
+    }
+
     fn finalize(&mut self, context: FinalizeContext) -> Result<(), ExecutionError> {
         match self.next_expected_call() {
             Some(ExpectedCall::Finalize(handler)) => handler(&mut self.runtime, context),
@@ -57,6 +57,7 @@ pub fn create_dummy_user_application_description(
             },
             required_application_ids: vec![],
             parameters: vec![],
+            admin: None,
         },
         contract_blob,
         service_blob,
@@ -79,6 +80,7 @@ pub fn create_dummy_message_context(authenticated_signer: Option<Owner>) -> Mess
     MessageContext {
         chain_id: ChainId::root(0),
         is_bouncing: false,
+        is_request: false,
         authenticated_signer,
         refund_grant_to: None,
         height: BlockHeight(0),
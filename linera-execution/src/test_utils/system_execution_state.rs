@@ -11,7 +11,10 @@ use custom_debug_derive::Debug;
 use linera_base::{
     crypto::CryptoHash,
     data_types::{Amount, ApplicationPermissions, Blob, Timestamp},
-    identifiers::{AccountOwner, ApplicationId, BlobId, ChainDescription, ChainId, Owner},
+    identifiers::{
+        AccountOwner, ApplicationId, BlobId, ChainDescription, ChainId, Owner, StreamId,
+        UserApplicationId,
+    },
     ownership::ChainOwnership,
 };
 use linera_views::{
@@ -47,6 +50,10 @@ pub struct SystemExecutionState {
     pub timestamp: Timestamp,
     pub registry: ApplicationRegistry,
     pub used_blobs: BTreeSet<BlobId>,
+    #[debug(skip_if = BTreeMap::is_empty)]
+    pub event_subscriptions: BTreeMap<(ChainId, StreamId), BTreeSet<UserApplicationId>>,
+    #[debug(skip_if = BTreeMap::is_empty)]
+    pub event_subscribers: BTreeMap<StreamId, BTreeSet<ChainId>>,
     #[debug(skip_if = Not::not)]
     pub closed: bool,
     pub application_permissions: ApplicationPermissions,
@@ -110,6 +117,8 @@ impl SystemExecutionState {
             timestamp,
             registry,
             used_blobs,
+            event_subscriptions,
+            event_subscribers,
             closed,
             application_permissions,
             extra_blobs,
@@ -168,6 +177,18 @@ impl SystemExecutionState {
                 .insert(&blob_id)
                 .expect("inserting blob IDs should not fail");
         }
+        for (stream, applications) in event_subscriptions {
+            view.system
+                .event_subscriptions
+                .insert(&stream, applications)
+                .expect("inserting event subscriptions should not fail");
+        }
+        for (stream_id, chain_ids) in event_subscribers {
+            view.system
+                .event_subscribers
+                .insert(&stream_id, chain_ids)
+                .expect("inserting event subscribers should not fail");
+        }
         view.system.closed.set(closed);
         view.system
             .application_permissions
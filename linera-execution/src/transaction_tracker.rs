@@ -1,13 +1,13 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::vec;
+use std::{collections::BTreeMap, vec};
 
 use custom_debug_derive::Debug;
 use linera_base::{
     data_types::{Amount, ArithmeticError, OracleResponse},
     ensure,
-    identifiers::ApplicationId,
+    identifiers::{ApplicationId, UserApplicationId},
 };
 
 use crate::{
@@ -25,6 +25,9 @@ pub struct TransactionTracker {
     #[debug(skip_if = Vec::is_empty)]
     outcomes: Vec<ExecutionOutcome>,
     next_message_index: u32,
+    /// The change in the number of bytes stored by each application.
+    #[debug(skip_if = BTreeMap::is_empty)]
+    stored_bytes: BTreeMap<UserApplicationId, i32>,
 }
 
 impl TransactionTracker {
@@ -34,6 +37,7 @@ impl TransactionTracker {
             next_message_index,
             oracle_responses: Vec::new(),
             outcomes: Vec::new(),
+            stored_bytes: BTreeMap::new(),
         }
     }
 
@@ -77,6 +81,24 @@ impl TransactionTracker {
         Ok(())
     }
 
+    /// Tracks a change in the number of bytes stored by an application.
+    pub fn track_stored_bytes(
+        &mut self,
+        application_id: UserApplicationId,
+        delta: i32,
+    ) -> Result<(), ArithmeticError> {
+        let stored_bytes = self.stored_bytes.entry(application_id).or_default();
+        *stored_bytes = stored_bytes
+            .checked_add(delta)
+            .ok_or(ArithmeticError::Overflow)?;
+        Ok(())
+    }
+
+    /// Returns the change in the number of bytes stored by each application.
+    pub fn stored_bytes(&self) -> &BTreeMap<UserApplicationId, i32> {
+        &self.stored_bytes
+    }
+
     pub fn add_oracle_response(&mut self, oracle_response: OracleResponse) {
         self.oracle_responses.push(oracle_response);
     }
@@ -120,6 +142,7 @@ impl TransactionTracker {
             oracle_responses,
             outcomes,
             next_message_index,
+            stored_bytes: _,
         } = self;
         if let Some(mut responses) = replaying_oracle_responses {
             ensure!(
@@ -7,6 +7,7 @@
 
 use std::{
     any::Any,
+    collections::BTreeMap,
     sync::{Arc, Mutex},
 };
 
@@ -131,7 +132,7 @@ async fn test_write_batch() {
         assert_eq!(batch, expected_batch);
 
         callback
-            .send(())
+            .send(-3)
             .expect("Failed to notify that writing the batch finished");
     });
 
@@ -147,6 +148,11 @@ async fn test_write_batch() {
         runtime.inner().resource_controller.tracker.bytes_written,
         expected_bytes_count as u64
     );
+    assert_eq!(runtime.inner().resource_controller.tracker.bytes_stored, -3);
+    assert_eq!(
+        runtime.inner().transaction_tracker.stored_bytes(),
+        &BTreeMap::from([(expected_application_id, -3)])
+    );
 }
 
 /// Creates a [`SyncRuntimeInternal`] instance for contracts, and returns it and the receiver
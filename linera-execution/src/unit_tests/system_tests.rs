@@ -49,6 +49,7 @@ async fn application_message_index() -> anyhow::Result<()> {
         parameters: vec![],
         instantiation_argument: vec![],
         required_application_ids: vec![],
+        admin: None,
     };
     let mut txn_tracker = TransactionTracker::default();
     view.context()
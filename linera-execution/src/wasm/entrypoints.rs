@@ -3,6 +3,7 @@
 
 //! Wasm entrypoints for contracts and services.
 
+use linera_base::identifiers::{EventId, MessageId};
 use linera_witty::wit_import;
 
 /// WIT entrypoints for application contracts.
@@ -11,6 +12,9 @@ pub trait ContractEntrypoints {
     fn instantiate(argument: Vec<u8>);
     fn execute_operation(operation: Vec<u8>) -> Vec<u8>;
     fn execute_message(message: Vec<u8>);
+    fn process_event(event_id: EventId, value: Vec<u8>);
+    fn handle_response(request_id: MessageId, request: Vec<u8>, response: Option<Vec<u8>>);
+    fn migrate();
     fn finalize();
 }
 
@@ -7,11 +7,15 @@ use linera_base::{
     crypto::CryptoHash,
     data_types::{Amount, ApplicationPermissions, BlockHeight, SendMessageRequest, Timestamp},
     identifiers::{
-        Account, AccountOwner, ApplicationId, ChainId, ChannelName, MessageId, Owner, StreamName,
+        Account, AccountOwner, ApplicationId, ChainId, ChannelName, EventId, MessageId, Owner,
+        StreamId, StreamName,
     },
     ownership::{ChainOwnership, CloseChainError},
 };
-use linera_views::batch::{Batch, WriteOperation};
+use linera_views::{
+    batch::{Batch, WriteOperation},
+    store::KeyRange,
+};
 use linera_witty::{wit_export, Instance, RuntimeError};
 use tracing::log;
 
@@ -210,6 +214,41 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Schedules a message to be sent to this application on another chain, that can only
+    /// be received in a block whose timestamp is at least `not_before`.
+    fn send_delayed_message(
+        caller: &mut Caller,
+        message: SendMessageRequest<Vec<u8>>,
+        not_before: Timestamp,
+    ) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .send_delayed_message(message, not_before)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Schedules a request to be sent to this application on another chain.
+    fn send_request(
+        caller: &mut Caller,
+        request: SendMessageRequest<Vec<u8>>,
+    ) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .send_request(request)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Responds to the request currently being executed.
+    fn respond(caller: &mut Caller, response: Vec<u8>) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .respond(response)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Subscribes to a message channel from another chain.
     fn subscribe(
         caller: &mut Caller,
@@ -345,6 +384,57 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Subscribes this application to an event stream of another chain.
+    fn subscribe_to_events(
+        caller: &mut Caller,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+    ) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .subscribe_to_events(chain_id, application_id, name)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Unsubscribes this application from an event stream of another chain.
+    fn unsubscribe_from_events(
+        caller: &mut Caller,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+    ) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .unsubscribe_from_events(chain_id, application_id, name)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Reads the value of an event emitted on a stream of another chain.
+    fn read_event(
+        caller: &mut Caller,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        key: Vec<u8>,
+    ) -> Result<Vec<u8>, RuntimeError> {
+        let event_id = EventId {
+            chain_id,
+            stream_id: StreamId {
+                application_id: application_id.into(),
+                stream_name: name,
+            },
+            key,
+        };
+        caller
+            .user_data_mut()
+            .runtime
+            .read_event(event_id)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Queries a service and returns the response.
     fn query_service(
         caller: &mut Caller,
@@ -598,6 +688,29 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Reads the value of an event emitted on a stream of another chain.
+    fn read_event(
+        caller: &mut Caller,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        key: Vec<u8>,
+    ) -> Result<Vec<u8>, RuntimeError> {
+        let event_id = EventId {
+            chain_id,
+            stream_id: StreamId {
+                application_id: application_id.into(),
+                stream_name: name,
+            },
+            key,
+        };
+        caller
+            .user_data_mut()
+            .runtime
+            .read_event(event_id)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Aborts the query if the current time at block validation is `>= timestamp`. Note that block
     /// validation happens at or after the block timestamp, but isn't necessarily the same.
     fn assert_before(caller: &mut Caller, timestamp: Timestamp) -> Result<(), RuntimeError> {
@@ -771,6 +884,59 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Creates a new promise to search for the keys of a range, after the `key_prefix`.
+    /// The keys start from `start`, stop before `end` if any, are listed in decreasing
+    /// order if `reverse` is set, and at most `limit` of them are returned. The promise is
+    /// awaited with `find_keys_wait`.
+    fn find_keys_in_range_new(
+        caller: &mut Caller,
+        key_prefix: Vec<u8>,
+        start: Vec<u8>,
+        end: Option<Vec<u8>>,
+        reverse: bool,
+        limit: Option<u32>,
+    ) -> Result<u32, RuntimeError> {
+        let range = KeyRange {
+            start,
+            end,
+            reverse,
+            limit: limit.map(|limit| limit as usize),
+        };
+        let mut data = caller.user_data_mut();
+        let promise = data
+            .runtime
+            .find_keys_in_range_new(key_prefix, range)
+            .map_err(|error| RuntimeError::Custom(error.into()))?;
+
+        data.register_promise(promise)
+    }
+
+    /// Creates a new promise to search for the entries of a range of keys, after the
+    /// `key_prefix`. The range is described as in `find_keys_in_range_new`, and the promise
+    /// is awaited with `find_key_values_wait`.
+    fn find_key_values_in_range_new(
+        caller: &mut Caller,
+        key_prefix: Vec<u8>,
+        start: Vec<u8>,
+        end: Option<Vec<u8>>,
+        reverse: bool,
+        limit: Option<u32>,
+    ) -> Result<u32, RuntimeError> {
+        let range = KeyRange {
+            start,
+            end,
+            reverse,
+            limit: limit.map(|limit| limit as usize),
+        };
+        let mut data = caller.user_data_mut();
+        let promise = data
+            .runtime
+            .find_key_values_in_range_new(key_prefix, range)
+            .map_err(|error| RuntimeError::Custom(error.into()))?;
+
+        data.register_promise(promise)
+    }
+
     /// Writes a batch of `operations` to storage.
     fn write_batch(
         caller: &mut Caller,
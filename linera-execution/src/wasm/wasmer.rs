@@ -5,7 +5,10 @@
 
 use std::{marker::Unpin, sync::LazyLock};
 
-use linera_base::data_types::Bytecode;
+use linera_base::{
+    data_types::Bytecode,
+    identifiers::{EventId, MessageId},
+};
 use linera_witty::{
     wasmer::{EntrypointInstance, InstanceBuilder},
     ExportTo,
@@ -161,6 +164,38 @@ where
         Ok(())
     }
 
+    fn process_event(
+        &mut self,
+        _context: MessageContext,
+        event_id: EventId,
+        value: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        ContractEntrypoints::new(&mut self.instance)
+            .process_event(event_id, value)
+            .map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
+    fn handle_response(
+        &mut self,
+        _context: MessageContext,
+        request_id: MessageId,
+        request: Vec<u8>,
+        response: Option<Vec<u8>>,
+    ) -> Result<(), ExecutionError> {
+        ContractEntrypoints::new(&mut self.instance)
+            .handle_response(request_id, request, response)
+            .map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
+    fn migrate(&mut self) -> Result<(), ExecutionError> {
+        ContractEntrypoints::new(&mut self.instance)
+            .migrate()
+            .map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
     fn finalize(&mut self, _context: FinalizeContext) -> Result<(), ExecutionError> {
         ContractEntrypoints::new(&mut self.instance)
             .finalize()
@@ -5,7 +5,10 @@
 
 use std::sync::LazyLock;
 
-use linera_base::data_types::Bytecode;
+use linera_base::{
+    data_types::Bytecode,
+    identifiers::{EventId, MessageId},
+};
 use linera_witty::{wasmtime::EntrypointInstance, ExportTo, Instance};
 use tokio::sync::Mutex;
 use wasmtime::{AsContextMut, Config, Engine, Linker, Module, Store};
@@ -206,6 +209,42 @@ where
         Ok(())
     }
 
+    fn process_event(
+        &mut self,
+        _context: MessageContext,
+        event_id: EventId,
+        value: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        self.configure_initial_fuel()?;
+        let result = ContractEntrypoints::new(&mut self.instance).process_event(event_id, value);
+        self.persist_remaining_fuel()?;
+        result.map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
+    fn handle_response(
+        &mut self,
+        _context: MessageContext,
+        request_id: MessageId,
+        request: Vec<u8>,
+        response: Option<Vec<u8>>,
+    ) -> Result<(), ExecutionError> {
+        self.configure_initial_fuel()?;
+        let result = ContractEntrypoints::new(&mut self.instance)
+            .handle_response(request_id, request, response);
+        self.persist_remaining_fuel()?;
+        result.map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
+    fn migrate(&mut self) -> Result<(), ExecutionError> {
+        self.configure_initial_fuel()?;
+        let result = ContractEntrypoints::new(&mut self.instance).migrate();
+        self.persist_remaining_fuel()?;
+        result.map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
     fn finalize(&mut self, _context: FinalizeContext) -> Result<(), ExecutionError> {
         self.configure_initial_fuel()?;
         let result = ContractEntrypoints::new(&mut self.instance).finalize();
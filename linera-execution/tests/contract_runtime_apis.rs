@@ -13,22 +13,24 @@ use assert_matches::assert_matches;
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        Amount, Blob, BlockHeight, CompressedBytecode, Timestamp, UserApplicationDescription,
+        Amount, Blob, BlockHeight, CompressedBytecode, OracleResponse, Timestamp,
+        UserApplicationDescription,
     },
     identifiers::{
-        Account, AccountOwner, ApplicationId, BytecodeId, ChainDescription, ChainId, MessageId,
-        Owner,
+        Account, AccountOwner, ApplicationId, BytecodeId, ChainDescription, ChainId, Destination,
+        EventId, MessageId, Owner, StreamId, StreamName,
     },
     ownership::ChainOwnership,
 };
 use linera_execution::{
+    system::MAX_EVENT_STREAM_SUBSCRIBERS,
     test_utils::{
         create_dummy_message_context, create_dummy_operation_context, test_accounts_strategy,
         ExpectedCall, RegisterMockApplication, SystemExecutionState,
     },
-    BaseRuntime, ContractRuntime, ExecutionError, ExecutionOutcome, Message, MessageContext,
-    Operation, OperationContext, ResourceController, SystemExecutionError,
-    SystemExecutionStateView, TestExecutionRuntimeContext, TransactionTracker,
+    BaseRuntime, ContractRuntime, ExecutionError, ExecutionOutcome, ExecutionStateView, Message,
+    MessageContext, Operation, OperationContext, ResourceController, SystemExecutionError,
+    SystemExecutionStateView, SystemMessage, TestExecutionRuntimeContext, TransactionTracker,
 };
 use linera_views::context::MemoryContext;
 use test_case::test_matrix;
@@ -620,6 +622,418 @@ async fn test_read_balance_owners_system_api(
     .unwrap();
 }
 
+/// Tests the contract system API to read an event from another chain's stream, and that the
+/// value is recorded as an oracle response.
+#[test_log::test(tokio::test)]
+async fn test_read_event_system_api() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+
+    let event_id = EventId {
+        chain_id: ChainId::root(1),
+        stream_id: StreamId {
+            application_id: application_id.into(),
+            stream_name: StreamName(b"stream".to_vec()),
+        },
+        key: b"key".to_vec(),
+    };
+    let value = b"value".to_vec();
+    view.context()
+        .extra()
+        .add_events([(event_id.clone(), value.clone())])
+        .await?;
+
+    application.expect_call(ExpectedCall::execute_operation({
+        let event_id = event_id.clone();
+        let value = value.clone();
+        move |runtime, _context, _operation| {
+            assert_eq!(runtime.read_event(event_id)?, value);
+            Ok(vec![])
+        }
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context();
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new(0, None);
+
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation,
+        &mut tracker,
+        &mut controller,
+    )
+    .await?;
+
+    let (_, oracle_responses, _) = tracker.destructure()?;
+    assert_eq!(
+        oracle_responses,
+        vec![OracleResponse::Event(event_id, value)]
+    );
+
+    Ok(())
+}
+
+/// Tests the contract system APIs to subscribe to and unsubscribe from an event stream of
+/// another chain.
+#[test_log::test(tokio::test)]
+async fn test_subscribe_to_events_system_api() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+    let publisher_id = ChainId::root(1);
+    let stream_name = StreamName(b"stream".to_vec());
+    let stream_id = StreamId {
+        application_id: application_id.into(),
+        stream_name: stream_name.clone(),
+    };
+
+    // Subscribing twice only sends one message to the publisher.
+    application.expect_call(ExpectedCall::execute_operation({
+        let stream_name = stream_name.clone();
+        move |runtime, _context, _operation| {
+            runtime.subscribe_to_events(publisher_id, application_id, stream_name.clone())?;
+            runtime.subscribe_to_events(publisher_id, application_id, stream_name)?;
+            Ok(vec![])
+        }
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+    let outcomes = execute_dummy_operation(&mut view, application_id).await?;
+    assert_eq!(
+        system_messages(&outcomes),
+        vec![(
+            Destination::Recipient(publisher_id),
+            SystemMessage::SubscribeToEvents {
+                stream_id: stream_id.clone()
+            }
+        )]
+    );
+    let subscribers = view
+        .system
+        .event_subscriptions
+        .get(&(publisher_id, stream_id.clone()))
+        .await?;
+    assert_eq!(subscribers, Some(BTreeSet::from([application_id])));
+
+    // Stream names are limited in length when unsubscribing, too.
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            let stream_name = StreamName(vec![0; 65]);
+            assert_matches!(
+                runtime.unsubscribe_from_events(publisher_id, application_id, stream_name),
+                Err(ExecutionError::StreamNameTooLong)
+            );
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+    let outcomes = execute_dummy_operation(&mut view, application_id).await?;
+    assert!(system_messages(&outcomes).is_empty());
+
+    application.expect_call(ExpectedCall::execute_operation({
+        let stream_name = stream_name.clone();
+        move |runtime, _context, _operation| {
+            runtime.unsubscribe_from_events(publisher_id, application_id, stream_name)?;
+            Ok(vec![])
+        }
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+    let outcomes = execute_dummy_operation(&mut view, application_id).await?;
+    assert_eq!(
+        system_messages(&outcomes),
+        vec![(
+            Destination::Recipient(publisher_id),
+            SystemMessage::UnsubscribeFromEvents {
+                stream_id: stream_id.clone()
+            }
+        )]
+    );
+    let subscribers = view
+        .system
+        .event_subscriptions
+        .get(&(publisher_id, stream_id))
+        .await?;
+    assert_eq!(subscribers, None);
+
+    Ok(())
+}
+
+/// Tests that the events emitted on a stream are sent to the chains subscribed to it, until
+/// they unsubscribe.
+#[test_log::test(tokio::test)]
+async fn test_emit_to_event_subscribers() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+    let subscriber_id = ChainId::root(1);
+    let stream_name = StreamName(b"stream".to_vec());
+    let stream_id = StreamId {
+        application_id: application_id.into(),
+        stream_name: stream_name.clone(),
+    };
+    let subscriber_context = MessageContext {
+        message_id: MessageId {
+            chain_id: subscriber_id,
+            height: BlockHeight(0),
+            index: 0,
+        },
+        ..create_dummy_message_context(None)
+    };
+
+    let mut controller = ResourceController::default();
+    view.execute_message(
+        subscriber_context,
+        Timestamp::from(0),
+        Message::System(SystemMessage::SubscribeToEvents {
+            stream_id: stream_id.clone(),
+        }),
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+
+    application.expect_call(ExpectedCall::execute_operation({
+        let stream_name = stream_name.clone();
+        move |runtime, _context, _operation| {
+            runtime.emit(stream_name, b"key".to_vec(), b"value".to_vec())?;
+            Ok(vec![])
+        }
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+    let outcomes = execute_dummy_operation(&mut view, application_id).await?;
+    let event_id = EventId {
+        chain_id: ChainId::root(0),
+        stream_id: stream_id.clone(),
+        key: b"key".to_vec(),
+    };
+    assert_eq!(
+        system_messages(&outcomes),
+        vec![(
+            Destination::Recipient(subscriber_id),
+            SystemMessage::Event {
+                event_id,
+                value: b"value".to_vec(),
+            }
+        )]
+    );
+
+    view.execute_message(
+        subscriber_context,
+        Timestamp::from(0),
+        Message::System(SystemMessage::UnsubscribeFromEvents {
+            stream_id: stream_id.clone(),
+        }),
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+    assert_eq!(view.system.event_subscribers.get(&stream_id).await?, None);
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.emit(stream_name, b"key2".to_vec(), b"value".to_vec())?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+    let outcomes = execute_dummy_operation(&mut view, application_id).await?;
+    assert!(system_messages(&outcomes).is_empty());
+
+    Ok(())
+}
+
+/// Tests that an event stream accepts no more than [`MAX_EVENT_STREAM_SUBSCRIBERS`]
+/// subscribers.
+#[test_log::test(tokio::test)]
+async fn test_event_subscribers_limit() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, _application) = view.register_mock_application().await?;
+    let stream_id = StreamId {
+        application_id: application_id.into(),
+        stream_name: StreamName(b"stream".to_vec()),
+    };
+    let subscribers = (1..=MAX_EVENT_STREAM_SUBSCRIBERS as u32)
+        .map(ChainId::root)
+        .collect::<BTreeSet<_>>();
+    view.system
+        .event_subscribers
+        .insert(&stream_id, subscribers)?;
+    // Existing subscribers can subscribe again, but new ones are rejected.
+    subscribe_to_events(&mut view, ChainId::root(1), &stream_id).await?;
+    let new_subscriber_id = ChainId::root(MAX_EVENT_STREAM_SUBSCRIBERS as u32 + 1);
+    let result = subscribe_to_events(&mut view, new_subscriber_id, &stream_id).await;
+    assert_matches!(
+        result,
+        Err(ExecutionError::SystemError(
+            SystemExecutionError::TooManyEventSubscribers(id)
+        )) if *id == stream_id
+    );
+
+    Ok(())
+}
+
+/// Executes the message subscribing `subscriber_id` to the event stream `stream_id`.
+async fn subscribe_to_events(
+    view: &mut ExecutionStateView<MemoryContext<TestExecutionRuntimeContext>>,
+    subscriber_id: ChainId,
+    stream_id: &StreamId,
+) -> Result<(), ExecutionError> {
+    let context = MessageContext {
+        message_id: MessageId {
+            chain_id: subscriber_id,
+            height: BlockHeight(0),
+            index: 0,
+        },
+        ..create_dummy_message_context(None)
+    };
+    view.execute_message(
+        context,
+        Timestamp::from(0),
+        Message::System(SystemMessage::SubscribeToEvents {
+            stream_id: stream_id.clone(),
+        }),
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut ResourceController::default(),
+    )
+    .await
+}
+
+/// Tests that new events are handed over to the `process_event` entrypoint of the
+/// applications subscribed to their stream, and only to them.
+#[test_log::test(tokio::test)]
+async fn test_process_event() -> anyhow::Result<()> {
+    let mut view = SystemExecutionState {
+        description: Some(ChainDescription::Root(0)),
+        ..SystemExecutionState::default()
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+    let publisher_id = ChainId::root(1);
+    let stream_name = StreamName(b"stream".to_vec());
+    let event_id = EventId {
+        chain_id: publisher_id,
+        stream_id: StreamId {
+            application_id: application_id.into(),
+            stream_name: stream_name.clone(),
+        },
+        key: b"key".to_vec(),
+    };
+    let value = b"value".to_vec();
+    let event = Message::System(SystemMessage::Event {
+        event_id: event_id.clone(),
+        value: value.clone(),
+    });
+
+    // Events of streams without subscribers are ignored.
+    let mut controller = ResourceController::default();
+    view.execute_message(
+        create_dummy_message_context(None),
+        Timestamp::from(0),
+        event.clone(),
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.subscribe_to_events(publisher_id, application_id, stream_name)?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+    execute_dummy_operation(&mut view, application_id).await?;
+
+    application.expect_call(ExpectedCall::process_event({
+        let event_id = event_id.clone();
+        move |_runtime, _context, received_event_id, received_value| {
+            assert_eq!(received_event_id, event_id);
+            assert_eq!(received_value, value);
+            Ok(())
+        }
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+    view.execute_message(
+        create_dummy_message_context(None),
+        Timestamp::from(0),
+        event,
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Executes an empty operation of `application_id` and returns the outcomes of the
+/// transaction.
+async fn execute_dummy_operation(
+    view: &mut ExecutionStateView<MemoryContext<TestExecutionRuntimeContext>>,
+    application_id: ApplicationId,
+) -> anyhow::Result<Vec<ExecutionOutcome>> {
+    let mut tracker = TransactionTracker::new(0, Some(Vec::new()));
+    view.execute_operation(
+        create_dummy_operation_context(),
+        Timestamp::from(0),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut tracker,
+        &mut ResourceController::default(),
+    )
+    .await?;
+    let (outcomes, _, _) = tracker.destructure()?;
+    Ok(outcomes)
+}
+
+/// Returns the destinations and contents of the system messages in `outcomes`.
+fn system_messages(outcomes: &[ExecutionOutcome]) -> Vec<(Destination, SystemMessage)> {
+    outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            ExecutionOutcome::System(outcome) => Some(&outcome.messages),
+            ExecutionOutcome::User(..) => None,
+        })
+        .flatten()
+        .map(|message| (message.destination.clone(), message.message.clone()))
+        .collect()
+}
+
 /// A test helper representing a transfer endpoint.
 #[derive(Clone, Copy, Debug)]
 enum TransferTestEndpoint {
@@ -653,6 +1067,7 @@ impl TransferTestEndpoint {
             },
             parameters: vec![],
             required_application_ids: vec![],
+            admin: None,
         }
     }
 
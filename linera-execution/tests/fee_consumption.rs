@@ -150,6 +150,9 @@ async fn test_fee_consumption(
         maximum_block_proposal_size: 47,
         maximum_bytes_read_per_block: 53,
         maximum_bytes_written_per_block: 59,
+        maximum_operations_per_block: 61,
+        maximum_incoming_bundles_per_block: 67,
+        maximum_operation_argument_bytes: 71,
     };
 
     let consumed_fees = spends
@@ -187,6 +190,7 @@ async fn test_fee_consumption(
     let context = MessageContext {
         chain_id: ChainId::root(0),
         is_bouncing: false,
+        is_request: false,
         authenticated_signer,
         refund_grant_to,
         height: BlockHeight(0),
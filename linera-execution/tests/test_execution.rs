@@ -9,29 +9,36 @@ use anyhow::Context as _;
 use assert_matches::assert_matches;
 use futures::{stream, StreamExt, TryStreamExt};
 use linera_base::{
-    crypto::PublicKey,
+    crypto::{CryptoHash, PublicKey},
     data_types::{
-        Amount, ApplicationPermissions, BlockHeight, Resources, SendMessageRequest, Timestamp,
+        Amount, ApplicationPermissions, Blob, BlockHeight, CompressedBytecode, Resources,
+        SendMessageRequest, Timestamp,
     },
     identifiers::{
-        Account, AccountOwner, ChainDescription, ChainId, Destination, MessageId, Owner,
+        Account, AccountOwner, BytecodeId, ChainDescription, ChainId, Destination, MessageId,
+        Owner, UserApplicationId,
     },
     ownership::ChainOwnership,
+    state_proof::{execution_state_hash, StateProofError},
 };
 use linera_execution::{
     committee::{Committee, Epoch},
-    system::{SystemExecutionError, SystemMessage},
+    system::{request_deadline, SystemChannel, SystemExecutionError, SystemMessage},
     test_utils::{
         create_dummy_message_context, create_dummy_operation_context,
-        create_dummy_user_application_registrations, ExpectedCall, RegisterMockApplication,
-        SystemExecutionState,
+        create_dummy_user_application_description, create_dummy_user_application_registrations,
+        ExpectedCall, MockApplication, RegisterMockApplication, SystemExecutionState,
     },
-    BaseRuntime, ContractRuntime, ExecutionError, ExecutionOutcome, ExecutionRuntimeContext,
-    Message, MessageKind, Operation, OperationContext, Query, QueryContext, RawExecutionOutcome,
-    RawOutgoingMessage, ResourceControlPolicy, ResourceController, Response, SystemOperation,
-    TransactionTracker,
+    ApplicationUpgrade, BaseRuntime, ChannelSubscription, ContractRuntime, ExecutionError,
+    ExecutionOutcome, ExecutionRuntimeContext, Message, MessageContext, MessageKind, Operation,
+    OperationContext, Query, QueryContext, RawExecutionOutcome, RawOutgoingMessage,
+    ResourceControlPolicy, ResourceController, Response, SystemOperation, TransactionTracker,
+};
+use linera_views::{
+    batch::Batch,
+    context::Context,
+    views::{CryptoHashView, HashableView as _, View},
 };
-use linera_views::{batch::Batch, context::Context, views::View};
 use test_case::test_case;
 
 #[tokio::test]
@@ -642,6 +649,7 @@ async fn test_sending_message_from_finalize() -> anyhow::Result<()> {
         destination: Destination::from(destination_chain),
         authenticated: false,
         grant: Amount::ZERO,
+        not_before: None,
         kind: MessageKind::Simple,
         message: SystemMessage::RegisterApplications { applications },
     };
@@ -962,6 +970,7 @@ async fn test_simple_message() -> anyhow::Result<()> {
         destination: Destination::from(destination_chain),
         authenticated: false,
         grant: Amount::ZERO,
+        not_before: None,
         kind: MessageKind::Simple,
         message: SystemMessage::RegisterApplications {
             applications: vec![application_description],
@@ -995,6 +1004,60 @@ async fn test_simple_message() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that a delayed message is sent with its not-before timestamp.
+#[tokio::test]
+async fn test_delayed_message() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+
+    let dummy_message = SendMessageRequest {
+        destination: Destination::from(ChainId::root(1)),
+        authenticated: false,
+        is_tracked: false,
+        grant: Resources::default(),
+        message: b"msg".to_vec(),
+    };
+    let not_before = Timestamp::from(1_000);
+
+    let mut expected_dummy_message = RawOutgoingMessage::from(dummy_message.clone())
+        .into_priced(&ResourceControlPolicy::default())?;
+    expected_dummy_message.not_before = Some(not_before);
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _context, _operation| {
+            runtime.send_delayed_message(dummy_message, not_before)?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let mut controller = ResourceController::default();
+    let mut txn_tracker = TransactionTracker::new(0, Some(Vec::new()));
+    view.execute_operation(
+        create_dummy_operation_context(),
+        Timestamp::from(0),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+
+    let (outcomes, _, _) = txn_tracker.destructure()?;
+    assert_matches!(
+        &outcomes[0],
+        ExecutionOutcome::User(id, outcome)
+            if *id == application_id && outcome.messages == [expected_dummy_message]
+    );
+
+    Ok(())
+}
+
 /// Tests if a message is scheduled to be sent while an application is handling a cross-application
 /// call.
 #[tokio::test]
@@ -1058,6 +1121,7 @@ async fn test_message_from_cross_application_call() -> anyhow::Result<()> {
         destination: Destination::from(destination_chain),
         authenticated: false,
         grant: Amount::ZERO,
+        not_before: None,
         kind: MessageKind::Simple,
         message: SystemMessage::RegisterApplications {
             applications: vec![target_description],
@@ -1168,6 +1232,7 @@ async fn test_message_from_deeper_call() -> anyhow::Result<()> {
         destination: Destination::from(destination_chain),
         authenticated: false,
         grant: Amount::ZERO,
+        not_before: None,
         kind: MessageKind::Simple,
         message: SystemMessage::RegisterApplications {
             applications: vec![target_description],
@@ -1336,6 +1401,7 @@ async fn test_multiple_messages_from_different_applications() -> anyhow::Result<
         destination: Destination::from(first_destination_chain),
         authenticated: false,
         grant: Amount::ZERO,
+        not_before: None,
         kind: MessageKind::Simple,
         message: SystemMessage::RegisterApplications {
             applications: vec![sending_target_description.clone(), caller_description],
@@ -1346,6 +1412,7 @@ async fn test_multiple_messages_from_different_applications() -> anyhow::Result<
         destination: Destination::from(second_destination_chain),
         authenticated: false,
         grant: Amount::ZERO,
+        not_before: None,
         kind: MessageKind::Simple,
         message: SystemMessage::RegisterApplications {
             applications: vec![sending_target_description],
@@ -1586,6 +1653,635 @@ async fn test_close_chain() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests upgrading an application, which runs the new code's migration exactly once.
+#[tokio::test]
+async fn test_application_upgrade() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let admin = Owner::from(PublicKey::test_key(0));
+    let (mut description, contract_blob, service_blob) =
+        create_dummy_user_application_description(0);
+    description.admin = Some(admin);
+    // Upgrades can only happen on the chain that created the application.
+    description.creation.chain_id = ChainId::root(0);
+    let (application_id, _application) = view
+        .register_mock_application_with(description, contract_blob, service_blob)
+        .await?;
+
+    // Make the new bytecode available, with its own mock contract.
+    let new_contract_blob = Blob::new_contract_bytecode(CompressedBytecode {
+        compressed_bytes: b"new contract".to_vec(),
+    });
+    let new_service_blob = Blob::new_service_bytecode(CompressedBytecode {
+        compressed_bytes: b"new service".to_vec(),
+    });
+    let new_bytecode_id = BytecodeId::new(new_contract_blob.id().hash, new_service_blob.id().hash);
+    let upgraded_application = MockApplication::default();
+    let upgraded_code_id = UserApplicationId {
+        bytecode_id: new_bytecode_id,
+        creation: application_id.creation,
+    };
+    let extra = view.context().extra();
+    extra
+        .user_contracts()
+        .insert(upgraded_code_id, upgraded_application.clone().into());
+    extra
+        .add_blobs([new_contract_blob, new_service_blob])
+        .await?;
+
+    // Only the admin can upgrade the application.
+    let operation = SystemOperation::UpgradeApplication {
+        application_id,
+        bytecode_id: new_bytecode_id,
+    };
+    let mut controller = ResourceController::default();
+    let result = view
+        .execute_operation(
+            create_dummy_operation_context(),
+            Timestamp::from(0),
+            operation.clone().into(),
+            &mut TransactionTracker::default(),
+            &mut controller,
+        )
+        .await;
+    assert_matches!(
+        result,
+        Err(ExecutionError::SystemError(
+            SystemExecutionError::UnauthorizedApplicationUpgrade(_)
+        ))
+    );
+
+    let context = OperationContext {
+        authenticated_signer: Some(admin),
+        ..create_dummy_operation_context()
+    };
+    let mut txn_tracker = TransactionTracker::default();
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation.into(),
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    let upgrade = ApplicationUpgrade {
+        bytecode_id: new_bytecode_id,
+        version: 1,
+    };
+    assert_eq!(
+        view.system.registry.upgrades.get(&application_id).await?,
+        Some(upgrade)
+    );
+
+    // The upgrade is broadcast to the chains using the application.
+    let (outcomes, _, _) = txn_tracker.destructure()?;
+    assert_eq!(
+        outcomes,
+        vec![ExecutionOutcome::System(
+            RawExecutionOutcome::default().with_message(RawOutgoingMessage {
+                destination: Destination::Subscribers(SystemChannel::Upgrades.name()),
+                authenticated: false,
+                grant: Amount::ZERO,
+                not_before: None,
+                kind: MessageKind::Protected,
+                message: SystemMessage::UpgradeApplications {
+                    upgrades: vec![(application_id, upgrade)],
+                },
+            })
+        )]
+    );
+
+    // The first operation runs the migration before the new code handles it.
+    upgraded_application.expect_call(ExpectedCall::migrate(|_runtime| Ok(())));
+    upgraded_application.expect_call(ExpectedCall::execute_operation(
+        |_runtime, _context, _operation| Ok(vec![]),
+    ));
+    upgraded_application.expect_call(ExpectedCall::default_finalize());
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation.clone(),
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+    assert!(
+        !view
+            .system
+            .registry
+            .pending_migrations
+            .contains(&application_id)
+            .await?
+    );
+
+    // Later operations don't migrate again.
+    upgraded_application.expect_call(ExpectedCall::execute_operation(
+        |_runtime, _context, _operation| Ok(vec![]),
+    ));
+    upgraded_application.expect_call(ExpectedCall::default_finalize());
+    view.execute_operation(
+        context,
+        Timestamp::from(0),
+        operation,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Tests responding to a request, and bouncing a rejected request as a failed response.
+#[tokio::test]
+async fn test_request_and_response() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let (application_id, application) = view.register_mock_application().await?;
+
+    let sender_chain_id = ChainId::root(1);
+    let request_id = MessageId {
+        chain_id: sender_chain_id,
+        height: BlockHeight(3),
+        index: 1,
+    };
+    let context = MessageContext {
+        is_request: true,
+        message_id: request_id,
+        ..create_dummy_message_context(None)
+    };
+    let request = Message::User {
+        application_id,
+        bytes: b"ping".to_vec(),
+    };
+
+    // The receiving application can respond to the request only once.
+    application.expect_call(ExpectedCall::execute_message(
+        |runtime, _context, _message| {
+            runtime.respond(b"pong".to_vec())?;
+            assert_matches!(
+                runtime.respond(b"pong".to_vec()),
+                Err(ExecutionError::NoRequestToRespondTo)
+            );
+            Ok(())
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+    let mut controller = ResourceController::default();
+    let mut txn_tracker = TransactionTracker::new(0, Some(Vec::new()));
+    view.execute_message(
+        context,
+        Timestamp::from(0),
+        request.clone(),
+        None,
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    let (outcomes, _, _) = txn_tracker.destructure()?;
+    assert_eq!(
+        outcomes[0],
+        ExecutionOutcome::System(
+            RawExecutionOutcome::default().with_message(RawOutgoingMessage {
+                destination: Destination::Recipient(sender_chain_id),
+                authenticated: false,
+                grant: Amount::ZERO,
+                not_before: None,
+                kind: MessageKind::Protected,
+                message: SystemMessage::Response {
+                    application_id,
+                    request_id,
+                    request: b"ping".to_vec(),
+                    response: Some(b"pong".to_vec()),
+                },
+            })
+        )
+    );
+
+    // Other messages can't be responded to.
+    application.expect_call(ExpectedCall::execute_message(
+        |runtime, _context, _message| runtime.respond(b"pong".to_vec()),
+    ));
+    let result = view
+        .execute_message(
+            MessageContext {
+                is_request: false,
+                ..context
+            },
+            Timestamp::from(0),
+            request.clone(),
+            None,
+            &mut TransactionTracker::new(0, Some(Vec::new())),
+            &mut controller,
+        )
+        .await;
+    assert_matches!(result, Err(ExecutionError::NoRequestToRespondTo));
+
+    // A rejected request returns a failed response instead of bouncing.
+    let mut txn_tracker = TransactionTracker::new(0, Some(Vec::new()));
+    view.bounce_message(context, Amount::ZERO, request, &mut txn_tracker)
+        .await?;
+    let (outcomes, _, _) = txn_tracker.destructure()?;
+    assert_eq!(
+        outcomes,
+        vec![ExecutionOutcome::System(
+            RawExecutionOutcome::default().with_message(RawOutgoingMessage {
+                destination: Destination::Recipient(sender_chain_id),
+                authenticated: true,
+                grant: Amount::ZERO,
+                not_before: None,
+                kind: MessageKind::Protected,
+                message: SystemMessage::Response {
+                    application_id,
+                    request_id,
+                    request: b"ping".to_vec(),
+                    response: None,
+                },
+            })
+        )]
+    );
+
+    Ok(())
+}
+
+/// Tests that the sender of a request waits for the response from the recipient only, and
+/// that the request fails if the response doesn't arrive in time.
+#[tokio::test]
+async fn test_outstanding_requests() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+    let chain_id = ChainId::root(0);
+    let recipient_id = ChainId::root(1);
+
+    let (application_id, application) = view.register_mock_application().await?;
+
+    // The application sends a request in each of two blocks.
+    let mut controller = ResourceController::default();
+    let mut request_ids = Vec::new();
+    let timestamps = [Timestamp::from(0), Timestamp::from(1_000)];
+    for (height, timestamp) in [BlockHeight(0), BlockHeight(1)].into_iter().zip(timestamps) {
+        application.expect_call(ExpectedCall::execute_operation(
+            move |runtime, _context, _operation| {
+                runtime.send_request(SendMessageRequest {
+                    destination: Destination::Recipient(recipient_id),
+                    authenticated: false,
+                    is_tracked: true,
+                    grant: Amount::ZERO,
+                    message: b"ping".to_vec(),
+                })?;
+                Ok(vec![])
+            },
+        ));
+        application.expect_call(ExpectedCall::default_finalize());
+        let context = OperationContext {
+            height,
+            ..create_dummy_operation_context()
+        };
+        let operation = Operation::User {
+            application_id,
+            bytes: vec![],
+        };
+        view.system.timestamp.set(timestamp);
+        let mut txn_tracker = TransactionTracker::new(0, Some(Vec::new()));
+        view.execute_operation(
+            context,
+            Timestamp::from(0),
+            operation,
+            &mut txn_tracker,
+            &mut controller,
+        )
+        .await?;
+        view.record_outstanding_requests(height, timestamp, &mut txn_tracker)
+            .await?;
+        let request_id = MessageId {
+            chain_id,
+            height,
+            index: 0,
+        };
+        let (outcomes, _, _) = txn_tracker.destructure()?;
+        if height == BlockHeight(0) {
+            assert_eq!(
+                outcomes[1],
+                timeout_outcome(chain_id, request_deadline(timestamp))
+            );
+        } else {
+            // The first timeout is still pending, so no other one is scheduled.
+            assert_eq!(outcomes.len(), 1);
+        }
+        assert_eq!(
+            view.system
+                .outstanding_requests
+                .get(&request_id)
+                .await?
+                .map(|request| request.deadline),
+            Some(request_deadline(timestamp))
+        );
+        request_ids.push(request_id);
+    }
+
+    let response = |request_id| {
+        Message::System(SystemMessage::Response {
+            application_id,
+            request_id,
+            request: b"ping".to_vec(),
+            response: Some(b"pong".to_vec()),
+        })
+    };
+    let sent_by = |sender| MessageContext {
+        message_id: MessageId {
+            chain_id: sender,
+            height: BlockHeight(0),
+            index: 0,
+        },
+        ..create_dummy_message_context(None)
+    };
+
+    // A response from another chain is ignored.
+    view.execute_message(
+        sent_by(ChainId::root(2)),
+        Timestamp::from(0),
+        response(request_ids[0]),
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+    assert!(
+        view.system
+            .outstanding_requests
+            .contains_key(&request_ids[0])
+            .await?
+    );
+
+    // The response from the recipient is handed over to the application.
+    let first_request_id = request_ids[0];
+    application.expect_call(ExpectedCall::handle_response(
+        move |_runtime, _context, request_id, request, response| {
+            assert_eq!(request_id, first_request_id);
+            assert_eq!(request, b"ping");
+            assert_eq!(response, Some(b"pong".to_vec()));
+            Ok(())
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+    view.execute_message(
+        sent_by(recipient_id),
+        Timestamp::from(0),
+        response(request_ids[0]),
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+    assert!(
+        !view
+            .system
+            .outstanding_requests
+            .contains_key(&request_ids[0])
+            .await?
+    );
+    assert_eq!(view.system.request_deadlines.count().await?, 1);
+
+    // A repeated response doesn't reach the application anymore, and neither does the first
+    // timeout, which is received before the second request times out. The next timeout is
+    // then scheduled.
+    view.execute_message(
+        sent_by(recipient_id),
+        Timestamp::from(0),
+        response(request_ids[0]),
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+    let first_deadline = request_deadline(timestamps[0]);
+    view.system.timestamp.set(first_deadline);
+    let mut txn_tracker = TransactionTracker::new(0, Some(Vec::new()));
+    view.execute_message(
+        sent_by(chain_id),
+        Timestamp::from(0),
+        Message::System(SystemMessage::RequestTimeout),
+        None,
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+    view.record_outstanding_requests(BlockHeight(2), first_deadline, &mut txn_tracker)
+        .await?;
+    let second_deadline = request_deadline(timestamps[1]);
+    let (outcomes, _, _) = txn_tracker.destructure()?;
+    assert_eq!(outcomes, vec![timeout_outcome(chain_id, second_deadline)]);
+
+    // The second request times out, and the application is told that it failed.
+    let second_request_id = request_ids[1];
+    application.expect_call(ExpectedCall::handle_response(
+        move |_runtime, _context, request_id, request, response| {
+            assert_eq!(request_id, second_request_id);
+            assert_eq!(request, b"ping");
+            assert_eq!(response, None);
+            Ok(())
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+    view.system.timestamp.set(second_deadline);
+    view.execute_message(
+        sent_by(chain_id),
+        Timestamp::from(0),
+        Message::System(SystemMessage::RequestTimeout),
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+
+    // The late response is ignored.
+    view.execute_message(
+        sent_by(recipient_id),
+        Timestamp::from(0),
+        response(request_ids[1]),
+        None,
+        &mut TransactionTracker::new(0, Some(Vec::new())),
+        &mut controller,
+    )
+    .await?;
+    assert!(view.system.outstanding_requests.indices().await?.is_empty());
+    assert_eq!(view.system.request_deadlines.count().await?, 0);
+    assert_eq!(*view.system.request_timer.get(), None);
+
+    Ok(())
+}
+
+/// Returns the outcome scheduling the timeout of the outstanding requests of `chain_id`.
+fn timeout_outcome(chain_id: ChainId, deadline: Timestamp) -> ExecutionOutcome {
+    ExecutionOutcome::System(
+        RawExecutionOutcome::default().with_message(RawOutgoingMessage {
+            destination: Destination::Recipient(chain_id),
+            authenticated: false,
+            grant: Amount::ZERO,
+            not_before: Some(deadline),
+            kind: MessageKind::Protected,
+            message: SystemMessage::RequestTimeout,
+        }),
+    )
+}
+
+/// Tests that an application can't be upgraded on a chain other than the one that created it.
+#[tokio::test]
+async fn test_application_upgrade_outside_creator_chain() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    // The application was created on another chain.
+    let admin = Owner::from(PublicKey::test_key(0));
+    let (mut description, contract_blob, service_blob) =
+        create_dummy_user_application_description(0);
+    description.admin = Some(admin);
+    assert_ne!(description.creation.chain_id, ChainId::root(0));
+    let bytecode_id = description.bytecode_id;
+    let (application_id, _application) = view
+        .register_mock_application_with(description, contract_blob, service_blob)
+        .await?;
+
+    let context = OperationContext {
+        authenticated_signer: Some(admin),
+        ..create_dummy_operation_context()
+    };
+    let operation = SystemOperation::UpgradeApplication {
+        application_id,
+        bytecode_id,
+    };
+    let result = view
+        .execute_operation(
+            context,
+            Timestamp::from(0),
+            operation.into(),
+            &mut TransactionTracker::default(),
+            &mut ResourceController::default(),
+        )
+        .await;
+    assert_matches!(
+        result,
+        Err(ExecutionError::SystemError(
+            SystemExecutionError::UpgradeOutsideCreatorChain(id)
+        )) if *id == application_id
+    );
+    assert!(view
+        .system
+        .registry
+        .upgrades
+        .get(&application_id)
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+/// Tests that chains registering an upgradable application subscribe to the creator's upgrades,
+/// and apply the upgrades they receive.
+#[tokio::test]
+async fn test_application_upgrade_propagation() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let admin = Owner::from(PublicKey::test_key(0));
+    let (mut description, contract_blob, service_blob) =
+        create_dummy_user_application_description(0);
+    description.admin = Some(admin);
+    let creator_id = description.creation.chain_id;
+    let application_id = UserApplicationId::from(&description);
+    let new_contract_blob = Blob::new_contract_bytecode(CompressedBytecode {
+        compressed_bytes: b"new contract".to_vec(),
+    });
+    let new_service_blob = Blob::new_service_bytecode(CompressedBytecode {
+        compressed_bytes: b"new service".to_vec(),
+    });
+    let new_bytecode_id = BytecodeId::new(new_contract_blob.id().hash, new_service_blob.id().hash);
+    view.context()
+        .extra()
+        .add_blobs([
+            contract_blob,
+            service_blob,
+            new_contract_blob,
+            new_service_blob,
+        ])
+        .await?;
+
+    // Registering the application subscribes to the creator's upgrades.
+    let context = create_dummy_message_context(None);
+    let outcome = view
+        .system
+        .execute_message(
+            context,
+            SystemMessage::RegisterApplications {
+                applications: vec![description],
+            },
+            &mut TransactionTracker::default(),
+        )
+        .await?;
+    let subscription = ChannelSubscription {
+        chain_id: creator_id,
+        name: SystemChannel::Upgrades.name(),
+    };
+    assert_eq!(
+        outcome.messages,
+        vec![RawOutgoingMessage {
+            destination: Destination::Recipient(creator_id),
+            authenticated: false,
+            grant: Amount::ZERO,
+            not_before: None,
+            kind: MessageKind::Protected,
+            message: SystemMessage::Subscribe {
+                id: context.chain_id,
+                subscription: subscription.clone(),
+            },
+        }]
+    );
+    assert!(view.system.subscriptions.contains(&subscription).await?);
+
+    // Upgrades from the creator's channel are applied, and those of unknown applications are
+    // ignored.
+    let (unknown_description, _, _) = create_dummy_user_application_description(1);
+    let upgrade = ApplicationUpgrade {
+        bytecode_id: new_bytecode_id,
+        version: 1,
+    };
+    view.system
+        .execute_message(
+            context,
+            SystemMessage::UpgradeApplications {
+                upgrades: vec![
+                    (UserApplicationId::from(&unknown_description), upgrade),
+                    (application_id, upgrade),
+                ],
+            },
+            &mut TransactionTracker::default(),
+        )
+        .await?;
+    assert_eq!(
+        view.system.registry.upgrades.get(&application_id).await?,
+        Some(upgrade)
+    );
+    assert!(
+        view.system
+            .registry
+            .pending_migrations
+            .contains(&application_id)
+            .await?
+    );
+
+    Ok(())
+}
+
 /// Tests an application attempting to transfer the tokens in the chain's balance while executing
 /// messages.
 #[test_case(
@@ -1678,3 +2374,90 @@ async fn test_message_receipt_spending_chain_balance(
 
     Ok(execution_result)
 }
+
+/// Tests that proofs of the values of keys of an application can be verified against the
+/// hash of the execution state, and that tampered proofs are rejected.
+#[tokio::test]
+async fn test_application_state_proof() -> anyhow::Result<()> {
+    let mut state = SystemExecutionState::default();
+    state.description = Some(ChainDescription::Root(0));
+    let mut view = state.into_view().await;
+
+    let application_ids = (0..3)
+        .map(|index| UserApplicationId::from(&create_dummy_user_application_description(index).0))
+        .collect::<Vec<_>>();
+    for (index, application_id) in application_ids.iter().take(2).enumerate() {
+        let mut application_state = view.users.try_load_entry_mut(application_id).await?;
+        for key in 0..20u8 {
+            application_state
+                .insert(vec![key], vec![index as u8, key])
+                .await?;
+        }
+    }
+    let state_hash = view.crypto_hash().await?;
+    // The verifier computes the hash of the execution state like the view.
+    assert_eq!(
+        execution_state_hash(
+            CryptoHash::from(view.system.hash().await?),
+            view.users.hash().await?.into()
+        ),
+        state_hash
+    );
+
+    let proof = view
+        .prove_application_state(application_ids[0], &[7])
+        .await?;
+    assert_eq!(proof.other_applications.len(), 1);
+    assert_eq!(proof.value, Some(vec![0, 7]));
+    // The proof only contains the path of the key, not the rest of the state.
+    let key_proof = proof.key_proof.as_ref().unwrap();
+    assert!(key_proof.siblings.len() < 20);
+    assert_eq!(
+        proof.verify(state_hash, application_ids[0], &[7])?,
+        Some(&[0, 7][..])
+    );
+    // Key 8 is stored too, so its path leaves the one of key 7 before the leaf.
+    assert_matches!(
+        proof.verify(state_hash, application_ids[0], &[8]),
+        Err(StateProofError::MisplacedLeaf)
+    );
+    assert_matches!(
+        proof.verify(state_hash, application_ids[1], &[7]),
+        Err(StateProofError::DuplicateApplication(id)) if id == application_ids[1]
+    );
+    assert_matches!(
+        proof.verify(state_hash, application_ids[2], &[7]),
+        Err(StateProofError::StateHashMismatch(hash)) if hash == state_hash
+    );
+
+    let mut tampered_proof = proof.clone();
+    tampered_proof.value = Some(vec![1, 7]);
+    assert_matches!(
+        tampered_proof.verify(state_hash, application_ids[0], &[7]),
+        Err(StateProofError::ValueMismatch)
+    );
+    let mut tampered_proof = proof.clone();
+    tampered_proof.value = None;
+    assert_matches!(
+        tampered_proof.verify(state_hash, application_ids[0], &[7]),
+        Err(StateProofError::ValueMismatch)
+    );
+
+    // A key that the application doesn't store.
+    let proof = view
+        .prove_application_state(application_ids[0], b"missing key")
+        .await?;
+    assert_eq!(proof.value, None);
+    assert_eq!(
+        proof.verify(state_hash, application_ids[0], b"missing key")?,
+        None
+    );
+
+    // An application without any state on the chain.
+    let proof = view
+        .prove_application_state(application_ids[2], &[7])
+        .await?;
+    assert!(proof.key_proof.is_none());
+    assert_eq!(proof.verify(state_hash, application_ids[2], &[7])?, None);
+    Ok(())
+}
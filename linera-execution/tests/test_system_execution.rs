@@ -82,6 +82,7 @@ async fn test_simple_system_message() -> anyhow::Result<()> {
     let context = MessageContext {
         chain_id: ChainId::root(0),
         is_bouncing: false,
+        is_request: false,
         height: BlockHeight(0),
         certificate_hash: CryptoHash::test_hash("certificate"),
         message_id: MessageId {
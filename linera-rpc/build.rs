@@ -11,7 +11,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .protoc_arg("--experimental_allow_proto3_optional")
         .compile_protos(&["proto/rpc.proto"], no_includes)?;
 
-    let subject_alt_names = vec!["localhost".to_string()];
+    let subject_alt_names = vec!["localhost".to_string(), "127.0.0.1".to_string()];
     let cert = rcgen::generate_simple_self_signed(subject_alt_names)?;
 
     // Write the certificate to a file (PEM format)
@@ -1,6 +1,12 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::{
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
 use linera_base::identifiers::ChainId;
 use linera_execution::committee::ValidatorName;
 use serde::{Deserialize, Serialize};
@@ -10,23 +16,26 @@ use crate::simple;
 
 #[derive(Clone, Debug, clap::Parser)]
 pub struct CrossChainConfig {
-    /// Number of cross-chain messages allowed before dropping them.
-    #[arg(long = "cross-chain-queue-size", default_value = "1000")]
-    pub(crate) queue_size: usize,
-
-    /// Maximum number of retries for a cross-chain message.
+    /// Number of failed deliveries after which a cross-chain message is reported as stuck.
+    /// It is still retried.
     #[arg(long = "cross-chain-max-retries", default_value = "10")]
     pub(crate) max_retries: u32,
 
-    /// Delay before retrying of cross-chain message.
+    /// Delay before retrying a cross-chain message for the first time. It doubles at each
+    /// new retry.
     #[arg(long = "cross-chain-retry-delay-ms", default_value = "2000")]
     pub(crate) retry_delay_ms: u64,
 
+    /// Maximum delay before retrying a cross-chain message.
+    #[arg(long = "cross-chain-max-retry-delay-ms", default_value = "60000")]
+    pub(crate) max_retry_delay_ms: u64,
+
     /// Introduce a delay before sending every cross-chain message (e.g. for testing purpose).
     #[arg(long = "cross-chain-sender-delay-ms", default_value = "0")]
     pub(crate) sender_delay_ms: u64,
 
-    /// Drop cross-chain messages randomly at the given rate (0 <= rate < 1) (meant for testing).
+    /// Fail to send cross-chain messages randomly at the given rate (0 <= rate < 1) (meant
+    /// for testing).
     #[arg(long = "cross-chain-sender-failure-rate", default_value = "0.0")]
     pub(crate) sender_failure_rate: f32,
 
@@ -67,6 +76,19 @@ impl ShardConfig {
     }
 }
 
+/// How the chains are assigned to the shards of a validator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum ShardAssignment {
+    /// Each chain is assigned to the shard with index `hash(chain) % shards.len()`.
+    /// Changing the number of shards reassigns almost every chain.
+    #[default]
+    Modulo,
+    /// Each chain is assigned to the shard with the highest `hash(chain, shard address)`.
+    /// Adding a shard only reassigns the chains it now owns, and removing a shard only
+    /// reassigns the chains it owned.
+    ConsistentHashing,
+}
+
 /// The network protocol.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum NetworkProtocol {
@@ -81,6 +103,23 @@ pub enum TlsConfig {
     Tls,
 }
 
+/// The TLS configuration of the QUIC endpoints of the simple network protocol, as paths
+/// to PEM files.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuicConfig {
+    /// The certificate chain that QUIC servers present. It must be valid for the hosts
+    /// that clients connect to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate_path: Option<PathBuf>,
+    /// The private key of the certificate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key_path: Option<PathBuf>,
+    /// The root certificates trusted to authenticate QUIC servers, in addition to the
+    /// usual web PKI roots, e.g. the self-signed certificate of a validator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trusted_roots_path: Option<PathBuf>,
+}
+
 impl NetworkProtocol {
     fn scheme(&self) -> &'static str {
         match self {
@@ -94,6 +133,33 @@ impl NetworkProtocol {
     }
 }
 
+/// A shard map that the shards of a validator are handing their chains off to.
+///
+/// Changing the shard map of a running validator happens in two steps, so that no two
+/// shards ever run the same chain:
+/// 1. The new shard map is published as the `next_shard_map` of the server configuration,
+///    e.g. with `linera-server edit-shards`, and the new shards are started. Once they
+///    read it, the shards stop handling the chains that it assigns to another shard, and
+///    wait for the requests in progress for these chains to finish. The new shards don't
+///    handle them either yet, so the moved chains are unavailable until the next step.
+/// 2. Once every shard has logged that it drained the moved chains, the new shard map
+///    replaces the current one, e.g. with `linera-server switch-shards`. The shards
+///    then handle the chains that they own in the new shard map, and the removed shards
+///    can be stopped.
+///
+/// A proxy or a shard that misses the first step still drains the moved chains before
+/// using the new shard map, one reload later.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NextShardMap {
+    /// The shards of the next shard map.
+    pub shards: Vec<ShardConfig>,
+    /// How the chains are assigned to the shards of the next shard map.
+    #[serde(default)]
+    pub shard_assignment: ShardAssignment,
+    /// The version of the next shard map, higher than the current one.
+    pub shard_map_version: u64,
+}
+
 /// The network configuration for all shards.
 pub type ValidatorInternalNetworkConfig = ValidatorInternalNetworkPreConfig<NetworkProtocol>;
 
@@ -107,9 +173,21 @@ pub struct ValidatorInternalNetworkPreConfig<P> {
     pub name: ValidatorName,
     /// The network protocol to use for all shards.
     pub protocol: P,
-    /// The available shards. Each chain UID is mapped to a unique shard in the vector in
-    /// a static way.
+    /// The available shards. Each chain UID is mapped to a unique shard in the vector
+    /// according to `shard_assignment`.
     pub shards: Vec<ShardConfig>,
+    /// How the chains are assigned to the shards.
+    #[serde(default)]
+    pub shard_assignment: ShardAssignment,
+    /// The version of the shard map, i.e. of `shards` and `shard_assignment`. It is
+    /// incremented at each change, so that running proxies and shards only replace their
+    /// shard map by a more recent one.
+    #[serde(default)]
+    pub shard_map_version: u64,
+    /// The shard map that replaces the current one once the chains that it moves to
+    /// another shard are drained, if a handoff is in progress.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_shard_map: Option<NextShardMap>,
     /// The host name of the proxy on the internal network (IP or hostname).
     pub host: String,
     /// The port the proxy listens on the internal network.
@@ -126,6 +204,9 @@ impl<P> ValidatorInternalNetworkPreConfig<P> {
             name: self.name,
             protocol,
             shards: self.shards.clone(),
+            shard_assignment: self.shard_assignment,
+            shard_map_version: self.shard_map_version,
+            next_shard_map: self.next_shard_map.clone(),
             host: self.host.clone(),
             port: self.port,
             metrics_host: self.metrics_host.clone(),
@@ -200,7 +281,7 @@ where
         let parts = s.split(':').collect::<Vec<_>>();
         anyhow::ensure!(
             parts.len() == 3,
-            "Expecting format `(tcp|udp|grpc|grpcs):host:port`"
+            "Expecting format `(tcp|udp|quic|grpc|grpcs):host:port`"
         );
         let protocol = parts[0].parse().map_err(|s| anyhow::anyhow!("{}", s))?;
         let host = parts[1].to_owned();
@@ -230,14 +311,63 @@ impl std::str::FromStr for NetworkProtocol {
 }
 
 impl<P> ValidatorInternalNetworkPreConfig<P> {
-    /// Static shard assignment
+    /// Returns the shard assigned to the `chain_id` by the current shard map.
     pub fn get_shard_id(&self, chain_id: ChainId) -> ShardId {
-        use std::hash::{Hash, Hasher};
+        self.assign_shard(chain_id, &self.shards, self.shard_assignment)
+    }
+
+    fn assign_shard(
+        &self,
+        chain_id: ChainId,
+        shards: &[ShardConfig],
+        shard_assignment: ShardAssignment,
+    ) -> ShardId {
+        match shard_assignment {
+            ShardAssignment::Modulo => (self.chain_hash(chain_id, None) as ShardId) % shards.len(),
+            ShardAssignment::ConsistentHashing => shards
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, shard)| self.chain_hash(chain_id, Some(shard)))
+                .map(|(shard_id, _)| shard_id)
+                .expect("a validator has at least one shard"),
+        }
+    }
+
+    /// Returns whether the `chain_id` is assigned to another shard by the next shard map,
+    /// if a handoff is in progress. No shard handles such a chain until the handoff is
+    /// over.
+    pub fn is_moving(&self, chain_id: ChainId) -> bool {
+        self.next_shard_map.as_ref().is_some_and(|next| {
+            let shard_id = self.assign_shard(chain_id, &next.shards, next.shard_assignment);
+            next.shards[shard_id] != *self.get_shard_for(chain_id)
+        })
+    }
+
+    /// Returns whether `shard` handles the requests for `chain_id`: it must own the chain
+    /// in the current shard map, and the chain must not be moving to another shard.
+    pub fn handles(&self, chain_id: ChainId, shard: &ShardConfig) -> bool {
+        self.get_shard_for(chain_id) == shard && !self.is_moving(chain_id)
+    }
+
+    /// Returns the shards of the next shard map if a handoff is in progress, or else of
+    /// the current one. This is what shards that are starting are numbered by.
+    pub fn latest_shards(&self) -> &[ShardConfig] {
+        match &self.next_shard_map {
+            Some(next) => &next.shards,
+            None => &self.shards,
+        }
+    }
+
+    fn chain_hash(&self, chain_id: ChainId, shard: Option<&ShardConfig>) -> u64 {
         let mut s = std::collections::hash_map::DefaultHasher::new();
         // Use the validator public key to randomise shard assignment.
         self.name.hash(&mut s);
         chain_id.hash(&mut s);
-        (s.finish() as ShardId) % self.shards.len()
+        if let Some(shard) = shard {
+            shard.host.hash(&mut s);
+            shard.port.hash(&mut s);
+        }
+        s.finish()
     }
 
     pub fn shard(&self, shard_id: ShardId) -> &ShardConfig {
@@ -249,3 +379,201 @@ impl<P> ValidatorInternalNetworkPreConfig<P> {
         self.shard(self.get_shard_id(chain_id))
     }
 }
+
+/// The internal network configuration of a validator, shared by the tasks routing requests
+/// to its shards. Its shard map can be replaced by a more recent one while in use.
+#[derive(Debug)]
+pub struct SharedInternalNetworkConfig<P>(Arc<RwLock<Arc<ValidatorInternalNetworkPreConfig<P>>>>);
+
+impl<P> Clone for SharedInternalNetworkConfig<P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<P: Clone> SharedInternalNetworkConfig<P> {
+    pub fn new(config: ValidatorInternalNetworkPreConfig<P>) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(config))))
+    }
+
+    /// Returns the current configuration.
+    pub fn get(&self) -> Arc<ValidatorInternalNetworkPreConfig<P>> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Updates the shard map with the one of `config`, if it is more recent. The other
+    /// fields are left unchanged. Returns whether the shard map was updated.
+    ///
+    /// A shard map with a higher version only replaces the current one if it was the
+    /// [`NextShardMap`] already. Otherwise it becomes the next shard map, so that the
+    /// chains that it moves are drained before it is used. See [`NextShardMap`].
+    pub fn update_shard_map<Q>(&self, config: &ValidatorInternalNetworkPreConfig<Q>) -> bool {
+        let mut current = self.0.write().unwrap();
+        let mut updated = ValidatorInternalNetworkPreConfig::clone(&current);
+        if config.shard_map_version > current.shard_map_version && !config.shards.is_empty() {
+            let next = NextShardMap {
+                shards: config.shards.clone(),
+                shard_assignment: config.shard_assignment,
+                shard_map_version: config.shard_map_version,
+            };
+            if current.next_shard_map.as_ref() == Some(&next) {
+                updated.shards = next.shards;
+                updated.shard_assignment = next.shard_assignment;
+                updated.shard_map_version = next.shard_map_version;
+                updated.next_shard_map = None;
+            } else {
+                updated.next_shard_map = Some(next);
+            }
+        } else {
+            match &config.next_shard_map {
+                Some(next)
+                    if next.shard_map_version > current.shard_map_version
+                        && !next.shards.is_empty()
+                        && current.next_shard_map.as_ref() != Some(next) =>
+                {
+                    updated.next_shard_map = Some(next.clone());
+                }
+                _ => return false,
+            }
+        }
+        *current = Arc::new(updated);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::crypto::{CryptoHash, KeyPair};
+
+    use super::*;
+
+    fn make_config(num_shards: u16) -> ValidatorInternalNetworkPreConfig<()> {
+        ValidatorInternalNetworkPreConfig {
+            name: ValidatorName(KeyPair::generate().public()),
+            protocol: (),
+            shards: (0..num_shards)
+                .map(|i| ShardConfig {
+                    host: format!("shard{i}"),
+                    port: 9000 + i,
+                    metrics_host: format!("shard{i}"),
+                    metrics_port: None,
+                })
+                .collect(),
+            shard_assignment: ShardAssignment::ConsistentHashing,
+            shard_map_version: 0,
+            next_shard_map: None,
+            host: "proxy".into(),
+            port: 8000,
+            metrics_host: "proxy".into(),
+            metrics_port: 8001,
+        }
+    }
+
+    #[test]
+    fn test_consistent_hashing_moves_few_chains() {
+        let mut config = make_config(4);
+        let chain_ids = (0..1000u64)
+            .map(|i| ChainId(CryptoHash::test_hash(i.to_string())))
+            .collect::<Vec<_>>();
+        let assign = |config: &ValidatorInternalNetworkPreConfig<()>| {
+            chain_ids
+                .iter()
+                .map(|chain_id| config.get_shard_for(*chain_id).clone())
+                .collect::<Vec<_>>()
+        };
+        let shards = assign(&config);
+
+        // Adding a shard only moves chains to that shard.
+        let new_shard = ShardConfig {
+            host: "shard4".into(),
+            port: 9004,
+            metrics_host: "shard4".into(),
+            metrics_port: None,
+        };
+        config.shards.push(new_shard.clone());
+        let new_shards = assign(&config);
+        let mut num_moved = 0;
+        for (shard, new) in shards.iter().zip(&new_shards) {
+            if shard != new {
+                assert_eq!(*new, new_shard);
+                num_moved += 1;
+            }
+        }
+        assert!((100..300).contains(&num_moved), "{num_moved} chains moved");
+
+        // Removing a shard only moves the chains it owned.
+        let removed_shard = config.shards.remove(1);
+        for (shard, new) in new_shards.iter().zip(assign(&config)) {
+            assert!(*shard == new || *shard == removed_shard);
+        }
+    }
+
+    #[test]
+    fn test_update_shard_map() {
+        let shared = SharedInternalNetworkConfig::new(make_config(2));
+        let mut config = make_config(3);
+        assert!(!shared.update_shard_map(&config));
+        assert_eq!(shared.get().shards.len(), 2);
+        config.shard_map_version = 1;
+        // The new shard map is only used once the chains it moves were drained.
+        assert!(shared.update_shard_map(&config));
+        assert_eq!(shared.get().shards.len(), 2);
+        assert_eq!(shared.get().latest_shards(), config.shards);
+        assert!(shared.update_shard_map(&config));
+        assert_eq!(shared.get().shards, config.shards);
+        assert_eq!(shared.get().shard_map_version, 1);
+        assert!(shared.get().next_shard_map.is_none());
+        assert_ne!(shared.get().name, config.name);
+        assert!(!shared.update_shard_map(&config));
+    }
+
+    #[test]
+    fn test_shard_map_handoff() {
+        let mut config = make_config(2);
+        let chain_ids = (0..100u64)
+            .map(|i| ChainId(CryptoHash::test_hash(i.to_string())))
+            .collect::<Vec<_>>();
+        let shared = SharedInternalNetworkConfig::new(config.clone());
+
+        // Announce a shard map that adds a shard.
+        let mut next_config = make_config(3);
+        next_config.name = config.name;
+        let next = NextShardMap {
+            shards: next_config.shards.clone(),
+            shard_assignment: config.shard_assignment,
+            shard_map_version: 1,
+        };
+        config.next_shard_map = Some(next.clone());
+        assert!(shared.update_shard_map(&config));
+        assert!(!shared.update_shard_map(&config));
+        let draining = shared.get();
+        assert_eq!(draining.shard_map_version, 0);
+        let mut num_moving_chains = 0;
+        for chain_id in &chain_ids {
+            let is_moving =
+                next_config.get_shard_for(*chain_id) != draining.get_shard_for(*chain_id);
+            assert_eq!(draining.is_moving(*chain_id), is_moving);
+            // No shard handles the chains that are moving.
+            let num_handling_shards = next
+                .shards
+                .iter()
+                .filter(|shard| draining.handles(*chain_id, shard))
+                .count();
+            assert_eq!(num_handling_shards, usize::from(!is_moving));
+            num_moving_chains += usize::from(is_moving);
+        }
+        assert!(num_moving_chains > 0);
+
+        // Switch to the new shard map.
+        config.shards = next.shards.clone();
+        config.shard_map_version = 1;
+        config.next_shard_map = None;
+        assert!(shared.update_shard_map(&config));
+        let switched = shared.get();
+        assert_eq!(switched.shard_map_version, 1);
+        for chain_id in &chain_ids {
+            assert!(!switched.is_moving(*chain_id));
+            assert!(switched.handles(*chain_id, switched.get_shard_for(*chain_id)));
+        }
+    }
+}
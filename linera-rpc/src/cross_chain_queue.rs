@@ -0,0 +1,632 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The cross-chain requests that a shard sends to the shards of the same validator.
+//!
+//! Requests are written to storage before being sent, and only removed once they are
+//! delivered, so that they survive restarts of the shard and outages of their targets.
+//! Failed deliveries are retried with an exponential backoff, without limit: a request
+//! that failed `--cross-chain-max-retries` times is reported as stuck, but still retried.
+//!
+//! A cross-chain request is generated from the whole outbox of its origin chain, so it
+//! replaces the pending request of the same kind between the same two chains.
+
+#[cfg(with_metrics)]
+use std::sync::LazyLock;
+use std::{
+    collections::{hash_map, BTreeMap, BTreeSet, HashMap},
+    future::Future,
+    pin::pin,
+    sync::{Arc, Mutex},
+};
+
+use futures::future;
+use linera_base::{
+    crypto::{BcsHashable, CryptoHash},
+    data_types::Timestamp,
+    identifiers::ChainId,
+    time::{timer, Duration, Instant},
+};
+use linera_core::data_types::CrossChainRequest;
+use linera_storage::Storage;
+use linera_views::views::ViewError;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex as AsyncMutex, Notify};
+#[cfg(with_metrics)]
+use {
+    linera_base::prometheus_util::{register_int_counter_vec, register_int_gauge_vec},
+    prometheus::{IntCounterVec, IntGaugeVec},
+};
+
+use crate::config::{CrossChainConfig, ShardId};
+
+/// The maximal time that [`CrossChainQueue::next`] waits before checking the queue again.
+const MAX_WAIT: Duration = Duration::from_secs(1);
+
+#[cfg(with_metrics)]
+static CROSS_CHAIN_QUEUE_DEPTH: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec(
+        "cross_chain_queue_depth",
+        "Number of cross-chain requests waiting to be delivered",
+        &["source_shard", "target_shard"],
+    )
+});
+
+#[cfg(with_metrics)]
+static CROSS_CHAIN_QUEUE_OLDEST_REQUEST_AGE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec(
+        "cross_chain_queue_oldest_request_age_ms",
+        "Age of the oldest cross-chain request waiting to be delivered, in milliseconds",
+        &["source_shard", "target_shard"],
+    )
+});
+
+#[cfg(with_metrics)]
+static CROSS_CHAIN_DELIVERY_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "cross_chain_delivery_failures",
+        "Number of failed deliveries of cross-chain requests",
+        &["source_shard", "target_shard"],
+    )
+});
+
+/// What identifies the pending requests that replace each other.
+#[derive(Serialize, Deserialize)]
+struct RequestKey {
+    confirmation: bool,
+    sender: ChainId,
+    recipient: ChainId,
+}
+
+impl<'de> BcsHashable<'de> for RequestKey {}
+
+impl RequestKey {
+    fn id(request: &CrossChainRequest) -> CryptoHash {
+        let key = match request {
+            CrossChainRequest::UpdateRecipient {
+                sender, recipient, ..
+            } => RequestKey {
+                confirmation: false,
+                sender: *sender,
+                recipient: *recipient,
+            },
+            CrossChainRequest::ConfirmUpdatedRecipient {
+                sender, recipient, ..
+            } => RequestKey {
+                confirmation: true,
+                sender: *sender,
+                recipient: *recipient,
+            },
+        };
+        CryptoHash::new(&key)
+    }
+}
+
+/// A pending request, as written to storage.
+#[derive(Serialize, Deserialize)]
+struct PersistedRequest {
+    enqueued_at: Timestamp,
+    request: CrossChainRequest,
+}
+
+/// A pending request.
+struct Entry {
+    request: CrossChainRequest,
+    /// Incremented each time the request is replaced.
+    version: u64,
+    /// When the oldest request that this one replaced was pushed.
+    enqueued_at: Timestamp,
+    /// When the current request was pushed.
+    replaced_at: Timestamp,
+    /// The number of failed deliveries since the last successful one.
+    attempts: u32,
+    next_attempt: Instant,
+    in_flight: bool,
+    last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    entries: HashMap<CryptoHash, Entry>,
+    /// The entries that are not in flight, by their next attempt.
+    due: BTreeSet<(Instant, CryptoHash)>,
+    next_version: u64,
+    /// The locks serializing the storage operations on each request, while there are any.
+    storage_locks: HashMap<CryptoHash, Arc<AsyncMutex<()>>>,
+    #[cfg(with_metrics)]
+    metrics_updated_at: Option<Instant>,
+    #[cfg(with_metrics)]
+    reported_targets: BTreeSet<ShardId>,
+}
+
+impl QueueState {
+    fn insert(
+        &mut self,
+        id: CryptoHash,
+        request: CrossChainRequest,
+        enqueued_at: Timestamp,
+        next_attempt: Instant,
+    ) -> &mut Entry {
+        let version = self.next_version;
+        self.next_version += 1;
+        match self.entries.entry(id) {
+            hash_map::Entry::Occupied(entry) => {
+                let entry = entry.into_mut();
+                entry.request = request;
+                entry.version = version;
+                entry.replaced_at = Timestamp::now();
+                entry
+            }
+            hash_map::Entry::Vacant(entry) => {
+                self.due.insert((next_attempt, id));
+                entry.insert(Entry {
+                    request,
+                    version,
+                    enqueued_at,
+                    replaced_at: enqueued_at,
+                    attempts: 0,
+                    next_attempt,
+                    in_flight: false,
+                    last_error: None,
+                })
+            }
+        }
+    }
+
+    /// Returns the request `id`, which is in flight, to the queue.
+    fn reschedule(&mut self, id: CryptoHash, next_attempt: Instant) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.in_flight = false;
+            entry.next_attempt = next_attempt;
+            self.due.insert((next_attempt, id));
+        }
+    }
+}
+
+/// A request taken from the queue, to be reported as delivered or failed.
+pub struct PendingDelivery {
+    id: CryptoHash,
+    version: u64,
+    /// The request to send.
+    pub request: CrossChainRequest,
+    /// The number of this delivery attempt, starting at 1.
+    pub attempt: u32,
+}
+
+/// The pending requests of a shard for one target shard.
+#[derive(Clone, Debug)]
+pub struct TargetShardStatus {
+    pub shard_id: ShardId,
+    pub num_requests: usize,
+    pub oldest_request_age: Duration,
+    /// The requests that failed at least `--cross-chain-max-retries` times in a row.
+    pub stuck_requests: Vec<StuckRequest>,
+}
+
+/// A request that could not be delivered for a while.
+#[derive(Clone, Debug)]
+pub struct StuckRequest {
+    pub origin: ChainId,
+    pub target: ChainId,
+    pub attempts: u32,
+    pub age: Duration,
+    pub last_error: Option<String>,
+}
+
+/// The persistent queue of the cross-chain requests sent by a shard.
+pub struct CrossChainQueue<S> {
+    inner: Arc<Inner<S>>,
+}
+
+impl<S> Clone for CrossChainQueue<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+struct Inner<S> {
+    storage: S,
+    shard_id: ShardId,
+    /// Returns the shard of a chain in the current shard map.
+    shard_for: Box<dyn Fn(ChainId) -> ShardId + Send + Sync>,
+    max_retries: u32,
+    retry_delay: Duration,
+    max_retry_delay: Duration,
+    sender_delay: Duration,
+    state: Mutex<QueueState>,
+    notify: Notify,
+}
+
+impl<S> CrossChainQueue<S>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+{
+    /// Creates an empty queue for the requests sent by the shard `shard_id`. Call
+    /// [`CrossChainQueue::load`] to resume the delivery of the persisted requests.
+    pub fn new(
+        storage: S,
+        shard_id: ShardId,
+        shard_for: impl Fn(ChainId) -> ShardId + Send + Sync + 'static,
+        config: &CrossChainConfig,
+    ) -> Self {
+        let inner = Inner {
+            storage,
+            shard_id,
+            shard_for: Box::new(shard_for),
+            max_retries: config.max_retries,
+            retry_delay: Duration::from_millis(config.retry_delay_ms),
+            max_retry_delay: Duration::from_millis(config.max_retry_delay_ms),
+            sender_delay: Duration::from_millis(config.sender_delay_ms),
+            state: Mutex::default(),
+            notify: Notify::new(),
+        };
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+
+    /// The shard sending the requests.
+    pub fn shard_id(&self) -> ShardId {
+        self.inner.shard_id
+    }
+
+    /// Adds the persisted requests sent by the chains of this shard to the queue, and
+    /// returns how many were added.
+    pub async fn load(&self) -> Result<usize, ViewError> {
+        let requests = self
+            .inner
+            .storage
+            .read_pending_cross_chain_requests()
+            .await?;
+        let now = Instant::now();
+        let mut count = 0;
+        let mut state = self.inner.state.lock().unwrap();
+        for (id, bytes) in requests {
+            let PersistedRequest {
+                enqueued_at,
+                request,
+            } = bcs::from_bytes(&bytes)?;
+            if (self.inner.shard_for)(request.origin_chain_id()) != self.inner.shard_id
+                || state.entries.contains_key(&id)
+            {
+                continue;
+            }
+            state.insert(id, request, enqueued_at, now);
+            count += 1;
+        }
+        drop(state);
+        self.inner.notify.notify_one();
+        Ok(count)
+    }
+
+    /// Persists a request and schedules its delivery. The request is scheduled even if it
+    /// could not be persisted.
+    pub async fn push(&self, request: CrossChainRequest) -> Result<(), ViewError> {
+        let id = RequestKey::id(&request);
+        {
+            let mut state = self.inner.state.lock().unwrap();
+            let next_attempt = Instant::now() + self.inner.sender_delay;
+            state.insert(id, request, Timestamp::now(), next_attempt);
+        }
+        self.inner.notify.notify_one();
+        self.with_storage_lock(id, || async {
+            // Persist the latest request, which may have replaced this one in the
+            // meantime. There is nothing to persist if it was delivered already.
+            let persisted = {
+                let state = self.inner.state.lock().unwrap();
+                let Some(entry) = state.entries.get(&id) else {
+                    return Ok(());
+                };
+                PersistedRequest {
+                    enqueued_at: entry.enqueued_at,
+                    request: entry.request.clone(),
+                }
+            };
+            let bytes = bcs::to_bytes(&persisted)?;
+            self.inner
+                .storage
+                .write_pending_cross_chain_request(id, &bytes)
+                .await
+        })
+        .await
+    }
+
+    /// Waits until a request is due and takes it from the queue. It is not returned again
+    /// until it is reported as delivered or failed.
+    pub async fn next(&self) -> PendingDelivery {
+        loop {
+            let wait = {
+                let mut state = self.inner.state.lock().unwrap();
+                #[cfg(with_metrics)]
+                self.update_metrics(&mut state);
+                let now = Instant::now();
+                match state.due.first().copied() {
+                    Some((next_attempt, id)) if next_attempt <= now => {
+                        state.due.remove(&(next_attempt, id));
+                        let entry = state
+                            .entries
+                            .get_mut(&id)
+                            .expect("due requests are in the queue");
+                        entry.in_flight = true;
+                        return PendingDelivery {
+                            id,
+                            version: entry.version,
+                            request: entry.request.clone(),
+                            attempt: entry.attempts + 1,
+                        };
+                    }
+                    Some((next_attempt, _)) => (next_attempt - now).min(MAX_WAIT),
+                    None => MAX_WAIT,
+                }
+            };
+            let notified = pin!(self.inner.notify.notified());
+            let sleep = pin!(timer::sleep(wait));
+            future::select(notified, sleep).await;
+        }
+    }
+
+    /// Removes a delivered request from the queue, unless it was replaced in the meantime.
+    pub async fn delivered(&self, delivery: &PendingDelivery) -> Result<(), ViewError> {
+        self.with_storage_lock(delivery.id, || async {
+            {
+                let mut state = self.inner.state.lock().unwrap();
+                let Some(entry) = state.entries.get_mut(&delivery.id) else {
+                    return Ok(());
+                };
+                if entry.version != delivery.version {
+                    // Deliver the newer request right away.
+                    entry.attempts = 0;
+                    entry.enqueued_at = entry.replaced_at;
+                    entry.last_error = None;
+                    state.reschedule(delivery.id, Instant::now());
+                    drop(state);
+                    self.inner.notify.notify_one();
+                    return Ok(());
+                }
+                state.entries.remove(&delivery.id);
+            }
+            // A request pushed from now on is only written once this one is deleted.
+            self.inner
+                .storage
+                .delete_pending_cross_chain_request(delivery.id)
+                .await
+        })
+        .await
+    }
+
+    /// Runs a storage operation on the request `id`, after the previous ones.
+    async fn with_storage_lock<F, Fut>(&self, id: CryptoHash, operation: F) -> Fut::Output
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future,
+    {
+        let lock = {
+            let mut state = self.inner.state.lock().unwrap();
+            state.storage_locks.entry(id).or_default().clone()
+        };
+        let output = {
+            let _guard = lock.lock().await;
+            operation().await
+        };
+        let mut state = self.inner.state.lock().unwrap();
+        // The lock is used by the map and by this operation only, unless others wait for it.
+        if Arc::strong_count(&lock) == 2 {
+            state.storage_locks.remove(&id);
+        }
+        output
+    }
+
+    /// Schedules a request that could not be delivered to be retried later.
+    pub fn failed(&self, delivery: &PendingDelivery, error: String) {
+        let mut state = self.inner.state.lock().unwrap();
+        let Some(entry) = state.entries.get_mut(&delivery.id) else {
+            return;
+        };
+        entry.attempts = entry.attempts.saturating_add(1);
+        entry.last_error = Some(error);
+        let next_attempt = Instant::now() + self.retry_delay(entry.attempts);
+        #[cfg(with_metrics)]
+        {
+            let target_shard = (self.inner.shard_for)(entry.request.target_chain_id());
+            CROSS_CHAIN_DELIVERY_FAILURES
+                .with_label_values(&[&self.inner.shard_id.to_string(), &target_shard.to_string()])
+                .inc();
+        }
+        state.reschedule(delivery.id, next_attempt);
+        drop(state);
+        self.inner.notify.notify_one();
+    }
+
+    /// Returns the pending requests for each target shard in the current shard map.
+    pub fn status(&self) -> Vec<TargetShardStatus> {
+        let state = self.inner.state.lock().unwrap();
+        self.target_statuses(&state)
+    }
+
+    /// Returns the number of pending requests sent by the given chain.
+    pub fn num_pending_requests_from(&self, origin: ChainId) -> usize {
+        let state = self.inner.state.lock().unwrap();
+        state
+            .entries
+            .values()
+            .filter(|entry| entry.request.origin_chain_id() == origin)
+            .count()
+    }
+
+    /// Returns the shard of a chain in the current shard map.
+    pub fn shard_for(&self, chain_id: ChainId) -> ShardId {
+        (self.inner.shard_for)(chain_id)
+    }
+
+    /// Returns the delay before the retry following the given number of failed attempts.
+    fn retry_delay(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.inner
+            .retry_delay
+            .saturating_mul(factor)
+            .min(self.inner.max_retry_delay)
+    }
+
+    fn target_statuses(&self, state: &QueueState) -> Vec<TargetShardStatus> {
+        let now = Timestamp::now();
+        let mut targets = BTreeMap::<ShardId, TargetShardStatus>::new();
+        for entry in state.entries.values() {
+            let target = entry.request.target_chain_id();
+            let shard_id = (self.inner.shard_for)(target);
+            let age = now.duration_since(entry.enqueued_at);
+            let status = targets
+                .entry(shard_id)
+                .or_insert_with(|| TargetShardStatus {
+                    shard_id,
+                    num_requests: 0,
+                    oldest_request_age: Duration::ZERO,
+                    stuck_requests: Vec::new(),
+                });
+            status.num_requests += 1;
+            status.oldest_request_age = status.oldest_request_age.max(age);
+            if entry.attempts >= self.inner.max_retries {
+                status.stuck_requests.push(StuckRequest {
+                    origin: entry.request.origin_chain_id(),
+                    target,
+                    attempts: entry.attempts,
+                    age,
+                    last_error: entry.last_error.clone(),
+                });
+            }
+        }
+        targets.into_values().collect()
+    }
+
+    /// Updates the gauges of the queue, at most once per [`MAX_WAIT`].
+    #[cfg(with_metrics)]
+    fn update_metrics(&self, state: &mut QueueState) {
+        let now = Instant::now();
+        if state
+            .metrics_updated_at
+            .is_some_and(|updated_at| now.duration_since(updated_at) < MAX_WAIT)
+        {
+            return;
+        }
+        state.metrics_updated_at = Some(now);
+        let source_shard = self.inner.shard_id.to_string();
+        let statuses = self.target_statuses(state);
+        // Reset the gauges of the targets that have no pending requests anymore.
+        for shard_id in std::mem::take(&mut state.reported_targets) {
+            let labels = [source_shard.as_str(), &shard_id.to_string()];
+            CROSS_CHAIN_QUEUE_DEPTH.with_label_values(&labels).set(0);
+            CROSS_CHAIN_QUEUE_OLDEST_REQUEST_AGE
+                .with_label_values(&labels)
+                .set(0);
+        }
+        for status in statuses {
+            let labels = [source_shard.as_str(), &status.shard_id.to_string()];
+            CROSS_CHAIN_QUEUE_DEPTH
+                .with_label_values(&labels)
+                .set(status.num_requests.try_into().unwrap_or(i64::MAX));
+            CROSS_CHAIN_QUEUE_OLDEST_REQUEST_AGE
+                .with_label_values(&labels)
+                .set(
+                    status
+                        .oldest_request_age
+                        .as_millis()
+                        .try_into()
+                        .unwrap_or(i64::MAX),
+                );
+            state.reported_targets.insert(status.shard_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::data_types::BlockHeight;
+    use linera_chain::data_types::Medium;
+    use linera_storage::{DbStorage, TestClock};
+    use linera_views::memory::MemoryStore;
+
+    use super::*;
+
+    fn confirmation(latest_heights: Vec<(Medium, BlockHeight)>) -> CrossChainRequest {
+        CrossChainRequest::ConfirmUpdatedRecipient {
+            sender: ChainId::root(0),
+            recipient: ChainId::root(1),
+            latest_heights,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cross_chain_queue() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let config = CrossChainConfig {
+            max_retries: 1,
+            retry_delay_ms: 0,
+            max_retry_delay_ms: 0,
+            sender_delay_ms: 0,
+            sender_failure_rate: 0.0,
+            max_concurrent_tasks: 1,
+        };
+        let first = confirmation(Vec::new());
+        let second = confirmation(vec![(Medium::Direct, BlockHeight(1))]);
+
+        let queue = CrossChainQueue::new(storage.clone(), 0, |_| 0, &config);
+        queue.push(first.clone()).await?;
+        let delivery = queue.next().await;
+        assert_eq!(delivery.request, first);
+        // A newer request replaces the one being sent.
+        queue.push(second.clone()).await?;
+        assert!(queue.inner.state.lock().unwrap().due.is_empty());
+        queue.failed(&delivery, "unreachable".to_string());
+        assert_eq!(queue.inner.state.lock().unwrap().due.len(), 1);
+        let status = queue.status();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].num_requests, 1);
+        assert_eq!(status[0].stuck_requests.len(), 1);
+
+        // After a restart, only the newer request is sent again.
+        let queue = CrossChainQueue::new(storage.clone(), 0, |_| 0, &config);
+        assert_eq!(queue.load().await?, 1);
+        let delivery = queue.next().await;
+        assert_eq!(delivery.request, second);
+        assert_eq!(delivery.attempt, 1);
+        queue.delivered(&delivery).await?;
+        assert!(queue.status().is_empty());
+
+        let queue = CrossChainQueue::new(storage, 0, |_| 0, &config);
+        assert_eq!(queue.load().await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_while_delivered() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let config = CrossChainConfig {
+            max_retries: 1,
+            retry_delay_ms: 0,
+            max_retry_delay_ms: 0,
+            sender_delay_ms: 0,
+            sender_failure_rate: 0.0,
+            max_concurrent_tasks: 1,
+        };
+        let first = confirmation(Vec::new());
+        let second = confirmation(vec![(Medium::Direct, BlockHeight(1))]);
+
+        let queue = CrossChainQueue::new(storage.clone(), 0, |_| 0, &config);
+        queue.push(first).await?;
+        let delivery = queue.next().await;
+        // The newer request is not erased by the removal of the delivered one, whichever
+        // storage operation starts first.
+        let (delivered, pushed) =
+            futures::join!(queue.delivered(&delivery), queue.push(second.clone()));
+        delivered?;
+        pushed?;
+        assert_eq!(queue.num_pending_requests_from(ChainId::root(0)), 1);
+        assert!(queue.inner.state.lock().unwrap().storage_locks.is_empty());
+
+        let queue = CrossChainQueue::new(storage, 0, |_| 0, &config);
+        assert_eq!(queue.load().await?, 1);
+        assert_eq!(queue.next().await.request, second);
+        Ok(())
+    }
+}
@@ -0,0 +1,483 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The service that operators use to inspect the shards run by a server process, and a
+//! client for it.
+//!
+//! Requests must be authenticated with the token given to the server, as a bearer token in
+//! their `authorization` metadata. This includes the requests to the reflection service.
+//! The token is sent in clear text, so the service should only listen on trusted networks.
+
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use linera_base::identifiers::ChainId;
+use linera_chain::{data_types::Medium, manager::LockedBlock};
+use linera_core::{data_types::ChainInfoQuery, worker::WorkerState, JoinSetExt as _};
+use linera_storage::Storage;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    service::{interceptor::InterceptedService, Interceptor},
+    Request, Response, Status,
+};
+use tracing::info;
+
+use super::{
+    api::{
+        self,
+        validator_admin_client::ValidatorAdminClient,
+        validator_admin_server::{ValidatorAdmin, ValidatorAdminServer},
+        ChainStateSummary, ChainWorkers, CrossChainQueueStatus, CrossChainQueueTarget,
+        LockedBlockSummary, QueueSize, ResendOutboxMessagesResult, ShardCacheStats,
+        ShardChainWorkers, SnapshotStorageRequest, StuckCrossChainRequest,
+    },
+    transport, GrpcError, GrpcServerHandle,
+};
+use crate::{
+    config::ShardId,
+    cross_chain_queue::{CrossChainQueue, StuckRequest, TargetShardStatus},
+};
+
+/// The metadata entry with the token of the requests.
+const AUTHORIZATION_METADATA_KEY: &str = "authorization";
+
+/// The administration service of the shards run by a server process.
+pub struct AdminServer<S>
+where
+    S: Storage,
+{
+    /// The storage shared by the shards.
+    storage: S,
+    /// The worker of each shard and the queue of the cross-chain requests it sends.
+    shards: Vec<(WorkerState<S>, CrossChainQueue<S>)>,
+}
+
+impl<S> AdminServer<S>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+{
+    pub fn new(storage: S, shards: Vec<(WorkerState<S>, CrossChainQueue<S>)>) -> Self {
+        Self { storage, shards }
+    }
+
+    /// Serves the requests authenticated with `token` until `shutdown_signal` is cancelled.
+    pub fn spawn(
+        self,
+        host: String,
+        port: u16,
+        token: String,
+        shutdown_signal: CancellationToken,
+        join_set: &mut JoinSet<()>,
+    ) -> GrpcServerHandle {
+        info!("spawning admin gRPC server on {}:{}", host, port);
+        let handle = join_set.spawn_task(async move {
+            let server_address = SocketAddr::from((IpAddr::from_str(&host)?, port));
+            let check_token = CheckToken {
+                expected: bearer(&token)?,
+            };
+
+            let reflection_service = tonic_reflection::server::Builder::configure()
+                .register_encoded_file_descriptor_set(crate::FILE_DESCRIPTOR_SET)
+                .build_v1()?;
+
+            tonic::transport::Server::builder()
+                .add_service(InterceptedService::new(
+                    reflection_service,
+                    check_token.clone(),
+                ))
+                .add_service(ValidatorAdminServer::with_interceptor(self, check_token))
+                .serve_with_shutdown(server_address, shutdown_signal.cancelled_owned())
+                .await?;
+
+            Ok(())
+        });
+
+        GrpcServerHandle { handle }
+    }
+
+    /// Returns the worker and the cross-chain queue of the shard of `chain_id`.
+    fn shard(&self, chain_id: ChainId) -> Result<&(WorkerState<S>, CrossChainQueue<S>), Status> {
+        let (_, queue) = self
+            .shards
+            .first()
+            .ok_or_else(|| Status::unavailable("no shards are running"))?;
+        let shard_id = queue.shard_for(chain_id);
+        self.shards
+            .iter()
+            .find(|(_, queue)| queue.shard_id() == shard_id)
+            .ok_or_else(|| {
+                Status::not_found(format!(
+                    "chain {chain_id} belongs to shard {shard_id}, which is not run by this server"
+                ))
+            })
+    }
+}
+
+#[tonic::async_trait]
+impl<S> ValidatorAdmin for AdminServer<S>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+{
+    async fn get_cross_chain_queue(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<CrossChainQueueStatus>, Status> {
+        let targets = self
+            .shards
+            .iter()
+            .flat_map(|(_, queue)| {
+                let source_shard_id = queue.shard_id();
+                queue
+                    .status()
+                    .into_iter()
+                    .map(move |status| queue_target(source_shard_id, status))
+            })
+            .collect();
+        Ok(Response::new(CrossChainQueueStatus { targets }))
+    }
+
+    async fn list_chain_workers(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<ChainWorkers>, Status> {
+        let shards = self
+            .shards
+            .iter()
+            .map(|(state, queue)| ShardChainWorkers {
+                shard_id: queue.shard_id() as u64,
+                chain_ids: state
+                    .loaded_chain_ids()
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            })
+            .collect();
+        Ok(Response::new(ChainWorkers { shards }))
+    }
+
+    async fn get_chain_state(
+        &self,
+        request: Request<api::ChainId>,
+    ) -> Result<Response<ChainStateSummary>, Status> {
+        let chain_id = request.into_inner().try_into()?;
+        let (state, queue) = self.shard(chain_id)?;
+        let chain = state.chain_state_view(chain_id).await.map_err(internal)?;
+
+        let locked_block = chain
+            .manager
+            .locked
+            .get()
+            .as_ref()
+            .map(|locked| match locked {
+                LockedBlock::Fast(proposal) => LockedBlockSummary {
+                    round: locked.round().to_string(),
+                    height: proposal.content.block.height.0,
+                    hash: None,
+                },
+                LockedBlock::Regular(certificate) => LockedBlockSummary {
+                    round: locked.round().to_string(),
+                    height: certificate.inner().height().0,
+                    hash: Some(certificate.hash().into()),
+                },
+            });
+
+        let origins = chain.inboxes.indices().await.map_err(internal)?;
+        let inboxes = chain
+            .inboxes
+            .try_load_entries(&origins)
+            .await
+            .map_err(internal)?;
+        let mut inbox_sizes = Vec::new();
+        for (origin, inbox) in origins.iter().zip(inboxes) {
+            let Some(inbox) = inbox else {
+                continue;
+            };
+            let delayed = inbox.delayed_bundles.count().await.map_err(internal)?;
+            let size = inbox.added_bundles.count() + delayed;
+            inbox_sizes.push(queue_size(origin.sender, &origin.medium, size));
+        }
+
+        let targets = chain.outboxes.indices().await.map_err(internal)?;
+        let outboxes = chain
+            .outboxes
+            .try_load_entries(&targets)
+            .await
+            .map_err(internal)?;
+        let outboxes = targets
+            .iter()
+            .zip(outboxes)
+            .filter_map(|(target, outbox)| {
+                let size = outbox?.queue.count();
+                Some(queue_size(target.recipient, &target.medium, size))
+            })
+            .collect();
+
+        Ok(Response::new(ChainStateSummary {
+            shard_id: queue.shard_id() as u64,
+            next_block_height: chain.tip_state.get().next_block_height.0,
+            current_round: chain.manager.current_round().to_string(),
+            locked_block,
+            inboxes: inbox_sizes,
+            outboxes,
+            pending_cross_chain_requests: queue.num_pending_requests_from(chain_id) as u64,
+        }))
+    }
+
+    async fn get_cache_stats(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<api::CacheStats>, Status> {
+        let mut shards = Vec::new();
+        for (state, queue) in &self.shards {
+            let stats = state.executed_block_cache_stats().await;
+            shards.push(ShardCacheStats {
+                shard_id: queue.shard_id() as u64,
+                executed_blocks: Some(api::ValueCacheStats {
+                    size: stats.size as u64,
+                    capacity: stats.capacity as u64,
+                    hits: stats.hits,
+                    misses: stats.misses,
+                }),
+            });
+        }
+        Ok(Response::new(api::CacheStats { shards }))
+    }
+
+    async fn evict_chain_worker(
+        &self,
+        request: Request<api::ChainId>,
+    ) -> Result<Response<()>, Status> {
+        let chain_id = request.into_inner().try_into()?;
+        let (state, _) = self.shard(chain_id)?;
+        info!("Evicting the worker of chain {chain_id} on request of an operator");
+        state.evict_chain_workers(|id| id == chain_id);
+        Ok(Response::new(()))
+    }
+
+    async fn resend_outbox_messages(
+        &self,
+        request: Request<api::ChainId>,
+    ) -> Result<Response<ResendOutboxMessagesResult>, Status> {
+        let chain_id = request.into_inner().try_into()?;
+        let (state, queue) = self.shard(chain_id)?;
+        info!("Re-sending the outbox messages of chain {chain_id} on request of an operator");
+        let (_, actions) = state
+            .handle_chain_info_query(ChainInfoQuery::new(chain_id))
+            .await
+            .map_err(internal)?;
+        let num_requests = actions.cross_chain_requests.len() as u64;
+        for request in actions.cross_chain_requests {
+            queue.push(request).await.map_err(internal)?;
+        }
+        Ok(Response::new(ResendOutboxMessagesResult { num_requests }))
+    }
+
+    async fn snapshot_storage(
+        &self,
+        request: Request<SnapshotStorageRequest>,
+    ) -> Result<Response<()>, Status> {
+        let path = PathBuf::from(request.into_inner().path);
+        info!(
+            "Writing a snapshot of the storage to {} on request of an operator",
+            path.display()
+        );
+        self.storage.snapshot(&path).await.map_err(internal)?;
+        Ok(Response::new(()))
+    }
+}
+
+/// Rejects the requests without the expected bearer token.
+#[derive(Clone)]
+struct CheckToken {
+    expected: MetadataValue<Ascii>,
+}
+
+impl Interceptor for CheckToken {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        match request.metadata().get(AUTHORIZATION_METADATA_KEY) {
+            Some(value) if constant_time_eq(self.expected.as_bytes(), value.as_bytes()) => {
+                Ok(request)
+            }
+            _ => Err(Status::unauthenticated("missing or invalid admin token")),
+        }
+    }
+}
+
+/// Adds a bearer token to the requests.
+#[derive(Clone)]
+pub struct AddToken {
+    value: MetadataValue<Ascii>,
+}
+
+impl Interceptor for AddToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request
+            .metadata_mut()
+            .insert(AUTHORIZATION_METADATA_KEY, self.value.clone());
+        Ok(request)
+    }
+}
+
+/// A client of the administration service of a server process.
+#[derive(Clone)]
+pub struct AdminClient {
+    client: ValidatorAdminClient<InterceptedService<transport::Channel, AddToken>>,
+}
+
+impl AdminClient {
+    /// Creates a client sending requests authenticated with `token` to `address`, e.g.
+    /// `http://127.0.0.1:9200`.
+    pub fn new(address: String, token: &str) -> Result<Self, GrpcError> {
+        let channel = transport::create_channel(address, &transport::Options::default())?;
+        let add_token = AddToken {
+            value: bearer(token)?,
+        };
+        let client = ValidatorAdminClient::with_interceptor(channel, add_token);
+        Ok(Self { client })
+    }
+
+    pub async fn cross_chain_queue(&mut self) -> Result<CrossChainQueueStatus, Status> {
+        Ok(self.client.get_cross_chain_queue(()).await?.into_inner())
+    }
+
+    pub async fn chain_workers(&mut self) -> Result<ChainWorkers, Status> {
+        Ok(self.client.list_chain_workers(()).await?.into_inner())
+    }
+
+    pub async fn chain_state(&mut self, chain_id: ChainId) -> Result<ChainStateSummary, Status> {
+        let request = api::ChainId::from(chain_id);
+        Ok(self.client.get_chain_state(request).await?.into_inner())
+    }
+
+    pub async fn cache_stats(&mut self) -> Result<api::CacheStats, Status> {
+        Ok(self.client.get_cache_stats(()).await?.into_inner())
+    }
+
+    pub async fn evict_chain_worker(&mut self, chain_id: ChainId) -> Result<(), Status> {
+        let request = api::ChainId::from(chain_id);
+        self.client.evict_chain_worker(request).await?;
+        Ok(())
+    }
+
+    /// Returns the number of cross-chain requests scheduled.
+    pub async fn resend_outbox_messages(&mut self, chain_id: ChainId) -> Result<u64, Status> {
+        let request = api::ChainId::from(chain_id);
+        let result = self.client.resend_outbox_messages(request).await?;
+        Ok(result.into_inner().num_requests)
+    }
+
+    /// Writes a snapshot of the storage of the server into the directory `path` on its host.
+    pub async fn snapshot_storage(&mut self, path: &Path) -> Result<(), Status> {
+        let request = SnapshotStorageRequest {
+            path: path.display().to_string(),
+        };
+        self.client.snapshot_storage(request).await?;
+        Ok(())
+    }
+}
+
+fn bearer(token: &str) -> Result<MetadataValue<Ascii>, GrpcError> {
+    Ok(format!("Bearer {token}").parse()?)
+}
+
+/// Compares a byte string with the expected one, in a time that only depends on the
+/// length of the expected one.
+fn constant_time_eq(expected: &[u8], actual: &[u8]) -> bool {
+    let difference =
+        expected
+            .iter()
+            .enumerate()
+            .fold(expected.len() ^ actual.len(), |difference, (i, byte)| {
+                let other = actual.get(i).copied().unwrap_or(!byte);
+                difference | usize::from(byte ^ other)
+            });
+    difference == 0
+}
+
+fn internal(error: impl fmt::Display) -> Status {
+    Status::internal(error.to_string())
+}
+
+fn queue_size(chain_id: ChainId, medium: &Medium, size: usize) -> QueueSize {
+    let channel = match medium {
+        Medium::Direct => None,
+        Medium::Channel(name) => Some(name.to_string()),
+    };
+    QueueSize {
+        chain_id: Some(chain_id.into()),
+        channel,
+        size: size as u64,
+    }
+}
+
+fn queue_target(source_shard_id: ShardId, status: TargetShardStatus) -> CrossChainQueueTarget {
+    CrossChainQueueTarget {
+        source_shard_id: source_shard_id as u64,
+        target_shard_id: status.shard_id as u64,
+        num_requests: status.num_requests as u64,
+        oldest_request_age_ms: millis(status.oldest_request_age),
+        stuck_requests: status
+            .stuck_requests
+            .into_iter()
+            .map(stuck_request)
+            .collect(),
+    }
+}
+
+fn stuck_request(request: StuckRequest) -> StuckCrossChainRequest {
+    StuckCrossChainRequest {
+        origin: Some(request.origin.into()),
+        target: Some(request.target.into()),
+        attempts: request.attempts,
+        age_ms: millis(request.age),
+        last_error: request.last_error,
+    }
+}
+
+fn millis(duration: std::time::Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_token() {
+        let mut check_token = CheckToken {
+            expected: bearer("secret").unwrap(),
+        };
+        let mut add_token = AddToken {
+            value: bearer("secret").unwrap(),
+        };
+        let request = add_token.call(Request::new(())).unwrap();
+        assert!(check_token.call(request).is_ok());
+
+        let mut add_token = AddToken {
+            value: bearer("wrong").unwrap(),
+        };
+        let request = add_token.call(Request::new(())).unwrap();
+        let status = check_token.call(request).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let status = check_token.call(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret "));
+        assert!(!constant_time_eq(b"secret", b"secre"));
+        assert!(!constant_time_eq(b"secret", b""));
+        assert!(!constant_time_eq(b"", b"secret"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
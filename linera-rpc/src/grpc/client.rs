@@ -34,7 +34,7 @@ use {
 
 use super::{
     api::{self, validator_node_client::ValidatorNodeClient, SubscriptionRequest},
-    transport, GRPC_MAX_MESSAGE_SIZE,
+    rate_limited_retry_after_ms, transport, GRPC_MAX_MESSAGE_SIZE,
 };
 use crate::{
     HandleConfirmedCertificateRequest, HandleLiteCertRequest, HandleTimeoutCertificateRequest,
@@ -124,13 +124,18 @@ impl GrpcClient {
         })?;
         loop {
             match f(self.client.clone(), Request::new(request_inner.clone())).await {
-                Err(s) if Self::is_retryable(&s) && retry_count < self.max_retries => {
-                    let delay = self.retry_delay.saturating_mul(retry_count);
-                    retry_count += 1;
-                    linera_base::time::timer::sleep(delay).await;
-                    continue;
-                }
                 Err(s) => {
+                    // The rate limits are handled by the caller, which may use other nodes
+                    // in the meantime.
+                    if let Some(retry_after_ms) = rate_limited_retry_after_ms(&s) {
+                        return Err(NodeError::RateLimited { retry_after_ms });
+                    }
+                    if Self::is_retryable(&s) && retry_count < self.max_retries {
+                        let delay = self.retry_delay.saturating_mul(retry_count);
+                        retry_count += 1;
+                        linera_base::time::timer::sleep(delay).await;
+                        continue;
+                    }
                     return Err(NodeError::GrpcError {
                         error: format!("remote request [{handler}] failed with status: {s:?}",),
                     });
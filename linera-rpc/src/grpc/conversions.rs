@@ -204,6 +204,10 @@ impl TryFrom<BlockProposal> for api::BlockProposal {
                 .validated_block_certificate
                 .map(|cert| bincode::serialize(&cert))
                 .transpose()?,
+            multi_sig: block_proposal
+                .multi_sig
+                .map(|multi_sig| bincode::serialize(&multi_sig))
+                .transpose()?,
         })
     }
 }
@@ -227,6 +231,10 @@ impl TryFrom<api::BlockProposal> for BlockProposal {
                 .validated_block_certificate
                 .map(|bytes| bincode::deserialize(&bytes))
                 .transpose()?,
+            multi_sig: block_proposal
+                .multi_sig
+                .map(|bytes| bincode::deserialize(&bytes))
+                .transpose()?,
         })
     }
 }
@@ -621,7 +629,7 @@ impl TryFrom<api::ChainId> for ChainId {
 impl From<PublicKey> for api::PublicKey {
     fn from(public_key: PublicKey) -> Self {
         Self {
-            bytes: public_key.0.to_vec(),
+            bytes: public_key.as_bytes().to_vec(),
         }
     }
 }
@@ -637,7 +645,7 @@ impl TryFrom<api::PublicKey> for PublicKey {
 impl From<ValidatorName> for api::PublicKey {
     fn from(validator_name: ValidatorName) -> Self {
         Self {
-            bytes: validator_name.0 .0.to_vec(),
+            bytes: validator_name.0.as_bytes().to_vec(),
         }
     }
 }
@@ -653,7 +661,7 @@ impl TryFrom<api::PublicKey> for ValidatorName {
 impl From<Signature> for api::Signature {
     fn from(signature: Signature) -> Self {
         Self {
-            bytes: signature.0.to_vec(),
+            bytes: signature.to_bytes(),
         }
     }
 }
@@ -662,7 +670,7 @@ impl TryFrom<api::Signature> for Signature {
     type Error = GrpcProtoConversionError;
 
     fn try_from(signature: api::Signature) -> Result<Self, Self::Error> {
-        Ok(Self(signature.bytes.as_slice().try_into()?))
+        Ok(signature.bytes.as_slice().try_into()?)
     }
 }
 
@@ -1011,6 +1019,8 @@ pub mod tests {
     pub fn test_public_key() {
         let public_key = KeyPair::generate().public();
         round_trip_check::<_, api::PublicKey>(public_key);
+        let public_key = KeyPair::generate_secp256k1().public();
+        round_trip_check::<_, api::PublicKey>(public_key);
     }
 
     #[test]
@@ -1018,6 +1028,9 @@ pub mod tests {
         let key_pair = KeyPair::generate();
         let signature = Signature::new(&Foo("test".into()), &key_pair);
         round_trip_check::<_, api::Signature>(signature);
+        let key_pair = KeyPair::generate_secp256k1();
+        let signature = Signature::new(&Foo("test".into()), &key_pair);
+        round_trip_check::<_, api::Signature>(signature);
     }
 
     #[test]
@@ -1226,6 +1239,7 @@ pub mod tests {
             signature: Signature::new(&Foo("test".into()), &KeyPair::generate()),
             blobs: vec![],
             validated_block_certificate: Some(cert),
+            multi_sig: None,
         };
 
         round_trip_check::<_, api::BlockProposal>(block_proposal);
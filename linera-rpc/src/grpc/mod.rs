@@ -1,6 +1,8 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(with_server)]
+mod admin;
 mod client;
 mod conversions;
 mod node_provider;
@@ -9,6 +11,8 @@ pub mod pool;
 mod server;
 pub mod transport;
 
+#[cfg(with_server)]
+pub use admin::*;
 pub use client::*;
 pub use conversions::*;
 pub use node_provider::*;
@@ -36,6 +40,9 @@ pub enum GrpcError {
     #[error(transparent)]
     InvalidUri(#[from] tonic::codegen::http::uri::InvalidUri),
 
+    #[error("invalid metadata value: {0}")]
+    InvalidMetadataValue(#[from] tonic::metadata::errors::InvalidMetadataValue),
+
     #[cfg(with_server)]
     #[error(transparent)]
     Reflection(#[from] tonic_reflection::server::Error),
@@ -47,3 +54,31 @@ pub const GRPC_MAX_MESSAGE_SIZE: usize = 16 * MEBIBYTE;
 /// Limit of gRPC message size up to which we will try to populate with data when estimating.
 /// We leave 30% of buffer for the rest of the message and potential underestimation.
 pub const GRPC_CHUNKED_MESSAGE_FILL_LIMIT: usize = GRPC_MAX_MESSAGE_SIZE * 7 / 10;
+
+/// The metadata entry of a rate-limited response, with the number of milliseconds after
+/// which the request may be sent again.
+const RETRY_AFTER_MS_METADATA_KEY: &str = "linera-retry-after-ms";
+
+/// Returns the status of a request rejected because of the rate limits of a proxy.
+pub fn rate_limited_status(retry_after_ms: u64) -> tonic::Status {
+    let mut status = tonic::Status::resource_exhausted("rate limit exceeded");
+    status
+        .metadata_mut()
+        .insert(RETRY_AFTER_MS_METADATA_KEY, retry_after_ms.into());
+    status
+}
+
+/// Returns the number of milliseconds after which the request may be sent again, if the
+/// status means that it was rejected because of the rate limits of a proxy.
+pub fn rate_limited_retry_after_ms(status: &tonic::Status) -> Option<u64> {
+    if status.code() != tonic::Code::ResourceExhausted {
+        return None;
+    }
+    status
+        .metadata()
+        .get(RETRY_AFTER_MS_METADATA_KEY)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
@@ -7,13 +7,13 @@ use std::{
     net::{IpAddr, SocketAddr},
     str::FromStr,
     task::{Context, Poll},
-    time::{Duration, Instant},
+    time::Instant,
 };
 
 use futures::{
     channel::{mpsc, mpsc::Receiver},
     future::BoxFuture,
-    FutureExt as _, StreamExt,
+    stream, FutureExt as _, StreamExt,
 };
 use linera_base::{data_types::Blob, identifiers::ChainId};
 use linera_core::{
@@ -49,12 +49,14 @@ use super::{
     GrpcError, GRPC_MAX_MESSAGE_SIZE,
 };
 use crate::{
-    config::{CrossChainConfig, NotificationConfig, ShardId, ValidatorInternalNetworkConfig},
+    config::{
+        CrossChainConfig, NetworkProtocol, NotificationConfig, ShardId, SharedInternalNetworkConfig,
+    },
+    cross_chain_queue::CrossChainQueue,
     HandleConfirmedCertificateRequest, HandleLiteCertRequest, HandleTimeoutCertificateRequest,
     HandleValidatedCertificateRequest,
 };
 
-type CrossChainSender = mpsc::Sender<(linera_core::data_types::CrossChainRequest, ShardId)>;
 type NotificationSender = mpsc::Sender<Notification>;
 
 #[cfg(with_metrics)]
@@ -106,13 +108,13 @@ where
 {
     state: WorkerState<S>,
     shard_id: ShardId,
-    network: ValidatorInternalNetworkConfig,
-    cross_chain_sender: CrossChainSender,
+    network: SharedInternalNetworkConfig<NetworkProtocol>,
+    cross_chain_queue: CrossChainQueue<S>,
     notification_sender: NotificationSender,
 }
 
 pub struct GrpcServerHandle {
-    handle: TaskHandle<Result<(), GrpcError>>,
+    pub(super) handle: TaskHandle<Result<(), GrpcError>>,
 }
 
 impl GrpcServerHandle {
@@ -179,8 +181,9 @@ where
         port: u16,
         state: WorkerState<S>,
         shard_id: ShardId,
-        internal_network: ValidatorInternalNetworkConfig,
+        internal_network: SharedInternalNetworkConfig<NetworkProtocol>,
         cross_chain_config: CrossChainConfig,
+        cross_chain_queue: CrossChainQueue<S>,
         notification_config: NotificationConfig,
         shutdown_signal: CancellationToken,
         join_set: &mut JoinSet<()>,
@@ -190,9 +193,6 @@ where
             host, port, shard_id
         );
 
-        let (cross_chain_sender, cross_chain_receiver) =
-            mpsc::channel(cross_chain_config.queue_size);
-
         let (notification_sender, notification_receiver) =
             mpsc::channel(notification_config.notification_queue_size);
 
@@ -204,13 +204,11 @@ where
             Self::forward_cross_chain_queries(
                 state.nickname().to_string(),
                 internal_network.clone(),
-                cross_chain_config.max_retries,
-                Duration::from_millis(cross_chain_config.retry_delay_ms),
-                Duration::from_millis(cross_chain_config.sender_delay_ms),
                 cross_chain_config.sender_failure_rate,
                 cross_chain_config.max_concurrent_tasks,
                 shard_id,
-                cross_chain_receiver,
+                cross_chain_queue.clone(),
+                shutdown_signal.clone(),
             )
         });
 
@@ -221,7 +219,7 @@ where
             );
             Self::forward_notifications(
                 state.nickname().to_string(),
-                internal_network.proxy_address(),
+                internal_network.get().proxy_address(),
                 notification_receiver,
             )
         });
@@ -232,7 +230,7 @@ where
             state,
             shard_id,
             network: internal_network,
-            cross_chain_sender,
+            cross_chain_queue,
             notification_sender,
         };
 
@@ -304,21 +302,17 @@ where
         }
     }
 
-    fn handle_network_actions(&self, actions: NetworkActions) {
-        let mut cross_chain_sender = self.cross_chain_sender.clone();
+    async fn handle_network_actions(&self, actions: NetworkActions) {
         let mut notification_sender = self.notification_sender.clone();
 
         for request in actions.cross_chain_requests {
-            let shard_id = self.network.get_shard_id(request.target_chain_id());
             trace!(
                 source_shard_id = self.shard_id,
-                target_shard_id = shard_id,
+                target_chain_id = %request.target_chain_id(),
                 "Scheduling cross-chain query",
             );
-
-            if let Err(error) = cross_chain_sender.try_send((request, shard_id)) {
-                error!(%error, "dropping cross-chain request");
-                break;
+            if let Err(error) = self.cross_chain_queue.push(request).await {
+                error!(%error, "failed to persist cross-chain request");
             }
         }
 
@@ -332,85 +326,85 @@ where
     }
 
     #[instrument(skip_all, fields(nickname, %this_shard))]
-    #[expect(clippy::too_many_arguments)]
     async fn forward_cross_chain_queries(
         nickname: String,
-        network: ValidatorInternalNetworkConfig,
-        cross_chain_max_retries: u32,
-        cross_chain_retry_delay: Duration,
-        cross_chain_sender_delay: Duration,
+        network: SharedInternalNetworkConfig<NetworkProtocol>,
         cross_chain_sender_failure_rate: f32,
         cross_chain_max_concurrent_tasks: usize,
         this_shard: ShardId,
-        receiver: mpsc::Receiver<(linera_core::data_types::CrossChainRequest, ShardId)>,
+        queue: CrossChainQueue<S>,
+        shutdown_signal: CancellationToken,
     ) {
         let pool = GrpcConnectionPool::default();
         let max_concurrent_tasks = Some(cross_chain_max_concurrent_tasks);
 
-        receiver
-            .for_each_concurrent(max_concurrent_tasks, |(cross_chain_request, shard_id)| {
-                let shard = network.shard(shard_id);
-                let remote_address = shard.http_address();
-
-                let pool = pool.clone();
-                let nickname = nickname.clone();
+        match queue.load().await {
+            Ok(count) => info!(nickname, count, "Loaded pending cross-chain queries"),
+            Err(error) => error!(nickname, %error, "Failed to load pending cross-chain queries"),
+        }
 
-                // Send the cross-chain query and retry if needed.
-                async move {
+        stream::unfold(queue.clone(), |queue| async move {
+            let delivery = queue.next().await;
+            Some((delivery, queue))
+        })
+        .take_until(shutdown_signal.cancelled_owned())
+        .for_each_concurrent(max_concurrent_tasks, |delivery| {
+            let network = network.get();
+            let shard_id = network.get_shard_id(delivery.request.target_chain_id());
+            let remote_address = network.shard(shard_id).http_address();
+            let is_moving = network.is_moving(delivery.request.target_chain_id());
+
+            let pool = pool.clone();
+            let nickname = nickname.clone();
+            let queue = queue.clone();
+
+            // Send the cross-chain query, to be retried later if it fails.
+            async move {
+                let result = || async {
                     if cross_chain_sender_failure_rate > 0.0
                         && rand::thread_rng().gen::<f32>() < cross_chain_sender_failure_rate
                     {
-                        warn!("Dropped 1 cross-chain message intentionally.");
-                        return;
+                        anyhow::bail!("failed intentionally");
                     }
-
-                    for i in 0..cross_chain_max_retries {
-                        // Delay increases linearly with the attempt number.
-                        linera_base::time::timer::sleep(
-                            cross_chain_sender_delay + cross_chain_retry_delay * i,
-                        )
-                        .await;
-
-                        let result = || async {
-                            let cross_chain_request = cross_chain_request.clone().try_into()?;
-                            let request = Request::new(cross_chain_request);
-                            let mut client =
-                                ValidatorWorkerClient::new(pool.channel(remote_address.clone())?)
-                                    .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
-                                    .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE);
-                            let response = client.handle_cross_chain_request(request).await?;
-                            Ok::<_, anyhow::Error>(response)
-                        };
-                        match result().await {
-                            Err(error) => {
-                                warn!(
-                                    nickname,
-                                    %error,
-                                    i,
-                                    from_shard = this_shard,
-                                    to_shard = shard_id,
-                                    "Failed to send cross-chain query",
-                                );
-                            }
-                            _ => {
-                                trace!(
-                                    from_shard = this_shard,
-                                    to_shard = shard_id,
-                                    "Sent cross-chain query",
-                                );
-                                break;
-                            }
-                        }
-                        error!(
+                    if is_moving {
+                        // No shard handles the target chain until the handoff is over.
+                        anyhow::bail!("the target chain is being moved to another shard");
+                    }
+                    let cross_chain_request = delivery.request.clone().try_into()?;
+                    let request = Request::new(cross_chain_request);
+                    let mut client =
+                        ValidatorWorkerClient::new(pool.channel(remote_address.clone())?)
+                            .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
+                            .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE);
+                    let response = client.handle_cross_chain_request(request).await?;
+                    Ok::<_, anyhow::Error>(response)
+                };
+                match result().await {
+                    Err(error) => {
+                        warn!(
                             nickname,
+                            %error,
+                            attempt = delivery.attempt,
                             from_shard = this_shard,
                             to_shard = shard_id,
-                            "Dropping cross-chain query",
+                            "Failed to send cross-chain query",
                         );
+                        queue.failed(&delivery, error.to_string());
+                    }
+                    Ok(_) => {
+                        trace!(
+                            from_shard = this_shard,
+                            to_shard = shard_id,
+                            "Sent cross-chain query",
+                        );
+                        if let Err(error) = queue.delivered(&delivery).await {
+                            error!(nickname, %error, "Failed to remove delivered cross-chain query");
+                        }
                     }
                 }
-            })
-            .await;
+            }
+        })
+        .await;
     }
 
     fn log_request_success_and_latency(start: Instant, method_name: &str) {
@@ -452,7 +446,7 @@ where
             match self.state.clone().handle_block_proposal(proposal).await {
                 Ok((info, actions)) => {
                     Self::log_request_success_and_latency(start, "handle_block_proposal");
-                    self.handle_network_actions(actions);
+                    self.handle_network_actions(actions).await;
                     info.try_into()?
                 }
                 Err(error) => {
@@ -497,7 +491,7 @@ where
         {
             Ok((info, actions)) => {
                 Self::log_request_success_and_latency(start, "handle_lite_certificate");
-                self.handle_network_actions(actions);
+                self.handle_network_actions(actions).await;
                 if let Some(receiver) = receiver {
                     if let Err(e) = receiver.await {
                         error!("Failed to wait for message delivery: {e}");
@@ -550,7 +544,7 @@ where
         {
             Ok((info, actions)) => {
                 Self::log_request_success_and_latency(start, "handle_confirmed_certificate");
-                self.handle_network_actions(actions);
+                self.handle_network_actions(actions).await;
                 if let Some(receiver) = receiver {
                     if let Err(e) = receiver.await {
                         error!("Failed to wait for message delivery: {e}");
@@ -595,7 +589,7 @@ where
         {
             Ok((info, actions)) => {
                 Self::log_request_success_and_latency(start, "handle_validated_certificate");
-                self.handle_network_actions(actions);
+                self.handle_network_actions(actions).await;
                 Ok(Response::new(info.try_into()?))
             }
             Err(error) => {
@@ -669,7 +663,7 @@ where
         match self.state.clone().handle_chain_info_query(query).await {
             Ok((info, actions)) => {
                 Self::log_request_success_and_latency(start, "handle_chain_info_query");
-                self.handle_network_actions(actions);
+                self.handle_network_actions(actions).await;
                 Ok(Response::new(info.try_into()?))
             }
             Err(error) => {
@@ -779,7 +773,12 @@ where
         match self.state.clone().handle_cross_chain_request(request).await {
             Ok(actions) => {
                 Self::log_request_success_and_latency(start, "handle_cross_chain_request");
-                self.handle_network_actions(actions)
+                self.handle_network_actions(actions).await
+            }
+            Err(error @ WorkerError::ChainNotHandled(_)) => {
+                // The sender keeps the request and retries once the shard map is updated.
+                debug!(nickname = self.state.nickname(), %error, "Rejected cross-chain request");
+                return Err(Status::unavailable(error.to_string()));
             }
             Err(error) => {
                 #[cfg(with_metrics)]
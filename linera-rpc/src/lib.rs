@@ -10,6 +10,8 @@
 #![allow(clippy::blocks_in_conditions)]
 
 pub mod config;
+#[cfg(with_server)]
+pub mod cross_chain_queue;
 pub mod mass_client;
 pub mod node_provider;
 
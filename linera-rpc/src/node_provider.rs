@@ -6,7 +6,7 @@ use linera_core::node::{NodeError, ValidatorNodeProvider};
 
 #[cfg(with_simple_network)]
 use crate::simple::SimpleNodeProvider;
-use crate::{client::Client, grpc::GrpcNodeProvider};
+use crate::{client::Client, config::QuicConfig, grpc::GrpcNodeProvider};
 
 /// A general node provider which delegates node provision to the underlying
 /// node provider according to the `ValidatorPublicNetworkConfig`.
@@ -25,6 +25,19 @@ impl NodeProvider {
             simple: SimpleNodeProvider::new(options),
         }
     }
+
+    /// Authenticates the validators that use QUIC as configured by `quic_config`. QUIC is
+    /// only supported with the simple network.
+    #[cfg_attr(not(with_simple_network), allow(unused_mut))]
+    pub fn with_quic_config(mut self, quic_config: QuicConfig) -> Self {
+        #[cfg(with_simple_network)]
+        {
+            self.simple = self.simple.with_quic_config(quic_config);
+        }
+        #[cfg(not(with_simple_network))]
+        let _ = quic_config;
+        self
+    }
 }
 
 impl ValidatorNodeProvider for NodeProvider {
@@ -34,7 +47,7 @@ impl ValidatorNodeProvider for NodeProvider {
         let address = address.to_lowercase();
 
         #[cfg(with_simple_network)]
-        if address.starts_with("tcp") || address.starts_with("udp") {
+        if address.starts_with("tcp") || address.starts_with("udp") || address.starts_with("quic") {
             return Ok(Client::Simple(self.simple.make_node(&address)?));
         }
 
@@ -24,7 +24,10 @@ use linera_core::{
 };
 use linera_version::VersionInfo;
 
-use super::{codec, transport::TransportProtocol};
+use super::{
+    codec,
+    transport::{QuicConnector, TransportProtocol},
+};
 use crate::{
     config::ValidatorPublicNetworkPreConfig, mass_client, HandleConfirmedCertificateRequest,
     HandleLiteCertRequest, HandleTimeoutCertificateRequest, HandleValidatedCertificateRequest,
@@ -34,6 +37,7 @@ use crate::{
 #[derive(Clone)]
 pub struct SimpleClient {
     network: ValidatorPublicNetworkPreConfig<TransportProtocol>,
+    quic: QuicConnector,
     send_timeout: Duration,
     recv_timeout: Duration,
 }
@@ -41,19 +45,24 @@ pub struct SimpleClient {
 impl SimpleClient {
     pub(crate) fn new(
         network: ValidatorPublicNetworkPreConfig<TransportProtocol>,
+        quic: QuicConnector,
         send_timeout: Duration,
         recv_timeout: Duration,
     ) -> Self {
         Self {
             network,
+            quic,
             send_timeout,
             recv_timeout,
         }
     }
 
     async fn send_recv_internal(&self, message: RpcMessage) -> Result<RpcMessage, codec::Error> {
-        let address = format!("{}:{}", self.network.host, self.network.port);
-        let mut stream = self.network.protocol.connect(address).await?;
+        let mut stream = self
+            .network
+            .protocol
+            .connect(&self.network.host, self.network.port, &self.quic)
+            .await?;
         // Send message
         timer::timeout(self.send_timeout, stream.send(message))
             .await
@@ -236,6 +245,7 @@ impl ValidatorNode for SimpleClient {
 #[derive(Clone)]
 pub struct SimpleMassClient {
     pub network: ValidatorPublicNetworkPreConfig<TransportProtocol>,
+    quic: QuicConnector,
     send_timeout: Duration,
     recv_timeout: Duration,
 }
@@ -243,11 +253,13 @@ pub struct SimpleMassClient {
 impl SimpleMassClient {
     pub fn new(
         network: ValidatorPublicNetworkPreConfig<TransportProtocol>,
+        quic: QuicConnector,
         send_timeout: Duration,
         recv_timeout: Duration,
     ) -> Self {
         Self {
             network,
+            quic,
             send_timeout,
             recv_timeout,
         }
@@ -261,8 +273,11 @@ impl mass_client::MassClient for SimpleMassClient {
         requests: Vec<RpcMessage>,
         max_in_flight: usize,
     ) -> Result<Vec<RpcMessage>, mass_client::MassClientError> {
-        let address = format!("{}:{}", self.network.host, self.network.port);
-        let mut stream = self.network.protocol.connect(address).await?;
+        let mut stream = self
+            .network
+            .protocol
+            .connect(&self.network.host, self.network.port, &self.quic)
+            .await?;
         let mut requests = requests.into_iter();
         let mut in_flight = 0;
         let mut responses = Vec::new();
@@ -5,16 +5,32 @@ use std::str::FromStr as _;
 
 use linera_core::node::{NodeError, ValidatorNodeProvider};
 
-use super::SimpleClient;
-use crate::{config::ValidatorPublicNetworkPreConfig, node_provider::NodeOptions};
+use super::{QuicConnector, SimpleClient};
+use crate::{
+    config::{QuicConfig, ValidatorPublicNetworkPreConfig},
+    node_provider::NodeOptions,
+};
 
 /// A client without an address - serves as a client factory.
-#[derive(Copy, Clone)]
-pub struct SimpleNodeProvider(NodeOptions);
+#[derive(Clone)]
+pub struct SimpleNodeProvider {
+    options: NodeOptions,
+    /// The QUIC connections, shared by the clients of this provider.
+    quic: QuicConnector,
+}
 
 impl SimpleNodeProvider {
     pub fn new(options: NodeOptions) -> Self {
-        Self(options)
+        Self {
+            options,
+            quic: QuicConnector::default(),
+        }
+    }
+
+    /// Authenticates the validators that use QUIC as configured by `quic_config`.
+    pub fn with_quic_config(mut self, quic_config: QuicConfig) -> Self {
+        self.quic = QuicConnector::new(quic_config);
+        self
     }
 }
 
@@ -28,7 +44,12 @@ impl ValidatorNodeProvider for SimpleNodeProvider {
             }
         })?;
 
-        let client = SimpleClient::new(network, self.0.send_timeout, self.0.recv_timeout);
+        let client = SimpleClient::new(
+            network,
+            self.quic.clone(),
+            self.options.send_timeout,
+            self.options.recv_timeout,
+        );
 
         Ok(client)
     }
@@ -1,10 +1,12 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::net::SocketAddr;
+
 use async_trait::async_trait;
-use futures::{channel::mpsc, stream::StreamExt};
-use linera_base::{data_types::Blob, time::Duration};
+use linera_base::data_types::Blob;
 use linera_core::{
+    data_types::CrossChainRequest,
     node::NodeError,
     worker::{NetworkActions, WorkerError, WorkerState},
     JoinSetExt as _,
@@ -15,9 +17,10 @@ use tokio::{sync::oneshot, task::JoinSet};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, instrument, warn};
 
-use super::transport::{MessageHandler, ServerHandle, TransportProtocol};
+use super::transport::{MessageHandler, QuicConnector, ServerHandle, TransportProtocol};
 use crate::{
-    config::{CrossChainConfig, ShardId, ValidatorInternalNetworkPreConfig},
+    config::{CrossChainConfig, QuicConfig, ShardId, SharedInternalNetworkConfig},
+    cross_chain_queue::CrossChainQueue,
     RpcMessage,
 };
 
@@ -26,12 +29,14 @@ pub struct Server<S>
 where
     S: Storage,
 {
-    network: ValidatorInternalNetworkPreConfig<TransportProtocol>,
+    network: SharedInternalNetworkConfig<TransportProtocol>,
     host: String,
     port: u16,
     state: WorkerState<S>,
     shard_id: ShardId,
     cross_chain_config: CrossChainConfig,
+    cross_chain_queue: CrossChainQueue<S>,
+    quic_config: QuicConfig,
     // Stats
     packets_processed: u64,
     user_errors: u64,
@@ -42,12 +47,13 @@ where
     S: Storage,
 {
     pub fn new(
-        network: ValidatorInternalNetworkPreConfig<TransportProtocol>,
+        network: SharedInternalNetworkConfig<TransportProtocol>,
         host: String,
         port: u16,
         state: WorkerState<S>,
         shard_id: ShardId,
         cross_chain_config: CrossChainConfig,
+        cross_chain_queue: CrossChainQueue<S>,
     ) -> Self {
         Self {
             network,
@@ -56,11 +62,20 @@ where
             state,
             shard_id,
             cross_chain_config,
+            cross_chain_queue,
+            quic_config: QuicConfig::default(),
             packets_processed: 0,
             user_errors: 0,
         }
     }
 
+    /// Configures the certificate of this server and the servers it trusts, if it uses
+    /// QUIC.
+    pub fn with_quic_config(mut self, quic_config: QuicConfig) -> Self {
+        self.quic_config = quic_config;
+        self
+    }
+
     pub fn packets_processed(&self) -> u64 {
         self.packets_processed
     }
@@ -74,69 +89,71 @@ impl<S> Server<S>
 where
     S: Storage + Clone + Send + Sync + 'static,
 {
-    #[expect(clippy::too_many_arguments)]
     async fn forward_cross_chain_queries(
         nickname: String,
-        network: ValidatorInternalNetworkPreConfig<TransportProtocol>,
-        cross_chain_max_retries: u32,
-        cross_chain_retry_delay: Duration,
-        cross_chain_sender_delay: Duration,
+        network: SharedInternalNetworkConfig<TransportProtocol>,
+        quic: QuicConnector,
         cross_chain_sender_failure_rate: f32,
         this_shard: ShardId,
-        mut receiver: mpsc::Receiver<(RpcMessage, ShardId)>,
+        queue: CrossChainQueue<S>,
+        shutdown_signal: CancellationToken,
     ) {
         let mut pool = network
+            .get()
             .protocol
-            .make_outgoing_connection_pool()
+            .make_outgoing_connection_pool(&quic)
             .await
             .expect("Initialization should not fail");
 
-        while let Some((message, shard_id)) = receiver.next().await {
-            if cross_chain_sender_failure_rate > 0.0
-                && rand::thread_rng().gen::<f32>() < cross_chain_sender_failure_rate
-            {
-                warn!("Dropped 1 cross-message intentionally.");
-                continue;
-            }
-
-            let shard = network.shard(shard_id);
-            let remote_address = format!("{}:{}", shard.host, shard.port);
+        match queue.load().await {
+            Ok(count) => info!(nickname, count, "Loaded pending cross-chain queries"),
+            Err(error) => error!(nickname, %error, "Failed to load pending cross-chain queries"),
+        }
 
-            // Send the cross-chain query and retry if needed.
-            for i in 0..cross_chain_max_retries {
-                // Delay increases linearly with the attempt number.
-                linera_base::time::timer::sleep(
-                    cross_chain_sender_delay + cross_chain_retry_delay * i,
-                )
-                .await;
+        loop {
+            let delivery = tokio::select! { biased;
+                _ = shutdown_signal.cancelled() => return,
+                delivery = queue.next() => delivery,
+            };
+            let network = network.get();
+            let shard_id = network.get_shard_id(delivery.request.target_chain_id());
+            let remote_address = network.shard(shard_id).address();
 
-                let status = pool.send_message_to(message.clone(), &remote_address).await;
-                match status {
-                    Err(error) => {
-                        warn!(
-                            nickname,
-                            %error,
-                            i,
-                            from_shard = this_shard,
-                            to_shard = shard_id,
-                            "Failed to send cross-chain query",
-                        );
-                    }
-                    _ => {
-                        debug!(
-                            from_shard = this_shard,
-                            to_shard = shard_id,
-                            "Sent cross-chain query",
-                        );
-                        break;
+            let status = if cross_chain_sender_failure_rate > 0.0
+                && rand::thread_rng().gen::<f32>() < cross_chain_sender_failure_rate
+            {
+                Err("failed intentionally".to_string())
+            } else if network.is_moving(delivery.request.target_chain_id()) {
+                // No shard handles the target chain until the handoff is over.
+                Err("the target chain is being moved to another shard".to_string())
+            } else {
+                let message = RpcMessage::CrossChainRequest(Box::new(delivery.request.clone()));
+                pool.send_message_to(message, &remote_address)
+                    .await
+                    .map_err(|error| error.to_string())
+            };
+            match status {
+                Err(error) => {
+                    warn!(
+                        nickname,
+                        %error,
+                        attempt = delivery.attempt,
+                        from_shard = this_shard,
+                        to_shard = shard_id,
+                        "Failed to send cross-chain query",
+                    );
+                    queue.failed(&delivery, error);
+                }
+                Ok(()) => {
+                    debug!(
+                        from_shard = this_shard,
+                        to_shard = shard_id,
+                        "Sent cross-chain query",
+                    );
+                    if let Err(error) = queue.delivered(&delivery).await {
+                        error!(nickname, %error, "Failed to remove delivered cross-chain query");
                     }
                 }
-                error!(
-                    nickname,
-                    from_shard = this_shard,
-                    to_shard = shard_id,
-                    "Dropping cross-chain query",
-                );
             }
         }
     }
@@ -146,33 +163,27 @@ where
         shutdown_signal: CancellationToken,
         join_set: &mut JoinSet<()>,
     ) -> ServerHandle {
+        let protocol = self.network.get().protocol;
         info!(
             "Listening to {:?} traffic on {}:{}",
-            self.network.protocol, self.host, self.port
+            protocol, self.host, self.port
         );
         let address = (self.host.clone(), self.port);
 
-        let (cross_chain_sender, cross_chain_receiver) =
-            mpsc::channel(self.cross_chain_config.queue_size);
-
         join_set.spawn_task(Self::forward_cross_chain_queries(
             self.state.nickname().to_string(),
             self.network.clone(),
-            self.cross_chain_config.max_retries,
-            Duration::from_millis(self.cross_chain_config.retry_delay_ms),
-            Duration::from_millis(self.cross_chain_config.sender_delay_ms),
+            QuicConnector::new(self.quic_config.clone()),
             self.cross_chain_config.sender_failure_rate,
             self.shard_id,
-            cross_chain_receiver,
+            self.cross_chain_queue.clone(),
+            shutdown_signal.clone(),
         ));
 
-        let protocol = self.network.protocol;
-        let state = RunningServerState {
-            server: self,
-            cross_chain_sender,
-        };
+        let quic_config = self.quic_config.clone();
+        let state = RunningServerState { server: self };
         // Launch server for the appropriate protocol.
-        protocol.spawn_server(address, state, shutdown_signal, join_set)
+        protocol.spawn_server(address, state, &quic_config, shutdown_signal, join_set)
     }
 }
 
@@ -182,7 +193,6 @@ where
     S: Storage,
 {
     server: Server<S>,
-    cross_chain_sender: mpsc::Sender<(RpcMessage, ShardId)>,
 }
 
 #[async_trait]
@@ -198,13 +208,17 @@ where
             chain_id = ?message.target_chain_id()
         )
     )]
-    async fn handle_message(&mut self, message: RpcMessage) -> Option<RpcMessage> {
+    async fn handle_message(
+        &mut self,
+        message: RpcMessage,
+        _peer: SocketAddr,
+    ) -> Option<RpcMessage> {
         let reply = match message {
             RpcMessage::BlockProposal(message) => {
                 match self.server.state.handle_block_proposal(*message).await {
                     Ok((info, actions)) => {
                         // Cross-shard requests
-                        self.handle_network_actions(actions);
+                        self.handle_network_actions(actions).await;
                         // Response
                         Ok(Some(RpcMessage::ChainInfoResponse(Box::new(info))))
                     }
@@ -227,7 +241,7 @@ where
                 {
                     Ok((info, actions)) => {
                         // Cross-shard requests
-                        self.handle_network_actions(actions);
+                        self.handle_network_actions(actions).await;
                         if let Some(receiver) = receiver {
                             if let Err(e) = receiver.await {
                                 error!("Failed to wait for message delivery: {e}");
@@ -255,7 +269,7 @@ where
                 {
                     Ok((info, actions)) => {
                         // Cross-shard requests
-                        self.handle_network_actions(actions);
+                        self.handle_network_actions(actions).await;
                         // Response
                         Ok(Some(RpcMessage::ChainInfoResponse(Box::new(info))))
                     }
@@ -274,7 +288,7 @@ where
                 {
                     Ok((info, actions)) => {
                         // Cross-shard requests
-                        self.handle_network_actions(actions);
+                        self.handle_network_actions(actions).await;
                         // Response
                         Ok(Some(RpcMessage::ChainInfoResponse(Box::new(info))))
                     }
@@ -300,7 +314,7 @@ where
                 {
                     Ok((info, actions)) => {
                         // Cross-shard requests
-                        self.handle_network_actions(actions);
+                        self.handle_network_actions(actions).await;
                         if let Some(receiver) = receiver {
                             if let Err(e) = receiver.await {
                                 error!("Failed to wait for message delivery: {e}");
@@ -319,7 +333,7 @@ where
                 match self.server.state.handle_chain_info_query(*message).await {
                     Ok((info, actions)) => {
                         // Cross-shard requests
-                        self.handle_network_actions(actions);
+                        self.handle_network_actions(actions).await;
                         // Response
                         Ok(Some(RpcMessage::ChainInfoResponse(Box::new(info))))
                     }
@@ -330,9 +344,23 @@ where
                 }
             }
             RpcMessage::CrossChainRequest(request) => {
-                match self.server.state.handle_cross_chain_request(*request).await {
+                match self
+                    .server
+                    .state
+                    .handle_cross_chain_request(CrossChainRequest::clone(&request))
+                    .await
+                {
                     Ok(actions) => {
-                        self.handle_network_actions(actions);
+                        self.handle_network_actions(actions).await;
+                    }
+                    Err(WorkerError::ChainNotHandled(chain_id)) => {
+                        // The sender doesn't wait for a response: keep the request until
+                        // the shard map says where to deliver it.
+                        let nickname = self.server.state.nickname();
+                        debug!(nickname, %chain_id, "Requeuing cross-chain request for a chain this shard doesn't handle");
+                        if let Err(error) = self.server.cross_chain_queue.push(*request).await {
+                            error!(nickname, %error, "Failed to persist cross-chain request");
+                        }
                     }
                     Err(error) => {
                         let nickname = self.server.state.nickname();
@@ -432,21 +460,18 @@ where
 
 impl<S> RunningServerState<S>
 where
-    S: Storage + Send,
+    S: Storage + Clone + Send + Sync + 'static,
 {
-    fn handle_network_actions(&mut self, actions: NetworkActions) {
+    async fn handle_network_actions(&self, actions: NetworkActions) {
         for request in actions.cross_chain_requests {
-            let shard_id = self.server.network.get_shard_id(request.target_chain_id());
             debug!(
-                "[{}] Scheduling cross-chain query: {} -> {}",
+                "[{}] Scheduling cross-chain query from shard {} to chain {}",
                 self.server.state.nickname(),
                 self.server.shard_id,
-                shard_id
+                request.target_chain_id()
             );
-            let request = RpcMessage::CrossChainRequest(Box::new(request));
-            if let Err(error) = self.cross_chain_sender.try_send((request, shard_id)) {
-                error!(%error, "dropping cross-chain request");
-                break;
+            if let Err(error) = self.server.cross_chain_queue.push(request).await {
+                error!(%error, "failed to persist cross-chain request");
             }
         }
     }
@@ -2,7 +2,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::HashMap, io, mem, net::SocketAddr, pin::pin, sync::Arc};
+use std::{collections::HashMap, fs, io, mem, net::SocketAddr, path::Path, pin::pin, sync::Arc};
 
 use async_trait::async_trait;
 use futures::{
@@ -11,17 +11,27 @@ use futures::{
     Sink, SinkExt, Stream, StreamExt, TryStreamExt,
 };
 use linera_core::{JoinSetExt as _, TaskHandle};
+use quinn::{
+    crypto::rustls::{QuicClientConfig, QuicServerConfig},
+    ConnectionError, Endpoint, Incoming, RecvStream, SendStream,
+};
+use rustls::{crypto::CryptoProvider, pki_types::CertificateDer};
 use serde::{Deserialize, Serialize};
 use tokio::{
-    io::AsyncWriteExt,
+    io::{AsyncWriteExt, Join},
     net::{lookup_host, TcpListener, TcpStream, ToSocketAddrs, UdpSocket},
-    sync::Mutex,
+    sync::{Mutex, OnceCell},
     task::JoinSet,
 };
-use tokio_util::{codec::Framed, sync::CancellationToken, udp::UdpFramed};
+use tokio_util::{
+    codec::{Framed, FramedRead, FramedWrite},
+    sync::CancellationToken,
+    udp::UdpFramed,
+};
 use tracing::{error, warn};
 
 use crate::{
+    config::QuicConfig,
     simple::{codec, codec::Codec},
     RpcMessage,
 };
@@ -32,11 +42,17 @@ pub const DEFAULT_MAX_DATAGRAM_SIZE: &str = "65507";
 /// Number of tasks to spawn before attempting to reap some finished tasks to prevent memory leaks.
 const REAP_TASKS_THRESHOLD: usize = 100;
 
+/// The application protocol negotiated by QUIC connections.
+const QUIC_ALPN: &[u8] = b"linera-simple";
+
 // Supported transport protocols.
 #[derive(clap::ValueEnum, Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TransportProtocol {
     Udp,
     Tcp,
+    /// QUIC, encrypted with TLS 1.3 as configured by a [`QuicConfig`]. Each request is
+    /// sent on its own stream, so that the requests to a peer share a single connection.
+    Quic,
 }
 
 impl std::str::FromStr for TransportProtocol {
@@ -58,6 +74,7 @@ impl TransportProtocol {
         match self {
             TransportProtocol::Udp => "udp",
             TransportProtocol::Tcp => "tcp",
+            TransportProtocol::Quic => "quic",
         }
     }
 }
@@ -78,7 +95,9 @@ pub trait ConnectionPool: Send {
 /// may exist at the same time and handle separate requests concurrently.
 #[async_trait]
 pub trait MessageHandler: Clone {
-    async fn handle_message(&mut self, message: RpcMessage) -> Option<RpcMessage>;
+    /// Handles a message received from `peer`, returning the reply to send back, if any.
+    async fn handle_message(&mut self, message: RpcMessage, peer: SocketAddr)
+        -> Option<RpcMessage>;
 }
 
 /// The result of spawning a server is oneshot channel to track completion, and the set of
@@ -113,12 +132,15 @@ impl<T> Transport for T where
 }
 
 impl TransportProtocol {
-    /// Creates a transport for this protocol.
+    /// Creates a transport for this protocol. QUIC transports are streams of a connection
+    /// of the `quic` connector, which is opened once and shared by its clones.
     pub async fn connect(
         self,
-        address: impl ToSocketAddrs,
+        host: &str,
+        port: u16,
+        quic: &QuicConnector,
     ) -> Result<impl Transport, std::io::Error> {
-        let mut addresses = lookup_host(address)
+        let mut addresses = lookup_host((host, port))
             .await
             .expect("Invalid address to connect to");
         let address = addresses
@@ -137,29 +159,46 @@ impl TransportProtocol {
             TransportProtocol::Tcp => {
                 let stream = TcpStream::connect(address).await?;
 
-                Framed::new(stream, Codec).right_stream()
+                Framed::new(stream, Codec).left_stream().right_stream()
+            }
+            TransportProtocol::Quic => {
+                let connection = quic.connection(host, address).await?;
+                let (send_stream, recv_stream) = connection.open_bi().await.inspect_err(|_| {
+                    quic.forget(host, address);
+                })?;
+
+                Framed::new(tokio::io::join(recv_stream, send_stream), Codec)
+                    .right_stream()
+                    .right_stream()
             }
         };
 
         Ok(stream)
     }
 
-    /// Creates a [`ConnectionPool`] for this protocol.
+    /// Creates a [`ConnectionPool`] for this protocol. QUIC messages are sent with the
+    /// connections of the `quic` connector.
     pub async fn make_outgoing_connection_pool(
         self,
+        quic: &QuicConnector,
     ) -> Result<Box<dyn ConnectionPool>, std::io::Error> {
         let pool: Box<dyn ConnectionPool> = match self {
             Self::Udp => Box::new(UdpConnectionPool::new().await?),
             Self::Tcp => Box::new(TcpConnectionPool::new().await?),
+            Self::Quic => Box::new(QuicConnectionPool {
+                connector: quic.clone(),
+            }),
         };
         Ok(pool)
     }
 
-    /// Runs a server for this protocol and the given message handler.
+    /// Runs a server for this protocol and the given message handler. QUIC servers
+    /// present the certificate of `quic_config`.
     pub fn spawn_server<S>(
         self,
         address: impl ToSocketAddrs + Send + 'static,
         state: S,
+        quic_config: &QuicConfig,
         shutdown_signal: CancellationToken,
         join_set: &mut JoinSet<()>,
     ) -> ServerHandle
@@ -169,6 +208,12 @@ impl TransportProtocol {
         let handle = match self {
             Self::Udp => join_set.spawn_task(UdpServer::run(address, state, shutdown_signal)),
             Self::Tcp => join_set.spawn_task(TcpServer::run(address, state, shutdown_signal)),
+            Self::Quic => join_set.spawn_task(QuicServer::run(
+                address,
+                state,
+                quic_config.clone(),
+                shutdown_signal,
+            )),
         };
         ServerHandle { handle }
     }
@@ -263,7 +308,7 @@ where
         let udp_sink = self.udp_sink.clone();
 
         let new_task = self.join_set.spawn_task(async move {
-            if let Some(reply) = state.handle_message(message).await {
+            if let Some(reply) = state.handle_message(message, peer).await {
                 if let Some(task) = previous_task {
                     if let Err(error) = task.await {
                         warn!("Message handler task panicked: {}", error);
@@ -366,6 +411,7 @@ impl ConnectionPool for TcpConnectionPool {
 /// Server implementation for TCP.
 pub struct TcpServer<State> {
     connection: Framed<TcpStream, Codec>,
+    peer: SocketAddr,
     handler: State,
     shutdown_signal: CancellationToken,
 }
@@ -386,8 +432,8 @@ where
         let listener = TcpListener::bind(address).await?;
 
         let mut accept_stream = stream::try_unfold(listener, |listener| async move {
-            let (socket, _) = listener.accept().await?;
-            Ok::<_, io::Error>(Some((socket, listener)))
+            let (socket, peer) = listener.accept().await?;
+            Ok::<_, io::Error>(Some(((socket, peer), listener)))
         });
         let mut accept_stream = pin!(accept_stream);
 
@@ -402,9 +448,10 @@ where
                     return Ok(());
                 }
                 maybe_socket = accept_stream.next() => match maybe_socket {
-                    Some(Ok(socket)) => {
+                    Some(Ok((socket, peer))) => {
                         let server = TcpServer::new_connection(
                             socket,
+                            peer,
                             handler.clone(),
                             connection_shutdown_signal.clone(),
                         );
@@ -432,11 +479,13 @@ where
     /// [`TcpStream`].
     fn new_connection(
         tcp_stream: TcpStream,
+        peer: SocketAddr,
         handler: State,
         shutdown_signal: CancellationToken,
     ) -> Self {
         TcpServer {
             connection: Framed::new(tcp_stream, Codec),
+            peer,
             handler,
             shutdown_signal,
         }
@@ -471,7 +520,7 @@ where
 
     /// Handles a single request message from a client.
     async fn handle_message(&mut self, message: RpcMessage) {
-        if let Some(reply) = self.handler.handle_message(message).await {
+        if let Some(reply) = self.handler.handle_message(message, self.peer).await {
             if let Err(error) = self.connection.send(reply).await {
                 error!("Failed to send query response: {error}");
             }
@@ -493,3 +542,384 @@ where
         }
     }
 }
+
+/// Returns the configuration of QUIC servers, which need a certificate and its key.
+fn quic_server_config(config: &QuicConfig) -> Result<quinn::ServerConfig, io::Error> {
+    let (Some(certificate_path), Some(private_key_path)) =
+        (&config.certificate_path, &config.private_key_path)
+    else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "QUIC servers need a certificate and a private key",
+        ));
+    };
+    let certificates = read_certificates(certificate_path)?;
+    let key =
+        rustls_pemfile::private_key(&mut io::BufReader::new(fs::File::open(private_key_path)?))?
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Missing private key in {}", private_key_path.display()),
+                )
+            })?;
+    let mut crypto = rustls::ServerConfig::builder_with_provider(quic_crypto_provider())
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .map_err(io::Error::other)?
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+        .map_err(io::Error::other)?;
+    crypto.alpn_protocols = vec![QUIC_ALPN.to_vec()];
+    let crypto = QuicServerConfig::try_from(crypto).map_err(io::Error::other)?;
+    Ok(quinn::ServerConfig::with_crypto(Arc::new(crypto)))
+}
+
+/// Creates an endpoint for outgoing QUIC connections. Servers are authenticated with
+/// the usual root certificates and the trusted roots. A self-signed certificate of
+/// this endpoint is trusted too, so that the proxy and the shards of a validator can
+/// share it.
+fn quic_client_endpoint(config: &QuicConfig) -> Result<Endpoint, io::Error> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Some(path) = &config.trusted_roots_path {
+        for certificate in read_certificates(path)? {
+            roots.add(certificate).map_err(io::Error::other)?;
+        }
+    }
+    if let Some(path) = &config.certificate_path {
+        roots.add_parsable_certificates(read_certificates(path)?);
+    }
+    let mut crypto = rustls::ClientConfig::builder_with_provider(quic_crypto_provider())
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .map_err(io::Error::other)?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    crypto.alpn_protocols = vec![QUIC_ALPN.to_vec()];
+    let crypto = QuicClientConfig::try_from(crypto).map_err(io::Error::other)?;
+    let mut endpoint = Endpoint::client(SocketAddr::from(([0, 0, 0, 0], 0)))?;
+    endpoint.set_default_client_config(quinn::ClientConfig::new(Arc::new(crypto)));
+    Ok(endpoint)
+}
+
+/// Returns the cryptographic primitives used by QUIC connections.
+fn quic_crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+/// Reads the certificates of a PEM file.
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, io::Error> {
+    let certificates = rustls_pemfile::certs(&mut io::BufReader::new(fs::File::open(path)?))
+        .collect::<Result<Vec<_>, _>>()?;
+    if certificates.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Missing certificate in {}", path.display()),
+        ));
+    }
+    Ok(certificates)
+}
+
+/// Opens outgoing QUIC connections from a single long-lived endpoint, and keeps one
+/// connection per peer. The clones of a connector share its endpoint and connections.
+#[derive(Clone)]
+pub struct QuicConnector(Arc<QuicConnectorState>);
+
+struct QuicConnectorState {
+    config: QuicConfig,
+    /// The endpoint, created on the first connection.
+    endpoint: OnceCell<Endpoint>,
+    /// The open connections, by peer address.
+    connections: std::sync::Mutex<HashMap<SocketAddr, quinn::Connection>>,
+}
+
+impl std::fmt::Debug for QuicConnector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("QuicConnector")
+            .field("config", &self.0.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for QuicConnector {
+    fn default() -> Self {
+        Self::new(QuicConfig::default())
+    }
+}
+
+impl QuicConnector {
+    /// Creates a connector authenticating servers as configured by `config`.
+    pub fn new(config: QuicConfig) -> Self {
+        Self(Arc::new(QuicConnectorState {
+            config,
+            endpoint: OnceCell::new(),
+            connections: std::sync::Mutex::default(),
+        }))
+    }
+
+    /// Returns the connection to the server `host` at `address`, opening it if needed.
+    pub async fn connection(
+        &self,
+        host: &str,
+        address: SocketAddr,
+    ) -> Result<quinn::Connection, io::Error> {
+        if let Some(connection) = self.0.connections.lock().unwrap().get(&address) {
+            if connection.close_reason().is_none() {
+                return Ok(connection.clone());
+            }
+        }
+        let endpoint = self
+            .0
+            .endpoint
+            .get_or_try_init(|| async { quic_client_endpoint(&self.0.config) })
+            .await?;
+        let connection = match endpoint.connect(address, host) {
+            Ok(connecting) => connecting.await,
+            Err(error) => return Err(io::Error::other(error)),
+        };
+        let connection = connection.inspect_err(|error| {
+            error!("Failed to open connection to {host} at {address}: {error}");
+        })?;
+        self.0
+            .connections
+            .lock()
+            .unwrap()
+            .insert(address, connection.clone());
+        Ok(connection)
+    }
+
+    /// Closes the connection to `address` after a failure, so that the next request opens
+    /// a new one.
+    fn forget(&self, host: &str, address: SocketAddr) {
+        if let Some(connection) = self.0.connections.lock().unwrap().remove(&address) {
+            warn!("Dropping failed connection to {host} at {address}");
+            connection.close(0u32.into(), b"failed");
+        }
+    }
+}
+
+/// An implementation of [`ConnectionPool`] based on QUIC. Each message is sent on a new
+/// unidirectional stream of the connection to its destination.
+struct QuicConnectionPool {
+    connector: QuicConnector,
+}
+
+impl ConnectionPool for QuicConnectionPool {
+    fn send_message_to<'a>(
+        &'a mut self,
+        message: RpcMessage,
+        address: &'a str,
+    ) -> future::BoxFuture<'a, Result<(), codec::Error>> {
+        Box::pin(async move {
+            let (host, port) = address
+                .rsplit_once(':')
+                .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Invalid address {address}"),
+                    )
+                })?;
+            let socket_address = lookup_host((host, port)).await?.next().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Couldn't resolve address {address}"),
+                )
+            })?;
+            let connection = self.connector.connection(host, socket_address).await?;
+            let result = async {
+                let send_stream = connection.open_uni().await.map_err(io::Error::from)?;
+                let mut stream = FramedWrite::new(send_stream, Codec);
+                stream.send(message).await?;
+                stream.close().await
+            }
+            .await;
+            if result.is_err() {
+                self.connector.forget(host, socket_address);
+            }
+            result
+        })
+    }
+}
+
+/// Server implementation for QUIC.
+pub struct QuicServer<State> {
+    handler: State,
+    shutdown_signal: CancellationToken,
+}
+
+impl<State> QuicServer<State>
+where
+    State: MessageHandler + Send + 'static,
+{
+    /// Runs the QUIC server implementation.
+    ///
+    /// Accepts connections and spawns a task with a new [`QuicServer`] instance to serve each
+    /// of them.
+    pub async fn run(
+        address: impl ToSocketAddrs,
+        handler: State,
+        config: QuicConfig,
+        shutdown_signal: CancellationToken,
+    ) -> Result<(), std::io::Error> {
+        let address = lookup_host(address).await?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Couldn't resolve address to bind")
+        })?;
+        let endpoint = Endpoint::server(quic_server_config(&config)?, address)?;
+
+        let connection_shutdown_signal = shutdown_signal.child_token();
+        let mut join_set = JoinSet::new();
+        let mut reap_countdown = REAP_TASKS_THRESHOLD;
+
+        loop {
+            tokio::select! { biased;
+                _ = shutdown_signal.cancelled() => {
+                    join_set.await_all_tasks().await;
+                    endpoint.wait_idle().await;
+                    return Ok(());
+                }
+                maybe_incoming = endpoint.accept() => match maybe_incoming {
+                    Some(incoming) => {
+                        let server = QuicServer {
+                            handler: handler.clone(),
+                            shutdown_signal: connection_shutdown_signal.clone(),
+                        };
+                        join_set.spawn_task(server.serve(incoming));
+                        reap_countdown -= 1;
+                    }
+                    None => {
+                        join_set.await_all_tasks().await;
+                        return Ok(());
+                    }
+                },
+            }
+
+            if reap_countdown == 0 {
+                join_set.reap_finished_tasks();
+                reap_countdown = REAP_TASKS_THRESHOLD;
+            }
+        }
+    }
+
+    /// Serves a client through a single connection, handling the streams it opens
+    /// concurrently. Responses are sent back on bidirectional streams, and dropped for
+    /// unidirectional ones.
+    async fn serve(self, incoming: Incoming) {
+        let connection = match incoming.await {
+            Ok(connection) => connection,
+            Err(error) => {
+                Self::handle_error(error);
+                return;
+            }
+        };
+        let peer = connection.remote_address();
+        let mut join_set = JoinSet::new();
+        let mut reap_countdown = REAP_TASKS_THRESHOLD;
+
+        loop {
+            tokio::select! { biased;
+                _ = self.shutdown_signal.cancelled() => {
+                    join_set.await_all_tasks().await;
+                    connection.close(0u32.into(), b"shutting down");
+                    return;
+                }
+                result = connection.accept_bi() => match result {
+                    Ok((send_stream, recv_stream)) => {
+                        join_set.spawn_task(Self::serve_stream(
+                            self.handler.clone(),
+                            peer,
+                            send_stream,
+                            recv_stream,
+                            self.shutdown_signal.clone(),
+                        ));
+                    }
+                    Err(error) => {
+                        Self::handle_error(error);
+                        break;
+                    }
+                },
+                result = connection.accept_uni() => match result {
+                    Ok(recv_stream) => {
+                        join_set.spawn_task(Self::serve_one_way_stream(
+                            self.handler.clone(),
+                            peer,
+                            recv_stream,
+                        ));
+                    }
+                    Err(error) => {
+                        Self::handle_error(error);
+                        break;
+                    }
+                },
+            }
+
+            reap_countdown -= 1;
+            if reap_countdown == 0 {
+                join_set.reap_finished_tasks();
+                reap_countdown = REAP_TASKS_THRESHOLD;
+            }
+        }
+
+        join_set.await_all_tasks().await;
+    }
+
+    /// Handles the requests received on a bidirectional stream, replying to each of them.
+    async fn serve_stream(
+        mut handler: State,
+        peer: SocketAddr,
+        send_stream: SendStream,
+        recv_stream: RecvStream,
+        shutdown_signal: CancellationToken,
+    ) {
+        let mut stream: Framed<Join<RecvStream, SendStream>, Codec> =
+            Framed::new(tokio::io::join(recv_stream, send_stream), Codec);
+        loop {
+            tokio::select! { biased;
+                _ = shutdown_signal.cancelled() => break,
+                result = stream.next() => match result {
+                    Some(Ok(message)) => {
+                        if let Some(reply) = handler.handle_message(message, peer).await {
+                            if let Err(error) = stream.send(reply).await {
+                                error!("Failed to send query response: {error}");
+                            }
+                        }
+                    }
+                    Some(Err(error)) => {
+                        warn!("Error while reading QUIC stream: {error}");
+                        break;
+                    }
+                    None => break,
+                },
+            }
+        }
+        if let Err(error) = stream.close().await {
+            warn!("Failed to close QUIC stream: {error}");
+        }
+    }
+
+    /// Handles the requests received on a unidirectional stream.
+    async fn serve_one_way_stream(mut handler: State, peer: SocketAddr, recv_stream: RecvStream) {
+        let mut stream = FramedRead::new(recv_stream, Codec);
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(message) => {
+                    handler.handle_message(message, peer).await;
+                }
+                Err(error) => {
+                    warn!("Error while reading QUIC stream: {error}");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Logs the reason why a connection ended, unless it was closed normally.
+    fn handle_error(error: ConnectionError) {
+        if !matches!(
+            error,
+            ConnectionError::ApplicationClosed(_)
+                | ConnectionError::LocallyClosed
+                | ConnectionError::TimedOut
+        ) {
+            warn!("QUIC connection failed: {error}");
+        }
+    }
+}
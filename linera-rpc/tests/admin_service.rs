@@ -0,0 +1,289 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests of the administration service of the shards, through its gRPC server.
+
+#![cfg(with_server)]
+
+use std::num::NonZeroUsize;
+
+use linera_base::{
+    crypto::KeyPair,
+    data_types::{Amount, Timestamp},
+    identifiers::{ChainDescription, ChainId, Owner},
+    time::Duration,
+};
+use linera_core::{data_types::CrossChainRequest, worker::WorkerState};
+use linera_execution::committee::{Committee, ValidatorName};
+use linera_rpc::{
+    config::CrossChainConfig,
+    cross_chain_queue::CrossChainQueue,
+    grpc::{
+        api::{self, validator_admin_client::ValidatorAdminClient},
+        transport, AdminClient, AdminServer,
+    },
+};
+use linera_storage::{DbStorage, Storage as _, TestClock};
+use linera_views::memory::MemoryStore;
+use tempfile::TempDir;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    Code, Request, Status,
+};
+use tonic_reflection::pb::v1::{
+    server_reflection_client::ServerReflectionClient, server_reflection_request::MessageRequest,
+    server_reflection_response::MessageResponse, ServerReflectionRequest,
+};
+
+type TestStorage = DbStorage<MemoryStore, TestClock>;
+
+const TOKEN: &str = "secret";
+
+struct TestAdmin {
+    address: String,
+    chain_id: ChainId,
+    queue: CrossChainQueue<TestStorage>,
+    shutdown_signal: CancellationToken,
+    join_set: JoinSet<()>,
+}
+
+impl TestAdmin {
+    /// Runs the administration service of a shard with one chain.
+    async fn spawn() -> Self {
+        let key_pair = KeyPair::generate();
+        let storage = TestStorage::make_test_storage(None).await;
+        let description = ChainDescription::Root(0);
+        let chain_id = ChainId::from(description);
+        storage
+            .create_chain(
+                Committee::make_simple(vec![ValidatorName(key_pair.public())]),
+                chain_id,
+                description,
+                Owner::from(key_pair.public()),
+                Amount::ONE,
+                Timestamp::from(0),
+            )
+            .await
+            .unwrap();
+        let state = WorkerState::new(
+            "Admin test shard".to_string(),
+            Some(key_pair),
+            storage.clone(),
+            NonZeroUsize::new(10).unwrap(),
+        );
+        let config = CrossChainConfig {
+            max_retries: 1,
+            retry_delay_ms: 0,
+            max_retry_delay_ms: 0,
+            sender_delay_ms: 0,
+            sender_failure_rate: 0.0,
+            max_concurrent_tasks: 1,
+        };
+        let queue = CrossChainQueue::new(storage.clone(), 0, |_| 0, &config);
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let shutdown_signal = CancellationToken::new();
+        let mut join_set = JoinSet::new();
+        AdminServer::new(storage, vec![(state, queue.clone())]).spawn(
+            "127.0.0.1".to_string(),
+            port,
+            TOKEN.to_string(),
+            shutdown_signal.clone(),
+            &mut join_set,
+        );
+        let admin = TestAdmin {
+            address: format!("http://127.0.0.1:{port}"),
+            chain_id,
+            queue,
+            shutdown_signal,
+            join_set,
+        };
+        admin.wait_until_listening().await;
+        admin
+    }
+
+    async fn wait_until_listening(&self) {
+        let mut client = self.client(TOKEN);
+        for _ in 0..100 {
+            if client.chain_workers().await.is_ok() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("The admin server at {} is not listening", self.address);
+    }
+
+    fn client(&self, token: &str) -> AdminClient {
+        AdminClient::new(self.address.clone(), token).unwrap()
+    }
+
+    fn channel(&self) -> transport::Channel {
+        transport::create_channel(self.address.clone(), &transport::Options::default()).unwrap()
+    }
+
+    /// Lists the services through the reflection service, with the given token if any.
+    async fn list_services(&self, token: Option<&str>) -> Result<Vec<String>, Status> {
+        let value = token.map(bearer);
+        let mut client = ServerReflectionClient::with_interceptor(
+            self.channel(),
+            move |mut request: Request<()>| {
+                if let Some(value) = &value {
+                    request
+                        .metadata_mut()
+                        .insert("authorization", value.clone());
+                }
+                Ok(request)
+            },
+        );
+        let request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(MessageRequest::ListServices(String::new())),
+        };
+        let mut responses = client
+            .server_reflection_info(futures::stream::iter([request]))
+            .await?
+            .into_inner();
+        let response = responses.message().await?.expect("a reflection response");
+        match response.message_response {
+            Some(MessageResponse::ListServicesResponse(services)) => Ok(services
+                .service
+                .into_iter()
+                .map(|service| service.name)
+                .collect()),
+            response => panic!("Unexpected reflection response: {response:?}"),
+        }
+    }
+
+    async fn shut_down(mut self) {
+        self.shutdown_signal.cancel();
+        while self.join_set.join_next().await.is_some() {}
+    }
+}
+
+fn bearer(token: &str) -> MetadataValue<Ascii> {
+    format!("Bearer {token}").parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_requests_need_the_token() {
+    let admin = TestAdmin::spawn().await;
+
+    let status = admin.client("wrong").chain_workers().await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+    let status = admin
+        .client(&format!("{TOKEN} "))
+        .chain_workers()
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let mut client = ValidatorAdminClient::new(admin.channel());
+    let status = client.list_chain_workers(()).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    assert!(admin.client(TOKEN).chain_workers().await.is_ok());
+    admin.shut_down().await;
+}
+
+#[tokio::test]
+async fn test_admin_reflection_needs_the_token() {
+    let admin = TestAdmin::spawn().await;
+
+    for token in [None, Some("wrong")] {
+        let status = admin.list_services(token).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+    }
+    let services = admin.list_services(Some(TOKEN)).await.unwrap();
+    assert!(services.iter().any(|name| name.ends_with("ValidatorAdmin")));
+    admin.shut_down().await;
+}
+
+#[tokio::test]
+async fn test_admin_chain_workers_and_state() {
+    let admin = TestAdmin::spawn().await;
+    let mut client = admin.client(TOKEN);
+    let chain_id = admin.chain_id;
+
+    let state = client.chain_state(chain_id).await.unwrap();
+    assert_eq!(state.shard_id, 0);
+    assert_eq!(state.next_block_height, 0);
+    assert!(state.locked_block.is_none());
+    assert!(state.inboxes.is_empty());
+    assert!(state.outboxes.is_empty());
+    assert_eq!(state.pending_cross_chain_requests, 0);
+
+    // Reading the state of the chain loaded its worker.
+    let workers = client.chain_workers().await.unwrap();
+    assert_eq!(workers.shards.len(), 1);
+    assert_eq!(
+        workers.shards[0].chain_ids,
+        vec![api::ChainId::from(chain_id)]
+    );
+
+    client.evict_chain_worker(chain_id).await.unwrap();
+    let workers = client.chain_workers().await.unwrap();
+    assert!(workers.shards[0].chain_ids.is_empty());
+    admin.shut_down().await;
+}
+
+#[tokio::test]
+async fn test_admin_cross_chain_queue() {
+    let admin = TestAdmin::spawn().await;
+    let mut client = admin.client(TOKEN);
+    let chain_id = admin.chain_id;
+
+    assert!(client.cross_chain_queue().await.unwrap().targets.is_empty());
+    admin
+        .queue
+        .push(CrossChainRequest::ConfirmUpdatedRecipient {
+            sender: chain_id,
+            recipient: ChainId::root(1),
+            latest_heights: Vec::new(),
+        })
+        .await
+        .unwrap();
+    let status = client.cross_chain_queue().await.unwrap();
+    assert_eq!(status.targets.len(), 1);
+    assert_eq!(status.targets[0].source_shard_id, 0);
+    assert_eq!(status.targets[0].target_shard_id, 0);
+    assert_eq!(status.targets[0].num_requests, 1);
+    let state = client.chain_state(chain_id).await.unwrap();
+    assert_eq!(state.pending_cross_chain_requests, 1);
+
+    // The chain has no outgoing messages to send again.
+    assert_eq!(client.resend_outbox_messages(chain_id).await.unwrap(), 0);
+    admin.shut_down().await;
+}
+
+#[tokio::test]
+async fn test_admin_cache_stats() {
+    let admin = TestAdmin::spawn().await;
+    let mut client = admin.client(TOKEN);
+
+    let stats = client.cache_stats().await.unwrap();
+    assert_eq!(stats.shards.len(), 1);
+    assert_eq!(stats.shards[0].shard_id, 0);
+    assert!(stats.shards[0].executed_blocks.is_some());
+    admin.shut_down().await;
+}
+
+#[tokio::test]
+async fn test_admin_snapshot_storage() {
+    let admin = TestAdmin::spawn().await;
+    let mut client = admin.client(TOKEN);
+    let directory = TempDir::new().unwrap();
+    let path = directory.path().join("snapshot");
+
+    client.snapshot_storage(&path).await.unwrap();
+    assert!(path.is_dir());
+    assert!(path.read_dir().unwrap().next().is_some());
+    // The snapshot is not written over an existing one.
+    assert!(client.snapshot_storage(&path).await.is_err());
+    admin.shut_down().await;
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use linera_base::{
+    crypto::{PublicKey, Signature},
     data_types::{BlobContent, OracleResponse, Round},
     hashed::Hashed,
     identifiers::{AccountOwner, BlobType, ChainDescription, Destination, GenericApplicationId},
@@ -60,6 +61,8 @@ fn get_registry() -> Result<Registry> {
     tracer.trace_type::<BlobType>(&samples)?;
     tracer.trace_type::<BlobContent>(&samples)?;
     tracer.trace_type::<AccountOwner>(&samples)?;
+    tracer.trace_type::<PublicKey>(&samples)?;
+    tracer.trace_type::<Signature>(&samples)?;
     tracer.registry()
 }
 
@@ -0,0 +1,224 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests of the QUIC transport of the simple network protocol.
+
+#![cfg(with_simple_network)]
+
+use std::{net::SocketAddr, path::Path};
+
+use async_trait::async_trait;
+use futures::{SinkExt as _, StreamExt as _};
+use linera_base::time::Duration;
+use linera_rpc::{
+    config::QuicConfig,
+    simple::{MessageHandler, QuicConnector, ServerHandle, TransportProtocol},
+    RpcMessage,
+};
+use linera_version::VersionInfo;
+use tempfile::TempDir;
+use tokio::{sync::mpsc, task::JoinSet};
+use tokio_util::sync::CancellationToken;
+
+const HOST: &str = "127.0.0.1";
+
+/// Replies to version queries, and reports every message it receives.
+#[derive(Clone)]
+struct TestHandler {
+    received: mpsc::UnboundedSender<RpcMessage>,
+}
+
+#[async_trait]
+impl MessageHandler for TestHandler {
+    async fn handle_message(
+        &mut self,
+        message: RpcMessage,
+        _peer: SocketAddr,
+    ) -> Option<RpcMessage> {
+        let reply = matches!(message, RpcMessage::VersionInfoQuery)
+            .then(|| RpcMessage::VersionInfoResponse(Box::default()));
+        self.received.send(message).unwrap();
+        reply
+    }
+}
+
+/// Writes a self-signed certificate for the test host and its key to `directory`.
+fn write_certificate(directory: &Path) -> QuicConfig {
+    let certificate =
+        rcgen::generate_simple_self_signed(vec!["localhost".into(), HOST.into()]).unwrap();
+    let certificate_path = directory.join("cert.pem");
+    let private_key_path = directory.join("key.pem");
+    std::fs::write(&certificate_path, certificate.serialize_pem().unwrap()).unwrap();
+    std::fs::write(&private_key_path, certificate.serialize_private_key_pem()).unwrap();
+    QuicConfig {
+        certificate_path: Some(certificate_path),
+        private_key_path: Some(private_key_path),
+        trusted_roots_path: None,
+    }
+}
+
+/// A client configuration trusting the certificate of `server_config`.
+fn trusting(server_config: &QuicConfig) -> QuicConfig {
+    QuicConfig {
+        trusted_roots_path: server_config.certificate_path.clone(),
+        ..QuicConfig::default()
+    }
+}
+
+struct TestServer {
+    port: u16,
+    received: mpsc::UnboundedReceiver<RpcMessage>,
+    shutdown_signal: CancellationToken,
+    join_set: JoinSet<()>,
+}
+
+impl TestServer {
+    /// Runs a QUIC server presenting the certificate of `config`.
+    fn spawn(config: &QuicConfig) -> (Self, ServerHandle) {
+        let port = std::net::UdpSocket::bind((HOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let (sender, received) = mpsc::unbounded_channel();
+        let shutdown_signal = CancellationToken::new();
+        let mut join_set = JoinSet::new();
+        let handle = TransportProtocol::Quic.spawn_server(
+            (HOST, port),
+            TestHandler { received: sender },
+            config,
+            shutdown_signal.clone(),
+            &mut join_set,
+        );
+        let server = TestServer {
+            port,
+            received,
+            shutdown_signal,
+            join_set,
+        };
+        (server, handle)
+    }
+
+    fn address(&self) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], self.port))
+    }
+
+    /// Connects to the server, waiting until it listens. Returns the ID of the connection.
+    async fn connect(&self, connector: &QuicConnector) -> usize {
+        for _ in 0..100 {
+            if let Ok(connection) = connector.connection(HOST, self.address()).await {
+                return connection.stable_id();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Server {} is not listening", self.address());
+    }
+
+    /// Sends a request on a new stream and returns the reply.
+    async fn request(&self, connector: &QuicConnector, message: RpcMessage) -> RpcMessage {
+        let mut stream = TransportProtocol::Quic
+            .connect(HOST, self.port, connector)
+            .await
+            .unwrap();
+        stream.send(message).await.unwrap();
+        stream.next().await.unwrap().unwrap()
+    }
+
+    async fn shut_down(mut self) {
+        self.shutdown_signal.cancel();
+        while self.join_set.join_next().await.is_some() {}
+    }
+}
+
+#[tokio::test]
+async fn test_quic_request_and_reply() {
+    let directory = TempDir::new().unwrap();
+    let config = write_certificate(directory.path());
+    let (mut server, _handle) = TestServer::spawn(&config);
+    let connector = QuicConnector::new(trusting(&config));
+    server.connect(&connector).await;
+
+    let reply = server
+        .request(&connector, RpcMessage::VersionInfoQuery)
+        .await;
+    assert_eq!(
+        VersionInfo::try_from(reply).unwrap(),
+        VersionInfo::default()
+    );
+    assert!(matches!(
+        server.received.recv().await,
+        Some(RpcMessage::VersionInfoQuery)
+    ));
+    server.shut_down().await;
+}
+
+#[tokio::test]
+async fn test_quic_connection_is_reused() {
+    let directory = TempDir::new().unwrap();
+    let config = write_certificate(directory.path());
+    let (server, _handle) = TestServer::spawn(&config);
+    let connector = QuicConnector::new(trusting(&config));
+    let connection_id = server.connect(&connector).await;
+
+    for _ in 0..3 {
+        server
+            .request(&connector, RpcMessage::VersionInfoQuery)
+            .await;
+    }
+    // The clones of the connector share its connections too.
+    assert_eq!(server.connect(&connector.clone()).await, connection_id);
+    server.shut_down().await;
+}
+
+#[tokio::test]
+async fn test_quic_one_way_messages() {
+    let directory = TempDir::new().unwrap();
+    let config = write_certificate(directory.path());
+    let (mut server, _handle) = TestServer::spawn(&config);
+    let connector = QuicConnector::new(trusting(&config));
+    server.connect(&connector).await;
+
+    let mut pool = TransportProtocol::Quic
+        .make_outgoing_connection_pool(&connector)
+        .await
+        .unwrap();
+    let address = format!("{HOST}:{}", server.port);
+    for _ in 0..2 {
+        pool.send_message_to(RpcMessage::GenesisConfigHashQuery, &address)
+            .await
+            .unwrap();
+    }
+    for _ in 0..2 {
+        assert!(matches!(
+            server.received.recv().await,
+            Some(RpcMessage::GenesisConfigHashQuery)
+        ));
+    }
+    server.shut_down().await;
+}
+
+#[tokio::test]
+async fn test_quic_untrusted_certificate_is_rejected() {
+    let directory = TempDir::new().unwrap();
+    let config = write_certificate(directory.path());
+    let (server, _handle) = TestServer::spawn(&config);
+    // Wait until the server listens.
+    server.connect(&QuicConnector::new(trusting(&config))).await;
+
+    let other_directory = TempDir::new().unwrap();
+    let other_config = write_certificate(other_directory.path());
+    for connector in [
+        QuicConnector::default(),
+        QuicConnector::new(trusting(&other_config)),
+    ] {
+        assert!(connector.connection(HOST, server.address()).await.is_err());
+    }
+    server.shut_down().await;
+}
+
+#[tokio::test]
+async fn test_quic_server_needs_a_certificate() {
+    let (server, handle) = TestServer::spawn(&QuicConfig::default());
+    assert!(handle.join().await.is_err());
+    server.shut_down().await;
+}
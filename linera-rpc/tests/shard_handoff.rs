@@ -0,0 +1,212 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests that a chain moved to another shard is never run by two shards at once.
+
+#![cfg(all(with_server, with_simple_network))]
+
+use std::num::NonZeroUsize;
+
+use clap::Parser as _;
+use linera_base::{
+    crypto::KeyPair,
+    data_types::{Amount, Timestamp},
+    identifiers::{ChainDescription, ChainId, Owner},
+    time::Duration,
+};
+use linera_core::{
+    data_types::ChainInfoQuery,
+    node::{NodeError, ValidatorNode as _, ValidatorNodeProvider as _},
+    worker::WorkerState,
+};
+use linera_execution::committee::{Committee, ValidatorName};
+use linera_rpc::{
+    config::{
+        CrossChainConfig, NextShardMap, ShardAssignment, ShardConfig, SharedInternalNetworkConfig,
+        ValidatorInternalNetworkPreConfig,
+    },
+    cross_chain_queue::CrossChainQueue,
+    simple::{Server, SimpleClient, SimpleNodeProvider, TransportProtocol},
+    NodeOptions,
+};
+use linera_storage::{DbStorage, Storage as _, TestClock};
+use linera_views::memory::MemoryStore;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+type TestStorage = DbStorage<MemoryStore, TestClock>;
+
+fn make_shard() -> ShardConfig {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    ShardConfig {
+        host: "127.0.0.1".into(),
+        port,
+        metrics_host: "127.0.0.1".into(),
+        metrics_port: None,
+    }
+}
+
+/// Runs a shard handling the chains that `network` assigns to it. Returns its worker.
+fn spawn_shard(
+    storage: &TestStorage,
+    network: &SharedInternalNetworkConfig<TransportProtocol>,
+    shard: &ShardConfig,
+    key_pair: &KeyPair,
+    shutdown_signal: &CancellationToken,
+    join_set: &mut JoinSet<()>,
+) -> WorkerState<TestStorage> {
+    let handling_network = network.clone();
+    let handled_shard = shard.clone();
+    let state = WorkerState::new(
+        format!("Shard @ {}", shard.address()),
+        Some(key_pair.copy()),
+        storage.clone(),
+        NonZeroUsize::new(10).unwrap(),
+    )
+    .with_handled_chains(move |chain_id| handling_network.get().handles(chain_id, &handled_shard));
+    let queue_network = network.clone();
+    let cross_chain_config = CrossChainConfig::parse_from(["test"]);
+    let cross_chain_queue = CrossChainQueue::new(
+        storage.clone(),
+        0,
+        move |chain_id| queue_network.get().get_shard_id(chain_id),
+        &cross_chain_config,
+    );
+    Server::new(
+        network.clone(),
+        shard.host.clone(),
+        shard.port,
+        state.clone(),
+        0,
+        cross_chain_config,
+        cross_chain_queue,
+    )
+    .spawn(shutdown_signal.clone(), join_set);
+    state
+}
+
+async fn connect(shard: &ShardConfig) -> SimpleClient {
+    let provider = SimpleNodeProvider::new(NodeOptions {
+        send_timeout: Duration::from_secs(1),
+        recv_timeout: Duration::from_secs(1),
+        retry_delay: Duration::from_millis(10),
+        max_retries: 10,
+    });
+    let client = provider
+        .make_node(&format!("tcp:{}", shard.address()))
+        .unwrap();
+    for _ in 0..100 {
+        if client.get_version_info().await.is_ok() {
+            return client;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Shard {} is not listening", shard.address());
+}
+
+/// Returns whether the shard answered a query about the chain.
+async fn handles(client: &SimpleClient, chain_id: ChainId) -> bool {
+    match client
+        .handle_chain_info_query(ChainInfoQuery::new(chain_id))
+        .await
+    {
+        Ok(response) => {
+            assert_eq!(response.info.chain_id, chain_id);
+            true
+        }
+        Err(NodeError::WorkerError { error }) if error.contains("not handled") => false,
+        Err(error) => panic!("Unexpected error: {error}"),
+    }
+}
+
+#[tokio::test]
+async fn test_shard_handoff() {
+    let key_pair = KeyPair::generate();
+    let storage = TestStorage::make_test_storage(None).await;
+    let description = ChainDescription::Root(0);
+    let chain_id = ChainId::from(description);
+    storage
+        .create_chain(
+            Committee::make_simple(vec![ValidatorName(key_pair.public())]),
+            chain_id,
+            description,
+            Owner::from(key_pair.public()),
+            Amount::ONE,
+            Timestamp::from(0),
+        )
+        .await
+        .unwrap();
+
+    let (old_shard, new_shard) = (make_shard(), make_shard());
+    let mut config = ValidatorInternalNetworkPreConfig {
+        name: ValidatorName(key_pair.public()),
+        protocol: TransportProtocol::Tcp,
+        shards: vec![old_shard.clone()],
+        shard_assignment: ShardAssignment::Modulo,
+        shard_map_version: 0,
+        next_shard_map: None,
+        host: "127.0.0.1".into(),
+        port: 0,
+        metrics_host: "127.0.0.1".into(),
+        metrics_port: 0,
+    };
+    let network = SharedInternalNetworkConfig::new(config.clone());
+    let shutdown_signal = CancellationToken::new();
+    let mut join_set = JoinSet::new();
+    let old_state = spawn_shard(
+        &storage,
+        &network,
+        &old_shard,
+        &key_pair,
+        &shutdown_signal,
+        &mut join_set,
+    );
+    spawn_shard(
+        &storage,
+        &network,
+        &new_shard,
+        &key_pair,
+        &shutdown_signal,
+        &mut join_set,
+    );
+    let old_client = connect(&old_shard).await;
+    let new_client = connect(&new_shard).await;
+
+    // Only the shard that owns the chain in the current shard map runs it.
+    assert!(handles(&old_client, chain_id).await);
+    assert!(!handles(&new_client, chain_id).await);
+    assert_eq!(old_state.loaded_chain_ids(), vec![chain_id]);
+
+    // While the chain is handed off, no shard runs it.
+    config.next_shard_map = Some(NextShardMap {
+        shards: vec![new_shard.clone()],
+        shard_assignment: ShardAssignment::Modulo,
+        shard_map_version: 1,
+    });
+    assert!(network.update_shard_map(&config));
+    let undrained = old_state
+        .drain_chain_workers(
+            |chain_id| !network.get().handles(chain_id, &old_shard),
+            Duration::from_secs(10),
+        )
+        .await;
+    assert!(undrained.is_empty());
+    assert!(old_state.loaded_chain_ids().is_empty());
+    assert!(!handles(&old_client, chain_id).await);
+    assert!(!handles(&new_client, chain_id).await);
+
+    // Once the new shard map is used, only the new shard runs the chain.
+    config.shards = vec![new_shard.clone()];
+    config.shard_map_version = 1;
+    config.next_shard_map = None;
+    assert!(network.update_shard_map(&config));
+    assert!(handles(&new_client, chain_id).await);
+    assert!(!handles(&old_client, chain_id).await);
+
+    shutdown_signal.cancel();
+    while join_set.join_next().await.is_some() {}
+}
@@ -57,6 +57,36 @@ pub enum Operation {
         /// Target account to claim the amount into
         target_account: Account,
     },
+    /// Allows `spender` to transfer up to `allowance` tokens from a (locally owned) account.
+    /// This replaces any previous allowance for the same spender. Implementations may set the
+    /// approved tokens aside until they are spent or the allowance is lowered.
+    Approve {
+        /// Owner of the account to approve transfers from
+        owner: AccountOwner,
+        /// Account owner allowed to transfer the tokens
+        spender: AccountOwner,
+        /// Maximum amount the spender may transfer
+        allowance: Amount,
+    },
+    /// Transfers tokens from a (locally owned) account on behalf of its owner, using the
+    /// allowance previously approved for `spender`.
+    TransferFrom {
+        /// Owner of the account to transfer from
+        owner: AccountOwner,
+        /// Account owner spending its allowance
+        spender: AccountOwner,
+        /// Amount to be transferred
+        amount: Amount,
+        /// Target account to transfer the amount to
+        target_account: Account,
+    },
+    /// Requests the amount that `spender` is still allowed to transfer from an account.
+    Allowance {
+        /// Owner of the account
+        owner: AccountOwner,
+        /// Account owner allowed to transfer the tokens
+        spender: AccountOwner,
+    },
 }
 
 /// A fungible response
@@ -69,6 +99,8 @@ pub enum FungibleResponse {
     Balance(Amount),
     /// Ticker symbol response
     TickerSymbol(String),
+    /// Allowance response
+    Allowance(Amount),
 }
 
 /// The initial state to instantiate fungible with
@@ -6,11 +6,17 @@
 use linera_base::{
     crypto::CryptoHash,
     data_types::{Amount, BlockHeight, TimeDelta, Timestamp},
-    identifiers::{ApplicationId, BytecodeId, ChainId, MessageId, Owner},
+    identifiers::{
+        ApplicationId, BytecodeId, ChainId, EventId, GenericApplicationId, MessageId, Owner,
+        StreamId, StreamName,
+    },
     ownership::{ChainOwnership, CloseChainError, TimeoutConfig},
 };
 
-use super::wit::contract_system_api as wit_system_api;
+use super::wit::{
+    contract_system_api as wit_system_api,
+    exports::linera::app::contract_entrypoints as wit_entrypoints,
+};
 
 impl From<wit_system_api::Timestamp> for Timestamp {
     fn from(timestamp: wit_system_api::Timestamp) -> Self {
@@ -135,3 +141,72 @@ impl From<wit_system_api::CloseChainError> for CloseChainError {
         }
     }
 }
+
+impl From<wit_entrypoints::EventId> for EventId {
+    fn from(event_id: wit_entrypoints::EventId) -> Self {
+        EventId {
+            chain_id: event_id.chain_id.into(),
+            stream_id: event_id.stream_id.into(),
+            key: event_id.key,
+        }
+    }
+}
+
+impl From<wit_entrypoints::StreamId> for StreamId {
+    fn from(stream_id: wit_entrypoints::StreamId) -> Self {
+        StreamId {
+            application_id: stream_id.application_id.into(),
+            stream_name: StreamName(stream_id.stream_name.inner0),
+        }
+    }
+}
+
+impl From<wit_entrypoints::GenericApplicationId> for GenericApplicationId {
+    fn from(application_id: wit_entrypoints::GenericApplicationId) -> Self {
+        match application_id {
+            wit_entrypoints::GenericApplicationId::System => GenericApplicationId::System,
+            wit_entrypoints::GenericApplicationId::User(application_id) => {
+                GenericApplicationId::User(application_id.into())
+            }
+        }
+    }
+}
+
+impl From<wit_entrypoints::ApplicationId> for ApplicationId {
+    fn from(application_id: wit_entrypoints::ApplicationId) -> Self {
+        ApplicationId {
+            bytecode_id: BytecodeId::new(
+                application_id.bytecode_id.contract_blob_hash.into(),
+                application_id.bytecode_id.service_blob_hash.into(),
+            ),
+            creation: application_id.creation.into(),
+        }
+    }
+}
+
+impl From<wit_entrypoints::MessageId> for MessageId {
+    fn from(message_id: wit_entrypoints::MessageId) -> Self {
+        MessageId {
+            chain_id: message_id.chain_id.into(),
+            height: BlockHeight(message_id.height.inner0),
+            index: message_id.index,
+        }
+    }
+}
+
+impl From<wit_entrypoints::ChainId> for ChainId {
+    fn from(chain_id: wit_entrypoints::ChainId) -> Self {
+        ChainId(chain_id.inner0.into())
+    }
+}
+
+impl From<wit_entrypoints::CryptoHash> for CryptoHash {
+    fn from(crypto_hash: wit_entrypoints::CryptoHash) -> Self {
+        CryptoHash::from([
+            crypto_hash.part1,
+            crypto_hash.part2,
+            crypto_hash.part3,
+            crypto_hash.part4,
+        ])
+    }
+}
@@ -86,6 +86,54 @@ macro_rules! contract {
                 )
             }
 
+            fn process_event(
+                event_id: $crate::contract::wit::exports::linera::app::contract_entrypoints::EventId,
+                value: Vec<u8>,
+            ) {
+                use $crate::util::BlockingWait;
+                $crate::contract::run_async_entrypoint::<$contract, _, _>(
+                    unsafe { &mut CONTRACT },
+                    move |contract| {
+                        contract
+                            .process_event(event_id.into(), value)
+                            .blocking_wait()
+                    },
+                )
+            }
+
+            fn handle_response(
+                request_id: $crate::contract::wit::exports::linera::app::contract_entrypoints::MessageId,
+                request: Vec<u8>,
+                response: Option<Vec<u8>>,
+            ) {
+                use $crate::util::BlockingWait;
+                $crate::contract::run_async_entrypoint::<$contract, _, _>(
+                    unsafe { &mut CONTRACT },
+                    move |contract| {
+                        let request: <$contract as $crate::Contract>::Message =
+                            $crate::bcs::from_bytes(&request)
+                                .expect("Failed to deserialize request");
+                        let response: Option<<$contract as $crate::Contract>::Message> = response
+                            .map(|response| {
+                                $crate::bcs::from_bytes(&response)
+                                    .expect("Failed to deserialize response")
+                            });
+
+                        contract
+                            .handle_response(request_id.into(), request, response)
+                            .blocking_wait()
+                    },
+                )
+            }
+
+            fn migrate() {
+                use $crate::util::BlockingWait;
+                $crate::contract::run_async_entrypoint::<$contract, _, _>(
+                    unsafe { &mut CONTRACT },
+                    move |contract| contract.migrate().blocking_wait(),
+                )
+            }
+
             fn finalize() {
                 use $crate::util::BlockingWait;
 
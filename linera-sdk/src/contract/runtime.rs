@@ -165,6 +165,23 @@ where
         self.prepare_message(message).send_to(destination)
     }
 
+    /// Schedules a request to be sent to this application on another chain.
+    ///
+    /// The response, or the failure if the request is rejected or times out, is later handed
+    /// over to [`Contract::handle_response`].
+    pub fn send_request(&mut self, destination: ChainId, request: Application::Message) {
+        self.prepare_message(request).send_request_to(destination)
+    }
+
+    /// Responds to the request currently being executed.
+    ///
+    /// This can be called at most once per request, and only by the application that received it.
+    pub fn respond(&mut self, response: Application::Message) {
+        let serialized_response =
+            bcs::to_bytes(&response).expect("Failed to serialize response to be sent");
+        wit::respond(&serialized_response)
+    }
+
     /// Returns a `MessageBuilder` to prepare a message to be sent.
     pub fn prepare_message(
         &mut self,
@@ -273,6 +290,39 @@ where
         wit::emit(&name.into(), key, value);
     }
 
+    /// Subscribes this application to an event stream of another chain.
+    ///
+    /// New events on the stream will be delivered to [`Contract::process_event`].
+    pub fn subscribe_to_events(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+    ) {
+        wit::subscribe_to_events(chain_id.into(), application_id.into(), &name.into());
+    }
+
+    /// Unsubscribes this application from an event stream of another chain.
+    pub fn unsubscribe_from_events(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+    ) {
+        wit::unsubscribe_from_events(chain_id.into(), application_id.into(), &name.into());
+    }
+
+    /// Reads the value of an event with the given key, emitted on another chain's stream.
+    pub fn read_event(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        key: &[u8],
+    ) -> Vec<u8> {
+        wit::read_event(chain_id.into(), application_id.into(), &name.into(), key)
+    }
+
     /// Queries an application service as an oracle and returns the response.
     ///
     /// Should only be used with queries where it is very likely that all validators will compute
@@ -331,6 +381,7 @@ where
     authenticated: bool,
     is_tracked: bool,
     grant: Resources,
+    not_before: Option<Timestamp>,
     message: Message,
 }
 
@@ -344,6 +395,7 @@ where
             authenticated: false,
             is_tracked: false,
             grant: Resources::default(),
+            not_before: None,
             message,
         }
     }
@@ -367,6 +419,15 @@ where
         self
     }
 
+    /// Delays the delivery of the message: the receiver can only receive it in a block whose
+    /// timestamp is at least `timestamp`.
+    ///
+    /// Requests can't be delayed.
+    pub fn with_delay_until(mut self, timestamp: Timestamp) -> Self {
+        self.not_before = Some(timestamp);
+        self
+    }
+
     /// Schedules this `Message` to be sent to the `destination`.
     pub fn send_to(self, destination: impl Into<Destination>) {
         let serialized_message =
@@ -380,6 +441,29 @@ where
             message: serialized_message,
         };
 
-        wit::send_message(&raw_message.into())
+        match self.not_before {
+            None => wit::send_message(&raw_message.into()),
+            Some(not_before) => wit::send_delayed_message(&raw_message.into(), not_before.into()),
+        }
+    }
+
+    /// Schedules this `Message` to be sent to the `destination` as a request, whose response
+    /// is handed over to [`Contract::handle_response`].
+    ///
+    /// Requests are always tracked.
+    pub fn send_request_to(self, destination: ChainId) {
+        assert!(self.not_before.is_none(), "Requests can't be delayed");
+        let serialized_message =
+            bcs::to_bytes(&self.message).expect("Failed to serialize request to be sent");
+
+        let raw_message = SendMessageRequest {
+            destination: Destination::Recipient(destination),
+            authenticated: self.authenticated,
+            is_tracked: true,
+            grant: self.grant,
+            message: serialized_message,
+        };
+
+        wit::send_request(&raw_message.into())
     }
 }
@@ -15,7 +15,7 @@ use linera_base::{
     },
     identifiers::{
         Account, AccountOwner, ApplicationId, BytecodeId, ChainId, ChannelName, Destination,
-        MessageId, Owner, StreamName,
+        EventId, MessageId, Owner, StreamId, StreamName,
     },
     ownership::{ChainOwnership, CloseChainError},
 };
@@ -52,15 +52,22 @@ where
     can_close_chain: Option<bool>,
     call_application_handler: Option<CallApplicationHandler>,
     send_message_requests: Arc<Mutex<Vec<SendMessageRequest<Application::Message>>>>,
+    send_delayed_message_requests:
+        Arc<Mutex<Vec<(SendMessageRequest<Application::Message>, Timestamp)>>>,
+    send_request_requests: Arc<Mutex<Vec<SendMessageRequest<Application::Message>>>>,
+    responses: Vec<Application::Message>,
     subscribe_requests: Vec<(ChainId, ChannelName)>,
     unsubscribe_requests: Vec<(ChainId, ChannelName)>,
     outgoing_transfers: HashMap<Account, Amount>,
     events: Vec<(StreamName, Vec<u8>, Vec<u8>)>,
+    event_subscribe_requests: Vec<(ChainId, ApplicationId, StreamName)>,
+    event_unsubscribe_requests: Vec<(ChainId, ApplicationId, StreamName)>,
     claim_requests: Vec<ClaimRequest>,
     expected_service_queries: VecDeque<(ApplicationId, String, String)>,
     expected_post_requests: VecDeque<(String, Vec<u8>, Vec<u8>)>,
     expected_read_data_blob_requests: VecDeque<(DataBlobHash, Vec<u8>)>,
     expected_assert_data_blob_exists_requests: VecDeque<(DataBlobHash, Option<()>)>,
+    expected_read_event_requests: VecDeque<(EventId, Vec<u8>)>,
     expected_open_chain_calls:
         VecDeque<(ChainOwnership, ApplicationPermissions, Amount, MessageId)>,
     expected_create_application_calls: VecDeque<ExpectedCreateApplicationCall>,
@@ -99,15 +106,21 @@ where
             can_close_chain: None,
             call_application_handler: None,
             send_message_requests: Arc::default(),
+            send_delayed_message_requests: Arc::default(),
+            send_request_requests: Arc::default(),
+            responses: Vec::new(),
             subscribe_requests: Vec::new(),
             unsubscribe_requests: Vec::new(),
             outgoing_transfers: HashMap::new(),
             events: Vec::new(),
+            event_subscribe_requests: Vec::new(),
+            event_unsubscribe_requests: Vec::new(),
             claim_requests: Vec::new(),
             expected_service_queries: VecDeque::new(),
             expected_post_requests: VecDeque::new(),
             expected_read_data_blob_requests: VecDeque::new(),
             expected_assert_data_blob_exists_requests: VecDeque::new(),
+            expected_read_event_requests: VecDeque::new(),
             expected_open_chain_calls: VecDeque::new(),
             expected_create_application_calls: VecDeque::new(),
             key_value_store: KeyValueStore::mock().to_mut(),
@@ -448,7 +461,12 @@ where
         &mut self,
         message: Application::Message,
     ) -> MessageBuilder<Application::Message> {
-        MessageBuilder::new(message, self.send_message_requests.clone())
+        MessageBuilder::new(
+            message,
+            self.send_message_requests.clone(),
+            self.send_delayed_message_requests.clone(),
+            self.send_request_requests.clone(),
+        )
     }
 
     /// Returns the list of [`SendMessageRequest`]s created so far during the test.
@@ -460,6 +478,40 @@ where
             .expect("Unit test should be single-threaded")
     }
 
+    /// Returns the list of delayed [`SendMessageRequest`]s created so far during the test,
+    /// with the earliest time at which they can be received.
+    pub fn created_send_delayed_message_requests(
+        &self,
+    ) -> MutexGuard<'_, Vec<(SendMessageRequest<Application::Message>, Timestamp)>> {
+        self.send_delayed_message_requests
+            .try_lock()
+            .expect("Unit test should be single-threaded")
+    }
+
+    /// Schedules a request to be sent to this application on another chain.
+    pub fn send_request(&mut self, destination: ChainId, request: Application::Message) {
+        self.prepare_message(request).send_request_to(destination)
+    }
+
+    /// Returns the list of requests created so far during the test.
+    pub fn created_send_request_requests(
+        &self,
+    ) -> MutexGuard<'_, Vec<SendMessageRequest<Application::Message>>> {
+        self.send_request_requests
+            .try_lock()
+            .expect("Unit test should be single-threaded")
+    }
+
+    /// Responds to the request currently being executed.
+    pub fn respond(&mut self, response: Application::Message) {
+        self.responses.push(response);
+    }
+
+    /// Returns the list of responses sent so far during the test.
+    pub fn responses(&self) -> &[Application::Message] {
+        &self.responses
+    }
+
     /// Subscribes to a message channel from another chain.
     pub fn subscribe(&mut self, chain: ChainId, channel: ChannelName) {
         self.subscribe_requests.push((chain, channel));
@@ -727,6 +779,66 @@ where
         self.events.push((name, key.to_vec(), value.to_vec()));
     }
 
+    /// Subscribes this application to an event stream of another chain.
+    pub fn subscribe_to_events(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+    ) {
+        self.event_subscribe_requests
+            .push((chain_id, application_id, name));
+    }
+
+    /// Returns the list of requests to subscribe to event streams made in the test so far.
+    pub fn event_subscribe_requests(&self) -> &[(ChainId, ApplicationId, StreamName)] {
+        &self.event_subscribe_requests
+    }
+
+    /// Unsubscribes this application from an event stream of another chain.
+    pub fn unsubscribe_from_events(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+    ) {
+        self.event_unsubscribe_requests
+            .push((chain_id, application_id, name));
+    }
+
+    /// Returns the list of requests to unsubscribe from event streams made in the test so far.
+    pub fn event_unsubscribe_requests(&self) -> &[(ChainId, ApplicationId, StreamName)] {
+        &self.event_unsubscribe_requests
+    }
+
+    /// Adds an expected `read_event` call, and the value it should return in the test.
+    pub fn add_expected_read_event_request(&mut self, event_id: EventId, value: Vec<u8>) {
+        self.expected_read_event_requests
+            .push_back((event_id, value));
+    }
+
+    /// Reads the value of an event with the given key, emitted on another chain's stream.
+    pub fn read_event(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        key: &[u8],
+    ) -> Vec<u8> {
+        let maybe_request = self.expected_read_event_requests.pop_front();
+        let (expected_event_id, value) = maybe_request.expect("Unexpected read_event request");
+        let event_id = EventId {
+            chain_id,
+            stream_id: StreamId {
+                application_id: application_id.into(),
+                stream_name: name,
+            },
+            key: key.to_vec(),
+        };
+        assert_eq!(event_id, expected_event_id);
+        value
+    }
+
     /// Adds an expected `query_service` call`, and the response it should return in the test.
     pub fn add_expected_service_query<A: ServiceAbi + Send>(
         &mut self,
@@ -839,8 +951,11 @@ where
     authenticated: bool,
     is_tracked: bool,
     grant: Resources,
+    not_before: Option<Timestamp>,
     message: Message,
     send_message_requests: Arc<Mutex<Vec<SendMessageRequest<Message>>>>,
+    send_delayed_message_requests: Arc<Mutex<Vec<(SendMessageRequest<Message>, Timestamp)>>>,
+    send_request_requests: Arc<Mutex<Vec<SendMessageRequest<Message>>>>,
 }
 
 impl<Message> MessageBuilder<Message>
@@ -851,13 +966,18 @@ where
     pub(crate) fn new(
         message: Message,
         send_message_requests: Arc<Mutex<Vec<SendMessageRequest<Message>>>>,
+        send_delayed_message_requests: Arc<Mutex<Vec<(SendMessageRequest<Message>, Timestamp)>>>,
+        send_request_requests: Arc<Mutex<Vec<SendMessageRequest<Message>>>>,
     ) -> Self {
         MessageBuilder {
             authenticated: false,
             is_tracked: false,
             grant: Resources::default(),
+            not_before: None,
             message,
             send_message_requests,
+            send_delayed_message_requests,
+            send_request_requests,
         }
     }
 
@@ -880,6 +1000,15 @@ where
         self
     }
 
+    /// Delays the delivery of the message: the receiver can only receive it in a block whose
+    /// timestamp is at least `timestamp`.
+    ///
+    /// Requests can't be delayed.
+    pub fn with_delay_until(mut self, timestamp: Timestamp) -> Self {
+        self.not_before = Some(timestamp);
+        self
+    }
+
     /// Schedules this `Message` to be sent to the `destination`.
     pub fn send_to(self, destination: impl Into<Destination>) {
         let request = SendMessageRequest {
@@ -890,7 +1019,33 @@ where
             message: self.message,
         };
 
-        self.send_message_requests
+        match self.not_before {
+            None => self
+                .send_message_requests
+                .try_lock()
+                .expect("Unit test should be single-threaded")
+                .push(request),
+            Some(not_before) => self
+                .send_delayed_message_requests
+                .try_lock()
+                .expect("Unit test should be single-threaded")
+                .push((request, not_before)),
+        }
+    }
+
+    /// Schedules this `Message` to be sent to the `destination` as a request.
+    ///
+    /// Requests are always tracked.
+    pub fn send_request_to(self, destination: ChainId) {
+        let request = SendMessageRequest {
+            destination: Destination::Recipient(destination),
+            authenticated: self.authenticated,
+            is_tracked: true,
+            grant: self.grant,
+            message: self.message,
+        };
+
+        self.send_request_requests
             .try_lock()
             .expect("Unit test should be single-threaded")
             .push(request);
@@ -54,6 +54,7 @@ use linera_base::{
     abi::{ContractAbi, ServiceAbi, WithContractAbi, WithServiceAbi},
     crypto::CryptoHash,
     doc_scalar,
+    identifiers::{EventId, MessageId},
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 pub use serde_json;
@@ -128,6 +129,38 @@ pub trait Contract: WithContractAbi + ContractAbi + Sized {
     /// chain.
     async fn execute_message(&mut self, message: Self::Message);
 
+    /// Handles a new event on a stream of another chain.
+    ///
+    /// This is only called for streams the application subscribed to using
+    /// [`ContractRuntime::subscribe_to_events`]. The default implementation ignores the
+    /// event, so applications that never subscribe to event streams don't need to implement
+    /// it.
+    async fn process_event(&mut self, _event_id: EventId, _value: Vec<u8>) {}
+
+    /// Handles the response to a request sent with [`ContractRuntime::send_request`].
+    ///
+    /// The `response` is `None` if the receiving chain rejected the request, or didn't respond
+    /// within a day, in which case the application should roll back whatever it did when
+    /// sending it. After a day, the receiving chain doesn't execute the request anymore, but a
+    /// response that it sent before and that arrives too late is ignored. The original
+    /// `request` is handed back so that the application can tell which request is being
+    /// answered.
+    async fn handle_response(
+        &mut self,
+        request_id: MessageId,
+        _request: Self::Message,
+        _response: Option<Self::Message>,
+    ) {
+        panic!("Unexpected response to {request_id:?}: `handle_response` is not implemented");
+    }
+
+    /// Migrates the application's state after an upgrade to a new bytecode.
+    ///
+    /// This is called once on each chain, before the new bytecode handles anything else
+    /// there. The default implementation does nothing, which is enough if the state layout
+    /// didn't change.
+    async fn migrate(&mut self) {}
+
     /// Finishes the execution of the current transaction.
     ///
     /// This is called once at the end of the transaction, to allow all applications that
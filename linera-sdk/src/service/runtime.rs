@@ -8,7 +8,7 @@ use std::cell::Cell;
 use linera_base::{
     abi::ServiceAbi,
     data_types::{Amount, BlockHeight, Timestamp},
-    identifiers::{AccountOwner, ApplicationId, ChainId},
+    identifiers::{AccountOwner, ApplicationId, ChainId, StreamName},
 };
 
 use super::wit::service_system_api as wit;
@@ -159,4 +159,15 @@ where
     pub fn assert_data_blob_exists(&mut self, hash: DataBlobHash) {
         wit::assert_data_blob_exists(hash.0.into())
     }
+
+    /// Reads the value of an event with the given key, emitted on a chain's stream.
+    pub fn read_event(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        key: &[u8],
+    ) -> Vec<u8> {
+        wit::read_event(chain_id.into(), application_id.into(), &name.into(), key)
+    }
 }
@@ -11,7 +11,7 @@ use std::{
 use linera_base::{
     abi::ServiceAbi,
     data_types::{Amount, BlockHeight, Timestamp},
-    identifiers::{AccountOwner, ApplicationId, ChainId},
+    identifiers::{AccountOwner, ApplicationId, ChainId, EventId, StreamId, StreamName},
 };
 
 use crate::{DataBlobHash, KeyValueStore, Service, ViewStorageContext};
@@ -31,6 +31,7 @@ where
     query_application_handler: RefCell<Option<QueryApplicationHandler>>,
     url_blobs: RefCell<Option<HashMap<String, Vec<u8>>>>,
     blobs: RefCell<Option<HashMap<DataBlobHash, Vec<u8>>>>,
+    events: RefCell<Option<HashMap<EventId, Vec<u8>>>>,
     key_value_store: KeyValueStore,
 }
 
@@ -60,6 +61,7 @@ where
             query_application_handler: RefCell::new(None),
             url_blobs: RefCell::new(None),
             blobs: RefCell::new(None),
+            events: RefCell::new(None),
             key_value_store: KeyValueStore::mock(),
         }
     }
@@ -419,6 +421,49 @@ where
             });
     }
 
+    /// Configures the `value` returned when reading the event with the given ID during the test.
+    pub fn with_event(self, event_id: EventId, value: Vec<u8>) -> Self {
+        self.set_event(event_id, value);
+        self
+    }
+
+    /// Configures the `value` returned when reading the event with the given ID during the test.
+    pub fn set_event(&self, event_id: EventId, value: Vec<u8>) -> &Self {
+        self.events
+            .borrow_mut()
+            .get_or_insert_with(HashMap::new)
+            .insert(event_id, value);
+        self
+    }
+
+    /// Reads the value of an event with the given key, emitted on a chain's stream.
+    pub fn read_event(
+        &mut self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        name: StreamName,
+        key: &[u8],
+    ) -> Vec<u8> {
+        let event_id = EventId {
+            chain_id,
+            stream_id: StreamId {
+                application_id: application_id.into(),
+                stream_name: name,
+            },
+            key: key.to_vec(),
+        };
+        self.events
+            .borrow()
+            .as_ref()
+            .and_then(|events| events.get(&event_id).cloned())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Event {event_id:?} has not been mocked, \
+                    please call `MockServiceRuntime::set_event` first"
+                )
+            })
+    }
+
     /// Loads a mocked value from the `cell` cache or panics with a provided `message`.
     fn fetch_mocked_value<T>(cell: &Cell<Option<T>>, message: &str) -> T
     where
@@ -372,6 +372,7 @@ impl ActiveChain {
                     parameters,
                     instantiation_argument,
                     required_application_ids,
+                    admin: None,
                 });
             })
             .await;
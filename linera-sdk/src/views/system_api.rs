@@ -9,7 +9,7 @@ use std::sync::Arc;
 use linera_base::ensure;
 use linera_views::{
     batch::Batch,
-    store::{ReadableKeyValueStore, WithError, WritableKeyValueStore},
+    store::{KeyRange, ReadableKeyValueStore, WithError, WritableKeyValueStore},
 };
 use thiserror::Error;
 
@@ -182,6 +182,34 @@ impl ReadableKeyValueStore for KeyValueStore {
         yield_once().await;
         Ok(self.wit_api.find_key_values_wait(promise))
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, KeyValueStoreError> {
+        ensure!(
+            key_prefix.len() <= Self::MAX_KEY_SIZE,
+            KeyValueStoreError::KeyTooLong
+        );
+        let promise = self.wit_api.find_keys_in_range_new(key_prefix, range);
+        yield_once().await;
+        Ok(self.wit_api.find_keys_wait(promise))
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, KeyValueStoreError> {
+        ensure!(
+            key_prefix.len() <= Self::MAX_KEY_SIZE,
+            KeyValueStoreError::KeyTooLong
+        );
+        let promise = self.wit_api.find_key_values_in_range_new(key_prefix, range);
+        yield_once().await;
+        Ok(self.wit_api.find_key_values_wait(promise))
+    }
 }
 
 impl WritableKeyValueStore for KeyValueStore {
@@ -304,6 +332,33 @@ impl WitInterface {
         }
     }
 
+    /// Creates a promise for finding the keys of a range after a specified prefix in the
+    /// key-value store
+    fn find_keys_in_range_new(&self, key_prefix: &[u8], range: &KeyRange) -> u32 {
+        let end = range.end.as_deref();
+        let limit = range
+            .limit
+            .map(|limit| u32::try_from(limit).unwrap_or(u32::MAX));
+        match self {
+            WitInterface::Contract => contract_wit::find_keys_in_range_new(
+                key_prefix,
+                &range.start,
+                end,
+                range.reverse,
+                limit,
+            ),
+            WitInterface::Service => service_wit::find_keys_in_range_new(
+                key_prefix,
+                &range.start,
+                end,
+                range.reverse,
+                limit,
+            ),
+            #[cfg(with_testing)]
+            WitInterface::Mock { store, .. } => store.find_keys_in_range_new(key_prefix, range),
+        }
+    }
+
     /// Resolves a promise for finding keys having a specified prefix in the key-value store
     fn find_keys_wait(&self, promise: u32) -> Vec<Vec<u8>> {
         match self {
@@ -324,6 +379,35 @@ impl WitInterface {
         }
     }
 
+    /// Creates a promise for finding the key/values of a range after a specified prefix in
+    /// the key-value store
+    fn find_key_values_in_range_new(&self, key_prefix: &[u8], range: &KeyRange) -> u32 {
+        let end = range.end.as_deref();
+        let limit = range
+            .limit
+            .map(|limit| u32::try_from(limit).unwrap_or(u32::MAX));
+        match self {
+            WitInterface::Contract => contract_wit::find_key_values_in_range_new(
+                key_prefix,
+                &range.start,
+                end,
+                range.reverse,
+                limit,
+            ),
+            WitInterface::Service => service_wit::find_key_values_in_range_new(
+                key_prefix,
+                &range.start,
+                end,
+                range.reverse,
+                limit,
+            ),
+            #[cfg(with_testing)]
+            WitInterface::Mock { store, .. } => {
+                store.find_key_values_in_range_new(key_prefix, range)
+            }
+        }
+    }
+
     /// Resolves a promise for finding the key/values having a specified prefix in the key-value store
     fn find_key_values_wait(&self, promise: u32) -> Vec<(Vec<u8>, Vec<u8>)> {
         match self {
@@ -404,6 +488,15 @@ mod tests {
         let value = mock_store.read_value(b"bar").await?;
         assert_eq!(value, Some(42_u128));
 
+        // Find keys in a range
+        let range = KeyRange::new(b"b".to_vec(), Some(b"g".to_vec())).reversed();
+        let keys = mock_store.find_keys_in_range(&[], &range).await?;
+        assert_eq!(keys, vec![b"foo".to_vec(), b"bar".to_vec()]);
+
+        let range = KeyRange::new(b"c".to_vec(), None);
+        let key_values = mock_store.find_key_values_in_range(b"f", &range).await?;
+        assert_eq!(key_values, vec![(b"oo".to_vec(), bcs::to_bytes(&32_u128)?)]);
+
         Ok(())
     }
 }
@@ -9,6 +9,7 @@ use linera_base::{
         Account, BlobId, ChainDescription, ChainId, ChannelName, Destination, GenericApplicationId,
         Owner, StreamName,
     },
+    state_proof::StateProof,
 };
 
 pub type JSONObject = serde_json::Value;
@@ -112,6 +113,14 @@ pub struct Blocks;
 )]
 pub struct Block;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/service_schema.graphql",
+    query_path = "gql/service_requests.graphql",
+    response_derives = "Debug, Serialize, Clone, PartialEq"
+)]
+pub struct ApplicationStateProof;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "gql/service_schema.graphql",
@@ -183,6 +192,7 @@ mod from {
                 authenticated_signer,
                 grant,
                 refund_grant_to,
+                not_before,
                 kind,
                 index,
                 message,
@@ -191,6 +201,7 @@ mod from {
                 authenticated_signer,
                 grant,
                 refund_grant_to,
+                not_before,
                 kind,
                 index: index as u32,
                 message,
@@ -205,6 +216,7 @@ mod from {
                 authenticated_signer,
                 grant,
                 refund_grant_to,
+                not_before,
                 kind,
                 message,
             } = val;
@@ -213,6 +225,7 @@ mod from {
                 authenticated_signer,
                 grant,
                 refund_grant_to,
+                not_before,
                 kind,
                 message,
             }
@@ -40,6 +40,13 @@ use crate::{
     util::ChildExt,
 };
 
+/// The file of the certificate of the QUIC endpoints of the local validators, which the
+/// clients trust.
+pub const QUIC_CERTIFICATE_FILE: &str = "quic_cert.pem";
+
+/// The file of the private key of the QUIC endpoints of the local validators.
+const QUIC_PRIVATE_KEY_FILE: &str = "quic_key.pem";
+
 pub enum ProcessInbox {
     Skip,
     Automatic,
@@ -86,7 +93,7 @@ async fn make_testing_config(database: Database) -> Result<StorageConfig> {
             {
                 let config = ScyllaDbStore::new_test_config().await?;
                 Ok(StorageConfig::ScyllaDb {
-                    uri: config.inner_config.uri,
+                    uri: config.inner_config.inner_config.inner_config.uri,
                 })
             }
             #[cfg(not(feature = "scylladb"))]
@@ -437,6 +444,17 @@ impl LocalNet {
                 "#
             ));
         }
+        if self.network.uses_quic() {
+            self.write_quic_certificate()?;
+            content.push_str(&format!(
+                r#"
+
+                [quic]
+                certificate_path = "{QUIC_CERTIFICATE_FILE}"
+                private_key_path = "{QUIC_PRIVATE_KEY_FILE}"
+                "#
+            ));
+        }
         fs_err::write(&path, content)?;
         path.into_os_string().into_string().map_err(|error| {
             anyhow!(
@@ -446,6 +464,23 @@ impl LocalNet {
         })
     }
 
+    /// Writes the self-signed certificate of the QUIC endpoints of the local validators,
+    /// unless it exists already.
+    fn write_quic_certificate(&self) -> Result<()> {
+        let certificate_path = self.path_provider.path().join(QUIC_CERTIFICATE_FILE);
+        if certificate_path.exists() {
+            return Ok(());
+        }
+        let certificate =
+            rcgen::generate_simple_self_signed(vec!["localhost".into(), "127.0.0.1".into()])?;
+        fs_err::write(
+            self.path_provider.path().join(QUIC_PRIVATE_KEY_FILE),
+            certificate.serialize_private_key_pem(),
+        )?;
+        fs_err::write(certificate_path, certificate.serialize_pem()?)?;
+        Ok(())
+    }
+
     async fn generate_initial_validator_config(&mut self) -> Result<()> {
         let mut command = self.command_for_binary("linera-server").await?;
         command.arg("generate");
@@ -490,7 +525,7 @@ impl LocalNet {
                 let nickname = format!("validator proxy {validator}");
                 Self::ensure_grpc_server_has_started(&nickname, port, "https").await?;
             }
-            Network::Tcp | Network::Udp => {
+            Network::Tcp | Network::Udp | Network::Quic => {
                 info!("Letting validator proxy {validator} start");
                 linera_base::time::timer::sleep(Duration::from_secs(2)).await;
             }
@@ -537,6 +572,7 @@ impl LocalNet {
         let storage = StorageConfigNamespace {
             storage_config: self.storage_config.clone(),
             namespace,
+            compression_threshold: None,
         }
         .to_string();
 
@@ -602,7 +638,7 @@ impl LocalNet {
                 let nickname = format!("validator server {validator}:{shard}");
                 Self::ensure_grpc_server_has_started(&nickname, port, "https").await?;
             }
-            Network::Tcp | Network::Udp => {
+            Network::Tcp | Network::Udp | Network::Quic => {
                 info!("Letting validator server {validator}:{shard} start");
                 linera_base::time::timer::sleep(Duration::from_secs(2)).await;
             }
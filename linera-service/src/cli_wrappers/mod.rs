@@ -66,6 +66,7 @@ pub enum Network {
     Grpcs,
     Tcp,
     Udp,
+    Quic,
 }
 
 /// Network protocol in use outside and inside a Linera net.
@@ -77,6 +78,13 @@ pub struct NetworkConfig {
     pub external: Network,
 }
 
+impl NetworkConfig {
+    /// Returns whether the proxies or the shards communicate over QUIC.
+    pub fn uses_quic(&self) -> bool {
+        self.internal.is_quic() || self.external.is_quic()
+    }
+}
+
 impl Network {
     fn toml(&self) -> &'static str {
         match self {
@@ -84,6 +92,7 @@ impl Network {
             Network::Grpcs => "{ Grpc = \"Tls\" }",
             Network::Tcp => "{ Simple = \"Tcp\" }",
             Network::Udp => "{ Simple = \"Udp\" }",
+            Network::Quic => "{ Simple = \"Quic\" }",
         }
     }
 
@@ -93,6 +102,7 @@ impl Network {
             Network::Grpcs => "grpcs",
             Network::Tcp => "tcp",
             Network::Udp => "udp",
+            Network::Quic => "quic",
         }
     }
 
@@ -102,13 +112,18 @@ impl Network {
             Network::Grpcs => Network::Grpc,
             Network::Tcp => Network::Tcp,
             Network::Udp => Network::Udp,
+            Network::Quic => Network::Quic,
         }
     }
 
+    pub fn is_quic(&self) -> bool {
+        matches!(self, Network::Quic)
+    }
+
     pub fn localhost(&self) -> &'static str {
         match self {
             Network::Grpc | Network::Grpcs => "localhost",
-            Network::Tcp | Network::Udp => "127.0.0.1",
+            Network::Tcp | Network::Udp | Network::Quic => "127.0.0.1",
         }
     }
 }
@@ -36,7 +36,7 @@ use tracing::{error, info, warn};
 
 use crate::{
     cli_wrappers::{
-        local_net::{PathProvider, ProcessInbox},
+        local_net::{PathProvider, ProcessInbox, QUIC_CERTIFICATE_FILE},
         Network,
     },
     faucet::ClaimOutcome,
@@ -184,6 +184,19 @@ impl ClientWrapper {
             "--wait-for-outgoing-messages".into(),
         ]
         .into_iter()
+        .chain(
+            self.network
+                .is_quic()
+                .then(|| {
+                    let roots = self.path_provider.path().join(QUIC_CERTIFICATE_FILE);
+                    [
+                        "--quic-trusted-roots".into(),
+                        roots.to_string_lossy().into_owned().into(),
+                    ]
+                })
+                .into_iter()
+                .flatten(),
+        )
     }
 
     /// Returns the [`Command`] instance configured to run the appropriate binary.
@@ -253,6 +266,9 @@ impl ClientWrapper {
             maximum_block_proposal_size,
             maximum_bytes_read_per_block,
             maximum_bytes_written_per_block,
+            maximum_operations_per_block,
+            maximum_incoming_bundles_per_block,
+            maximum_operation_argument_bytes,
         } = policy;
         let mut command = self.command().await?;
         command
@@ -298,6 +314,18 @@ impl ClientWrapper {
             .args([
                 "--maximum-bytes-written-per-block",
                 &maximum_bytes_written_per_block.to_string(),
+            ])
+            .args([
+                "--maximum-operations-per-block",
+                &maximum_operations_per_block.to_string(),
+            ])
+            .args([
+                "--maximum-incoming-bundles-per-block",
+                &maximum_incoming_bundles_per_block.to_string(),
+            ])
+            .args([
+                "--maximum-operation-argument-bytes",
+                &maximum_operation_argument_bytes.to_string(),
             ]);
         if let Some(seed) = self.testing_prng_seed {
             command.arg("--testing-prng-seed").arg(seed.to_string());
@@ -16,7 +16,7 @@ use chrono::Utc;
 use colored::Colorize;
 use futures::{lock::Mutex, FutureExt as _, StreamExt};
 use linera_base::{
-    crypto::{CryptoHash, CryptoRng},
+    crypto::{CryptoHash, CryptoRng, KeyPair},
     data_types::{ApplicationPermissions, Timestamp},
     identifiers::{AccountOwner, ChainDescription, ChainId, MessageId, Owner},
     ownership::ChainOwnership,
@@ -43,7 +43,7 @@ use linera_core::{
 };
 use linera_execution::{
     committee::{Committee, ValidatorName, ValidatorState},
-    Message, ResourceControlPolicy, SystemMessage,
+    Message, Operation, ResourceControlPolicy, SystemMessage,
 };
 use linera_service::{
     cli_wrappers,
@@ -52,7 +52,7 @@ use linera_service::{
     project::{self, Project},
     util, wallet,
 };
-use linera_storage::Storage;
+use linera_storage::{Storage, SCHEMA_VERSION};
 use linera_views::store::CommonStoreConfig;
 use serde_json::Value;
 use tokio::task::JoinSet;
@@ -120,8 +120,28 @@ impl Runnable for Job {
                 sender,
                 recipient,
                 amount,
+                multi_sig_config,
             } => {
                 let chain_client = context.make_chain_client(sender.chain_id)?;
+                if let Some(multi_sig_owner) = multi_sig_config.into_multi_sig_owner()? {
+                    let key_pairs = multi_sig_owner
+                        .public_keys
+                        .iter()
+                        .filter_map(|public_key| {
+                            context
+                                .wallet()
+                                .key_pair_for_owner(&Owner::from(public_key))
+                        })
+                        .collect::<Vec<_>>();
+                    ensure!(
+                        key_pairs.len() >= multi_sig_owner.threshold as usize,
+                        "The wallet only has {} of the {} required member keys",
+                        key_pairs.len(),
+                        multi_sig_owner.threshold
+                    );
+                    let owner = chain_client.add_multi_sig_owner(multi_sig_owner, key_pairs);
+                    info!("Signing as multi-signature owner {}", owner);
+                }
                 let owner = match sender.owner {
                     Some(AccountOwner::User(owner)) => Some(owner),
                     Some(AccountOwner::Application(_)) => {
@@ -333,6 +353,29 @@ impl Runnable for Job {
                 println!("{}", balance);
             }
 
+            Estimate {
+                chain_id,
+                json_operations,
+                json_operations_path,
+            } => {
+                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_client = context.make_chain_client(chain_id)?;
+                let operations: Vec<Operation> =
+                    serde_json::from_slice(&read_json(json_operations, json_operations_path)?)?;
+                info!(
+                    "Estimating the execution of {} operation(s) on chain {chain_id}",
+                    operations.len()
+                );
+                let time_start = Instant::now();
+                let estimate = chain_client.estimate_operations(operations).await?;
+                let time_total = time_start.elapsed();
+                info!("Estimate obtained after {} ms", time_total.as_millis());
+                if let Some(error) = &estimate.error {
+                    warn!("The operations failed to execute: {error}");
+                }
+                println!("{}", serde_json::to_string_pretty(&estimate)?);
+            }
+
             SyncBalance { account } => {
                 let account = account.unwrap_or_else(|| context.default_account());
                 let chain_client = context.make_chain_client(account.chain_id)?;
@@ -621,6 +664,9 @@ impl Runnable for Job {
                                     maximum_block_proposal_size,
                                     maximum_bytes_read_per_block,
                                     maximum_bytes_written_per_block,
+                                    maximum_operations_per_block,
+                                    maximum_incoming_bundles_per_block,
+                                    maximum_operation_argument_bytes,
                                 } => {
                                     if let Some(block) = block {
                                         policy.block = block;
@@ -688,6 +734,24 @@ impl Runnable for Job {
                                         policy.maximum_bytes_written_per_block =
                                             maximum_bytes_written_per_block;
                                     }
+                                    if let Some(maximum_operations_per_block) =
+                                        maximum_operations_per_block
+                                    {
+                                        policy.maximum_operations_per_block =
+                                            maximum_operations_per_block;
+                                    }
+                                    if let Some(maximum_incoming_bundles_per_block) =
+                                        maximum_incoming_bundles_per_block
+                                    {
+                                        policy.maximum_incoming_bundles_per_block =
+                                            maximum_incoming_bundles_per_block;
+                                    }
+                                    if let Some(maximum_operation_argument_bytes) =
+                                        maximum_operation_argument_bytes
+                                    {
+                                        policy.maximum_operation_argument_bytes =
+                                            maximum_operation_argument_bytes;
+                                    }
                                     info!("{policy}");
                                     if committee.policy() == &policy {
                                         return Ok(ClientOutcome::Committed(None));
@@ -946,6 +1010,7 @@ impl Runnable for Job {
                 json_argument,
                 json_argument_path,
                 required_application_ids,
+                admin,
             } => {
                 let start_time = Instant::now();
                 let creator = creator.unwrap_or_else(|| context.default_chain());
@@ -971,6 +1036,7 @@ impl Runnable for Job {
                                     parameters,
                                     argument,
                                     required_application_ids.unwrap_or_default(),
+                                    admin,
                                 )
                                 .await
                         }
@@ -994,6 +1060,7 @@ impl Runnable for Job {
                 json_argument,
                 json_argument_path,
                 required_application_ids,
+                admin,
             } => {
                 let start_time = Instant::now();
                 let publisher = publisher.unwrap_or_else(|| context.default_chain());
@@ -1019,6 +1086,7 @@ impl Runnable for Job {
                                     parameters,
                                     argument,
                                     required_application_ids.unwrap_or_default(),
+                                    admin,
                                 )
                                 .await
                         }
@@ -1033,6 +1101,35 @@ impl Runnable for Job {
                 println!("{}", application_id);
             }
 
+            UpgradeApplication {
+                application_id,
+                bytecode_id,
+            } => {
+                let start_time = Instant::now();
+                let chain_id = application_id.creation.chain_id;
+                info!(
+                    "Upgrading application {} on chain {}",
+                    application_id, chain_id
+                );
+                let chain_client = context.make_chain_client(chain_id)?;
+                let certificate = context
+                    .apply_client_command(&chain_client, |chain_client| {
+                        let chain_client = chain_client.clone();
+                        async move {
+                            chain_client
+                                .upgrade_application(application_id, bytecode_id)
+                                .await
+                        }
+                    })
+                    .await
+                    .context("Failed to upgrade application")?;
+                info!(
+                    "Application upgraded in {} ms",
+                    start_time.elapsed().as_millis()
+                );
+                debug!("{:?}", certificate);
+            }
+
             RequestApplication {
                 application_id,
                 target_chain_id,
@@ -1095,6 +1192,7 @@ impl Runnable for Job {
                     json_argument,
                     json_argument_path,
                     required_application_ids,
+                    admin,
                 } => {
                     let start_time = Instant::now();
                     let publisher = publisher.unwrap_or_else(|| context.default_chain());
@@ -1125,6 +1223,7 @@ impl Runnable for Job {
                                         parameters,
                                         argument,
                                         required_application_ids.unwrap_or_default(),
+                                        admin,
                                     )
                                     .await
                             }
@@ -1211,7 +1310,7 @@ impl Runnable for Job {
             }
 
             CreateGenesisConfig { .. }
-            | Keygen
+            | Keygen { .. }
             | Net(_)
             | Storage { .. }
             | Wallet(_)
@@ -1415,6 +1514,7 @@ fn log_file_name_for(command: &ClientCommand) -> Cow<'static, str> {
         | ClientCommand::CloseChain { .. }
         | ClientCommand::LocalBalance { .. }
         | ClientCommand::QueryBalance { .. }
+        | ClientCommand::Estimate { .. }
         | ClientCommand::SyncBalance { .. }
         | ClientCommand::Sync { .. }
         | ClientCommand::ProcessInbox { .. }
@@ -1430,6 +1530,7 @@ fn log_file_name_for(command: &ClientCommand) -> Cow<'static, str> {
         | ClientCommand::ReadDataBlob { .. }
         | ClientCommand::CreateApplication { .. }
         | ClientCommand::PublishAndCreate { .. }
+        | ClientCommand::UpgradeApplication { .. }
         | ClientCommand::RequestApplication { .. }
         | ClientCommand::Keygen { .. }
         | ClientCommand::Assign { .. }
@@ -1499,6 +1600,9 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
             maximum_block_proposal_size,
             maximum_bytes_read_per_block,
             maximum_bytes_written_per_block,
+            maximum_operations_per_block,
+            maximum_incoming_bundles_per_block,
+            maximum_operation_argument_bytes,
             testing_prng_seed,
             network_name,
         } => {
@@ -1513,6 +1617,11 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
             let maximum_blob_size = maximum_blob_size.unwrap_or(u64::MAX);
             let maximum_bytecode_size = maximum_bytecode_size.unwrap_or(u64::MAX);
             let maximum_block_proposal_size = maximum_block_proposal_size.unwrap_or(u64::MAX);
+            let maximum_operations_per_block = maximum_operations_per_block.unwrap_or(u64::MAX);
+            let maximum_incoming_bundles_per_block =
+                maximum_incoming_bundles_per_block.unwrap_or(u64::MAX);
+            let maximum_operation_argument_bytes =
+                maximum_operation_argument_bytes.unwrap_or(u64::MAX);
             let policy = ResourceControlPolicy {
                 block: *block_price,
                 fuel_unit: *fuel_unit_price,
@@ -1532,6 +1641,9 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                 maximum_block_proposal_size,
                 maximum_bytes_read_per_block,
                 maximum_bytes_written_per_block,
+                maximum_operations_per_block,
+                maximum_incoming_bundles_per_block,
+                maximum_operation_argument_bytes,
             };
             let timestamp = start_timestamp
                 .map(|st| {
@@ -1606,15 +1718,31 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
             }
         },
 
-        ClientCommand::Keygen => {
+        ClientCommand::Keygen {
+            secp256k1,
+            secret_key_file,
+        } => {
             let start_time = Instant::now();
             let mut wallet = options.wallet().await?;
-            let key_pair = wallet.generate_key_pair();
-            let owner = Owner::from(key_pair.public());
+            let key_pair = if let Some(path) = secret_key_file {
+                let secret = fs_err::read_to_string(path)?;
+                let secret = hex::decode(secret.trim().trim_start_matches("0x"))?;
+                KeyPair::from_secp256k1_secret_bytes(&secret)?
+            } else if *secp256k1 {
+                wallet.generate_secp256k1_key_pair()
+            } else {
+                wallet.generate_key_pair()
+            };
+            let public_key = key_pair.public();
+            if let Some(address) = public_key.ethereum_address() {
+                info!("Ethereum address: {}", address);
+            }
+            let owner = Owner::from(public_key);
             wallet
                 .mutate(|w| w.add_unassigned_key_pair(key_pair))
                 .await?;
             println!("{}", owner);
+            info!("Public key: {}", public_key);
             info!("Key generated in {} ms", start_time.elapsed().as_millis());
             Ok(0)
         }
@@ -1770,6 +1898,57 @@ async fn run(options: &ClientOptions) -> Result<i32, anyhow::Error> {
                     );
                     println!("The list of namespaces is {:?}", namespaces);
                 }
+                DatabaseToolCommand::Snapshot { path, .. } => {
+                    full_storage_config.snapshot(&path).await?;
+                    info!(
+                        "Snapshot written to {} in {} ms",
+                        path.display(),
+                        start_time.elapsed().as_millis()
+                    );
+                }
+                DatabaseToolCommand::Restore { path, .. } => {
+                    full_storage_config.restore(&path).await?;
+                    info!(
+                        "Namespace restored from {} in {} ms",
+                        path.display(),
+                        start_time.elapsed().as_millis()
+                    );
+                }
+                DatabaseToolCommand::Migrate { .. } => {
+                    let version = full_storage_config.migrate().await?;
+                    info!(
+                        "Namespace migrated from schema version {} to {} in {} ms",
+                        version,
+                        SCHEMA_VERSION,
+                        start_time.elapsed().as_millis()
+                    );
+                }
+                DatabaseToolCommand::Export { path, .. } => {
+                    let summary = full_storage_config.export(&path).await?;
+                    info!(
+                        "{} key-value pairs exported to {} in {} ms",
+                        summary.count,
+                        path.display(),
+                        start_time.elapsed().as_millis()
+                    );
+                }
+                DatabaseToolCommand::Import { path, .. } => {
+                    let summary = full_storage_config.import(&path).await?;
+                    info!(
+                        "{} key-value pairs imported from {} in {} ms",
+                        summary.count,
+                        path.display(),
+                        start_time.elapsed().as_millis()
+                    );
+                }
+                DatabaseToolCommand::CollectGarbage { dry_run, .. } => {
+                    let report = full_storage_config.collect_garbage(dry_run).await?;
+                    info!(
+                        "Garbage collection done in {} ms",
+                        start_time.elapsed().as_millis()
+                    );
+                    print!("{report}");
+                }
             }
             Ok(0)
         }
@@ -52,6 +52,7 @@ impl StorageConfigProvider {
                 let storage = StorageConfigNamespace {
                     storage_config,
                     namespace,
+                    compression_threshold: None,
                 };
                 Ok(StorageConfigProvider {
                     storage,
@@ -91,6 +92,8 @@ impl StorageConfigProvider {
             StorageConfig::Memory => anyhow::bail!("Not possible to work with memory"),
             #[cfg(feature = "rocksdb")]
             StorageConfig::RocksDb { .. } => anyhow::bail!("Not possible to work with RocksDB"),
+            #[cfg(feature = "redb")]
+            StorageConfig::Redb { .. } => anyhow::bail!("Not possible to work with redb"),
             #[cfg(feature = "storage-service")]
             StorageConfig::Service { .. } => Ok(Database::Service),
             #[cfg(feature = "dynamodb")]
@@ -19,6 +19,7 @@ use linera_base::{
     hashed::Hashed,
     identifiers::{ApplicationId, BytecodeId, ChainId, Owner, UserApplicationId},
     ownership::{ChainOwnership, TimeoutConfig},
+    state_proof::StateProof,
     BcsHexParseError,
 };
 use linera_chain::{
@@ -28,7 +29,7 @@ use linera_chain::{
 use linera_client::chain_listener::{ChainListener, ChainListenerConfig, ClientContext};
 use linera_core::{
     client::{ChainClient, ChainClientError},
-    data_types::ClientOutcome,
+    data_types::{ClientOutcome, OperationsEstimate},
     worker::Notification,
 };
 use linera_execution::{
@@ -260,6 +261,17 @@ where
         }
     }
 
+    /// Estimates the resources used and the fees charged for executing the given operations
+    /// in a new block, without proposing it. Incoming messages are not included.
+    async fn estimate_operations(
+        &self,
+        chain_id: ChainId,
+        operations: Vec<Operation>,
+    ) -> Result<OperationsEstimate, Error> {
+        let client = self.context.lock().await.make_chain_client(chain_id)?;
+        Ok(client.estimate_operations(operations).await?)
+    }
+
     /// Transfers `amount` units of value from the given owner's account to the recipient.
     /// If no owner is given, try to take the units out of the unattributed account.
     async fn transfer(
@@ -613,6 +625,7 @@ where
                         parameters,
                         instantiation_argument,
                         required_application_ids,
+                        None,
                     )
                     .await
                     .map_err(Error::from)
@@ -732,6 +745,34 @@ where
         }
     }
 
+    /// Returns a proof of the value of a key of an application, to be verified against the
+    /// state hash of the requested block. Only the state after the chain's latest block is
+    /// stored, so the block must be the chain's latest one.
+    async fn state_proof(
+        &self,
+        chain_id: ChainId,
+        application_id: UserApplicationId,
+        #[graphql(desc = "The hex-encoded key.")] key: String,
+        #[graphql(desc = "The hash of the block whose state hash the proof is checked against.")]
+        block_hash: CryptoHash,
+    ) -> Result<StateProof, Error> {
+        let key = hex::decode(key)?;
+        let client = self.context.lock().await.make_chain_client(chain_id)?;
+        let chain = client.chain_state_view().await?;
+        let tip_hash = chain.tip_state.get().block_hash;
+        if tip_hash != Some(block_hash) {
+            return Err(Error::new(format!(
+                "Block {block_hash} is not the latest block of chain {chain_id}; \
+                 state proofs are only available for the latest block ({tip_hash:?})"
+            )));
+        }
+        let proof = chain
+            .execution_state
+            .prove_application_state(application_id, &key)
+            .await?;
+        Ok(proof)
+    }
+
     /// Returns the version information on this node service.
     async fn version(&self) -> linera_version::VersionInfo {
         linera_version::VersionInfo::default()
@@ -24,7 +24,8 @@ use linera_client::config::GenesisConfig;
 use linera_core::{notifier::ChannelNotifier, JoinSetExt as _};
 use linera_rpc::{
     config::{
-        ShardConfig, TlsConfig, ValidatorInternalNetworkConfig, ValidatorPublicNetworkConfig,
+        NetworkProtocol, ShardConfig, SharedInternalNetworkConfig, TlsConfig,
+        ValidatorPublicNetworkConfig,
     },
     grpc::{
         api::{
@@ -38,8 +39,8 @@ use linera_rpc::{
             PendingBlobResult, SubscriptionRequest, VersionInfo,
         },
         pool::GrpcConnectionPool,
-        GrpcProtoConversionError, GrpcProxyable, GRPC_CHUNKED_MESSAGE_FILL_LIMIT,
-        GRPC_MAX_MESSAGE_SIZE,
+        rate_limited_status, GrpcProtoConversionError, GrpcProxyable,
+        GRPC_CHUNKED_MESSAGE_FILL_LIMIT, GRPC_MAX_MESSAGE_SIZE,
     },
 };
 use linera_sdk::{base::Blob, views::ViewError};
@@ -64,6 +65,7 @@ use {
 
 #[cfg(with_metrics)]
 use crate::prometheus_server;
+use crate::rate_limit::{RateLimiter, RpcMethod};
 
 #[cfg(with_metrics)]
 static PROXY_REQUEST_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
@@ -150,11 +152,12 @@ pub struct GrpcProxy<S>(Arc<GrpcProxyInner<S>>);
 
 struct GrpcProxyInner<S> {
     public_config: ValidatorPublicNetworkConfig,
-    internal_config: ValidatorInternalNetworkConfig,
+    internal_config: SharedInternalNetworkConfig<NetworkProtocol>,
     genesis_config: GenesisConfig,
     worker_connection_pool: GrpcConnectionPool,
     notifier: ChannelNotifier<Result<Notification, Status>>,
     tls: TlsConfig,
+    rate_limiter: RateLimiter,
     storage: S,
 }
 
@@ -164,11 +167,12 @@ where
 {
     pub fn new(
         public_config: ValidatorPublicNetworkConfig,
-        internal_config: ValidatorInternalNetworkConfig,
+        internal_config: SharedInternalNetworkConfig<NetworkProtocol>,
         genesis_config: GenesisConfig,
         connect_timeout: Duration,
         timeout: Duration,
         tls: TlsConfig,
+        rate_limiter: RateLimiter,
         storage: S,
     ) -> Self {
         Self(Arc::new(GrpcProxyInner {
@@ -180,10 +184,15 @@ where
                 .with_timeout(timeout),
             notifier: ChannelNotifier::default(),
             tls,
+            rate_limiter,
             storage,
         }))
     }
 
+    pub fn internal_config(&self) -> &SharedInternalNetworkConfig<NetworkProtocol> {
+        &self.0.internal_config
+    }
+
     fn as_validator_node(&self) -> ValidatorNodeServer<Self> {
         ValidatorNodeServer::new(self.clone())
             .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
@@ -199,17 +208,18 @@ where
     }
 
     fn metrics_address(&self) -> SocketAddr {
-        SocketAddr::from(([0, 0, 0, 0], self.0.internal_config.metrics_port))
+        SocketAddr::from(([0, 0, 0, 0], self.0.internal_config.get().metrics_port))
     }
 
     fn internal_address(&self) -> SocketAddr {
-        SocketAddr::from(([0, 0, 0, 0], self.0.internal_config.port))
+        SocketAddr::from(([0, 0, 0, 0], self.0.internal_config.get().port))
     }
 
     fn shard_for(&self, proxyable: &impl GrpcProxyable) -> Option<ShardConfig> {
         Some(
             self.0
                 .internal_config
+                .get()
                 .get_shard_for(proxyable.chain_id()?)
                 .clone(),
         )
@@ -298,13 +308,16 @@ where
 
     async fn worker_client<R>(
         &self,
+        method: RpcMethod,
         request: Request<R>,
     ) -> Result<(ValidatorWorkerClient<Channel>, R), Status>
     where
         R: Debug + GrpcProxyable,
     {
-        debug!("proxying request from {:?}", request.remote_addr());
+        let remote_addr = request.remote_addr();
+        debug!("proxying request from {:?}", remote_addr);
         let inner = request.into_inner();
+        self.check_rate_limit(method, remote_addr, inner.chain_id())?;
         let shard = self
             .shard_for(&inner)
             .ok_or_else(|| Status::not_found("could not find shard for message"))?;
@@ -314,6 +327,22 @@ where
         Ok((client, inner))
     }
 
+    /// Returns an error if the request exceeds the rate limits of its client or chain.
+    fn check_rate_limit(
+        &self,
+        method: RpcMethod,
+        remote_addr: Option<SocketAddr>,
+        chain_id: Option<ChainId>,
+    ) -> Result<(), Status> {
+        self.0
+            .rate_limiter
+            .check(method, remote_addr.map(|address| address.ip()), chain_id)
+            .map_err(|delay| {
+                debug!(?method, ?remote_addr, ?chain_id, "rate limit exceeded");
+                rate_limited_status(delay.as_millis().try_into().unwrap_or(u64::MAX))
+            })
+    }
+
     fn log_and_return_proxy_request_outcome(
         result: Result<Response<ChainInfoResult>, Status>,
         method_name: &str,
@@ -346,12 +375,15 @@ where
             | ViewError::TryLockError(_)
             | ViewError::InconsistentEntries
             | ViewError::PostLoadValuesError
+            | ViewError::SchemaVersionMismatch { .. }
+            | ViewError::UnsupportedSchemaUpgrade { .. }
             | ViewError::IoError(_) => Status::internal(err.to_string()),
             ViewError::KeyTooLong | ViewError::ArithmeticError(_) => {
                 Status::out_of_range(err.to_string())
             }
             ViewError::NotFound(_)
             | ViewError::BlobsNotFound(_)
+            | ViewError::EventsNotFound(_)
             | ViewError::CannotAcquireCollectionEntry
             | ViewError::MissingEntries => Status::not_found(err.to_string()),
         };
@@ -372,7 +404,9 @@ where
         &self,
         request: Request<BlockProposal>,
     ) -> Result<Response<ChainInfoResult>, Status> {
-        let (mut client, inner) = self.worker_client(request).await?;
+        let (mut client, inner) = self
+            .worker_client(RpcMethod::HandleBlockProposal, request)
+            .await?;
         Self::log_and_return_proxy_request_outcome(
             client.handle_block_proposal(inner).await,
             "handle_block_proposal",
@@ -384,7 +418,9 @@ where
         &self,
         request: Request<LiteCertificate>,
     ) -> Result<Response<ChainInfoResult>, Status> {
-        let (mut client, inner) = self.worker_client(request).await?;
+        let (mut client, inner) = self
+            .worker_client(RpcMethod::HandleLiteCertificate, request)
+            .await?;
         Self::log_and_return_proxy_request_outcome(
             client.handle_lite_certificate(inner).await,
             "handle_lite_certificate",
@@ -396,7 +432,9 @@ where
         &self,
         request: Request<api::HandleConfirmedCertificateRequest>,
     ) -> Result<Response<ChainInfoResult>, Status> {
-        let (mut client, inner) = self.worker_client(request).await?;
+        let (mut client, inner) = self
+            .worker_client(RpcMethod::HandleConfirmedCertificate, request)
+            .await?;
         Self::log_and_return_proxy_request_outcome(
             client.handle_confirmed_certificate(inner).await,
             "handle_confirmed_certificate",
@@ -408,7 +446,9 @@ where
         &self,
         request: Request<api::HandleValidatedCertificateRequest>,
     ) -> Result<Response<ChainInfoResult>, Status> {
-        let (mut client, inner) = self.worker_client(request).await?;
+        let (mut client, inner) = self
+            .worker_client(RpcMethod::HandleValidatedCertificate, request)
+            .await?;
         Self::log_and_return_proxy_request_outcome(
             client.handle_validated_certificate(inner).await,
             "handle_validated_certificate",
@@ -420,7 +460,9 @@ where
         &self,
         request: Request<api::HandleTimeoutCertificateRequest>,
     ) -> Result<Response<ChainInfoResult>, Status> {
-        let (mut client, inner) = self.worker_client(request).await?;
+        let (mut client, inner) = self
+            .worker_client(RpcMethod::HandleTimeoutCertificate, request)
+            .await?;
         Self::log_and_return_proxy_request_outcome(
             client.handle_timeout_certificate(inner).await,
             "handle_timeout_certificate",
@@ -432,7 +474,9 @@ where
         &self,
         request: Request<ChainInfoQuery>,
     ) -> Result<Response<ChainInfoResult>, Status> {
-        let (mut client, inner) = self.worker_client(request).await?;
+        let (mut client, inner) = self
+            .worker_client(RpcMethod::HandleChainInfoQuery, request)
+            .await?;
         Self::log_and_return_proxy_request_outcome(
             client.handle_chain_info_query(inner).await,
             "handle_chain_info_query",
@@ -444,6 +488,7 @@ where
         &self,
         request: Request<SubscriptionRequest>,
     ) -> Result<Response<Self::SubscribeStream>, Status> {
+        self.check_rate_limit(RpcMethod::Subscribe, request.remote_addr(), None)?;
         let subscription_request = request.into_inner();
         let chain_ids = subscription_request
             .chain_ids
@@ -462,8 +507,9 @@ where
     #[instrument(skip_all, err(Display))]
     async fn get_version_info(
         &self,
-        _request: Request<()>,
+        request: Request<()>,
     ) -> Result<Response<VersionInfo>, Status> {
+        self.check_rate_limit(RpcMethod::GetVersionInfo, request.remote_addr(), None)?;
         // We assume each shard is running the same version as the proxy
         Ok(Response::new(linera_version::VersionInfo::default().into()))
     }
@@ -471,13 +517,15 @@ where
     #[instrument(skip_all, err(Display))]
     async fn get_genesis_config_hash(
         &self,
-        _request: Request<()>,
+        request: Request<()>,
     ) -> Result<Response<CryptoHash>, Status> {
+        self.check_rate_limit(RpcMethod::GetGenesisConfigHash, request.remote_addr(), None)?;
         Ok(Response::new(self.0.genesis_config.hash().into()))
     }
 
     #[instrument(skip_all, err(Display))]
     async fn upload_blob(&self, request: Request<BlobContent>) -> Result<Response<BlobId>, Status> {
+        self.check_rate_limit(RpcMethod::UploadBlob, request.remote_addr(), None)?;
         let content: linera_sdk::base::BlobContent = request.into_inner().try_into()?;
         let blob = Blob::new(content);
         let id = blob.id();
@@ -493,6 +541,7 @@ where
         &self,
         request: Request<BlobId>,
     ) -> Result<Response<BlobContent>, Status> {
+        self.check_rate_limit(RpcMethod::DownloadBlob, request.remote_addr(), None)?;
         let blob_id = request.into_inner().try_into()?;
         let blob = self
             .0
@@ -508,7 +557,9 @@ where
         &self,
         request: Request<PendingBlobRequest>,
     ) -> Result<Response<PendingBlobResult>, Status> {
-        let (mut client, inner) = self.worker_client(request).await?;
+        let (mut client, inner) = self
+            .worker_client(RpcMethod::DownloadPendingBlob, request)
+            .await?;
         #[cfg_attr(not(with_metrics), expect(clippy::needless_match))]
         match client.download_pending_blob(inner).await {
             Ok(blob_result) => {
@@ -533,7 +584,9 @@ where
         &self,
         request: Request<HandlePendingBlobRequest>,
     ) -> Result<Response<ChainInfoResult>, Status> {
-        let (mut client, inner) = self.worker_client(request).await?;
+        let (mut client, inner) = self
+            .worker_client(RpcMethod::HandlePendingBlob, request)
+            .await?;
         #[cfg_attr(not(with_metrics), expect(clippy::needless_match))]
         match client.handle_pending_blob(inner).await {
             Ok(blob_result) => {
@@ -558,6 +611,7 @@ where
         &self,
         request: Request<CryptoHash>,
     ) -> Result<Response<Certificate>, Status> {
+        self.check_rate_limit(RpcMethod::DownloadCertificate, request.remote_addr(), None)?;
         let hash = request.into_inner().try_into()?;
         let certificate: linera_chain::types::Certificate = self
             .0
@@ -574,6 +628,7 @@ where
         &self,
         request: Request<CertificatesBatchRequest>,
     ) -> Result<Response<CertificatesBatchResponse>, Status> {
+        self.check_rate_limit(RpcMethod::DownloadCertificates, request.remote_addr(), None)?;
         let hashes: Vec<linera_base::crypto::CryptoHash> = request
             .into_inner()
             .hashes
@@ -614,6 +669,7 @@ where
         &self,
         request: Request<BlobId>,
     ) -> Result<Response<CryptoHash>, Status> {
+        self.check_rate_limit(RpcMethod::BlobLastUsedBy, request.remote_addr(), None)?;
         let blob_id = request.into_inner().try_into()?;
         let blob_state = self
             .0
@@ -629,6 +685,7 @@ where
         &self,
         request: Request<BlobIds>,
     ) -> Result<Response<BlobIds>, Status> {
+        self.check_rate_limit(RpcMethod::MissingBlobIds, request.remote_addr(), None)?;
         let blob_ids: Vec<linera_base::identifiers::BlobId> = request.into_inner().try_into()?;
         let missing_blob_ids = self
             .0
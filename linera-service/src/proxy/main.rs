@@ -3,11 +3,11 @@
 
 #![deny(clippy::large_futures)]
 
-use std::{net::SocketAddr, path::PathBuf, time::Duration};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 
 use anyhow::{bail, ensure, Result};
 use async_trait::async_trait;
-use futures::{FutureExt as _, SinkExt, StreamExt};
+use futures::{future, FutureExt as _, SinkExt, StreamExt};
 use linera_client::{
     config::{GenesisConfig, ValidatorServerConfig},
     storage::{run_with_storage, Runnable, StorageConfigNamespace},
@@ -15,10 +15,10 @@ use linera_client::{
 use linera_core::{node::NodeError, JoinSetExt as _};
 use linera_rpc::{
     config::{
-        NetworkProtocol, ShardConfig, ValidatorInternalNetworkPreConfig,
+        NetworkProtocol, QuicConfig, ShardConfig, SharedInternalNetworkConfig,
         ValidatorPublicNetworkPreConfig,
     },
-    simple::{MessageHandler, TransportProtocol},
+    simple::{MessageHandler, QuicConnector, TransportProtocol},
     RpcMessage,
 };
 use linera_sdk::base::Blob;
@@ -29,10 +29,13 @@ use linera_storage::Storage;
 use linera_views::store::CommonStoreConfig;
 use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
-use tracing::{error, info, instrument};
+use tracing::{debug, error, info, instrument};
 
 mod grpc;
+mod rate_limit;
+
 use grpc::GrpcProxy;
+use rate_limit::{RateLimitConfig, RateLimiter, RpcMethod};
 
 /// Options for running the proxy.
 #[derive(clap::Parser, Debug, Clone)]
@@ -76,6 +79,16 @@ pub struct ProxyOptions {
     /// Path to the file describing the initial user chains (aka genesis state)
     #[arg(long = "genesis")]
     genesis_config_path: PathBuf,
+
+    /// If set, reads the server configuration file at this interval and routes requests
+    /// with its shard map when its version is more recent.
+    #[arg(long = "shard-map-reload-interval-secs", value_parser = util::parse_secs)]
+    shard_map_reload_interval: Option<Duration>,
+
+    /// Path to a TOML file with the token-bucket limits of the requests of each source IP
+    /// address and of each chain, per RPC method. Requests are not limited by default.
+    #[arg(long = "rate-limit-config")]
+    rate_limit_config_path: Option<PathBuf>,
 }
 
 /// A Linera Proxy, either gRPC or over 'Simple Transport', meaning TCP or UDP.
@@ -91,19 +104,29 @@ where
 
 struct ProxyContext {
     config: ValidatorServerConfig,
+    config_path: PathBuf,
     genesis_config: GenesisConfig,
     send_timeout: Duration,
     recv_timeout: Duration,
+    shard_map_reload_interval: Option<Duration>,
+    rate_limit_config: RateLimitConfig,
 }
 
 impl ProxyContext {
     pub fn from_options(options: &ProxyOptions) -> Result<Self> {
         let config = util::read_json(&options.config_path)?;
         let genesis_config = util::read_json(&options.genesis_config_path)?;
+        let rate_limit_config = match &options.rate_limit_config_path {
+            Some(path) => RateLimitConfig::read(path)?,
+            None => RateLimitConfig::default(),
+        };
         Ok(Self {
             config,
+            config_path: options.config_path.clone(),
             send_timeout: options.send_timeout,
             recv_timeout: options.recv_timeout,
+            shard_map_reload_interval: options.shard_map_reload_interval,
+            rate_limit_config,
             genesis_config,
         })
     }
@@ -119,7 +142,31 @@ impl Runnable for ProxyContext {
     {
         let shutdown_notifier = CancellationToken::new();
         tokio::spawn(util::listen_for_shutdown_signals(shutdown_notifier.clone()));
+        let config_path = self.config_path.clone();
+        let shard_map_reload_interval = self.shard_map_reload_interval;
         let proxy = Proxy::from_context(self, storage)?;
+        if let Some(interval) = shard_map_reload_interval {
+            match &proxy {
+                Proxy::Simple(simple_proxy) => {
+                    tokio::spawn(util::reload_shard_map_periodically(
+                        config_path,
+                        simple_proxy.internal_config.clone(),
+                        interval,
+                        shutdown_notifier.clone(),
+                        |_| future::ready(()),
+                    ));
+                }
+                Proxy::Grpc(grpc_proxy) => {
+                    tokio::spawn(util::reload_shard_map_periodically(
+                        config_path,
+                        grpc_proxy.internal_config().clone(),
+                        interval,
+                        shutdown_notifier.clone(),
+                        |_| future::ready(()),
+                    ));
+                }
+            }
+        }
         match proxy {
             Proxy::Simple(simple_proxy) => simple_proxy.run(shutdown_notifier).await,
             Proxy::Grpc(grpc_proxy) => grpc_proxy.run(shutdown_notifier).await,
@@ -139,11 +186,12 @@ where
             (NetworkProtocol::Grpc { .. }, NetworkProtocol::Grpc(tls)) => {
                 Self::Grpc(GrpcProxy::new(
                     context.config.validator.network,
-                    context.config.internal_network,
+                    SharedInternalNetworkConfig::new(context.config.internal_network),
                     context.genesis_config,
                     context.send_timeout,
                     context.recv_timeout,
                     tls,
+                    RateLimiter::new(context.rate_limit_config),
                     storage,
                 ))
             }
@@ -151,10 +199,12 @@ where
                 NetworkProtocol::Simple(internal_transport),
                 NetworkProtocol::Simple(public_transport),
             ) => Self::Simple(Box::new(SimpleProxy {
-                internal_config: context
-                    .config
-                    .internal_network
-                    .clone_with_protocol(internal_transport),
+                internal_config: SharedInternalNetworkConfig::new(
+                    context
+                        .config
+                        .internal_network
+                        .clone_with_protocol(internal_transport),
+                ),
                 public_config: context
                     .config
                     .validator
@@ -163,6 +213,9 @@ where
                 genesis_config: context.genesis_config,
                 send_timeout: context.send_timeout,
                 recv_timeout: context.recv_timeout,
+                rate_limiter: Arc::new(RateLimiter::new(context.rate_limit_config)),
+                quic: QuicConnector::new(context.config.quic.clone()),
+                quic_config: context.config.quic,
                 storage,
             })),
             _ => {
@@ -184,10 +237,15 @@ where
     S: Storage + Clone + Send + Sync + 'static,
 {
     public_config: ValidatorPublicNetworkPreConfig<TransportProtocol>,
-    internal_config: ValidatorInternalNetworkPreConfig<TransportProtocol>,
+    internal_config: SharedInternalNetworkConfig<TransportProtocol>,
     genesis_config: GenesisConfig,
     send_timeout: Duration,
     recv_timeout: Duration,
+    rate_limiter: Arc<RateLimiter>,
+    /// The connections to the shards, if they use QUIC.
+    quic: QuicConnector,
+    /// The certificate of the proxy, if it uses QUIC.
+    quic_config: QuicConfig,
     storage: S,
 }
 
@@ -197,7 +255,22 @@ where
     S: Storage + Clone + Send + Sync + 'static,
 {
     #[instrument(skip_all, fields(chain_id = ?message.target_chain_id()))]
-    async fn handle_message(&mut self, message: RpcMessage) -> Option<RpcMessage> {
+    async fn handle_message(
+        &mut self,
+        message: RpcMessage,
+        peer: SocketAddr,
+    ) -> Option<RpcMessage> {
+        if let Some(method) = RpcMethod::from_message(&message) {
+            let chain_id = message.target_chain_id();
+            if let Err(delay) = self.rate_limiter.check(method, Some(peer.ip()), chain_id) {
+                debug!(?method, %peer, ?chain_id, "rate limit exceeded");
+                let retry_after_ms = delay.as_millis().try_into().unwrap_or(u64::MAX);
+                return Some(RpcMessage::Error(Box::new(NodeError::RateLimited {
+                    retry_after_ms,
+                })));
+            }
+        }
+
         if message.is_local_message() {
             match self.try_local_message(message).await {
                 Ok(maybe_response) => {
@@ -215,13 +288,15 @@ where
             return None;
         };
 
-        let shard = self.internal_config.get_shard_for(chain_id).clone();
-        let protocol = self.internal_config.protocol;
+        let internal_config = self.internal_config.get();
+        let shard = internal_config.get_shard_for(chain_id).clone();
+        let protocol = internal_config.protocol;
 
         match Self::try_proxy_message(
             message,
             shard.clone(),
             protocol,
+            &self.quic,
             self.send_timeout,
             self.recv_timeout,
         )
@@ -240,21 +315,22 @@ impl<S> SimpleProxy<S>
 where
     S: Storage + Clone + Send + Sync + 'static,
 {
-    #[instrument(name = "SimpleProxy::run", skip_all, fields(port = self.public_config.port, metrics_port = self.internal_config.metrics_port), err)]
+    #[instrument(name = "SimpleProxy::run", skip_all, fields(port = self.public_config.port, metrics_port = self.internal_config.get().metrics_port), err)]
     async fn run(self, shutdown_signal: CancellationToken) -> Result<()> {
         info!("Starting simple server");
         let mut join_set = JoinSet::new();
         let address = self.get_listen_address(self.public_config.port);
+        let quic_config = self.quic_config.clone();
 
         #[cfg(with_metrics)]
         Self::start_metrics(
-            self.get_listen_address(self.internal_config.metrics_port),
+            self.get_listen_address(self.internal_config.get().metrics_port),
             shutdown_signal.clone(),
         );
 
         self.public_config
             .protocol
-            .spawn_server(address, self, shutdown_signal, &mut join_set)
+            .spawn_server(address, self, &quic_config, shutdown_signal, &mut join_set)
             .join()
             .await?;
 
@@ -276,10 +352,11 @@ where
         message: RpcMessage,
         shard: ShardConfig,
         protocol: TransportProtocol,
+        quic: &QuicConnector,
         send_timeout: Duration,
         recv_timeout: Duration,
     ) -> Result<Option<RpcMessage>> {
-        let mut connection = protocol.connect((shard.host, shard.port)).await?;
+        let mut connection = protocol.connect(&shard.host, shard.port, quic).await?;
         linera_base::time::timer::timeout(send_timeout, connection.send(message)).await??;
         let message = linera_base::time::timer::timeout(recv_timeout, connection.next())
             .await?
@@ -385,6 +462,8 @@ impl ProxyOptions {
             max_concurrent_queries: self.max_concurrent_queries,
             max_stream_queries: self.max_stream_queries,
             cache_size: self.cache_size,
+            compression_threshold: None,
+            encryption_key: None,
         };
         let full_storage_config = self.storage_config.add_common_config(common_config).await?;
         let genesis_config: GenesisConfig = util::read_json(&self.genesis_config_path)?;
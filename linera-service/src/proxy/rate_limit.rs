@@ -0,0 +1,449 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admission control of the requests received by the proxy.
+//!
+//! Each RPC method has a token bucket per source IP address, and one per chain of each
+//! source IP address. IPv6 addresses are grouped by their network prefix, since a single
+//! client usually controls a whole `/64` subnet. A request is accepted if the buckets it
+//! belongs to all have a token left, and then consumes one token from each of them. Buckets
+//! are refilled continuously at the configured rate, up to their burst size.
+
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv6Addr},
+    num::NonZeroUsize,
+    path::Path,
+    sync::Mutex,
+};
+
+use anyhow::{ensure, Context as _, Result};
+use linera_base::{
+    identifiers::ChainId,
+    time::{Duration, Instant},
+};
+use linera_rpc::RpcMessage;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+
+/// The maximal number of buckets kept. The least recently used ones are removed first.
+const MAX_BUCKETS: usize = 100_000;
+
+/// The default length of the prefix identifying an IPv6 client.
+const DEFAULT_IPV6_PREFIX_LENGTH: u8 = 64;
+
+/// The requests of the proxy that have separate budgets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcMethod {
+    HandleBlockProposal,
+    HandleLiteCertificate,
+    HandleConfirmedCertificate,
+    HandleValidatedCertificate,
+    HandleTimeoutCertificate,
+    HandleChainInfoQuery,
+    Subscribe,
+    GetVersionInfo,
+    GetGenesisConfigHash,
+    UploadBlob,
+    DownloadBlob,
+    DownloadPendingBlob,
+    HandlePendingBlob,
+    DownloadConfirmedBlock,
+    DownloadCertificate,
+    DownloadCertificates,
+    BlobLastUsedBy,
+    MissingBlobIds,
+}
+
+impl RpcMethod {
+    /// Returns the method of a request of the simple protocol, or `None` if the message is
+    /// not a request that clients send to the proxy.
+    pub fn from_message(message: &RpcMessage) -> Option<Self> {
+        use RpcMessage::*;
+
+        let method = match message {
+            BlockProposal(_) => Self::HandleBlockProposal,
+            LiteCertificate(_) => Self::HandleLiteCertificate,
+            ConfirmedCertificate(_) => Self::HandleConfirmedCertificate,
+            ValidatedCertificate(_) => Self::HandleValidatedCertificate,
+            TimeoutCertificate(_) => Self::HandleTimeoutCertificate,
+            ChainInfoQuery(_) => Self::HandleChainInfoQuery,
+            VersionInfoQuery => Self::GetVersionInfo,
+            GenesisConfigHashQuery => Self::GetGenesisConfigHash,
+            UploadBlob(_) => Self::UploadBlob,
+            DownloadBlob(_) => Self::DownloadBlob,
+            DownloadPendingBlob(_) => Self::DownloadPendingBlob,
+            HandlePendingBlob(_) => Self::HandlePendingBlob,
+            DownloadConfirmedBlock(_) => Self::DownloadConfirmedBlock,
+            DownloadCertificates(_) => Self::DownloadCertificates,
+            BlobLastUsedBy(_) => Self::BlobLastUsedBy,
+            MissingBlobIds(_) => Self::MissingBlobIds,
+            CrossChainRequest(_)
+            | Vote(_)
+            | Error(_)
+            | ChainInfoResponse(_)
+            | VersionInfoResponse(_)
+            | GenesisConfigHashResponse(_)
+            | UploadBlobResponse(_)
+            | DownloadBlobResponse(_)
+            | DownloadPendingBlobResponse(_)
+            | DownloadConfirmedBlockResponse(_)
+            | DownloadCertificatesResponse(_)
+            | BlobLastUsedByResponse(_)
+            | MissingBlobIdsResponse(_) => return None,
+        };
+        Some(method)
+    }
+}
+
+/// The size and refill rate of a token bucket.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BucketConfig {
+    /// The number of tokens added to the bucket per second.
+    pub requests_per_second: f64,
+    /// The maximal number of tokens in the bucket, i.e. of requests accepted at once.
+    pub burst: u32,
+}
+
+/// The budgets of a method. Requests are not limited by a missing budget.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MethodLimits {
+    /// The budget of each source IP address.
+    pub per_ip: Option<BucketConfig>,
+    /// The budget of each source IP address for each chain.
+    pub per_chain: Option<BucketConfig>,
+}
+
+/// The rate limits of the proxy, usually read from a TOML file such as:
+///
+/// ```toml
+/// ipv6_prefix_length = 64
+///
+/// [default]
+/// per_ip = { requests_per_second = 100.0, burst = 200 }
+///
+/// [methods.upload_blob]
+/// per_ip = { requests_per_second = 5.0, burst = 10 }
+/// per_chain = { requests_per_second = 5.0, burst = 10 }
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// The number of leading bits of an IPv6 address that identify a client. All the
+    /// addresses with the same prefix share their budgets.
+    #[serde(default = "default_ipv6_prefix_length")]
+    pub ipv6_prefix_length: u8,
+    /// The budgets of the methods that are not listed in `methods`.
+    #[serde(default)]
+    pub default: MethodLimits,
+    /// The budgets of specific methods, replacing the default ones.
+    #[serde(default)]
+    pub methods: BTreeMap<RpcMethod, MethodLimits>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            ipv6_prefix_length: DEFAULT_IPV6_PREFIX_LENGTH,
+            default: MethodLimits::default(),
+            methods: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_ipv6_prefix_length() -> u8 {
+    DEFAULT_IPV6_PREFIX_LENGTH
+}
+
+impl RateLimitConfig {
+    /// Reads the configuration from a TOML file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs_err::read_to_string(path)?;
+        let config: Self = toml::from_str(&contents)
+            .with_context(|| format!("invalid rate limit configuration {}", path.display()))?;
+        ensure!(
+            config.ipv6_prefix_length <= 128,
+            "the IPv6 prefix length must be at most 128 bits"
+        );
+        for limits in std::iter::once(&config.default).chain(config.methods.values()) {
+            for bucket in limits.per_ip.iter().chain(&limits.per_chain) {
+                ensure!(
+                    bucket.requests_per_second > 0.0 && bucket.burst > 0,
+                    "rate limits must have a positive rate and burst size"
+                );
+            }
+        }
+        Ok(config)
+    }
+
+    fn limits(&self, method: RpcMethod) -> &MethodLimits {
+        self.methods.get(&method).unwrap_or(&self.default)
+    }
+
+    /// Returns the address identifying the client with the given IP address: IPv6 addresses
+    /// are truncated to their prefix.
+    fn client_address(&self, ip: IpAddr) -> IpAddr {
+        match ip.to_canonical() {
+            IpAddr::V4(ip) => IpAddr::V4(ip),
+            IpAddr::V6(ip) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.ipv6_prefix_length.min(128)))
+                    .unwrap_or(0);
+                IpAddr::V6(Ipv6Addr::from(u128::from(ip) & mask))
+            }
+        }
+    }
+}
+
+/// The source of requests that a bucket is for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum Client {
+    Ip(IpAddr),
+    Chain(Option<IpAddr>, ChainId),
+}
+
+/// The remaining budget of a client for a method.
+#[derive(Debug)]
+struct TokenBucket {
+    config: BucketConfig,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: BucketConfig, now: Instant) -> Self {
+        Self {
+            config,
+            tokens: config.burst.into(),
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.config.requests_per_second)
+            .min(self.config.burst.into());
+        self.last_refill = now;
+    }
+
+    /// Returns the time until the bucket has a token.
+    fn delay(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64((1.0 - self.tokens) / self.config.requests_per_second)
+    }
+}
+
+/// Checks the requests against the budgets of their source IP address and chain.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<LruCache<(RpcMethod, Client), TokenBucket>>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(LruCache::new(
+                NonZeroUsize::new(MAX_BUCKETS).expect("The number of buckets should not be zero"),
+            )),
+        }
+    }
+
+    /// Accepts a request for `method` from `ip` about `chain_id`, or returns the time after
+    /// which it would be accepted.
+    pub fn check(
+        &self,
+        method: RpcMethod,
+        ip: Option<IpAddr>,
+        chain_id: Option<ChainId>,
+    ) -> Result<(), Duration> {
+        self.check_at(method, ip, chain_id, Instant::now())
+    }
+
+    fn check_at(
+        &self,
+        method: RpcMethod,
+        ip: Option<IpAddr>,
+        chain_id: Option<ChainId>,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let limits = self.config.limits(method);
+        let ip = ip.map(|ip| self.config.client_address(ip));
+        let clients = [
+            limits.per_ip.zip(ip.map(Client::Ip)),
+            limits
+                .per_chain
+                .zip(chain_id.map(|chain_id| Client::Chain(ip, chain_id))),
+        ];
+        if clients.iter().all(Option::is_none) {
+            return Ok(());
+        }
+        let mut buckets = self.buckets.lock().unwrap();
+        let mut delay = Duration::ZERO;
+        for (config, client) in clients.iter().flatten() {
+            let bucket =
+                buckets.get_or_insert_mut((method, *client), || TokenBucket::new(*config, now));
+            bucket.refill(now);
+            delay = delay.max(bucket.delay());
+        }
+        if delay > Duration::ZERO {
+            return Err(delay);
+        }
+        for (_, client) in clients.iter().flatten() {
+            if let Some(bucket) = buckets.peek_mut(&(method, *client)) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    fn limiter() -> RateLimiter {
+        let mut config = RateLimitConfig {
+            ipv6_prefix_length: DEFAULT_IPV6_PREFIX_LENGTH,
+            default: MethodLimits {
+                per_ip: Some(BucketConfig {
+                    requests_per_second: 1.0,
+                    burst: 2,
+                }),
+                per_chain: None,
+            },
+            methods: BTreeMap::new(),
+        };
+        config.methods.insert(
+            RpcMethod::UploadBlob,
+            MethodLimits {
+                per_ip: None,
+                per_chain: Some(BucketConfig {
+                    requests_per_second: 10.0,
+                    burst: 1,
+                }),
+            },
+        );
+        RateLimiter::new(config)
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        let limiter = limiter();
+        let ip = Some(IpAddr::from([127, 0, 0, 1]));
+        let other_ip = Some(IpAddr::from([127, 0, 0, 2]));
+        let chain_id = Some(ChainId::root(0));
+        let method = RpcMethod::HandleChainInfoQuery;
+        let now = Instant::now();
+
+        assert!(limiter.check_at(method, ip, chain_id, now).is_ok());
+        assert!(limiter.check_at(method, ip, chain_id, now).is_ok());
+        assert_eq!(
+            limiter.check_at(method, ip, chain_id, now),
+            Err(Duration::from_secs(1))
+        );
+        // Other clients and methods have their own budgets.
+        assert!(limiter.check_at(method, other_ip, chain_id, now).is_ok());
+        assert!(limiter
+            .check_at(RpcMethod::DownloadBlob, ip, None, now)
+            .is_ok());
+        // The bucket is refilled over time.
+        let later = now + Duration::from_millis(1500);
+        assert!(limiter.check_at(method, ip, chain_id, later).is_ok());
+        assert!(limiter.check_at(method, ip, chain_id, later).is_err());
+
+        // Uploads are only limited per chain, separately for each IP address.
+        let method = RpcMethod::UploadBlob;
+        assert!(limiter.check_at(method, ip, None, now).is_ok());
+        assert!(limiter.check_at(method, ip, chain_id, now).is_ok());
+        assert_eq!(
+            limiter.check_at(method, ip, chain_id, now),
+            Err(Duration::from_millis(100))
+        );
+        assert!(limiter.check_at(method, other_ip, chain_id, now).is_ok());
+        assert!(limiter
+            .check_at(method, ip, Some(ChainId::root(1)), now)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_ipv6_clients_share_their_prefix() {
+        let limiter = limiter();
+        let method = RpcMethod::HandleChainInfoQuery;
+        let now = Instant::now();
+        let ip = |address: &str| Some(address.parse::<IpAddr>().unwrap());
+
+        assert!(limiter
+            .check_at(method, ip("2001:db8::1"), None, now)
+            .is_ok());
+        assert!(limiter
+            .check_at(method, ip("2001:db8::2"), None, now)
+            .is_ok());
+        assert!(limiter
+            .check_at(method, ip("2001:db8::ffff:1"), None, now)
+            .is_err());
+        // Other subnets have their own budgets.
+        assert!(limiter
+            .check_at(method, ip("2001:db8:0:1::1"), None, now)
+            .is_ok());
+        // IPv4 addresses mapped to IPv6 are limited like the IPv4 addresses themselves.
+        assert!(limiter.check_at(method, ip("10.0.0.1"), None, now).is_ok());
+        assert!(limiter.check_at(method, ip("10.0.0.1"), None, now).is_ok());
+        assert!(limiter
+            .check_at(method, ip("::ffff:10.0.0.1"), None, now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_least_recently_used_buckets_are_removed() {
+        let limiter = limiter();
+        let method = RpcMethod::HandleChainInfoQuery;
+        let now = Instant::now();
+        let ips =
+            (0..=u32::try_from(MAX_BUCKETS).unwrap()).map(|i| IpAddr::from(Ipv4Addr::from(i)));
+        let first_ip = Some(IpAddr::from([0, 0, 0, 0]));
+        assert!(limiter.check_at(method, first_ip, None, now).is_ok());
+        assert!(limiter.check_at(method, first_ip, None, now).is_ok());
+        assert!(limiter.check_at(method, first_ip, None, now).is_err());
+        for ip in ips.skip(1) {
+            assert!(limiter.check_at(method, Some(ip), None, now).is_ok());
+        }
+        assert_eq!(limiter.buckets.lock().unwrap().len(), MAX_BUCKETS);
+        // The bucket of the first address was removed, so it has a new budget.
+        assert!(limiter.check_at(method, first_ip, None, now).is_ok());
+    }
+
+    #[test]
+    fn test_read_config() {
+        let config: RateLimitConfig = toml::from_str(
+            r#"
+            [default]
+            per_ip = { requests_per_second = 100.0, burst = 200 }
+
+            [methods.upload_blob]
+            per_chain = { requests_per_second = 5.0, burst = 10 }
+            "#,
+        )
+        .unwrap();
+        let limits = config.limits(RpcMethod::UploadBlob);
+        assert!(limits.per_ip.is_none());
+        assert_eq!(limits.per_chain.unwrap().burst, 10);
+        let limits = config.limits(RpcMethod::HandleBlockProposal);
+        assert_eq!(limits.per_ip.unwrap().burst, 200);
+        assert_eq!(config.ipv6_prefix_length, DEFAULT_IPV6_PREFIX_LENGTH);
+    }
+}
@@ -14,7 +14,10 @@ use std::{
 use anyhow::{bail, Context};
 use async_trait::async_trait;
 use futures::{stream::FuturesUnordered, FutureExt as _, StreamExt, TryFutureExt as _};
-use linera_base::crypto::{CryptoRng, KeyPair};
+use linera_base::{
+    crypto::{CryptoHash, CryptoRng, KeyPair},
+    identifiers::ChainId,
+};
 use linera_client::{
     config::{CommitteeConfig, GenesisConfig, ValidatorConfig, ValidatorServerConfig},
     persistent::{self, Persist},
@@ -24,28 +27,39 @@ use linera_core::{worker::WorkerState, JoinSetExt as _};
 use linera_execution::{committee::ValidatorName, WasmRuntime, WithWasmDefault};
 use linera_rpc::{
     config::{
-        CrossChainConfig, NetworkProtocol, NotificationConfig, ShardConfig, ShardId, TlsConfig,
+        CrossChainConfig, NetworkProtocol, NextShardMap, NotificationConfig, QuicConfig,
+        ShardAssignment, ShardConfig, ShardId, SharedInternalNetworkConfig, TlsConfig,
         ValidatorInternalNetworkConfig, ValidatorPublicNetworkConfig,
     },
+    cross_chain_queue::CrossChainQueue,
     grpc, simple,
 };
 #[cfg(with_metrics)]
 use linera_service::prometheus_server;
 use linera_service::util;
-use linera_storage::Storage;
+use linera_storage::{GarbageCollector, Storage};
 use linera_views::store::CommonStoreConfig;
 use serde::Deserialize;
 use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+/// How long to wait for the requests running for the chains moved to another shard.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
 
 struct ServerContext {
     server_config: ValidatorServerConfig,
+    server_config_path: PathBuf,
     cross_chain_config: CrossChainConfig,
     notification_config: NotificationConfig,
     shard: Option<usize>,
     grace_period: Duration,
     max_loaded_chains: NonZeroUsize,
+    garbage_collection_interval: Option<Duration>,
+    shard_map_reload_interval: Option<Duration>,
+    admin_host: String,
+    admin_port: Option<u16>,
+    admin_token: Option<String>,
 }
 
 impl ServerContext {
@@ -58,7 +72,8 @@ impl ServerContext {
     where
         S: Storage + Clone + Send + Sync + 'static,
     {
-        let shard = self.server_config.internal_network.shard(shard_id);
+        // During a handoff, new shards are numbered by the next shard map.
+        let shard = &self.server_config.internal_network.latest_shards()[shard_id];
         info!("Shard booted on {}", shard.host);
         let state = WorkerState::new(
             format!("Shard {} @ {}:{}", shard_id, local_ip_addr, shard.port),
@@ -76,6 +91,7 @@ impl ServerContext {
         &self,
         listen_address: &str,
         states: Vec<(WorkerState<S>, ShardId, ShardConfig)>,
+        storage: &S,
         protocol: simple::TransportProtocol,
         shutdown_signal: CancellationToken,
     ) -> JoinSet<()>
@@ -85,12 +101,20 @@ impl ServerContext {
         let mut join_set = JoinSet::new();
         let handles = FuturesUnordered::new();
 
-        let internal_network = self
-            .server_config
-            .internal_network
-            .clone_with_protocol(protocol);
+        let internal_network = SharedInternalNetworkConfig::new(
+            self.server_config
+                .internal_network
+                .clone_with_protocol(protocol),
+        );
+        let states = Self::restrict_to_handled_chains(states, &internal_network);
+        self.reload_shard_map(&internal_network, &states, shutdown_signal.clone());
+        self.collect_garbage(storage, &internal_network, &states, shutdown_signal.clone());
 
+        let mut admin_shards = Vec::new();
         for (state, shard_id, shard) in states {
+            let cross_chain_queue =
+                self.make_cross_chain_queue(storage, shard_id, &internal_network);
+            admin_shards.push((state.clone(), cross_chain_queue.clone()));
             let internal_network = internal_network.clone();
             let cross_chain_config = self.cross_chain_config.clone();
             let listen_address = listen_address.to_owned();
@@ -107,7 +131,9 @@ impl ServerContext {
                 state,
                 shard_id,
                 cross_chain_config,
+                cross_chain_queue,
             )
+            .with_quic_config(self.server_config.quic.clone())
             .spawn(shutdown_signal.clone(), &mut join_set);
 
             handles.push(
@@ -120,6 +146,7 @@ impl ServerContext {
             );
         }
 
+        self.spawn_admin(storage, admin_shards, shutdown_signal, &mut join_set);
         join_set.spawn_task(handles.collect::<()>());
 
         join_set
@@ -129,6 +156,7 @@ impl ServerContext {
         &self,
         listen_address: &str,
         states: Vec<(WorkerState<S>, ShardId, ShardConfig)>,
+        storage: &S,
         shutdown_signal: CancellationToken,
     ) -> JoinSet<()>
     where
@@ -137,19 +165,31 @@ impl ServerContext {
         let mut join_set = JoinSet::new();
         let handles = FuturesUnordered::new();
 
+        let internal_network =
+            SharedInternalNetworkConfig::new(self.server_config.internal_network.clone());
+        let states = Self::restrict_to_handled_chains(states, &internal_network);
+        self.reload_shard_map(&internal_network, &states, shutdown_signal.clone());
+        self.collect_garbage(storage, &internal_network, &states, shutdown_signal.clone());
+
+        let mut admin_shards = Vec::new();
         for (state, shard_id, shard) in states {
             #[cfg(with_metrics)]
             if let Some(port) = shard.metrics_port {
                 Self::start_metrics(listen_address, port, shutdown_signal.clone());
             }
 
+            let cross_chain_queue =
+                self.make_cross_chain_queue(storage, shard_id, &internal_network);
+            admin_shards.push((state.clone(), cross_chain_queue.clone()));
+
             let server_handle = grpc::GrpcServer::spawn(
                 listen_address.to_string(),
                 shard.port,
                 state,
                 shard_id,
-                self.server_config.internal_network.clone(),
+                internal_network.clone(),
                 self.cross_chain_config.clone(),
+                cross_chain_queue,
                 self.notification_config.clone(),
                 shutdown_signal.clone(),
                 &mut join_set,
@@ -165,11 +205,171 @@ impl ServerContext {
             );
         }
 
+        self.spawn_admin(storage, admin_shards, shutdown_signal, &mut join_set);
         join_set.spawn_task(handles.collect::<()>());
 
         join_set
     }
 
+    /// Creates the queue of the cross-chain requests sent by a shard. Requests are sent
+    /// to the shards of their target chains in the current shard map.
+    fn make_cross_chain_queue<S, P>(
+        &self,
+        storage: &S,
+        shard_id: ShardId,
+        internal_network: &SharedInternalNetworkConfig<P>,
+    ) -> CrossChainQueue<S>
+    where
+        S: Storage + Clone + Send + Sync + 'static,
+        P: Clone + Send + Sync + 'static,
+    {
+        let internal_network = internal_network.clone();
+        CrossChainQueue::new(
+            storage.clone(),
+            shard_id,
+            move |chain_id| internal_network.get().get_shard_id(chain_id),
+            &self.cross_chain_config,
+        )
+    }
+
+    /// Runs the administration service of the shards of this process, if requested.
+    fn spawn_admin<S>(
+        &self,
+        storage: &S,
+        shards: Vec<(WorkerState<S>, CrossChainQueue<S>)>,
+        shutdown_signal: CancellationToken,
+        join_set: &mut JoinSet<()>,
+    ) where
+        S: Storage + Clone + Send + Sync + 'static,
+    {
+        let (Some(port), Some(token)) = (self.admin_port, &self.admin_token) else {
+            return;
+        };
+        let server_handle = grpc::AdminServer::new(storage.clone(), shards).spawn(
+            self.admin_host.clone(),
+            port,
+            token.clone(),
+            shutdown_signal,
+            join_set,
+        );
+        join_set.spawn_task(
+            server_handle
+                .join()
+                .inspect_err(|error| error!("Error running admin server: {error:?}"))
+                .map(|_| ()),
+        );
+    }
+
+    /// Makes each shard reject the requests for the chains that it doesn't handle in the
+    /// current shard map, so that no two shards run the same chain.
+    fn restrict_to_handled_chains<S, P>(
+        states: Vec<(WorkerState<S>, ShardId, ShardConfig)>,
+        internal_network: &SharedInternalNetworkConfig<P>,
+    ) -> Vec<(WorkerState<S>, ShardId, ShardConfig)>
+    where
+        S: Storage + Clone + Send + Sync + 'static,
+        P: Clone + Send + Sync + 'static,
+    {
+        states
+            .into_iter()
+            .map(|(state, shard_id, shard)| {
+                let internal_network = internal_network.clone();
+                let handled_shard = shard.clone();
+                let state = state.with_handled_chains(move |chain_id| {
+                    internal_network.get().handles(chain_id, &handled_shard)
+                });
+                (state, shard_id, shard)
+            })
+            .collect()
+    }
+
+    /// Updates the shard map when a new version is written to the server configuration
+    /// file, if requested. The shards of this process then drain the chains that they
+    /// don't handle anymore, i.e. wait until the requests in progress for these chains
+    /// are done, and stop caching them, since their states may be modified elsewhere.
+    fn reload_shard_map<S, P>(
+        &self,
+        internal_network: &SharedInternalNetworkConfig<P>,
+        states: &[(WorkerState<S>, ShardId, ShardConfig)],
+        shutdown_signal: CancellationToken,
+    ) where
+        S: Storage + Clone + Send + Sync + 'static,
+        P: Clone + Send + Sync + 'static,
+    {
+        let Some(interval) = self.shard_map_reload_interval else {
+            return;
+        };
+        let shards = states
+            .iter()
+            .map(|(state, _, shard)| (state.clone(), shard.clone()))
+            .collect::<Vec<_>>();
+        tokio::spawn(util::reload_shard_map_periodically(
+            self.server_config_path.clone(),
+            internal_network.clone(),
+            interval,
+            shutdown_signal,
+            move |network| {
+                let shards = shards.clone();
+                async move {
+                    let mut undrained = Vec::new();
+                    for (state, shard) in &shards {
+                        undrained.extend(
+                            state
+                                .drain_chain_workers(
+                                    |chain_id| !network.handles(chain_id, shard),
+                                    DRAIN_TIMEOUT,
+                                )
+                                .await,
+                        );
+                    }
+                    if let Some(next) = &network.next_shard_map {
+                        if undrained.is_empty() {
+                            info!(
+                                "Drained the chains moved by version {} of the shard map",
+                                next.shard_map_version
+                            );
+                        } else {
+                            warn!(
+                                "Failed to drain {} chains moved by version {} of the shard map",
+                                undrained.len(),
+                                next.shard_map_version
+                            );
+                        }
+                    }
+                }
+            },
+        ));
+    }
+
+    /// Removes the blobs that are no longer needed periodically, if requested. Shards share
+    /// the storage, so only the process running shard 0 removes the unused blobs, but each
+    /// process removes the blobs of the abandoned proposals of its own chains.
+    fn collect_garbage<S, P>(
+        &self,
+        storage: &S,
+        internal_network: &SharedInternalNetworkConfig<P>,
+        states: &[(WorkerState<S>, ShardId, ShardConfig)],
+        shutdown_signal: CancellationToken,
+    ) where
+        S: Storage + Clone + Send + Sync + 'static,
+        P: Clone + Send + Sync + 'static,
+    {
+        let Some(interval) = self.garbage_collection_interval else {
+            return;
+        };
+        let shards = states
+            .iter()
+            .map(|(state, shard_id, _)| (state.clone(), *shard_id))
+            .collect();
+        tokio::spawn(collect_garbage_periodically(
+            storage.clone(),
+            internal_network.clone(),
+            shards,
+            interval,
+            shutdown_signal,
+        ));
+    }
+
     #[cfg(with_metrics)]
     fn start_metrics(host: &str, port: u16, shutdown_signal: CancellationToken) {
         prometheus_server::start_metrics((host.to_owned(), port), shutdown_signal);
@@ -198,11 +398,11 @@ impl Runnable for ServerContext {
         let states = match self.shard {
             Some(shard) => {
                 info!("Running shard number {}", shard);
-                vec![self.make_shard_state(&listen_address, shard, storage)]
+                vec![self.make_shard_state(&listen_address, shard, storage.clone())]
             }
             None => {
                 info!("Running all shards");
-                let num_shards = self.server_config.internal_network.shards.len();
+                let num_shards = self.server_config.internal_network.latest_shards().len();
                 (0..num_shards)
                     .map(|shard| self.make_shard_state(&listen_address, shard, storage.clone()))
                     .collect()
@@ -210,11 +410,17 @@ impl Runnable for ServerContext {
         };
 
         let mut join_set = match self.server_config.internal_network.protocol {
-            NetworkProtocol::Simple(protocol) => {
-                self.spawn_simple(&listen_address, states, protocol, shutdown_notifier)
-            }
+            NetworkProtocol::Simple(protocol) => self.spawn_simple(
+                &listen_address,
+                states,
+                &storage,
+                protocol,
+                shutdown_notifier,
+            ),
             NetworkProtocol::Grpc(tls_config) => match tls_config {
-                TlsConfig::ClearText => self.spawn_grpc(&listen_address, states, shutdown_notifier),
+                TlsConfig::ClearText => {
+                    self.spawn_grpc(&listen_address, states, &storage, shutdown_notifier)
+                }
                 TlsConfig::Tls => bail!("TLS not supported between proxy and shards."),
             },
         };
@@ -225,6 +431,71 @@ impl Runnable for ServerContext {
     }
 }
 
+/// Removes the blobs that are not used by any confirmed block, if `shards` contains
+/// shard 0, and the blobs of the abandoned proposals of the chains of `shards`, every
+/// `interval`.
+async fn collect_garbage_periodically<S, P>(
+    storage: S,
+    internal_network: SharedInternalNetworkConfig<P>,
+    shards: Vec<(WorkerState<S>, ShardId)>,
+    interval: Duration,
+    shutdown_signal: CancellationToken,
+) where
+    S: Storage + Clone + Send + Sync + 'static,
+    P: Clone + Send + Sync + 'static,
+{
+    let remove_unused_blobs = shards.iter().any(|(_, shard_id)| *shard_id == 0);
+    let mut garbage_collector = GarbageCollector::new_deferred(storage);
+    loop {
+        tokio::select! {
+            () = tokio::time::sleep(interval) => {}
+            () = shutdown_signal.cancelled() => return,
+        }
+        if remove_unused_blobs {
+            match garbage_collector.run_pass(false).await {
+                Ok(report) => info!(
+                    "Removed {} unused blobs ({} bytes), {} kept until the next pass",
+                    report.removed_blobs.len(),
+                    report.removed_bytes,
+                    report.num_deferred_blobs,
+                ),
+                Err(error) => error!("Failed to remove unused blobs: {error}"),
+            }
+        }
+        let network = internal_network.get();
+        let worker_for = |chain_id| {
+            let shard_id = network.get_shard_id(chain_id);
+            shards
+                .iter()
+                .find_map(|(state, id)| (*id == shard_id).then_some(state))
+        };
+        let abandoned = match garbage_collector
+            .find_abandoned_proposals(|chain_id| worker_for(chain_id).is_some())
+            .await
+        {
+            Ok(abandoned) => abandoned,
+            Err(error) => {
+                error!("Failed to find abandoned proposals: {error}");
+                continue;
+            }
+        };
+        let mut num_removed_blobs = 0;
+        for (chain_id, progress) in abandoned {
+            let Some(state) = worker_for(chain_id) else {
+                continue;
+            };
+            match state.remove_pending_blobs(chain_id, progress).await {
+                Ok(blobs) => num_removed_blobs += blobs.len(),
+                Err(error) => error!(
+                    "Failed to remove the blobs of an abandoned proposal in chain {chain_id}: \
+                     {error}"
+                ),
+            }
+        }
+        info!("Removed {num_removed_blobs} blobs of abandoned proposals");
+    }
+}
+
 #[derive(clap::Parser)]
 #[command(
     name = "linera-server",
@@ -272,6 +543,14 @@ struct ValidatorOptions {
 
     /// The public name and the port of each of the shards
     shards: Vec<ShardConfig>,
+
+    /// How the chains are assigned to the shards.
+    #[serde(default)]
+    shard_assignment: ShardAssignment,
+
+    /// The certificate of the proxy and the shards, if they use QUIC.
+    #[serde(default)]
+    quic: QuicConfig,
 }
 
 fn make_server_config<R: CryptoRng>(
@@ -290,6 +569,9 @@ fn make_server_config<R: CryptoRng>(
         name,
         protocol: options.internal_protocol,
         shards: options.shards,
+        shard_assignment: options.shard_assignment,
+        shard_map_version: 0,
+        next_shard_map: None,
         host: options.internal_host,
         port: options.internal_port,
         metrics_host: options.metrics_host,
@@ -302,6 +584,7 @@ fn make_server_config<R: CryptoRng>(
             validator,
             key,
             internal_network,
+            quic: options.quic,
         },
     )?)
 }
@@ -359,6 +642,33 @@ enum ServerCommand {
         /// The maximal number of entries in the storage cache.
         #[arg(long, default_value = "1000")]
         cache_size: usize,
+
+        /// If set, removes the blobs that are not used by any confirmed block, and the
+        /// blobs of the proposals of chains that made no progress, at this interval. Blobs
+        /// are only removed once they were unneeded for a whole interval.
+        #[arg(long = "blob-garbage-collection-interval-secs", value_parser = util::parse_secs)]
+        garbage_collection_interval: Option<Duration>,
+
+        /// If set, reads the server configuration file at this interval and uses its shard
+        /// map when its version is more recent. This allows adding and removing shards
+        /// without restarting the validator: see `edit-shards` and `switch-shards`.
+        #[arg(long = "shard-map-reload-interval-secs", value_parser = util::parse_secs)]
+        shard_map_reload_interval: Option<Duration>,
+
+        /// If set, serves the gRPC administration service of the shards run by this
+        /// process on this port, e.g. to inspect the cross-chain requests that could not be
+        /// delivered. See `linera-server admin`.
+        #[arg(long, requires = "admin_token")]
+        admin_port: Option<u16>,
+
+        /// The address on which the administration service listens. Its token is sent in
+        /// clear text, so it should only be reachable from trusted hosts.
+        #[arg(long, default_value = "127.0.0.1")]
+        admin_host: String,
+
+        /// The token that the requests to the administration service must present.
+        #[arg(long, env = "LINERA_SERVER_ADMIN_TOKEN", hide_env_values = true)]
+        admin_token: Option<String>,
     },
 
     /// Act as a trusted third-party and generate all server configurations
@@ -402,7 +712,21 @@ enum ServerCommand {
         cache_size: usize,
     },
 
-    /// Replaces the configurations of the shards by following the given template.
+    /// Prepares new configurations of the shards by following the given template.
+    ///
+    /// The new shard map is written as the next one, with an incremented version, and
+    /// only replaces the current one with `switch-shards`. To add or remove shards
+    /// without restarting the validator, with `--shard-map-reload-interval-secs`:
+    ///
+    /// 1. Run this command, and start the new shards with their index in the new shard
+    ///    map. The shards stop handling the chains that are moved to another shard.
+    /// 2. Once every shard has logged that it drained the chains moved by the new
+    ///    version, i.e. after at least one reload interval, run `switch-shards`.
+    /// 3. Once the reload interval has elapsed again, stop the removed shards.
+    ///
+    /// The chains that are moved are unavailable between the first two steps. With
+    /// `--shard-assignment consistent-hashing`, this only moves the chains of the added or
+    /// removed shards.
     #[command(name = "edit-shards")]
     EditShards {
         /// Path to the file containing the server configuration of this Linera validator.
@@ -434,6 +758,70 @@ enum ServerCommand {
         /// shard number.
         #[arg(long)]
         metrics_port: Option<String>,
+
+        /// How the chains are assigned to the shards. Unchanged if not set.
+        #[arg(long, value_enum)]
+        shard_assignment: Option<ShardAssignment>,
+    },
+
+    /// Replaces the shard map by the one prepared with `edit-shards`. This must only run
+    /// once every shard has drained the chains that the new shard map moves.
+    #[command(name = "switch-shards")]
+    SwitchShards {
+        /// Path to the file containing the server configuration of this Linera validator.
+        #[arg(long = "server")]
+        server_config_path: PathBuf,
+    },
+
+    /// Sends a request to the administration service of a running server, enabled with
+    /// `run --admin-port`.
+    #[command(name = "admin")]
+    Admin {
+        /// The address of the administration service, e.g. `http://127.0.0.1:9200`.
+        #[arg(long)]
+        address: String,
+
+        /// The token of the administration service.
+        #[arg(long, env = "LINERA_SERVER_ADMIN_TOKEN", hide_env_values = true)]
+        admin_token: String,
+
+        #[command(subcommand)]
+        command: AdminCommand,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum AdminCommand {
+    /// Lists the chains loaded in memory by each shard.
+    #[command(name = "list-chain-workers")]
+    ListChainWorkers,
+
+    /// Shows the consensus state of a chain and the sizes of its inboxes and outboxes.
+    #[command(name = "show-chain")]
+    ShowChain { chain_id: ChainId },
+
+    /// Shows the cross-chain requests that the shards have not delivered yet.
+    #[command(name = "cross-chain-queue")]
+    CrossChainQueue,
+
+    /// Shows the statistics of the caches of each shard.
+    #[command(name = "cache-stats")]
+    CacheStats,
+
+    /// Unloads a chain from memory. Its state is loaded from storage again when needed.
+    #[command(name = "evict-chain-worker")]
+    EvictChainWorker { chain_id: ChainId },
+
+    /// Sends the messages in the outboxes of a chain to their recipients again.
+    #[command(name = "resend-outbox-messages")]
+    ResendOutboxMessages { chain_id: ChainId },
+
+    /// Writes a point-in-time snapshot of the storage of the server, which keeps running.
+    /// It can be restored with `linera storage restore`.
+    #[command(name = "snapshot-storage")]
+    SnapshotStorage {
+        /// The directory of the snapshot on the host of the server. It must not exist yet.
+        path: PathBuf,
     },
 }
 
@@ -482,7 +870,9 @@ fn log_file_name_for(command: &ServerCommand) -> Cow<'static, str> {
         }
         ServerCommand::Generate { .. }
         | ServerCommand::Initialize { .. }
-        | ServerCommand::EditShards { .. } => "server".into(),
+        | ServerCommand::EditShards { .. }
+        | ServerCommand::SwitchShards { .. }
+        | ServerCommand::Admin { .. } => "server".into(),
     }
 }
 
@@ -501,6 +891,11 @@ async fn run(options: ServerOptions) {
             max_concurrent_queries,
             max_stream_queries,
             cache_size,
+            garbage_collection_interval,
+            shard_map_reload_interval,
+            admin_host,
+            admin_port,
+            admin_token,
         } => {
             linera_version::VERSION_INFO.log();
 
@@ -515,20 +910,34 @@ async fn run(options: ServerOptions) {
             {
                 panic!("Multiple shards not supported with RocksDB");
             }
+            #[cfg(feature = "redb")]
+            if server_config.internal_network.shards.len() > 1
+                && storage_config.storage_config.is_redb()
+            {
+                panic!("Multiple shards not supported with redb");
+            }
 
             let job = ServerContext {
                 server_config,
+                server_config_path,
                 cross_chain_config,
                 notification_config,
                 shard,
                 grace_period,
                 max_loaded_chains,
+                garbage_collection_interval,
+                shard_map_reload_interval,
+                admin_host,
+                admin_port,
+                admin_token,
             };
             let wasm_runtime = wasm_runtime.with_wasm_default();
             let common_config = CommonStoreConfig {
                 max_concurrent_queries,
                 max_stream_queries,
                 cache_size,
+                compression_threshold: None,
+                encryption_key: None,
             };
             let full_storage_config = storage_config
                 .add_common_config(common_config)
@@ -592,6 +1001,8 @@ async fn run(options: ServerOptions) {
                 max_concurrent_queries,
                 max_stream_queries,
                 cache_size,
+                compression_threshold: None,
+                encryption_key: None,
             };
             let full_storage_config = storage_config
                 .add_common_config(common_config)
@@ -609,20 +1020,171 @@ async fn run(options: ServerOptions) {
             port,
             metrics_host,
             metrics_port,
+            shard_assignment,
         } => {
             let mut server_config =
                 persistent::File::<ValidatorServerConfig>::read(&server_config_path)
                     .expect("Failed to read server config");
             let shards = generate_shard_configs(num_shards, host, port, metrics_host, metrics_port)
                 .expect("Failed to generate shard configs");
-            server_config.internal_network.shards = shards;
+            let internal_network = &mut server_config.internal_network;
+            internal_network.next_shard_map = Some(NextShardMap {
+                shards,
+                shard_assignment: shard_assignment.unwrap_or(internal_network.shard_assignment),
+                shard_map_version: internal_network.shard_map_version + 1,
+            });
             Persist::persist(&mut server_config)
                 .await
                 .expect("Failed to write updated server config");
         }
+
+        ServerCommand::SwitchShards { server_config_path } => {
+            let mut server_config =
+                persistent::File::<ValidatorServerConfig>::read(&server_config_path)
+                    .expect("Failed to read server config");
+            let internal_network = &mut server_config.internal_network;
+            let next = internal_network
+                .next_shard_map
+                .take()
+                .expect("No shard map was prepared with `edit-shards`");
+            internal_network.shards = next.shards;
+            internal_network.shard_assignment = next.shard_assignment;
+            internal_network.shard_map_version = next.shard_map_version;
+            Persist::persist(&mut server_config)
+                .await
+                .expect("Failed to write updated server config");
+        }
+
+        ServerCommand::Admin {
+            address,
+            admin_token,
+            command,
+        } => {
+            run_admin_command(address, &admin_token, command)
+                .await
+                .expect("Failed to run admin command");
+        }
     }
 }
 
+async fn run_admin_command(
+    address: String,
+    token: &str,
+    command: AdminCommand,
+) -> anyhow::Result<()> {
+    let mut client = grpc::AdminClient::new(address, token)?;
+    match command {
+        AdminCommand::ListChainWorkers => {
+            for shard in client.chain_workers().await?.shards {
+                println!(
+                    "Shard {}: {} chains loaded",
+                    shard.shard_id,
+                    shard.chain_ids.len()
+                );
+                for chain_id in shard.chain_ids {
+                    println!("  {}", ChainId::try_from(chain_id)?);
+                }
+            }
+        }
+
+        AdminCommand::ShowChain { chain_id } => {
+            let summary = client.chain_state(chain_id).await?;
+            println!("Chain {chain_id} on shard {}", summary.shard_id);
+            println!("  Next block height: {}", summary.next_block_height);
+            println!("  Current round: {}", summary.current_round);
+            match summary.locked_block {
+                Some(locked) => {
+                    print!(
+                        "  Locked block: height {} in round {}",
+                        locked.height, locked.round
+                    );
+                    if let Some(hash) = locked.hash {
+                        print!(", hash {}", CryptoHash::try_from(hash)?);
+                    }
+                    println!();
+                }
+                None => println!("  Locked block: none"),
+            }
+            println!("  Inboxes:");
+            print_queue_sizes(summary.inboxes)?;
+            println!("  Outboxes:");
+            print_queue_sizes(summary.outboxes)?;
+            println!(
+                "  Pending cross-chain requests: {}",
+                summary.pending_cross_chain_requests
+            );
+        }
+
+        AdminCommand::CrossChainQueue => {
+            let targets = client.cross_chain_queue().await?.targets;
+            if targets.is_empty() {
+                println!("No pending cross-chain requests");
+            }
+            for target in targets {
+                println!(
+                    "Shard {} -> shard {}: {} requests, oldest pending for {} ms",
+                    target.source_shard_id,
+                    target.target_shard_id,
+                    target.num_requests,
+                    target.oldest_request_age_ms
+                );
+                for request in target.stuck_requests {
+                    let origin = request.origin.context("missing origin")?;
+                    let recipient = request.target.context("missing target")?;
+                    println!(
+                        "  Stuck request from {} to {}: {} attempts in {} ms, last error: {}",
+                        ChainId::try_from(origin)?,
+                        ChainId::try_from(recipient)?,
+                        request.attempts,
+                        request.age_ms,
+                        request.last_error.as_deref().unwrap_or("none")
+                    );
+                }
+            }
+        }
+
+        AdminCommand::CacheStats => {
+            for shard in client.cache_stats().await?.shards {
+                let stats = shard.executed_blocks.unwrap_or_default();
+                println!(
+                    "Shard {}: executed blocks {}/{} entries, {} hits, {} misses",
+                    shard.shard_id, stats.size, stats.capacity, stats.hits, stats.misses
+                );
+            }
+        }
+
+        AdminCommand::EvictChainWorker { chain_id } => {
+            client.evict_chain_worker(chain_id).await?;
+            println!("Evicted the worker of chain {chain_id}");
+        }
+
+        AdminCommand::ResendOutboxMessages { chain_id } => {
+            let num_requests = client.resend_outbox_messages(chain_id).await?;
+            println!("Scheduled {num_requests} cross-chain requests from chain {chain_id}");
+        }
+
+        AdminCommand::SnapshotStorage { path } => {
+            client.snapshot_storage(&path).await?;
+            println!("Wrote a snapshot of the storage to {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn print_queue_sizes(queues: Vec<grpc::api::QueueSize>) -> anyhow::Result<()> {
+    if queues.is_empty() {
+        println!("    none");
+    }
+    for queue in queues {
+        let chain_id = ChainId::try_from(queue.chain_id.context("missing chain ID")?)?;
+        match queue.channel {
+            Some(channel) => println!("    {chain_id} (channel {channel}): {}", queue.size),
+            None => println!("    {chain_id}: {}", queue.size),
+        }
+    }
+    Ok(())
+}
+
 fn generate_shard_configs(
     num_shards: String,
     host: String,
@@ -694,6 +1256,10 @@ mod test {
             port = 9002
             metrics_host = "metrics_host2"
             metrics_port = 5002
+
+            [quic]
+            certificate_path = "quic_cert.pem"
+            private_key_path = "quic_key.pem"
         "#;
         let options: ValidatorOptions = toml::from_str(toml_str).unwrap();
         assert_eq!(
@@ -722,6 +1288,12 @@ mod test {
                         metrics_port: Some(5002),
                     },
                 ],
+                shard_assignment: ShardAssignment::Modulo,
+                quic: QuicConfig {
+                    certificate_path: Some("quic_cert.pem".into()),
+                    private_key_path: Some("quic_key.pem".into()),
+                    trusted_roots_path: None,
+                },
             }
         );
     }
@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
+    future::Future,
     io::{BufRead, BufReader, Write},
     num::ParseIntError,
-    path::Path,
+    path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
 
@@ -15,10 +17,12 @@ use http::Uri;
 #[cfg(test)]
 use linera_base::command::parse_version_message;
 use linera_base::data_types::TimeDelta;
+use linera_client::config::ValidatorServerConfig;
 pub use linera_client::util::*;
+use linera_rpc::config::{SharedInternalNetworkConfig, ValidatorInternalNetworkPreConfig};
 use tokio::signal::unix;
 use tokio_util::sync::CancellationToken;
-use tracing::debug;
+use tracing::{debug, error, info};
 
 /// Extension trait for [`tokio::process::Child`].
 pub trait ChildExt: std::fmt::Debug {
@@ -57,6 +61,54 @@ pub async fn listen_for_shutdown_signals(shutdown_sender: CancellationToken) {
         _ = sighup.recv() => debug!("Received SIGHUP"),
     }
 }
+/// Reads the server configuration at `path` every `interval`, and updates the shard map of
+/// `internal_network` whenever the file contains a more recent version of it or of the
+/// next shard map. Then `on_update` is called with the new configuration, and awaited
+/// before the file is read again.
+/// `on_update` is called with the new configuration.
+pub async fn reload_shard_map_periodically<P, F>(
+    path: PathBuf,
+    internal_network: SharedInternalNetworkConfig<P>,
+    interval: Duration,
+    shutdown_signal: CancellationToken,
+    on_update: impl Fn(Arc<ValidatorInternalNetworkPreConfig<P>>) -> F,
+) where
+    P: Clone,
+    F: Future<Output = ()>,
+{
+    loop {
+        tokio::select! {
+            () = tokio::time::sleep(interval) => {}
+            () = shutdown_signal.cancelled() => return,
+        }
+        let server_config = match read_json::<ValidatorServerConfig>(&path) {
+            Ok(server_config) => server_config,
+            Err(error) => {
+                error!(
+                    "Failed to read the shard map from {}: {error}",
+                    path.display()
+                );
+                continue;
+            }
+        };
+        if internal_network.update_shard_map(&server_config.internal_network) {
+            let network = internal_network.get();
+            match &network.next_shard_map {
+                Some(next) => info!(
+                    "Handing off the chains moved by version {} of the shard map, with {} shards",
+                    next.shard_map_version,
+                    next.shards.len()
+                ),
+                None => info!(
+                    "Using version {} of the shard map, with {} shards",
+                    network.shard_map_version,
+                    network.shards.len()
+                ),
+            }
+            on_update(network).await;
+        }
+    }
+}
 
 pub fn read_json<T: serde::de::DeserializeOwned>(path: impl Into<std::path::PathBuf>) -> Result<T> {
     Ok(serde_json::from_reader(fs_err::File::open(path)?)?)
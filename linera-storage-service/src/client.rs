@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{mem, sync::Arc};
+use std::{mem, path::Path, sync::Arc};
 
 use async_lock::{Semaphore, SemaphoreGuard};
 use linera_base::ensure;
@@ -480,6 +480,18 @@ impl AdminKeyValueStore for ServiceStoreClientInternal {
         let _response = client.process_delete_namespace(request).await?;
         Ok(())
     }
+
+    async fn snapshot(&self, _path: &Path) -> Result<(), ServiceStoreError> {
+        Err(ServiceStoreError::SnapshotNotSupported)
+    }
+
+    async fn restore(
+        _config: &Self::Config,
+        _namespace: &str,
+        _path: &Path,
+    ) -> Result<(), ServiceStoreError> {
+        Err(ServiceStoreError::SnapshotNotSupported)
+    }
 }
 
 #[cfg(with_testing)]
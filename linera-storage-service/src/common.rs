@@ -59,6 +59,10 @@ pub enum ServiceStoreError {
     /// An error occurred during BCS serialization
     #[error(transparent)]
     BcsError(#[from] bcs::Error),
+
+    /// Snapshots have to be taken on the storage of the server
+    #[error("Snapshots are not supported by the storage service, snapshot the storage of the server instead")]
+    SnapshotNotSupported,
 }
 
 impl KeyValueStoreError for ServiceStoreError {
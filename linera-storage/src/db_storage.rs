@@ -3,7 +3,12 @@
 
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    fmt::Debug,
+    ops::Bound::{Excluded, Unbounded},
+    path::Path,
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use dashmap::DashMap;
@@ -11,7 +16,7 @@ use linera_base::{
     crypto::CryptoHash,
     data_types::{Blob, TimeDelta, Timestamp},
     hashed::Hashed,
-    identifiers::{BlobId, ChainId, UserApplicationId},
+    identifiers::{BlobId, BlobType, ChainId, EventId, UserApplicationId},
 };
 use linera_chain::{
     types::{ConfirmedBlock, ConfirmedBlockCertificate, LiteCertificate},
@@ -25,7 +30,7 @@ use linera_views::{
     backends::dual::{DualStoreRootKeyAssignment, StoreInUse},
     batch::Batch,
     context::ViewContext,
-    store::KeyValueStore,
+    store::{KeyIterable as _, KeyRange, KeyValueIterable as _, KeyValueStore},
     views::{View, ViewError},
 };
 use serde::{Deserialize, Serialize};
@@ -43,7 +48,10 @@ use {
     prometheus::{HistogramVec, IntCounterVec},
 };
 
-use crate::{ChainRuntimeContext, Clock, Storage};
+use crate::{
+    migration::{check_schema_version, initialize_schema_version},
+    ChainRuntimeContext, Clock, Storage,
+};
 
 /// The metric counting how often a blob is tested for existence from storage
 #[cfg(with_metrics)]
@@ -140,6 +148,28 @@ pub static WRITE_BLOB_COUNTER: LazyLock<IntCounterVec> = LazyLock::new(|| {
     )
 });
 
+/// The metric counting how often an event is read from storage.
+#[cfg(with_metrics)]
+#[doc(hidden)]
+pub static READ_EVENT_COUNTER: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "read_event",
+        "The metric counting how often an event is read from storage",
+        &[],
+    )
+});
+
+/// The metric counting how often an event is written to storage.
+#[cfg(with_metrics)]
+#[doc(hidden)]
+pub static WRITE_EVENT_COUNTER: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "write_event",
+        "The metric counting how often an event is written to storage",
+        &[],
+    )
+});
+
 /// The metric counting how often a certificate is read from storage.
 #[cfg(with_metrics)]
 #[doc(hidden)]
@@ -192,6 +222,8 @@ trait BatchExt {
 
     fn add_certificate(&mut self, certificate: &ConfirmedBlockCertificate)
         -> Result<(), ViewError>;
+
+    fn add_event(&mut self, event_id: EventId, value: &[u8]) -> Result<(), ViewError>;
 }
 
 impl BatchExt for Batch {
@@ -222,6 +254,14 @@ impl BatchExt for Batch {
         self.put_key_value(value_key.to_vec(), certificate.value())?;
         Ok(())
     }
+
+    fn add_event(&mut self, event_id: EventId, value: &[u8]) -> Result<(), ViewError> {
+        #[cfg(with_metrics)]
+        WRITE_EVENT_COUNTER.with_label_values(&[]).inc();
+        let event_key = bcs::to_bytes(&BaseKey::Event(event_id))?;
+        self.put_key_value_bytes(event_key, value.to_vec());
+        Ok(())
+    }
 }
 
 /// Main implementation of the [`Storage`] trait.
@@ -236,12 +276,44 @@ pub struct DbStorage<Store, Clock> {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-enum BaseKey {
+pub(crate) enum BaseKey {
     ChainState(ChainId),
     Certificate(CryptoHash),
     ConfirmedBlock(CryptoHash),
     Blob(BlobId),
     BlobState(BlobId),
+    Event(EventId),
+    SchemaVersion,
+    PendingCrossChainRequest(CryptoHash),
+    PendingBlobsChain(ChainId),
+}
+
+impl BaseKey {
+    /// Returns the prefix shared by the serializations of all the `BaseKey::Blob` keys.
+    fn blob_prefix() -> Result<Vec<u8>, bcs::Error> {
+        let blob_id = BlobId::new(CryptoHash::from([0; 4]), BlobType::Data);
+        let mut prefix = bcs::to_bytes(&BaseKey::Blob(blob_id))?;
+        prefix.truncate(prefix.len() - bcs::serialized_size(&blob_id)?);
+        Ok(prefix)
+    }
+
+    /// Returns the prefix shared by the serializations of all the
+    /// `BaseKey::PendingCrossChainRequest` keys.
+    fn pending_cross_chain_request_prefix() -> Result<Vec<u8>, bcs::Error> {
+        let id = CryptoHash::from([0; 4]);
+        let mut prefix = bcs::to_bytes(&BaseKey::PendingCrossChainRequest(id))?;
+        prefix.truncate(prefix.len() - bcs::serialized_size(&id)?);
+        Ok(prefix)
+    }
+
+    /// Returns the prefix shared by the serializations of all the
+    /// `BaseKey::PendingBlobsChain` keys.
+    fn pending_blobs_chain_prefix() -> Result<Vec<u8>, bcs::Error> {
+        let chain_id = ChainId::root(0);
+        let mut prefix = bcs::to_bytes(&BaseKey::PendingBlobsChain(chain_id))?;
+        prefix.truncate(prefix.len() - bcs::serialized_size(&chain_id)?);
+        Ok(prefix)
+    }
 }
 
 /// An implementation of [`DualStoreRootKeyAssignment`] that stores the
@@ -543,6 +615,30 @@ where
         Ok(())
     }
 
+    async fn contains_event(&self, event_id: EventId) -> Result<bool, ViewError> {
+        let event_key = bcs::to_bytes(&BaseKey::Event(event_id))?;
+        Ok(self.store.contains_key(&event_key).await?)
+    }
+
+    async fn read_event(&self, event_id: EventId) -> Result<Vec<u8>, ViewError> {
+        let event_key = bcs::to_bytes(&BaseKey::Event(event_id.clone()))?;
+        let maybe_value = self.store.read_value_bytes(&event_key).await?;
+        #[cfg(with_metrics)]
+        READ_EVENT_COUNTER.with_label_values(&[]).inc();
+        maybe_value.ok_or_else(|| ViewError::EventsNotFound(vec![event_id]))
+    }
+
+    async fn write_events(
+        &self,
+        events: impl IntoIterator<Item = (EventId, Vec<u8>)> + Send,
+    ) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        for (event_id, value) in events {
+            batch.add_event(event_id, &value)?;
+        }
+        self.write_batch(batch).await
+    }
+
     async fn maybe_write_blob_state(
         &self,
         blob_id: BlobId,
@@ -646,6 +742,118 @@ where
         self.write_batch(batch).await
     }
 
+    async fn list_blob_ids(
+        &self,
+        after: Option<BlobId>,
+        limit: usize,
+    ) -> Result<Vec<BlobId>, ViewError> {
+        let prefix = BaseKey::blob_prefix()?;
+        let start = match after {
+            Some(blob_id) => Excluded(bcs::to_bytes(&blob_id)?),
+            None => Unbounded,
+        };
+        let range = KeyRange::from_bounds(start, Unbounded).with_limit(limit);
+        self.store
+            .find_keys_in_range(&prefix, &range)
+            .await?
+            .iter()
+            .map(|key| Ok(bcs::from_bytes(key)?))
+            .collect()
+    }
+
+    async fn missing_blob_states(&self, blob_ids: &[BlobId]) -> Result<Vec<BlobId>, ViewError> {
+        if blob_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let blob_state_keys = blob_ids
+            .iter()
+            .map(|blob_id| bcs::to_bytes(&BaseKey::BlobState(*blob_id)))
+            .collect::<Result<_, _>>()?;
+        let results = self.store.contains_keys(blob_state_keys).await?;
+        Ok(blob_ids
+            .iter()
+            .zip(results)
+            .filter_map(|(blob_id, result)| (!result).then_some(*blob_id))
+            .collect())
+    }
+
+    async fn delete_blobs(&self, blob_ids: &[BlobId]) -> Result<(), ViewError> {
+        if blob_ids.is_empty() {
+            return Ok(());
+        }
+        let mut batch = Batch::new();
+        for blob_id in blob_ids {
+            batch.delete_key(bcs::to_bytes(&BaseKey::Blob(*blob_id))?);
+        }
+        self.write_batch(batch).await
+    }
+
+    async fn write_pending_blobs_chain(&self, chain_id: ChainId) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        let key = bcs::to_bytes(&BaseKey::PendingBlobsChain(chain_id))?;
+        batch.put_key_value_bytes(key, Vec::new());
+        self.write_batch(batch).await
+    }
+
+    async fn delete_pending_blobs_chains(&self, chain_ids: &[ChainId]) -> Result<(), ViewError> {
+        if chain_ids.is_empty() {
+            return Ok(());
+        }
+        let mut batch = Batch::new();
+        for chain_id in chain_ids {
+            batch.delete_key(bcs::to_bytes(&BaseKey::PendingBlobsChain(*chain_id))?);
+        }
+        self.write_batch(batch).await
+    }
+
+    async fn read_pending_blobs_chains(&self) -> Result<Vec<ChainId>, ViewError> {
+        let prefix = BaseKey::pending_blobs_chain_prefix()?;
+        let mut chain_ids = Vec::new();
+        for key in self.store.find_keys_by_prefix(&prefix).await?.iterator() {
+            chain_ids.push(bcs::from_bytes(key?)?);
+        }
+        Ok(chain_ids)
+    }
+
+    async fn write_pending_cross_chain_request(
+        &self,
+        id: CryptoHash,
+        request: &[u8],
+    ) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        let key = bcs::to_bytes(&BaseKey::PendingCrossChainRequest(id))?;
+        batch.put_key_value_bytes(key, request.to_vec());
+        self.write_batch(batch).await
+    }
+
+    async fn delete_pending_cross_chain_request(&self, id: CryptoHash) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        batch.delete_key(bcs::to_bytes(&BaseKey::PendingCrossChainRequest(id))?);
+        self.write_batch(batch).await
+    }
+
+    async fn read_pending_cross_chain_requests(
+        &self,
+    ) -> Result<Vec<(CryptoHash, Vec<u8>)>, ViewError> {
+        let prefix = BaseKey::pending_cross_chain_request_prefix()?;
+        let mut requests = Vec::new();
+        for entry in self
+            .store
+            .find_key_values_by_prefix(&prefix)
+            .await?
+            .into_iterator_owned()
+        {
+            let (key, value) = entry?;
+            requests.push((bcs::from_bytes(&key)?, value));
+        }
+        Ok(requests)
+    }
+
+    async fn snapshot(&self, path: &Path) -> Result<(), ViewError> {
+        self.store.snapshot(path).await?;
+        Ok(())
+    }
+
     async fn write_blobs_and_certificate(
         &self,
         blobs: &[Blob],
@@ -656,6 +864,15 @@ where
             batch.add_blob(blob)?;
         }
         batch.add_certificate(certificate)?;
+        let block = certificate.block();
+        for event in block.body.events.iter().flatten() {
+            let event_id = EventId {
+                chain_id: block.header.chain_id,
+                stream_id: event.stream_id.clone(),
+                key: event.key.clone(),
+            };
+            batch.add_event(event_id, &event.value)?;
+        }
         self.write_batch(batch).await
     }
 
@@ -766,23 +983,31 @@ where
     Store: KeyValueStore + Clone + Send + Sync + 'static,
     Store::Error: Send + Sync,
 {
+    /// Connects to the storage, creating it if needed. The schema version of existing data
+    /// is checked, see [`check_schema_version`].
     pub async fn initialize(
         config: Store::Config,
         namespace: &str,
         root_key: &[u8],
         wasm_runtime: Option<WasmRuntime>,
-    ) -> Result<Self, Store::Error> {
+    ) -> Result<Self, ViewError> {
         let store = Store::maybe_create_and_connect(&config, namespace, root_key).await?;
+        initialize_schema_version(&store).await?;
+        check_schema_version(&store).await?;
         Ok(Self::create(store, wasm_runtime, WallClock))
     }
 
+    /// Connects to an existing storage, checking the schema version of its data, see
+    /// [`check_schema_version`]. Older data must be upgraded with
+    /// [`migrate`](crate::migrate) first.
     pub async fn new(
         config: Store::Config,
         namespace: &str,
         root_key: &[u8],
         wasm_runtime: Option<WasmRuntime>,
-    ) -> Result<Self, Store::Error> {
+    ) -> Result<Self, ViewError> {
         let store = Store::connect(&config, namespace, root_key).await?;
+        check_schema_version(&store).await?;
         Ok(Self::create(store, wasm_runtime, WallClock))
     }
 }
@@ -814,8 +1039,9 @@ where
         root_key: &[u8],
         wasm_runtime: Option<WasmRuntime>,
         clock: TestClock,
-    ) -> Result<Self, Store::Error> {
+    ) -> Result<Self, ViewError> {
         let store = Store::recreate_and_connect(&config, namespace, root_key).await?;
+        initialize_schema_version(&store).await?;
         Ok(Self::create(store, wasm_runtime, clock))
     }
 }
@@ -0,0 +1,491 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Removal of the blobs that are no longer needed by a validator.
+//!
+//! A blob is kept in two places:
+//! * Blobs used by confirmed blocks are written to storage together with a blob state.
+//!   Blobs without a blob state are not used by any confirmed block, and are removed.
+//! * Blobs of blocks that are not confirmed yet are kept in the chain states: the blobs of
+//!   the proposal and of the locked block in the chain manager, and those of a validated
+//!   block that is still missing some. They are removed once the proposal is abandoned,
+//!   i.e. the chain made no progress since the previous pass. The proposal and the locked
+//!   block themselves are kept, so the validator still refuses conflicting blocks, and
+//!   clients provide the blobs again if they retry.
+//!
+//! The chain states of a running validator are owned by its chain workers, so the
+//! [`GarbageCollector`] only finds the abandoned proposals and the workers remove their
+//! blobs. The chain states of a storage that is not in use are updated directly, by
+//! [`collect_garbage_offline`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use linera_base::identifiers::{BlobId, ChainId};
+use linera_chain::ChainProgress;
+use linera_views::{
+    store::KeyValueStore,
+    views::{RootView as _, ViewError},
+};
+
+use crate::{DbStorage, Storage, WallClock};
+
+/// The maximal number of blobs read or deleted at once.
+const BLOB_BATCH_SIZE: usize = 100;
+
+/// The outcome of a garbage collection pass.
+#[derive(Clone, Debug, Default)]
+pub struct GarbageCollectionReport {
+    /// Whether the pass only reported the blobs to remove, without removing them.
+    pub dry_run: bool,
+    /// The number of blobs in storage before the pass.
+    pub num_blobs: usize,
+    /// The blobs not used by any confirmed block that were removed, or would have been
+    /// in a dry run.
+    pub removed_blobs: Vec<BlobId>,
+    /// The total size of the removed blobs, in bytes.
+    pub removed_bytes: u64,
+    /// The number of blobs not used by any confirmed block that are kept until the next
+    /// pass, because they were not found by the previous one.
+    pub num_deferred_blobs: usize,
+    /// The chains whose abandoned proposals kept blobs that were removed, or would have
+    /// been in a dry run.
+    pub abandoned_chains: Vec<ChainId>,
+    /// The blobs kept in chain states for abandoned proposals that were removed, or would
+    /// have been in a dry run.
+    pub removed_pending_blobs: Vec<BlobId>,
+    /// The total size of the blobs removed from chain states, in bytes.
+    pub removed_pending_bytes: u64,
+}
+
+impl fmt::Display for GarbageCollectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = if self.dry_run {
+            "would be removed"
+        } else {
+            "removed"
+        };
+        writeln!(
+            f,
+            "{} of {} blobs {action} ({} bytes), {} kept until the next pass",
+            self.removed_blobs.len(),
+            self.num_blobs,
+            self.removed_bytes,
+            self.num_deferred_blobs,
+        )?;
+        for blob_id in &self.removed_blobs {
+            writeln!(f, "  {blob_id}")?;
+        }
+        writeln!(
+            f,
+            "{} blobs of abandoned proposals in {} chains {action} ({} bytes)",
+            self.removed_pending_blobs.len(),
+            self.abandoned_chains.len(),
+            self.removed_pending_bytes,
+        )?;
+        for chain_id in &self.abandoned_chains {
+            writeln!(f, "  {chain_id}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes the blobs that are not used by any confirmed block, and the blobs of abandoned
+/// proposals.
+pub struct GarbageCollector<S> {
+    storage: S,
+    /// Whether the storage is in use by a validator: blobs are only removed if the
+    /// previous pass already found them unused, and chain states are not modified.
+    deferred: bool,
+    /// The unused blobs found by the previous pass.
+    candidates: BTreeSet<BlobId>,
+    /// The progress of the chains with pending blobs found by the previous pass.
+    pending_chains: BTreeMap<ChainId, ChainProgress>,
+}
+
+impl<S> GarbageCollector<S>
+where
+    S: Storage + Send + Sync,
+{
+    /// Creates a garbage collector for a storage that is not in use. Each pass removes
+    /// all the unused blobs and all the blobs kept for blocks that are not confirmed yet,
+    /// so this must not run against a storage in use by a validator.
+    fn new(storage: S) -> Self {
+        Self {
+            storage,
+            deferred: false,
+            candidates: BTreeSet::new(),
+            pending_chains: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a garbage collector for a storage in use by a validator, only removing the
+    /// blobs that were already unused in the previous pass. This leaves time for the blob
+    /// states of confirmed blocks to be written after their blobs. The blobs of abandoned
+    /// proposals are found by [`Self::find_abandoned_proposals`], to be removed by the
+    /// chain workers.
+    pub fn new_deferred(storage: S) -> Self {
+        Self {
+            deferred: true,
+            ..Self::new(storage)
+        }
+    }
+
+    /// Finds the blobs that are not used by any confirmed block and removes them, unless
+    /// `dry_run` is set. For a storage that is not in use, the blobs kept for blocks that
+    /// are not confirmed yet are removed too.
+    pub async fn run_pass(&mut self, dry_run: bool) -> Result<GarbageCollectionReport, ViewError> {
+        let mut report = self.remove_unused_blobs(dry_run).await?;
+        if !self.deferred {
+            self.remove_pending_blobs(dry_run, &mut report).await?;
+        }
+        Ok(report)
+    }
+
+    /// Returns the chains accepted by `filter` with blobs kept for blocks that are not
+    /// confirmed yet and that made no progress since the previous call, together with that
+    /// progress. The chain states are only read.
+    ///
+    /// For a storage that is not in use, all the chains with such blobs are returned.
+    pub async fn find_abandoned_proposals(
+        &mut self,
+        filter: impl Fn(ChainId) -> bool,
+    ) -> Result<Vec<(ChainId, ChainProgress)>, ViewError> {
+        let mut abandoned = Vec::new();
+        let mut pending_chains = BTreeMap::new();
+        for chain_id in self.storage.read_pending_blobs_chains().await? {
+            if !filter(chain_id) {
+                continue;
+            }
+            let chain = self.storage.load_chain(chain_id).await?;
+            if !chain.has_pending_blobs().await? {
+                continue;
+            }
+            let progress = chain.progress();
+            if !self.deferred || self.pending_chains.get(&chain_id) == Some(&progress) {
+                abandoned.push((chain_id, progress));
+            } else {
+                pending_chains.insert(chain_id, progress);
+            }
+        }
+        self.pending_chains = pending_chains;
+        Ok(abandoned)
+    }
+
+    async fn remove_unused_blobs(
+        &mut self,
+        dry_run: bool,
+    ) -> Result<GarbageCollectionReport, ViewError> {
+        let mut num_blobs = 0;
+        let mut removed_blobs = Vec::new();
+        let mut removed_bytes = 0;
+        let mut deferred_blobs = BTreeSet::new();
+        let mut last_blob_id = None;
+        loop {
+            let blob_ids = self
+                .storage
+                .list_blob_ids(last_blob_id, BLOB_BATCH_SIZE)
+                .await?;
+            let Some(last) = blob_ids.last() else {
+                break;
+            };
+            last_blob_id = Some(*last);
+            num_blobs += blob_ids.len();
+            let (removed, deferred): (Vec<_>, Vec<_>) = self
+                .storage
+                .missing_blob_states(&blob_ids)
+                .await?
+                .into_iter()
+                .partition(|blob_id| !self.deferred || self.candidates.contains(blob_id));
+            for blob in self
+                .storage
+                .read_blobs(&removed)
+                .await?
+                .into_iter()
+                .flatten()
+            {
+                removed_bytes += blob.bytes().len() as u64;
+            }
+            if !dry_run {
+                // The next page starts after the last listed blob, even if it is removed.
+                self.storage.delete_blobs(&removed).await?;
+            }
+            removed_blobs.extend(removed);
+            deferred_blobs.extend(deferred);
+            if blob_ids.len() < BLOB_BATCH_SIZE {
+                break;
+            }
+        }
+        let num_deferred_blobs = deferred_blobs.len();
+        if !dry_run {
+            self.candidates = deferred_blobs;
+        }
+        Ok(GarbageCollectionReport {
+            dry_run,
+            num_blobs,
+            removed_blobs,
+            removed_bytes,
+            num_deferred_blobs,
+            ..GarbageCollectionReport::default()
+        })
+    }
+
+    /// Removes the blobs kept in the chain states of a storage that is not in use.
+    async fn remove_pending_blobs(
+        &mut self,
+        dry_run: bool,
+        report: &mut GarbageCollectionReport,
+    ) -> Result<(), ViewError> {
+        let chain_ids = self.storage.read_pending_blobs_chains().await?;
+        for chain_id in &chain_ids {
+            let mut chain = self.storage.load_chain(*chain_id).await?;
+            let blobs = chain.remove_pending_blobs().await?;
+            if blobs.is_empty() {
+                continue;
+            }
+            report.abandoned_chains.push(*chain_id);
+            for blob in blobs {
+                report.removed_pending_bytes += blob.bytes().len() as u64;
+                report.removed_pending_blobs.push(blob.id());
+            }
+            if !dry_run {
+                chain.save().await?;
+            }
+        }
+        if !dry_run {
+            self.storage.delete_pending_blobs_chains(&chain_ids).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Connects to a storage that is not in use, e.g. of a stopped validator, and removes all
+/// the blobs that are not used by any confirmed block, and all the blobs kept for blocks
+/// that are not confirmed yet. Only reports them if `dry_run` is set.
+pub async fn collect_garbage_offline<Store>(
+    config: Store::Config,
+    namespace: &str,
+    root_key: &[u8],
+    dry_run: bool,
+) -> Result<GarbageCollectionReport, ViewError>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    let storage = DbStorage::<Store, WallClock>::new(config, namespace, root_key, None).await?;
+    GarbageCollector::new(storage).run_pass(dry_run).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use futures::future::Either;
+    use linera_base::{
+        crypto::{CryptoHash, KeyPair},
+        data_types::{Amount, Blob, BlockHeight, Round, Timestamp},
+        identifiers::{ChainDescription, ChainId, Owner},
+    };
+    use linera_chain::{
+        data_types::BlockProposal,
+        test::{make_first_block, BlockTestExt as _, VoteTestExt as _},
+    };
+    use linera_execution::{
+        committee::{Committee, Epoch, ValidatorName},
+        BlobState, SystemOperation,
+    };
+    use linera_views::{
+        memory::MemoryStore,
+        views::{RootView as _, View as _},
+    };
+
+    use super::{GarbageCollector, BLOB_BATCH_SIZE};
+    use crate::{DbStorage, Storage as _, TestClock};
+
+    type TestStorage = DbStorage<MemoryStore, TestClock>;
+
+    /// Creates a chain owned by `key_pair`, with `key_pair` as the only validator.
+    async fn make_chain(key_pair: &KeyPair) -> (TestStorage, ChainId) {
+        let storage = TestStorage::make_test_storage(None).await;
+        let committee = Committee::make_simple(vec![ValidatorName(key_pair.public())]);
+        let description = ChainDescription::Root(1);
+        storage
+            .create_chain(
+                committee,
+                ChainId::root(0),
+                description,
+                Owner::from(key_pair.public()),
+                Amount::ONE,
+                Timestamp::from(0),
+            )
+            .await
+            .unwrap();
+        (storage, description.into())
+    }
+
+    /// Handles a proposal publishing `blob` as a validator does, and locks the validated
+    /// block if `lock` is set. Returns the blob.
+    async fn propose_block_with_blob(
+        storage: &TestStorage,
+        chain_id: ChainId,
+        key_pair: &KeyPair,
+        lock: bool,
+    ) -> Blob {
+        let blob = Blob::new_data(b"proposed".to_vec());
+        let block = make_first_block(chain_id).with_operation(SystemOperation::PublishDataBlob {
+            blob_hash: blob.id().hash,
+        });
+        let proposal =
+            BlockProposal::new_initial(Round::MultiLeader(0), block, key_pair, vec![blob.clone()]);
+        let mut chain = storage.load_chain(chain_id).await.unwrap();
+        let local_time = storage.clock().current_time();
+        let outcome = chain
+            .execute_block(&proposal.content.block, local_time, None)
+            .await
+            .unwrap();
+        chain.rollback();
+        let executed_block = outcome.with(proposal.content.block.clone());
+        storage.write_pending_blobs_chain(chain_id).await.unwrap();
+        let vote = chain
+            .manager
+            .create_vote(
+                proposal,
+                executed_block,
+                Some(key_pair),
+                local_time,
+                BTreeMap::new(),
+            )
+            .unwrap();
+        let Some(Either::Left(vote)) = vote else {
+            panic!("Expected a vote to validate the block");
+        };
+        let certificate = vote.clone().into_certificate();
+        if lock {
+            let blobs = BTreeMap::from([(blob.id(), blob.clone())]);
+            chain
+                .manager
+                .create_final_vote(certificate, Some(key_pair), local_time, blobs)
+                .unwrap();
+        }
+        chain.save().await.unwrap();
+        blob
+    }
+
+    #[tokio::test]
+    async fn test_remove_unused_blobs_page_by_page() {
+        let storage = TestStorage::make_test_storage(None).await;
+        let blobs = (0..BLOB_BATCH_SIZE + 10)
+            .map(|i| Blob::new_data(i.to_le_bytes().to_vec()))
+            .collect::<Vec<_>>();
+        storage.write_blobs(&blobs).await.unwrap();
+        let used_blob_id = blobs[BLOB_BATCH_SIZE].id();
+        storage
+            .write_blob_state(
+                used_blob_id,
+                &BlobState {
+                    last_used_by: CryptoHash::test_hash("block"),
+                    chain_id: ChainId::root(0),
+                    block_height: BlockHeight::ZERO,
+                    epoch: Epoch::ZERO,
+                },
+            )
+            .await
+            .unwrap();
+
+        let mut garbage_collector = GarbageCollector::new_deferred(storage.clone());
+        let report = garbage_collector.run_pass(false).await.unwrap();
+        assert_eq!(report.num_blobs, blobs.len());
+        assert!(report.removed_blobs.is_empty());
+        assert_eq!(report.num_deferred_blobs, blobs.len() - 1);
+        let report = garbage_collector.run_pass(false).await.unwrap();
+        assert_eq!(report.removed_blobs.len(), blobs.len() - 1);
+        assert_eq!(
+            storage.list_blob_ids(None, blobs.len()).await.unwrap(),
+            vec![used_blob_id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_blobs_of_proposals_from_unused_storage() {
+        let key_pair = KeyPair::generate();
+        let (storage, chain_id) = make_chain(&key_pair).await;
+        let blob = propose_block_with_blob(&storage, chain_id, &key_pair, true).await;
+        let chain = storage.load_chain(chain_id).await.unwrap();
+        assert!(chain.has_pending_blobs().await.unwrap());
+        drop(chain);
+
+        let report = GarbageCollector::new(storage.clone())
+            .run_pass(true)
+            .await
+            .unwrap();
+        assert_eq!(report.abandoned_chains, vec![chain_id]);
+        assert_eq!(report.removed_pending_blobs, vec![blob.id()]);
+        assert_eq!(report.removed_pending_bytes, blob.bytes().len() as u64);
+        let chain = storage.load_chain(chain_id).await.unwrap();
+        assert!(chain.has_pending_blobs().await.unwrap());
+        drop(chain);
+
+        let report = GarbageCollector::new(storage.clone())
+            .run_pass(false)
+            .await
+            .unwrap();
+        assert_eq!(report.removed_pending_blobs, vec![blob.id()]);
+        let chain = storage.load_chain(chain_id).await.unwrap();
+        assert!(!chain.has_pending_blobs().await.unwrap());
+        // The validator still knows what it voted for and what it locked.
+        assert!(chain.manager.proposed.get().is_some());
+        assert!(chain.manager.locked.get().is_some());
+        assert!(storage
+            .read_pending_blobs_chains()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_abandoned_proposals() {
+        let key_pair = KeyPair::generate();
+        let (storage, chain_id) = make_chain(&key_pair).await;
+        propose_block_with_blob(&storage, chain_id, &key_pair, false).await;
+        let progress = storage.load_chain(chain_id).await.unwrap().progress();
+
+        let mut garbage_collector = GarbageCollector::new_deferred(storage.clone());
+        // The proposal is only abandoned once the chain made no progress for a whole pass.
+        let abandoned = garbage_collector
+            .find_abandoned_proposals(|_| true)
+            .await
+            .unwrap();
+        assert!(abandoned.is_empty());
+        // Chains of other shards are ignored, and found again from scratch afterwards.
+        let abandoned = garbage_collector
+            .find_abandoned_proposals(|_| false)
+            .await
+            .unwrap();
+        assert!(abandoned.is_empty());
+        let abandoned = garbage_collector
+            .find_abandoned_proposals(|_| true)
+            .await
+            .unwrap();
+        assert!(abandoned.is_empty());
+        let abandoned = garbage_collector
+            .find_abandoned_proposals(|_| true)
+            .await
+            .unwrap();
+        assert_eq!(abandoned, vec![(chain_id, progress)]);
+        // Finding the abandoned proposals leaves the chain states unchanged.
+        let mut chain = storage.load_chain(chain_id).await.unwrap();
+        assert!(chain.has_pending_blobs().await.unwrap());
+
+        // The chain worker removes the blobs.
+        assert_eq!(chain.remove_pending_blobs().await.unwrap().len(), 1);
+        chain.save().await.unwrap();
+        drop(chain);
+        let abandoned = garbage_collector
+            .find_abandoned_proposals(|_| true)
+            .await
+            .unwrap();
+        assert!(abandoned.is_empty());
+    }
+}
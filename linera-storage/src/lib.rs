@@ -6,8 +6,10 @@
 #![deny(clippy::large_futures)]
 
 mod db_storage;
+mod garbage_collection;
+mod migration;
 
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use async_trait::async_trait;
 use dashmap::{mapref::entry::Entry, DashMap};
@@ -16,7 +18,7 @@ use linera_base::{
     data_types::{Amount, Blob, BlockHeight, TimeDelta, Timestamp, UserApplicationDescription},
     hashed::Hashed,
     identifiers::{
-        BlobId, ChainDescription, ChainId, GenericApplicationId, Owner, UserApplicationId,
+        BlobId, ChainDescription, ChainId, EventId, GenericApplicationId, Owner, UserApplicationId,
     },
     ownership::ChainOwnership,
 };
@@ -43,11 +45,18 @@ use {
 
 #[cfg(with_testing)]
 pub use crate::db_storage::TestClock;
-pub use crate::db_storage::{ChainStatesFirstAssignment, DbStorage, WallClock};
 #[cfg(with_metrics)]
 pub use crate::db_storage::{
     READ_CERTIFICATE_COUNTER, READ_HASHED_CONFIRMED_BLOCK_COUNTER, WRITE_CERTIFICATE_COUNTER,
 };
+pub use crate::{
+    db_storage::{ChainStatesFirstAssignment, DbStorage, WallClock},
+    garbage_collection::{collect_garbage_offline, GarbageCollectionReport, GarbageCollector},
+    migration::{
+        check_schema_version, initialize_schema_version, migrate, migrations, Migration,
+        SCHEMA_VERSION,
+    },
+};
 
 /// Communicate with a persistent storage using the "views" abstraction.
 #[cfg_attr(not(web), async_trait)]
@@ -112,7 +121,19 @@ pub trait Storage: Sized {
     /// Writes the given blob.
     async fn write_blob(&self, blob: &Blob) -> Result<(), ViewError>;
 
-    /// Writes blobs and certificate
+    /// Tests the existence of an event with the given event ID.
+    async fn contains_event(&self, event_id: EventId) -> Result<bool, ViewError>;
+
+    /// Reads the value of the event with the given event ID.
+    async fn read_event(&self, event_id: EventId) -> Result<Vec<u8>, ViewError>;
+
+    /// Writes the given events.
+    async fn write_events(
+        &self,
+        events: impl IntoIterator<Item = (EventId, Vec<u8>)> + Send,
+    ) -> Result<(), ViewError>;
+
+    /// Writes blobs and certificate, as well as the events emitted by the certified block.
     async fn write_blobs_and_certificate(
         &self,
         blobs: &[Blob],
@@ -148,6 +169,52 @@ pub trait Storage: Sized {
     /// Writes several blobs.
     async fn write_blobs(&self, blobs: &[Blob]) -> Result<(), ViewError>;
 
+    /// Lists the IDs of at most `limit` blobs in storage, in increasing order, starting
+    /// after `after` if provided.
+    async fn list_blob_ids(
+        &self,
+        after: Option<BlobId>,
+        limit: usize,
+    ) -> Result<Vec<BlobId>, ViewError>;
+
+    /// Returns what blobs from the input have no blob state in storage, i.e. are not used
+    /// by any confirmed block.
+    async fn missing_blob_states(&self, blob_ids: &[BlobId]) -> Result<Vec<BlobId>, ViewError>;
+
+    /// Deletes the given blobs. Their blob states, if any, are kept.
+    async fn delete_blobs(&self, blob_ids: &[BlobId]) -> Result<(), ViewError>;
+
+    /// Records that a chain keeps blobs for blocks that are not confirmed yet, so that the
+    /// [`GarbageCollector`] can find them.
+    async fn write_pending_blobs_chain(&self, chain_id: ChainId) -> Result<(), ViewError>;
+
+    /// Forgets that the given chains keep blobs for blocks that are not confirmed yet.
+    async fn delete_pending_blobs_chains(&self, chain_ids: &[ChainId]) -> Result<(), ViewError>;
+
+    /// Lists the chains recorded as keeping blobs for blocks that are not confirmed yet.
+    async fn read_pending_blobs_chains(&self) -> Result<Vec<ChainId>, ViewError>;
+
+    /// Writes a serialized cross-chain request that is waiting to be delivered, replacing
+    /// the one with the same ID, if any.
+    async fn write_pending_cross_chain_request(
+        &self,
+        id: CryptoHash,
+        request: &[u8],
+    ) -> Result<(), ViewError>;
+
+    /// Deletes a cross-chain request that was delivered.
+    async fn delete_pending_cross_chain_request(&self, id: CryptoHash) -> Result<(), ViewError>;
+
+    /// Reads all the serialized cross-chain requests waiting to be delivered, with their IDs.
+    async fn read_pending_cross_chain_requests(
+        &self,
+    ) -> Result<Vec<(CryptoHash, Vec<u8>)>, ViewError>;
+
+    /// Writes a consistent point-in-time snapshot of the storage into the directory `path`,
+    /// which must not exist yet, while the storage keeps being used. Not all backends
+    /// support this.
+    async fn snapshot(&self, path: &Path) -> Result<(), ViewError>;
+
     /// Tests existence of the certificate with the given hash.
     async fn contains_certificate(&self, hash: CryptoHash) -> Result<bool, ViewError>;
 
@@ -408,6 +475,10 @@ where
         self.storage.contains_blob(blob_id).await
     }
 
+    async fn get_event(&self, event_id: EventId) -> Result<Vec<u8>, ViewError> {
+        self.storage.read_event(event_id).await
+    }
+
     #[cfg(with_testing)]
     async fn add_blobs(
         &self,
@@ -416,6 +487,14 @@ where
         let blobs = Vec::from_iter(blobs);
         self.storage.write_blobs(&blobs).await
     }
+
+    #[cfg(with_testing)]
+    async fn add_events(
+        &self,
+        events: impl IntoIterator<Item = (EventId, Vec<u8>)> + Send,
+    ) -> Result<(), ViewError> {
+        self.storage.write_events(events).await
+    }
 }
 
 /// A clock that can be used to get the current `Timestamp`.
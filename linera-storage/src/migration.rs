@@ -0,0 +1,390 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Versioning of the layout of the data written by [`DbStorage`](crate::DbStorage), and
+//! upgrades of existing data from one layout to the next.
+
+use futures::future::BoxFuture;
+use linera_views::{
+    batch::Batch,
+    store::{KeyRange, KeyValueStore},
+    views::ViewError,
+};
+
+use crate::db_storage::BaseKey;
+
+/// The version of the layout of the data written by this code.
+///
+/// It must be increased whenever a change modifies what is written to storage, e.g. new
+/// fields in `ChainStateView` or in a serialized type, together with the registration of
+/// a [`Migration`] from the previous version in [`migrations`].
+///
+/// Version 1 is the first recorded version. It resets the networks: the data written
+/// before, without a version, can't be upgraded, and the storages of the validators and
+/// of the clients must be initialized again.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The version of the data written before schema versions were recorded.
+const UNVERSIONED: u32 = 0;
+
+/// The function upgrading the data of a storage. It is given the store of the storage,
+/// with the root key used by `DbStorage`.
+pub type MigrationFn<Store> = for<'a> fn(&'a Store) -> BoxFuture<'a, Result<(), ViewError>>;
+
+/// An upgrade of the data of a storage from one schema version to the next.
+pub struct Migration<Store> {
+    /// The version of the data that this migration upgrades. The data has version
+    /// `from_version + 1` afterwards.
+    pub from_version: u32,
+    /// A short description of the changes, and of why the data can't be upgraded if
+    /// there is no `run` function.
+    pub description: &'static str,
+    /// The function performing the upgrade, or `None` if existing data can't be upgraded
+    /// to the new layout.
+    pub run: Option<MigrationFn<Store>>,
+}
+
+/// Returns the registered migrations, in the order in which they apply.
+pub fn migrations<Store>() -> Vec<Migration<Store>>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    vec![Migration {
+        from_version: UNVERSIONED,
+        description: "The networks were reset: the serialization of the certificates and \
+             of the chain states changed, and the application states became Merkle trees, \
+             which changed the state hashes of the blocks",
+        run: None,
+    }]
+}
+
+/// Checks that the data of a storage has the schema version [`SCHEMA_VERSION`], without
+/// writing anything. A storage without data is accepted. Data with an older version must
+/// be upgraded with [`migrate`] first.
+pub async fn check_schema_version<Store>(store: &Store) -> Result<(), ViewError>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    let found = match read_schema_version(store).await? {
+        Some(SCHEMA_VERSION) => return Ok(()),
+        Some(version) => version,
+        None if is_empty(store).await? => return Ok(()),
+        None => UNVERSIONED,
+    };
+    if found > SCHEMA_VERSION {
+        return Err(ViewError::SchemaVersionMismatch {
+            found,
+            expected: SCHEMA_VERSION,
+        });
+    }
+    Err(ViewError::SchemaUpgradeRequired {
+        found,
+        expected: SCHEMA_VERSION,
+    })
+}
+
+/// Records the schema version [`SCHEMA_VERSION`] in a storage that has no data and no
+/// version yet, e.g. because it was just created. Other storages are left unchanged.
+pub async fn initialize_schema_version<Store>(store: &Store) -> Result<(), ViewError>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    if read_schema_version(store).await?.is_none() && is_empty(store).await? {
+        write_schema_version(store, SCHEMA_VERSION).await?;
+    }
+    Ok(())
+}
+
+/// Upgrades the data of a storage to [`SCHEMA_VERSION`] by running the missing
+/// migrations. Returns the version of the data before the upgrade.
+///
+/// Nothing is written unless all the missing migrations can run. A storage without a
+/// schema version and without data is considered to have the current version.
+pub async fn migrate<Store>(store: &Store) -> Result<u32, ViewError>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    run_migrations(store, migrations()).await
+}
+
+/// Upgrades the data of a storage to the version following the last of `migrations`.
+async fn run_migrations<Store>(
+    store: &Store,
+    migrations: Vec<Migration<Store>>,
+) -> Result<u32, ViewError>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    let target_version = migrations
+        .last()
+        .map_or(UNVERSIONED, |migration| migration.from_version + 1);
+    let initial_version = match read_schema_version(store).await? {
+        Some(version) => version,
+        None if is_empty(store).await? => {
+            write_schema_version(store, target_version).await?;
+            return Ok(target_version);
+        }
+        None => UNVERSIONED,
+    };
+    if initial_version > target_version {
+        return Err(ViewError::SchemaVersionMismatch {
+            found: initial_version,
+            expected: target_version,
+        });
+    }
+    let missing_migrations = migrations
+        .into_iter()
+        .filter(|migration| migration.from_version >= initial_version)
+        .collect::<Vec<_>>();
+    if let Some(migration) = missing_migrations
+        .iter()
+        .find(|migration| migration.run.is_none())
+    {
+        return Err(ViewError::UnsupportedSchemaUpgrade {
+            found: initial_version,
+            version: migration.from_version + 1,
+            reason: migration.description.to_string(),
+        });
+    }
+    let mut version = initial_version;
+    for migration in missing_migrations {
+        assert_eq!(
+            migration.from_version, version,
+            "The migrations should be registered in order"
+        );
+        let run = migration
+            .run
+            .expect("Migrations were checked to be runnable");
+        run(store).await?;
+        version += 1;
+        write_schema_version(store, version).await?;
+    }
+    Ok(initial_version)
+}
+
+/// Tests whether the storage contains no data under the root key of `DbStorage`.
+async fn is_empty<Store>(store: &Store) -> Result<bool, ViewError>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    let range = KeyRange::new(Vec::new(), None).with_limit(1);
+    Ok(store.find_keys_in_range(&[], &range).await?.is_empty())
+}
+
+async fn read_schema_version<Store>(store: &Store) -> Result<Option<u32>, ViewError>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    let key = bcs::to_bytes(&BaseKey::SchemaVersion)?;
+    Ok(store.read_value(&key).await?)
+}
+
+async fn write_schema_version<Store>(store: &Store, version: u32) -> Result<(), ViewError>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    let key = bcs::to_bytes(&BaseKey::SchemaVersion)?;
+    let mut batch = Batch::new();
+    batch.put_key_value(key, &version)?;
+    store.write_batch(batch).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt as _;
+    use linera_views::{
+        batch::Batch,
+        memory::{create_test_memory_store, MemoryStore},
+        store::{ReadableKeyValueStore as _, WritableKeyValueStore as _},
+        views::ViewError,
+    };
+
+    use super::{
+        check_schema_version, initialize_schema_version, migrate, migrations, read_schema_version,
+        run_migrations, write_schema_version, Migration, SCHEMA_VERSION,
+    };
+
+    async fn write_chain_data(store: &MemoryStore) {
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0, 1, 2], vec![3, 4, 5]);
+        store.write_batch(batch).await.unwrap();
+    }
+
+    /// Three versions after the unversioned data: the first two can be upgraded by
+    /// writing a marker key, the last one can't.
+    fn test_migrations(upgradable: bool) -> Vec<Migration<MemoryStore>> {
+        vec![
+            Migration {
+                from_version: 0,
+                description: "First change",
+                run: Some(|store| write_marker(store, 10).boxed()),
+            },
+            Migration {
+                from_version: 1,
+                description: "Second change",
+                run: Some(|store| write_marker(store, 11).boxed()),
+            },
+            Migration {
+                from_version: 2,
+                description: "Third change",
+                run: upgradable
+                    .then_some::<MigrationFn<MemoryStore>>(|store| write_marker(store, 12).boxed()),
+            },
+        ]
+    }
+
+    async fn write_marker(store: &MemoryStore, key: u8) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![key], vec![]);
+        Ok(store.write_batch(batch).await?)
+    }
+
+    #[test]
+    fn test_migrations_cover_every_version() {
+        let versions = migrations::<MemoryStore>()
+            .into_iter()
+            .map(|migration| migration.from_version)
+            .collect::<Vec<_>>();
+        assert_eq!(versions, (0..SCHEMA_VERSION).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_check_schema_version_doesnt_write() {
+        let store = create_test_memory_store();
+        check_schema_version(&store).await.unwrap();
+        assert_eq!(read_schema_version(&store).await.unwrap(), None);
+
+        write_chain_data(&store).await;
+        let error = check_schema_version(&store).await.unwrap_err();
+        assert!(matches!(
+            error,
+            ViewError::SchemaUpgradeRequired {
+                found: 0,
+                expected: SCHEMA_VERSION,
+            }
+        ));
+        assert_eq!(read_schema_version(&store).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_schema_version() {
+        let store = create_test_memory_store();
+        write_chain_data(&store).await;
+        write_schema_version(&store, SCHEMA_VERSION).await.unwrap();
+        check_schema_version(&store).await.unwrap();
+
+        write_schema_version(&store, SCHEMA_VERSION + 1)
+            .await
+            .unwrap();
+        let error = check_schema_version(&store).await.unwrap_err();
+        assert!(matches!(
+            error,
+            ViewError::SchemaVersionMismatch { found, expected }
+                if found == SCHEMA_VERSION + 1 && expected == SCHEMA_VERSION
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_initialize_schema_version() {
+        let store = create_test_memory_store();
+        initialize_schema_version(&store).await.unwrap();
+        assert_eq!(
+            read_schema_version(&store).await.unwrap(),
+            Some(SCHEMA_VERSION)
+        );
+        check_schema_version(&store).await.unwrap();
+
+        // Existing data without a version is not marked as current.
+        let store = create_test_memory_store();
+        write_chain_data(&store).await;
+        initialize_schema_version(&store).await.unwrap();
+        assert_eq!(read_schema_version(&store).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_empty_storage() {
+        let store = create_test_memory_store();
+        assert_eq!(migrate(&store).await.unwrap(), SCHEMA_VERSION);
+        assert_eq!(
+            read_schema_version(&store).await.unwrap(),
+            Some(SCHEMA_VERSION)
+        );
+        assert_eq!(migrate(&store).await.unwrap(), SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_unversioned_storage() {
+        let store = create_test_memory_store();
+        write_chain_data(&store).await;
+        let error = migrate(&store).await.unwrap_err();
+        assert!(matches!(
+            error,
+            ViewError::UnsupportedSchemaUpgrade {
+                found: 0,
+                version: 1,
+                ..
+            }
+        ));
+        assert_eq!(read_schema_version(&store).await.unwrap(), None);
+        assert!(store.contains_key(&[0, 1, 2]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_run_migrations() {
+        let store = create_test_memory_store();
+        write_chain_data(&store).await;
+        write_schema_version(&store, 1).await.unwrap();
+        assert_eq!(
+            run_migrations(&store, test_migrations(true)).await.unwrap(),
+            1
+        );
+        assert_eq!(read_schema_version(&store).await.unwrap(), Some(3));
+        assert!(!store.contains_key(&[10]).await.unwrap());
+        assert!(store.contains_key(&[11]).await.unwrap());
+        assert!(store.contains_key(&[12]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_run_migrations_stops_before_unsupported_upgrades() {
+        let store = create_test_memory_store();
+        write_chain_data(&store).await;
+        let error = run_migrations(&store, test_migrations(false))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ViewError::UnsupportedSchemaUpgrade {
+                found: 0,
+                version: 3,
+                ..
+            }
+        ));
+        // Nothing was written, not even the versions that the data could be upgraded to.
+        assert_eq!(read_schema_version(&store).await.unwrap(), None);
+        assert!(!store.contains_key(&[10]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_newer_storage() {
+        let store = create_test_memory_store();
+        write_chain_data(&store).await;
+        write_schema_version(&store, SCHEMA_VERSION + 1)
+            .await
+            .unwrap();
+        let error = migrate(&store).await.unwrap_err();
+        assert!(matches!(
+            error,
+            ViewError::SchemaVersionMismatch { found, expected }
+                if found == SCHEMA_VERSION + 1 && expected == SCHEMA_VERSION
+        ));
+    }
+}
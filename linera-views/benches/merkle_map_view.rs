@@ -0,0 +1,141 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+#[cfg(with_rocksdb)]
+use linera_views::rocks_db::RocksDbStore;
+use linera_views::{
+    context::{Context, ViewContext},
+    map_view::HashedMapView,
+    memory::MemoryStore,
+    merkle_map_view::MerkleMapView,
+    random::make_deterministic_rng,
+    store::{KeyValueStore, TestKeyValueStore as _},
+    views::{CryptoHashRootView, CryptoHashView, RootView, View, ViewError},
+};
+use rand::Rng;
+use tokio::runtime::Runtime;
+
+/// The number of entries in the map.
+const N_ENTRIES: u64 = 10_000;
+
+/// The number of entries modified between two hash computations.
+const N_MODIFIED: usize = 10;
+
+#[derive(CryptoHashRootView)]
+pub struct HashedMapStateView<C> {
+    pub map: HashedMapView<C, u64, u64>,
+}
+
+#[derive(CryptoHashRootView)]
+pub struct MerkleMapStateView<C> {
+    pub map: MerkleMapView<C, u64, u64>,
+}
+
+/// The operations needed to compare the two kinds of maps.
+pub trait MapStateView<C>: CryptoHashRootView<C> {
+    fn insert(&mut self, index: u64, value: u64);
+}
+
+impl<C> MapStateView<C> for HashedMapStateView<C>
+where
+    C: Context + Send + Sync + Clone + 'static,
+    ViewError: From<C::Error>,
+{
+    fn insert(&mut self, index: u64, value: u64) {
+        self.map.insert(&index, value).unwrap();
+    }
+}
+
+impl<C> MapStateView<C> for MerkleMapStateView<C>
+where
+    C: Context + Send + Sync + Clone + 'static,
+    ViewError: From<C::Error>,
+{
+    fn insert(&mut self, index: u64, value: u64) {
+        self.map.insert(&index, value).unwrap();
+    }
+}
+
+/// Measures the time needed to modify a few entries of a large map, then compute its
+/// hash and save it.
+pub async fn performance_map_view<V, S>(store: S, iterations: u64) -> Duration
+where
+    V: MapStateView<ViewContext<(), S>>,
+    S: KeyValueStore + Clone + Sync + 'static,
+    S::Error: Debug + Send + Sync + 'static,
+{
+    let context = ViewContext::<(), S>::create_root_context(store, ())
+        .await
+        .unwrap();
+    let mut view = V::load(context.clone()).await.unwrap();
+    for index in 0..N_ENTRIES {
+        view.insert(index, index);
+    }
+    view.crypto_hash_mut().await.unwrap();
+    view.save().await.unwrap();
+
+    let mut total_time = Duration::ZERO;
+    let mut rng = make_deterministic_rng();
+    for _ in 0..iterations {
+        let mut view = V::load(context.clone()).await.unwrap();
+        let measurement = Instant::now();
+        for _ in 0..N_MODIFIED {
+            let index = rng.gen_range(0..N_ENTRIES);
+            view.insert(index, rng.gen());
+        }
+        black_box(view.crypto_hash_mut().await.unwrap());
+        view.save().await.unwrap();
+        total_time += measurement.elapsed();
+    }
+
+    total_time
+}
+
+fn bench_map_view(criterion: &mut Criterion) {
+    criterion.bench_function("memory_hashed_map_view", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                let store = MemoryStore::new_test_store().await.unwrap();
+                performance_map_view::<HashedMapStateView<_>, _>(store, iterations).await
+            })
+    });
+
+    criterion.bench_function("memory_merkle_map_view", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                let store = MemoryStore::new_test_store().await.unwrap();
+                performance_map_view::<MerkleMapStateView<_>, _>(store, iterations).await
+            })
+    });
+
+    #[cfg(with_rocksdb)]
+    criterion.bench_function("rocksdb_hashed_map_view", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                let store = RocksDbStore::new_test_store().await.unwrap();
+                performance_map_view::<HashedMapStateView<_>, _>(store, iterations).await
+            })
+    });
+
+    #[cfg(with_rocksdb)]
+    criterion.bench_function("rocksdb_merkle_map_view", |bencher| {
+        bencher
+            .to_async(Runtime::new().expect("Failed to create Tokio runtime"))
+            .iter_custom(|iterations| async move {
+                let store = RocksDbStore::new_test_store().await.unwrap();
+                performance_map_view::<MerkleMapStateView<_>, _>(store, iterations).await
+            })
+    });
+}
+
+criterion_group!(benches, bench_map_view);
+criterion_main!(benches);
@@ -9,6 +9,11 @@ fn main() {
         with_dynamodb: { all(not(target_arch = "wasm32"), feature = "dynamodb") },
         with_indexeddb: { all(web, feature = "indexeddb") },
         with_rocksdb: { all(not(target_arch = "wasm32"), feature = "rocksdb") },
+        with_redb: { all(not(target_arch = "wasm32"), feature = "redb") },
         with_scylladb: { all(not(target_arch = "wasm32"), feature = "scylladb") },
+        with_encryption: { feature = "encryption" },
+
+        // `rand` can only use the randomness of the platform where `linera-base` enables it.
+        with_getrandom: { any(web, not(target_arch = "wasm32")) },
     };
 }
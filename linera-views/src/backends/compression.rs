@@ -0,0 +1,530 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Adds transparent zstd compression of large values to a given store.
+//!
+//! Values longer than a threshold are compressed before being written. Compressed values
+//! are stored after a header made of a magic number and a tag. Uncompressed values that
+//! happen to start with the magic number are written after a header as well, so that they
+//! are not mistaken for compressed ones.
+//!
+//! The namespaces created with compression are marked as such. Headers are only looked
+//! for in the marked namespaces, so that the values written without this layer are never
+//! misread. Compression can't be enabled on a namespace created without it.
+
+#[cfg(with_metrics)]
+use std::sync::LazyLock;
+use std::{io, path::Path};
+
+use thiserror::Error;
+#[cfg(with_metrics)]
+use {linera_base::prometheus_util::register_int_counter_vec, prometheus::IntCounterVec};
+
+#[cfg(with_testing)]
+use crate::store::TestKeyValueStore;
+use crate::{
+    batch::{Batch, WriteOperation},
+    store::{
+        AdminKeyValueStore, KeyRange, KeyValueIterable as _, KeyValueStoreError,
+        ReadableKeyValueStore, WithError, WritableKeyValueStore,
+    },
+};
+
+/// The compression threshold used for tests.
+pub const TEST_COMPRESSION_THRESHOLD: usize = 100;
+
+/// The magic number starting the values stored with a header.
+const MAGIC: [u8; 4] = [0xff, b'z', b's', b't'];
+
+/// The size of the header: the magic number followed by a tag.
+const HEADER_SIZE: usize = MAGIC.len() + 1;
+
+/// The tag of values stored uncompressed after a header.
+const TAG_RAW: u8 = 0;
+
+/// The tag of values compressed with zstd.
+const TAG_ZSTD: u8 = 1;
+
+/// The root key under which a namespace is marked as compressed. For the backends where
+/// root keys are prefixes of the keys, this only overlaps the root keys starting with 255,
+/// which `linera-storage` does not use.
+const MARKER_ROOT_KEY: &[u8] = &[u8::MAX];
+
+/// The key of the marker of the compressed namespaces.
+const MARKER_KEY: &[u8] = b"compression";
+
+/// The zstd compression level.
+const COMPRESSION_LEVEL: i32 = 3;
+
+#[cfg(with_metrics)]
+/// The total size of the compressed values before compression
+static COMPRESSION_INPUT_BYTES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "compression_input_bytes",
+        "Total size of the compressed values before compression",
+        &[],
+    )
+});
+
+#[cfg(with_metrics)]
+/// The total size of the compressed values after compression
+static COMPRESSION_OUTPUT_BYTES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec(
+        "compression_output_bytes",
+        "Total size of the compressed values after compression",
+        &[],
+    )
+});
+
+/// The composed error type built from the inner error type.
+#[derive(Error, Debug)]
+pub enum CompressionError<E> {
+    /// inner store error
+    #[error(transparent)]
+    InnerStoreError(#[from] E),
+
+    /// A stored value could not be decompressed
+    #[error("a stored value could not be decompressed: {0}")]
+    Decompression(io::Error),
+
+    /// Compression was enabled on a namespace created without it
+    #[error("the namespace was created without compression and must be migrated to enable it")]
+    UncompressedNamespace,
+}
+
+impl<E: KeyValueStoreError> From<bcs::Error> for CompressionError<E> {
+    fn from(error: bcs::Error) -> Self {
+        let error = E::from(error);
+        CompressionError::InnerStoreError(error)
+    }
+}
+
+impl<E: KeyValueStoreError + 'static> KeyValueStoreError for CompressionError<E> {
+    const BACKEND: &'static str = "compression";
+}
+
+/// A key-value store compressing the values above a given size.
+#[derive(Clone)]
+pub struct CompressionStore<K> {
+    /// The underlying store of the transformed store.
+    store: K,
+    /// The size above which values are compressed, or `None` if no value is compressed.
+    threshold: Option<usize>,
+    /// Whether the namespace was created with compression, so that values may be stored
+    /// after a header.
+    marked: bool,
+}
+
+impl<K> WithError for CompressionStore<K>
+where
+    K: WithError,
+    K::Error: 'static,
+{
+    type Error = CompressionError<K::Error>;
+}
+
+impl<K> ReadableKeyValueStore for CompressionStore<K>
+where
+    K: ReadableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    const MAX_KEY_SIZE: usize = K::MAX_KEY_SIZE;
+    type Keys = K::Keys;
+    type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+    fn max_stream_queries(&self) -> usize {
+        self.store.max_stream_queries()
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        match self.store.read_value_bytes(key).await? {
+            None => Ok(None),
+            Some(value) => Ok(Some(self.decode_value(value)?)),
+        }
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        Ok(self.store.contains_key(key).await?)
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, Self::Error> {
+        Ok(self.store.contains_keys(keys).await?)
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let values = self.store.read_multi_values_bytes(keys).await?;
+        values
+            .into_iter()
+            .map(|value| value.map(|value| self.decode_value(value)).transpose())
+            .collect()
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
+        Ok(self.store.find_keys_by_prefix(key_prefix).await?)
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, Self::Error> {
+        let key_values = self.store.find_key_values_by_prefix(key_prefix).await?;
+        let mut result = Vec::new();
+        for key_value in key_values.into_iterator_owned() {
+            let (key, value) = key_value?;
+            result.push((key, self.decode_value(value)?));
+        }
+        Ok(result)
+    }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(self.store.find_keys_in_range(key_prefix, range).await?)
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let key_values = self
+            .store
+            .find_key_values_in_range(key_prefix, range)
+            .await?;
+        key_values
+            .into_iter()
+            .map(|(key, value)| Ok((key, self.decode_value(value)?)))
+            .collect()
+    }
+}
+
+impl<K> WritableKeyValueStore for CompressionStore<K>
+where
+    K: WritableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    // Values that do not compress well may be stored after a header.
+    const MAX_VALUE_SIZE: usize = K::MAX_VALUE_SIZE - HEADER_SIZE;
+
+    async fn write_batch(&self, mut batch: Batch) -> Result<(), Self::Error> {
+        for operation in &mut batch.operations {
+            if let WriteOperation::Put { value, .. } = operation {
+                *value = self.encode_value(std::mem::take(value));
+            }
+        }
+        Ok(self.store.write_batch(batch).await?)
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        Ok(self.store.clear_journal().await?)
+    }
+}
+
+/// The configuration type for the `CompressionStore`.
+pub struct CompressionConfig<C> {
+    /// The inner configuration of the `CompressionStore`.
+    pub inner_config: C,
+    /// The size above which values are compressed, or `None` to disable compression.
+    pub threshold: Option<usize>,
+}
+
+impl<K> AdminKeyValueStore for CompressionStore<K>
+where
+    K: AdminKeyValueStore + ReadableKeyValueStore + WritableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    type Config = CompressionConfig<K::Config>;
+
+    fn get_name() -> String {
+        format!("compression {}", K::get_name())
+    }
+
+    async fn connect(
+        config: &Self::Config,
+        namespace: &str,
+        root_key: &[u8],
+    ) -> Result<Self, Self::Error> {
+        let store = K::connect(&config.inner_config, namespace, root_key).await?;
+        let marked = store
+            .clone_with_root_key(MARKER_ROOT_KEY)?
+            .contains_key(MARKER_KEY)
+            .await?;
+        if !marked && config.threshold.is_some() {
+            return Err(CompressionError::UncompressedNamespace);
+        }
+        Ok(CompressionStore {
+            store,
+            threshold: config.threshold,
+            marked,
+        })
+    }
+
+    fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error> {
+        let store = self.store.clone_with_root_key(root_key)?;
+        Ok(CompressionStore {
+            store,
+            threshold: self.threshold,
+            marked: self.marked,
+        })
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        Ok(K::list_all(&config.inner_config).await?)
+    }
+
+    async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
+        Ok(K::delete_all(&config.inner_config).await?)
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        Ok(K::exists(&config.inner_config, namespace).await?)
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        K::create(&config.inner_config, namespace).await?;
+        if config.threshold.is_some() {
+            let store = K::connect(&config.inner_config, namespace, MARKER_ROOT_KEY).await?;
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(MARKER_KEY.to_vec(), Vec::new());
+            store.write_batch(batch).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        Ok(K::delete(&config.inner_config, namespace).await?)
+    }
+
+    async fn snapshot(&self, path: &Path) -> Result<(), Self::Error> {
+        Ok(self.store.snapshot(path).await?)
+    }
+
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), Self::Error> {
+        Ok(K::restore(&config.inner_config, namespace, path).await?)
+    }
+}
+
+#[cfg(with_testing)]
+impl<K> TestKeyValueStore for CompressionStore<K>
+where
+    K: TestKeyValueStore + ReadableKeyValueStore + WritableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    async fn new_test_config() -> Result<CompressionConfig<K::Config>, Self::Error> {
+        let inner_config = K::new_test_config().await?;
+        Ok(CompressionConfig {
+            inner_config,
+            threshold: Some(TEST_COMPRESSION_THRESHOLD),
+        })
+    }
+}
+
+impl<K> CompressionStore<K>
+where
+    K: WithError,
+{
+    /// Creates a new store compressing the values above `threshold`, if any, for a
+    /// namespace created with compression.
+    pub fn new(store: K, threshold: Option<usize>) -> Self {
+        CompressionStore {
+            store,
+            threshold,
+            marked: true,
+        }
+    }
+
+    /// Gets the size above which values are compressed.
+    pub fn threshold(&self) -> Option<usize> {
+        self.threshold
+    }
+
+    fn encode_value(&self, value: Vec<u8>) -> Vec<u8> {
+        if !self.marked {
+            return value;
+        }
+        if self
+            .threshold
+            .is_some_and(|threshold| value.len() > threshold)
+        {
+            let compressed = zstd::stream::encode_all(&*value, COMPRESSION_LEVEL)
+                .expect("Compressing bytes in memory should not fail");
+            if compressed.len() + HEADER_SIZE < value.len() {
+                #[cfg(with_metrics)]
+                {
+                    COMPRESSION_INPUT_BYTES
+                        .with_label_values(&[])
+                        .inc_by(value.len() as u64);
+                    COMPRESSION_OUTPUT_BYTES
+                        .with_label_values(&[])
+                        .inc_by((compressed.len() + HEADER_SIZE) as u64);
+                }
+                return Self::with_header(TAG_ZSTD, &compressed);
+            }
+        }
+        if value.starts_with(&MAGIC) {
+            return Self::with_header(TAG_RAW, &value);
+        }
+        value
+    }
+
+    fn decode_value(&self, value: Vec<u8>) -> Result<Vec<u8>, CompressionError<K::Error>> {
+        if !self.marked || value.len() < HEADER_SIZE || !value.starts_with(&MAGIC) {
+            return Ok(value);
+        }
+        match value[MAGIC.len()] {
+            TAG_RAW => Ok(value[HEADER_SIZE..].to_vec()),
+            TAG_ZSTD => zstd::stream::decode_all(&value[HEADER_SIZE..])
+                .map_err(CompressionError::Decompression),
+            // Not written by this store: the value is returned unchanged.
+            _ => Ok(value),
+        }
+    }
+
+    fn with_header(tag: u8, bytes: &[u8]) -> Vec<u8> {
+        let mut value = Vec::with_capacity(HEADER_SIZE + bytes.len());
+        value.extend(MAGIC);
+        value.push(tag);
+        value.extend(bytes);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompressionConfig, CompressionError, CompressionStore, MAGIC, TAG_RAW, TAG_ZSTD};
+    use crate::{
+        batch::Batch,
+        memory::MemoryStore,
+        random::generate_test_namespace,
+        store::{
+            AdminKeyValueStore as _, KeyRange, ReadableKeyValueStore, TestKeyValueStore as _,
+            WritableKeyValueStore,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_compression_of_large_values() {
+        let store = MemoryStore::new_test_store().await.unwrap();
+        let compression_store = CompressionStore::new(store.clone(), Some(100));
+        let small_value = vec![7; 100];
+        let large_value = vec![7; 1000];
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0, 1], small_value.clone());
+        batch.put_key_value_bytes(vec![0, 2], large_value.clone());
+        compression_store.write_batch(batch).await.unwrap();
+
+        let stored_small_value = store.read_value_bytes(&[0, 1]).await.unwrap();
+        assert_eq!(stored_small_value, Some(small_value.clone()));
+        let stored_large_value = store.read_value_bytes(&[0, 2]).await.unwrap().unwrap();
+        assert!(stored_large_value.starts_with(&MAGIC));
+        assert_eq!(stored_large_value[MAGIC.len()], TAG_ZSTD);
+        assert!(stored_large_value.len() < large_value.len());
+
+        let values = compression_store
+            .read_multi_values_bytes(vec![vec![0, 1], vec![0, 2], vec![0, 3]])
+            .await
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![Some(small_value.clone()), Some(large_value.clone()), None]
+        );
+        let key_values = compression_store
+            .find_key_values_by_prefix(&[0])
+            .await
+            .unwrap();
+        assert_eq!(
+            key_values,
+            vec![(vec![1], small_value), (vec![2], large_value.clone())]
+        );
+        let range = KeyRange::new(vec![2], None);
+        let key_values = compression_store
+            .find_key_values_in_range(&[0], &range)
+            .await
+            .unwrap();
+        assert_eq!(key_values, vec![(vec![2], large_value)]);
+    }
+
+    // Values that look like a header are escaped in the compressed namespaces.
+    #[tokio::test]
+    async fn test_compression_escapes_headers() {
+        let store = MemoryStore::new_test_store().await.unwrap();
+        let compression_store = CompressionStore::new(store.clone(), Some(100));
+        let mut header_like_value = MAGIC.to_vec();
+        header_like_value.extend([TAG_ZSTD, 1, 2, 3]);
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], header_like_value.clone());
+        compression_store.write_batch(batch).await.unwrap();
+        let stored_value = store.read_value_bytes(&[1]).await.unwrap().unwrap();
+        assert_eq!(stored_value[MAGIC.len()], TAG_RAW);
+        let value = compression_store.read_value_bytes(&[1]).await.unwrap();
+        assert_eq!(value, Some(header_like_value));
+    }
+
+    // The values of a namespace created without compression are read unchanged, even if
+    // they look like a header, and compression can't be enabled there.
+    #[tokio::test]
+    async fn test_compression_of_uncompressed_namespaces() {
+        let inner_config = MemoryStore::new_test_config().await.unwrap();
+        let namespace = generate_test_namespace();
+        let mut config = CompressionConfig {
+            inner_config,
+            threshold: None,
+        };
+        let store = CompressionStore::<MemoryStore>::recreate_and_connect(&config, &namespace, &[])
+            .await
+            .unwrap();
+        let mut legacy_value = MAGIC.to_vec();
+        legacy_value.extend([TAG_RAW, 1, 2, 3]);
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0], legacy_value.clone());
+        store.write_batch(batch).await.unwrap();
+        let inner_store = MemoryStore::connect(&config.inner_config, &namespace, &[])
+            .await
+            .unwrap();
+        let stored_value = inner_store.read_value_bytes(&[0]).await.unwrap();
+        assert_eq!(stored_value.as_ref(), Some(&legacy_value));
+        let value = store.read_value_bytes(&[0]).await.unwrap();
+        assert_eq!(value, Some(legacy_value));
+
+        config.threshold = Some(100);
+        let result = CompressionStore::<MemoryStore>::connect(&config, &namespace, &[]).await;
+        assert!(matches!(
+            result,
+            Err(CompressionError::UncompressedNamespace)
+        ));
+    }
+
+    // Compression can be disabled on a namespace created with it.
+    #[tokio::test]
+    async fn test_compression_disabled_on_compressed_namespaces() {
+        let inner_config = MemoryStore::new_test_config().await.unwrap();
+        let namespace = generate_test_namespace();
+        let mut config = CompressionConfig {
+            inner_config,
+            threshold: Some(100),
+        };
+        let store = CompressionStore::<MemoryStore>::recreate_and_connect(&config, &namespace, &[])
+            .await
+            .unwrap();
+        let large_value = vec![7; 1000];
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0], large_value.clone());
+        store.write_batch(batch).await.unwrap();
+
+        config.threshold = None;
+        let store = CompressionStore::<MemoryStore>::connect(&config, &namespace, &[])
+            .await
+            .unwrap();
+        let value = store.read_value_bytes(&[0]).await.unwrap();
+        assert_eq!(value, Some(large_value));
+    }
+}
@@ -3,6 +3,8 @@
 
 //! Implements [`crate::store::KeyValueStore`] by combining two existing stores.
 
+use std::path::Path;
+
 use thiserror::Error;
 
 #[cfg(with_testing)]
@@ -10,7 +12,7 @@ use crate::store::TestKeyValueStore;
 use crate::{
     batch::Batch,
     store::{
-        AdminKeyValueStore, KeyIterable, KeyValueIterable, KeyValueStoreError,
+        AdminKeyValueStore, KeyIterable, KeyRange, KeyValueIterable, KeyValueStoreError,
         ReadableKeyValueStore, WithError, WritableKeyValueStore,
     },
 };
@@ -188,6 +190,46 @@ where
         };
         Ok(result)
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let result = match self.store_in_use {
+            StoreInUse::First => self
+                .first_store
+                .find_keys_in_range(key_prefix, range)
+                .await
+                .map_err(DualStoreError::First)?,
+            StoreInUse::Second => self
+                .second_store
+                .find_keys_in_range(key_prefix, range)
+                .await
+                .map_err(DualStoreError::Second)?,
+        };
+        Ok(result)
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let result = match self.store_in_use {
+            StoreInUse::First => self
+                .first_store
+                .find_key_values_in_range(key_prefix, range)
+                .await
+                .map_err(DualStoreError::First)?,
+            StoreInUse::Second => self
+                .second_store
+                .find_key_values_in_range(key_prefix, range)
+                .await
+                .map_err(DualStoreError::Second)?,
+        };
+        Ok(result)
+    }
 }
 
 impl<S1, S2, A> WritableKeyValueStore for DualStore<S1, S2, A>
@@ -327,6 +369,32 @@ where
             .map_err(DualStoreError::Second)?;
         Ok(())
     }
+
+    async fn snapshot(&self, path: &Path) -> Result<(), Self::Error> {
+        self.first_store
+            .snapshot(&path.join("first"))
+            .await
+            .map_err(DualStoreError::First)?;
+        self.second_store
+            .snapshot(&path.join("second"))
+            .await
+            .map_err(DualStoreError::Second)?;
+        Ok(())
+    }
+
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), Self::Error> {
+        S1::restore(&config.first_config, namespace, &path.join("first"))
+            .await
+            .map_err(DualStoreError::First)?;
+        S2::restore(&config.second_config, namespace, &path.join("second"))
+            .await
+            .map_err(DualStoreError::Second)?;
+        Ok(())
+    }
 }
 
 #[cfg(with_testing)]
@@ -3,7 +3,7 @@
 
 //! Implements [`crate::store::KeyValueStore`] for the DynamoDB database.
 
-use std::{collections::HashMap, env, sync::Arc};
+use std::{collections::HashMap, env, ops::Bound::Excluded, path::Path, sync::Arc};
 
 use async_lock::{Semaphore, SemaphoreGuard};
 use async_trait::async_trait;
@@ -37,10 +37,12 @@ use crate::store::TestKeyValueStore;
 use crate::{
     batch::SimpleUnorderedBatch,
     common::get_uleb128_size,
+    compression::{CompressionConfig, CompressionError, CompressionStore},
+    encryption::{EncryptionConfig, EncryptionError, EncryptionStore},
     journaling::{DirectWritableKeyValueStore, JournalConsistencyError, JournalingKeyValueStore},
     lru_caching::{LruCachingConfig, LruCachingStore},
     store::{
-        AdminKeyValueStore, CommonStoreInternalConfig, KeyIterable, KeyValueIterable,
+        AdminKeyValueStore, CommonStoreInternalConfig, KeyIterable, KeyRange, KeyValueIterable,
         KeyValueStoreError, ReadableKeyValueStore, WithError,
     },
     value_splitting::{ValueSplittingError, ValueSplittingStore},
@@ -507,6 +509,18 @@ impl AdminKeyValueStore for DynamoDbStoreInternal {
             .await?;
         Ok(())
     }
+
+    async fn snapshot(&self, _path: &Path) -> Result<(), DynamoDbStoreInternalError> {
+        Err(DynamoDbStoreInternalError::SnapshotNotSupported)
+    }
+
+    async fn restore(
+        _config: &Self::Config,
+        _namespace: &str,
+        _path: &Path,
+    ) -> Result<(), DynamoDbStoreInternalError> {
+        Err(DynamoDbStoreInternalError::SnapshotNotSupported)
+    }
 }
 
 impl DynamoDbStoreInternal {
@@ -666,6 +680,92 @@ impl DynamoDbStoreInternal {
             responses,
         })
     }
+
+    /// Finds the key-value pairs of the `range` following `key_prefix`. The values are
+    /// left empty unless `with_values` is set.
+    async fn find_key_values_in_range_internal(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+        with_values: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, DynamoDbStoreInternalError> {
+        check_key_size(key_prefix)?;
+        let limit = range.limit.unwrap_or(usize::MAX);
+        let mut key_values = Vec::new();
+        if range.is_empty() || limit == 0 {
+            return Ok(key_values);
+        }
+        let prefix_len = key_prefix.len();
+        let mut start = key_prefix.to_vec();
+        start.extend(&range.start);
+        let end = match range.bounds(key_prefix).1 {
+            Excluded(end) => Some(end),
+            _ => None,
+        };
+        // The `between` condition includes `end`, which is skipped below.
+        let key_condition = match end {
+            Some(_) => format!(
+                "{PARTITION_ATTRIBUTE} = :partition and {KEY_ATTRIBUTE} between :start and :end"
+            ),
+            None => format!("{PARTITION_ATTRIBUTE} = :partition and {KEY_ATTRIBUTE} >= :start"),
+        };
+        let attribute = if with_values {
+            KEY_VALUE_ATTRIBUTE
+        } else {
+            KEY_ATTRIBUTE
+        };
+        let big_root = extend_root_key(&self.root_key);
+        let mut start_key = None;
+        loop {
+            // One more item than needed is requested, in case `end` is among them.
+            let page_limit = i32::try_from(limit - key_values.len())
+                .unwrap_or(i32::MAX)
+                .saturating_add(1);
+            let mut query = self
+                .client
+                .query()
+                .table_name(&self.namespace)
+                .projection_expression(attribute)
+                .key_condition_expression(&key_condition)
+                .expression_attribute_values(
+                    ":partition",
+                    AttributeValue::B(Blob::new(big_root.clone())),
+                )
+                .expression_attribute_values(":start", AttributeValue::B(Blob::new(start.clone())))
+                .scan_index_forward(!range.reverse)
+                .limit(page_limit)
+                .set_exclusive_start_key(start_key);
+            if let Some(end) = &end {
+                query = query
+                    .expression_attribute_values(":end", AttributeValue::B(Blob::new(end.clone())));
+            }
+            let response = {
+                let _guard = self.acquire().await;
+                query.send().boxed().await?
+            };
+            for mut item in response.items.into_iter().flatten() {
+                if let Some(end) = &end {
+                    if extract_key(0, &item)? == end.as_slice() {
+                        continue;
+                    }
+                }
+                let key_value = if with_values {
+                    extract_key_value_owned(prefix_len, &mut item)?
+                } else {
+                    (extract_key(prefix_len, &item)?.to_vec(), Vec::new())
+                };
+                key_values.push(key_value);
+                if key_values.len() == limit {
+                    return Ok(key_values);
+                }
+            }
+            match response.last_evaluated_key {
+                None => break,
+                Some(value) => start_key = Some(value),
+            }
+        }
+        Ok(key_values)
+    }
 }
 
 struct QueryResponses {
@@ -918,6 +1018,26 @@ impl ReadableKeyValueStore for DynamoDbStoreInternal {
             .await?;
         Ok(DynamoDbKeyValues { result_queries })
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, DynamoDbStoreInternalError> {
+        let key_values = self
+            .find_key_values_in_range_internal(key_prefix, range, false)
+            .await?;
+        Ok(key_values.into_iter().map(|(key, _)| key).collect())
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, DynamoDbStoreInternalError> {
+        self.find_key_values_in_range_internal(key_prefix, range, true)
+            .await
+    }
 }
 
 #[async_trait]
@@ -1056,6 +1176,10 @@ pub enum DynamoDbStoreInternalError {
     /// An error occurred while building an object
     #[error(transparent)]
     Build(#[from] Box<BuildError>),
+
+    /// Snapshots have to be taken with the DynamoDB tools
+    #[error("Snapshots are not supported for DynamoDB, use on-demand backups instead")]
+    SnapshotNotSupported,
 }
 
 impl<InnerError> From<SdkError<InnerError>> for DynamoDbStoreInternalError
@@ -1135,25 +1259,42 @@ impl TestKeyValueStore for JournalingKeyValueStore<DynamoDbStoreInternal> {
 pub type DynamoDbStore = MeteredStore<
     LruCachingStore<
         MeteredStore<
-            ValueSplittingStore<MeteredStore<JournalingKeyValueStore<DynamoDbStoreInternal>>>,
+            CompressionStore<
+                EncryptionStore<
+                    MeteredStore<
+                        ValueSplittingStore<
+                            MeteredStore<JournalingKeyValueStore<DynamoDbStoreInternal>>,
+                        >,
+                    >,
+                >,
+            >,
         >,
     >,
 >;
 
 /// A shared DB client for DynamoDb implementing LruCaching
 #[cfg(not(with_metrics))]
-pub type DynamoDbStore =
-    LruCachingStore<ValueSplittingStore<JournalingKeyValueStore<DynamoDbStoreInternal>>>;
+pub type DynamoDbStore = LruCachingStore<
+    CompressionStore<
+        EncryptionStore<ValueSplittingStore<JournalingKeyValueStore<DynamoDbStoreInternal>>>,
+    >,
+>;
 
 /// The combined error type for the `DynamoDbStore`.
-pub type DynamoDbStoreError = ValueSplittingError<DynamoDbStoreInternalError>;
+pub type DynamoDbStoreError =
+    CompressionError<EncryptionError<ValueSplittingError<DynamoDbStoreInternalError>>>;
 
 /// The config type for DynamoDbStore
-pub type DynamoDbStoreConfig = LruCachingConfig<DynamoDbStoreInternalConfig>;
+pub type DynamoDbStoreConfig =
+    LruCachingConfig<CompressionConfig<EncryptionConfig<DynamoDbStoreInternalConfig>>>;
 
 /// Getting a configuration for the system
 pub async fn get_config(use_localstack: bool) -> Result<Config, DynamoDbStoreError> {
-    Ok(get_config_internal(use_localstack).await?)
+    let config = get_config_internal(use_localstack)
+        .await
+        .map_err(ValueSplittingError::from)
+        .map_err(EncryptionError::from)?;
+    Ok(config)
 }
 
 impl DynamoDbStoreConfig {
@@ -1166,6 +1307,14 @@ impl DynamoDbStoreConfig {
             config,
             common_config: common_config.reduced(),
         };
+        let inner_config = EncryptionConfig {
+            inner_config,
+            key: common_config.encryption_key.clone(),
+        };
+        let inner_config = CompressionConfig {
+            inner_config,
+            threshold: common_config.compression_threshold,
+        };
         DynamoDbStoreConfig {
             inner_config,
             cache_size: common_config.cache_size,
@@ -0,0 +1,741 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Adds authenticated encryption of the values to a given store.
+//!
+//! Values are encrypted with XChaCha20-Poly1305, under a key that is typically derived
+//! from a passphrase with Argon2id. The keys of the store are not encrypted, since they
+//! are needed to look up values and to iterate over prefixes and ranges. Every value is
+//! authenticated together with its root key and its key, so that values cannot be moved
+//! to another location without being noticed.
+//!
+//! Each namespace has a random salt, stored in clear next to its data and mixed into the
+//! derivation of its key, so that guessing a passphrase has to be done namespace by
+//! namespace. The salt is written when an encrypted namespace is created, together with a
+//! key-check value, i.e. a known plaintext encrypted with the key of the namespace. The
+//! key-check value marks the namespace as encrypted: connecting to it without a key, with
+//! the wrong key, or with a key to a namespace that has none, fails.
+//!
+//! The nonce of a value is derived from the value and its location. No source of
+//! randomness is needed to write values, at the price of writing the same ciphertext when
+//! the same value is written twice at the same location.
+
+use std::{fmt, path::Path};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{Aead as _, KeyInit as _, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use sha3::{Digest as _, Sha3_256};
+use thiserror::Error;
+
+#[cfg(with_testing)]
+use crate::store::TestKeyValueStore;
+use crate::{
+    batch::{Batch, WriteOperation},
+    store::{
+        AdminKeyValueStore, KeyRange, KeyValueIterable as _, KeyValueStoreError,
+        ReadableKeyValueStore, WithError, WritableKeyValueStore,
+    },
+};
+
+/// The size of the encryption keys.
+const KEY_SIZE: usize = 32;
+
+/// The size of the nonce stored in front of every encrypted value.
+const NONCE_SIZE: usize = 24;
+
+/// The size of the authentication tag appended to every encrypted value.
+const TAG_SIZE: usize = 16;
+
+/// The size of the salt of a namespace.
+const SALT_SIZE: usize = 16;
+
+/// The root key under which the salt of a namespace is stored. For the backends where
+/// root keys are prefixes of the keys, this only overlaps the root keys starting with 255,
+/// which `linera-storage` does not use.
+pub(crate) const SALT_ROOT_KEY: &[u8] = &[u8::MAX];
+
+/// The key of the salt of a namespace.
+pub(crate) const SALT_KEY: &[u8] = b"encryption salt";
+
+/// The key of the key-check value of a namespace.
+pub(crate) const CHECK_KEY: &[u8] = b"encryption check";
+
+/// The plaintext of the key-check values.
+const CHECK_PLAINTEXT: &[u8] = b"linera encrypted namespace";
+
+/// The key used for tests.
+#[cfg(with_testing)]
+pub const TEST_ENCRYPTION_KEY: [u8; KEY_SIZE] = [7; KEY_SIZE];
+
+/// The composed error type built from the inner error type.
+#[derive(Error, Debug)]
+pub enum EncryptionError<E> {
+    /// inner store error
+    #[error(transparent)]
+    InnerStoreError(#[from] E),
+
+    /// A stored value could not be decrypted, or was modified
+    #[error("a stored value could not be decrypted: wrong passphrase or corrupted value")]
+    Decryption,
+
+    /// The key is not the one the namespace is encrypted with
+    #[error("the namespace is encrypted with another key: wrong passphrase")]
+    WrongKey,
+
+    /// No key was given for an encrypted namespace
+    #[error("the namespace is encrypted and needs a passphrase")]
+    MissingKey,
+
+    /// A key was given for a namespace created without encryption
+    #[error("the namespace was created without encryption")]
+    UnencryptedNamespace,
+}
+
+impl<E: KeyValueStoreError> From<bcs::Error> for EncryptionError<E> {
+    fn from(error: bcs::Error) -> Self {
+        let error = E::from(error);
+        EncryptionError::InnerStoreError(error)
+    }
+}
+
+impl<E: KeyValueStoreError + 'static> KeyValueStoreError for EncryptionError<E> {
+    const BACKEND: &'static str = "encryption";
+}
+
+/// The secret from which the keys encrypting the values of each namespace are derived.
+#[derive(Clone)]
+pub struct EncryptionKey(Secret);
+
+#[derive(Clone)]
+enum Secret {
+    /// Random bytes.
+    Bytes([u8; KEY_SIZE]),
+    /// A passphrase, which is stretched with Argon2id.
+    Passphrase(String),
+}
+
+impl EncryptionKey {
+    /// Creates a key from its bytes.
+    pub fn new(bytes: [u8; KEY_SIZE]) -> Self {
+        EncryptionKey(Secret::Bytes(bytes))
+    }
+
+    /// Creates a key from a passphrase. The key of each namespace is derived from the
+    /// passphrase and the salt of the namespace with Argon2id.
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        EncryptionKey(Secret::Passphrase(passphrase.to_string()))
+    }
+
+    /// Derives the key of the namespace with the given salt.
+    fn namespace_key(&self, salt: &[u8]) -> [u8; KEY_SIZE] {
+        match &self.0 {
+            Secret::Bytes(bytes) => derive(bytes, &[b"namespace".as_slice(), salt].concat()),
+            Secret::Passphrase(passphrase) => {
+                let mut bytes = [0; KEY_SIZE];
+                Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut bytes)
+                    .expect("The default Argon2 parameters should be valid");
+                bytes
+            }
+        }
+    }
+}
+
+/// Derives a sub-key of `key` for the given purpose.
+fn derive(key: &[u8; KEY_SIZE], purpose: &[u8]) -> [u8; KEY_SIZE] {
+    let mut hasher = Sha3_256::default();
+    hasher.update(purpose);
+    hasher.update(key);
+    hasher.finalize().into()
+}
+
+/// Returns a new random salt for a namespace.
+#[cfg(with_getrandom)]
+pub(crate) fn new_salt() -> Vec<u8> {
+    use rand::RngCore as _;
+    let mut salt = vec![0; SALT_SIZE];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// Returns a new random salt for a namespace.
+#[cfg(not(with_getrandom))]
+pub(crate) fn new_salt() -> Vec<u8> {
+    panic!("Encrypted namespaces can't be created on a platform without randomness")
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+/// The cipher of the values of a namespace, used by an `EncryptionStore` and by the
+/// stores that encrypt their values themselves.
+#[derive(Clone)]
+pub(crate) struct Cipher {
+    /// The authenticated cipher.
+    aead: XChaCha20Poly1305,
+    /// The key used to derive the nonces.
+    nonce_key: [u8; KEY_SIZE],
+}
+
+impl Cipher {
+    /// Creates the cipher of the namespace with the given salt.
+    pub(crate) fn new(key: &EncryptionKey, salt: &[u8]) -> Self {
+        let key = key.namespace_key(salt);
+        let aead = XChaCha20Poly1305::new(&derive(&key, b"cipher").into());
+        let nonce_key = derive(&key, b"nonce");
+        Cipher { aead, nonce_key }
+    }
+
+    /// Encrypts the value of `key` under `root_key`.
+    pub(crate) fn encrypt_value(&self, root_key: &[u8], key: &[u8], value: &[u8]) -> Vec<u8> {
+        self.encrypt(&associated_data(root_key, key), value)
+    }
+
+    /// Returns the key-check value of the namespace of this cipher.
+    pub(crate) fn check_value(&self) -> Vec<u8> {
+        self.encrypt_value(SALT_ROOT_KEY, CHECK_KEY, CHECK_PLAINTEXT)
+    }
+
+    /// Whether `value` is the key-check value of the namespace of this cipher, i.e.
+    /// whether the namespace was encrypted with the same key.
+    pub(crate) fn is_check_value(&self, value: &[u8]) -> bool {
+        self.decrypt_value(SALT_ROOT_KEY, CHECK_KEY, value)
+            .is_some_and(|plaintext| plaintext == CHECK_PLAINTEXT)
+    }
+
+    /// Decrypts the value of `key` under `root_key`, or returns `None` if it was not
+    /// encrypted there with this cipher.
+    pub(crate) fn decrypt_value(
+        &self,
+        root_key: &[u8],
+        key: &[u8],
+        value: &[u8],
+    ) -> Option<Vec<u8>> {
+        self.decrypt(&associated_data(root_key, key), value)
+    }
+
+    fn encrypt(&self, associated_data: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut hasher = Sha3_256::default();
+        hasher.update(self.nonce_key);
+        hasher.update(associated_data);
+        hasher.update(value);
+        let hash = hasher.finalize();
+        let nonce = &hash[..NONCE_SIZE];
+        let payload = Payload {
+            msg: value,
+            aad: associated_data,
+        };
+        let ciphertext = self
+            .aead
+            .encrypt(XNonce::from_slice(nonce), payload)
+            .expect("Encrypting bytes in memory should not fail");
+        let mut encrypted_value = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        encrypted_value.extend_from_slice(nonce);
+        encrypted_value.extend(ciphertext);
+        encrypted_value
+    }
+
+    fn decrypt(&self, associated_data: &[u8], value: &[u8]) -> Option<Vec<u8>> {
+        if value.len() < NONCE_SIZE + TAG_SIZE {
+            return None;
+        }
+        let (nonce, ciphertext) = value.split_at(NONCE_SIZE);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: associated_data,
+        };
+        self.aead.decrypt(XNonce::from_slice(nonce), payload).ok()
+    }
+}
+
+/// The data authenticated with the value of `key` under `root_key`.
+fn associated_data(root_key: &[u8], key: &[u8]) -> Vec<u8> {
+    bcs::to_bytes(&(root_key, key)).expect("Serialization should not fail")
+}
+
+/// A key-value store encrypting its values.
+#[derive(Clone)]
+pub struct EncryptionStore<K> {
+    /// The underlying store of the transformed store.
+    store: K,
+    /// The cipher, or `None` if the values are stored in clear.
+    cipher: Option<Cipher>,
+    /// The root key of the store, which is authenticated with every value.
+    root_key: Vec<u8>,
+}
+
+impl<K> WithError for EncryptionStore<K>
+where
+    K: WithError,
+    K::Error: 'static,
+{
+    type Error = EncryptionError<K::Error>;
+}
+
+impl<K> ReadableKeyValueStore for EncryptionStore<K>
+where
+    K: ReadableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    const MAX_KEY_SIZE: usize = K::MAX_KEY_SIZE;
+    type Keys = K::Keys;
+    type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+    fn max_stream_queries(&self) -> usize {
+        self.store.max_stream_queries()
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        match self.store.read_value_bytes(key).await? {
+            None => Ok(None),
+            Some(value) => Ok(Some(self.decrypt_value(key, value)?)),
+        }
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        Ok(self.store.contains_key(key).await?)
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, Self::Error> {
+        Ok(self.store.contains_keys(keys).await?)
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let values = self.store.read_multi_values_bytes(keys.clone()).await?;
+        keys.iter()
+            .zip(values)
+            .map(|(key, value)| {
+                value
+                    .map(|value| self.decrypt_value(key, value))
+                    .transpose()
+            })
+            .collect()
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
+        Ok(self.store.find_keys_by_prefix(key_prefix).await?)
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, Self::Error> {
+        let key_values = self.store.find_key_values_by_prefix(key_prefix).await?;
+        let mut result = Vec::new();
+        for key_value in key_values.into_iterator_owned() {
+            let (key, value) = key_value?;
+            let value = self.decrypt_value(&[key_prefix, key.as_slice()].concat(), value)?;
+            result.push((key, value));
+        }
+        Ok(result)
+    }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(self.store.find_keys_in_range(key_prefix, range).await?)
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let key_values = self
+            .store
+            .find_key_values_in_range(key_prefix, range)
+            .await?;
+        key_values
+            .into_iter()
+            .map(|(key, value)| {
+                let value = self.decrypt_value(&[key_prefix, key.as_slice()].concat(), value)?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+}
+
+impl<K> WritableKeyValueStore for EncryptionStore<K>
+where
+    K: WritableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    // Every value is stored with a nonce and an authentication tag.
+    const MAX_VALUE_SIZE: usize = K::MAX_VALUE_SIZE - NONCE_SIZE - TAG_SIZE;
+
+    async fn write_batch(&self, mut batch: Batch) -> Result<(), Self::Error> {
+        if let Some(cipher) = &self.cipher {
+            for operation in &mut batch.operations {
+                if let WriteOperation::Put { key, value } = operation {
+                    *value = cipher.encrypt_value(&self.root_key, key, value);
+                }
+            }
+        }
+        Ok(self.store.write_batch(batch).await?)
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        Ok(self.store.clear_journal().await?)
+    }
+}
+
+/// The configuration type for the `EncryptionStore`.
+pub struct EncryptionConfig<C> {
+    /// The inner configuration of the `EncryptionStore`.
+    pub inner_config: C,
+    /// The encryption key, or `None` to store the values in clear.
+    pub key: Option<EncryptionKey>,
+}
+
+impl<K> AdminKeyValueStore for EncryptionStore<K>
+where
+    K: AdminKeyValueStore + ReadableKeyValueStore + WritableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    type Config = EncryptionConfig<K::Config>;
+
+    fn get_name() -> String {
+        format!("encryption {}", K::get_name())
+    }
+
+    async fn connect(
+        config: &Self::Config,
+        namespace: &str,
+        root_key: &[u8],
+    ) -> Result<Self, Self::Error> {
+        let store = K::connect(&config.inner_config, namespace, root_key).await?;
+        let cipher = Self::namespace_cipher(&store, config.key.as_ref()).await?;
+        let root_key = root_key.to_vec();
+        Ok(EncryptionStore {
+            store,
+            cipher,
+            root_key,
+        })
+    }
+
+    fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error> {
+        let store = self.store.clone_with_root_key(root_key)?;
+        let cipher = self.cipher.clone();
+        let root_key = root_key.to_vec();
+        Ok(EncryptionStore {
+            store,
+            cipher,
+            root_key,
+        })
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        Ok(K::list_all(&config.inner_config).await?)
+    }
+
+    async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
+        Ok(K::delete_all(&config.inner_config).await?)
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        Ok(K::exists(&config.inner_config, namespace).await?)
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        K::create(&config.inner_config, namespace).await?;
+        if let Some(key) = &config.key {
+            let store = K::connect(&config.inner_config, namespace, SALT_ROOT_KEY).await?;
+            let salt = new_salt();
+            let cipher = Cipher::new(key, &salt);
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(SALT_KEY.to_vec(), salt);
+            batch.put_key_value_bytes(CHECK_KEY.to_vec(), cipher.check_value());
+            store.write_batch(batch).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        Ok(K::delete(&config.inner_config, namespace).await?)
+    }
+
+    async fn snapshot(&self, path: &Path) -> Result<(), Self::Error> {
+        Ok(self.store.snapshot(path).await?)
+    }
+
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), Self::Error> {
+        Ok(K::restore(&config.inner_config, namespace, path).await?)
+    }
+}
+
+#[cfg(with_testing)]
+impl<K> TestKeyValueStore for EncryptionStore<K>
+where
+    K: TestKeyValueStore + ReadableKeyValueStore + WritableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    async fn new_test_config() -> Result<EncryptionConfig<K::Config>, Self::Error> {
+        let inner_config = K::new_test_config().await?;
+        Ok(EncryptionConfig {
+            inner_config,
+            key: Some(EncryptionKey::new(TEST_ENCRYPTION_KEY)),
+        })
+    }
+}
+
+impl<K> EncryptionStore<K>
+where
+    K: AdminKeyValueStore + ReadableKeyValueStore + WritableKeyValueStore,
+{
+    /// Returns the cipher of the namespace of `store` for the given key, if any, after
+    /// checking that the namespace is encrypted with this key, or not encrypted if there
+    /// is no key.
+    async fn namespace_cipher(
+        store: &K,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Option<Cipher>, EncryptionError<K::Error>> {
+        let store = store.clone_with_root_key(SALT_ROOT_KEY)?;
+        let keys = vec![SALT_KEY.to_vec(), CHECK_KEY.to_vec()];
+        let mut values = store.read_multi_values_bytes(keys).await?.into_iter();
+        let (salt, check_value) = (values.next().flatten(), values.next().flatten());
+        match (key, salt, check_value) {
+            (None, _, None) => Ok(None),
+            (None, _, Some(_)) => Err(EncryptionError::MissingKey),
+            (Some(key), Some(salt), Some(check_value)) => {
+                let cipher = Cipher::new(key, &salt);
+                if !cipher.is_check_value(&check_value) {
+                    return Err(EncryptionError::WrongKey);
+                }
+                Ok(Some(cipher))
+            }
+            (Some(_), _, _) => Err(EncryptionError::UnencryptedNamespace),
+        }
+    }
+}
+
+impl<K> EncryptionStore<K>
+where
+    K: WithError,
+{
+    /// Creates a new store encrypting the values with the given key, if any, for a
+    /// namespace with the given salt. The `root_key` must be the one of the underlying
+    /// store.
+    pub fn new(store: K, root_key: &[u8], key: Option<&EncryptionKey>, salt: &[u8]) -> Self {
+        let cipher = key.map(|key| Cipher::new(key, salt));
+        let root_key = root_key.to_vec();
+        EncryptionStore {
+            store,
+            cipher,
+            root_key,
+        }
+    }
+
+    /// Whether the values are encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    fn decrypt_value(
+        &self,
+        key: &[u8],
+        value: Vec<u8>,
+    ) -> Result<Vec<u8>, EncryptionError<K::Error>> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(value);
+        };
+        cipher
+            .decrypt_value(&self.root_key, key, &value)
+            .ok_or(EncryptionError::Decryption)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EncryptionConfig, EncryptionError, EncryptionKey, EncryptionStore, SALT_KEY, SALT_ROOT_KEY,
+    };
+    use crate::{
+        batch::Batch,
+        memory::{MemoryStore, MemoryStoreConfig, TEST_MEMORY_MAX_STREAM_QUERIES},
+        random::generate_test_namespace,
+        store::{
+            AdminKeyValueStore as _, KeyRange, ReadableKeyValueStore, TestKeyValueStore as _,
+            WritableKeyValueStore,
+        },
+    };
+
+    const SALT: &[u8] = &[0; 16];
+
+    #[tokio::test]
+    async fn test_encryption_round_trip() {
+        let store = MemoryStore::new_test_store().await.unwrap();
+        let key = EncryptionKey::new([1; 32]);
+        let encryption_store = EncryptionStore::new(store.clone(), &[], Some(&key), SALT);
+        let value = b"some secret value".to_vec();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0, 1], value.clone());
+        batch.put_key_value_bytes(vec![0, 2], Vec::new());
+        encryption_store.write_batch(batch).await.unwrap();
+
+        let stored_value = store.read_value_bytes(&[0, 1]).await.unwrap().unwrap();
+        assert!(!stored_value
+            .windows(value.len())
+            .any(|window| window == value.as_slice()));
+
+        let values = encryption_store
+            .read_multi_values_bytes(vec![vec![0, 1], vec![0, 2], vec![0, 3]])
+            .await
+            .unwrap();
+        assert_eq!(values, vec![Some(value.clone()), Some(Vec::new()), None]);
+        let key_values = encryption_store
+            .find_key_values_by_prefix(&[0])
+            .await
+            .unwrap();
+        assert_eq!(
+            key_values,
+            vec![(vec![1], value.clone()), (vec![2], Vec::new())]
+        );
+        let range = KeyRange::new(vec![1], Some(vec![2]));
+        let key_values = encryption_store
+            .find_key_values_in_range(&[0], &range)
+            .await
+            .unwrap();
+        assert_eq!(key_values, vec![(vec![1], value)]);
+    }
+
+    #[tokio::test]
+    async fn test_encryption_authenticates_values() {
+        let store = MemoryStore::new_test_store().await.unwrap();
+        let key = EncryptionKey::new([1; 32]);
+        let encryption_store = EncryptionStore::new(store.clone(), &[], Some(&key), SALT);
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![42; 10]);
+        encryption_store.write_batch(batch).await.unwrap();
+
+        // The wrong key cannot decrypt the value.
+        let other_key = EncryptionKey::new([2; 32]);
+        let other_store = EncryptionStore::new(store.clone(), &[], Some(&other_key), SALT);
+        assert!(other_store.read_value_bytes(&[1]).await.is_err());
+
+        // A value moved to another key is rejected.
+        let stored_value = store.read_value_bytes(&[1]).await.unwrap().unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![2], stored_value);
+        store.write_batch(batch).await.unwrap();
+        assert!(encryption_store.read_value_bytes(&[2]).await.is_err());
+
+        // So is a value read under another root key.
+        let other_root = encryption_store.clone_with_root_key(&[3]).unwrap();
+        let stored_value = store.read_value_bytes(&[1]).await.unwrap().unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], stored_value);
+        store
+            .clone_with_root_key(&[3])
+            .unwrap()
+            .write_batch(batch)
+            .await
+            .unwrap();
+        assert!(other_root.read_value_bytes(&[1]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_encryption_namespace_salts() {
+        let config = EncryptionConfig {
+            inner_config: MemoryStore::new_test_config().await.unwrap(),
+            key: Some(EncryptionKey::new([1; 32])),
+        };
+        let namespaces = [generate_test_namespace(), generate_test_namespace()];
+        let mut salts = Vec::new();
+        let mut stored_values = Vec::new();
+        for namespace in &namespaces {
+            let store =
+                EncryptionStore::<MemoryStore>::recreate_and_connect(&config, namespace, &[])
+                    .await
+                    .unwrap();
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(vec![1], vec![42; 10]);
+            store.write_batch(batch).await.unwrap();
+
+            // The salt is stored in clear in the namespace.
+            let inner_store = MemoryStore::connect(&config.inner_config, namespace, &[])
+                .await
+                .unwrap();
+            let salt_store = inner_store.clone_with_root_key(SALT_ROOT_KEY).unwrap();
+            salts.push(
+                salt_store
+                    .read_value_bytes(SALT_KEY)
+                    .await
+                    .unwrap()
+                    .unwrap(),
+            );
+            stored_values.push(inner_store.read_value_bytes(&[1]).await.unwrap().unwrap());
+
+            // A new connection uses the same salt.
+            let store = EncryptionStore::<MemoryStore>::connect(&config, namespace, &[])
+                .await
+                .unwrap();
+            assert_eq!(
+                store.read_value_bytes(&[1]).await.unwrap(),
+                Some(vec![42; 10])
+            );
+        }
+        // The same key encrypts the namespaces differently.
+        assert_ne!(salts[0], salts[1]);
+        assert_ne!(stored_values[0], stored_values[1]);
+    }
+
+    #[tokio::test]
+    async fn test_encryption_namespace_key_checks() {
+        let key = EncryptionKey::new([1; 32]);
+        let config_with = |key: Option<&EncryptionKey>| EncryptionConfig {
+            inner_config: MemoryStoreConfig::new(TEST_MEMORY_MAX_STREAM_QUERIES),
+            key: key.cloned(),
+        };
+        let encrypted_namespace = generate_test_namespace();
+        EncryptionStore::<MemoryStore>::recreate_and_connect(
+            &config_with(Some(&key)),
+            &encrypted_namespace,
+            &[],
+        )
+        .await
+        .unwrap();
+        let clear_namespace = generate_test_namespace();
+        EncryptionStore::<MemoryStore>::recreate_and_connect(
+            &config_with(None),
+            &clear_namespace,
+            &[],
+        )
+        .await
+        .unwrap();
+
+        let other_key = EncryptionKey::new([2; 32]);
+        let result = EncryptionStore::<MemoryStore>::connect(
+            &config_with(Some(&other_key)),
+            &encrypted_namespace,
+            &[],
+        )
+        .await;
+        assert!(matches!(result, Err(EncryptionError::WrongKey)));
+        let result =
+            EncryptionStore::<MemoryStore>::connect(&config_with(None), &encrypted_namespace, &[])
+                .await;
+        assert!(matches!(result, Err(EncryptionError::MissingKey)));
+        let result = EncryptionStore::<MemoryStore>::connect(
+            &config_with(Some(&key)),
+            &clear_namespace,
+            &[],
+        )
+        .await;
+        assert!(matches!(result, Err(EncryptionError::UnencryptedNamespace)));
+    }
+}
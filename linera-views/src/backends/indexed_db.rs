@@ -2,8 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Implements [`crate::store::KeyValueStore`] for the IndexedDB Web database.
+//!
+//! The values can be encrypted, like with the [`crate::encryption::EncryptionStore`]
+//! wrapper of the other backends, which can't wrap this store since it is not `Send`.
 
-use std::rc::Rc;
+use std::{ops::Bound::Excluded, path::Path, rc::Rc};
 
 use futures::future;
 use indexed_db_futures::{js_sys, prelude::*, web_sys};
@@ -12,9 +15,10 @@ use thiserror::Error;
 use crate::{
     batch::{Batch, WriteOperation},
     common::get_upper_bound_option,
+    encryption::{new_salt, Cipher, EncryptionKey, CHECK_KEY, SALT_KEY, SALT_ROOT_KEY},
     store::{
-        CommonStoreConfig, KeyValueStoreError, LocalAdminKeyValueStore, LocalReadableKeyValueStore,
-        LocalWritableKeyValueStore, WithError,
+        CommonStoreConfig, KeyRange, KeyValueStoreError, LocalAdminKeyValueStore,
+        LocalReadableKeyValueStore, LocalWritableKeyValueStore, WithError,
     },
 };
 
@@ -27,11 +31,14 @@ pub struct IndexedDbStoreConfig {
 
 impl IndexedDbStoreConfig {
     /// Creates a `IndexedDbStoreConfig`. `max_concurrent_queries` and `cache_size` are not used.
-    pub fn new(max_stream_queries: usize) -> Self {
+    /// The values are encrypted if an `encryption_key` is given.
+    pub fn new(max_stream_queries: usize, encryption_key: Option<EncryptionKey>) -> Self {
         let common_config = CommonStoreConfig {
             max_concurrent_queries: None,
             max_stream_queries,
             cache_size: 1000,
+            compression_threshold: None,
+            encryption_key,
         };
         Self { common_config }
     }
@@ -53,6 +60,8 @@ pub struct IndexedDbStore {
     pub max_stream_queries: usize,
     /// The used root key
     root_key: Vec<u8>,
+    /// The cipher of the values, or `None` if they are stored in clear.
+    cipher: Option<Cipher>,
 }
 
 impl IndexedDbStore {
@@ -70,6 +79,95 @@ impl IndexedDbStore {
         full_key.extend(key);
         full_key
     }
+
+    fn encrypt_value(&self, key: &[u8], value: Vec<u8>) -> Vec<u8> {
+        match &self.cipher {
+            None => value,
+            Some(cipher) => cipher.encrypt_value(&self.root_key, key, &value),
+        }
+    }
+
+    fn decrypt_value(&self, key: &[u8], value: Vec<u8>) -> Result<Vec<u8>, IndexedDbStoreError> {
+        match &self.cipher {
+            None => Ok(value),
+            Some(cipher) => cipher
+                .decrypt_value(&self.root_key, key, &value)
+                .ok_or(IndexedDbStoreError::Decryption),
+        }
+    }
+
+    /// Returns the cipher of the namespace for the given key, if any, after checking that
+    /// the namespace is encrypted with this key, or not encrypted if there is no key. The
+    /// namespaces being created when they are opened, an empty namespace is set up for
+    /// encryption.
+    async fn namespace_cipher(
+        &self,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Option<Cipher>, IndexedDbStoreError> {
+        let mut store = self.clone_with_root_key(SALT_ROOT_KEY)?;
+        // The salt and the key-check value are stored in clear.
+        store.cipher = None;
+        let keys = vec![SALT_KEY.to_vec(), CHECK_KEY.to_vec()];
+        let mut values = store.read_multi_values_bytes(keys).await?.into_iter();
+        let (salt, check_value) = (values.next().flatten(), values.next().flatten());
+        match (key, salt, check_value) {
+            (None, _, None) => Ok(None),
+            (None, _, Some(_)) => Err(IndexedDbStoreError::MissingKey),
+            (Some(key), Some(salt), Some(check_value)) => {
+                let cipher = Cipher::new(key, &salt);
+                if !cipher.is_check_value(&check_value) {
+                    return Err(IndexedDbStoreError::WrongKey);
+                }
+                Ok(Some(cipher))
+            }
+            (Some(key), _, _) => {
+                let namespace = self.clone_with_root_key(&[])?;
+                let range = KeyRange::new(Vec::new(), None).with_limit(1);
+                if !namespace.find_keys_in_range(&[], &range).await?.is_empty() {
+                    return Err(IndexedDbStoreError::UnencryptedNamespace);
+                }
+                let salt = new_salt();
+                let cipher = Cipher::new(key, &salt);
+                let mut batch = Batch::new();
+                batch.put_key_value_bytes(SALT_KEY.to_vec(), salt);
+                batch.put_key_value_bytes(CHECK_KEY.to_vec(), cipher.check_value());
+                store.write_batch(batch).await?;
+                Ok(Some(cipher))
+            }
+        }
+    }
+
+    /// Opens the object store of the namespace, which is created if needed, without
+    /// setting up the encryption of the values.
+    async fn open(
+        config: &IndexedDbStoreConfig,
+        namespace: &str,
+        root_key: &[u8],
+    ) -> Result<Self, IndexedDbStoreError> {
+        let namespace = namespace.to_string();
+        let object_store_name = namespace.clone();
+        let mut database = IdbDatabase::open(DATABASE_NAME)?.await?;
+
+        if !database.object_store_names().any(|n| n == namespace) {
+            let version = database.version();
+            database.close();
+            let mut db_req = IdbDatabase::open_f64(DATABASE_NAME, version + 1.0)?;
+            db_req.set_on_upgrade_needed(Some(move |event: &IdbVersionChangeEvent| {
+                event.db().create_object_store(&namespace)?;
+                Ok(())
+            }));
+            database = db_req.await?;
+        }
+        let database = Rc::new(database);
+        let root_key = root_key.to_vec();
+        Ok(IndexedDbStore {
+            database,
+            object_store_name,
+            max_stream_queries: config.common_config.max_stream_queries,
+            root_key,
+            cipher: None,
+        })
+    }
 }
 
 fn prefix_to_range(prefix: &[u8]) -> Result<web_sys::IdbKeyRange, wasm_bindgen::JsValue> {
@@ -87,6 +185,83 @@ fn prefix_to_range(prefix: &[u8]) -> Result<web_sys::IdbKeyRange, wasm_bindgen::
     }
 }
 
+fn key_range_to_range(
+    key_prefix: &[u8],
+    range: &KeyRange,
+) -> Result<web_sys::IdbKeyRange, wasm_bindgen::JsValue> {
+    let mut lower = key_prefix.to_vec();
+    lower.extend(&range.start);
+    let lower = js_sys::Uint8Array::from(&lower[..]);
+    if let Excluded(upper) = range.bounds(key_prefix).1 {
+        let upper = js_sys::Uint8Array::from(&upper[..]);
+        web_sys::IdbKeyRange::bound_with_lower_open_and_upper_open(
+            &lower.into(),
+            &upper.into(),
+            false,
+            true,
+        )
+    } else {
+        web_sys::IdbKeyRange::lower_bound(&lower.into())
+    }
+}
+
+impl IndexedDbStore {
+    /// Finds the key-value pairs of the `range` following `key_prefix`. The values are
+    /// left empty unless `with_values` is set.
+    async fn find_key_values_in_range_internal(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+        with_values: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, IndexedDbStoreError> {
+        let limit = range.limit.unwrap_or(usize::MAX);
+        let mut key_values = vec![];
+        if range.is_empty() || limit == 0 {
+            return Ok(key_values);
+        }
+        let key_prefix = self.full_key(key_prefix);
+        let idb_range = key_range_to_range(&key_prefix, range)?;
+        let direction = if range.reverse {
+            IdbCursorDirection::Prev
+        } else {
+            IdbCursorDirection::Next
+        };
+        let transaction = self.database.transaction_on_one(&self.object_store_name)?;
+        let object_store = transaction.object_store(&self.object_store_name)?;
+        let Some(cursor) = object_store
+            .open_cursor_with_range_and_direction_owned(idb_range, direction)?
+            .await?
+        else {
+            return Ok(key_values);
+        };
+
+        while key_values.len() < limit {
+            let Some(key) = cursor.primary_key() else {
+                break;
+            };
+            let key = js_sys::Uint8Array::new(&key);
+            let value = if with_values {
+                let value = js_sys::Uint8Array::new(&cursor.value()).to_vec();
+                let key = key
+                    .subarray(self.root_key.len() as u32, key.length())
+                    .to_vec();
+                self.decrypt_value(&key, value)?
+            } else {
+                Vec::new()
+            };
+            key_values.push((
+                key.subarray(key_prefix.len() as u32, key.length()).to_vec(),
+                value,
+            ));
+            if !cursor.continue_cursor()?.await? {
+                break;
+            }
+        }
+
+        Ok(key_values)
+    }
+}
+
 impl WithError for IndexedDbStore {
     type Error = IndexedDbStoreError;
 }
@@ -101,10 +276,12 @@ impl LocalReadableKeyValueStore for IndexedDbStore {
     }
 
     async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, IndexedDbStoreError> {
-        let key = self.full_key(key);
-        let key = js_sys::Uint8Array::from(key.as_slice());
-        let value = self.with_object_store(|o| o.get(&key))??.await?;
-        Ok(value.map(|v| js_sys::Uint8Array::new(&v).to_vec()))
+        let full_key = self.full_key(key);
+        let full_key = js_sys::Uint8Array::from(full_key.as_slice());
+        let value = self.with_object_store(|o| o.get(&full_key))??.await?;
+        value
+            .map(|v| self.decrypt_value(key, js_sys::Uint8Array::new(&v).to_vec()))
+            .transpose()
     }
 
     async fn contains_key(&self, key: &[u8]) -> Result<bool, IndexedDbStoreError> {
@@ -169,9 +346,15 @@ impl LocalReadableKeyValueStore for IndexedDbStore {
                 break;
             };
             let key = js_sys::Uint8Array::new(&key);
+            let value = js_sys::Uint8Array::new(&cursor.value()).to_vec();
+            let value = self.decrypt_value(
+                &key.subarray(self.root_key.len() as u32, key.length())
+                    .to_vec(),
+                value,
+            )?;
             key_values.push((
                 key.subarray(key_prefix.len() as u32, key.length()).to_vec(),
-                js_sys::Uint8Array::new(&cursor.value()).to_vec(),
+                value,
             ));
             if !cursor.continue_cursor()?.await? {
                 break;
@@ -180,9 +363,30 @@ impl LocalReadableKeyValueStore for IndexedDbStore {
 
         Ok(key_values)
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, IndexedDbStoreError> {
+        let key_values = self
+            .find_key_values_in_range_internal(key_prefix, range, false)
+            .await?;
+        Ok(key_values.into_iter().map(|(key, _)| key).collect())
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, IndexedDbStoreError> {
+        self.find_key_values_in_range_internal(key_prefix, range, true)
+            .await
+    }
 }
 
 impl LocalWritableKeyValueStore for IndexedDbStore {
+    // Encrypted values are larger, but IndexedDB does not limit their size anyway.
     const MAX_VALUE_SIZE: usize = usize::MAX;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), IndexedDbStoreError> {
@@ -194,6 +398,7 @@ impl LocalWritableKeyValueStore for IndexedDbStore {
         for ent in batch.operations {
             match ent {
                 WriteOperation::Put { key, value } => {
+                    let value = self.encrypt_value(&key, value);
                     let key = self.full_key(&key);
                     object_store
                         .put_key_val_owned(
@@ -237,28 +442,10 @@ impl LocalAdminKeyValueStore for IndexedDbStore {
         namespace: &str,
         root_key: &[u8],
     ) -> Result<Self, IndexedDbStoreError> {
-        let namespace = namespace.to_string();
-        let object_store_name = namespace.clone();
-        let mut database = IdbDatabase::open(DATABASE_NAME)?.await?;
-
-        if !database.object_store_names().any(|n| n == namespace) {
-            let version = database.version();
-            database.close();
-            let mut db_req = IdbDatabase::open_f64(DATABASE_NAME, version + 1.0)?;
-            db_req.set_on_upgrade_needed(Some(move |event: &IdbVersionChangeEvent| {
-                event.db().create_object_store(&namespace)?;
-                Ok(())
-            }));
-            database = db_req.await?;
-        }
-        let database = Rc::new(database);
-        let root_key = root_key.to_vec();
-        Ok(IndexedDbStore {
-            database,
-            object_store_name,
-            max_stream_queries: config.common_config.max_stream_queries,
-            root_key,
-        })
+        let mut store = Self::open(config, namespace, root_key).await?;
+        let key = config.common_config.encryption_key.as_ref();
+        store.cipher = store.namespace_cipher(key).await?;
+        Ok(store)
     }
 
     fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, IndexedDbStoreError> {
@@ -266,17 +453,19 @@ impl LocalAdminKeyValueStore for IndexedDbStore {
         let object_store_name = self.object_store_name.clone();
         let max_stream_queries = self.max_stream_queries;
         let root_key = root_key.to_vec();
+        let cipher = self.cipher.clone();
         Ok(Self {
             database,
             object_store_name,
             max_stream_queries,
             root_key,
+            cipher,
         })
     }
 
     async fn list_all(config: &Self::Config) -> Result<Vec<String>, IndexedDbStoreError> {
         let root_key = &[];
-        Ok(Self::connect(config, "", root_key)
+        Ok(Self::open(config, "", root_key)
             .await?
             .database
             .object_store_names()
@@ -285,7 +474,7 @@ impl LocalAdminKeyValueStore for IndexedDbStore {
 
     async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, IndexedDbStoreError> {
         let root_key = &[];
-        Ok(Self::connect(config, "", root_key)
+        Ok(Self::open(config, "", root_key)
             .await?
             .database
             .object_store_names()
@@ -294,7 +483,7 @@ impl LocalAdminKeyValueStore for IndexedDbStore {
 
     async fn create(config: &Self::Config, namespace: &str) -> Result<(), IndexedDbStoreError> {
         let root_key = &[];
-        Self::connect(config, "", root_key)
+        Self::open(config, "", root_key)
             .await?
             .database
             .create_object_store(namespace)?;
@@ -303,11 +492,23 @@ impl LocalAdminKeyValueStore for IndexedDbStore {
 
     async fn delete(config: &Self::Config, namespace: &str) -> Result<(), IndexedDbStoreError> {
         let root_key = &[];
-        Ok(Self::connect(config, "", root_key)
+        Ok(Self::open(config, "", root_key)
             .await?
             .database
             .delete_object_store(namespace)?)
     }
+
+    async fn snapshot(&self, _path: &Path) -> Result<(), IndexedDbStoreError> {
+        Err(IndexedDbStoreError::SnapshotNotSupported)
+    }
+
+    async fn restore(
+        _config: &Self::Config,
+        _namespace: &str,
+        _path: &Path,
+    ) -> Result<(), IndexedDbStoreError> {
+        Err(IndexedDbStoreError::SnapshotNotSupported)
+    }
 }
 
 #[cfg(with_testing)]
@@ -319,7 +520,7 @@ mod testing {
     pub async fn create_indexed_db_store_stream_queries(
         max_stream_queries: usize,
     ) -> IndexedDbStore {
-        let config = IndexedDbStoreConfig::new(max_stream_queries);
+        let config = IndexedDbStoreConfig::new(max_stream_queries, None);
         let namespace = generate_test_namespace();
         let root_key = &[];
         IndexedDbStore::connect(&config, &namespace, root_key)
@@ -327,6 +528,20 @@ mod testing {
             .unwrap()
     }
 
+    /// Creates a test IndexedDB store encrypting its values.
+    pub async fn create_indexed_db_encrypted_test_store() -> IndexedDbStore {
+        let config = IndexedDbStoreConfig::new(
+            TEST_INDEX_DB_MAX_STREAM_QUERIES,
+            Some(EncryptionKey::new(crate::encryption::TEST_ENCRYPTION_KEY)),
+        );
+        let namespace = generate_test_namespace();
+        // The root key `[]` would contain the salt of the namespace.
+        let root_key = &[0];
+        IndexedDbStore::connect(&config, &namespace, root_key)
+            .await
+            .unwrap()
+    }
+
     /// Creates a test IndexedDB store for working.
     #[cfg(with_testing)]
     pub async fn create_indexed_db_test_store() -> IndexedDbStore {
@@ -355,6 +570,26 @@ pub enum IndexedDbStoreError {
     /// JavaScript threw an exception whilst handling IndexedDB operations
     #[error("JavaScript exception: {0:?}")]
     Js(wasm_bindgen::JsValue),
+
+    /// IndexedDB has no access to the filesystem
+    #[error("Snapshots are not supported for IndexedDB")]
+    SnapshotNotSupported,
+
+    /// A stored value could not be decrypted, or was modified
+    #[error("a stored value could not be decrypted: wrong passphrase or corrupted value")]
+    Decryption,
+
+    /// The key is not the one the namespace is encrypted with
+    #[error("the namespace is encrypted with another key: wrong passphrase")]
+    WrongKey,
+
+    /// No key was given for an encrypted namespace
+    #[error("the namespace is encrypted and needs a passphrase")]
+    MissingKey,
+
+    /// A key was given for a namespace created without encryption
+    #[error("the namespace was created without encryption")]
+    UnencryptedNamespace,
 }
 
 impl From<web_sys::DomException> for IndexedDbStoreError {
@@ -19,6 +19,8 @@
 //! time the data in a block are written, the journal header is updated in the same
 //! transaction to mark the block as processed.
 
+use std::path::Path;
+
 use async_trait::async_trait;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use static_assertions as sa;
@@ -27,7 +29,8 @@ use thiserror::Error;
 use crate::{
     batch::{Batch, BatchValueWriter, DeletePrefixExpander, SimplifiedBatch},
     store::{
-        AdminKeyValueStore, KeyIterable, ReadableKeyValueStore, WithError, WritableKeyValueStore,
+        AdminKeyValueStore, KeyIterable, KeyRange, ReadableKeyValueStore, WithError,
+        WritableKeyValueStore,
     },
     views::MIN_VIEW_TAG,
 };
@@ -170,6 +173,22 @@ where
     ) -> Result<Self::KeyValues, Self::Error> {
         self.store.find_key_values_by_prefix(key_prefix).await
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.store.find_keys_in_range(key_prefix, range).await
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        self.store.find_key_values_in_range(key_prefix, range).await
+    }
 }
 
 impl<K> AdminKeyValueStore for JournalingKeyValueStore<K>
@@ -215,6 +234,18 @@ where
     async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
         K::delete(config, namespace).await
     }
+
+    async fn snapshot(&self, path: &Path) -> Result<(), Self::Error> {
+        self.store.snapshot(path).await
+    }
+
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), Self::Error> {
+        K::restore(config, namespace, path).await
+    }
 }
 
 impl<K> WritableKeyValueStore for JournalingKeyValueStore<K>
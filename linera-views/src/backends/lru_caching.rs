@@ -10,6 +10,7 @@ pub const TEST_CACHE_SIZE: usize = 1000;
 use std::sync::LazyLock;
 use std::{
     collections::{btree_map, hash_map::RandomState, BTreeMap},
+    path::Path,
     sync::{Arc, Mutex},
 };
 
@@ -20,7 +21,9 @@ use {linera_base::prometheus_util::register_int_counter_vec, prometheus::IntCoun
 use crate::{
     batch::{Batch, WriteOperation},
     common::get_interval,
-    store::{AdminKeyValueStore, ReadableKeyValueStore, WithError, WritableKeyValueStore},
+    store::{
+        AdminKeyValueStore, KeyRange, ReadableKeyValueStore, WithError, WritableKeyValueStore,
+    },
 };
 #[cfg(with_testing)]
 use crate::{memory::MemoryStore, store::TestKeyValueStore};
@@ -236,6 +239,22 @@ where
     ) -> Result<Self::KeyValues, Self::Error> {
         self.store.find_key_values_by_prefix(key_prefix).await
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.store.find_keys_in_range(key_prefix, range).await
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        self.store.find_key_values_in_range(key_prefix, range).await
+    }
 }
 
 impl<K> WritableKeyValueStore for LruCachingStore<K>
@@ -327,6 +346,18 @@ where
     async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
         K::delete(&config.inner_config, namespace).await
     }
+
+    async fn snapshot(&self, path: &Path) -> Result<(), Self::Error> {
+        self.store.snapshot(path).await
+    }
+
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), Self::Error> {
+        K::restore(&config.inner_config, namespace, path).await
+    }
 }
 
 #[cfg(with_testing)]
@@ -5,6 +5,7 @@
 
 use std::{
     collections::BTreeMap,
+    path::Path,
     sync::{Arc, LazyLock, Mutex, RwLock},
 };
 
@@ -18,8 +19,8 @@ use crate::{
     batch::{Batch, WriteOperation},
     common::get_interval,
     store::{
-        AdminKeyValueStore, CommonStoreInternalConfig, KeyValueStoreError, ReadableKeyValueStore,
-        WithError, WritableKeyValueStore,
+        AdminKeyValueStore, CommonStoreInternalConfig, KeyRange, KeyValueStoreError,
+        ReadableKeyValueStore, WithError, WritableKeyValueStore,
     },
 };
 
@@ -97,8 +98,44 @@ impl MemoryStores {
     fn sync_delete(&mut self, namespace: &str) {
         self.stores.remove(namespace);
     }
+
+    fn sync_snapshot(
+        &self,
+        namespace: &str,
+    ) -> Result<BTreeMap<Vec<u8>, MemoryStoreMap>, MemoryStoreError> {
+        let Some(stores) = self.stores.get(namespace) else {
+            return Err(MemoryStoreError::NamespaceNotFound);
+        };
+        let maps = stores
+            .iter()
+            .map(|(root_key, map)| {
+                let map = map.read().expect("MemoryStore lock should not be poisoned");
+                (root_key.clone(), map.clone())
+            })
+            .collect();
+        Ok(maps)
+    }
+
+    fn sync_restore(
+        &mut self,
+        namespace: &str,
+        maps: BTreeMap<Vec<u8>, MemoryStoreMap>,
+    ) -> Result<(), MemoryStoreError> {
+        if self.stores.contains_key(namespace) {
+            return Err(MemoryStoreError::NamespaceAlreadyExists);
+        }
+        let stores = maps
+            .into_iter()
+            .map(|(root_key, map)| (root_key, Arc::new(RwLock::new(map))))
+            .collect();
+        self.stores.insert(namespace.to_string(), stores);
+        Ok(())
+    }
 }
 
+/// The name of the file holding the snapshot of a namespace.
+const SNAPSHOT_FILE: &str = "snapshot.bcs";
+
 /// The global variables of the Namespace memory stores
 static MEMORY_STORES: LazyLock<Mutex<MemoryStores>> =
     LazyLock::new(|| Mutex::new(MemoryStores::default()));
@@ -217,6 +254,62 @@ impl ReadableKeyValueStore for MemoryStore {
         }
         Ok(key_values)
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, MemoryStoreError> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let map = self
+            .map
+            .read()
+            .expect("MemoryStore lock should not be poisoned");
+        let len = key_prefix.len();
+        let keys = map.range(range.bounds(key_prefix)).map(|(key, _)| key);
+        let limit = range.limit.unwrap_or(usize::MAX);
+        let keys = if range.reverse {
+            keys.rev()
+                .take(limit)
+                .map(|key| key[len..].to_vec())
+                .collect()
+        } else {
+            keys.take(limit).map(|key| key[len..].to_vec()).collect()
+        };
+        Ok(keys)
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, MemoryStoreError> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let map = self
+            .map
+            .read()
+            .expect("MemoryStore lock should not be poisoned");
+        let len = key_prefix.len();
+        let entries = map.range(range.bounds(key_prefix));
+        let limit = range.limit.unwrap_or(usize::MAX);
+        let key_values = if range.reverse {
+            entries
+                .rev()
+                .take(limit)
+                .map(|(key, value)| (key[len..].to_vec(), value.to_vec()))
+                .collect()
+        } else {
+            entries
+                .take(limit)
+                .map(|(key, value)| (key[len..].to_vec(), value.to_vec()))
+                .collect()
+        };
+        Ok(key_values)
+    }
 }
 
 impl WritableKeyValueStore for MemoryStore {
@@ -364,6 +457,34 @@ impl AdminKeyValueStore for MemoryStore {
         memory_stores.sync_delete(namespace);
         Ok(())
     }
+
+    async fn snapshot(&self, path: &Path) -> Result<(), MemoryStoreError> {
+        let maps = {
+            let memory_stores = MEMORY_STORES
+                .lock()
+                .expect("MEMORY_STORES lock should not be poisoned");
+            memory_stores.sync_snapshot(&self.namespace)?
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::create_dir(path)?;
+        std::fs::write(path.join(SNAPSHOT_FILE), bcs::to_bytes(&maps)?)?;
+        Ok(())
+    }
+
+    async fn restore(
+        _config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), MemoryStoreError> {
+        let bytes = std::fs::read(path.join(SNAPSHOT_FILE))?;
+        let maps = bcs::from_bytes(&bytes)?;
+        let mut memory_stores = MEMORY_STORES
+            .lock()
+            .expect("MEMORY_STORES lock should not be poisoned");
+        memory_stores.sync_restore(namespace, maps)
+    }
 }
 
 #[cfg(with_testing)]
@@ -400,6 +521,14 @@ pub enum MemoryStoreError {
     /// The namespace does not exist
     #[error("The namespace does not exist")]
     NamespaceNotFound,
+
+    /// The namespace already exists
+    #[error("The namespace already exists")]
+    NamespaceAlreadyExists,
+
+    /// Filesystem error
+    #[error("Filesystem error: {0}")]
+    FsError(#[from] std::io::Error),
 }
 
 impl KeyValueStoreError for MemoryStoreError {
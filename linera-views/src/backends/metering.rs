@@ -5,6 +5,7 @@
 
 use std::{
     collections::{btree_map::Entry, BTreeMap},
+    path::Path,
     sync::{Arc, LazyLock, Mutex},
 };
 
@@ -19,8 +20,8 @@ use crate::store::TestKeyValueStore;
 use crate::{
     batch::Batch,
     store::{
-        AdminKeyValueStore, KeyIterable as _, KeyValueIterable as _, ReadableKeyValueStore,
-        WithError, WritableKeyValueStore,
+        AdminKeyValueStore, KeyIterable as _, KeyRange, KeyValueIterable as _,
+        ReadableKeyValueStore, WithError, WritableKeyValueStore,
     },
 };
 
@@ -33,6 +34,8 @@ pub struct KeyValueStoreMetrics {
     read_multi_values_bytes_latency: HistogramVec,
     find_keys_by_prefix_latency: HistogramVec,
     find_key_values_by_prefix_latency: HistogramVec,
+    find_keys_in_range_latency: HistogramVec,
+    find_key_values_in_range_latency: HistogramVec,
     write_batch_latency: HistogramVec,
     clear_journal_latency: HistogramVec,
     connect_latency: HistogramVec,
@@ -42,6 +45,8 @@ pub struct KeyValueStoreMetrics {
     exists_latency: HistogramVec,
     create_latency: HistogramVec,
     delete_latency: HistogramVec,
+    snapshot_latency: HistogramVec,
+    restore_latency: HistogramVec,
     read_value_none_cases: IntCounterVec,
     read_value_key_size: HistogramVec,
     read_value_value_size: HistogramVec,
@@ -56,6 +61,8 @@ pub struct KeyValueStoreMetrics {
     find_key_values_by_prefix_prefix_size: HistogramVec,
     find_key_values_by_prefix_num_keys: HistogramVec,
     find_key_values_by_prefix_key_values_size: HistogramVec,
+    find_keys_in_range_num_keys: HistogramVec,
+    find_key_values_in_range_num_keys: HistogramVec,
     write_batch_size: HistogramVec,
     list_all_sizes: HistogramVec,
     exists_true_cases: IntCounterVec,
@@ -117,6 +124,14 @@ impl KeyValueStoreMetrics {
         let entry2 = format!("{} find key values by prefix latency", title_name);
         let find_key_values_by_prefix_latency = register_histogram_vec(&entry1, &entry2, &[], None);
 
+        let entry1 = format!("{}_find_keys_in_range_latency", var_name);
+        let entry2 = format!("{} find keys in range latency", title_name);
+        let find_keys_in_range_latency = register_histogram_vec(&entry1, &entry2, &[], None);
+
+        let entry1 = format!("{}_find_key_values_in_range_latency", var_name);
+        let entry2 = format!("{} find key values in range latency", title_name);
+        let find_key_values_in_range_latency = register_histogram_vec(&entry1, &entry2, &[], None);
+
         let entry1 = format!("{}_write_batch_latency", var_name);
         let entry2 = format!("{} write batch latency", title_name);
         let write_batch_latency = register_histogram_vec(&entry1, &entry2, &[], None);
@@ -153,6 +168,14 @@ impl KeyValueStoreMetrics {
         let entry2 = format!("{} delete latency", title_name);
         let delete_latency = register_histogram_vec(&entry1, &entry2, &[], None);
 
+        let entry1 = format!("{}_snapshot_latency", var_name);
+        let entry2 = format!("{} snapshot latency", title_name);
+        let snapshot_latency = register_histogram_vec(&entry1, &entry2, &[], None);
+
+        let entry1 = format!("{}_restore_latency", var_name);
+        let entry2 = format!("{} restore latency", title_name);
+        let restore_latency = register_histogram_vec(&entry1, &entry2, &[], None);
+
         let entry1 = format!("{}_read_value_number_none_cases", var_name);
         let entry2 = format!("{} read value number none cases", title_name);
         let read_value_none_cases = register_int_counter_vec(&entry1, &entry2, &[]);
@@ -212,6 +235,14 @@ impl KeyValueStoreMetrics {
         let find_key_values_by_prefix_key_values_size =
             register_histogram_vec(&entry1, &entry2, &[], None);
 
+        let entry1 = format!("{}_find_keys_in_range_num_keys", var_name);
+        let entry2 = format!("{} find keys in range num keys", title_name);
+        let find_keys_in_range_num_keys = register_histogram_vec(&entry1, &entry2, &[], None);
+
+        let entry1 = format!("{}_find_key_values_in_range_num_keys", var_name);
+        let entry2 = format!("{} find key values in range num keys", title_name);
+        let find_key_values_in_range_num_keys = register_histogram_vec(&entry1, &entry2, &[], None);
+
         let entry1 = format!("{}_write_batch_size", var_name);
         let entry2 = format!("{} write batch size", title_name);
         let write_batch_size = register_histogram_vec(&entry1, &entry2, &[], None);
@@ -231,6 +262,8 @@ impl KeyValueStoreMetrics {
             read_multi_values_bytes_latency,
             find_keys_by_prefix_latency,
             find_key_values_by_prefix_latency,
+            find_keys_in_range_latency,
+            find_key_values_in_range_latency,
             write_batch_latency,
             clear_journal_latency,
             connect_latency,
@@ -240,6 +273,8 @@ impl KeyValueStoreMetrics {
             exists_latency,
             create_latency,
             delete_latency,
+            snapshot_latency,
+            restore_latency,
             read_value_none_cases,
             read_value_key_size,
             read_value_value_size,
@@ -254,6 +289,8 @@ impl KeyValueStoreMetrics {
             find_key_values_by_prefix_prefix_size,
             find_key_values_by_prefix_num_keys,
             find_key_values_by_prefix_key_values_size,
+            find_keys_in_range_num_keys,
+            find_key_values_in_range_num_keys,
             write_batch_size,
             list_all_sizes,
             exists_true_cases,
@@ -407,6 +444,40 @@ where
             .observe(key_values_size as f64);
         Ok(result)
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let _latency = self.counter.find_keys_in_range_latency.measure_latency();
+        let result = self.store.find_keys_in_range(key_prefix, range).await?;
+        self.counter
+            .find_keys_in_range_num_keys
+            .with_label_values(&[])
+            .observe(result.len() as f64);
+        Ok(result)
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let _latency = self
+            .counter
+            .find_key_values_in_range_latency
+            .measure_latency();
+        let result = self
+            .store
+            .find_key_values_in_range(key_prefix, range)
+            .await?;
+        self.counter
+            .find_key_values_in_range_num_keys
+            .with_label_values(&[])
+            .observe(result.len() as f64);
+        Ok(result)
+    }
 }
 
 impl<K> WritableKeyValueStore for MeteredStore<K>
@@ -505,6 +576,22 @@ where
         let _latency = counter.delete_latency.measure_latency();
         K::delete(config, namespace).await
     }
+
+    async fn snapshot(&self, path: &Path) -> Result<(), Self::Error> {
+        let _latency = self.counter.snapshot_latency.measure_latency();
+        self.store.snapshot(path).await
+    }
+
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), Self::Error> {
+        let name = K::get_name();
+        let counter = get_counter(&name);
+        let _latency = counter.restore_latency.measure_latency();
+        K::restore(config, namespace, path).await
+    }
 }
 
 #[cfg(with_testing)]
@@ -8,10 +8,16 @@ pub mod metering;
 
 pub mod value_splitting;
 
+#[cfg(with_encryption)]
+pub mod encryption;
+
 pub mod memory;
 
 pub mod lru_caching;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod compression;
+
 pub mod dual;
 
 #[cfg(with_scylladb)]
@@ -20,6 +26,9 @@ pub mod scylla_db;
 #[cfg(with_rocksdb)]
 pub mod rocks_db;
 
+#[cfg(with_redb)]
+pub mod redb_db;
+
 #[cfg(with_dynamodb)]
 pub mod dynamo_db;
 
@@ -0,0 +1,603 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements [`crate::store::KeyValueStore`] for the embedded redb database.
+//!
+//! Each namespace is a directory containing one redb database file. Unlike RocksDB,
+//! redb is written in pure Rust, which makes it cheap to build and to run on small
+//! machines.
+
+use std::{ffi::OsString, ops::Bound, path::Path, sync::Arc};
+
+use linera_base::ensure;
+use redb::{ReadableTable as _, TableDefinition};
+use thiserror::Error;
+
+pub use crate::common::PathWithGuard;
+#[cfg(with_metrics)]
+use crate::metering::MeteredStore;
+#[cfg(with_testing)]
+use crate::store::TestKeyValueStore;
+use crate::{
+    batch::{Batch, WriteOperation},
+    common::get_interval,
+    compression::{CompressionConfig, CompressionError, CompressionStore},
+    encryption::{EncryptionConfig, EncryptionError, EncryptionStore},
+    lru_caching::{LruCachingConfig, LruCachingStore},
+    store::{
+        AdminKeyValueStore, CommonStoreInternalConfig, KeyRange, KeyValueStoreError,
+        ReadableKeyValueStore, WithError, WritableKeyValueStore,
+    },
+    value_splitting::{ValueSplittingError, ValueSplittingStore},
+};
+
+/// The number of streams for the test
+#[cfg(with_testing)]
+const TEST_REDB_MAX_STREAM_QUERIES: usize = 10;
+
+// Larger values are split by the `ValueSplittingStore`. redb supports values of several
+// GB, but a write transaction keeps its values in memory.
+const MAX_VALUE_SIZE: usize = 16 * 1024 * 1024;
+
+// Keys are stored in the pages of the B-tree, so they are kept much smaller than values.
+const MAX_KEY_SIZE: usize = 1024 * 1024;
+
+/// The name of the database file in the directory of a namespace.
+const DATABASE_FILE: &str = "data.redb";
+
+/// The table holding all the key-value pairs of a namespace.
+const TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("linera");
+
+fn check_key_size(key: &[u8]) -> Result<(), RedbStoreInternalError> {
+    ensure!(
+        key.len() <= MAX_KEY_SIZE,
+        RedbStoreInternalError::KeyTooLong
+    );
+    Ok(())
+}
+
+/// Runs a blocking computation on the thread pool of tokio.
+async fn spawn_blocking<F, O>(f: F) -> Result<O, RedbStoreInternalError>
+where
+    F: FnOnce() -> Result<O, RedbStoreInternalError> + Send + 'static,
+    O: Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await?
+}
+
+/// Returns the bounds as slices, the way redb expects them.
+fn as_slices(bounds: &(Bound<Vec<u8>>, Bound<Vec<u8>>)) -> (Bound<&[u8]>, Bound<&[u8]>) {
+    (
+        bounds.0.as_ref().map(Vec::as_slice),
+        bounds.1.as_ref().map(Vec::as_slice),
+    )
+}
+
+#[derive(Clone)]
+struct RedbStoreExecutor {
+    db: Arc<redb::Database>,
+    root_key: Vec<u8>,
+}
+
+impl RedbStoreExecutor {
+    fn full_key(&self, key: &[u8]) -> Result<Vec<u8>, RedbStoreInternalError> {
+        check_key_size(key)?;
+        let mut full_key = self.root_key.clone();
+        full_key.extend(key);
+        Ok(full_key)
+    }
+
+    fn read_multi_values_bytes_internal(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, RedbStoreInternalError> {
+        let transaction = self.db.begin_read()?;
+        let table = transaction.open_table(TABLE)?;
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            let full_key = self.full_key(&key)?;
+            let value = table.get(full_key.as_slice())?;
+            values.push(value.map(|value| value.value().to_vec()));
+        }
+        Ok(values)
+    }
+
+    fn contains_keys_internal(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<bool>, RedbStoreInternalError> {
+        let transaction = self.db.begin_read()?;
+        let table = transaction.open_table(TABLE)?;
+        let mut results = Vec::with_capacity(keys.len());
+        for key in keys {
+            let full_key = self.full_key(&key)?;
+            results.push(table.get(full_key.as_slice())?.is_some());
+        }
+        Ok(results)
+    }
+
+    /// Finds the key-value pairs whose keys start with `key_prefix`. The values are left
+    /// empty unless `with_values` is set.
+    #[allow(clippy::type_complexity)]
+    fn find_key_values_by_prefix_internal(
+        &self,
+        key_prefix: Vec<u8>,
+        with_values: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RedbStoreInternalError> {
+        let prefix = self.full_key(&key_prefix)?;
+        let len = prefix.len();
+        let bounds = get_interval(prefix);
+        let transaction = self.db.begin_read()?;
+        let table = transaction.open_table(TABLE)?;
+        let mut key_values = Vec::new();
+        for entry in table.range(as_slices(&bounds))? {
+            let (key, value) = entry?;
+            let value = if with_values {
+                value.value().to_vec()
+            } else {
+                Vec::new()
+            };
+            key_values.push((key.value()[len..].to_vec(), value));
+        }
+        Ok(key_values)
+    }
+
+    /// Finds the key-value pairs of the `range` following `key_prefix`. The values are
+    /// left empty unless `with_values` is set.
+    #[allow(clippy::type_complexity)]
+    fn find_key_values_in_range_internal(
+        &self,
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+        with_values: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RedbStoreInternalError> {
+        let prefix = self.full_key(&key_prefix)?;
+        let len = prefix.len();
+        let limit = range.limit.unwrap_or(usize::MAX);
+        let mut key_values = Vec::new();
+        if range.is_empty() || limit == 0 {
+            return Ok(key_values);
+        }
+        let bounds = range.bounds(&prefix);
+        let transaction = self.db.begin_read()?;
+        let table = transaction.open_table(TABLE)?;
+        let entries = table.range(as_slices(&bounds))?;
+        let entries: Box<dyn Iterator<Item = _>> = if range.reverse {
+            Box::new(entries.rev())
+        } else {
+            Box::new(entries)
+        };
+        for entry in entries.take(limit) {
+            let (key, value) = entry?;
+            let value = if with_values {
+                value.value().to_vec()
+            } else {
+                Vec::new()
+            };
+            key_values.push((key.value()[len..].to_vec(), value));
+        }
+        Ok(key_values)
+    }
+
+    fn write_batch_internal(&self, batch: Batch) -> Result<(), RedbStoreInternalError> {
+        let transaction = self.db.begin_write()?;
+        {
+            let mut table = transaction.open_table(TABLE)?;
+            for operation in batch.operations {
+                match operation {
+                    WriteOperation::Delete { key } => {
+                        let full_key = self.full_key(&key)?;
+                        table.remove(full_key.as_slice())?;
+                    }
+                    WriteOperation::Put { key, value } => {
+                        let full_key = self.full_key(&key)?;
+                        table.insert(full_key.as_slice(), value.as_slice())?;
+                    }
+                    WriteOperation::DeletePrefix { key_prefix } => {
+                        let prefix = self.full_key(&key_prefix)?;
+                        let bounds = get_interval(prefix);
+                        let keys = table
+                            .range(as_slices(&bounds))?
+                            .map(|entry| Ok(entry?.0.value().to_vec()))
+                            .collect::<Result<Vec<_>, RedbStoreInternalError>>()?;
+                        for key in keys {
+                            table.remove(key.as_slice())?;
+                        }
+                    }
+                }
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+/// The inner client
+#[derive(Clone)]
+pub struct RedbStoreInternal {
+    executor: RedbStoreExecutor,
+    _path_with_guard: PathWithGuard,
+    max_stream_queries: usize,
+}
+
+/// The initial configuration of the system
+#[derive(Clone, Debug)]
+pub struct RedbStoreInternalConfig {
+    /// The path to the storage containing the namespaces
+    path_with_guard: PathWithGuard,
+    /// The common configuration of the key value store
+    common_config: CommonStoreInternalConfig,
+}
+
+impl RedbStoreInternal {
+    fn check_namespace(namespace: &str) -> Result<(), RedbStoreInternalError> {
+        if !namespace
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || character == '_')
+        {
+            return Err(RedbStoreInternalError::InvalidNamespace);
+        }
+        Ok(())
+    }
+
+    fn build(
+        path_with_guard: PathWithGuard,
+        max_stream_queries: usize,
+        root_key: &[u8],
+    ) -> Result<RedbStoreInternal, RedbStoreInternalError> {
+        let path = path_with_guard.path_buf.clone();
+        std::fs::create_dir_all(&path)?;
+        let db = redb::Database::create(path.join(DATABASE_FILE))?;
+        // Reading from a table that was never written to fails, so we create it first.
+        let transaction = db.begin_write()?;
+        transaction.open_table(TABLE)?;
+        transaction.commit()?;
+        let executor = RedbStoreExecutor {
+            db: Arc::new(db),
+            root_key: root_key.to_vec(),
+        };
+        Ok(RedbStoreInternal {
+            executor,
+            _path_with_guard: path_with_guard,
+            max_stream_queries,
+        })
+    }
+}
+
+impl WithError for RedbStoreInternal {
+    type Error = RedbStoreInternalError;
+}
+
+impl ReadableKeyValueStore for RedbStoreInternal {
+    const MAX_KEY_SIZE: usize = MAX_KEY_SIZE;
+    type Keys = Vec<Vec<u8>>;
+    type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+    fn max_stream_queries(&self) -> usize {
+        self.max_stream_queries
+    }
+
+    async fn read_value_bytes(
+        &self,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, RedbStoreInternalError> {
+        let mut values = self.read_multi_values_bytes(vec![key.to_vec()]).await?;
+        Ok(values.pop().flatten())
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, RedbStoreInternalError> {
+        let results = self.contains_keys(vec![key.to_vec()]).await?;
+        Ok(results[0])
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, RedbStoreInternalError> {
+        let executor = self.executor.clone();
+        spawn_blocking(move || executor.contains_keys_internal(keys)).await
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, RedbStoreInternalError> {
+        let executor = self.executor.clone();
+        spawn_blocking(move || executor.read_multi_values_bytes_internal(keys)).await
+    }
+
+    async fn find_keys_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::Keys, RedbStoreInternalError> {
+        let executor = self.executor.clone();
+        let key_prefix = key_prefix.to_vec();
+        let key_values =
+            spawn_blocking(move || executor.find_key_values_by_prefix_internal(key_prefix, false))
+                .await?;
+        Ok(key_values.into_iter().map(|(key, _)| key).collect())
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, RedbStoreInternalError> {
+        let executor = self.executor.clone();
+        let key_prefix = key_prefix.to_vec();
+        spawn_blocking(move || executor.find_key_values_by_prefix_internal(key_prefix, true)).await
+    }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, RedbStoreInternalError> {
+        let executor = self.executor.clone();
+        let key_prefix = key_prefix.to_vec();
+        let range = range.clone();
+        let key_values = spawn_blocking(move || {
+            executor.find_key_values_in_range_internal(key_prefix, range, false)
+        })
+        .await?;
+        Ok(key_values.into_iter().map(|(key, _)| key).collect())
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RedbStoreInternalError> {
+        let executor = self.executor.clone();
+        let key_prefix = key_prefix.to_vec();
+        let range = range.clone();
+        spawn_blocking(move || executor.find_key_values_in_range_internal(key_prefix, range, true))
+            .await
+    }
+}
+
+impl WritableKeyValueStore for RedbStoreInternal {
+    const MAX_VALUE_SIZE: usize = MAX_VALUE_SIZE;
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), RedbStoreInternalError> {
+        let executor = self.executor.clone();
+        spawn_blocking(move || executor.write_batch_internal(batch)).await
+    }
+
+    async fn clear_journal(&self) -> Result<(), RedbStoreInternalError> {
+        Ok(())
+    }
+}
+
+impl AdminKeyValueStore for RedbStoreInternal {
+    type Config = RedbStoreInternalConfig;
+
+    fn get_name() -> String {
+        "redb internal".to_string()
+    }
+
+    async fn connect(
+        config: &Self::Config,
+        namespace: &str,
+        root_key: &[u8],
+    ) -> Result<Self, RedbStoreInternalError> {
+        Self::check_namespace(namespace)?;
+        let mut path_with_guard = config.path_with_guard.clone();
+        path_with_guard.path_buf.push(namespace);
+        let max_stream_queries = config.common_config.max_stream_queries;
+        let root_key = root_key.to_vec();
+        spawn_blocking(move || {
+            RedbStoreInternal::build(path_with_guard, max_stream_queries, &root_key)
+        })
+        .await
+    }
+
+    fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, RedbStoreInternalError> {
+        let mut store = self.clone();
+        store.executor.root_key = root_key.to_vec();
+        Ok(store)
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, RedbStoreInternalError> {
+        let entries = std::fs::read_dir(config.path_with_guard.path_buf.clone())?;
+        let mut namespaces = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                return Err(RedbStoreInternalError::NonDirectoryNamespace);
+            }
+            let namespace = entry
+                .file_name()
+                .into_string()
+                .map_err(RedbStoreInternalError::IntoStringError)?;
+            namespaces.push(namespace);
+        }
+        Ok(namespaces)
+    }
+
+    async fn exists(
+        config: &Self::Config,
+        namespace: &str,
+    ) -> Result<bool, RedbStoreInternalError> {
+        Self::check_namespace(namespace)?;
+        let path_buf = config.path_with_guard.path_buf.join(namespace);
+        Ok(path_buf.exists())
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), RedbStoreInternalError> {
+        Self::check_namespace(namespace)?;
+        let path_buf = config.path_with_guard.path_buf.join(namespace);
+        std::fs::create_dir_all(path_buf)?;
+        Ok(())
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), RedbStoreInternalError> {
+        Self::check_namespace(namespace)?;
+        let path_buf = config.path_with_guard.path_buf.join(namespace);
+        std::fs::remove_dir_all(path_buf)?;
+        Ok(())
+    }
+
+    /// Copies the content of the namespace into a new database, within a single read
+    /// transaction on the database handle of the store. Since redb locks the database
+    /// file, this is how a running process snapshots its own storage.
+    async fn snapshot(&self, path: &Path) -> Result<(), RedbStoreInternalError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::create_dir(path)?;
+        let db = self.executor.db.clone();
+        let target = path.join(DATABASE_FILE);
+        spawn_blocking(move || {
+            let snapshot = redb::Database::create(target)?;
+            let read_transaction = db.begin_read()?;
+            let source = read_transaction.open_table(TABLE)?;
+            let write_transaction = snapshot.begin_write()?;
+            {
+                let mut target = write_transaction.open_table(TABLE)?;
+                for entry in source.iter()? {
+                    let (key, value) = entry?;
+                    target.insert(key.value(), value.value())?;
+                }
+            }
+            write_transaction.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), RedbStoreInternalError> {
+        if Self::exists(config, namespace).await? {
+            return Err(RedbStoreInternalError::NamespaceAlreadyExists);
+        }
+        let path_buf = config.path_with_guard.path_buf.join(namespace);
+        std::fs::create_dir_all(&path_buf)?;
+        std::fs::copy(path.join(DATABASE_FILE), path_buf.join(DATABASE_FILE))?;
+        Ok(())
+    }
+}
+
+#[cfg(with_testing)]
+impl TestKeyValueStore for RedbStoreInternal {
+    async fn new_test_config() -> Result<RedbStoreInternalConfig, RedbStoreInternalError> {
+        let path_with_guard = PathWithGuard::new_testing();
+        let common_config = CommonStoreInternalConfig {
+            max_concurrent_queries: None,
+            max_stream_queries: TEST_REDB_MAX_STREAM_QUERIES,
+        };
+        Ok(RedbStoreInternalConfig {
+            path_with_guard,
+            common_config,
+        })
+    }
+}
+
+/// The error type for [`RedbStoreInternal`]
+#[derive(Error, Debug)]
+pub enum RedbStoreInternalError {
+    /// Tokio join error in redb.
+    #[error("tokio join error: {0}")]
+    TokioJoinError(#[from] tokio::task::JoinError),
+
+    /// An error occurred while opening the database.
+    #[error(transparent)]
+    Database(#[from] redb::DatabaseError),
+
+    /// An error occurred while starting a transaction.
+    #[error(transparent)]
+    Transaction(#[from] redb::TransactionError),
+
+    /// An error occurred while opening the table.
+    #[error(transparent)]
+    Table(#[from] redb::TableError),
+
+    /// An error occurred while accessing the storage.
+    #[error(transparent)]
+    Storage(#[from] redb::StorageError),
+
+    /// An error occurred while committing a transaction.
+    #[error(transparent)]
+    Commit(#[from] redb::CommitError),
+
+    /// The database contains a file which is not a directory
+    #[error("Namespaces should be directories")]
+    NonDirectoryNamespace,
+
+    /// Error converting `OsString` to `String`
+    #[error("error in the conversion from OsString: {0:?}")]
+    IntoStringError(OsString),
+
+    /// The key must have at most 1 MB
+    #[error("The key must have at most 1 MB")]
+    KeyTooLong,
+
+    /// Namespace contains forbidden characters
+    #[error("Namespace contains forbidden characters")]
+    InvalidNamespace,
+
+    /// The namespace already exists
+    #[error("The namespace already exists")]
+    NamespaceAlreadyExists,
+
+    /// Filesystem error
+    #[error("Filesystem error: {0}")]
+    FsError(#[from] std::io::Error),
+
+    /// BCS serialization error.
+    #[error(transparent)]
+    BcsError(#[from] bcs::Error),
+}
+
+impl KeyValueStoreError for RedbStoreInternalError {
+    const BACKEND: &'static str = "redb";
+}
+
+/// The `RedbStore` composed type with metrics
+#[cfg(with_metrics)]
+pub type RedbStore = MeteredStore<
+    LruCachingStore<
+        MeteredStore<
+            CompressionStore<
+                EncryptionStore<MeteredStore<ValueSplittingStore<MeteredStore<RedbStoreInternal>>>>,
+            >,
+        >,
+    >,
+>;
+
+/// The `RedbStore` composed type
+#[cfg(not(with_metrics))]
+pub type RedbStore =
+    LruCachingStore<CompressionStore<EncryptionStore<ValueSplittingStore<RedbStoreInternal>>>>;
+
+/// The composed error type for the `RedbStore`
+pub type RedbStoreError =
+    CompressionError<EncryptionError<ValueSplittingError<RedbStoreInternalError>>>;
+
+/// The composed config type for the `RedbStore`
+pub type RedbStoreConfig =
+    LruCachingConfig<CompressionConfig<EncryptionConfig<RedbStoreInternalConfig>>>;
+
+impl RedbStoreConfig {
+    /// Creates a new `RedbStoreConfig` from the input.
+    pub fn new(
+        path_with_guard: PathWithGuard,
+        common_config: crate::store::CommonStoreConfig,
+    ) -> RedbStoreConfig {
+        let inner_config = RedbStoreInternalConfig {
+            path_with_guard,
+            common_config: common_config.reduced(),
+        };
+        let inner_config = EncryptionConfig {
+            inner_config,
+            key: common_config.encryption_key.clone(),
+        };
+        let inner_config = CompressionConfig {
+            inner_config,
+            threshold: common_config.compression_threshold,
+        };
+        RedbStoreConfig {
+            inner_config,
+            cache_size: common_config.cache_size,
+        }
+    }
+}
@@ -6,14 +6,14 @@
 use std::{
     ffi::OsString,
     ops::{Bound, Bound::Excluded},
-    path::PathBuf,
+    path::Path,
     sync::Arc,
 };
 
 use linera_base::ensure;
-use tempfile::TempDir;
 use thiserror::Error;
 
+pub use crate::common::PathWithGuard;
 #[cfg(with_metrics)]
 use crate::metering::MeteredStore;
 #[cfg(with_testing)]
@@ -21,10 +21,12 @@ use crate::store::TestKeyValueStore;
 use crate::{
     batch::{Batch, WriteOperation},
     common::get_upper_bound,
+    compression::{CompressionConfig, CompressionError, CompressionStore},
+    encryption::{EncryptionConfig, EncryptionError, EncryptionStore},
     lru_caching::{LruCachingConfig, LruCachingStore},
     store::{
-        AdminKeyValueStore, CommonStoreInternalConfig, KeyValueStoreError, ReadableKeyValueStore,
-        WithError, WritableKeyValueStore,
+        AdminKeyValueStore, CommonStoreInternalConfig, KeyRange, KeyValueStoreError,
+        ReadableKeyValueStore, WithError, WritableKeyValueStore,
     },
     value_splitting::{ValueSplittingError, ValueSplittingStore},
 };
@@ -193,6 +195,70 @@ impl RocksDbStoreExecutor {
         Ok(key_values)
     }
 
+    /// Finds the key-value pairs of the `range` following `key_prefix`. The values are
+    /// left empty unless `with_values` is set.
+    #[allow(clippy::type_complexity)]
+    fn find_key_values_in_range_internal(
+        &self,
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+        with_values: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RocksDbStoreInternalError> {
+        check_key_size(&key_prefix)?;
+        let mut prefix = self.root_key.clone();
+        prefix.extend(key_prefix);
+        let len = prefix.len();
+        let mut start = prefix.clone();
+        start.extend(&range.start);
+        let (_, end) = range.bounds(&prefix);
+        let limit = range.limit.unwrap_or(usize::MAX);
+        let mut key_values = Vec::new();
+        if range.is_empty() || limit == 0 {
+            return Ok(key_values);
+        }
+        let mut iter = self.db.raw_iterator();
+        if range.reverse {
+            match &end {
+                Excluded(end) => {
+                    iter.seek_for_prev(end);
+                    if iter.key() == Some(end.as_slice()) {
+                        iter.prev();
+                    }
+                }
+                _ => iter.seek_to_last(),
+            }
+        } else {
+            iter.seek(&start);
+        }
+        while key_values.len() < limit {
+            let Some(key) = iter.key() else {
+                break;
+            };
+            let in_range = if range.reverse {
+                key >= start.as_slice()
+            } else {
+                match &end {
+                    Excluded(end) => key < end.as_slice(),
+                    _ => true,
+                }
+            };
+            if !in_range {
+                break;
+            }
+            let value = match iter.value() {
+                Some(value) if with_values => value.to_vec(),
+                _ => Vec::new(),
+            };
+            key_values.push((key[len..].to_vec(), value));
+            if range.reverse {
+                iter.prev();
+            } else {
+                iter.next();
+            }
+        }
+        Ok(key_values)
+    }
+
     fn write_batch_internal(&self, mut batch: Batch) -> Result<(), RocksDbStoreInternalError> {
         // NOTE: The delete_range functionality of RocksDB needs to have an upper bound in order to work.
         // Thus in order to have the system working, we need to handle the unlikely case of having to
@@ -396,6 +462,42 @@ impl ReadableKeyValueStore for RocksDbStoreInternal {
             )
             .await
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, RocksDbStoreInternalError> {
+        let executor = self.executor.clone();
+        let input = (key_prefix.to_vec(), range.clone());
+        let key_values = self
+            .spawn_mode
+            .spawn(
+                move |(key_prefix, range)| {
+                    executor.find_key_values_in_range_internal(key_prefix, range, false)
+                },
+                input,
+            )
+            .await?;
+        Ok(key_values.into_iter().map(|(key, _)| key).collect())
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RocksDbStoreInternalError> {
+        let executor = self.executor.clone();
+        let input = (key_prefix.to_vec(), range.clone());
+        self.spawn_mode
+            .spawn(
+                move |(key_prefix, range)| {
+                    executor.find_key_values_in_range_internal(key_prefix, range, true)
+                },
+                input,
+            )
+            .await
+    }
 }
 
 impl WritableKeyValueStore for RocksDbStoreInternal {
@@ -503,6 +605,56 @@ impl AdminKeyValueStore for RocksDbStoreInternal {
         std::fs::remove_dir_all(path)?;
         Ok(())
     }
+
+    /// Creates a RocksDB checkpoint of the namespace from the database handle of the
+    /// store. Since RocksDB locks the database, this is how a running process, e.g. a
+    /// validator, snapshots its own storage. The checkpoint shares the immutable SST files
+    /// with the database through hard links when both are on the same filesystem.
+    async fn snapshot(&self, path: &Path) -> Result<(), RocksDbStoreInternalError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let db = self.executor.db.clone();
+        self.spawn_mode
+            .spawn(
+                move |path| {
+                    let checkpoint = rocksdb::checkpoint::Checkpoint::new(&db)?;
+                    checkpoint.create_checkpoint(path)?;
+                    Ok(())
+                },
+                path.to_path_buf(),
+            )
+            .await
+    }
+
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), RocksDbStoreInternalError> {
+        if Self::exists(config, namespace).await? {
+            return Err(RocksDbStoreInternalError::NamespaceAlreadyExists);
+        }
+        let mut path_buf = config.path_with_guard.path_buf.clone();
+        path_buf.push(namespace);
+        copy_dir(path, &path_buf)?;
+        Ok(())
+    }
+}
+
+/// Copies the content of the directory `source` into the new directory `target`.
+fn copy_dir(source: &Path, target: &Path) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(target)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let target = target.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(with_testing)]
@@ -549,6 +701,10 @@ pub enum RocksDbStoreInternalError {
     #[error("Namespace contains forbidden characters")]
     InvalidNamespace,
 
+    /// The namespace already exists
+    #[error("The namespace already exists")]
+    NamespaceAlreadyExists,
+
     /// Filesystem error
     #[error("Filesystem error: {0}")]
     FsError(#[from] std::io::Error),
@@ -558,34 +714,6 @@ pub enum RocksDbStoreInternalError {
     BcsError(#[from] bcs::Error),
 }
 
-/// A path and the guard for the temporary directory if needed
-#[derive(Clone, Debug)]
-pub struct PathWithGuard {
-    /// The path to the data
-    pub path_buf: PathBuf,
-    /// The guard for the directory if one is needed
-    _dir: Option<Arc<TempDir>>,
-}
-
-impl PathWithGuard {
-    /// Create a PathWithGuard from an existing path.
-    pub fn new(path_buf: PathBuf) -> Self {
-        Self {
-            path_buf,
-            _dir: None,
-        }
-    }
-
-    /// Returns the test path for RocksDB without common config.
-    #[cfg(with_testing)]
-    pub fn new_testing() -> PathWithGuard {
-        let dir = TempDir::new().unwrap();
-        let path_buf = dir.path().to_path_buf();
-        let _dir = Some(Arc::new(dir));
-        PathWithGuard { path_buf, _dir }
-    }
-}
-
 impl KeyValueStoreError for RocksDbStoreInternalError {
     const BACKEND: &'static str = "rocks_db";
 }
@@ -593,18 +721,29 @@ impl KeyValueStoreError for RocksDbStoreInternalError {
 /// The `RocksDbStore` composed type with metrics
 #[cfg(with_metrics)]
 pub type RocksDbStore = MeteredStore<
-    LruCachingStore<MeteredStore<ValueSplittingStore<MeteredStore<RocksDbStoreInternal>>>>,
+    LruCachingStore<
+        MeteredStore<
+            CompressionStore<
+                EncryptionStore<
+                    MeteredStore<ValueSplittingStore<MeteredStore<RocksDbStoreInternal>>>,
+                >,
+            >,
+        >,
+    >,
 >;
 
 /// The `RocksDbStore` composed type
 #[cfg(not(with_metrics))]
-pub type RocksDbStore = LruCachingStore<ValueSplittingStore<RocksDbStoreInternal>>;
+pub type RocksDbStore =
+    LruCachingStore<CompressionStore<EncryptionStore<ValueSplittingStore<RocksDbStoreInternal>>>>;
 
 /// The composed error type for the `RocksDbStore`
-pub type RocksDbStoreError = ValueSplittingError<RocksDbStoreInternalError>;
+pub type RocksDbStoreError =
+    CompressionError<EncryptionError<ValueSplittingError<RocksDbStoreInternalError>>>;
 
 /// The composed config type for the `RocksDbStore`
-pub type RocksDbStoreConfig = LruCachingConfig<RocksDbStoreInternalConfig>;
+pub type RocksDbStoreConfig =
+    LruCachingConfig<CompressionConfig<EncryptionConfig<RocksDbStoreInternalConfig>>>;
 
 impl RocksDbStoreConfig {
     /// Creates a new `RocksDbStoreConfig` from the input.
@@ -618,6 +757,14 @@ impl RocksDbStoreConfig {
             spawn_mode,
             common_config: common_config.reduced(),
         };
+        let inner_config = EncryptionConfig {
+            inner_config,
+            key: common_config.encryption_key.clone(),
+        };
+        let inner_config = CompressionConfig {
+            inner_config,
+            threshold: common_config.compression_threshold,
+        };
         RocksDbStoreConfig {
             inner_config,
             cache_size: common_config.cache_size,
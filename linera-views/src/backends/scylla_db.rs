@@ -9,7 +9,8 @@
 
 use std::{
     collections::{hash_map::Entry, HashMap},
-    ops::Deref,
+    ops::{Bound::Excluded, Deref},
+    path::Path,
     sync::Arc,
 };
 
@@ -33,11 +34,13 @@ use crate::store::TestKeyValueStore;
 use crate::{
     batch::UnorderedBatch,
     common::{get_uleb128_size, get_upper_bound_option},
+    compression::{CompressionConfig, CompressionError, CompressionStore},
+    encryption::{EncryptionConfig, EncryptionError, EncryptionStore},
     journaling::{DirectWritableKeyValueStore, JournalConsistencyError, JournalingKeyValueStore},
     lru_caching::{LruCachingConfig, LruCachingStore},
     store::{
-        AdminKeyValueStore, CommonStoreInternalConfig, KeyValueStoreError, ReadableKeyValueStore,
-        WithError,
+        AdminKeyValueStore, CommonStoreInternalConfig, KeyRange, KeyValueStoreError,
+        ReadableKeyValueStore, WithError,
     },
     value_splitting::{ValueSplittingError, ValueSplittingStore},
 };
@@ -437,6 +440,57 @@ impl ScyllaDbClient {
         }
         Ok(key_values)
     }
+
+    /// Finds the key-value pairs of the `range` following `key_prefix`. The values are
+    /// left empty unless `with_values` is set.
+    async fn find_key_values_in_range_internal(
+        &self,
+        root_key: &[u8],
+        key_prefix: Vec<u8>,
+        range: KeyRange,
+        with_values: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ScyllaDbStoreInternalError> {
+        Self::check_key_size(&key_prefix)?;
+        if range.is_empty() || range.limit == Some(0) {
+            return Ok(Vec::new());
+        }
+        let session = &self.session;
+        let len = key_prefix.len();
+        let mut start = key_prefix.clone();
+        start.extend(&range.start);
+        let mut inputs = vec![root_key.to_vec(), start];
+        let mut conditions = "root_key = ? AND k >= ?".to_string();
+        if let Excluded(end) = range.bounds(&key_prefix).1 {
+            conditions.push_str(" AND k < ?");
+            inputs.push(end);
+        }
+        let columns = if with_values { "k,v" } else { "k" };
+        let order = if range.reverse { "DESC" } else { "ASC" };
+        let limit = match range.limit {
+            Some(limit) => format!(" LIMIT {}", limit.min(i32::MAX as usize)),
+            None => String::new(),
+        };
+        let query = format!(
+            "SELECT {} FROM kv.{} WHERE {} ORDER BY k {}{} ALLOW FILTERING",
+            columns, self.namespace, conditions, order, limit
+        );
+        let rows = session.query_iter(&*query, &inputs).await?;
+        let mut key_values = Vec::new();
+        if with_values {
+            let mut rows = rows.rows_stream::<(Vec<u8>, Vec<u8>)>()?;
+            while let Some(row) = rows.next().await {
+                let (key, value) = row?;
+                key_values.push((key[len..].to_vec(), value));
+            }
+        } else {
+            let mut rows = rows.rows_stream::<(Vec<u8>,)>()?;
+            while let Some(row) = rows.next().await {
+                let (key,) = row?;
+                key_values.push((key[len..].to_vec(), Vec::new()));
+            }
+        }
+        Ok(key_values)
+    }
 }
 
 /// The client itself and the keeping of the count of active connections.
@@ -498,6 +552,10 @@ pub enum ScyllaDbStoreInternalError {
     /// The batch is too long to be written
     #[error("The batch is too long to be written")]
     BatchTooLong,
+
+    /// Snapshots have to be taken with the ScyllaDB tools
+    #[error("Snapshots are not supported for ScyllaDB, use `nodetool snapshot` instead")]
+    SnapshotNotSupported,
 }
 
 impl KeyValueStoreError for ScyllaDbStoreInternalError {
@@ -595,6 +653,41 @@ impl ReadableKeyValueStore for ScyllaDbStoreInternal {
             .find_key_values_by_prefix_internal(&self.root_key, key_prefix.to_vec())
             .await
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, ScyllaDbStoreInternalError> {
+        let store = self.store.deref();
+        let _guard = self.acquire().await;
+        let key_values = store
+            .find_key_values_in_range_internal(
+                &self.root_key,
+                key_prefix.to_vec(),
+                range.clone(),
+                false,
+            )
+            .await?;
+        Ok(key_values.into_iter().map(|(key, _)| key).collect())
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ScyllaDbStoreInternalError> {
+        let store = self.store.deref();
+        let _guard = self.acquire().await;
+        store
+            .find_key_values_in_range_internal(
+                &self.root_key,
+                key_prefix.to_vec(),
+                range.clone(),
+                true,
+            )
+            .await
+    }
 }
 
 #[async_trait]
@@ -826,6 +919,18 @@ impl AdminKeyValueStore for ScyllaDbStoreInternal {
         let _result = session.execute_unpaged(&prepared, &[]).await?;
         Ok(())
     }
+
+    async fn snapshot(&self, _path: &Path) -> Result<(), ScyllaDbStoreInternalError> {
+        Err(ScyllaDbStoreInternalError::SnapshotNotSupported)
+    }
+
+    async fn restore(
+        _config: &Self::Config,
+        _namespace: &str,
+        _path: &Path,
+    ) -> Result<(), ScyllaDbStoreInternalError> {
+        Err(ScyllaDbStoreInternalError::SnapshotNotSupported)
+    }
 }
 
 impl ScyllaDbStoreInternal {
@@ -875,18 +980,30 @@ impl TestKeyValueStore for JournalingKeyValueStore<ScyllaDbStoreInternal> {
 pub type ScyllaDbStore = MeteredStore<
     LruCachingStore<
         MeteredStore<
-            ValueSplittingStore<MeteredStore<JournalingKeyValueStore<ScyllaDbStoreInternal>>>,
+            CompressionStore<
+                EncryptionStore<
+                    MeteredStore<
+                        ValueSplittingStore<
+                            MeteredStore<JournalingKeyValueStore<ScyllaDbStoreInternal>>,
+                        >,
+                    >,
+                >,
+            >,
         >,
     >,
 >;
 
 /// The `ScyllaDbStore` composed type
 #[cfg(not(with_metrics))]
-pub type ScyllaDbStore =
-    LruCachingStore<ValueSplittingStore<JournalingKeyValueStore<ScyllaDbStoreInternal>>>;
+pub type ScyllaDbStore = LruCachingStore<
+    CompressionStore<
+        EncryptionStore<ValueSplittingStore<JournalingKeyValueStore<ScyllaDbStoreInternal>>>,
+    >,
+>;
 
 /// The `ScyllaDbStoreConfig` input type
-pub type ScyllaDbStoreConfig = LruCachingConfig<ScyllaDbStoreInternalConfig>;
+pub type ScyllaDbStoreConfig =
+    LruCachingConfig<CompressionConfig<EncryptionConfig<ScyllaDbStoreInternalConfig>>>;
 
 impl ScyllaDbStoreConfig {
     /// Creates a `ScyllaDbStoreConfig` from the inputs.
@@ -895,6 +1012,14 @@ impl ScyllaDbStoreConfig {
             uri,
             common_config: common_config.reduced(),
         };
+        let inner_config = EncryptionConfig {
+            inner_config,
+            key: common_config.encryption_key.clone(),
+        };
+        let inner_config = CompressionConfig {
+            inner_config,
+            threshold: common_config.compression_threshold,
+        };
         ScyllaDbStoreConfig {
             inner_config,
             cache_size: common_config.cache_size,
@@ -903,4 +1028,5 @@ impl ScyllaDbStoreConfig {
 }
 
 /// The combined error type for the `ScyllaDbStore`.
-pub type ScyllaDbStoreError = ValueSplittingError<ScyllaDbStoreInternalError>;
+pub type ScyllaDbStoreError =
+    CompressionError<EncryptionError<ValueSplittingError<ScyllaDbStoreInternalError>>>;
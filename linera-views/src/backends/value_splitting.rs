@@ -3,13 +3,15 @@
 
 //! Adds support for large values to a given store by splitting them between several keys.
 
+use std::path::Path;
+
 use linera_base::ensure;
 use thiserror::Error;
 
 use crate::{
     batch::{Batch, WriteOperation},
     store::{
-        AdminKeyValueStore, KeyIterable, KeyValueIterable, KeyValueStoreError,
+        AdminKeyValueStore, KeyIterable, KeyRange, KeyValueIterable, KeyValueStoreError,
         ReadableKeyValueStore, WithError, WritableKeyValueStore,
     },
 };
@@ -231,6 +233,82 @@ where
         }
         Ok(key_values)
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        // Appending the index of the first segment preserves the order of the keys.
+        let mut inner_range = KeyRange {
+            start: Self::get_segment_key(&range.start, 0)?,
+            end: match &range.end {
+                Some(end) => Some(Self::get_segment_key(end, 0)?),
+                None => None,
+            },
+            reverse: range.reverse,
+            limit: range.limit,
+        };
+        let mut keys = Vec::new();
+        loop {
+            let big_keys = self
+                .store
+                .find_keys_in_range(key_prefix, &inner_range)
+                .await?;
+            let num_big_keys = big_keys.len();
+            let Some(last_big_key) = big_keys.last().cloned() else {
+                break;
+            };
+            for mut big_key in big_keys {
+                if Self::read_index_from_key(&big_key)? == 0 {
+                    big_key.truncate(big_key.len() - 4);
+                    keys.push(big_key);
+                }
+            }
+            // Without a limit, the inner store returned the whole range at once.
+            let Some(limit) = range.limit else {
+                break;
+            };
+            if keys.len() >= limit || inner_range.limit.is_some_and(|l| num_big_keys < l) {
+                break;
+            }
+            // Some of the keys were segments other than the first one: continue after them.
+            inner_range.limit = Some(limit - keys.len());
+            if range.reverse {
+                inner_range.end = Some(last_big_key);
+            } else {
+                inner_range.start = last_big_key;
+                inner_range.start.push(0);
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let keys = self.find_keys_in_range(key_prefix, range).await?;
+        let full_keys = keys
+            .iter()
+            .map(|key| {
+                let mut full_key = key_prefix.to_vec();
+                full_key.extend(key);
+                full_key
+            })
+            .collect();
+        let values = self.read_multi_values_bytes(full_keys).await?;
+        let mut key_values = Vec::with_capacity(keys.len());
+        for (key, value) in keys.into_iter().zip(values) {
+            let value = value.ok_or(ValueSplittingError::MissingSegment)?;
+            key_values.push((key, value));
+        }
+        Ok(key_values)
+    }
 }
 
 impl<K> WritableKeyValueStore for ValueSplittingStore<K>
@@ -322,6 +400,18 @@ where
     async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
         Ok(K::delete(config, namespace).await?)
     }
+
+    async fn snapshot(&self, path: &Path) -> Result<(), Self::Error> {
+        Ok(self.store.snapshot(path).await?)
+    }
+
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), Self::Error> {
+        Ok(K::restore(config, namespace, path).await?)
+    }
 }
 
 #[cfg(with_testing)]
@@ -442,6 +532,22 @@ impl ReadableKeyValueStore for LimitedTestMemoryStore {
     ) -> Result<Self::KeyValues, MemoryStoreError> {
         self.store.find_key_values_by_prefix(key_prefix).await
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, MemoryStoreError> {
+        self.store.find_keys_in_range(key_prefix, range).await
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, MemoryStoreError> {
+        self.store.find_key_values_in_range(key_prefix, range).await
+    }
 }
 
 #[cfg(with_testing)]
@@ -486,7 +592,7 @@ pub fn create_value_splitting_memory_store() -> ValueSplittingStore<LimitedTestM
 mod tests {
     use linera_views::{
         batch::Batch,
-        store::{ReadableKeyValueStore, WritableKeyValueStore},
+        store::{KeyRange, ReadableKeyValueStore, WritableKeyValueStore},
         value_splitting::{LimitedTestMemoryStore, ValueSplittingStore},
     };
     use rand::Rng;
@@ -583,4 +689,37 @@ mod tests {
         let keys = store.find_keys_by_prefix(&[0]).await.unwrap();
         assert_eq!(keys, vec![vec![0, 0, 0, 0, 1], vec![0, 0, 0, 0, 2]]);
     }
+
+    // Range queries with a limit skip the segments of the big values.
+    #[tokio::test]
+    async fn test_value_splitting4_range_with_limit() {
+        let store = LimitedTestMemoryStore::new();
+        const MAX_LEN: usize = LimitedTestMemoryStore::MAX_VALUE_SIZE;
+        let big_store = ValueSplittingStore::new(store);
+        let mut batch = Batch::new();
+        let mut rng = crate::random::make_deterministic_rng();
+        let mut key_values = Vec::new();
+        for key in 0..10u8 {
+            let len = if key % 2 == 0 { 3 * MAX_LEN } else { 10 };
+            let value = (0..len).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+            batch.put_key_value_bytes(vec![1, key], value.clone());
+            key_values.push((vec![key], value));
+        }
+        big_store.write_batch(batch).await.unwrap();
+
+        let range = KeyRange::new(vec![2], Some(vec![8])).with_limit(4);
+        let result = big_store
+            .find_key_values_in_range(&[1], &range)
+            .await
+            .unwrap();
+        assert_eq!(result, key_values[2..6].to_vec());
+
+        let range = KeyRange::new(vec![2], None).reversed().with_limit(3);
+        let keys = big_store.find_keys_in_range(&[1], &range).await.unwrap();
+        assert_eq!(keys, vec![vec![9], vec![8], vec![7]]);
+
+        let range = KeyRange::new(vec![3], Some(vec![5])).reversed();
+        let keys = big_store.find_keys_in_range(&[1], &range).await.unwrap();
+        assert_eq!(keys, vec![vec![4], vec![3]]);
+    }
 }
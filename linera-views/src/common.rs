@@ -4,16 +4,17 @@
 //! This provides some common code for the linera-views.
 
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     ops::{
         Bound,
         Bound::{Excluded, Included, Unbounded},
+        RangeBounds,
     },
 };
 
 use serde::de::DeserializeOwned;
 
-use crate::views::ViewError;
+use crate::{store::KeyRange, views::ViewError};
 
 #[doc(hidden)]
 pub type HasherOutputSize = <sha3::Sha3_256 as sha3::digest::OutputSizeUser>::OutputSize;
@@ -101,6 +102,71 @@ pub(crate) fn get_interval(key_prefix: Vec<u8>) -> (Bound<Vec<u8>>, Bound<Vec<u8
     (Included(key_prefix), upper_bound)
 }
 
+/// Computes the range of stored entries needed to answer a query on `range` once merged
+/// with the pending `updates` of a view. Each update may hide one stored entry, so the
+/// limit is increased accordingly. Deleted prefixes may hide any number of stored
+/// entries, in which case the whole range is read.
+pub(crate) fn get_storage_range<T>(
+    range: &KeyRange,
+    updates: &BTreeMap<Vec<u8>, Update<T>>,
+    has_deleted_prefixes: bool,
+) -> KeyRange {
+    let mut storage_range = range.clone();
+    storage_range.limit = match range.limit {
+        Some(limit) if !has_deleted_prefixes => {
+            Some(limit + updates.range(range.bounds(&[])).count())
+        }
+        _ => None,
+    };
+    storage_range
+}
+
+/// Merges the `stored` entries of `range` with the pending `updates` of a view, ignoring
+/// the stored keys that are updated or for which `is_deleted` holds. The entries are
+/// returned in the order and the number requested by `range`.
+pub(crate) fn merge_range<'a, T, V>(
+    range: &KeyRange,
+    stored: Vec<(Vec<u8>, V)>,
+    is_deleted: impl Fn(&[u8]) -> bool,
+    updates: &'a BTreeMap<Vec<u8>, Update<T>>,
+    value: impl Fn(&'a T) -> V,
+) -> Vec<(Vec<u8>, V)> {
+    let mut entries = stored
+        .into_iter()
+        .filter(|(key, _)| !updates.contains_key(key) && !is_deleted(key))
+        .collect::<BTreeMap<_, _>>();
+    for (key, update) in updates.range(range.bounds(&[])) {
+        if let Update::Set(update) = update {
+            entries.insert(key.clone(), value(update));
+        }
+    }
+    range.select(entries.into_iter().collect())
+}
+
+/// Computes the range of the keys of a view from bounds on its indices, given the
+/// serialization of the indices into keys.
+pub(crate) fn get_key_range<I, E>(
+    range: impl RangeBounds<I>,
+    reverse: bool,
+    limit: Option<usize>,
+    serialize: impl Fn(&I) -> Result<Vec<u8>, E>,
+) -> Result<KeyRange, E> {
+    let serialize_bound = |bound: Bound<&I>| -> Result<Bound<Vec<u8>>, E> {
+        Ok(match bound {
+            Included(index) => Included(serialize(index)?),
+            Excluded(index) => Excluded(serialize(index)?),
+            Unbounded => Unbounded,
+        })
+    };
+    let mut key_range = KeyRange::from_bounds(
+        serialize_bound(range.start_bound())?,
+        serialize_bound(range.end_bound())?,
+    );
+    key_range.reverse = reverse;
+    key_range.limit = limit;
+    Ok(key_range)
+}
+
 /// Deserializes an Optional vector of u8
 pub(crate) fn from_bytes_option<V: DeserializeOwned, E>(
     key_opt: &Option<Vec<u8>>,
@@ -205,6 +271,36 @@ pub(crate) fn insert_key_prefix(prefixes: &mut BTreeSet<Vec<u8>>, prefix: Vec<u8
     }
 }
 
+/// A path and the guard for the temporary directory if needed
+#[cfg(any(with_rocksdb, with_redb))]
+#[derive(Clone, Debug)]
+pub struct PathWithGuard {
+    /// The path to the data
+    pub path_buf: std::path::PathBuf,
+    /// The guard for the directory if one is needed
+    _dir: Option<std::sync::Arc<tempfile::TempDir>>,
+}
+
+#[cfg(any(with_rocksdb, with_redb))]
+impl PathWithGuard {
+    /// Create a PathWithGuard from an existing path.
+    pub fn new(path_buf: std::path::PathBuf) -> Self {
+        Self {
+            path_buf,
+            _dir: None,
+        }
+    }
+
+    /// Returns a temporary test path without common config.
+    #[cfg(with_testing)]
+    pub fn new_testing() -> PathWithGuard {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path_buf = dir.path().to_path_buf();
+        let _dir = Some(std::sync::Arc::new(dir));
+        PathWithGuard { path_buf, _dir }
+    }
+}
+
 #[test]
 fn suffix_closed_set_test1_the_lower_bound() {
     let mut set = BTreeSet::<Vec<u8>>::new();
@@ -362,3 +458,44 @@ fn test_upper_bound() {
     assert_eq!(get_upper_bound(&[0, 255]), Excluded(vec![1]));
     assert_eq!(get_upper_bound(&[255, 0]), Excluded(vec![255, 1]));
 }
+
+#[test]
+fn test_storage_range_limit() {
+    let mut updates = BTreeMap::new();
+    updates.insert(vec![1], Update::Set(()));
+    updates.insert(vec![3], Update::Removed);
+    updates.insert(vec![7], Update::Set(()));
+    let range = KeyRange::new(vec![0], Some(vec![5])).with_limit(2);
+    // Only the updates inside the range can hide stored entries.
+    assert_eq!(get_storage_range(&range, &updates, false).limit, Some(4));
+    // Deleted prefixes may hide any number of stored entries.
+    assert_eq!(get_storage_range(&range, &updates, true).limit, None);
+    let range = KeyRange::new(vec![0], Some(vec![5]));
+    assert_eq!(get_storage_range(&range, &updates, false).limit, None);
+}
+
+#[test]
+fn test_merge_range() {
+    let mut updates = BTreeMap::new();
+    updates.insert(vec![1], Update::Set(10));
+    updates.insert(vec![2], Update::Removed);
+    updates.insert(vec![4], Update::Set(40));
+    updates.insert(vec![9], Update::Set(90));
+    let stored = vec![
+        (vec![2], 2),
+        (vec![3], 3),
+        (vec![4], 4),
+        (vec![5], 5),
+        (vec![6], 6),
+    ];
+    let is_deleted = |key: &[u8]| key == [5];
+    let range = KeyRange::new(vec![1], Some(vec![7]));
+    let merged = merge_range(&range, stored.clone(), is_deleted, &updates, |value| *value);
+    assert_eq!(
+        merged,
+        vec![(vec![1], 10), (vec![3], 3), (vec![4], 40), (vec![6], 6)]
+    );
+    let range = range.reversed().with_limit(3);
+    let merged = merge_range(&range, stored, is_deleted, &updates, |value| *value);
+    assert_eq!(merged, vec![(vec![6], 6), (vec![4], 40), (vec![3], 3)]);
+}
@@ -8,7 +8,7 @@ use crate::{
     batch::{Batch, DeletePrefixExpander},
     common::from_bytes_option,
     memory::MemoryStore,
-    store::{KeyIterable, KeyValueIterable, KeyValueStoreError, RestrictedKeyValueStore},
+    store::{KeyIterable, KeyRange, KeyValueIterable, KeyValueStoreError, RestrictedKeyValueStore},
     views::MIN_VIEW_TAG,
 };
 
@@ -62,6 +62,22 @@ pub trait Context: Clone {
         key_prefix: &[u8],
     ) -> Result<Self::KeyValues, Self::Error>;
 
+    /// Finds the keys of the given `range` after the `key_prefix`. The `key_prefix` is not
+    /// included in the returned keys.
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, Self::Error>;
+
+    /// Finds the `(key,value)` pairs of the given `range` after the `key_prefix`. The
+    /// `key_prefix` is not included in the returned keys.
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error>;
+
     /// Applies the operations from the `batch`, persisting the changes.
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error>;
 
@@ -257,6 +273,22 @@ where
         self.store.find_key_values_by_prefix(key_prefix).await
     }
 
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.store.find_keys_in_range(key_prefix, range).await
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        self.store.find_key_values_in_range(key_prefix, range).await
+    }
+
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
         self.store.write_batch(batch).await
     }
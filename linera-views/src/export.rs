@@ -0,0 +1,325 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Export of the key-value pairs of a store into a portable file, and import of such a
+//! file into a store of any backend.
+//!
+//! The file starts with [`EXPORT_MAGIC`], followed by records, each of them encoded with
+//! BCS and preceded by its length as a little-endian `u64`. The first record is a header,
+//! the following ones are chunks of key-value pairs together with their checksum, and
+//! the last one contains the number of pairs and a checksum of all the chunk checksums,
+//! so that truncated, reordered or corrupted files are rejected.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest as _, Sha3_256};
+
+use crate::{
+    batch::Batch,
+    store::{KeyRange, ReadableKeyValueStore, WritableKeyValueStore},
+    views::ViewError,
+};
+
+/// The first bytes of an export file.
+pub const EXPORT_MAGIC: &[u8; 8] = b"LINERAKV";
+
+/// The version of the format of the export files written by this code.
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// The maximal number of key-value pairs in a chunk of the file and in a batch written
+/// by an import. This is the lowest limit among the backends, i.e. DynamoDB's.
+const MAX_BATCH_LENGTH: usize = 100;
+
+/// The maximal total size of the keys and values in a chunk of the file and in a batch
+/// written by an import, unless a single pair is larger.
+const MAX_BATCH_SIZE: usize = 1_000_000;
+
+/// The records of an export file.
+#[derive(Debug, Serialize, Deserialize)]
+enum ExportRecord {
+    /// The first record of the file.
+    Header { format_version: u32 },
+    /// Some key-value pairs, and the hash of their serialization.
+    Chunk {
+        key_values: Vec<(Vec<u8>, Vec<u8>)>,
+        checksum: [u8; 32],
+    },
+    /// The last record of the file.
+    End { count: u64, checksum: [u8; 32] },
+}
+
+/// The number of key-value pairs in an export, and the checksum of its content.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExportSummary {
+    /// The number of key-value pairs.
+    pub count: u64,
+    /// The hash of the checksums of all the chunks of the file.
+    pub checksum: [u8; 32],
+}
+
+/// Writes all the key-value pairs of `store` into `writer`, in the format read by
+/// [`import_key_values`].
+///
+/// The pairs are read in increasing order of keys, with one `find_key_values_in_range`
+/// query for each page of at most `MAX_BATCH_LENGTH` pairs, so that the memory used does
+/// not grow with the size of the store. Empty keys are not exported: they are not written
+/// by views and some backends do not support them.
+pub async fn export_key_values<S, W>(store: &S, mut writer: W) -> Result<ExportSummary, ViewError>
+where
+    S: ReadableKeyValueStore + Sync,
+    W: Write,
+{
+    writer.write_all(EXPORT_MAGIC)?;
+    write_record(
+        &mut writer,
+        &ExportRecord::Header {
+            format_version: EXPORT_FORMAT_VERSION,
+        },
+    )?;
+    let mut chunk_writer = ChunkWriter::new(writer);
+    // The smallest non-empty key.
+    let mut start = vec![0];
+    loop {
+        let range = KeyRange::new(start, None).with_limit(MAX_BATCH_LENGTH);
+        let key_values = store.find_key_values_in_range(&[], &range).await?;
+        let Some((last_key, _)) = key_values.last() else {
+            break;
+        };
+        // The smallest key greater than the last one.
+        start = last_key.clone();
+        start.push(0);
+        let is_last_page = key_values.len() < MAX_BATCH_LENGTH;
+        for (key, value) in key_values {
+            chunk_writer.push(key, value)?;
+        }
+        if is_last_page {
+            break;
+        }
+    }
+    chunk_writer.finish()
+}
+
+/// Writes the key-value pairs of an export read from `reader` into `store`, which should
+/// be empty.
+///
+/// The whole file is read and its checksums are verified before anything is written, so
+/// that nothing is imported from a truncated or corrupted file. The file is then read
+/// again to write the pairs in batches of bounded size, with at most
+/// `store.max_stream_queries()` batches being written at a time.
+pub async fn import_key_values<S, R>(store: &S, mut reader: R) -> Result<ExportSummary, ViewError>
+where
+    S: ReadableKeyValueStore + WritableKeyValueStore,
+    R: Read + Seek,
+{
+    let start = reader.stream_position()?;
+    let mut records = ExportReader::new(&mut reader)?;
+    while records.next_chunk()?.is_some() {}
+    let summary = records.summary()?;
+
+    reader.seek(SeekFrom::Start(start))?;
+    let mut records = ExportReader::new(&mut reader)?;
+    let max_concurrent_batches = store.max_stream_queries().max(1);
+    let mut batches = Vec::new();
+    let mut batch = Batch::new();
+    let mut batch_size = 0;
+    while let Some(key_values) = records.next_chunk()? {
+        for (key, value) in key_values {
+            batch_size += key.len() + value.len();
+            batch.put_key_value_bytes(key, value);
+            if batch.num_operations() >= MAX_BATCH_LENGTH || batch_size >= MAX_BATCH_SIZE {
+                batches.push(std::mem::take(&mut batch));
+                batch_size = 0;
+            }
+            if batches.len() >= max_concurrent_batches {
+                write_batches(store, std::mem::take(&mut batches)).await?;
+            }
+        }
+    }
+    if records.summary()? != summary {
+        return Err(invalid_export("the file changed during the import"));
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    write_batches(store, batches).await?;
+    Ok(summary)
+}
+
+/// Reads the chunks of an export, verifying their checksums.
+struct ExportReader<R> {
+    reader: R,
+    hasher: Sha3_256,
+    count: u64,
+    summary: Option<ExportSummary>,
+}
+
+impl<R: Read> ExportReader<R> {
+    /// Reads the beginning of the file, up to the header.
+    fn new(mut reader: R) -> Result<Self, ViewError> {
+        let mut magic = [0u8; EXPORT_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != EXPORT_MAGIC {
+            return Err(invalid_export(
+                "the file is not an export of a key-value store",
+            ));
+        }
+        match read_record(&mut reader)? {
+            ExportRecord::Header { format_version } if format_version == EXPORT_FORMAT_VERSION => {}
+            ExportRecord::Header { format_version } => {
+                return Err(invalid_export(format!(
+                    "unsupported format version {format_version}"
+                )));
+            }
+            _ => return Err(invalid_export("missing header")),
+        }
+        Ok(Self {
+            reader,
+            hasher: Sha3_256::default(),
+            count: 0,
+            summary: None,
+        })
+    }
+
+    /// Returns the key-value pairs of the next chunk, or `None` once the end of the file
+    /// is reached and the number of pairs and the checksum of the file are verified.
+    fn next_chunk(&mut self) -> Result<Option<Vec<(Vec<u8>, Vec<u8>)>>, ViewError> {
+        if self.summary.is_some() {
+            return Ok(None);
+        }
+        match read_record(&mut self.reader)? {
+            ExportRecord::Header { .. } => Err(invalid_export("duplicate header")),
+            ExportRecord::Chunk {
+                key_values,
+                checksum,
+            } => {
+                if chunk_checksum(&key_values)? != checksum {
+                    return Err(invalid_export("wrong checksum of a chunk"));
+                }
+                self.hasher.update(checksum);
+                self.count += key_values.len() as u64;
+                Ok(Some(key_values))
+            }
+            ExportRecord::End { count, checksum } => {
+                if count != self.count
+                    || <[u8; 32]>::from(self.hasher.clone().finalize()) != checksum
+                {
+                    return Err(invalid_export(
+                        "wrong number of key-value pairs or checksum",
+                    ));
+                }
+                self.summary = Some(ExportSummary { count, checksum });
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns the summary of the file, once all the chunks are read.
+    fn summary(&self) -> Result<ExportSummary, ViewError> {
+        self.summary
+            .ok_or_else(|| invalid_export("the end of the file was not read"))
+    }
+}
+
+/// Groups the exported key-value pairs into chunks and writes them.
+struct ChunkWriter<W> {
+    writer: W,
+    key_values: Vec<(Vec<u8>, Vec<u8>)>,
+    size: usize,
+    count: u64,
+    hasher: Sha3_256,
+}
+
+impl<W: Write> ChunkWriter<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            key_values: Vec::new(),
+            size: 0,
+            count: 0,
+            hasher: Sha3_256::default(),
+        }
+    }
+
+    fn push(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<(), ViewError> {
+        self.size += key.len() + value.len();
+        self.key_values.push((key, value));
+        if self.key_values.len() >= MAX_BATCH_LENGTH || self.size >= MAX_BATCH_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ViewError> {
+        if self.key_values.is_empty() {
+            return Ok(());
+        }
+        let key_values = std::mem::take(&mut self.key_values);
+        let checksum = chunk_checksum(&key_values)?;
+        self.hasher.update(checksum);
+        self.count += key_values.len() as u64;
+        self.size = 0;
+        write_record(
+            &mut self.writer,
+            &ExportRecord::Chunk {
+                key_values,
+                checksum,
+            },
+        )
+    }
+
+    fn finish(mut self) -> Result<ExportSummary, ViewError> {
+        self.flush()?;
+        let summary = ExportSummary {
+            count: self.count,
+            checksum: self.hasher.finalize().into(),
+        };
+        write_record(
+            &mut self.writer,
+            &ExportRecord::End {
+                count: summary.count,
+                checksum: summary.checksum,
+            },
+        )?;
+        self.writer.flush()?;
+        Ok(summary)
+    }
+}
+
+async fn write_batches<S>(store: &S, batches: Vec<Batch>) -> Result<(), ViewError>
+where
+    S: WritableKeyValueStore,
+{
+    try_join_all(batches.into_iter().map(|batch| store.write_batch(batch))).await?;
+    Ok(())
+}
+
+fn chunk_checksum(key_values: &[(Vec<u8>, Vec<u8>)]) -> Result<[u8; 32], ViewError> {
+    let bytes = bcs::to_bytes(key_values)?;
+    Ok(Sha3_256::digest(bytes).into())
+}
+
+fn write_record(writer: &mut impl Write, record: &ExportRecord) -> Result<(), ViewError> {
+    let bytes = bcs::to_bytes(record)?;
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+fn read_record(reader: &mut impl Read) -> Result<ExportRecord, ViewError> {
+    let mut length = [0u8; 8];
+    reader.read_exact(&mut length)?;
+    let length = usize::try_from(u64::from_le_bytes(length))
+        .map_err(|_| invalid_export("record too large"))?;
+    let mut bytes = Vec::new();
+    reader.take(length as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != length {
+        return Err(invalid_export("truncated record"));
+    }
+    Ok(bcs::from_bytes(&bytes)?)
+}
+
+fn invalid_export(message: impl Into<String>) -> ViewError {
+    ViewError::InvalidExport(message.into())
+}
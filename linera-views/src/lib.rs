@@ -19,6 +19,7 @@ The databases supported are of the NoSQL variety and they are key-value stores.
 We provide support for the following databases:
 * `MemoryStore` is using the memory
 * `RocksDbStore` is a disk-based key-value store
+* `RedbStore` is a disk-based key-value store built on the pure-Rust redb database.
 * `DynamoDbStore` is the AWS-based DynamoDB service.
 * `ScyllaDbStore` is a cloud-based Cassandra-compatible database.
 * `ServiceStoreClient` is a gRPC-based storage that uses either memory or RocksDB. It is available in `linera-storage-service`.
@@ -51,6 +52,7 @@ The following views implement the `View` trait:
 * `SetView` implements a set with keys.
 * `CollectionView` implements a map whose values are views themselves.
 * `ReentrantCollectionView` implements a map for which different keys can be accessed independently.
+* `MerkleMapView`, `MerkleSetView`, `MerkleCollectionView` and `MerkleKeyValueStoreView` maintain their hash incrementally in a Merkle tree, which also provides proofs of the values of individual keys.
 * `ViewContainer<C>` implements a `KeyValueStore` and is used internally.
 
 The `LogView` can be seen as an analog of `VecDeque` while `MapView` is an analog of `BTreeMap`.
@@ -74,6 +76,9 @@ pub mod common;
 /// Elementary data-structures implementing the [`views::View`] trait.
 pub mod views;
 
+/// Export and import of the content of a store.
+pub mod export;
+
 /// Backend implementing the [`crate::store::KeyValueStore`] trait.
 pub mod backends;
 
@@ -92,12 +97,18 @@ pub mod random;
 #[cfg(with_testing)]
 pub mod test_utils;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use backends::compression;
 #[cfg(with_dynamodb)]
 pub use backends::dynamo_db;
+#[cfg(with_encryption)]
+pub use backends::encryption;
 #[cfg(with_indexeddb)]
 pub use backends::indexed_db;
 #[cfg(with_metrics)]
 pub use backends::metering;
+#[cfg(with_redb)]
+pub use backends::redb_db;
 #[cfg(with_rocksdb)]
 pub use backends::rocks_db;
 #[cfg(with_scylladb)]
@@ -105,7 +116,7 @@ pub use backends::scylla_db;
 pub use backends::{journaling, lru_caching, memory, value_splitting};
 pub use views::{
     bucket_queue_view, collection_view, hashable_wrapper, key_value_store_view, log_view, map_view,
-    queue_view, reentrant_collection_view, register_view, set_view,
+    merkle_map_view, queue_view, reentrant_collection_view, register_view, set_view,
 };
 /// Re-exports used by the derive macros of this library.
 #[doc(hidden)]
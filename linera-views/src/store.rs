@@ -3,13 +3,24 @@
 
 //! This provides the trait definitions for the stores.
 
-use std::{fmt::Debug, future::Future};
+use std::{
+    fmt::Debug,
+    future::Future,
+    ops::Bound::{self, Excluded, Included, Unbounded},
+    path::Path,
+};
 
 use serde::de::DeserializeOwned;
 
+#[cfg(with_encryption)]
+use crate::encryption::EncryptionKey;
 #[cfg(with_testing)]
 use crate::random::generate_test_namespace;
-use crate::{batch::Batch, common::from_bytes_option, views::ViewError};
+use crate::{
+    batch::Batch,
+    common::{from_bytes_option, get_upper_bound, get_upper_bound_option},
+    views::ViewError,
+};
 
 /// The common initialization parameters for the `KeyValueStore`
 #[derive(Debug, Clone)]
@@ -29,6 +40,11 @@ pub struct CommonStoreConfig {
     pub max_stream_queries: usize,
     /// The cache size being used.
     pub cache_size: usize,
+    /// The size above which values are compressed, if any.
+    pub compression_threshold: Option<usize>,
+    /// The key used to encrypt the values, if any.
+    #[cfg(with_encryption)]
+    pub encryption_key: Option<EncryptionKey>,
 }
 
 impl CommonStoreConfig {
@@ -47,6 +63,9 @@ impl Default for CommonStoreConfig {
             max_concurrent_queries: None,
             max_stream_queries: 10,
             cache_size: 1000,
+            compression_threshold: None,
+            #[cfg(with_encryption)]
+            encryption_key: None,
         }
     }
 }
@@ -72,6 +91,123 @@ pub trait WithError {
     type Error: KeyValueStoreError;
 }
 
+/// A range of keys to search in a store, relative to a key prefix, together with the
+/// order in which the keys are returned and the maximal number of keys returned.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct KeyRange {
+    /// The first key of the range.
+    pub start: Vec<u8>,
+    /// The first key after the range, or `None` if the range contains all the keys
+    /// from `start` onwards.
+    pub end: Option<Vec<u8>>,
+    /// Whether the keys are returned in decreasing order.
+    pub reverse: bool,
+    /// The maximal number of keys returned, or `None` if all the keys are returned.
+    pub limit: Option<usize>,
+}
+
+impl KeyRange {
+    /// Creates the range of the keys from `start` (included) to `end` (excluded).
+    pub fn new(start: Vec<u8>, end: Option<Vec<u8>>) -> Self {
+        KeyRange {
+            start,
+            end,
+            reverse: false,
+            limit: None,
+        }
+    }
+
+    /// Creates the range of the keys between the given bounds.
+    pub fn from_bounds(start: Bound<Vec<u8>>, end: Bound<Vec<u8>>) -> Self {
+        // The smallest key greater than `key` is `key` followed by a zero byte.
+        let start = match start {
+            Included(key) => key,
+            Excluded(mut key) => {
+                key.push(0);
+                key
+            }
+            Unbounded => Vec::new(),
+        };
+        let end = match end {
+            Included(mut key) => {
+                key.push(0);
+                Some(key)
+            }
+            Excluded(key) => Some(key),
+            Unbounded => None,
+        };
+        Self::new(start, end)
+    }
+
+    /// Returns the keys of the range in decreasing order.
+    pub fn reversed(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Returns at most `limit` keys of the range.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Tests whether the range contains no key.
+    pub fn is_empty(&self) -> bool {
+        self.end.as_ref().is_some_and(|end| *end <= self.start)
+    }
+
+    /// Tests whether the range contains the given `key`.
+    pub fn contains(&self, key: &[u8]) -> bool {
+        key >= self.start.as_slice() && self.end.as_ref().map_or(true, |end| key < end.as_slice())
+    }
+
+    /// Returns the bounds of the range once its keys are prefixed by `key_prefix`.
+    pub fn bounds(&self, key_prefix: &[u8]) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+        let mut start = key_prefix.to_vec();
+        start.extend(&self.start);
+        let end = match &self.end {
+            Some(end) => {
+                let mut end_key = key_prefix.to_vec();
+                end_key.extend(end);
+                Excluded(end_key)
+            }
+            None => get_upper_bound(key_prefix),
+        };
+        (Included(start), end)
+    }
+
+    /// Returns the range of the keys of `range` prefixed by `key_prefix`.
+    pub fn prefixed(key_prefix: &[u8], range: &KeyRange) -> Self {
+        let mut start = key_prefix.to_vec();
+        start.extend(&range.start);
+        let end = match &range.end {
+            Some(end) => {
+                let mut end_key = key_prefix.to_vec();
+                end_key.extend(end);
+                Some(end_key)
+            }
+            None => get_upper_bound_option(key_prefix),
+        };
+        KeyRange {
+            start,
+            end,
+            ..range.clone()
+        }
+    }
+
+    /// Takes the `entries` of the range, sorted by increasing keys, and returns them in
+    /// the order and the number requested by the range.
+    pub fn select<T>(&self, mut entries: Vec<T>) -> Vec<T> {
+        if self.reverse {
+            entries.reverse();
+        }
+        if let Some(limit) = self.limit {
+            entries.truncate(limit);
+        }
+        entries
+    }
+}
+
 /// Low-level, asynchronous read key-value operations. Useful for storage APIs not based on views.
 #[trait_variant::make(ReadableKeyValueStore: Send)]
 pub trait LocalReadableKeyValueStore: WithError {
@@ -115,6 +251,68 @@ pub trait LocalReadableKeyValueStore: WithError {
     // https://github.com/rust-lang/impl-trait-utils/issues/17, but once that bug is fixed
     // we can revert them to `async fn` syntax, which is neater.
 
+    /// Finds the keys matching the prefix whose remainder belongs to the `range`, in the
+    /// order and up to the number of keys requested by the `range`. The prefix is not
+    /// included in the returned keys.
+    ///
+    /// The default implementation scans all the keys matching the prefix. Stores that can
+    /// seek a key and iterate in both directions should override it.
+    fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> impl Future<Output = Result<Vec<Vec<u8>>, Self::Error>>
+    where
+        Self: Sync,
+    {
+        async move {
+            if range.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut keys = Vec::new();
+            for key in self.find_keys_by_prefix(key_prefix).await?.iterator() {
+                let key = key?;
+                if range.contains(key) {
+                    keys.push(key.to_vec());
+                }
+            }
+            Ok(range.select(keys))
+        }
+    }
+
+    /// Finds the `(key,value)` pairs matching the prefix whose key remainder belongs to the
+    /// `range`, in the order and up to the number of pairs requested by the `range`. The
+    /// prefix is not included in the returned keys.
+    ///
+    /// The default implementation scans all the pairs matching the prefix. Stores that
+    /// can seek a key and iterate in both directions should override it.
+    fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> impl Future<Output = Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error>>
+    where
+        Self: Sync,
+    {
+        async move {
+            if range.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut key_values = Vec::new();
+            for entry in self
+                .find_key_values_by_prefix(key_prefix)
+                .await?
+                .into_iterator_owned()
+            {
+                let (key, value) = entry?;
+                if range.contains(&key) {
+                    key_values.push((key, value));
+                }
+            }
+            Ok(range.select(key_values))
+        }
+    }
+
     /// Reads a single `key` and deserializes the result if present.
     fn read_value<V: DeserializeOwned>(
         &self,
@@ -199,6 +397,24 @@ pub trait LocalAdminKeyValueStore: WithError + Sized {
     /// Deletes the given namespace.
     async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error>;
 
+    /// Writes a consistent point-in-time snapshot of the namespace of this store, for all
+    /// root keys, into the directory `path`, which must not exist yet. The snapshot is
+    /// taken through the connection of the store, so that a process can snapshot the
+    /// storage it is using without being stopped.
+    ///
+    /// Backends without a native snapshot facility return an error. For those, use the
+    /// tools of the database itself, e.g. `nodetool snapshot` for ScyllaDB or on-demand
+    /// backups for DynamoDB.
+    async fn snapshot(&self, path: &Path) -> Result<(), Self::Error>;
+
+    /// Restores the given namespace from a snapshot written by [`Self::snapshot`].
+    /// Returns an error if the namespace exists.
+    async fn restore(
+        config: &Self::Config,
+        namespace: &str,
+        path: &Path,
+    ) -> Result<(), Self::Error>;
+
     /// Initializes a storage if missing and provides it.
     fn maybe_create_and_connect(
         config: &Self::Config,
@@ -10,6 +10,7 @@ pub mod performance;
 use std::{
     collections::{BTreeMap, BTreeSet, HashSet},
     fmt::Debug,
+    io::Cursor,
 };
 
 use rand::{seq::SliceRandom, Rng};
@@ -19,9 +20,10 @@ use crate::{
         Batch, WriteOperation,
         WriteOperation::{Delete, Put},
     },
+    export::{export_key_values, import_key_values},
     random::{generate_test_namespace, make_deterministic_rng, make_nondeterministic_rng},
     store::{
-        KeyIterable, KeyValueIterable, LocalKeyValueStore, LocalRestrictedKeyValueStore,
+        KeyIterable, KeyRange, KeyValueIterable, LocalKeyValueStore, LocalRestrictedKeyValueStore,
         TestKeyValueStore,
     },
 };
@@ -154,6 +156,7 @@ pub fn span_random_reordering_put_delete<R: Rng>(
 /// * `read_multi_values_bytes`
 /// * `find_keys_by_prefix` / `find_key_values_by_prefix`
 /// * The ordering of keys returned by `find_keys_by_prefix` and `find_key_values_by_prefix`
+/// * `find_keys_in_range` / `find_key_values_in_range`, compared with the prefix queries
 pub async fn run_reads<S: LocalRestrictedKeyValueStore>(
     store: S,
     key_values: Vec<(Vec<u8>, Vec<u8>)>,
@@ -201,8 +204,70 @@ pub async fn run_reads<S: LocalRestrictedKeyValueStore>(
         }
         assert_eq!(set_key_value1, set_key_value2);
     }
-    // Now checking the read_multi_values_bytes
+    // Checking the range queries against the prefix queries
     let mut rng = make_deterministic_rng();
+    for key_prefix in keys
+        .iter()
+        .take(5)
+        .flat_map(|key| (0..key.len()).map(|u| &key[..=u]))
+    {
+        let all_key_values = store
+            .find_key_values_by_prefix(key_prefix)
+            .await
+            .unwrap()
+            .iterator()
+            .map(|entry| {
+                let (key, value) = entry.unwrap();
+                (key.to_vec(), value.to_vec())
+            })
+            .collect::<Vec<_>>();
+        let mut random_key = || {
+            let mut key = all_key_values[rng.gen_range(0..all_key_values.len())]
+                .0
+                .clone();
+            if rng.gen() {
+                key.push(0);
+            }
+            key
+        };
+        let start = random_key();
+        let end = random_key();
+        for (start, end) in [
+            (start.clone(), Some(end.clone())),
+            (start, None),
+            (Vec::new(), Some(end)),
+        ] {
+            for reverse in [false, true] {
+                for limit in [None, Some(1), Some(3)] {
+                    let range = KeyRange {
+                        start: start.clone(),
+                        end: end.clone(),
+                        reverse,
+                        limit,
+                    };
+                    let expected = range.select(
+                        all_key_values
+                            .iter()
+                            .filter(|(key, _)| range.contains(key))
+                            .cloned()
+                            .collect(),
+                    );
+                    let key_values = store
+                        .find_key_values_in_range(key_prefix, &range)
+                        .await
+                        .unwrap();
+                    let keys = store.find_keys_in_range(key_prefix, &range).await.unwrap();
+                    let expected_keys = expected
+                        .iter()
+                        .map(|(key, _)| key.clone())
+                        .collect::<Vec<_>>();
+                    assert_eq!(key_values, expected);
+                    assert_eq!(keys, expected_keys);
+                }
+            }
+        }
+    }
+    // Now checking the read_multi_values_bytes
     for _ in 0..3 {
         let mut keys = Vec::new();
         let mut values = Vec::new();
@@ -700,3 +765,123 @@ where
             .expect("A successful deletion");
     }
 }
+
+/// Exercises the snapshots of the `AdminKeyValueStore`: a namespace restored from a
+/// snapshot contains the data of all the root keys at the time of the snapshot.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn snapshot_test<S: TestKeyValueStore>()
+where
+    S::Error: Debug,
+{
+    let config = S::new_test_config().await.expect("config");
+    let namespace = generate_test_namespace();
+    let restored_namespace = generate_test_namespace();
+    let root_keys = [vec![], vec![1], vec![2, 3]];
+    let mut rng = make_deterministic_rng();
+    let mut expected = Vec::new();
+    let store = S::recreate_and_connect(&config, &namespace, &[])
+        .await
+        .expect("store");
+    for root_key in &root_keys {
+        let store = store.clone_with_root_key(root_key).expect("store");
+        let key_values = get_random_key_values(&mut rng, 20);
+        let mut batch = Batch::new();
+        for (key, value) in &key_values {
+            batch.put_key_value_bytes(key.clone(), value.clone());
+        }
+        store.write_batch(batch).await.expect("write_batch");
+        expected.push(key_values.into_iter().collect::<BTreeMap<_, _>>());
+    }
+    // The snapshot is taken through the open store, which keeps being used afterwards.
+    let dir = tempfile::tempdir().expect("temporary directory");
+    let path = dir.path().join("snapshot");
+    store.snapshot(&path).await.expect("snapshot");
+    // Changes after the snapshot are not part of it.
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![255; 10], vec![0]);
+    store.write_batch(batch).await.expect("write_batch");
+    drop(store);
+    S::restore(&config, &restored_namespace, &path)
+        .await
+        .expect("restore");
+    assert!(S::restore(&config, &restored_namespace, &path)
+        .await
+        .is_err());
+    {
+        let store = S::connect(&config, &restored_namespace, &[])
+            .await
+            .expect("store");
+        for (root_key, expected) in root_keys.iter().zip(expected) {
+            let store = store.clone_with_root_key(root_key).expect("store");
+            let key_values = store
+                .find_key_values_by_prefix(&[])
+                .await
+                .expect("find_key_values_by_prefix")
+                .into_iterator_owned()
+                .collect::<Result<BTreeMap<_, _>, _>>()
+                .expect("key values");
+            assert_eq!(key_values, expected);
+        }
+    }
+    S::delete(&config, &namespace).await.expect("deletion");
+    S::delete(&config, &restored_namespace)
+        .await
+        .expect("deletion");
+}
+
+/// Exercises the export of a store of type `S1` and its import into a store of type
+/// `S2`: the imported store contains the same key-value pairs, and corrupted exports are
+/// rejected.
+pub async fn export_import_test<S1: TestKeyValueStore + Sync, S2: TestKeyValueStore>()
+where
+    S1::Error: Debug,
+    S2::Error: Debug,
+{
+    let mut rng = make_deterministic_rng();
+    let key_values = get_random_key_values(&mut rng, 300);
+    let source = S1::new_test_store().await.expect("store");
+    let mut batch = Batch::new();
+    for (key, value) in &key_values {
+        batch.put_key_value_bytes(key.clone(), value.clone());
+    }
+    source.write_batch(batch).await.expect("write_batch");
+    let mut bytes = Vec::new();
+    let summary = export_key_values(&source, &mut bytes)
+        .await
+        .expect("export");
+    assert_eq!(summary.count, key_values.len() as u64);
+
+    let target = S2::new_test_store().await.expect("store");
+    let imported_summary = import_key_values(&target, Cursor::new(&bytes))
+        .await
+        .expect("import");
+    assert_eq!(imported_summary, summary);
+    let imported_key_values = target
+        .find_key_values_by_prefix(&[])
+        .await
+        .expect("find_key_values_by_prefix")
+        .into_iterator_owned()
+        .collect::<Result<BTreeMap<_, _>, _>>()
+        .expect("key values");
+    assert_eq!(
+        imported_key_values,
+        key_values.into_iter().collect::<BTreeMap<_, _>>()
+    );
+
+    let target = S2::new_test_store().await.expect("store");
+    let truncated = &bytes[..bytes.len() - 1];
+    assert!(import_key_values(&target, Cursor::new(truncated))
+        .await
+        .is_err());
+    let mut corrupted = bytes.clone();
+    let index = corrupted.len() / 2;
+    corrupted[index] ^= 1;
+    assert!(import_key_values(&target, Cursor::new(&corrupted))
+        .await
+        .is_err());
+    let keys = target
+        .find_keys_by_prefix(&[])
+        .await
+        .expect("find_keys_by_prefix");
+    assert_eq!(keys.iterator().count(), 0);
+}
@@ -9,6 +9,7 @@ use std::{
     io::Write,
     marker::PhantomData,
     mem,
+    ops::RangeBounds,
 };
 
 use async_lock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -22,10 +23,12 @@ use {
 
 use crate::{
     batch::Batch,
-    common::{CustomSerialize, HasherOutput, Update},
+    common::{
+        get_key_range, get_storage_range, merge_range, CustomSerialize, HasherOutput, Update,
+    },
     context::Context,
     hashable_wrapper::WrappedHashableContainerView,
-    store::KeyIterable,
+    store::{KeyIterable, KeyRange},
     views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
 };
 
@@ -610,6 +613,47 @@ where
         .await?;
         Ok(count)
     }
+
+    /// Returns the keys of the collection in the given `range`, in the order and the
+    /// number requested by the range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::collection_view::ByteCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::store::KeyRange;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: ByteCollectionView<_, RegisterView<_, String>> =
+    ///     ByteCollectionView::load(context).await.unwrap();
+    /// view.load_entry_mut(&[0, 1]).await.unwrap();
+    /// view.load_entry_mut(&[0, 2]).await.unwrap();
+    /// view.load_entry_mut(&[1]).await.unwrap();
+    /// let range = KeyRange::new(vec![0, 2], None).reversed().with_limit(1);
+    /// let keys = view.keys_in_range(&range).await.unwrap();
+    /// assert_eq!(keys, vec![vec![1]]);
+    /// # })
+    /// ```
+    pub async fn keys_in_range(&self, range: &KeyRange) -> Result<Vec<Vec<u8>>, ViewError> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let updates = self.updates.read().await;
+        let mut stored = Vec::new();
+        if !self.delete_storage_first {
+            let base = self.get_index_key(&[]);
+            let storage_range = get_storage_range(range, &*updates, false);
+            for key in self
+                .context
+                .find_keys_in_range(&base, &storage_range)
+                .await?
+            {
+                stored.push((key, ()));
+            }
+        }
+        let keys = merge_range(range, stored, |_| false, &*updates, |_| ());
+        Ok(keys.into_iter().map(|(key, ())| key).collect())
+    }
 }
 
 #[async_trait]
@@ -1338,6 +1382,40 @@ where
     pub async fn count(&self) -> Result<usize, ViewError> {
         self.collection.count().await
     }
+
+    /// Returns the indices of the collection in the given `range`, in the order
+    /// determined by the custom serialization or in the reverse order, and at most
+    /// `limit` of them.
+    /// The custom serialization has to preserve the order of the indices.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::{create_test_memory_context, MemoryContext};
+    /// # use linera_views::collection_view::CustomCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: CustomCollectionView<_, u128, RegisterView<_, String>> =
+    ///     CustomCollectionView::load(context).await.unwrap();
+    /// view.load_entry_mut(&23).await.unwrap();
+    /// view.load_entry_mut(&300).await.unwrap();
+    /// view.load_entry_mut(&3000).await.unwrap();
+    /// let indices = view.indices_in_range(24.., false, Some(1)).await.unwrap();
+    /// assert_eq!(indices, vec![300]);
+    /// # })
+    /// ```
+    pub async fn indices_in_range<R>(
+        &self,
+        range: R,
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, ViewError>
+    where
+        R: RangeBounds<I>,
+    {
+        let range = get_key_range(range, reverse, limit, I::to_custom_bytes)?;
+        let keys = self.collection.keys_in_range(&range).await?;
+        keys.iter().map(|key| I::from_custom_bytes(key)).collect()
+    }
 }
 
 impl<C, I, W> CustomCollectionView<C, I, W>
@@ -17,12 +17,12 @@ use {
 use crate::{
     batch::{Batch, WriteOperation},
     common::{
-        from_bytes_option, from_bytes_option_or_default, get_interval, get_upper_bound,
-        DeletionSet, HasherOutput, SuffixClosedSetIterator, Update,
+        from_bytes_option, from_bytes_option_or_default, get_interval, get_storage_range,
+        get_upper_bound, merge_range, DeletionSet, HasherOutput, SuffixClosedSetIterator, Update,
     },
     context::Context,
     map_view::ByteMapView,
-    store::{KeyIterable, KeyValueIterable},
+    store::{KeyIterable, KeyRange, KeyValueIterable},
     views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
 };
 
@@ -105,6 +105,30 @@ static KEY_VALUE_STORE_VIEW_FIND_KEY_VALUES_BY_PREFIX_LATENCY: LazyLock<Histogra
         )
     });
 
+#[cfg(with_metrics)]
+/// The latency of find keys in range operation
+static KEY_VALUE_STORE_VIEW_FIND_KEYS_IN_RANGE_LATENCY: LazyLock<HistogramVec> =
+    LazyLock::new(|| {
+        register_histogram_vec(
+            "key_value_store_view_find_keys_in_range_latency",
+            "KeyValueStoreView find keys in range latency",
+            &[],
+            bucket_latencies(5.0),
+        )
+    });
+
+#[cfg(with_metrics)]
+/// The latency of find key values in range operation
+static KEY_VALUE_STORE_VIEW_FIND_KEY_VALUES_IN_RANGE_LATENCY: LazyLock<HistogramVec> =
+    LazyLock::new(|| {
+        register_histogram_vec(
+            "key_value_store_view_find_key_values_in_range_latency",
+            "KeyValueStoreView find key values in range latency",
+            &[],
+            bucket_latencies(5.0),
+        )
+    });
+
 #[cfg(with_metrics)]
 /// The latency of write batch operation
 static KEY_VALUE_STORE_VIEW_WRITE_BATCH_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
@@ -1121,6 +1145,99 @@ where
         Ok(key_values)
     }
 
+    /// Returns the keys of the given `range`, in the order and the number requested by
+    /// the range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::key_value_store_view::KeyValueStoreView;
+    /// # use linera_views::store::KeyRange;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view = KeyValueStoreView::load(context).await.unwrap();
+    /// view.insert(vec![0, 1], vec![34]).await.unwrap();
+    /// view.insert(vec![2, 3], vec![42]).await.unwrap();
+    /// view.insert(vec![4, 5], vec![57]).await.unwrap();
+    /// let range = KeyRange::new(vec![1], None).reversed().with_limit(1);
+    /// let keys = view.find_keys_in_range(&range).await.unwrap();
+    /// assert_eq!(keys, vec![vec![4, 5]]);
+    /// # })
+    /// ```
+    pub async fn find_keys_in_range(&self, range: &KeyRange) -> Result<Vec<Vec<u8>>, ViewError> {
+        #[cfg(with_metrics)]
+        let _latency = KEY_VALUE_STORE_VIEW_FIND_KEYS_IN_RANGE_LATENCY.measure_latency();
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut stored = Vec::new();
+        if !self.deletion_set.delete_storage_first {
+            let key_prefix = self.context.base_tag(KeyTag::Index as u8);
+            let has_deleted_prefixes = !self.deletion_set.deleted_prefixes.is_empty();
+            let storage_range = get_storage_range(range, &self.updates, has_deleted_prefixes);
+            for key in self
+                .context
+                .find_keys_in_range(&key_prefix, &storage_range)
+                .await?
+            {
+                stored.push((key, ()));
+            }
+        }
+        let keys = merge_range(
+            range,
+            stored,
+            |key| self.deletion_set.contains_prefix_of(key),
+            &self.updates,
+            |_| (),
+        );
+        Ok(keys.into_iter().map(|(key, ())| key).collect())
+    }
+
+    /// Returns the key-value pairs of the given `range`, in the order and the number
+    /// requested by the range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::key_value_store_view::KeyValueStoreView;
+    /// # use linera_views::store::KeyRange;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view = KeyValueStoreView::load(context).await.unwrap();
+    /// view.insert(vec![0, 1], vec![34]).await.unwrap();
+    /// view.insert(vec![2, 3], vec![42]).await.unwrap();
+    /// view.insert(vec![4, 5], vec![57]).await.unwrap();
+    /// let range = KeyRange::new(vec![0, 2], Some(vec![4]));
+    /// let key_values = view.find_key_values_in_range(&range).await.unwrap();
+    /// assert_eq!(key_values, vec![(vec![2, 3], vec![42])]);
+    /// # })
+    /// ```
+    pub async fn find_key_values_in_range(
+        &self,
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+        #[cfg(with_metrics)]
+        let _latency = KEY_VALUE_STORE_VIEW_FIND_KEY_VALUES_IN_RANGE_LATENCY.measure_latency();
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut stored = Vec::new();
+        if !self.deletion_set.delete_storage_first {
+            let key_prefix = self.context.base_tag(KeyTag::Index as u8);
+            let has_deleted_prefixes = !self.deletion_set.deleted_prefixes.is_empty();
+            let storage_range = get_storage_range(range, &self.updates, has_deleted_prefixes);
+            stored = self
+                .context
+                .find_key_values_in_range(&key_prefix, &storage_range)
+                .await?;
+        }
+        Ok(merge_range(
+            range,
+            stored,
+            |key| self.deletion_set.contains_prefix_of(key),
+            &self.updates,
+            Clone::clone,
+        ))
+    }
+
     async fn compute_hash(&self) -> Result<<sha3::Sha3_256 as Hasher>::Output, ViewError> {
         #[cfg(with_metrics)]
         let _hash_latency = KEY_VALUE_STORE_VIEW_HASH_LATENCY.measure_latency();
@@ -1255,6 +1372,34 @@ where
         let view = self.view.read().await;
         Ok(view.find_key_values_by_prefix(key_prefix).await?)
     }
+
+    async fn find_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<Vec<u8>>, ViewContainerError> {
+        let view = self.view.read().await;
+        Ok(view
+            .find_keys_in_range(&KeyRange::prefixed(key_prefix, range))
+            .await?
+            .into_iter()
+            .map(|key| key[key_prefix.len()..].to_vec())
+            .collect())
+    }
+
+    async fn find_key_values_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewContainerError> {
+        let view = self.view.read().await;
+        Ok(view
+            .find_key_values_in_range(&KeyRange::prefixed(key_prefix, range))
+            .await?
+            .into_iter()
+            .map(|(key, value)| (key[key_prefix.len()..].to_vec(), value))
+            .collect())
+    }
 }
 
 #[cfg(with_testing)]
@@ -41,6 +41,7 @@ use std::{
     collections::{btree_map::Entry, BTreeMap},
     marker::PhantomData,
     mem,
+    ops::RangeBounds,
 };
 
 use async_trait::async_trait;
@@ -49,12 +50,12 @@ use serde::{de::DeserializeOwned, Serialize};
 use crate::{
     batch::Batch,
     common::{
-        from_bytes_option, get_interval, CustomSerialize, DeletionSet, HasherOutput,
-        SuffixClosedSetIterator, Update,
+        from_bytes_option, get_interval, get_key_range, get_storage_range, merge_range,
+        CustomSerialize, DeletionSet, HasherOutput, SuffixClosedSetIterator, Update,
     },
     context::Context,
     hashable_wrapper::WrappedHashableContainerView,
-    store::{KeyIterable, KeyValueIterable},
+    store::{KeyIterable, KeyRange, KeyValueIterable},
     views::{ClonableView, HashableView, Hasher, View, ViewError},
 };
 
@@ -581,6 +582,53 @@ where
         Ok(keys)
     }
 
+    /// Returns the keys of the map in the given `range`, in the order and the number
+    /// requested by the range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::store::KeyRange;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// map.insert(vec![2, 2], String::from("Hallo"));
+    /// let range = KeyRange::new(vec![1], None).reversed();
+    /// assert_eq!(
+    ///     map.keys_in_range(&range).await.unwrap(),
+    ///     vec![vec![2, 2], vec![1, 2]]
+    /// );
+    /// # })
+    /// ```
+    pub async fn keys_in_range(&self, range: &KeyRange) -> Result<Vec<Vec<u8>>, ViewError> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut stored = Vec::new();
+        if !self.deletion_set.delete_storage_first {
+            let base = self.context.base_key();
+            let has_deleted_prefixes = !self.deletion_set.deleted_prefixes.is_empty();
+            let storage_range = get_storage_range(range, &self.updates, has_deleted_prefixes);
+            for key in self
+                .context
+                .find_keys_in_range(&base, &storage_range)
+                .await?
+            {
+                stored.push((key, ()));
+            }
+        }
+        let keys = merge_range(
+            range,
+            stored,
+            |key| self.deletion_set.contains_prefix_of(key),
+            &self.updates,
+            |_| (),
+        );
+        Ok(keys.into_iter().map(|(key, ())| key).collect())
+    }
+
     /// Returns the number of keys of the map
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -852,6 +900,58 @@ where
     pub async fn key_values(&self) -> Result<Vec<(Vec<u8>, V)>, ViewError> {
         self.key_values_by_prefix(Vec::new()).await
     }
+
+    /// Returns the keys and values of the map in the given `range`, in the order and the
+    /// number requested by the range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::store::KeyRange;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// map.insert(vec![2, 2], String::from("Hallo"));
+    /// let range = KeyRange::new(vec![1], None).with_limit(1);
+    /// assert_eq!(
+    ///     map.key_values_in_range(&range).await.unwrap(),
+    ///     vec![(vec![1, 2], String::from("Bonjour"))]
+    /// );
+    /// # })
+    /// ```
+    pub async fn key_values_in_range(
+        &self,
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, V)>, ViewError> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut stored = Vec::new();
+        if !self.deletion_set.delete_storage_first {
+            let base = self.context.base_key();
+            let has_deleted_prefixes = !self.deletion_set.deleted_prefixes.is_empty();
+            let storage_range = get_storage_range(range, &self.updates, has_deleted_prefixes);
+            for (key, bytes) in self
+                .context
+                .find_key_values_in_range(&base, &storage_range)
+                .await?
+            {
+                stored.push((key, ValueOrBytes::Bytes(bytes)));
+            }
+        }
+        merge_range(
+            range,
+            stored,
+            |key| self.deletion_set.contains_prefix_of(key),
+            &self.updates,
+            ValueOrBytes::Value,
+        )
+        .into_iter()
+        .map(|(key, value)| Ok((key, value.to_value()?.into_owned())))
+        .collect()
+    }
 }
 
 impl<C, V> ByteMapView<C, V>
@@ -1883,6 +1983,76 @@ where
     pub async fn count(&self) -> Result<usize, ViewError> {
         self.map.count().await
     }
+
+    /// Returns the indices of the map in the given `range`, in the order determined by
+    /// the custom serialization or in the reverse order, and at most `limit` of them.
+    /// The custom serialization has to preserve the order of the indices, which is why this
+    /// is not offered on [`MapView`]: BCS serializes integers in little-endian order.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::CustomMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: CustomMapView<_, u128, String> = CustomMapView::load(context).await.unwrap();
+    /// map.insert(&34u128, String::from("Thanks"));
+    /// map.insert(&300u128, String::from("Spasiba"));
+    /// map.insert(&3000u128, String::from("Merci"));
+    /// let indices = map.indices_in_range(..=300, true, None).await.unwrap();
+    /// assert_eq!(indices, vec![300, 34]);
+    /// # })
+    /// ```
+    pub async fn indices_in_range<R>(
+        &self,
+        range: R,
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, ViewError>
+    where
+        R: RangeBounds<I>,
+    {
+        let range = get_key_range(range, reverse, limit, I::to_custom_bytes)?;
+        let keys = self.map.keys_in_range(&range).await?;
+        keys.iter().map(|key| I::from_custom_bytes(key)).collect()
+    }
+
+    /// Returns the `(index,value)` pairs of the map in the given `range`, in the order
+    /// determined by the custom serialization or in the reverse order, and at most
+    /// `limit` of them.
+    /// The custom serialization has to preserve the order of the indices.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::map_view::CustomMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: CustomMapView<_, u128, String> = CustomMapView::load(context).await.unwrap();
+    /// map.insert(&34u128, String::from("Thanks"));
+    /// map.insert(&300u128, String::from("Spasiba"));
+    /// map.insert(&3000u128, String::from("Merci"));
+    /// let index_values = map
+    ///     .index_values_in_range(35.., false, Some(1))
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(index_values, vec![(300, String::from("Spasiba"))]);
+    /// # })
+    /// ```
+    pub async fn index_values_in_range<R>(
+        &self,
+        range: R,
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(I, V)>, ViewError>
+    where
+        R: RangeBounds<I>,
+    {
+        let range = get_key_range(range, reverse, limit, I::to_custom_bytes)?;
+        let key_values = self.map.key_values_in_range(&range).await?;
+        key_values
+            .into_iter()
+            .map(|(key, value)| Ok((I::from_custom_bytes(&key)?, value)))
+            .collect()
+    }
 }
 
 impl<C, I, V> CustomMapView<C, I, V>
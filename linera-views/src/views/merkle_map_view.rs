@@ -0,0 +1,1640 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Merkle views implement maps, sets and collections whose hash is maintained
+//! incrementally.
+//!
+//! Computing the hash of a [`ByteMapView`] requires rescanning all of its entries. The views
+//! of this module instead persist the nodes of a binary Merkle tree indexed by the hashes of
+//! the keys. A subtree containing a single entry is represented by the leaf of that entry,
+//! so the depth of the tree is logarithmic in the number of entries and rehashing after `k`
+//! entries were modified costs `O(k log n)` node reads and writes.
+//!
+//! The keys modified since the last hash computation are tracked in memory. If the view is
+//! flushed before its hash is computed, these keys are persisted so that their leaves are
+//! updated by the next hash computation.
+//!
+//! There are 5 different variants:
+//! * The [`MerkleByteMapView`][class1] whose keys are the `Vec<u8>` and the values are a serializable type `V`.
+//! * The [`MerkleMapView`][class2] whose keys are a serializable type `I` and the values a serializable type `V`.
+//! * The [`MerkleSetView`][class3] whose entries are a serializable type `I`.
+//! * The [`MerkleCollectionView`][class4] whose keys are a serializable type `I` and the values are views.
+//! * The [`MerkleKeyValueStoreView`][class5] whose keys and values are `Vec<u8>`, as a key-value store.
+//!
+//! The hashes of these views differ from the ones of [`ByteMapView`], [`MapView`][map],
+//! [`SetView`][set], [`CollectionView`][collection] and [`KeyValueStoreView`] on the same data.
+//!
+//! Since the root hash commits to every leaf, the maps and the key-value store can also
+//! produce a [`MerkleProof`] of the value of one key, made of the hashes of the siblings of
+//! the nodes on the path of that key.
+//!
+//! [class1]: merkle_map_view::MerkleByteMapView
+//! [class2]: merkle_map_view::MerkleMapView
+//! [class3]: merkle_map_view::MerkleSetView
+//! [class4]: merkle_map_view::MerkleCollectionView
+//! [class5]: merkle_map_view::MerkleKeyValueStoreView
+//! [map]: crate::map_view::MapView
+//! [set]: crate::set_view::SetView
+//! [collection]: crate::collection_view::CollectionView
+
+#[cfg(with_metrics)]
+use std::sync::LazyLock;
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
+    mem,
+};
+
+use async_trait::async_trait;
+use linera_base::{
+    crypto::CryptoHash,
+    state_proof::{MerkleProof, MerkleTerminal},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+#[cfg(with_metrics)]
+use {
+    linera_base::prometheus_util::{bucket_latencies, register_histogram_vec, MeasureLatency},
+    prometheus::HistogramVec,
+};
+
+use crate::{
+    batch::{Batch, WriteOperation},
+    collection_view::{ByteCollectionView, ReadGuardedView},
+    common::HasherOutput,
+    context::Context,
+    key_value_store_view::{KeyValueStoreView, SizeData},
+    map_view::ByteMapView,
+    store::KeyRange,
+    views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
+};
+
+#[cfg(with_metrics)]
+/// The runtime of hash computation
+static MERKLE_MAP_VIEW_HASH_RUNTIME: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec(
+        "merkle_map_view_hash_runtime",
+        "MerkleMapView hash runtime",
+        &[],
+        bucket_latencies(5.0),
+    )
+});
+
+/// Key tags to create the sub-keys of a Merkle view on top of the base key.
+#[repr(u8)]
+enum KeyTag {
+    /// Prefix for the entries of the view.
+    Inner = MIN_VIEW_TAG,
+    /// Prefix for the nodes of the Merkle tree.
+    Node,
+    /// Prefix for the keys whose leaves are outdated.
+    Pending,
+}
+
+/// A node of the Merkle tree. It is stored under the bit prefix of the key hashes that it
+/// covers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum MerkleNode {
+    /// A subtree containing a single entry.
+    Leaf {
+        key_hash: HasherOutput,
+        value_hash: HasherOutput,
+    },
+    /// A subtree containing several entries, with the hashes of its two children.
+    Internal { children: [Option<HasherOutput>; 2] },
+}
+
+impl MerkleNode {
+    fn hash(&self) -> Result<HasherOutput, ViewError> {
+        let mut hasher = sha3::Sha3_256::default();
+        match self {
+            MerkleNode::Leaf {
+                key_hash,
+                value_hash,
+            } => {
+                hasher.update_with_bytes(&[0])?;
+                hasher.update_with_bytes(key_hash)?;
+                hasher.update_with_bytes(value_hash)?;
+            }
+            MerkleNode::Internal { children } => {
+                hasher.update_with_bytes(&[1])?;
+                for child in children {
+                    match child {
+                        None => hasher.update_with_bytes(&[0])?,
+                        Some(hash) => {
+                            hasher.update_with_bytes(&[1])?;
+                            hasher.update_with_bytes(hash)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(hasher.finalize())
+    }
+}
+
+fn hash_bytes(bytes: &[u8]) -> Result<HasherOutput, ViewError> {
+    let mut hasher = sha3::Sha3_256::default();
+    hasher.update_with_bytes(bytes)?;
+    Ok(hasher.finalize())
+}
+
+/// Returns the bit of `key_hash` at the given depth of the tree.
+fn bit(key_hash: &[u8], depth: usize) -> usize {
+    ((key_hash[depth / 8] >> (7 - depth % 8)) & 1) as usize
+}
+
+/// Returns the depth of the first bit where two distinct key hashes differ.
+fn first_different_bit(key_hash: &[u8], other_hash: &[u8]) -> usize {
+    for (i, (byte, other_byte)) in key_hash.iter().zip(other_hash).enumerate() {
+        if byte != other_byte {
+            return 8 * i + (byte ^ other_byte).leading_zeros() as usize;
+        }
+    }
+    unreachable!("the key hashes are different");
+}
+
+/// Returns the key of the node at the given depth on the path of `key_hash`.
+fn node_key(key_hash: &[u8], depth: usize) -> Vec<u8> {
+    let mut key = (depth as u16).to_be_bytes().to_vec();
+    key.extend_from_slice(&key_hash[..depth.div_ceil(8)]);
+    if depth % 8 != 0 {
+        *key.last_mut().unwrap() &= 0xff << (8 - depth % 8);
+    }
+    key
+}
+
+/// Returns the key of the sibling of the node at the given positive depth on the path of
+/// `key_hash`.
+fn sibling_key(key_hash: &[u8], depth: usize) -> Vec<u8> {
+    let mut key = node_key(key_hash, depth);
+    key[2 + (depth - 1) / 8] ^= 0x80 >> ((depth - 1) % 8);
+    key
+}
+
+/// The nodes of a Merkle tree being updated, on top of the stored ones.
+struct NodeOverlay<'a, C> {
+    nodes: &'a ByteMapView<C, MerkleNode>,
+    updates: BTreeMap<Vec<u8>, Option<MerkleNode>>,
+}
+
+impl<'a, C> NodeOverlay<'a, C>
+where
+    C: Context + Sync,
+    ViewError: From<C::Error>,
+{
+    async fn get(&self, key: &[u8]) -> Result<Option<MerkleNode>, ViewError> {
+        match self.updates.get(key) {
+            Some(node) => Ok(node.clone()),
+            None => self.nodes.get(key).await,
+        }
+    }
+
+    fn set(&mut self, key: Vec<u8>, node: Option<MerkleNode>) {
+        self.updates.insert(key, node);
+    }
+
+    async fn root_hash(&self) -> Result<HasherOutput, ViewError> {
+        match self.get(&node_key(&[], 0)).await? {
+            None => hash_bytes(&[]),
+            Some(node) => node.hash(),
+        }
+    }
+
+    async fn insert(
+        &mut self,
+        key_hash: HasherOutput,
+        value_hash: HasherOutput,
+    ) -> Result<(), ViewError> {
+        let leaf = MerkleNode::Leaf {
+            key_hash,
+            value_hash,
+        };
+        let mut depth = 0;
+        loop {
+            let key = node_key(&key_hash, depth);
+            match self.get(&key).await? {
+                None => {
+                    self.set(key, Some(leaf));
+                    break;
+                }
+                Some(MerkleNode::Internal { .. }) => depth += 1,
+                Some(MerkleNode::Leaf {
+                    key_hash: other_hash,
+                    ..
+                }) if other_hash == key_hash => {
+                    self.set(key, Some(leaf));
+                    break;
+                }
+                Some(MerkleNode::Leaf {
+                    key_hash: other_hash,
+                    value_hash: other_value_hash,
+                }) => {
+                    let other_leaf = MerkleNode::Leaf {
+                        key_hash: other_hash,
+                        value_hash: other_value_hash,
+                    };
+                    // Both leaves share the bits up to `split`, so they are separated by a
+                    // chain of internal nodes whose children on our path are set by
+                    // `rehash_path`.
+                    let split = first_different_bit(&key_hash, &other_hash);
+                    for level in depth..split {
+                        let children = [None, None];
+                        self.set(
+                            node_key(&key_hash, level),
+                            Some(MerkleNode::Internal { children }),
+                        );
+                    }
+                    let mut children = [None, None];
+                    children[bit(&other_hash, split)] = Some(other_leaf.hash()?);
+                    self.set(
+                        node_key(&key_hash, split),
+                        Some(MerkleNode::Internal { children }),
+                    );
+                    self.set(node_key(&other_hash, split + 1), Some(other_leaf));
+                    self.set(node_key(&key_hash, split + 1), Some(leaf));
+                    depth = split + 1;
+                    break;
+                }
+            }
+        }
+        self.rehash_path(&key_hash, depth).await
+    }
+
+    async fn remove(&mut self, key_hash: HasherOutput) -> Result<(), ViewError> {
+        let mut depth = 0;
+        loop {
+            let key = node_key(&key_hash, depth);
+            match self.get(&key).await? {
+                None => return Ok(()),
+                Some(MerkleNode::Internal { .. }) => depth += 1,
+                Some(MerkleNode::Leaf {
+                    key_hash: other_hash,
+                    ..
+                }) => {
+                    if other_hash != key_hash {
+                        return Ok(());
+                    }
+                    self.set(key, None);
+                    break;
+                }
+            }
+        }
+        // A subtree left with a single entry is replaced by the leaf of that entry.
+        while depth > 0 {
+            let key = node_key(&key_hash, depth);
+            let sibling_key = sibling_key(&key_hash, depth);
+            let node = self.get(&key).await?;
+            let sibling = self.get(&sibling_key).await?;
+            let parent = match (node, sibling) {
+                (None, None) => None,
+                (None, Some(leaf @ MerkleNode::Leaf { .. })) => {
+                    self.set(sibling_key, None);
+                    Some(leaf)
+                }
+                (Some(leaf @ MerkleNode::Leaf { .. }), None) => {
+                    self.set(key, None);
+                    Some(leaf)
+                }
+                _ => break,
+            };
+            depth -= 1;
+            self.set(node_key(&key_hash, depth), parent);
+        }
+        self.rehash_path(&key_hash, depth).await
+    }
+
+    /// Updates the internal nodes above the given depth on the path of `key_hash`.
+    async fn rehash_path(&mut self, key_hash: &[u8], depth: usize) -> Result<(), ViewError> {
+        for level in (0..depth).rev() {
+            let key = node_key(key_hash, level);
+            let Some(MerkleNode::Internal { mut children }) = self.get(&key).await? else {
+                unreachable!("nodes above an entry are internal");
+            };
+            let child = self.get(&node_key(key_hash, level + 1)).await?;
+            children[bit(key_hash, level)] = child.map(|child| child.hash()).transpose()?;
+            self.set(key, Some(MerkleNode::Internal { children }));
+        }
+        Ok(())
+    }
+}
+
+/// The Merkle tree of a view, together with the keys whose leaves are outdated.
+#[derive(Debug)]
+struct MerkleTree<C> {
+    nodes: ByteMapView<C, MerkleNode>,
+    pending: ByteMapView<C, ()>,
+    touched: BTreeSet<Vec<u8>>,
+}
+
+impl<C> MerkleTree<C>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+{
+    fn post_load(context: &C) -> Result<Self, ViewError> {
+        let base_key = context.base_tag(KeyTag::Node as u8);
+        let nodes = ByteMapView::post_load(context.clone_with_base_key(base_key), &[])?;
+        let base_key = context.base_tag(KeyTag::Pending as u8);
+        let pending = ByteMapView::post_load(context.clone_with_base_key(base_key), &[])?;
+        Ok(Self {
+            nodes,
+            pending,
+            touched: BTreeSet::new(),
+        })
+    }
+
+    fn rollback(&mut self) {
+        self.nodes.rollback();
+        self.pending.rollback();
+        self.touched.clear();
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        !self.touched.is_empty()
+            || self.nodes.has_pending_changes().await
+            || self.pending.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        for short_key in mem::take(&mut self.touched) {
+            self.pending.insert(short_key, ());
+        }
+        let delete_nodes = self.nodes.flush(batch)?;
+        let delete_pending = self.pending.flush(batch)?;
+        Ok(delete_nodes && delete_pending)
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.pending.clear();
+        self.touched.clear();
+    }
+
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(MerkleTree {
+            nodes: self.nodes.clone_unchecked()?,
+            pending: self.pending.clone_unchecked()?,
+            touched: self.touched.clone(),
+        })
+    }
+
+    /// Marks the leaf of `short_key` as outdated.
+    fn touch(&mut self, short_key: Vec<u8>) {
+        self.touched.insert(short_key);
+    }
+
+    /// Returns the keys whose leaves are outdated.
+    async fn outdated_keys(&self) -> Result<Vec<Vec<u8>>, ViewError> {
+        let mut short_keys = self
+            .pending
+            .keys()
+            .await?
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        short_keys.extend(self.touched.iter().cloned());
+        Ok(short_keys.into_iter().collect())
+    }
+
+    /// Computes the root hash once the given leaves are updated. A missing value hash
+    /// removes the leaf. Also returns the modified nodes.
+    async fn update(
+        &self,
+        leaves: Vec<(Vec<u8>, Option<HasherOutput>)>,
+    ) -> Result<(HasherOutput, BTreeMap<Vec<u8>, Option<MerkleNode>>), ViewError> {
+        #[cfg(with_metrics)]
+        let _hash_latency = MERKLE_MAP_VIEW_HASH_RUNTIME.measure_latency();
+        let mut overlay = NodeOverlay {
+            nodes: &self.nodes,
+            updates: BTreeMap::new(),
+        };
+        for (short_key, value_hash) in leaves {
+            let key_hash = hash_bytes(&short_key)?;
+            match value_hash {
+                Some(value_hash) => overlay.insert(key_hash, value_hash).await?,
+                None => overlay.remove(key_hash).await?,
+            }
+        }
+        let hash = overlay.root_hash().await?;
+        Ok((hash, overlay.updates))
+    }
+
+    /// Returns the path of `short_key` in the tree once the given leaves are updated.
+    async fn prove(
+        &self,
+        leaves: Vec<(Vec<u8>, Option<HasherOutput>)>,
+        short_key: &[u8],
+    ) -> Result<MerkleProof, ViewError> {
+        let (_, updates) = self.update(leaves).await?;
+        let overlay = NodeOverlay {
+            nodes: &self.nodes,
+            updates,
+        };
+        let key_hash = hash_bytes(short_key)?;
+        let mut siblings = Vec::new();
+        let terminal = loop {
+            let depth = siblings.len();
+            match overlay.get(&node_key(&key_hash, depth)).await? {
+                None => break MerkleTerminal::Empty,
+                Some(MerkleNode::Leaf {
+                    key_hash,
+                    value_hash,
+                }) => {
+                    break MerkleTerminal::Leaf {
+                        key_hash: CryptoHash::from(key_hash),
+                        value_hash: CryptoHash::from(value_hash),
+                    }
+                }
+                Some(MerkleNode::Internal { children }) => {
+                    let sibling = children[1 - bit(&key_hash, depth)];
+                    siblings.push(sibling.map(CryptoHash::from));
+                }
+            }
+        };
+        Ok(MerkleProof { siblings, terminal })
+    }
+
+    /// Stages the modified nodes and marks all the leaves as up to date.
+    async fn commit(
+        &mut self,
+        updates: BTreeMap<Vec<u8>, Option<MerkleNode>>,
+    ) -> Result<(), ViewError> {
+        for (key, node) in updates {
+            match node {
+                Some(node) => self.nodes.insert(key, node),
+                None => self.nodes.remove(key),
+            }
+        }
+        for short_key in self.pending.keys().await? {
+            self.pending.remove(short_key);
+        }
+        self.touched.clear();
+        Ok(())
+    }
+}
+
+/// A map view indexed by `Vec<u8>` whose hash is maintained in a Merkle tree.
+#[derive(Debug)]
+pub struct MerkleByteMapView<C, V> {
+    map: ByteMapView<C, V>,
+    tree: MerkleTree<C>,
+}
+
+#[async_trait]
+impl<C, V> View<C> for MerkleByteMapView<C, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    V: Send + Sync + Serialize,
+{
+    const NUM_INIT_KEYS: usize = 0;
+
+    fn context(&self) -> &C {
+        self.map.context()
+    }
+
+    fn pre_load(_context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        Ok(Vec::new())
+    }
+
+    fn post_load(context: C, _values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let base_key = context.base_tag(KeyTag::Inner as u8);
+        let map = ByteMapView::post_load(context.clone_with_base_key(base_key), &[])?;
+        let tree = MerkleTree::post_load(&context)?;
+        Ok(Self { map, tree })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        Self::post_load(context, &[])
+    }
+
+    fn rollback(&mut self) {
+        self.map.rollback();
+        self.tree.rollback();
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        self.map.has_pending_changes().await || self.tree.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        let delete_map = self.map.flush(batch)?;
+        let delete_tree = self.tree.flush(batch)?;
+        Ok(delete_map && delete_tree)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.tree.clear();
+    }
+}
+
+impl<C, V> ClonableView<C> for MerkleByteMapView<C, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    V: Clone + Send + Sync + Serialize,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(MerkleByteMapView {
+            map: self.map.clone_unchecked()?,
+            tree: self.tree.clone_unchecked()?,
+        })
+    }
+}
+
+impl<C, V> MerkleByteMapView<C, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+{
+    /// Inserts or resets the value of a key of the map.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map = MerkleByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// assert_eq!(map.keys().await.unwrap(), vec![vec![0, 1]]);
+    /// # })
+    /// ```
+    pub fn insert(&mut self, short_key: Vec<u8>, value: V) {
+        self.tree.touch(short_key.clone());
+        self.map.insert(short_key, value);
+    }
+
+    /// Removes a value. If absent then nothing is done.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map = MerkleByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.remove(vec![0, 1]);
+    /// assert!(map.keys().await.unwrap().is_empty());
+    /// # })
+    /// ```
+    pub fn remove(&mut self, short_key: Vec<u8>) {
+        self.tree.touch(short_key.clone());
+        self.map.remove(short_key);
+    }
+
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.map.extra()
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub async fn contains_key(&self, short_key: &[u8]) -> Result<bool, ViewError> {
+        self.map.contains_key(short_key).await
+    }
+}
+
+impl<C, V> MerkleByteMapView<C, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    /// Removes all the keys starting with the given prefix. Unlike
+    /// [`ByteMapView::remove_by_prefix`], this has to read the removed keys in order to
+    /// update their leaves.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map = MerkleByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![0, 2], String::from("Bonjour"));
+    /// map.remove_by_prefix(vec![0]).await.unwrap();
+    /// assert!(map.keys().await.unwrap().is_empty());
+    /// # })
+    /// ```
+    pub async fn remove_by_prefix(&mut self, key_prefix: Vec<u8>) -> Result<(), ViewError> {
+        for short_key in self.map.keys_by_prefix(key_prefix.clone()).await? {
+            self.tree.touch(short_key);
+        }
+        self.map.remove_by_prefix(key_prefix);
+        Ok(())
+    }
+
+    /// Reads the value at the given position, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map = MerkleByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// assert_eq!(map.get(&[0, 1]).await.unwrap(), Some(String::from("Hello")));
+    /// # })
+    /// ```
+    pub async fn get(&self, short_key: &[u8]) -> Result<Option<V>, ViewError> {
+        self.map.get(short_key).await
+    }
+
+    /// Obtains a mutable reference to a value at a given position if available.
+    pub async fn get_mut(&mut self, short_key: &[u8]) -> Result<Option<&mut V>, ViewError> {
+        self.tree.touch(short_key.to_vec());
+        self.map.get_mut(short_key).await
+    }
+
+    /// Returns the list of keys of the map in lexicographic order.
+    pub async fn keys(&self) -> Result<Vec<Vec<u8>>, ViewError> {
+        self.map.keys().await
+    }
+
+    /// Returns the number of entries of the map.
+    pub async fn count(&self) -> Result<usize, ViewError> {
+        self.map.count().await
+    }
+
+    /// Returns the list of keys and values of the map in lexicographic order.
+    pub async fn key_values(&self) -> Result<Vec<(Vec<u8>, V)>, ViewError> {
+        self.map.key_values().await
+    }
+
+    /// Returns a proof of the value of `short_key`, to be checked against the hash of the
+    /// view. The value is hashed as its BCS serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleByteMapView;
+    /// # use linera_views::views::{HashableView, View};
+    /// # let context = create_test_memory_context();
+    /// let mut map = MerkleByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// let hash = map.hash().await.unwrap();
+    /// let proof = map.prove(&[0, 1]).await.unwrap();
+    /// let value = bcs::to_bytes("Hello").unwrap();
+    /// assert_eq!(proof.root_hash(&[0, 1], Some(&value)).unwrap(), <[u8; 32]>::from(hash));
+    /// # })
+    /// ```
+    pub async fn prove(&self, short_key: &[u8]) -> Result<MerkleProof, ViewError> {
+        let short_keys = self.tree.outdated_keys().await?;
+        let leaves = self.leaves(short_keys).await?;
+        self.tree.prove(leaves, short_key).await
+    }
+
+    async fn leaves(
+        &self,
+        short_keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<(Vec<u8>, Option<HasherOutput>)>, ViewError> {
+        let values = self.map.multi_get(short_keys.clone()).await?;
+        let mut leaves = Vec::with_capacity(short_keys.len());
+        for (short_key, value) in short_keys.into_iter().zip(values) {
+            let value_hash = match value {
+                None => None,
+                Some(value) => Some(hash_bytes(&bcs::to_bytes(&value)?)?),
+            };
+            leaves.push((short_key, value_hash));
+        }
+        Ok(leaves)
+    }
+}
+
+impl<C, V> MerkleByteMapView<C, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    V: Default + DeserializeOwned + 'static,
+{
+    /// Obtains a mutable reference to a value at a given position.
+    /// Default value if the index is missing.
+    pub async fn get_mut_or_default(&mut self, short_key: &[u8]) -> Result<&mut V, ViewError> {
+        self.tree.touch(short_key.to_vec());
+        self.map.get_mut_or_default(short_key).await
+    }
+}
+
+#[async_trait]
+impl<C, V> HashableView<C> for MerkleByteMapView<C, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let short_keys = self.tree.outdated_keys().await?;
+        let leaves = self.leaves(short_keys).await?;
+        let (hash, updates) = self.tree.update(leaves).await?;
+        self.tree.commit(updates).await?;
+        Ok(hash)
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let short_keys = self.tree.outdated_keys().await?;
+        let leaves = self.leaves(short_keys).await?;
+        let (hash, _) = self.tree.update(leaves).await?;
+        Ok(hash)
+    }
+}
+
+/// A map view with a type for keys, whose hash is maintained in a Merkle tree.
+#[derive(Debug)]
+pub struct MerkleMapView<C, I, V> {
+    map: MerkleByteMapView<C, V>,
+    _phantom: PhantomData<I>,
+}
+
+#[async_trait]
+impl<C, I, V> View<C> for MerkleMapView<C, I, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Send + Sync,
+    V: Send + Sync + Serialize,
+{
+    const NUM_INIT_KEYS: usize = MerkleByteMapView::<C, V>::NUM_INIT_KEYS;
+
+    fn context(&self) -> &C {
+        self.map.context()
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        MerkleByteMapView::<C, V>::pre_load(context)
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let map = MerkleByteMapView::post_load(context, values)?;
+        Ok(MerkleMapView {
+            map,
+            _phantom: PhantomData,
+        })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        Self::post_load(context, &[])
+    }
+
+    fn rollback(&mut self) {
+        self.map.rollback()
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        self.map.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        self.map.flush(batch)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear()
+    }
+}
+
+impl<C, I, V> ClonableView<C> for MerkleMapView<C, I, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Send + Sync,
+    V: Clone + Send + Sync + Serialize,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(MerkleMapView {
+            map: self.map.clone_unchecked()?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<C, I, V> MerkleMapView<C, I, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Serialize,
+{
+    /// Inserts or resets a value at an index.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: MerkleMapView<_, u32, _> = MerkleMapView::load(context).await.unwrap();
+    /// map.insert(&(24 as u32), String::from("Hello"));
+    /// assert_eq!(
+    ///     map.get(&(24 as u32)).await.unwrap(),
+    ///     Some(String::from("Hello"))
+    /// );
+    /// # })
+    /// ```
+    pub fn insert<Q>(&mut self, index: &Q, value: V) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.map.insert(short_key, value);
+        Ok(())
+    }
+
+    /// Removes a value. If absent then the operation does nothing.
+    pub fn remove<Q>(&mut self, index: &Q) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.map.remove(short_key);
+        Ok(())
+    }
+
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.map.extra()
+    }
+
+    /// Returns `true` if the map contains a value for the specified index.
+    pub async fn contains_key<Q>(&self, index: &Q) -> Result<bool, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.map.contains_key(&short_key).await
+    }
+}
+
+impl<C, I, V> MerkleMapView<C, I, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Serialize + DeserializeOwned,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    /// Reads the value at the given index, if any.
+    pub async fn get<Q>(&self, index: &Q) -> Result<Option<V>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.map.get(&short_key).await
+    }
+
+    /// Obtains a mutable reference to a value at a given index if available.
+    pub async fn get_mut<Q>(&mut self, index: &Q) -> Result<Option<&mut V>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.map.get_mut(&short_key).await
+    }
+
+    /// Returns the list of indices in the map. The order is determined by serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleMapView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut map: MerkleMapView<_, u32, String> = MerkleMapView::load(context).await.unwrap();
+    /// map.insert(&(37 as u32), String::from("Hello"));
+    /// assert_eq!(map.indices().await.unwrap(), vec![37 as u32]);
+    /// # })
+    /// ```
+    pub async fn indices(&self) -> Result<Vec<I>, ViewError> {
+        self.map
+            .keys()
+            .await?
+            .iter()
+            .map(|short_key| Ok(C::deserialize_value(short_key)?))
+            .collect()
+    }
+
+    /// Returns the list of indices and values in the map. The order is determined by
+    /// serialization.
+    pub async fn index_values(&self) -> Result<Vec<(I, V)>, ViewError> {
+        self.map
+            .key_values()
+            .await?
+            .into_iter()
+            .map(|(short_key, value)| Ok((C::deserialize_value(&short_key)?, value)))
+            .collect()
+    }
+
+    /// Returns the number of entries of the map.
+    pub async fn count(&self) -> Result<usize, ViewError> {
+        self.map.count().await
+    }
+
+    /// Returns a proof of the value at the given index, to be checked against the hash of
+    /// the view. The index is hashed as its serialization and the value as its BCS
+    /// serialization.
+    pub async fn prove<Q>(&self, index: &Q) -> Result<MerkleProof, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.map.prove(&short_key).await
+    }
+}
+
+impl<C, I, V> MerkleMapView<C, I, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Serialize,
+    V: Default + DeserializeOwned + 'static,
+{
+    /// Obtains a mutable reference to a value at a given index.
+    /// Default value if the index is missing.
+    pub async fn get_mut_or_default<Q>(&mut self, index: &Q) -> Result<&mut V, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.map.get_mut_or_default(&short_key).await
+    }
+}
+
+#[async_trait]
+impl<C, I, V> HashableView<C> for MerkleMapView<C, I, V>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Send + Sync,
+    V: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.map.hash_mut().await
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.map.hash().await
+    }
+}
+
+/// A set view with a type for entries, whose hash is maintained in a Merkle tree.
+#[derive(Debug)]
+pub struct MerkleSetView<C, I> {
+    set: MerkleByteMapView<C, ()>,
+    _phantom: PhantomData<I>,
+}
+
+#[async_trait]
+impl<C, I> View<C> for MerkleSetView<C, I>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Send + Sync,
+{
+    const NUM_INIT_KEYS: usize = MerkleByteMapView::<C, ()>::NUM_INIT_KEYS;
+
+    fn context(&self) -> &C {
+        self.set.context()
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        MerkleByteMapView::<C, ()>::pre_load(context)
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let set = MerkleByteMapView::post_load(context, values)?;
+        Ok(MerkleSetView {
+            set,
+            _phantom: PhantomData,
+        })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        Self::post_load(context, &[])
+    }
+
+    fn rollback(&mut self) {
+        self.set.rollback()
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        self.set.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        self.set.flush(batch)
+    }
+
+    fn clear(&mut self) {
+        self.set.clear()
+    }
+}
+
+impl<C, I> ClonableView<C> for MerkleSetView<C, I>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Send + Sync,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(MerkleSetView {
+            set: self.set.clone_unchecked()?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<C, I> MerkleSetView<C, I>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Serialize + DeserializeOwned,
+{
+    /// Inserts a value. If already present then no effect.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleSetView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut set: MerkleSetView<_, u32> = MerkleSetView::load(context).await.unwrap();
+    /// set.insert(&(34 as u32)).unwrap();
+    /// assert!(set.contains(&(34 as u32)).await.unwrap());
+    /// assert_eq!(set.indices().await.unwrap(), vec![34 as u32]);
+    /// # })
+    /// ```
+    pub fn insert<Q>(&mut self, index: &Q) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.set.insert(short_key, ());
+        Ok(())
+    }
+
+    /// Removes a value from the set. If absent then no effect.
+    pub fn remove<Q>(&mut self, index: &Q) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.set.remove(short_key);
+        Ok(())
+    }
+
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.set.extra()
+    }
+
+    /// Returns true if the given index exists in the set.
+    pub async fn contains<Q>(&self, index: &Q) -> Result<bool, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.set.contains_key(&short_key).await
+    }
+
+    /// Returns the list of indices in the set. The order is determined by serialization.
+    pub async fn indices(&self) -> Result<Vec<I>, ViewError> {
+        self.set
+            .keys()
+            .await?
+            .iter()
+            .map(|short_key| Ok(C::deserialize_value(short_key)?))
+            .collect()
+    }
+
+    /// Returns the number of entries in the set.
+    pub async fn count(&self) -> Result<usize, ViewError> {
+        self.set.count().await
+    }
+}
+
+#[async_trait]
+impl<C, I> HashableView<C> for MerkleSetView<C, I>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Send + Sync,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.set.hash_mut().await
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.set.hash().await
+    }
+}
+
+/// A collection view with a type for keys, whose hash is maintained in a Merkle tree. The
+/// leaf of an entry contains the hash of its subview.
+#[derive(Debug)]
+pub struct MerkleCollectionView<C, I, W> {
+    collection: ByteCollectionView<C, W>,
+    tree: MerkleTree<C>,
+    _phantom: PhantomData<I>,
+}
+
+#[async_trait]
+impl<C, I, W> View<C> for MerkleCollectionView<C, I, W>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Send + Sync,
+    W: View<C> + Send + Sync,
+{
+    const NUM_INIT_KEYS: usize = 0;
+
+    fn context(&self) -> &C {
+        self.collection.context()
+    }
+
+    fn pre_load(_context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        Ok(Vec::new())
+    }
+
+    fn post_load(context: C, _values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let base_key = context.base_tag(KeyTag::Inner as u8);
+        let collection = ByteCollectionView::post_load(context.clone_with_base_key(base_key), &[])?;
+        let tree = MerkleTree::post_load(&context)?;
+        Ok(Self {
+            collection,
+            tree,
+            _phantom: PhantomData,
+        })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        Self::post_load(context, &[])
+    }
+
+    fn rollback(&mut self) {
+        self.collection.rollback();
+        self.tree.rollback();
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        self.collection.has_pending_changes().await || self.tree.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        let delete_collection = self.collection.flush(batch)?;
+        let delete_tree = self.tree.flush(batch)?;
+        Ok(delete_collection && delete_tree)
+    }
+
+    fn clear(&mut self) {
+        self.collection.clear();
+        self.tree.clear();
+    }
+}
+
+impl<C, I, W> ClonableView<C> for MerkleCollectionView<C, I, W>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Send + Sync,
+    W: ClonableView<C> + Send + Sync,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(MerkleCollectionView {
+            collection: self.collection.clone_unchecked()?,
+            tree: self.tree.clone_unchecked()?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<C, I, W> MerkleCollectionView<C, I, W>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Serialize,
+    W: View<C> + Sync,
+{
+    /// Loads a subview for the data at the given index in the collection. If an entry
+    /// is absent then a default entry is added to the collection. The resulting view
+    /// can be modified.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleCollectionView;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view: MerkleCollectionView<_, u64, RegisterView<_, String>> =
+    ///     MerkleCollectionView::load(context).await.unwrap();
+    /// let subview = view.load_entry_mut(&23).await.unwrap();
+    /// let value = subview.get();
+    /// assert_eq!(*value, String::default());
+    /// # })
+    /// ```
+    pub async fn load_entry_mut<Q>(&mut self, index: &Q) -> Result<&mut W, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.tree.touch(short_key.clone());
+        self.collection.load_entry_mut(&short_key).await
+    }
+
+    /// Loads a subview for the data at the given index in the collection, if any. The
+    /// resulting view is read-only.
+    pub async fn try_load_entry<Q>(
+        &self,
+        index: &Q,
+    ) -> Result<Option<ReadGuardedView<W>>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.collection.try_load_entry(&short_key).await
+    }
+
+    /// Resets an entry to the default value.
+    pub fn reset_entry_to_default<Q>(&mut self, index: &Q) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.tree.touch(short_key.clone());
+        self.collection.reset_entry_to_default(&short_key)
+    }
+
+    /// Removes an entry from the collection. If absent, nothing happens.
+    pub fn remove_entry<Q>(&mut self, index: &Q) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.tree.touch(short_key.clone());
+        self.collection.remove_entry(short_key);
+        Ok(())
+    }
+
+    /// Returns `true` if the collection contains a subview at the given index.
+    pub async fn contains_key<Q>(&self, index: &Q) -> Result<bool, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = C::derive_short_key(index)?;
+        self.collection.contains_key(&short_key).await
+    }
+
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.collection.extra()
+    }
+
+    /// Returns the number of entries in the collection.
+    pub async fn count(&self) -> Result<usize, ViewError> {
+        self.collection.count().await
+    }
+}
+
+impl<C, I, W> MerkleCollectionView<C, I, W>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: DeserializeOwned,
+    W: View<C> + Sync,
+{
+    /// Returns the list of indices in the collection. The order is determined by
+    /// serialization.
+    pub async fn indices(&self) -> Result<Vec<I>, ViewError> {
+        self.collection
+            .keys()
+            .await?
+            .iter()
+            .map(|short_key| Ok(C::deserialize_value(short_key)?))
+            .collect()
+    }
+}
+
+impl<C, I, W> MerkleCollectionView<C, I, W>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    W: HashableView<C> + Send + Sync,
+{
+    async fn leaves(
+        &self,
+        short_keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<(Vec<u8>, Option<HasherOutput>)>, ViewError> {
+        let mut leaves = Vec::with_capacity(short_keys.len());
+        for short_key in short_keys {
+            let value_hash = match self.collection.try_load_entry(&short_key).await? {
+                None => None,
+                Some(view) => Some(hash_bytes(view.hash().await?.as_ref())?),
+            };
+            leaves.push((short_key, value_hash));
+        }
+        Ok(leaves)
+    }
+}
+
+#[async_trait]
+impl<C, I, W> HashableView<C> for MerkleCollectionView<C, I, W>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+    I: Send + Sync,
+    W: HashableView<C> + Send + Sync + 'static,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let short_keys = self.tree.outdated_keys().await?;
+        let leaves = self.leaves(short_keys).await?;
+        let (hash, updates) = self.tree.update(leaves).await?;
+        self.tree.commit(updates).await?;
+        Ok(hash)
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let short_keys = self.tree.outdated_keys().await?;
+        let leaves = self.leaves(short_keys).await?;
+        let (hash, _) = self.tree.update(leaves).await?;
+        Ok(hash)
+    }
+}
+
+/// A key-value store view whose hash is maintained in a Merkle tree. The leaf of each key
+/// commits to the hash of its raw value.
+#[derive(Debug)]
+pub struct MerkleKeyValueStoreView<C> {
+    store: KeyValueStoreView<C>,
+    tree: MerkleTree<C>,
+}
+
+#[async_trait]
+impl<C> View<C> for MerkleKeyValueStoreView<C>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+{
+    const NUM_INIT_KEYS: usize = KeyValueStoreView::<C>::NUM_INIT_KEYS;
+
+    fn context(&self) -> &C {
+        self.store.context()
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        let base_key = context.base_tag(KeyTag::Inner as u8);
+        KeyValueStoreView::<C>::pre_load(&context.clone_with_base_key(base_key))
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let base_key = context.base_tag(KeyTag::Inner as u8);
+        let store = KeyValueStoreView::post_load(context.clone_with_base_key(base_key), values)?;
+        let tree = MerkleTree::post_load(&context)?;
+        Ok(Self { store, tree })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        let keys = Self::pre_load(&context)?;
+        let values = context.read_multi_values_bytes(keys).await?;
+        Self::post_load(context, &values)
+    }
+
+    fn rollback(&mut self) {
+        self.store.rollback();
+        self.tree.rollback();
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        self.store.has_pending_changes().await || self.tree.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        let delete_store = self.store.flush(batch)?;
+        let delete_tree = self.tree.flush(batch)?;
+        Ok(delete_store && delete_tree)
+    }
+
+    fn clear(&mut self) {
+        self.store.clear();
+        self.tree.clear();
+    }
+}
+
+impl<C> ClonableView<C> for MerkleKeyValueStoreView<C>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(MerkleKeyValueStoreView {
+            store: self.store.clone_unchecked()?,
+            tree: self.tree.clone_unchecked()?,
+        })
+    }
+}
+
+impl<C> MerkleKeyValueStoreView<C>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+{
+    /// Getting the total sizes that will be used for keys and values when stored.
+    pub fn total_size(&self) -> SizeData {
+        self.store.total_size()
+    }
+
+    /// Returns the list of indices in lexicographic order.
+    pub async fn indices(&self) -> Result<Vec<Vec<u8>>, ViewError> {
+        self.store.indices().await
+    }
+
+    /// Returns the list of indices and values in lexicographic order.
+    pub async fn index_values(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+        self.store.index_values().await
+    }
+
+    /// Returns the number of entries.
+    pub async fn count(&self) -> Result<usize, ViewError> {
+        self.store.count().await
+    }
+
+    /// Obtains the value at the given index, if any.
+    pub async fn get(&self, index: &[u8]) -> Result<Option<Vec<u8>>, ViewError> {
+        self.store.get(index).await
+    }
+
+    /// Tests whether the store contains a specific index.
+    pub async fn contains_key(&self, index: &[u8]) -> Result<bool, ViewError> {
+        self.store.contains_key(index).await
+    }
+
+    /// Tests whether the view contains a range of indices.
+    pub async fn contains_keys(&self, indices: Vec<Vec<u8>>) -> Result<Vec<bool>, ViewError> {
+        self.store.contains_keys(indices).await
+    }
+
+    /// Obtains the values of a range of indices.
+    pub async fn multi_get(
+        &self,
+        indices: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, ViewError> {
+        self.store.multi_get(indices).await
+    }
+
+    /// Returns the keys matching the given prefix, without the prefix.
+    pub async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Vec<Vec<u8>>, ViewError> {
+        self.store.find_keys_by_prefix(key_prefix).await
+    }
+
+    /// Returns the key-value pairs whose keys match the given prefix, without the prefix.
+    pub async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+        self.store.find_key_values_by_prefix(key_prefix).await
+    }
+
+    /// Returns the keys in the given range.
+    pub async fn find_keys_in_range(&self, range: &KeyRange) -> Result<Vec<Vec<u8>>, ViewError> {
+        self.store.find_keys_in_range(range).await
+    }
+
+    /// Returns the key-value pairs whose keys are in the given range.
+    pub async fn find_key_values_in_range(
+        &self,
+        range: &KeyRange,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+        self.store.find_key_values_in_range(range).await
+    }
+
+    /// Applies the given batch of write operations. Unlike
+    /// [`KeyValueStoreView::write_batch`], deleting a key prefix has to read the removed
+    /// keys in order to update their leaves.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleKeyValueStoreView;
+    /// # use linera_views::batch::Batch;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut view = MerkleKeyValueStoreView::load(context).await.unwrap();
+    /// view.insert(vec![0, 1], vec![34]).await.unwrap();
+    /// let mut batch = Batch::new();
+    /// batch.delete_key_prefix(vec![0]);
+    /// view.write_batch(batch).await.unwrap();
+    /// assert!(view.indices().await.unwrap().is_empty());
+    /// # })
+    /// ```
+    pub async fn write_batch(&mut self, batch: Batch) -> Result<(), ViewError> {
+        for operation in &batch.operations {
+            match operation {
+                WriteOperation::Delete { key } | WriteOperation::Put { key, .. } => {
+                    self.tree.touch(key.clone());
+                }
+                WriteOperation::DeletePrefix { key_prefix } => {
+                    for suffix in self.store.find_keys_by_prefix(key_prefix).await? {
+                        let mut key = key_prefix.clone();
+                        key.extend(suffix);
+                        self.tree.touch(key);
+                    }
+                }
+            }
+        }
+        self.store.write_batch(batch).await
+    }
+
+    /// Sets or inserts a value.
+    pub async fn insert(&mut self, index: Vec<u8>, value: Vec<u8>) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(index, value);
+        self.write_batch(batch).await
+    }
+
+    /// Removes a value. If absent then the action has no effect.
+    pub async fn remove(&mut self, index: Vec<u8>) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        batch.delete_key(index);
+        self.write_batch(batch).await
+    }
+
+    /// Deletes the keys starting with the given prefix.
+    pub async fn remove_by_prefix(&mut self, key_prefix: Vec<u8>) -> Result<(), ViewError> {
+        let mut batch = Batch::new();
+        batch.delete_key_prefix(key_prefix);
+        self.write_batch(batch).await
+    }
+
+    /// Returns a proof of the value of `index`, to be checked against the hash of the
+    /// view.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::merkle_map_view::MerkleKeyValueStoreView;
+    /// # use linera_views::views::{HashableView, View};
+    /// # let context = create_test_memory_context();
+    /// let mut view = MerkleKeyValueStoreView::load(context).await.unwrap();
+    /// view.insert(vec![0, 1], vec![34]).await.unwrap();
+    /// let hash = view.hash().await.unwrap();
+    /// let proof = view.prove(&[0, 1]).await.unwrap();
+    /// assert_eq!(proof.root_hash(&[0, 1], Some(&[34][..])).unwrap(), <[u8; 32]>::from(hash));
+    /// let proof = view.prove(&[0, 2]).await.unwrap();
+    /// assert_eq!(proof.root_hash(&[0, 2], None).unwrap(), <[u8; 32]>::from(hash));
+    /// # })
+    /// ```
+    pub async fn prove(&self, index: &[u8]) -> Result<MerkleProof, ViewError> {
+        let short_keys = self.tree.outdated_keys().await?;
+        let leaves = self.leaves(short_keys).await?;
+        self.tree.prove(leaves, index).await
+    }
+
+    async fn leaves(
+        &self,
+        short_keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<(Vec<u8>, Option<HasherOutput>)>, ViewError> {
+        let values = self.store.multi_get(short_keys.clone()).await?;
+        let mut leaves = Vec::with_capacity(short_keys.len());
+        for (short_key, value) in short_keys.into_iter().zip(values) {
+            let value_hash = value.map(|value| hash_bytes(&value)).transpose()?;
+            leaves.push((short_key, value_hash));
+        }
+        Ok(leaves)
+    }
+}
+
+#[async_trait]
+impl<C> HashableView<C> for MerkleKeyValueStoreView<C>
+where
+    C: Context + Send + Sync,
+    ViewError: From<C::Error>,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let short_keys = self.tree.outdated_keys().await?;
+        let leaves = self.leaves(short_keys).await?;
+        let (hash, updates) = self.tree.update(leaves).await?;
+        self.tree.commit(updates).await?;
+        Ok(hash)
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        let short_keys = self.tree.outdated_keys().await?;
+        let leaves = self.leaves(short_keys).await?;
+        let (hash, _) = self.tree.update(leaves).await?;
+        Ok(hash)
+    }
+}
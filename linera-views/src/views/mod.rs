@@ -4,7 +4,11 @@
 use std::{fmt::Debug, io::Write};
 
 use async_trait::async_trait;
-use linera_base::{crypto::CryptoHash, data_types::ArithmeticError, identifiers::BlobId};
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::ArithmeticError,
+    identifiers::{BlobId, EventId},
+};
 pub use linera_views_derive::{
     ClonableView, CryptoHashRootView, CryptoHashView, HashableView, RootView, View,
 };
@@ -47,6 +51,9 @@ pub mod key_value_store_view;
 /// Wrapping a view to compute a hash.
 pub mod hashable_wrapper;
 
+/// The `MerkleMapView` implements maps, sets and collections whose hash is maintained incrementally.
+pub mod merkle_map_view;
+
 /// The minimum value for the view tags. Values in 0..MIN_VIEW_TAG are used for other purposes.
 pub const MIN_VIEW_TAG: u8 = 1;
 
@@ -158,6 +165,53 @@ pub enum ViewError {
     /// Some blobs were not found.
     #[error("Blobs not found: {0:?}")]
     BlobsNotFound(Vec<BlobId>),
+
+    /// Some events were not found.
+    #[error("Events not found: {0:?}")]
+    EventsNotFound(Vec<EventId>),
+
+    /// The file being imported is not a valid export of a key-value store.
+    #[error("Invalid export file: {0}")]
+    InvalidExport(String),
+
+    /// The data in storage was written by a newer version of the code.
+    #[error(
+        "The storage has schema version {found} but this code only supports versions up to \
+         {expected}; please upgrade"
+    )]
+    SchemaVersionMismatch {
+        /// The schema version recorded in storage.
+        found: u32,
+        /// The schema version of this code.
+        expected: u32,
+    },
+
+    /// The data in storage was written by an older version of the code and must be upgraded
+    /// before use.
+    #[error(
+        "The storage has schema version {found} but this code expects version {expected}; \
+         please run `linera storage migrate`"
+    )]
+    SchemaUpgradeRequired {
+        /// The schema version recorded in storage.
+        found: u32,
+        /// The schema version of this code.
+        expected: u32,
+    },
+
+    /// The data in storage was written with a layout that can't be upgraded to the current one.
+    #[error(
+        "The storage has schema version {found} and can't be upgraded past version {version}: \
+         {reason}. The storage must be initialized again"
+    )]
+    UnsupportedSchemaUpgrade {
+        /// The schema version recorded in storage.
+        found: u32,
+        /// The first schema version that the data can't be upgraded to.
+        version: u32,
+        /// Why the data can't be upgraded.
+        reason: String,
+    },
 }
 
 impl ViewError {
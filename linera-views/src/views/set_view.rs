@@ -3,7 +3,7 @@
 
 #[cfg(with_metrics)]
 use std::sync::LazyLock;
-use std::{borrow::Borrow, collections::BTreeMap, marker::PhantomData, mem};
+use std::{borrow::Borrow, collections::BTreeMap, marker::PhantomData, mem, ops::RangeBounds};
 
 use async_trait::async_trait;
 use serde::{de::DeserializeOwned, Serialize};
@@ -15,10 +15,12 @@ use {
 
 use crate::{
     batch::Batch,
-    common::{CustomSerialize, HasherOutput, Update},
+    common::{
+        get_key_range, get_storage_range, merge_range, CustomSerialize, HasherOutput, Update,
+    },
     context::Context,
     hashable_wrapper::WrappedHashableContainerView,
-    store::KeyIterable,
+    store::{KeyIterable, KeyRange},
     views::{ClonableView, HashableView, Hasher, View, ViewError},
 };
 
@@ -254,6 +256,45 @@ where
         Ok(count)
     }
 
+    /// Returns the keys of the set in the given `range`, in the order and the number
+    /// requested by the range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::create_test_memory_context, set_view::ByteSetView};
+    /// # use linera_views::store::KeyRange;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.insert(vec![0, 1]);
+    /// set.insert(vec![0, 2]);
+    /// set.insert(vec![3]);
+    /// let range = KeyRange::new(vec![0, 2], None).reversed();
+    /// assert_eq!(
+    ///     set.keys_in_range(&range).await.unwrap(),
+    ///     vec![vec![3], vec![0, 2]]
+    /// );
+    /// # })
+    /// ```
+    pub async fn keys_in_range(&self, range: &KeyRange) -> Result<Vec<Vec<u8>>, ViewError> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut stored = Vec::new();
+        if !self.delete_storage_first {
+            let base = self.context.base_key();
+            let storage_range = get_storage_range(range, &self.updates, false);
+            for key in self
+                .context
+                .find_keys_in_range(&base, &storage_range)
+                .await?
+            {
+                stored.push((key, ()));
+            }
+        }
+        let keys = merge_range(range, stored, |_| false, &self.updates, |_| ());
+        Ok(keys.into_iter().map(|(key, ())| key).collect())
+    }
+
     /// Applies a function f on each index (aka key). Keys are visited in a
     /// lexicographic order. If the function returns false, then the loop ends
     /// prematurely.
@@ -863,6 +904,37 @@ where
         self.set.count().await
     }
 
+    /// Returns the indices of the set in the given `range`, in the order determined by
+    /// the custom serialization or in the reverse order, and at most `limit` of them.
+    /// The custom serialization has to preserve the order of the indices.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::create_test_memory_context;
+    /// # use linera_views::set_view::CustomSetView;
+    /// # use linera_views::views::View;
+    /// # let context = create_test_memory_context();
+    /// let mut set = CustomSetView::<_, u128>::load(context).await.unwrap();
+    /// set.insert(&34u128);
+    /// set.insert(&300u128);
+    /// set.insert(&3000u128);
+    /// let indices = set.indices_in_range(.., true, Some(2)).await.unwrap();
+    /// assert_eq!(indices, vec![3000, 300]);
+    /// # })
+    /// ```
+    pub async fn indices_in_range<R>(
+        &self,
+        range: R,
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, ViewError>
+    where
+        R: RangeBounds<I>,
+    {
+        let range = get_key_range(range, reverse, limit, I::to_custom_bytes)?;
+        let keys = self.set.keys_in_range(&range).await?;
+        keys.iter().map(|key| I::from_custom_bytes(key)).collect()
+    }
+
     /// Applies a function f on each index. Indices are visited in an order
     /// determined by the custom serialization. If the function does return
     /// false, then the loop prematurely ends.
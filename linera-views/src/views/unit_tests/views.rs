@@ -9,11 +9,13 @@ use test_case::test_case;
 
 #[cfg(with_dynamodb)]
 use crate::dynamo_db::DynamoDbStore;
+#[cfg(with_redb)]
+use crate::redb_db::RedbStore;
 #[cfg(with_rocksdb)]
 use crate::rocks_db::RocksDbStore;
 #[cfg(with_scylladb)]
 use crate::scylla_db::ScyllaDbStore;
-#[cfg(any(with_scylladb, with_dynamodb, with_rocksdb))]
+#[cfg(any(with_scylladb, with_dynamodb, with_rocksdb, with_redb))]
 use crate::store::TestKeyValueStore;
 use crate::{
     batch::Batch,
@@ -26,7 +28,7 @@ use crate::{
     },
     views::{HashableView, View, ViewError},
 };
-#[cfg(any(with_rocksdb, with_scylladb, with_dynamodb))]
+#[cfg(any(with_rocksdb, with_redb, with_scylladb, with_dynamodb))]
 use crate::{context::ViewContext, random::generate_test_namespace, store::AdminKeyValueStore};
 
 #[tokio::test]
@@ -40,6 +42,12 @@ async fn test_queue_operations_with_rocks_db_context() -> Result<(), anyhow::Err
     run_test_queue_operations_test_cases(RocksDbContextFactory).await
 }
 
+#[cfg(with_redb)]
+#[tokio::test]
+async fn test_queue_operations_with_redb_context() -> Result<(), anyhow::Error> {
+    run_test_queue_operations_test_cases(RedbContextFactory).await
+}
+
 #[cfg(with_dynamodb)]
 #[tokio::test]
 async fn test_queue_operations_with_dynamo_db_context() -> Result<(), anyhow::Error> {
@@ -223,6 +231,25 @@ impl TestContextFactory for RocksDbContextFactory {
     }
 }
 
+#[cfg(with_redb)]
+struct RedbContextFactory;
+
+#[cfg(with_redb)]
+#[async_trait]
+impl TestContextFactory for RedbContextFactory {
+    type Context = ViewContext<(), RedbStore>;
+
+    async fn new_context(&mut self) -> Result<Self::Context, anyhow::Error> {
+        let config = RedbStore::new_test_config().await?;
+        let namespace = generate_test_namespace();
+        let root_key = &[];
+        let store = RedbStore::recreate_and_connect(&config, &namespace, root_key).await?;
+        let context = ViewContext::create_root_context(store, ()).await?;
+
+        Ok(context)
+    }
+}
+
 #[cfg(with_dynamodb)]
 struct DynamoDbContextFactory;
 
@@ -3,11 +3,16 @@
 
 #[cfg(with_dynamodb)]
 use linera_views::dynamo_db::DynamoDbStore;
+#[cfg(with_redb)]
+use linera_views::redb_db::RedbStore;
 #[cfg(with_rocksdb)]
 use linera_views::rocks_db::RocksDbStore;
 #[cfg(with_scylladb)]
 use linera_views::scylla_db::ScyllaDbStore;
-use linera_views::{memory::MemoryStore, test_utils::admin_test};
+use linera_views::{
+    memory::MemoryStore,
+    test_utils::{admin_test, export_import_test, snapshot_test},
+};
 
 #[tokio::test]
 async fn admin_test_memory() {
@@ -20,6 +25,41 @@ async fn admin_test_rocks_db() {
     admin_test::<RocksDbStore>().await;
 }
 
+#[tokio::test]
+async fn snapshot_test_memory() {
+    snapshot_test::<MemoryStore>().await;
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn snapshot_test_rocks_db() {
+    snapshot_test::<RocksDbStore>().await;
+}
+
+#[tokio::test]
+async fn export_import_test_memory() {
+    export_import_test::<MemoryStore, MemoryStore>().await;
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn export_import_test_rocks_db() {
+    export_import_test::<RocksDbStore, MemoryStore>().await;
+    export_import_test::<MemoryStore, RocksDbStore>().await;
+}
+
+#[cfg(with_redb)]
+#[tokio::test]
+async fn admin_test_redb() {
+    admin_test::<RedbStore>().await;
+}
+
+#[cfg(with_redb)]
+#[tokio::test]
+async fn snapshot_test_redb() {
+    snapshot_test::<RedbStore>().await;
+}
+
 #[cfg(with_dynamodb)]
 #[tokio::test]
 async fn admin_test_dynamo_db() {
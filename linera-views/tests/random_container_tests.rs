@@ -4,17 +4,23 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::Result;
+use linera_base::{crypto::CryptoHash, state_proof::collection_hash};
 use linera_views::{
     bucket_queue_view::HashedBucketQueueView,
     collection_view::HashedCollectionView,
     context::{create_test_memory_context, Context},
     key_value_store_view::{KeyValueStoreView, SizeData},
     map_view::HashedByteMapView,
+    merkle_map_view::{
+        MerkleByteMapView, MerkleCollectionView, MerkleKeyValueStoreView, MerkleMapView,
+    },
     queue_view::HashedQueueView,
     random::make_deterministic_rng,
     reentrant_collection_view::HashedReentrantCollectionView,
     register_view::RegisterView,
-    views::{CryptoHashRootView, CryptoHashView, RootView, View, ViewError},
+    set_view::HashedByteSetView,
+    store::KeyRange,
+    views::{CryptoHashRootView, CryptoHashView, HashableView, RootView, View, ViewError},
 };
 use rand::{distributions::Uniform, Rng, RngCore};
 
@@ -403,6 +409,392 @@ async fn map_view_mutability() -> Result<()> {
     Ok(())
 }
 
+#[derive(CryptoHashRootView)]
+pub struct MerkleByteMapStateView<C> {
+    pub map: MerkleByteMapView<C, u8>,
+}
+
+/// Computes the hash of a `MerkleByteMapView` built in one go from the given entries.
+async fn merkle_byte_map_hash(map: &BTreeMap<Vec<u8>, u8>) -> Result<CryptoHash> {
+    let context = create_test_memory_context();
+    let mut view = MerkleByteMapStateView::load(context).await?;
+    for (key, value) in map {
+        view.map.insert(key.clone(), *value);
+    }
+    Ok(view.crypto_hash().await?)
+}
+
+async fn run_merkle_map_view_mutability<R: RngCore + Clone>(rng: &mut R) -> Result<()> {
+    let context = create_test_memory_context();
+    let mut state_map = BTreeMap::new();
+    let n = 10;
+    for _ in 0..n {
+        let mut view = MerkleByteMapStateView::load(context.clone()).await?;
+        let save = rng.gen::<bool>();
+        assert_eq!(
+            view.crypto_hash().await?,
+            merkle_byte_map_hash(&state_map).await?
+        );
+        let count_oper = rng.gen_range(0..25);
+        let mut new_state_map = state_map.clone();
+        for _ in 0..count_oper {
+            let choice = rng.gen_range(0..6);
+            if choice == 0 {
+                // inserting random stuff
+                let n_ins = rng.gen_range(0..10);
+                for _ in 0..n_ins {
+                    let len = rng.gen_range(1..4);
+                    let key = rng
+                        .clone()
+                        .sample_iter(Uniform::from(0..4))
+                        .take(len)
+                        .collect::<Vec<_>>();
+                    let value = rng.gen::<u8>();
+                    view.map.insert(key.clone(), value);
+                    new_state_map.insert(key, value);
+                }
+            }
+            if choice == 1 && !new_state_map.is_empty() {
+                // deleting an existing entry
+                let pos = rng.gen_range(0..new_state_map.len());
+                let key = new_state_map.keys().nth(pos).unwrap().clone();
+                view.map.remove(key.clone());
+                new_state_map.remove(&key);
+            }
+            if choice == 2 {
+                // deleting a prefix
+                let key_prefix = vec![rng.gen_range(0..4)];
+                view.map.remove_by_prefix(key_prefix.clone()).await?;
+                remove_by_prefix(&mut new_state_map, key_prefix);
+            }
+            if choice == 3 {
+                // Doing the clearing
+                view.clear();
+                new_state_map.clear();
+            }
+            if choice == 4 {
+                // Doing the rollback
+                view.rollback();
+                assert!(!view.has_pending_changes().await);
+                new_state_map = state_map.clone();
+            }
+            if choice == 5 {
+                let key = vec![rng.gen_range(0..4)];
+                let value = view.map.get_mut_or_default(&key).await?;
+                *value = value.wrapping_add(1);
+                let entry = new_state_map.entry(key).or_default();
+                *entry = entry.wrapping_add(1);
+            }
+            if rng.gen::<bool>() {
+                // Folding the modified entries into the Merkle tree
+                assert_eq!(
+                    view.crypto_hash_mut().await?,
+                    merkle_byte_map_hash(&new_state_map).await?
+                );
+            }
+            let key_values = view.map.key_values().await?;
+            let new_state_vec = new_state_map.clone().into_iter().collect::<Vec<_>>();
+            assert_eq!(key_values, new_state_vec);
+        }
+        // The hash only depends on the entries, not on the history of the view.
+        assert_eq!(
+            view.crypto_hash().await?,
+            merkle_byte_map_hash(&new_state_map).await?
+        );
+        if save {
+            // Some modified entries may not be folded into the Merkle tree yet.
+            state_map = new_state_map;
+            view.save().await?;
+            assert!(!view.has_pending_changes().await);
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn merkle_map_view_mutability() -> Result<()> {
+    let mut rng = make_deterministic_rng();
+    for _ in 0..5 {
+        run_merkle_map_view_mutability(&mut rng).await?;
+    }
+    Ok(())
+}
+
+#[derive(CryptoHashRootView)]
+pub struct RangeStateView<C> {
+    pub map: HashedByteMapView<C, u8>,
+    pub set: HashedByteSetView<C>,
+    pub store: KeyValueStoreView<C>,
+}
+
+fn random_key<R: RngCore>(rng: &mut R, min_len: usize) -> Vec<u8> {
+    let len = rng.gen_range(min_len..3);
+    (0..len).map(|_| rng.gen_range(0..4)).collect()
+}
+
+/// Checks the range queries of the views against a model, while some entries and prefixes
+/// are modified or removed without being saved, so that the stored entries are merged with
+/// the pending updates.
+#[tokio::test]
+async fn views_range_queries() -> Result<()> {
+    let mut rng = make_deterministic_rng();
+    let context = create_test_memory_context();
+    let mut view = RangeStateView::load(context.clone()).await?;
+    let mut saved_map = BTreeMap::<Vec<u8>, u8>::new();
+    let mut map = saved_map.clone();
+    for _ in 0..200 {
+        match rng.gen_range(0..10) {
+            0 => {
+                let key_prefix = random_key(&mut rng, 0);
+                view.map.remove_by_prefix(key_prefix.clone());
+                for key in map.keys().filter(|key| key.starts_with(&key_prefix)) {
+                    view.set.remove(key.clone());
+                }
+                view.store.remove_by_prefix(key_prefix.clone()).await?;
+                remove_by_prefix(&mut map, key_prefix);
+            }
+            1..=2 => {
+                let key = random_key(&mut rng, 1);
+                view.map.remove(key.clone());
+                view.set.remove(key.clone());
+                view.store.remove(key.clone()).await?;
+                map.remove(&key);
+            }
+            3 => {
+                view.save().await?;
+                saved_map = map.clone();
+            }
+            4 => {
+                view.rollback();
+                map = saved_map.clone();
+            }
+            _ => {
+                let key = random_key(&mut rng, 1);
+                let value = rng.gen::<u8>();
+                view.map.insert(key.clone(), value);
+                view.set.insert(key.clone());
+                view.store.insert(key.clone(), vec![value]).await?;
+                map.insert(key, value);
+            }
+        }
+        for _ in 0..5 {
+            let mut range = KeyRange::new(random_key(&mut rng, 0), None);
+            if rng.gen::<bool>() {
+                range.end = Some(random_key(&mut rng, 0));
+            }
+            range.reverse = rng.gen::<bool>();
+            if rng.gen::<bool>() {
+                range.limit = Some(rng.gen_range(0..4));
+            }
+            let key_values = range.select(
+                map.iter()
+                    .filter(|(key, _)| range.contains(key))
+                    .map(|(key, value)| (key.clone(), *value))
+                    .collect(),
+            );
+            let keys = key_values
+                .iter()
+                .map(|(key, _)| key.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(view.map.keys_in_range(&range).await?, keys);
+            assert_eq!(view.map.key_values_in_range(&range).await?, key_values);
+            assert_eq!(view.set.keys_in_range(&range).await?, keys);
+            assert_eq!(view.store.find_keys_in_range(&range).await?, keys);
+            let store_key_values = key_values
+                .iter()
+                .map(|(key, value)| (key.clone(), vec![*value]))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                view.store.find_key_values_in_range(&range).await?,
+                store_key_values
+            );
+        }
+    }
+    Ok(())
+}
+
+#[derive(CryptoHashRootView)]
+struct MerkleCollectionStateView<C> {
+    pub v: MerkleCollectionView<C, u8, RegisterView<C, u32>>,
+}
+
+#[tokio::test]
+async fn merkle_collection_view_history_independence() -> Result<()> {
+    let mut rng = make_deterministic_rng();
+    let context = create_test_memory_context();
+    let mut view = MerkleCollectionStateView::load(context).await?;
+    let mut map = BTreeMap::new();
+    for _ in 0..100 {
+        let index = rng.gen_range(0..20);
+        if rng.gen_range(0..4) == 0 {
+            view.v.remove_entry(&index)?;
+            map.remove(&index);
+        } else {
+            let value = rng.gen::<u32>();
+            view.v.load_entry_mut(&index).await?.set(value);
+            map.insert(index, value);
+        }
+        if rng.gen::<bool>() {
+            view.save().await?;
+        }
+        if rng.gen::<bool>() {
+            view.crypto_hash_mut().await?;
+        }
+    }
+    let mut new_view = MerkleCollectionStateView::load(create_test_memory_context()).await?;
+    for (index, value) in &map {
+        new_view.v.load_entry_mut(index).await?.set(*value);
+    }
+    assert_eq!(view.v.indices().await?, new_view.v.indices().await?);
+    assert_eq!(view.crypto_hash().await?, new_view.crypto_hash().await?);
+    Ok(())
+}
+
+#[derive(CryptoHashRootView)]
+struct MerkleKeyValueStoreStateView<C> {
+    pub store: MerkleKeyValueStoreView<C>,
+}
+
+/// Checks the proofs of present and absent keys against the root hash of the store, both
+/// before and after the modified entries are folded into the Merkle tree.
+#[tokio::test]
+async fn merkle_key_value_store_view_proofs() -> Result<()> {
+    let mut rng = make_deterministic_rng();
+    let context = create_test_memory_context();
+    let mut map = BTreeMap::new();
+    for _ in 0..20 {
+        let mut view = MerkleKeyValueStoreStateView::load(context.clone()).await?;
+        for _ in 0..rng.gen_range(0..10) {
+            let key = vec![rng.gen_range(0..8), rng.gen_range(0..8)];
+            if rng.gen_range(0..4) == 0 {
+                view.store.remove_by_prefix(key[..1].to_vec()).await?;
+                remove_by_prefix(&mut map, key[..1].to_vec());
+            } else {
+                let value = vec![rng.gen::<u8>(); rng.gen_range(0..3)];
+                view.store.insert(key.clone(), value.clone()).await?;
+                map.insert(key, value);
+            }
+        }
+        if rng.gen::<bool>() {
+            view.store.hash_mut().await?;
+        }
+        let root_hash = <[u8; 32]>::from(view.store.hash().await?);
+        for key in [vec![rng.gen_range(0..8), rng.gen_range(0..8)], vec![9]] {
+            let proof = view.store.prove(&key).await?;
+            let value = map.get(&key).map(Vec::as_slice);
+            assert_eq!(proof.root_hash(&key, value)?, root_hash);
+            // A proof can't be used to claim a different value.
+            assert_ne!(
+                proof.root_hash(&key, Some(&b"other value"[..])).ok(),
+                Some(root_hash)
+            );
+            if value.is_some() {
+                assert_ne!(proof.root_hash(&key, None).ok(), Some(root_hash));
+            }
+        }
+        if rng.gen::<bool>() {
+            view.save().await?;
+        } else {
+            view.rollback();
+            map = MerkleKeyValueStoreStateView::load(context.clone())
+                .await?
+                .store
+                .index_values()
+                .await?
+                .into_iter()
+                .collect();
+        }
+    }
+    Ok(())
+}
+
+#[derive(CryptoHashRootView)]
+struct StateProofView<C> {
+    pub byte_map: MerkleByteMapView<C, u32>,
+    pub map: MerkleMapView<C, u16, String>,
+    pub collection: HashedCollectionView<C, u8, MerkleKeyValueStoreView<C>>,
+    pub reentrant_collection: HashedReentrantCollectionView<C, u64, MerkleKeyValueStoreView<C>>,
+}
+
+/// Checks that the hashes computed by the verifiers of `linera_base::state_proof` match
+/// the ones of the hashed views that they re-implement.
+#[tokio::test]
+async fn state_proof_hashes_match_the_views() -> Result<()> {
+    let mut rng = make_deterministic_rng();
+    for _ in 0..10 {
+        let mut view = StateProofView::load(create_test_memory_context()).await?;
+        for _ in 0..rng.gen_range(0..20) {
+            let key = rng.gen_range(0..8u8);
+            let value = rng.gen::<u32>();
+            view.byte_map.insert(vec![key], value);
+            view.map.insert(&u16::from(key), value.to_string())?;
+            view.collection
+                .load_entry_mut(&key)
+                .await?
+                .insert(vec![key], value.to_le_bytes().to_vec())
+                .await?;
+            view.reentrant_collection
+                .try_load_entry_mut(&u64::from(key))
+                .await?
+                .insert(vec![key], value.to_le_bytes().to_vec())
+                .await?;
+        }
+        if rng.gen::<bool>() {
+            view.save().await?;
+        }
+
+        let hash = <[u8; 32]>::from(view.byte_map.hash().await?);
+        for key in 0..9u8 {
+            let value = view
+                .byte_map
+                .get(&[key])
+                .await?
+                .map(|value| bcs::to_bytes(&value))
+                .transpose()?;
+            let proof = view.byte_map.prove(&[key]).await?;
+            assert_eq!(proof.root_hash(&[key], value.as_deref())?, hash);
+        }
+        let hash = <[u8; 32]>::from(view.map.hash().await?);
+        for index in 0..9u16 {
+            let value = view
+                .map
+                .get(&index)
+                .await?
+                .map(|value| bcs::to_bytes(&value))
+                .transpose()?;
+            let proof = view.map.prove(&index).await?;
+            assert_eq!(
+                proof.root_hash(&bcs::to_bytes(&index)?, value.as_deref())?,
+                hash
+            );
+        }
+
+        let mut entries = Vec::new();
+        for index in view.collection.indices().await? {
+            let entry = view.collection.try_load_entry(&index).await?.unwrap();
+            entries.push((bcs::to_bytes(&index)?, entry.hash().await?.into()));
+        }
+        assert_eq!(
+            collection_hash(entries),
+            <[u8; 32]>::from(view.collection.hash().await?)
+        );
+        let mut entries = Vec::new();
+        for index in view.reentrant_collection.indices().await? {
+            let entry = view
+                .reentrant_collection
+                .try_load_entry(&index)
+                .await?
+                .unwrap();
+            entries.push((bcs::to_bytes(&index)?, entry.hash().await?.into()));
+        }
+        assert_eq!(
+            collection_hash(entries),
+            <[u8; 32]>::from(view.reentrant_collection.hash().await?)
+        );
+    }
+    Ok(())
+}
+
 #[derive(CryptoHashRootView)]
 pub struct BucketQueueStateView<C> {
     pub queue: HashedBucketQueueView<C, u8, 5>,
@@ -1,6 +1,10 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(not(target_arch = "wasm32"))]
+use linera_views::compression::CompressionStore;
+#[cfg(with_encryption)]
+use linera_views::encryption::EncryptionStore;
 use linera_views::{
     batch::Batch,
     context::{create_test_memory_context, Context as _},
@@ -61,6 +65,28 @@ async fn test_reads_memory() {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_reads_compression_memory() {
+    for scenario in get_random_test_scenarios() {
+        let store = CompressionStore::<MemoryStore>::new_test_store()
+            .await
+            .unwrap();
+        run_reads(store, scenario).await;
+    }
+}
+
+#[cfg(with_encryption)]
+#[tokio::test]
+async fn test_reads_encryption_memory() {
+    for scenario in get_random_test_scenarios() {
+        let store = EncryptionStore::<MemoryStore>::new_test_store()
+            .await
+            .unwrap();
+        run_reads(store, scenario).await;
+    }
+}
+
 #[cfg(with_rocksdb)]
 #[tokio::test]
 async fn test_reads_rocks_db() {
@@ -72,6 +98,17 @@ async fn test_reads_rocks_db() {
     }
 }
 
+#[cfg(with_redb)]
+#[tokio::test]
+async fn test_reads_redb() {
+    for scenario in get_random_test_scenarios() {
+        let store = linera_views::redb_db::RedbStore::new_test_store()
+            .await
+            .unwrap();
+        run_reads(store, scenario).await;
+    }
+}
+
 #[cfg(with_dynamodb)]
 #[tokio::test]
 async fn test_reads_dynamo_db() {
@@ -103,6 +140,16 @@ async fn test_reads_indexed_db() {
     }
 }
 
+#[cfg(with_indexeddb)]
+#[wasm_bindgen_test]
+async fn test_reads_indexed_db_encrypted() {
+    for scenario in get_random_test_scenarios() {
+        let key_value_store =
+            linera_views::indexed_db::create_indexed_db_encrypted_test_store().await;
+        run_reads(key_value_store, scenario).await;
+    }
+}
+
 #[tokio::test]
 async fn test_reads_key_value_store_view_memory() {
     for scenario in get_random_test_scenarios() {
@@ -136,6 +183,24 @@ async fn test_memory_writes_from_blank() {
     run_writes_from_blank(&store).await;
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_compression_memory_writes_from_blank() {
+    let store = CompressionStore::<MemoryStore>::new_test_store()
+        .await
+        .unwrap();
+    run_writes_from_blank(&store).await;
+}
+
+#[cfg(with_encryption)]
+#[tokio::test]
+async fn test_encryption_memory_writes_from_blank() {
+    let store = EncryptionStore::<MemoryStore>::new_test_store()
+        .await
+        .unwrap();
+    run_writes_from_blank(&store).await;
+}
+
 #[tokio::test]
 async fn test_key_value_store_view_memory_writes_from_blank() {
     let context = create_test_memory_context();
@@ -152,6 +217,15 @@ async fn test_rocks_db_writes_from_blank() {
     run_writes_from_blank(&store).await;
 }
 
+#[cfg(with_redb)]
+#[tokio::test]
+async fn test_redb_writes_from_blank() {
+    let store = linera_views::redb_db::RedbStore::new_test_store()
+        .await
+        .unwrap();
+    run_writes_from_blank(&store).await;
+}
+
 #[cfg(with_dynamodb)]
 #[tokio::test]
 async fn test_dynamo_db_writes_from_blank() {
@@ -177,6 +251,13 @@ async fn test_indexed_db_writes_from_blank() {
     run_writes_from_blank(&key_value_store).await;
 }
 
+#[cfg(with_indexeddb)]
+#[wasm_bindgen_test]
+async fn test_indexed_db_encrypted_writes_from_blank() {
+    let key_value_store = linera_views::indexed_db::create_indexed_db_encrypted_test_store().await;
+    run_writes_from_blank(&key_value_store).await;
+}
+
 #[tokio::test]
 async fn test_big_value_read_write() {
     use rand::{distributions::Alphanumeric, Rng};
@@ -236,6 +317,17 @@ async fn test_rocks_db_big_write_read() {
     run_big_write_read(store, target_size, value_sizes).await;
 }
 
+#[cfg(with_redb)]
+#[tokio::test]
+async fn test_redb_big_write_read() {
+    let store = linera_views::redb_db::RedbStore::new_test_store()
+        .await
+        .unwrap();
+    let value_sizes = vec![100, 1000, 200000, 5000000];
+    let target_size = 20000000;
+    run_big_write_read(store, target_size, value_sizes).await;
+}
+
 #[cfg(with_indexeddb)]
 #[wasm_bindgen_test]
 async fn test_indexed_db_big_write_read() {
@@ -271,6 +363,15 @@ async fn test_rocks_db_writes_from_state() {
     run_writes_from_state(&store).await;
 }
 
+#[cfg(with_redb)]
+#[tokio::test]
+async fn test_redb_writes_from_state() {
+    let store = linera_views::redb_db::RedbStore::new_test_store()
+        .await
+        .unwrap();
+    run_writes_from_state(&store).await;
+}
+
 #[cfg(with_indexeddb)]
 #[wasm_bindgen_test]
 async fn test_indexed_db_writes_from_state() {
@@ -7,11 +7,13 @@ use anyhow::Result;
 use async_trait::async_trait;
 #[cfg(with_dynamodb)]
 use linera_views::dynamo_db::DynamoDbStore;
+#[cfg(with_redb)]
+use linera_views::redb_db::RedbStore;
 #[cfg(with_rocksdb)]
 use linera_views::rocks_db::RocksDbStore;
 #[cfg(with_scylladb)]
 use linera_views::scylla_db::ScyllaDbStore;
-#[cfg(any(with_scylladb, with_rocksdb, with_dynamodb))]
+#[cfg(any(with_scylladb, with_rocksdb, with_redb, with_dynamodb))]
 use linera_views::store::AdminKeyValueStore as _;
 use linera_views::{
     batch::{
@@ -177,6 +179,35 @@ impl StateStorage for RocksDbTestStorage {
     }
 }
 
+#[cfg(with_redb)]
+pub struct RedbTestStorage {
+    store: RedbStore,
+    accessed_chains: BTreeSet<usize>,
+}
+
+#[cfg(with_redb)]
+#[async_trait]
+impl StateStorage for RedbTestStorage {
+    type Context = ViewContext<usize, RedbStore>;
+
+    async fn new() -> Self {
+        let store = RedbStore::new_test_store().await.unwrap();
+        let accessed_chains = BTreeSet::new();
+        RedbTestStorage {
+            store,
+            accessed_chains,
+        }
+    }
+
+    async fn load(&mut self, id: usize) -> Result<StateView<Self::Context>, ViewError> {
+        self.accessed_chains.insert(id);
+        let root_key = bcs::to_bytes(&id)?;
+        let store = self.store.clone_with_root_key(&root_key)?;
+        let context = ViewContext::create_root_context(store, id).await?;
+        StateView::load(context).await
+    }
+}
+
 #[cfg(with_scylladb)]
 pub struct ScyllaDbTestStorage {
     store: ScyllaDbStore,
@@ -729,6 +760,30 @@ async fn test_views_in_rocks_db() -> Result<()> {
     Ok(())
 }
 
+#[cfg(with_redb)]
+#[cfg(test)]
+async fn test_views_in_redb_param(config: &TestConfig) -> Result<()> {
+    tracing::warn!("Testing config {:?} with redb", config);
+
+    let mut store = RedbTestStorage::new().await;
+    let hash = test_store(&mut store, config).await?;
+    assert_eq!(store.accessed_chains.len(), 1);
+
+    let mut store = MemoryTestStorage::new().await;
+    let hash2 = test_store(&mut store, config).await?;
+    assert_eq!(hash, hash2);
+    Ok(())
+}
+
+#[cfg(with_redb)]
+#[tokio::test]
+async fn test_views_in_redb() -> Result<()> {
+    for config in TestConfig::samples() {
+        test_views_in_redb_param(&config).await?;
+    }
+    Ok(())
+}
+
 #[cfg(with_scylladb)]
 #[cfg(test)]
 async fn test_views_in_scylla_db_param(config: &TestConfig) -> Result<()> {
@@ -767,7 +822,7 @@ async fn test_views_in_dynamo_db() -> Result<()> {
     Ok(())
 }
 
-#[cfg(with_rocksdb)]
+#[cfg(any(with_rocksdb, with_redb))]
 #[cfg(test)]
 async fn test_store_rollback_kernel<S>(store: &mut S) -> Result<()>
 where
@@ -819,6 +874,14 @@ async fn test_store_rollback() -> Result<()> {
     Ok(())
 }
 
+#[cfg(with_redb)]
+#[tokio::test]
+async fn test_store_rollback_redb() -> Result<()> {
+    let mut store = RedbTestStorage::new().await;
+    test_store_rollback_kernel(&mut store).await?;
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_collection_removal() -> Result<()> {
     type EntryType = HashedRegisterView<MemoryContext<()>, u8>;